@@ -0,0 +1,101 @@
+//! Executes transactions with `revm` built without the standard library.
+//!
+//! This crate is `no_std` and depends on `revm` with `default-features = false`, so it
+//! fails to build if `std` is required anywhere on the execution path:
+//!
+//! ```sh
+//! cargo check -p revm-no-std-test --target riscv32imac-unknown-none-elf
+//! ```
+//!
+//! The parts of `revm` that need `std` are behind features that enable it:
+//! - the `ethersdb`, `alloydb`, `rpc-compat` and `rayon` features,
+//! - the `CustomPrintTracer` and `TracerEip3155` inspectors,
+//! - the `std::error::Error` implementations of the error types.
+#![cfg_attr(not(test), no_std)]
+
+use core::convert::Infallible;
+use revm::{
+    db::{CacheDB, EmptyDB},
+    primitives::{AccountInfo, Address, EVMError, ExecutionResult, TxKind, U256},
+    DatabaseCommit, Evm,
+};
+
+/// Initial balance of the sender of [transfer].
+pub const SENDER_BALANCE: u64 = 1_000_000;
+
+/// Executes and commits a value transfer from `from` to `to` over an empty database in
+/// which only `from` is funded.
+///
+/// Returns the execution result and the database after the commit.
+pub fn transfer(
+    from: Address,
+    to: Address,
+    value: U256,
+) -> Result<(ExecutionResult, CacheDB<EmptyDB>), EVMError<Infallible>> {
+    let mut db = CacheDB::new(EmptyDB::default());
+    db.insert_account_info(
+        from,
+        AccountInfo {
+            balance: U256::from(SENDER_BALANCE),
+            ..Default::default()
+        },
+    );
+
+    let mut evm = Evm::builder()
+        .with_db(db)
+        .modify_tx_env(|tx| {
+            tx.caller = from;
+            tx.transact_to = Some(TxKind::Call(to));
+            tx.value = value;
+            tx.gas_limit = 21_000;
+        })
+        .build();
+    let result = evm.transact()?;
+    let mut db = evm.into_context().evm.inner.db;
+    db.commit(result.state);
+    Ok((result.result, db))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::{
+        interpreter::gas::validate_initial_tx_gas,
+        primitives::{address, GasScheduleOverrides, Output, SpecId, SuccessReason, TxEnv},
+        DatabaseRef,
+    };
+
+    #[test]
+    fn transfer_matches_std() {
+        let from = address!("1000000000000000000000000000000000000001");
+        let to = address!("2000000000000000000000000000000000000002");
+        let value = U256::from(100);
+        let (result, db) = transfer(from, to, value).unwrap();
+
+        // a transfer only pays the initial gas, at the default gas price.
+        let gas_used = validate_initial_tx_gas(
+            &GasScheduleOverrides::NONE,
+            SpecId::LATEST,
+            &[],
+            false,
+            &[],
+            0,
+        );
+        let fee = U256::from(gas_used) * TxEnv::default().gas_price;
+        assert_eq!(
+            result,
+            ExecutionResult::Success {
+                reason: SuccessReason::Stop,
+                gas_used,
+                gas_refunded: 0,
+                logs: Default::default(),
+                output: Output::Call(Default::default()),
+            }
+        );
+        let sender = db.basic_ref(from).unwrap().unwrap();
+        assert_eq!(sender.nonce, 1);
+        assert_eq!(sender.balance, U256::from(SENDER_BALANCE) - value - fee);
+        let receiver = db.basic_ref(to).unwrap().unwrap();
+        assert_eq!(receiver.balance, value);
+    }
+}
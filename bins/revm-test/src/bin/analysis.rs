@@ -17,7 +17,9 @@ fn main() {
         .modify_tx_env(|tx| {
             // execution globals block hash/gas_limit/coinbase/timestamp..
             tx.caller = address!("1000000000000000000000000000000000000000");
-            tx.transact_to = TxKind::Call(address!("0000000000000000000000000000000000000000"));
+            tx.transact_to = Some(TxKind::Call(address!(
+                "0000000000000000000000000000000000000000"
+            )));
             //evm.env.tx.data = Bytes::from(hex::decode("30627b7c").unwrap());
             tx.data = bytes!("8035F0CE");
         })
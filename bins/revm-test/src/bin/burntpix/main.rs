@@ -38,7 +38,7 @@ fn main() {
     let mut evm = Evm::builder()
         .modify_tx_env(|tx| {
             tx.caller = address!("1000000000000000000000000000000000000000");
-            tx.transact_to = TxKind::Call(BURNTPIX_MAIN_ADDRESS);
+            tx.transact_to = Some(TxKind::Call(BURNTPIX_MAIN_ADDRESS));
             tx.data = run_call_data.clone().into();
         })
         .with_db(db)
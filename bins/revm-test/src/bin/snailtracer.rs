@@ -14,7 +14,9 @@ pub fn simple_example() {
         .modify_tx_env(|tx| {
             // execution globals block hash/gas_limit/coinbase/timestamp..
             tx.caller = address!("1000000000000000000000000000000000000000");
-            tx.transact_to = TxKind::Call(address!("0000000000000000000000000000000000000000"));
+            tx.transact_to = Some(TxKind::Call(address!(
+                "0000000000000000000000000000000000000000"
+            )));
             tx.data = bytes!("30627b7c");
         })
         .build();
@@ -16,11 +16,11 @@ fn main() {
                 .parse()
                 .unwrap();
             tx.value = U256::from(10);
-            tx.transact_to = TxKind::Call(
+            tx.transact_to = Some(TxKind::Call(
                 "0x0000000000000000000000000000000000000000"
                     .parse()
                     .unwrap(),
-            );
+            ));
         })
         .build();
 
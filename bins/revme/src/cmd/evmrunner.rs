@@ -86,7 +86,7 @@ impl Cmd {
                 tx.caller = "0x0000000000000000000000000000000000000001"
                     .parse()
                     .unwrap();
-                tx.transact_to = TxKind::Call(Address::ZERO);
+                tx.transact_to = Some(TxKind::Call(Address::ZERO));
                 tx.data = input;
             })
             .build();
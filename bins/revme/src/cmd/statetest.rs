@@ -1,11 +1,9 @@
-pub mod merkle_trie;
-pub mod models;
 mod runner;
-pub mod utils;
 
-pub use runner::TestError as Error;
+pub use crate::statetest::{merkle_trie, models, utils, TestError as Error};
 
-use runner::{find_all_json_tests, run, TestError};
+use crate::statetest::TestError;
+use runner::{find_all_json_tests, run};
 use std::path::PathBuf;
 use structopt::StructOpt;
 
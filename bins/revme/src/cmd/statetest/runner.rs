@@ -1,17 +1,15 @@
-use super::{
+use crate::statetest::{
+    build_state, check_execution, executable_spec_id,
     merkle_trie::{log_rlp_hash, state_merkle_trie_root},
-    models::{SpecName, Test, TestSuite},
-    utils::recover_address,
+    models::{Test, TestSuite},
+    prestate, test_tx_patch, unit_env, TestError, TestErrorKind,
 };
 use indicatif::{ProgressBar, ProgressDrawTarget};
 use revm::{
     db::EmptyDB,
     inspector_handle_register,
     inspectors::TracerEip3155,
-    primitives::{
-        calc_excess_blob_gas, keccak256, Bytecode, Bytes, EVMResultGeneric, Env, Eof,
-        ExecutionResult, SpecId, TxKind, B256, EOF_MAGIC_BYTES,
-    },
+    primitives::{Bytes, EVMResultGeneric, ExecutionResult},
     Evm, State,
 };
 use serde_json::json;
@@ -25,40 +23,8 @@ use std::{
     },
     time::{Duration, Instant},
 };
-use thiserror::Error;
 use walkdir::{DirEntry, WalkDir};
 
-#[derive(Debug, Error)]
-#[error("Test {name} failed: {kind}")]
-pub struct TestError {
-    pub name: String,
-    pub kind: TestErrorKind,
-}
-
-#[derive(Debug, Error)]
-pub enum TestErrorKind {
-    #[error("logs root mismatch: got {got}, expected {expected}")]
-    LogsRootMismatch { got: B256, expected: B256 },
-    #[error("state root mismatch: got {got}, expected {expected}")]
-    StateRootMismatch { got: B256, expected: B256 },
-    #[error("unknown private key: {0:?}")]
-    UnknownPrivateKey(B256),
-    #[error("unexpected exception: got {got_exception:?}, expected {expected_exception:?}")]
-    UnexpectedException {
-        expected_exception: Option<String>,
-        got_exception: Option<String>,
-    },
-    #[error("unexpected output: got {got_output:?}, expected {expected_output:?}")]
-    UnexpectedOutput {
-        expected_output: Option<Bytes>,
-        got_output: Option<Bytes>,
-    },
-    #[error(transparent)]
-    SerdeDeserialize(#[from] serde_json::Error),
-    #[error("thread panicked")]
-    Panic,
-}
-
 pub fn find_all_json_tests(path: &Path) -> Vec<PathBuf> {
     if path.is_file() {
         vec![path.to_path_buf()]
@@ -165,76 +131,16 @@ fn check_evm_execution<EXT>(
         }
     };
 
-    // If we expect exception revm should return error from execution.
-    // So we do not check logs and state root.
-    //
-    // Note that some tests that have exception and run tests from before state clear
-    // would touch the caller account and make it appear in state root calculation.
-    // This is not something that we would expect as invalid tx should not touch state.
-    // but as this is a cleanup of invalid tx it is not properly defined and in the end
-    // it does not matter.
-    // Test where this happens: `tests/GeneralStateTests/stTransactionTest/NoSrcAccountCreate.json`
-    // and you can check that we have only two "hash" values for before and after state clear.
-    match (&test.expect_exception, exec_result) {
-        // do nothing
-        (None, Ok(result)) => {
-            // check output
-            if let Some((expected_output, output)) = expected_output.zip(result.output()) {
-                if expected_output != output {
-                    let kind = TestErrorKind::UnexpectedOutput {
-                        expected_output: Some(expected_output.clone()),
-                        got_output: result.output().cloned(),
-                    };
-                    print_json_output(Some(kind.to_string()));
-                    return Err(TestError {
-                        name: test_name.to_string(),
-                        kind,
-                    });
-                }
-            }
-        }
-        // return okay, exception is expected.
-        (Some(_), Err(_)) => return Ok(()),
-        _ => {
-            let kind = TestErrorKind::UnexpectedException {
-                expected_exception: test.expect_exception.clone(),
-                got_exception: exec_result.clone().err().map(|e| e.to_string()),
-            };
-            print_json_output(Some(kind.to_string()));
-            return Err(TestError {
-                name: test_name.to_string(),
-                kind,
-            });
-        }
-    }
-
-    if logs_root != test.logs {
-        let kind = TestErrorKind::LogsRootMismatch {
-            got: logs_root,
-            expected: test.logs,
-        };
-        print_json_output(Some(kind.to_string()));
-        return Err(TestError {
-            name: test_name.to_string(),
-            kind,
-        });
-    }
-
-    if state_root != test.hash {
-        let kind = TestErrorKind::StateRootMismatch {
-            got: state_root,
-            expected: test.hash,
-        };
-        print_json_output(Some(kind.to_string()));
-        return Err(TestError {
-            name: test_name.to_string(),
-            kind,
-        });
+    let result = check_execution(test, expected_output, exec_result, logs_root, state_root);
+    // return okay without printing, exception is expected.
+    if test.expect_exception.is_some() && exec_result.is_err() {
+        return Ok(());
     }
-
-    print_json_output(None);
-
-    Ok(())
+    print_json_output(result.as_ref().err().map(ToString::to_string));
+    result.map_err(|kind| TestError {
+        name: test_name.to_string(),
+        kind,
+    })
 }
 
 pub fn execute_test_suite(
@@ -253,124 +159,32 @@ pub fn execute_test_suite(
         kind: e.into(),
     })?;
 
-    for (name, unit) in suite.0 {
+    for (name, unit) in &suite.0 {
         // Create database and insert cache
-        let mut cache_state = revm::CacheState::new(false);
-        for (address, info) in unit.pre {
-            let code_hash = keccak256(&info.code);
-            let bytecode = match info.code.get(..2) {
-                Some(magic) if magic == &EOF_MAGIC_BYTES => {
-                    Bytecode::Eof(Eof::decode(info.code.clone()).unwrap().into())
-                }
-                _ => Bytecode::new_raw(info.code),
-            };
-            let acc_info = revm::primitives::AccountInfo {
-                balance: info.balance,
-                code_hash,
-                code: Some(bytecode),
-                nonce: info.nonce,
-            };
-            cache_state.insert_account_with_storage(address, acc_info, info.storage);
-        }
-
-        let mut env = Box::<Env>::default();
-        // for mainnet
-        env.cfg.chain_id = 1;
-        // env.cfg.spec_id is set down the road
-
-        // block env
-        env.block.number = unit.env.current_number;
-        env.block.coinbase = unit.env.current_coinbase;
-        env.block.timestamp = unit.env.current_timestamp;
-        env.block.gas_limit = unit.env.current_gas_limit;
-        env.block.basefee = unit.env.current_base_fee.unwrap_or_default();
-        env.block.difficulty = unit.env.current_difficulty;
-        // after the Merge prevrandao replaces mix_hash field in block and replaced difficulty opcode in EVM.
-        env.block.prevrandao = unit.env.current_random;
-        // EIP-4844
-        if let Some(current_excess_blob_gas) = unit.env.current_excess_blob_gas {
-            env.block
-                .set_blob_excess_gas_and_price(current_excess_blob_gas.to());
-        } else if let (Some(parent_blob_gas_used), Some(parent_excess_blob_gas)) = (
-            unit.env.parent_blob_gas_used,
-            unit.env.parent_excess_blob_gas,
-        ) {
-            env.block
-                .set_blob_excess_gas_and_price(calc_excess_blob_gas(
-                    parent_blob_gas_used.to(),
-                    parent_excess_blob_gas.to(),
-                ));
-        }
-
-        // tx env
-        env.tx.caller = if let Some(address) = unit.transaction.sender {
-            address
-        } else {
-            recover_address(unit.transaction.secret_key.as_slice()).ok_or_else(|| TestError {
-                name: name.clone(),
-                kind: TestErrorKind::UnknownPrivateKey(unit.transaction.secret_key),
-            })?
-        };
-        env.tx.gas_price = unit
-            .transaction
-            .gas_price
-            .or(unit.transaction.max_fee_per_gas)
-            .unwrap_or_default();
-        env.tx.gas_priority_fee = unit.transaction.max_priority_fee_per_gas;
-        // EIP-4844
-        env.tx.blob_hashes = unit.transaction.blob_versioned_hashes;
-        env.tx.max_fee_per_blob_gas = unit.transaction.max_fee_per_blob_gas;
+        let cache_state = prestate(unit).map_err(|kind| TestError {
+            name: name.clone(),
+            kind,
+        })?;
+        let env = Box::new(unit_env(name, unit)?);
 
         // post and execution
-        for (spec_name, tests) in unit.post {
-            if matches!(
-                spec_name,
-                SpecName::ByzantiumToConstantinopleAt5
-                    | SpecName::Constantinople
-                    | SpecName::Unknown
-            ) {
+        for (spec_name, tests) in &unit.post {
+            let Some(spec_id) = executable_spec_id(spec_name) else {
                 continue;
-            }
+            };
 
-            let spec_id = spec_name.to_spec_id();
-
-            for (index, test) in tests.into_iter().enumerate() {
-                env.tx.gas_limit = unit.transaction.gas_limit[test.indexes.gas].saturating_to();
-
-                env.tx.data = unit
-                    .transaction
-                    .data
-                    .get(test.indexes.data)
-                    .unwrap()
-                    .clone();
-                env.tx.value = unit.transaction.value[test.indexes.value];
-
-                env.tx.access_list = unit
-                    .transaction
-                    .access_lists
-                    .get(test.indexes.data)
-                    .and_then(Option::as_deref)
-                    .cloned()
-                    .unwrap_or_default();
-
-                let to = match unit.transaction.to {
-                    Some(add) => TxKind::Call(add),
-                    None => TxKind::Create,
-                };
-                env.tx.transact_to = to;
-
-                let mut cache = cache_state.clone();
-                cache.set_state_clear_flag(SpecId::enabled(
-                    spec_id,
-                    revm::primitives::SpecId::SPURIOUS_DRAGON,
-                ));
-                let mut state = revm::db::State::builder()
-                    .with_cached_prestate(cache)
-                    .with_bundle_update()
-                    .build();
+            for (index, test) in tests.iter().enumerate() {
+                let patch = test_tx_patch(unit, test).map_err(|kind| TestError {
+                    name: name.clone(),
+                    kind,
+                })?;
+                let mut test_env = env.clone();
+                test_env.apply_patch(&patch);
+
+                let mut state = build_state(&cache_state, spec_id);
                 let mut evm = Evm::builder()
                     .with_db(&mut state)
-                    .modify_env(|e| e.clone_from(&env))
+                    .modify_env(|e| e.clone_from(&test_env))
                     .with_spec_id(spec_id)
                     .build();
 
@@ -389,9 +203,9 @@ pub fn execute_test_suite(
                     *elapsed.lock().unwrap() += timer.elapsed();
 
                     let Err(e) = check_evm_execution(
-                        &test,
+                        test,
                         unit.out.as_ref(),
-                        &name,
+                        name,
                         &res,
                         &evm,
                         print_json_outcome,
@@ -407,9 +221,9 @@ pub fn execute_test_suite(
 
                     // dump state and traces if test failed
                     let output = check_evm_execution(
-                        &test,
+                        test,
                         unit.out.as_ref(),
-                        &name,
+                        name,
                         &res,
                         &evm,
                         print_json_outcome,
@@ -428,22 +242,14 @@ pub fn execute_test_suite(
                 }
 
                 // re build to run with tracing
-                let mut cache = cache_state.clone();
-                cache.set_state_clear_flag(SpecId::enabled(
-                    spec_id,
-                    revm::primitives::SpecId::SPURIOUS_DRAGON,
-                ));
-                let state = revm::db::State::builder()
-                    .with_cached_prestate(cache)
-                    .with_bundle_update()
-                    .build();
+                let state = build_state(&cache_state, spec_id);
 
                 let path = path.display();
                 println!("\nTraces:");
                 let mut evm = Evm::builder()
                     .with_spec_id(spec_id)
                     .with_db(state)
-                    .with_env(env.clone())
+                    .with_env(test_env.clone())
                     .with_external_context(TracerEip3155::new(Box::new(stdout())).without_summary())
                     .append_handler_register(inspector_handle_register)
                     .build();
@@ -454,7 +260,7 @@ pub fn execute_test_suite(
                 println!("\nState before: {cache_state:#?}");
                 println!("\nState after: {:#?}", evm.context.evm.db.cache);
                 println!("\nSpecification: {spec_id:?}");
-                println!("\nEnvironment: {env:#?}");
+                println!("\nEnvironment: {test_env:#?}");
                 println!("\nTest name: {name:?} (index: {index}, path: {path}) failed:\n{e}");
 
                 return Err(e);
@@ -1 +1,3 @@
+#[cfg(feature = "cli")]
 pub mod cmd;
+pub mod statetest;
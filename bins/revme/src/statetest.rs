@@ -0,0 +1,490 @@
+//! Execution of the [ethereum/tests](https://github.com/ethereum/tests) state tests.
+//!
+//! [`run_case`] runs all tests of a single fixture and reports a [`CaseResult`] for every
+//! executed test, it allows validating custom handlers against the official test suite.
+pub mod merkle_trie;
+pub mod models;
+pub mod utils;
+
+use merkle_trie::{log_rlp_hash, state_merkle_trie_root};
+use models::{SpecName, Test, TestSuite, TestUnit, TxPartIndices};
+use revm::{
+    db::{EmptyDB, State},
+    primitives::{
+        calc_excess_blob_gas, eof::EofDecodeError, keccak256, Address, Bytecode, Bytes,
+        EVMResultGeneric, Env, EnvPatch, Eof, ExecutionResult, HashMap, SpecId, TxEnvPatch, TxKind,
+        B256, EOF_MAGIC_BYTES, U256,
+    },
+    CacheState, Evm,
+};
+use std::{convert::Infallible, path::Path};
+use thiserror::Error;
+use utils::recover_address;
+
+#[derive(Debug, Error)]
+#[error("Test {name} failed: {kind}")]
+pub struct TestError {
+    pub name: String,
+    pub kind: TestErrorKind,
+}
+
+#[derive(Debug, Error)]
+pub enum TestErrorKind {
+    #[error("logs root mismatch: got {got}, expected {expected}")]
+    LogsRootMismatch { got: B256, expected: B256 },
+    #[error("state root mismatch: got {got}, expected {expected}")]
+    StateRootMismatch { got: B256, expected: B256 },
+    #[error("unknown private key: {0:?}")]
+    UnknownPrivateKey(B256),
+    #[error("unexpected exception: got {got_exception:?}, expected {expected_exception:?}")]
+    UnexpectedException {
+        expected_exception: Option<String>,
+        got_exception: Option<String>,
+    },
+    #[error("unexpected output: got {got_output:?}, expected {expected_output:?}")]
+    UnexpectedOutput {
+        expected_output: Option<Bytes>,
+        got_output: Option<Bytes>,
+    },
+    #[error(transparent)]
+    SerdeDeserialize(#[from] serde_json::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("invalid EOF code of {address}: {error:?}")]
+    InvalidEofCode {
+        address: Address,
+        error: EofDecodeError,
+    },
+    #[error("{part} index {index} out of bounds")]
+    TxPartIndexOutOfBounds { part: &'static str, index: usize },
+    #[error("thread panicked")]
+    Panic,
+}
+
+/// Fixture executed by [`run_case`].
+#[derive(Clone, Copy, Debug)]
+pub enum CaseInput<'a> {
+    /// Path to the JSON fixture.
+    Path(&'a Path),
+    /// Content of the JSON fixture.
+    Json(&'a str),
+}
+
+impl<'a> From<&'a Path> for CaseInput<'a> {
+    fn from(path: &'a Path) -> Self {
+        Self::Path(path)
+    }
+}
+
+impl<'a> From<&'a str> for CaseInput<'a> {
+    fn from(json: &'a str) -> Self {
+        Self::Json(json)
+    }
+}
+
+/// Difference between the post state of the execution and the expected post state.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StateMismatch {
+    /// Account is expected but it does not exist.
+    MissingAccount(Address),
+    /// Account exists but it is not expected.
+    UnexpectedAccount(Address),
+    Balance {
+        address: Address,
+        got: U256,
+        expected: U256,
+    },
+    Nonce {
+        address: Address,
+        got: u64,
+        expected: u64,
+    },
+    Code {
+        address: Address,
+        got: Bytes,
+        expected: Bytes,
+    },
+    /// Storage slot value differs, missing slots are zero.
+    Storage {
+        address: Address,
+        slot: U256,
+        got: U256,
+        expected: U256,
+    },
+}
+
+/// Result of a single test of the fixture.
+#[derive(Debug)]
+pub struct CaseResult {
+    /// Name of the test unit.
+    pub name: String,
+    /// Spec the test was executed with.
+    pub spec_id: SpecId,
+    /// Indexes of the transaction parts.
+    pub indexes: TxPartIndices,
+    /// Error if the test failed.
+    pub error: Option<TestErrorKind>,
+    /// Accounts and storage slots that differ from the expected post state.
+    ///
+    /// Filled only if the state root does not match.
+    pub mismatches: Vec<StateMismatch>,
+}
+
+impl CaseResult {
+    /// Returns `true` if the test passed.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Runs all tests of the state test fixture and calls `on_result` with the result of
+/// every executed test.
+///
+/// If `spec_filter` is set only tests of that spec are executed. Returns an error if
+/// the fixture can't be loaded.
+pub fn run_case<'a>(
+    input: impl Into<CaseInput<'a>>,
+    spec_filter: Option<SpecId>,
+    on_result: &mut dyn FnMut(CaseResult),
+) -> Result<(), TestError> {
+    let input = input.into();
+    let name = match input {
+        CaseInput::Path(path) => path.to_string_lossy().into_owned(),
+        CaseInput::Json(_) => String::from("<json>"),
+    };
+    let suite: TestSuite = match input {
+        CaseInput::Path(path) => std::fs::read_to_string(path)
+            .map_err(TestErrorKind::from)
+            .and_then(|s| serde_json::from_str(&s).map_err(Into::into)),
+        CaseInput::Json(json) => serde_json::from_str(json).map_err(Into::into),
+    }
+    .map_err(|kind| TestError { name, kind })?;
+
+    for (name, unit) in &suite.0 {
+        let cache_state = prestate(unit).map_err(|kind| TestError {
+            name: name.clone(),
+            kind,
+        })?;
+        let env = unit_env(name, unit)?;
+
+        for (spec_name, tests) in &unit.post {
+            let Some(spec_id) = executable_spec_id(spec_name) else {
+                continue;
+            };
+            if spec_filter.is_some_and(|filter| filter != spec_id) {
+                continue;
+            }
+
+            for test in tests {
+                let patch = match test_tx_patch(unit, test) {
+                    Ok(patch) => patch,
+                    Err(error) => {
+                        on_result(CaseResult {
+                            name: name.clone(),
+                            spec_id,
+                            indexes: test.indexes,
+                            error: Some(error),
+                            mismatches: Vec::new(),
+                        });
+                        continue;
+                    }
+                };
+                let mut state = build_state(&cache_state, spec_id);
+                let mut evm = Evm::builder()
+                    .with_db(&mut state)
+                    .modify_env(|e| {
+                        **e = env.clone();
+                        e.apply_patch(&patch);
+                    })
+                    .with_spec_id(spec_id)
+                    .build();
+                let exec_result = evm.transact_commit();
+                drop(evm);
+
+                let logs_root =
+                    log_rlp_hash(exec_result.as_ref().map(|r| r.logs()).unwrap_or_default());
+                let state_root = state_merkle_trie_root(state.cache.trie_account());
+                let error =
+                    check_execution(test, unit.out.as_ref(), &exec_result, logs_root, state_root)
+                        .err();
+                let mismatches = match error {
+                    Some(TestErrorKind::StateRootMismatch { .. }) => {
+                        state_mismatches(&state.cache, &test.post_state)
+                    }
+                    _ => Vec::new(),
+                };
+                on_result(CaseResult {
+                    name: name.clone(),
+                    spec_id,
+                    indexes: test.indexes,
+                    error,
+                    mismatches,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the spec of the test, `None` if tests of this spec are not executed.
+pub(crate) fn executable_spec_id(spec_name: &SpecName) -> Option<SpecId> {
+    match spec_name {
+        SpecName::ByzantiumToConstantinopleAt5 | SpecName::Constantinople | SpecName::Unknown => {
+            None
+        }
+        spec_name => Some(spec_name.to_spec_id()),
+    }
+}
+
+/// Returns the pre state of the test unit.
+pub(crate) fn prestate(unit: &TestUnit) -> Result<CacheState, TestErrorKind> {
+    let mut cache_state = CacheState::new(false);
+    for (address, info) in &unit.pre {
+        let code_hash = keccak256(&info.code);
+        let bytecode = match info.code.get(..2) {
+            Some(magic) if magic == &EOF_MAGIC_BYTES => {
+                let eof = Eof::decode(info.code.clone()).map_err(|error| {
+                    TestErrorKind::InvalidEofCode {
+                        address: *address,
+                        error,
+                    }
+                })?;
+                Bytecode::Eof(eof.into())
+            }
+            _ => Bytecode::new_raw(info.code.clone()),
+        };
+        let acc_info = revm::primitives::AccountInfo {
+            balance: info.balance,
+            code_hash,
+            code: Some(bytecode),
+            nonce: info.nonce,
+        };
+        cache_state.insert_account_with_storage(*address, acc_info, info.storage.clone());
+    }
+    Ok(cache_state)
+}
+
+/// Returns the environment of the test unit, parts of the transaction that differ
+/// between tests are set with the patch of [`test_tx_patch`].
+pub(crate) fn unit_env(name: &str, unit: &TestUnit) -> Result<Env, TestError> {
+    let mut env = Env::default();
+    // for mainnet
+    env.cfg.chain_id = 1;
+    // env.cfg.spec_id is set down the road
+
+    // block env
+    env.block.number = unit.env.current_number;
+    env.block.coinbase = unit.env.current_coinbase;
+    env.block.timestamp = unit.env.current_timestamp;
+    env.block.gas_limit = unit.env.current_gas_limit;
+    env.block.basefee = unit.env.current_base_fee.unwrap_or_default();
+    env.block.difficulty = unit.env.current_difficulty;
+    // after the Merge prevrandao replaces mix_hash field in block and replaced difficulty opcode in EVM.
+    env.block.prevrandao = unit.env.current_random;
+    // EIP-4844
+    if let Some(current_excess_blob_gas) = unit.env.current_excess_blob_gas {
+        env.block
+            .set_blob_excess_gas_and_price(current_excess_blob_gas.to());
+    } else if let (Some(parent_blob_gas_used), Some(parent_excess_blob_gas)) = (
+        unit.env.parent_blob_gas_used,
+        unit.env.parent_excess_blob_gas,
+    ) {
+        env.block
+            .set_blob_excess_gas_and_price(calc_excess_blob_gas(
+                parent_blob_gas_used.to(),
+                parent_excess_blob_gas.to(),
+            ));
+    }
+
+    // tx env
+    env.tx_mut().caller = if let Some(address) = unit.transaction.sender {
+        address
+    } else {
+        recover_address(unit.transaction.secret_key.as_slice()).ok_or_else(|| TestError {
+            name: name.to_string(),
+            kind: TestErrorKind::UnknownPrivateKey(unit.transaction.secret_key),
+        })?
+    };
+    env.tx_mut().gas_price = unit
+        .transaction
+        .gas_price
+        .or(unit.transaction.max_fee_per_gas)
+        .unwrap_or_default();
+    env.tx_mut().gas_priority_fee = unit.transaction.max_priority_fee_per_gas;
+    // EIP-4844
+    env.tx_mut().blob_hashes = unit.transaction.blob_versioned_hashes.clone();
+    env.tx_mut().max_fee_per_blob_gas = unit.transaction.max_fee_per_blob_gas;
+    Ok(env)
+}
+
+/// Returns the patch of the transaction parts selected by the indexes of the `test`.
+pub(crate) fn test_tx_patch(unit: &TestUnit, test: &Test) -> Result<EnvPatch, TestErrorKind> {
+    fn part<'a, T>(
+        parts: &'a [T],
+        part: &'static str,
+        index: usize,
+    ) -> Result<&'a T, TestErrorKind> {
+        parts
+            .get(index)
+            .ok_or(TestErrorKind::TxPartIndexOutOfBounds { part, index })
+    }
+
+    let indexes = test.indexes;
+    let tx = TxEnvPatch {
+        gas_limit: Some(part(&unit.transaction.gas_limit, "gas", indexes.gas)?.saturating_to()),
+        data: Some(part(&unit.transaction.data, "data", indexes.data)?.clone()),
+        value: Some(*part(&unit.transaction.value, "value", indexes.value)?),
+        access_list: Some(
+            unit.transaction
+                .access_lists
+                .get(indexes.data)
+                .and_then(Option::as_deref)
+                .cloned()
+                .unwrap_or_default(),
+        ),
+        transact_to: Some(Some(match unit.transaction.to {
+            Some(add) => TxKind::Call(add),
+            None => TxKind::Create,
+        })),
+        ..Default::default()
+    };
+    Ok(EnvPatch {
+        tx,
+        ..Default::default()
+    })
+}
+
+/// Builds the database with the pre state for the given spec.
+pub(crate) fn build_state(cache_state: &CacheState, spec_id: SpecId) -> State<EmptyDB> {
+    let mut cache = cache_state.clone();
+    cache.set_state_clear_flag(SpecId::enabled(spec_id, SpecId::SPURIOUS_DRAGON));
+    State::builder()
+        .with_cached_prestate(cache)
+        .with_bundle_update()
+        .build()
+}
+
+/// Checks the result of the execution against the expectations of the `test`.
+pub(crate) fn check_execution(
+    test: &Test,
+    expected_output: Option<&Bytes>,
+    exec_result: &EVMResultGeneric<ExecutionResult, Infallible>,
+    logs_root: B256,
+    state_root: B256,
+) -> Result<(), TestErrorKind> {
+    // If we expect exception revm should return error from execution.
+    // So we do not check logs and state root.
+    //
+    // Note that some tests that have exception and run tests from before state clear
+    // would touch the caller account and make it appear in state root calculation.
+    // This is not something that we would expect as invalid tx should not touch state.
+    // but as this is a cleanup of invalid tx it is not properly defined and in the end
+    // it does not matter.
+    // Test where this happens: `tests/GeneralStateTests/stTransactionTest/NoSrcAccountCreate.json`
+    // and you can check that we have only two "hash" values for before and after state clear.
+    match (&test.expect_exception, exec_result) {
+        // do nothing
+        (None, Ok(result)) => {
+            // check output
+            if let Some((expected_output, output)) = expected_output.zip(result.output()) {
+                if expected_output != output {
+                    return Err(TestErrorKind::UnexpectedOutput {
+                        expected_output: Some(expected_output.clone()),
+                        got_output: result.output().cloned(),
+                    });
+                }
+            }
+        }
+        // return okay, exception is expected.
+        (Some(_), Err(_)) => return Ok(()),
+        _ => {
+            return Err(TestErrorKind::UnexpectedException {
+                expected_exception: test.expect_exception.clone(),
+                got_exception: exec_result.clone().err().map(|e| e.to_string()),
+            });
+        }
+    }
+
+    if logs_root != test.logs {
+        return Err(TestErrorKind::LogsRootMismatch {
+            got: logs_root,
+            expected: test.logs,
+        });
+    }
+
+    if state_root != test.hash {
+        return Err(TestErrorKind::StateRootMismatch {
+            got: state_root,
+            expected: test.hash,
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns the differences between the `cache` and the `expected` post state, sorted.
+pub fn state_mismatches(
+    cache: &CacheState,
+    expected: &HashMap<Address, models::AccountInfo>,
+) -> Vec<StateMismatch> {
+    let accounts: HashMap<_, _> = cache.trie_account().into_iter().collect();
+    let mut mismatches = Vec::new();
+    for (&address, expected) in expected {
+        let Some(account) = accounts.get(&address) else {
+            mismatches.push(StateMismatch::MissingAccount(address));
+            continue;
+        };
+        if account.info.balance != expected.balance {
+            mismatches.push(StateMismatch::Balance {
+                address,
+                got: account.info.balance,
+                expected: expected.balance,
+            });
+        }
+        if account.info.nonce != expected.nonce {
+            mismatches.push(StateMismatch::Nonce {
+                address,
+                got: account.info.nonce,
+                expected: expected.nonce,
+            });
+        }
+        let code = account
+            .info
+            .code
+            .as_ref()
+            .map(Bytecode::original_bytes)
+            .unwrap_or_default();
+        if code != expected.code {
+            mismatches.push(StateMismatch::Code {
+                address,
+                got: code,
+                expected: expected.code.clone(),
+            });
+        }
+        let slots = account.storage.keys().chain(
+            expected
+                .storage
+                .keys()
+                .filter(|slot| !account.storage.contains_key(*slot)),
+        );
+        for &slot in slots {
+            let got = account.storage.get(&slot).copied().unwrap_or_default();
+            let expected = expected.storage.get(&slot).copied().unwrap_or_default();
+            if got != expected {
+                mismatches.push(StateMismatch::Storage {
+                    address,
+                    slot,
+                    got,
+                    expected,
+                });
+            }
+        }
+    }
+    for address in accounts.keys() {
+        if !expected.contains_key(address) {
+            mismatches.push(StateMismatch::UnexpectedAccount(*address));
+        }
+    }
+    mismatches.sort();
+    mismatches
+}
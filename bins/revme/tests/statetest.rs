@@ -0,0 +1,117 @@
+use revm::primitives::{address, SpecId, U256};
+use revme::statetest::{run_case, CaseResult, StateMismatch, TestErrorKind};
+use std::path::{Path, PathBuf};
+
+/// Returns the path of a local fixture.
+///
+/// Local fixtures were filled with this runner, they only cover the library API like the spec
+/// filter and the mismatch reports. Conformance is checked by [`upstream_fixtures`].
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn run(input: &Path, spec_filter: Option<SpecId>) -> Vec<CaseResult> {
+    let mut results = Vec::new();
+    run_case(input, spec_filter, &mut |result| results.push(result)).unwrap();
+    results
+}
+
+#[test]
+fn value_transfer() {
+    let results = run(&fixture("valueTransfer.json"), None);
+    // Two tests for Shanghai and Cancun, second one expects an exception.
+    assert_eq!(results.len(), 4);
+    for result in &results {
+        assert!(result.is_success(), "{result:?}");
+        assert!(result.mismatches.is_empty());
+    }
+
+    let results = run(&fixture("valueTransfer.json"), Some(SpecId::CANCUN));
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.spec_id == SpecId::CANCUN));
+}
+
+#[test]
+fn store_and_log() {
+    let results = run(&fixture("storeAndLog.json"), None);
+    assert_eq!(results.len(), 1);
+    assert!(results[0].is_success(), "{:?}", results[0]);
+}
+
+#[test]
+fn reports_state_mismatch() {
+    let json = std::fs::read_to_string(fixture("storeAndLog.json")).unwrap();
+    let mut suite: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let test = &mut suite["storeAndLog"]["post"]["Cancun"][0];
+    test["hash"] = "0x0000000000000000000000000000000000000000000000000000000000000001".into();
+    test["postState"]["0x095e7baea6a6c7c4c2dfeb977efac326af552d87"]["storage"]["0x0"] =
+        "0x2".into();
+    let json = suite.to_string();
+
+    let mut results = Vec::new();
+    run_case(json.as_str(), None, &mut |result| results.push(result)).unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(matches!(
+        results[0].error,
+        Some(TestErrorKind::StateRootMismatch { .. })
+    ));
+    assert_eq!(
+        results[0].mismatches,
+        vec![StateMismatch::Storage {
+            address: address!("095e7baea6a6c7c4c2dfeb977efac326af552d87"),
+            slot: U256::ZERO,
+            got: U256::from(1),
+            expected: U256::from(2),
+        }]
+    );
+}
+
+#[test]
+fn reports_malformed_fixture() {
+    let json = std::fs::read_to_string(fixture("storeAndLog.json")).unwrap();
+    let mut suite: serde_json::Value = serde_json::from_str(&json).unwrap();
+    suite["storeAndLog"]["post"]["Cancun"][0]["indexes"]["data"] = 7.into();
+    let json = suite.to_string();
+
+    let mut results = Vec::new();
+    run_case(json.as_str(), None, &mut |result| results.push(result)).unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(matches!(
+        results[0].error,
+        Some(TestErrorKind::TxPartIndexOutOfBounds {
+            part: "data",
+            index: 7
+        })
+    ));
+
+    let pre = suite["storeAndLog"]["pre"].as_object_mut().unwrap();
+    let account = pre.values_mut().next().unwrap();
+    account["code"] = "0xef0001".into();
+    let json = suite.to_string();
+
+    let err = run_case(json.as_str(), None, &mut |_| {}).unwrap_err();
+    assert!(matches!(err.kind, TestErrorKind::InvalidEofCode { .. }));
+}
+
+/// Fixtures of ethereum/tests that are run through the library API.
+const UPSTREAM_FIXTURES: &[&str] = &[
+    "GeneralStateTests/stExample/add11.json",
+    "GeneralStateTests/stLogTests/log0_emptyMem.json",
+];
+
+/// Runs fixtures of the ethereum/tests checkout in the `ETHTESTS` directory, see the
+/// ethereum-tests workflow.
+#[test]
+#[ignore = "needs an ethereum/tests checkout in ETHTESTS"]
+fn upstream_fixtures() {
+    let root = PathBuf::from(std::env::var_os("ETHTESTS").expect("ETHTESTS is not set"));
+    for path in UPSTREAM_FIXTURES {
+        let results = run(&root.join(path), None);
+        assert!(!results.is_empty(), "{path} has no executable tests");
+        for result in &results {
+            assert!(result.is_success(), "{path}: {result:?}");
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Fuzzes insertion of the call output into the caller memory.
+//!
+//! Run with `cargo +nightly fuzz run insert_call_outcome` from `crates/interpreter`.
+#![no_main]
+
+use libfuzzer_sys::{arbitrary::Arbitrary, fuzz_target};
+use revm_interpreter::{
+    primitives::Bytes, CallOutcome, Contract, Gas, InstructionResult, Interpreter,
+    InterpreterResult, SharedMemory,
+};
+
+/// Memory bytes that are not part of the call output.
+const FILL: u8 = 0xAA;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    memory_len: u16,
+    out_start: usize,
+    out_len: u16,
+    output: Vec<u8>,
+    revert: bool,
+}
+
+fuzz_target!(|input: Input| {
+    let Input {
+        memory_len,
+        out_start,
+        out_len,
+        output,
+        revert,
+    } = input;
+    let memory_len = memory_len as usize;
+    let out_range = out_start..out_start.saturating_add(out_len as usize);
+
+    let mut memory = SharedMemory::new();
+    memory.resize(memory_len);
+    memory.set(0, &vec![FILL; memory_len]);
+
+    let mut interp = Interpreter::new(Contract::default(), u64::MAX, false);
+    let result = InterpreterResult::new(
+        if revert {
+            InstructionResult::Revert
+        } else {
+            InstructionResult::Return
+        },
+        Bytes::copy_from_slice(&output),
+        Gas::new(0),
+    );
+    interp.insert_call_outcome(&mut memory, CallOutcome::new(result, out_range.clone()));
+
+    let written = out_range
+        .len()
+        .min(output.len())
+        .min(memory_len.saturating_sub(out_start));
+    assert_eq!(memory.len(), memory_len);
+    for (i, byte) in memory.context_memory().iter().enumerate() {
+        if i >= out_start && i - out_start < written {
+            assert_eq!(*byte, output[i - out_start], "output mismatch at {i}");
+        } else {
+            assert_eq!(
+                *byte, FILL,
+                "memory outside of the out range was written at {i}"
+            );
+        }
+    }
+});
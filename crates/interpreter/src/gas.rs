@@ -6,6 +6,8 @@ mod constants;
 pub use calc::*;
 pub use constants::*;
 
+use crate::primitives::SpecId;
+
 /// Represents the state of gas during execution.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -60,6 +62,23 @@ impl Gas {
         self.refunded
     }
 
+    /// EIP-7623: Increase calldata cost
+    ///
+    /// Raises the gas used, gas spent minus the refund, to the `floor`. Refund is dropped if
+    /// the floor applies. Must be called after [`Gas::finalize_refund`].
+    ///
+    /// Returns `true` if the floor was applied.
+    #[inline]
+    pub fn apply_floor(&mut self, floor: u64) -> bool {
+        let used = self.spent().saturating_sub(self.refunded.max(0) as u64);
+        if used >= floor {
+            return false;
+        }
+        self.remaining = self.limit.saturating_sub(floor);
+        self.refunded = 0;
+        true
+    }
+
     /// Returns the total amount of gas spent.
     #[inline]
     pub const fn spent(&self) -> u64 {
@@ -73,6 +92,9 @@ impl Gas {
     }
 
     /// Return remaining gas after subtracting 63/64 parts.
+    ///
+    /// Computed as `remaining - remaining / 64`, so it does not overflow near `u64::MAX`.
+    #[inline]
     pub const fn remaining_63_of_64_parts(&self) -> u64 {
         self.remaining - self.remaining / 64
     }
@@ -98,15 +120,38 @@ impl Gas {
         self.refunded += refund;
     }
 
+    /// Applies the refund cap for the given spec and sets the final refund.
+    ///
+    /// Negative refund is clamped to zero and the refund is limited to the Nth part of the gas
+    /// spent, N is 2 before London and 5 after it (EIP-3529: Reduction in refunds).
+    ///
+    /// Returns the refund before the cap, it can be negative.
+    #[inline]
+    pub fn finalize_refund(&mut self, spec_id: SpecId) -> i64 {
+        let max_refund_quotient = if SpecId::enabled(spec_id, SpecId::LONDON) {
+            5
+        } else {
+            2
+        };
+        let refund_before_cap = self.refunded;
+        self.refunded =
+            (self.refunded.max(0) as u64).min(self.spent() / max_refund_quotient) as i64;
+        refund_before_cap
+    }
+
     /// Set a refund value for final refund.
     ///
     /// Max refund value is limited to Nth part (depending of fork) of gas spend.
     ///
     /// Related to EIP-3529: Reduction in refunds
     #[inline]
+    #[deprecated = "use `Gas::finalize_refund` instead"]
     pub fn set_final_refund(&mut self, is_london: bool) {
-        let max_refund_quotient = if is_london { 5 } else { 2 };
-        self.refunded = (self.refunded() as u64).min(self.spent() / max_refund_quotient) as i64;
+        self.finalize_refund(if is_london {
+            SpecId::LONDON
+        } else {
+            SpecId::BERLIN
+        });
     }
 
     /// Set a refund value. This overrides the current refund value.
@@ -115,6 +160,16 @@ impl Gas {
         self.refunded = refund;
     }
 
+    /// Adds `extra` gas to the limit and to the remaining gas.
+    ///
+    /// Used to top up the gas of an interpreter suspended by
+    /// [`Interpreter::run_resumable`](crate::Interpreter::run_resumable).
+    #[inline]
+    pub fn extend(&mut self, extra: u64) {
+        self.limit = self.limit.saturating_add(extra);
+        self.remaining = self.remaining.saturating_add(extra);
+    }
+
     /// Records an explicit cost.
     ///
     /// Returns `false` if the gas limit is exceeded.
@@ -129,3 +184,143 @@ impl Gas {
         success
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::GasScheduleOverrides;
+    use proptest::prelude::*;
+
+    fn finalized(limit: u64, spent: u64, refund: i64, spec_id: SpecId) -> Gas {
+        let mut gas = Gas::new(limit);
+        assert!(gas.record_cost(spent));
+        gas.record_refund(refund);
+        assert_eq!(gas.finalize_refund(spec_id), refund);
+        gas
+    }
+
+    #[test]
+    fn max_gas_limit() {
+        let mut gas = Gas::new(u64::MAX);
+        assert_eq!(gas.remaining_63_of_64_parts(), u64::MAX - u64::MAX / 64);
+        assert!(gas.record_cost(gas.remaining_63_of_64_parts()));
+        assert_eq!(gas.remaining(), u64::MAX / 64);
+
+        gas.erase_cost(u64::MAX - u64::MAX / 64);
+        assert_eq!(gas.spent(), 0);
+        assert!(gas.record_cost(u64::MAX));
+        assert_eq!(gas.spent(), u64::MAX);
+        assert!(!gas.record_cost(1));
+    }
+
+    #[test]
+    fn london_boundary() {
+        // cap is half of the gas spent before London.
+        let gas = finalized(100_000, 50_000, 40_000, SpecId::BERLIN);
+        assert_eq!(gas.refunded(), 25_000);
+
+        // and fifth part of the gas spent after it.
+        let gas = finalized(100_000, 50_000, 40_000, SpecId::LONDON);
+        assert_eq!(gas.refunded(), 10_000);
+
+        // refund under the cap is not changed.
+        let gas = finalized(100_000, 50_000, 9_000, SpecId::LONDON);
+        assert_eq!(gas.refunded(), 9_000);
+    }
+
+    #[test]
+    fn negative_refund_is_clamped() {
+        for spec_id in [SpecId::BERLIN, SpecId::LONDON] {
+            let gas = finalized(100_000, 50_000, -4_800, spec_id);
+            assert_eq!(gas.refunded(), 0);
+        }
+    }
+
+    #[test]
+    fn finalize_is_idempotent() {
+        let mut gas = finalized(100_000, 50_000, 40_000, SpecId::LONDON);
+        assert_eq!(gas.finalize_refund(SpecId::LONDON), 10_000);
+        assert_eq!(gas.refunded(), 10_000);
+    }
+
+    proptest! {
+        #[test]
+        fn gas_used_is_above_floor(
+            spec_id in prop::sample::select(vec![
+                SpecId::FRONTIER,
+                SpecId::BERLIN,
+                SpecId::LONDON,
+                SpecId::PRAGUE,
+            ]),
+            limit in 21_000u64..30_000_000,
+            spent in 0.0..=1.0f64,
+            floor in 0.0..=1.0f64,
+            refund in -30_000_000i64..30_000_000,
+        ) {
+            let spent = 21_000 + ((limit - 21_000) as f64 * spent) as u64;
+            let floor = (limit as f64 * floor) as u64;
+
+            let mut gas = finalized(limit, spent, refund, spec_id);
+            gas.apply_floor(floor);
+
+            // EIP-3529 and EIP-7623 applied by hand.
+            let max_refund = if spec_id >= SpecId::LONDON { spent / 5 } else { spent / 2 };
+            let refund = i128::from(refund).clamp(0, i128::from(max_refund));
+            let used = (i128::from(spent) - refund).max(i128::from(floor));
+
+            prop_assert!(gas.refunded() >= 0);
+            prop_assert_eq!(i128::from(gas.spent()) - i128::from(gas.refunded()), used);
+            prop_assert!(gas.spent() - gas.refunded() as u64 >= floor);
+        }
+    }
+
+    #[test]
+    fn size_costs_saturate() {
+        let none = GasScheduleOverrides::NONE;
+        assert_eq!(code_deposit_cost(&none, 0x6000), 0x6000 * CODEDEPOSIT);
+        assert_eq!(code_deposit_cost(&none, usize::MAX), u64::MAX);
+        let overrides = GasScheduleOverrides {
+            code_deposit: Some(1 << 63),
+            ..Default::default()
+        };
+        assert_eq!(code_deposit_cost(&overrides, 2), u64::MAX);
+        // the word cost of the largest length still fits.
+        assert_eq!(
+            initcode_cost(u64::MAX),
+            crate::num_words(u64::MAX) * INITCODE_WORD_COST
+        );
+    }
+
+    #[test]
+    fn floor() {
+        // gas used under the floor is raised to it.
+        let mut gas = finalized(100_000, 30_000, 0, SpecId::PRAGUE);
+        assert!(gas.apply_floor(40_000));
+        assert_eq!(
+            (gas.spent(), gas.remaining(), gas.refunded()),
+            (40_000, 60_000, 0)
+        );
+
+        // gas used over the floor is not changed.
+        let mut gas = finalized(100_000, 50_000, 0, SpecId::PRAGUE);
+        assert!(!gas.apply_floor(40_000));
+        assert_eq!(gas.spent(), 50_000);
+
+        // refund that drops gas used under the floor is dropped.
+        let mut gas = finalized(100_000, 45_000, 9_000, SpecId::PRAGUE);
+        assert!(gas.apply_floor(40_000));
+        assert_eq!((gas.spent(), gas.refunded()), (40_000, 0));
+
+        // refund that keeps gas used over the floor is kept.
+        let mut gas = finalized(100_000, 50_000, 9_000, SpecId::PRAGUE);
+        assert!(!gas.apply_floor(40_000));
+        assert_eq!((gas.spent(), gas.refunded()), (50_000, 9_000));
+    }
+
+    #[test]
+    fn tx_floor_cost() {
+        assert_eq!(calc_tx_floor_cost(&[]), 21_000);
+        // zero byte is one token, non zero byte is four.
+        assert_eq!(calc_tx_floor_cost(&[0, 1, 0, 2]), 21_000 + 10 * 10);
+    }
+}
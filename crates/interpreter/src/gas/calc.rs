@@ -1,7 +1,7 @@
 use super::constants::*;
 use crate::{
     num_words,
-    primitives::{AccessListItem, SpecId, U256},
+    primitives::{AccessListItem, GasScheduleOverrides, SpecId, U256},
     SelfDestructResult,
 };
 
@@ -15,14 +15,37 @@ macro_rules! tri {
     };
 }
 
+/// Returns the overridden cost, or the `default` cost of the spec if it is not overridden.
+#[inline(always)]
+const fn cost_or(cost: Option<u64>, default: u64) -> u64 {
+    match cost {
+        Some(cost) => cost,
+        None => default,
+    }
+}
+
 /// `SSTORE` opcode refund calculation.
+///
+/// Refunds that the spec derives from the storage costs are derived from the overridden costs,
+/// so a refund never exceeds the cost it gives back. The clear refund before London is fixed.
 #[allow(clippy::collapsible_else_if)]
 #[inline]
-pub fn sstore_refund(spec_id: SpecId, original: U256, current: U256, new: U256) -> i64 {
+pub fn sstore_refund(
+    overrides: &GasScheduleOverrides,
+    spec_id: SpecId,
+    original: U256,
+    current: U256,
+    new: U256,
+) -> i64 {
+    let sstore_set = cost_or(overrides.sstore_set, SSTORE_SET);
+    let sstore_reset = cost_or(overrides.sstore_reset, SSTORE_RESET);
+    let cold_sload = cost_or(overrides.cold_sload, COLD_SLOAD_COST);
     if spec_id.is_enabled_in(SpecId::ISTANBUL) {
         // EIP-3529: Reduction in refunds
         let sstore_clears_schedule = if spec_id.is_enabled_in(SpecId::LONDON) {
-            (SSTORE_RESET - COLD_SLOAD_COST + ACCESS_LIST_STORAGE_KEY) as i64
+            (sstore_reset.saturating_sub(cold_sload)
+                + cost_or(overrides.access_list_storage_key, ACCESS_LIST_STORAGE_KEY))
+                as i64
         } else {
             REFUND_SSTORE_CLEARS
         };
@@ -44,14 +67,17 @@ pub fn sstore_refund(spec_id: SpecId, original: U256, current: U256, new: U256)
 
                 if original == new {
                     let (gas_sstore_reset, gas_sload) = if spec_id.is_enabled_in(SpecId::BERLIN) {
-                        (SSTORE_RESET - COLD_SLOAD_COST, WARM_STORAGE_READ_COST)
+                        (
+                            sstore_reset.saturating_sub(cold_sload),
+                            cost_or(overrides.warm_storage_read, WARM_STORAGE_READ_COST),
+                        )
                     } else {
-                        (SSTORE_RESET, sload_cost(spec_id, false))
+                        (sstore_reset, sload_cost(overrides, spec_id, false))
                     };
                     if original == U256::ZERO {
-                        refund += (SSTORE_SET - gas_sload) as i64;
+                        refund += sstore_set.saturating_sub(gas_sload) as i64;
                     } else {
-                        refund += (gas_sstore_reset - gas_sload) as i64;
+                        refund += gas_sstore_reset.saturating_sub(gas_sload) as i64;
                     }
                 }
 
@@ -69,8 +95,8 @@ pub fn sstore_refund(spec_id: SpecId, original: U256, current: U256, new: U256)
 
 /// `CREATE2` opcode cost calculation.
 #[inline]
-pub const fn create2_cost(len: u64) -> Option<u64> {
-    CREATE.checked_add(tri!(cost_per_word(len, KECCAK256WORD)))
+pub const fn create2_cost(overrides: &GasScheduleOverrides, len: u64) -> Option<u64> {
+    cost_or(overrides.create, CREATE).checked_add(tri!(cost_per_word(len, KECCAK256WORD)))
 }
 
 #[inline]
@@ -98,16 +124,19 @@ const fn log2floor(value: U256) -> u64 {
 
 /// `EXP` opcode cost calculation.
 #[inline]
-pub fn exp_cost(spec_id: SpecId, power: U256) -> Option<u64> {
+pub fn exp_cost(overrides: &GasScheduleOverrides, spec_id: SpecId, power: U256) -> Option<u64> {
     if power == U256::ZERO {
         Some(EXP)
     } else {
         // EIP-160: EXP cost increase
-        let gas_byte = U256::from(if spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON) {
-            50
-        } else {
-            10
-        });
+        let gas_byte = U256::from(cost_or(
+            overrides.exp_byte,
+            if spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON) {
+                50
+            } else {
+                10
+            },
+        ));
         let gas = U256::from(EXP)
             .checked_add(gas_byte.checked_mul(U256::from(log2floor(power) / 8 + 1))?)?;
 
@@ -123,9 +152,14 @@ pub const fn verylowcopy_cost(len: u64) -> Option<u64> {
 
 /// `EXTCODECOPY` opcode cost calculation.
 #[inline]
-pub const fn extcodecopy_cost(spec_id: SpecId, len: u64, is_cold: bool) -> Option<u64> {
+pub const fn extcodecopy_cost(
+    overrides: &GasScheduleOverrides,
+    spec_id: SpecId,
+    len: u64,
+    is_cold: bool,
+) -> Option<u64> {
     let base_gas = if spec_id.is_enabled_in(SpecId::BERLIN) {
-        warm_cold_cost(is_cold)
+        warm_cold_cost(overrides, is_cold)
     } else if spec_id.is_enabled_in(SpecId::TANGERINE) {
         700
     } else {
@@ -136,8 +170,10 @@ pub const fn extcodecopy_cost(spec_id: SpecId, len: u64, is_cold: bool) -> Optio
 
 /// `LOG` opcode cost calculation.
 #[inline]
-pub const fn log_cost(n: u8, len: u64) -> Option<u64> {
-    tri!(LOG.checked_add(tri!(LOGDATA.checked_mul(len)))).checked_add(LOGTOPIC * n as u64)
+pub const fn log_cost(overrides: &GasScheduleOverrides, n: u8, len: u64) -> Option<u64> {
+    let data_gas = tri!(cost_or(overrides.log_data, LOGDATA).checked_mul(len));
+    let topics_gas = tri!(cost_or(overrides.log_topic, LOGTOPIC).checked_mul(n as u64));
+    tri!(cost_or(overrides.log, LOG).checked_add(data_gas)).checked_add(topics_gas)
 }
 
 /// `KECCAK256` opcode cost calculation.
@@ -156,23 +192,59 @@ pub const fn cost_per_word(len: u64, multiple: u64) -> Option<u64> {
 ///
 /// Apply extra gas cost of 2 for every 32-byte chunk of initcode.
 ///
-/// This cannot overflow as the initcode length is assumed to be checked.
+/// Saturates to `u64::MAX` like [`code_deposit_cost`], the cost of any `u64` length fits though.
 #[inline]
 pub const fn initcode_cost(len: u64) -> u64 {
-    let Some(cost) = cost_per_word(len, INITCODE_WORD_COST) else {
-        panic!("initcode cost overflow")
-    };
-    cost
+    match cost_per_word(len, INITCODE_WORD_COST) {
+        Some(cost) => cost,
+        None => u64::MAX,
+    }
+}
+
+/// EIP-7623: Increase calldata cost
+///
+/// Returns the floor of the gas used by the transaction with the given calldata. Zero byte
+/// counts as one token and non zero byte as [`STANDARD_TOKEN_COST`] tokens.
+#[inline]
+pub fn calc_tx_floor_cost(input: &[u8]) -> u64 {
+    let zero_data_len = input.iter().filter(|v| **v == 0).count() as u64;
+    let non_zero_data_len = input.len() as u64 - zero_data_len;
+    let tokens =
+        zero_data_len.saturating_add(non_zero_data_len.saturating_mul(STANDARD_TOKEN_COST));
+    tokens
+        .saturating_mul(TOTAL_COST_FLOOR_PER_TOKEN)
+        .saturating_add(21000)
+}
+
+/// Gas spent by the transaction before execution and the minimum gas it uses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InitialAndFloorGas {
+    /// Intrinsic gas of the transaction, see [`validate_initial_tx_gas`].
+    pub initial_gas: u64,
+    /// EIP-7623: Minimum gas used by the transaction, see [`calc_tx_floor_cost`]. Zero before
+    /// Prague.
+    pub floor_gas: u64,
+}
+
+/// Cost of depositing the code of the created contract.
+///
+/// Saturates to `u64::MAX`, forcing out of gas, so a code size limit raised with
+/// [`CfgEnv::limit_contract_code_size`](crate::primitives::CfgEnv::limit_contract_code_size)
+/// can't make the deposit free.
+#[inline]
+pub const fn code_deposit_cost(overrides: &GasScheduleOverrides, len: usize) -> u64 {
+    (len as u64).saturating_mul(cost_or(overrides.code_deposit, CODEDEPOSIT))
 }
 
 /// `SLOAD` opcode cost calculation.
 #[inline]
-pub const fn sload_cost(spec_id: SpecId, is_cold: bool) -> u64 {
+pub const fn sload_cost(overrides: &GasScheduleOverrides, spec_id: SpecId, is_cold: bool) -> u64 {
     if spec_id.is_enabled_in(SpecId::BERLIN) {
         if is_cold {
-            COLD_SLOAD_COST
+            cost_or(overrides.cold_sload, COLD_SLOAD_COST)
         } else {
-            WARM_STORAGE_READ_COST
+            cost_or(overrides.warm_storage_read, WARM_STORAGE_READ_COST)
         }
     } else if spec_id.is_enabled_in(SpecId::ISTANBUL) {
         // EIP-1884: Repricing for trie-size-dependent opcodes
@@ -188,6 +260,7 @@ pub const fn sload_cost(spec_id: SpecId, is_cold: bool) -> u64 {
 /// `SSTORE` opcode cost calculation.
 #[inline]
 pub fn sstore_cost(
+    overrides: &GasScheduleOverrides,
     spec_id: SpecId,
     original: U256,
     current: U256,
@@ -199,59 +272,109 @@ pub fn sstore_cost(
     if spec_id.is_enabled_in(SpecId::ISTANBUL) && gas <= CALL_STIPEND {
         return None;
     }
+    Some(sstore_cost_and_refund(overrides, spec_id, original, current, new, is_cold).0)
+}
 
-    if spec_id.is_enabled_in(SpecId::BERLIN) {
+/// `SSTORE` opcode cost and refund calculation.
+///
+/// `original` is the value of the slot at the start of the transaction, `current` is the
+/// value before this `SSTORE` and `new` is the value being stored. Refund can be negative
+/// if a refund of a previous `SSTORE` in the same transaction is taken back.
+///
+/// The EIP-1706 check that fails `SSTORE` with gas left lower than or equal to the call
+/// stipend is not included as it depends on the remaining gas, see [`sstore_cost`].
+///
+/// See [`sstore_refund`] for the refund.
+#[inline]
+pub fn sstore_cost_and_refund(
+    overrides: &GasScheduleOverrides,
+    spec_id: SpecId,
+    original: U256,
+    current: U256,
+    new: U256,
+    is_cold: bool,
+) -> (u64, i64) {
+    let sstore_set = cost_or(overrides.sstore_set, SSTORE_SET);
+    let sstore_reset = cost_or(overrides.sstore_reset, SSTORE_RESET);
+    let gas_cost = if spec_id.is_enabled_in(SpecId::BERLIN) {
         // Berlin specification logic
-        let mut gas_cost = istanbul_sstore_cost::<WARM_STORAGE_READ_COST, WARM_SSTORE_RESET>(
-            original, current, new,
+        let cold_sload = cost_or(overrides.cold_sload, COLD_SLOAD_COST);
+        let gas_cost = istanbul_sstore_cost(
+            cost_or(overrides.warm_storage_read, WARM_STORAGE_READ_COST),
+            sstore_set,
+            sstore_reset.saturating_sub(cold_sload),
+            original,
+            current,
+            new,
         );
-
         if is_cold {
-            gas_cost += COLD_SLOAD_COST;
+            gas_cost + cold_sload
+        } else {
+            gas_cost
         }
-        Some(gas_cost)
     } else if spec_id.is_enabled_in(SpecId::ISTANBUL) {
         // Istanbul logic
-        Some(istanbul_sstore_cost::<INSTANBUL_SLOAD_GAS, SSTORE_RESET>(
-            original, current, new,
-        ))
+        istanbul_sstore_cost(
+            INSTANBUL_SLOAD_GAS,
+            sstore_set,
+            sstore_reset,
+            original,
+            current,
+            new,
+        )
     } else {
         // Frontier logic
-        Some(frontier_sstore_cost(current, new))
-    }
+        frontier_sstore_cost(sstore_set, sstore_reset, current, new)
+    };
+    (
+        gas_cost,
+        sstore_refund(overrides, spec_id, original, current, new),
+    )
 }
 
 /// EIP-2200: Structured Definitions for Net Gas Metering
 #[inline]
-fn istanbul_sstore_cost<const SLOAD_GAS: u64, const SSTORE_RESET_GAS: u64>(
+fn istanbul_sstore_cost(
+    sload_gas: u64,
+    sstore_set_gas: u64,
+    sstore_reset_gas: u64,
     original: U256,
     current: U256,
     new: U256,
 ) -> u64 {
     if new == current {
-        SLOAD_GAS
+        sload_gas
     } else if original == current && original == U256::ZERO {
-        SSTORE_SET
+        sstore_set_gas
     } else if original == current {
-        SSTORE_RESET_GAS
+        sstore_reset_gas
     } else {
-        SLOAD_GAS
+        sload_gas
     }
 }
 
 /// Frontier sstore cost just had two cases set and reset values.
 #[inline]
-fn frontier_sstore_cost(current: U256, new: U256) -> u64 {
+fn frontier_sstore_cost(
+    sstore_set_gas: u64,
+    sstore_reset_gas: u64,
+    current: U256,
+    new: U256,
+) -> u64 {
     if current == U256::ZERO && new != U256::ZERO {
-        SSTORE_SET
+        sstore_set_gas
     } else {
-        SSTORE_RESET
+        sstore_reset_gas
     }
 }
 
 /// `SELFDESTRUCT` opcode cost calculation.
 #[inline]
-pub const fn selfdestruct_cost(spec_id: SpecId, res: SelfDestructResult) -> u64 {
+pub const fn selfdestruct_cost(
+    overrides: &GasScheduleOverrides,
+    spec_id: SpecId,
+    res: SelfDestructResult,
+) -> u64 {
     // EIP-161: State trie clearing (invariant-preserving alternative)
     let should_charge_topup = if spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON) {
         res.had_value && !res.target_exists
@@ -262,21 +385,21 @@ pub const fn selfdestruct_cost(spec_id: SpecId, res: SelfDestructResult) -> u64
     // EIP-150: Gas cost changes for IO-heavy operations
     let selfdestruct_gas_topup = if spec_id.is_enabled_in(SpecId::TANGERINE) && should_charge_topup
     {
-        25000
+        cost_or(overrides.new_account, NEWACCOUNT)
     } else {
         0
     };
 
     // EIP-150: Gas cost changes for IO-heavy operations
     let selfdestruct_gas = if spec_id.is_enabled_in(SpecId::TANGERINE) {
-        5000
+        SELFDESTRUCT_COST
     } else {
         0
     };
 
     let mut gas = selfdestruct_gas + selfdestruct_gas_topup;
     if spec_id.is_enabled_in(SpecId::BERLIN) && res.is_cold {
-        gas += COLD_ACCOUNT_ACCESS_COST
+        gas += cost_or(overrides.cold_account_access, COLD_ACCOUNT_ACCESS_COST)
     }
     gas
 }
@@ -290,14 +413,16 @@ pub const fn selfdestruct_cost(spec_id: SpecId, res: SelfDestructResult) -> u64
 /// this is only accounted if value is transferred.
 #[inline]
 pub const fn call_cost(
+    overrides: &GasScheduleOverrides,
     spec_id: SpecId,
     transfers_value: bool,
     is_cold: bool,
     new_account_accounting: bool,
 ) -> u64 {
+    let new_account = cost_or(overrides.new_account, NEWACCOUNT);
     // Account access.
     let mut gas = if spec_id.is_enabled_in(SpecId::BERLIN) {
-        warm_cold_cost(is_cold)
+        warm_cold_cost(overrides, is_cold)
     } else if spec_id.is_enabled_in(SpecId::TANGERINE) {
         // EIP-150: Gas cost changes for IO-heavy operations
         700
@@ -307,7 +432,7 @@ pub const fn call_cost(
 
     // transfer value cost
     if transfers_value {
-        gas += CALLVALUE;
+        gas += cost_or(overrides.call_value, CALLVALUE);
     }
 
     // new account cost
@@ -316,75 +441,107 @@ pub const fn call_cost(
         if spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON) {
             // account only if there is value transferred.
             if transfers_value {
-                gas += NEWACCOUNT;
+                gas += new_account;
             }
         } else {
-            gas += NEWACCOUNT;
+            gas += new_account;
         }
     }
 
     gas
 }
 
+/// Gas stipend that `CALL` and `CALLCODE` give to the callee on top of the forwarded gas.
+///
+/// Stipend is given only if value is transferred, in every spec and for every target,
+/// precompiles and accounts charged as new accounts included. It is not charged to the caller,
+/// unused stipend is returned to the caller together with the rest of the callee gas.
+#[inline]
+pub const fn call_stipend(transfers_value: bool) -> u64 {
+    if transfers_value {
+        CALL_STIPEND
+    } else {
+        0
+    }
+}
+
 /// Berlin warm and cold storage access cost for account access.
 #[inline]
-pub const fn warm_cold_cost(is_cold: bool) -> u64 {
+pub const fn warm_cold_cost(overrides: &GasScheduleOverrides, is_cold: bool) -> u64 {
     if is_cold {
-        COLD_ACCOUNT_ACCESS_COST
+        cost_or(overrides.cold_account_access, COLD_ACCOUNT_ACCESS_COST)
     } else {
-        WARM_STORAGE_READ_COST
+        cost_or(overrides.warm_storage_read, WARM_STORAGE_READ_COST)
     }
 }
 
 /// Memory expansion cost calculation for a given memory length.
 #[inline]
-pub const fn memory_gas_for_len(len: usize) -> u64 {
-    memory_gas(crate::interpreter::num_words(len as u64))
+pub const fn memory_gas_for_len(overrides: &GasScheduleOverrides, len: usize) -> u64 {
+    memory_gas(overrides, crate::interpreter::num_words(len as u64))
 }
 
 /// Memory expansion cost calculation for a given number of words.
+///
+/// Only the linear cost per word is overridable, the quadratic part of the cost is not affected.
 #[inline]
-pub const fn memory_gas(num_words: u64) -> u64 {
-    MEMORY
+pub const fn memory_gas(overrides: &GasScheduleOverrides, num_words: u64) -> u64 {
+    cost_or(overrides.memory_word, MEMORY)
         .saturating_mul(num_words)
         .saturating_add(num_words.saturating_mul(num_words) / 512)
 }
 
+/// Gas cost of the transaction calldata.
+#[inline]
+pub fn calldata_cost(overrides: &GasScheduleOverrides, spec_id: SpecId, input: &[u8]) -> u64 {
+    let zero_data_len = input.iter().filter(|v| **v == 0).count() as u64;
+    let non_zero_data_len = input.len() as u64 - zero_data_len;
+
+    zero_data_len
+        .saturating_mul(cost_or(overrides.tx_data_zero, TRANSACTION_ZERO_DATA))
+        // EIP-2028: Transaction data gas cost reduction
+        .saturating_add(non_zero_data_len.saturating_mul(cost_or(
+            overrides.tx_data_non_zero,
+            if spec_id.is_enabled_in(SpecId::ISTANBUL) {
+                TRANSACTION_NON_ZERO_DATA_INIT
+            } else {
+                TRANSACTION_NON_ZERO_DATA_FRONTIER
+            },
+        )))
+}
+
 /// Initial gas that is deducted for transaction to be included.
 /// Initial gas contains initial stipend gas, gas for access list and input data.
 pub fn validate_initial_tx_gas(
+    overrides: &GasScheduleOverrides,
     spec_id: SpecId,
     input: &[u8],
     is_create: bool,
     access_list: &[AccessListItem],
     authorization_list_num: u64,
 ) -> u64 {
-    let mut initial_gas = 0;
-    let zero_data_len = input.iter().filter(|v| **v == 0).count() as u64;
-    let non_zero_data_len = input.len() as u64 - zero_data_len;
-
     // initdate stipend
-    initial_gas += zero_data_len * TRANSACTION_ZERO_DATA;
-    // EIP-2028: Transaction data gas cost reduction
-    initial_gas += non_zero_data_len
-        * if spec_id.is_enabled_in(SpecId::ISTANBUL) {
-            16
-        } else {
-            68
-        };
+    let mut initial_gas = calldata_cost(overrides, spec_id, input);
 
     // get number of access list account and storages.
     if spec_id.is_enabled_in(SpecId::BERLIN) {
         let accessed_slots: usize = access_list.iter().map(|item| item.storage_keys.len()).sum();
-        initial_gas += access_list.len() as u64 * ACCESS_LIST_ADDRESS;
-        initial_gas += accessed_slots as u64 * ACCESS_LIST_STORAGE_KEY;
+        initial_gas = initial_gas
+            .saturating_add(
+                (access_list.len() as u64)
+                    .saturating_mul(cost_or(overrides.access_list_address, ACCESS_LIST_ADDRESS)),
+            )
+            .saturating_add((accessed_slots as u64).saturating_mul(cost_or(
+                overrides.access_list_storage_key,
+                ACCESS_LIST_STORAGE_KEY,
+            )));
     }
 
     // base stipend
     initial_gas += if is_create {
         if spec_id.is_enabled_in(SpecId::HOMESTEAD) {
             // EIP-2: Homestead Hard-fork Changes
-            53000
+            cost_or(overrides.create, CREATE).saturating_add(21000)
         } else {
             21000
         }
@@ -395,13 +552,214 @@ pub fn validate_initial_tx_gas(
     // EIP-3860: Limit and meter initcode
     // Init code stipend for bytecode analysis
     if spec_id.is_enabled_in(SpecId::SHANGHAI) && is_create {
-        initial_gas += initcode_cost(input.len() as u64)
+        initial_gas = initial_gas.saturating_add(initcode_cost(input.len() as u64));
     }
 
     //   EIP-7702
     if spec_id.is_enabled_in(SpecId::PRAGUE) {
-        initial_gas += authorization_list_num * PER_CONTRACT_CODE_BASE_COST;
+        initial_gas = initial_gas
+            .saturating_add(authorization_list_num.saturating_mul(PER_CONTRACT_CODE_BASE_COST));
     }
 
     initial_gas
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Original, current, new, (Istanbul cost, refund), (Berlin cost, refund), London refund.
+    type SstoreRow = (u8, u8, u8, (u64, i64), (u64, i64), i64);
+
+    /// Gas cost and refund of warm `SSTORE` for every combination of original, current and new
+    /// value, from the tables of EIP-2200 (Istanbul), EIP-2929 (Berlin) and EIP-3529 (London).
+    ///
+    /// London cost is the same as Berlin.
+    #[rustfmt::skip]
+    const SSTORE_MATRIX: [SstoreRow; 27] = [
+        (0, 0, 0, (800, 0), (100, 0), 0),
+        (0, 0, 1, (20000, 0), (20000, 0), 0),
+        (0, 0, 2, (20000, 0), (20000, 0), 0),
+        (0, 1, 0, (800, 19200), (100, 19900), 19900),
+        (0, 1, 1, (800, 0), (100, 0), 0),
+        (0, 1, 2, (800, 0), (100, 0), 0),
+        (0, 2, 0, (800, 19200), (100, 19900), 19900),
+        (0, 2, 1, (800, 0), (100, 0), 0),
+        (0, 2, 2, (800, 0), (100, 0), 0),
+        (1, 0, 0, (800, 0), (100, 0), 0),
+        (1, 0, 1, (800, -10800), (100, -12200), -2000),
+        (1, 0, 2, (800, -15000), (100, -15000), -4800),
+        (1, 1, 0, (5000, 15000), (2900, 15000), 4800),
+        (1, 1, 1, (800, 0), (100, 0), 0),
+        (1, 1, 2, (5000, 0), (2900, 0), 0),
+        (1, 2, 0, (800, 15000), (100, 15000), 4800),
+        (1, 2, 1, (800, 4200), (100, 2800), 2800),
+        (1, 2, 2, (800, 0), (100, 0), 0),
+        (2, 0, 0, (800, 0), (100, 0), 0),
+        (2, 0, 1, (800, -15000), (100, -15000), -4800),
+        (2, 0, 2, (800, -10800), (100, -12200), -2000),
+        (2, 1, 0, (800, 15000), (100, 15000), 4800),
+        (2, 1, 1, (800, 0), (100, 0), 0),
+        (2, 1, 2, (800, 4200), (100, 2800), 2800),
+        (2, 2, 0, (5000, 15000), (2900, 15000), 4800),
+        (2, 2, 1, (5000, 0), (2900, 0), 0),
+        (2, 2, 2, (800, 0), (100, 0), 0),
+    ];
+
+    #[test]
+    fn sstore_cost_and_refund_matrix() {
+        for (original, current, new, istanbul, berlin, london_refund) in SSTORE_MATRIX {
+            let [original, current, new] = [original, current, new].map(U256::from);
+            let london = (berlin.0, london_refund);
+            for (spec_id, (cost, refund)) in [
+                (SpecId::ISTANBUL, istanbul),
+                (SpecId::BERLIN, berlin),
+                (SpecId::LONDON, london),
+            ] {
+                let case = (spec_id, original, current, new);
+                assert_eq!(
+                    sstore_cost_and_refund(
+                        &GasScheduleOverrides::NONE,
+                        spec_id,
+                        original,
+                        current,
+                        new,
+                        false
+                    ),
+                    (cost, refund),
+                    "warm {case:?}"
+                );
+                // Cold access is charged only from Berlin (EIP-2929).
+                let cold_cost = if spec_id == SpecId::ISTANBUL {
+                    cost
+                } else {
+                    cost + COLD_SLOAD_COST
+                };
+                assert_eq!(
+                    sstore_cost_and_refund(
+                        &GasScheduleOverrides::NONE,
+                        spec_id,
+                        original,
+                        current,
+                        new,
+                        true
+                    ),
+                    (cold_cost, refund),
+                    "cold {case:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sstore_frontier() {
+        let [zero, one] = [U256::ZERO, U256::from(1)];
+        let spec_id = SpecId::PETERSBURG;
+        assert_eq!(
+            sstore_cost_and_refund(&GasScheduleOverrides::NONE, spec_id, zero, zero, one, true),
+            (SSTORE_SET, 0)
+        );
+        assert_eq!(
+            sstore_cost_and_refund(&GasScheduleOverrides::NONE, spec_id, one, one, zero, false),
+            (SSTORE_RESET, REFUND_SSTORE_CLEARS)
+        );
+        assert_eq!(
+            sstore_cost(
+                &GasScheduleOverrides::NONE,
+                spec_id,
+                one,
+                one,
+                one,
+                0,
+                false
+            ),
+            Some(SSTORE_RESET)
+        );
+    }
+
+    #[test]
+    fn sstore_refunds_follow_overridden_costs() {
+        let [zero, one] = [U256::ZERO, U256::from(1)];
+        let overrides = GasScheduleOverrides {
+            sstore_set: Some(5000),
+            sstore_reset: Some(3000),
+            ..Default::default()
+        };
+        let spec_id = SpecId::LONDON;
+        // set and restored to zero, the refund gives back the set cost minus the warm read.
+        let (set_cost, _) = sstore_cost_and_refund(&overrides, spec_id, zero, zero, one, false);
+        let (_, refund) = sstore_cost_and_refund(&overrides, spec_id, zero, one, zero, false);
+        assert_eq!(set_cost, 5000);
+        assert_eq!(refund, 5000 - 100);
+        // reset and restored, the refund gives back the reset cost minus the warm read.
+        let (reset_cost, _) = sstore_cost_and_refund(&overrides, spec_id, one, one, zero, true);
+        let (_, refund) = sstore_cost_and_refund(&overrides, spec_id, one, zero, one, false);
+        assert_eq!(reset_cost, 3000);
+        assert_eq!(refund, -(3000 - 2100 + 1900) + (3000 - 2100 - 100));
+        // cleared slot is refunded the reset cost without the cold access, as EIP-3529.
+        let (_, refund) = sstore_cost_and_refund(&overrides, spec_id, one, one, zero, false);
+        assert_eq!(refund, 3000 - 2100 + 1900);
+
+        // without overrides the refunds of the spec are kept.
+        for (original, current, new, _, _, london_refund) in SSTORE_MATRIX {
+            let [original, current, new] = [original, current, new].map(U256::from);
+            assert_eq!(
+                sstore_refund(&GasScheduleOverrides::NONE, spec_id, original, current, new),
+                london_refund
+            );
+        }
+    }
+
+    #[test]
+    fn sstore_reentrancy_sentry() {
+        let [zero, one] = [U256::ZERO, U256::from(1)];
+        for spec_id in [SpecId::ISTANBUL, SpecId::BERLIN, SpecId::LONDON] {
+            // EIP-1706: fails if gas left is less than or equal to the call stipend, even if
+            // the cost itself would fit.
+            assert_eq!(
+                sstore_cost(
+                    &GasScheduleOverrides::NONE,
+                    spec_id,
+                    one,
+                    one,
+                    one,
+                    CALL_STIPEND,
+                    false
+                ),
+                None
+            );
+            assert_eq!(
+                sstore_cost(
+                    &GasScheduleOverrides::NONE,
+                    spec_id,
+                    one,
+                    one,
+                    one,
+                    0,
+                    false
+                ),
+                None
+            );
+            let (cost, _) = sstore_cost_and_refund(
+                &GasScheduleOverrides::NONE,
+                spec_id,
+                zero,
+                zero,
+                one,
+                false,
+            );
+            assert_eq!(
+                sstore_cost(
+                    &GasScheduleOverrides::NONE,
+                    spec_id,
+                    zero,
+                    zero,
+                    one,
+                    CALL_STIPEND + 1,
+                    false
+                ),
+                Some(cost)
+            );
+        }
+    }
+}
@@ -13,6 +13,8 @@ pub const MID: u64 = 8;
 pub const HIGH: u64 = 10;
 pub const JUMPDEST: u64 = 1;
 pub const SELFDESTRUCT: i64 = 24000;
+/// Static cost of `SELFDESTRUCT` since Tangerine, see [`SELFDESTRUCT`] for the refund.
+pub const SELFDESTRUCT_COST: u64 = 5000;
 pub const CREATE: u64 = 32000;
 pub const CALLVALUE: u64 = 9000;
 pub const NEWACCOUNT: u64 = 25000;
@@ -50,6 +52,10 @@ pub const WARM_SSTORE_RESET: u64 = SSTORE_RESET - COLD_SLOAD_COST;
 /// EIP-7702
 pub const PER_CONTRACT_CODE_BASE_COST: u64 = 2400;
 
+/// EIP-7623: Increase calldata cost
+pub const STANDARD_TOKEN_COST: u64 = 4;
+pub const TOTAL_COST_FLOOR_PER_TOKEN: u64 = 10;
+
 /// EIP-3860 : Limit and meter initcode
 pub const INITCODE_WORD_COST: u64 = 2;
 
@@ -1,19 +1,25 @@
-use crate::primitives::{Address, Bytes, Env, Log, B256, U256};
+use crate::primitives::{Address, Bytes, Env, Log, SpecId, B256, U256};
 
 mod dummy;
 pub use dummy::DummyHost;
 
 /// EVM context host.
-pub trait Host {
+///
+/// Implemented for every type that implements both [`HostRead`] and [`HostWrite`].
+pub trait Host: HostRead + HostWrite {}
+
+impl<T: HostRead + HostWrite + ?Sized> Host for T {}
+
+/// Read side of the EVM context host.
+///
+/// It is enough to run instructions that don't modify the state, for example in analysis passes.
+pub trait HostRead {
     /// Returns a reference to the environment.
     fn env(&self) -> &Env;
 
-    /// Returns a mutable reference to the environment.
-    fn env_mut(&mut self) -> &mut Env;
-
     /// Load an account.
     ///
-    /// Returns (is_cold, is_new_account)
+    /// Returns whether the account was cold loaded, is empty and exists.
     fn load_account(&mut self, address: Address) -> Option<LoadAccountResult>;
 
     /// Get the block hash of the given block `number`.
@@ -31,20 +37,51 @@ pub trait Host {
     /// Get storage value of `address` at `index` and if the account is cold.
     fn sload(&mut self, address: Address, index: U256) -> Option<(U256, bool)>;
 
+    /// Get the transient storage value of `address` at `index`.
+    fn tload(&mut self, address: Address, index: U256) -> U256;
+
+    /// Get the initcontainer of the initcode transaction with the given keccak256 hash, see
+    /// [`TxEnv::initcodes`](crate::primitives::TxEnv::initcodes).
+    ///
+    /// The default implementation uses `TxEnv::initcode_by_hash`, it hashes every initcode of
+    /// the transaction on each lookup, so each TXCREATE costs a keccak256 of all initcodes.
+    /// Hosts should override it to hash the initcodes once per transaction, as the revm
+    /// `Context` does.
+    #[inline]
+    fn initcode_by_hash(&mut self, hash: B256) -> Option<Bytes> {
+        self.env().tx().initcode_by_hash(hash).cloned()
+    }
+}
+
+/// Write side of the EVM context host.
+pub trait HostWrite {
+    /// Returns a mutable reference to the environment.
+    fn env_mut(&mut self) -> &mut Env;
+
     /// Set storage value of account address at index.
     ///
     /// Returns (original, present, new, is_cold).
     fn sstore(&mut self, address: Address, index: U256, value: U256) -> Option<SStoreResult>;
 
-    /// Get the transient storage value of `address` at `index`.
-    fn tload(&mut self, address: Address, index: U256) -> U256;
-
     /// Set the transient storage value of `address` at `index`.
     fn tstore(&mut self, address: Address, index: U256, value: U256);
 
     /// Emit a log owned by `address` with given `LogData`.
     fn log(&mut self, log: Log);
 
+    /// Emit a log owned by `address` with the given topics and data.
+    ///
+    /// Called by the `LOG*` instructions. The default implementation creates a [`Log`] and calls
+    /// [`HostWrite::log`], hosts that buffer the logs can override it to avoid the allocations.
+    #[inline]
+    fn log_raw(&mut self, address: Address, topics: &[B256], data: &[u8]) {
+        self.log(Log::new_unchecked(
+            address,
+            topics.to_vec(),
+            Bytes::copy_from_slice(data),
+        ));
+    }
+
     /// Mark `address` to be deleted, with funds transferred to `target`.
     fn selfdestruct(&mut self, address: Address, target: Address) -> Option<SelfDestructResult>;
 }
@@ -68,17 +105,45 @@ pub struct SStoreResult {
 pub struct LoadAccountResult {
     /// Is account cold loaded
     pub is_cold: bool,
-    /// Is account empty, if true account is not created.
+    /// Is account empty as defined by EIP-161: no code, zero nonce and zero balance.
     pub is_empty: bool,
+    /// Whether the account exists, even if it is empty.
+    ///
+    /// Account exists if it was returned by the database, or if it was created (touched)
+    /// earlier in the transaction and is not yet written to the database.
+    pub exists_in_db: bool,
+}
+
+impl LoadAccountResult {
+    /// Returns `true` if the account is charged as a new account when called or
+    /// selfdestructed to.
+    ///
+    /// Before EIP-161 (Spurious Dragon) these are non existing accounts, after it these are
+    /// empty accounts, and they are charged only if value is transferred, see
+    /// [`call_cost`](crate::gas::call_cost).
+    #[inline]
+    pub const fn is_new_account(&self, spec_id: SpecId) -> bool {
+        if spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON) {
+            self.is_empty
+        } else {
+            !self.exists_in_db
+        }
+    }
 }
 
 /// Result of a selfdestruct instruction.
 #[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SelfDestructResult {
+    /// Selfdestructed account had non-zero balance.
     pub had_value: bool,
+    /// Target account exists (see EIP-161 for the definition of existence).
     pub target_exists: bool,
+    /// Target account was cold loaded.
     pub is_cold: bool,
+    /// Account was already selfdestructed earlier in the same transaction.
+    ///
+    /// Used to refund `SELFDESTRUCT` gas only once before London.
     pub previously_destroyed: bool,
 }
 
@@ -88,9 +153,13 @@ mod tests {
 
     fn assert_host<H: Host + ?Sized>() {}
 
+    fn assert_host_read<H: HostRead + ?Sized>() {}
+
     #[test]
     fn object_safety() {
         assert_host::<DummyHost>();
         assert_host::<dyn Host>();
+        assert_host_read::<dyn Host>();
+        assert_host_read::<dyn HostRead>();
     }
 }
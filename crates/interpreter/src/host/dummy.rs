@@ -1,12 +1,12 @@
 use crate::{
     primitives::{hash_map::Entry, Address, Bytes, Env, HashMap, Log, B256, KECCAK_EMPTY, U256},
-    Host, SStoreResult, SelfDestructResult,
+    HostRead, HostWrite, SStoreResult, SelfDestructResult,
 };
 use std::vec::Vec;
 
 use super::LoadAccountResult;
 
-/// A dummy [Host] implementation.
+/// A dummy [Host](crate::Host) implementation.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct DummyHost {
     pub env: Env,
@@ -33,20 +33,18 @@ impl DummyHost {
     }
 }
 
-impl Host for DummyHost {
+impl HostRead for DummyHost {
     #[inline]
     fn env(&self) -> &Env {
         &self.env
     }
 
-    #[inline]
-    fn env_mut(&mut self) -> &mut Env {
-        &mut self.env
-    }
-
     #[inline]
     fn load_account(&mut self, _address: Address) -> Option<LoadAccountResult> {
-        Some(LoadAccountResult::default())
+        Some(LoadAccountResult {
+            exists_in_db: true,
+            ..Default::default()
+        })
     }
 
     #[inline]
@@ -80,6 +78,21 @@ impl Host for DummyHost {
         }
     }
 
+    #[inline]
+    fn tload(&mut self, _address: Address, index: U256) -> U256 {
+        self.transient_storage
+            .get(&index)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl HostWrite for DummyHost {
+    #[inline]
+    fn env_mut(&mut self) -> &mut Env {
+        &mut self.env
+    }
+
     #[inline]
     fn sstore(&mut self, _address: Address, index: U256, value: U256) -> Option<SStoreResult> {
         let present = self.storage.insert(index, value);
@@ -91,14 +104,6 @@ impl Host for DummyHost {
         })
     }
 
-    #[inline]
-    fn tload(&mut self, _address: Address, index: U256) -> U256 {
-        self.transient_storage
-            .get(&index)
-            .copied()
-            .unwrap_or_default()
-    }
-
     #[inline]
     fn tstore(&mut self, _address: Address, index: U256, value: U256) {
         self.transient_storage.insert(index, value);
@@ -1,69 +1,114 @@
-use crate::primitives::{HaltReason, OutOfGasError, SuccessReason};
+use crate::primitives::{HaltReason, OutOfGasError, SuccessReason, UnknownIdentifier};
+use core::fmt;
 
+/// Result of an instruction or of the interpreter loop.
+///
+/// Every variant has a stable identifier, returned by [`InstructionResult::as_str`] and used by
+/// serde. Identifiers do not change when variants are reordered or renamed.
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum InstructionResult {
     // success codes
     #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "Continue"))]
     Continue = 0x00,
+    #[cfg_attr(feature = "serde", serde(rename = "Stop"))]
     Stop,
+    #[cfg_attr(feature = "serde", serde(rename = "Return"))]
     Return,
+    #[cfg_attr(feature = "serde", serde(rename = "SelfDestruct"))]
     SelfDestruct,
+    #[cfg_attr(feature = "serde", serde(rename = "ReturnContract"))]
     ReturnContract,
 
     // revert codes
+    #[cfg_attr(feature = "serde", serde(rename = "Revert"))]
     Revert = 0x10, // revert opcode
+    #[cfg_attr(feature = "serde", serde(rename = "CallTooDeep"))]
     CallTooDeep,
+    #[cfg_attr(feature = "serde", serde(rename = "OutOfFunds"))]
     OutOfFunds,
     /// Revert if CREATE/CREATE2 starts with 0xEF00
+    #[cfg_attr(feature = "serde", serde(rename = "CreateInitCodeStartingEF00"))]
     CreateInitCodeStartingEF00,
     /// Invalid EOF initcode,
+    #[cfg_attr(feature = "serde", serde(rename = "InvalidEOFInitCode"))]
     InvalidEOFInitCode,
     /// ExtDelegateCall calling a non EOF contract.
+    #[cfg_attr(feature = "serde", serde(rename = "InvalidExtDelegateCallTarget"))]
     InvalidExtDelegateCallTarget,
 
     // Actions
+    #[cfg_attr(feature = "serde", serde(rename = "CallOrCreate"))]
     CallOrCreate = 0x20,
 
     // error codes
+    #[cfg_attr(feature = "serde", serde(rename = "OutOfGas"))]
     OutOfGas = 0x50,
+    #[cfg_attr(feature = "serde", serde(rename = "MemoryOOG"))]
     MemoryOOG,
+    #[cfg_attr(feature = "serde", serde(rename = "MemoryLimitOOG"))]
     MemoryLimitOOG,
+    #[cfg_attr(feature = "serde", serde(rename = "PrecompileOOG"))]
     PrecompileOOG,
+    #[cfg_attr(feature = "serde", serde(rename = "InvalidOperandOOG"))]
     InvalidOperandOOG,
+    #[cfg_attr(feature = "serde", serde(rename = "OpcodeNotFound"))]
     OpcodeNotFound,
+    #[cfg_attr(feature = "serde", serde(rename = "CallNotAllowedInsideStatic"))]
     CallNotAllowedInsideStatic,
+    #[cfg_attr(feature = "serde", serde(rename = "StateChangeDuringStaticCall"))]
     StateChangeDuringStaticCall,
+    #[cfg_attr(feature = "serde", serde(rename = "InvalidEFOpcode"))]
     InvalidEFOpcode,
+    #[cfg_attr(feature = "serde", serde(rename = "InvalidJump"))]
     InvalidJump,
+    #[cfg_attr(feature = "serde", serde(rename = "NotActivated"))]
     NotActivated,
+    #[cfg_attr(feature = "serde", serde(rename = "StackUnderflow"))]
     StackUnderflow,
+    #[cfg_attr(feature = "serde", serde(rename = "StackOverflow"))]
     StackOverflow,
+    #[cfg_attr(feature = "serde", serde(rename = "OutOfOffset"))]
     OutOfOffset,
+    #[cfg_attr(feature = "serde", serde(rename = "CreateCollision"))]
     CreateCollision,
+    #[cfg_attr(feature = "serde", serde(rename = "OverflowPayment"))]
     OverflowPayment,
+    #[cfg_attr(feature = "serde", serde(rename = "PrecompileError"))]
     PrecompileError,
+    #[cfg_attr(feature = "serde", serde(rename = "NonceOverflow"))]
     NonceOverflow,
     /// Create init code size exceeds limit (runtime).
+    #[cfg_attr(feature = "serde", serde(rename = "CreateContractSizeLimit"))]
     CreateContractSizeLimit,
     /// Error on created contract that begins with EF
+    #[cfg_attr(feature = "serde", serde(rename = "CreateContractStartingWithEF"))]
     CreateContractStartingWithEF,
     /// EIP-3860: Limit and meter initcode. Initcode size limit exceeded.
+    #[cfg_attr(feature = "serde", serde(rename = "CreateInitCodeSizeLimit"))]
     CreateInitCodeSizeLimit,
     /// Fatal external error. Returned by database.
+    #[cfg_attr(feature = "serde", serde(rename = "FatalExternalError"))]
     FatalExternalError,
     /// RETURNCONTRACT called in not init eof code.
+    #[cfg_attr(feature = "serde", serde(rename = "ReturnContractInNotInitEOF"))]
     ReturnContractInNotInitEOF,
     /// Legacy contract is calling opcode that is enabled only in EOF.
+    #[cfg_attr(feature = "serde", serde(rename = "EOFOpcodeDisabledInLegacy"))]
     EOFOpcodeDisabledInLegacy,
     /// EOF function stack overflow
+    #[cfg_attr(feature = "serde", serde(rename = "EOFFunctionStackOverflow"))]
     EOFFunctionStackOverflow,
     /// Aux data overflow, new aux data is larger tha u16 max size.
+    #[cfg_attr(feature = "serde", serde(rename = "EofAuxDataOverflow"))]
     EofAuxDataOverflow,
     /// Aux data is smaller then already present data size.
+    #[cfg_attr(feature = "serde", serde(rename = "EofAuxDataTooSmall"))]
     EofAuxDataTooSmall,
     /// EXT*CALL target address needs to be padded with 0s.
+    #[cfg_attr(feature = "serde", serde(rename = "InvalidEXTCALLTarget"))]
     InvalidEXTCALLTarget,
 }
 
@@ -190,6 +235,108 @@ impl InstructionResult {
     pub const fn is_error(self) -> bool {
         matches!(self, return_error!())
     }
+
+    /// Returns the stable identifier of the result.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Continue => "Continue",
+            Self::Stop => "Stop",
+            Self::Return => "Return",
+            Self::SelfDestruct => "SelfDestruct",
+            Self::ReturnContract => "ReturnContract",
+            Self::Revert => "Revert",
+            Self::CallTooDeep => "CallTooDeep",
+            Self::OutOfFunds => "OutOfFunds",
+            Self::CreateInitCodeStartingEF00 => "CreateInitCodeStartingEF00",
+            Self::InvalidEOFInitCode => "InvalidEOFInitCode",
+            Self::InvalidExtDelegateCallTarget => "InvalidExtDelegateCallTarget",
+            Self::CallOrCreate => "CallOrCreate",
+            Self::OutOfGas => "OutOfGas",
+            Self::MemoryOOG => "MemoryOOG",
+            Self::MemoryLimitOOG => "MemoryLimitOOG",
+            Self::PrecompileOOG => "PrecompileOOG",
+            Self::InvalidOperandOOG => "InvalidOperandOOG",
+            Self::OpcodeNotFound => "OpcodeNotFound",
+            Self::CallNotAllowedInsideStatic => "CallNotAllowedInsideStatic",
+            Self::StateChangeDuringStaticCall => "StateChangeDuringStaticCall",
+            Self::InvalidEFOpcode => "InvalidEFOpcode",
+            Self::InvalidJump => "InvalidJump",
+            Self::NotActivated => "NotActivated",
+            Self::StackUnderflow => "StackUnderflow",
+            Self::StackOverflow => "StackOverflow",
+            Self::OutOfOffset => "OutOfOffset",
+            Self::CreateCollision => "CreateCollision",
+            Self::OverflowPayment => "OverflowPayment",
+            Self::PrecompileError => "PrecompileError",
+            Self::NonceOverflow => "NonceOverflow",
+            Self::CreateContractSizeLimit => "CreateContractSizeLimit",
+            Self::CreateContractStartingWithEF => "CreateContractStartingWithEF",
+            Self::CreateInitCodeSizeLimit => "CreateInitCodeSizeLimit",
+            Self::FatalExternalError => "FatalExternalError",
+            Self::ReturnContractInNotInitEOF => "ReturnContractInNotInitEOF",
+            Self::EOFOpcodeDisabledInLegacy => "EOFOpcodeDisabledInLegacy",
+            Self::EOFFunctionStackOverflow => "EOFFunctionStackOverflow",
+            Self::EofAuxDataOverflow => "EofAuxDataOverflow",
+            Self::EofAuxDataTooSmall => "EofAuxDataTooSmall",
+            Self::InvalidEXTCALLTarget => "InvalidEXTCALLTarget",
+        }
+    }
+}
+
+impl core::str::FromStr for InstructionResult {
+    type Err = UnknownIdentifier;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Continue" => Self::Continue,
+            "Stop" => Self::Stop,
+            "Return" => Self::Return,
+            "SelfDestruct" => Self::SelfDestruct,
+            "ReturnContract" => Self::ReturnContract,
+            "Revert" => Self::Revert,
+            "CallTooDeep" => Self::CallTooDeep,
+            "OutOfFunds" => Self::OutOfFunds,
+            "CreateInitCodeStartingEF00" => Self::CreateInitCodeStartingEF00,
+            "InvalidEOFInitCode" => Self::InvalidEOFInitCode,
+            "InvalidExtDelegateCallTarget" => Self::InvalidExtDelegateCallTarget,
+            "CallOrCreate" => Self::CallOrCreate,
+            "OutOfGas" => Self::OutOfGas,
+            "MemoryOOG" => Self::MemoryOOG,
+            "MemoryLimitOOG" => Self::MemoryLimitOOG,
+            "PrecompileOOG" => Self::PrecompileOOG,
+            "InvalidOperandOOG" => Self::InvalidOperandOOG,
+            "OpcodeNotFound" => Self::OpcodeNotFound,
+            "CallNotAllowedInsideStatic" => Self::CallNotAllowedInsideStatic,
+            "StateChangeDuringStaticCall" => Self::StateChangeDuringStaticCall,
+            "InvalidEFOpcode" => Self::InvalidEFOpcode,
+            "InvalidJump" => Self::InvalidJump,
+            "NotActivated" => Self::NotActivated,
+            "StackUnderflow" => Self::StackUnderflow,
+            "StackOverflow" => Self::StackOverflow,
+            "OutOfOffset" => Self::OutOfOffset,
+            "CreateCollision" => Self::CreateCollision,
+            "OverflowPayment" => Self::OverflowPayment,
+            "PrecompileError" => Self::PrecompileError,
+            "NonceOverflow" => Self::NonceOverflow,
+            "CreateContractSizeLimit" => Self::CreateContractSizeLimit,
+            "CreateContractStartingWithEF" => Self::CreateContractStartingWithEF,
+            "CreateInitCodeSizeLimit" => Self::CreateInitCodeSizeLimit,
+            "FatalExternalError" => Self::FatalExternalError,
+            "ReturnContractInNotInitEOF" => Self::ReturnContractInNotInitEOF,
+            "EOFOpcodeDisabledInLegacy" => Self::EOFOpcodeDisabledInLegacy,
+            "EOFFunctionStackOverflow" => Self::EOFFunctionStackOverflow,
+            "EofAuxDataOverflow" => Self::EofAuxDataOverflow,
+            "EofAuxDataTooSmall" => Self::EofAuxDataTooSmall,
+            "InvalidEXTCALLTarget" => Self::InvalidEXTCALLTarget,
+            _ => return Err(UnknownIdentifier),
+        })
+    }
+}
+
+impl fmt::Display for InstructionResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// Internal result that are not ex
@@ -325,7 +472,7 @@ impl From<InstructionResult> for SuccessOrHalt {
 
 #[cfg(test)]
 mod tests {
-    use crate::InstructionResult;
+    use crate::{primitives::UnknownIdentifier, InstructionResult};
 
     #[test]
     fn all_results_are_covered() {
@@ -395,4 +542,94 @@ mod tests {
             assert!(result.is_error());
         }
     }
+
+    /// Identifiers are part of the RPC output, changing one is a breaking change.
+    #[test]
+    fn stable_identifiers() {
+        let results = [
+            (InstructionResult::Continue, "Continue"),
+            (InstructionResult::Stop, "Stop"),
+            (InstructionResult::Return, "Return"),
+            (InstructionResult::SelfDestruct, "SelfDestruct"),
+            (InstructionResult::ReturnContract, "ReturnContract"),
+            (InstructionResult::Revert, "Revert"),
+            (InstructionResult::CallTooDeep, "CallTooDeep"),
+            (InstructionResult::OutOfFunds, "OutOfFunds"),
+            (
+                InstructionResult::CreateInitCodeStartingEF00,
+                "CreateInitCodeStartingEF00",
+            ),
+            (InstructionResult::InvalidEOFInitCode, "InvalidEOFInitCode"),
+            (
+                InstructionResult::InvalidExtDelegateCallTarget,
+                "InvalidExtDelegateCallTarget",
+            ),
+            (InstructionResult::CallOrCreate, "CallOrCreate"),
+            (InstructionResult::OutOfGas, "OutOfGas"),
+            (InstructionResult::MemoryOOG, "MemoryOOG"),
+            (InstructionResult::MemoryLimitOOG, "MemoryLimitOOG"),
+            (InstructionResult::PrecompileOOG, "PrecompileOOG"),
+            (InstructionResult::InvalidOperandOOG, "InvalidOperandOOG"),
+            (InstructionResult::OpcodeNotFound, "OpcodeNotFound"),
+            (
+                InstructionResult::CallNotAllowedInsideStatic,
+                "CallNotAllowedInsideStatic",
+            ),
+            (
+                InstructionResult::StateChangeDuringStaticCall,
+                "StateChangeDuringStaticCall",
+            ),
+            (InstructionResult::InvalidEFOpcode, "InvalidEFOpcode"),
+            (InstructionResult::InvalidJump, "InvalidJump"),
+            (InstructionResult::NotActivated, "NotActivated"),
+            (InstructionResult::StackUnderflow, "StackUnderflow"),
+            (InstructionResult::StackOverflow, "StackOverflow"),
+            (InstructionResult::OutOfOffset, "OutOfOffset"),
+            (InstructionResult::CreateCollision, "CreateCollision"),
+            (InstructionResult::OverflowPayment, "OverflowPayment"),
+            (InstructionResult::PrecompileError, "PrecompileError"),
+            (InstructionResult::NonceOverflow, "NonceOverflow"),
+            (
+                InstructionResult::CreateContractSizeLimit,
+                "CreateContractSizeLimit",
+            ),
+            (
+                InstructionResult::CreateContractStartingWithEF,
+                "CreateContractStartingWithEF",
+            ),
+            (
+                InstructionResult::CreateInitCodeSizeLimit,
+                "CreateInitCodeSizeLimit",
+            ),
+            (InstructionResult::FatalExternalError, "FatalExternalError"),
+            (
+                InstructionResult::ReturnContractInNotInitEOF,
+                "ReturnContractInNotInitEOF",
+            ),
+            (
+                InstructionResult::EOFOpcodeDisabledInLegacy,
+                "EOFOpcodeDisabledInLegacy",
+            ),
+            (
+                InstructionResult::EOFFunctionStackOverflow,
+                "EOFFunctionStackOverflow",
+            ),
+            (InstructionResult::EofAuxDataOverflow, "EofAuxDataOverflow"),
+            (InstructionResult::EofAuxDataTooSmall, "EofAuxDataTooSmall"),
+            (
+                InstructionResult::InvalidEXTCALLTarget,
+                "InvalidEXTCALLTarget",
+            ),
+        ];
+        for (result, id) in results {
+            assert_eq!(result.as_str(), id);
+            assert_eq!(id.parse(), Ok(result));
+            #[cfg(feature = "serde")]
+            assert_eq!(
+                serde_json::to_value(result).unwrap(),
+                serde_json::Value::String(id.into())
+            );
+        }
+        assert_eq!("stop".parse::<InstructionResult>(), Err(UnknownIdentifier));
+    }
 }
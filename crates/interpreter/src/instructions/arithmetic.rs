@@ -2,28 +2,28 @@ use super::i256::{i256_div, i256_mod};
 use crate::{
     gas,
     primitives::{Spec, U256},
-    Host, Interpreter,
+    HostRead, Interpreter,
 };
 
-pub fn add<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn add<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::VERYLOW);
     pop_top!(interpreter, op1, op2);
     *op2 = op1.wrapping_add(*op2);
 }
 
-pub fn mul<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn mul<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::LOW);
     pop_top!(interpreter, op1, op2);
     *op2 = op1.wrapping_mul(*op2);
 }
 
-pub fn sub<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn sub<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::VERYLOW);
     pop_top!(interpreter, op1, op2);
     *op2 = op1.wrapping_sub(*op2);
 }
 
-pub fn div<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn div<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::LOW);
     pop_top!(interpreter, op1, op2);
     if *op2 != U256::ZERO {
@@ -31,13 +31,13 @@ pub fn div<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     }
 }
 
-pub fn sdiv<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn sdiv<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::LOW);
     pop_top!(interpreter, op1, op2);
     *op2 = i256_div(op1, *op2);
 }
 
-pub fn rem<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn rem<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::LOW);
     pop_top!(interpreter, op1, op2);
     if *op2 != U256::ZERO {
@@ -45,27 +45,30 @@ pub fn rem<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     }
 }
 
-pub fn smod<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn smod<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::LOW);
     pop_top!(interpreter, op1, op2);
     *op2 = i256_mod(op1, *op2)
 }
 
-pub fn addmod<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn addmod<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::MID);
     pop_top!(interpreter, op1, op2, op3);
     *op3 = op1.add_mod(op2, *op3)
 }
 
-pub fn mulmod<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn mulmod<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::MID);
     pop_top!(interpreter, op1, op2, op3);
     *op3 = op1.mul_mod(op2, *op3)
 }
 
-pub fn exp<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn exp<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host: &mut H) {
     pop_top!(interpreter, op1, op2);
-    gas_or_fail!(interpreter, gas::exp_cost(SPEC::SPEC_ID, *op2));
+    gas_or_fail!(
+        interpreter,
+        gas::exp_cost(&interpreter.gas_overrides, SPEC::SPEC_ID, *op2)
+    );
     *op2 = op1.pow(*op2);
 }
 
@@ -84,7 +87,7 @@ pub fn exp<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host: &
 /// `y | !mask` where `|` is the bitwise `OR` and `!` is bitwise negation. Similarly, if
 /// `b == 0` then the yellow paper says the output should start with all zeros, then end with
 /// bits from `b`; this is equal to `y & mask` where `&` is bitwise `AND`.
-pub fn signextend<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn signextend<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::LOW);
     pop_top!(interpreter, ext, x);
     // For 31 we also don't need to do anything.
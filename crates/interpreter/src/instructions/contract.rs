@@ -8,8 +8,8 @@ use crate::{
     primitives::{
         eof::EofHeader, keccak256, Address, BerlinSpec, Bytes, Eof, Spec, SpecId::*, B256, U256,
     },
-    CallInputs, CallScheme, CallValue, CreateInputs, CreateScheme, EOFCreateInputs, Host,
-    InstructionResult, InterpreterAction, InterpreterResult, LoadAccountResult, MAX_INITCODE_SIZE,
+    CallInput, CallInputs, CallScheme, CallValue, CreateInputs, CreateScheme, EOFCreateInputs,
+    Host, InstructionResult, InterpreterAction, InterpreterResult, LoadAccountResult,
 };
 use core::cmp::max;
 use std::boxed::Box;
@@ -81,6 +81,69 @@ pub fn eofcreate<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H)
     interpreter.instruction_pointer = unsafe { interpreter.instruction_pointer.offset(1) };
 }
 
+/// Experimental TXCREATE instruction, creates a contract from an initcontainer of the
+/// initcode transaction, see [`TxEnv::initcodes`](crate::primitives::TxEnv::initcodes).
+///
+/// Initcontainer is referenced by its keccak256 hash, zero is pushed if it is not found.
+#[cfg(feature = "eof-txcreate")]
+pub fn txcreate<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
+    require_eof!(interpreter);
+    require_non_staticcall!(interpreter);
+    gas!(interpreter, EOF_CREATE_GAS);
+    pop!(interpreter, hash, value, salt, data_offset, data_size);
+
+    // resize memory and get return range.
+    let Some(input_range) = resize_memory(interpreter, data_offset, data_size) else {
+        return;
+    };
+
+    let initcode_hash = B256::from(hash);
+    let Some(initcode_len) = host
+        .initcode_by_hash(initcode_hash)
+        .map(|initcode| initcode.len())
+    else {
+        // missing initcode is a failure of the creation, not of the instruction.
+        push!(interpreter, U256::ZERO);
+        return;
+    };
+
+    // deduct gas for hash of the initcontainer.
+    gas_or_fail!(
+        interpreter,
+        cost_per_word(initcode_len as u64, KECCAK256WORD)
+    );
+
+    let input = if !input_range.is_empty() {
+        interpreter
+            .shared_memory
+            .slice_range(input_range)
+            .to_vec()
+            .into()
+    } else {
+        Bytes::new()
+    };
+
+    let created_address = interpreter
+        .contract
+        .target_address
+        .create2(salt.to_be_bytes(), initcode_hash);
+
+    let gas_limit = interpreter.gas().remaining_63_of_64_parts();
+    gas!(interpreter, gas_limit);
+    // Send initcode hash for execution, initcontainer is validated with the transaction.
+    interpreter.instruction_result = InstructionResult::CallOrCreate;
+    interpreter.next_action = InterpreterAction::EOFCreate {
+        inputs: Box::new(EOFCreateInputs::new_tx_initcode(
+            interpreter.contract.target_address,
+            created_address,
+            value,
+            initcode_hash,
+            gas_limit,
+            input,
+        )),
+    };
+}
+
 pub fn return_contract<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     require_init_eof!(interpreter);
     let deploy_container_index = unsafe { *interpreter.instruction_pointer };
@@ -143,20 +206,11 @@ pub fn return_contract<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &
     };
 }
 
-pub fn extcall_input(interpreter: &mut Interpreter) -> Option<Bytes> {
+pub fn extcall_input(interpreter: &mut Interpreter) -> Option<CallInput> {
     pop_ret!(interpreter, input_offset, input_size, None);
 
-    let return_memory_offset = resize_memory(interpreter, input_offset, input_size)?;
-
-    if return_memory_offset.is_empty() {
-        return Some(Bytes::new());
-    }
-
-    Some(Bytes::copy_from_slice(
-        interpreter
-            .shared_memory
-            .slice_range(return_memory_offset.clone()),
-    ))
+    let input_range = resize_memory(interpreter, input_offset, input_size)?;
+    Some(CallInput::SharedBuffer(input_range))
 }
 
 pub fn extcall_gas_calc<H: Host + ?Sized>(
@@ -172,6 +226,7 @@ pub fn extcall_gas_calc<H: Host + ?Sized>(
 
     // TODO(EOF) is_empty should only be checked on delegatecall
     let call_cost = gas::call_cost(
+        &interpreter.gas_overrides,
         BerlinSpec::SPEC_ID,
         transfers_value,
         load_result.is_cold,
@@ -244,6 +299,7 @@ pub fn extcall<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host
             scheme: CallScheme::ExtCall,
             is_static: interpreter.is_static,
             is_eof: true,
+            dry_run: false,
             return_memory_offset: 0..0,
         }),
     };
@@ -280,6 +336,7 @@ pub fn extdelegatecall<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpret
             scheme: CallScheme::ExtDelegateCall,
             is_static: interpreter.is_static,
             is_eof: true,
+            dry_run: false,
             return_memory_offset: 0..0,
         }),
     };
@@ -315,6 +372,7 @@ pub fn extstaticcall<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut
             scheme: CallScheme::ExtStaticCall,
             is_static: true,
             is_eof: true,
+            dry_run: false,
             return_memory_offset: 0..0,
         }),
     };
@@ -340,13 +398,7 @@ pub fn create<const IS_CREATE2: bool, H: Host + ?Sized, SPEC: Spec>(
         // EIP-3860: Limit and meter initcode
         if SPEC::enabled(SHANGHAI) {
             // Limit is set as double of max contract bytecode size
-            let max_initcode_size = host
-                .env()
-                .cfg
-                .limit_contract_code_size
-                .map(|limit| limit.saturating_mul(2))
-                .unwrap_or(MAX_INITCODE_SIZE);
-            if len > max_initcode_size {
+            if len > host.env().cfg.max_initcode_size() {
                 interpreter.instruction_result = InstructionResult::CreateInitCodeSizeLimit;
                 return;
             }
@@ -362,10 +414,16 @@ pub fn create<const IS_CREATE2: bool, H: Host + ?Sized, SPEC: Spec>(
     let scheme = if IS_CREATE2 {
         pop!(interpreter, salt);
         // SAFETY: len is reasonable in size as gas for it is already deducted.
-        gas_or_fail!(interpreter, gas::create2_cost(len.try_into().unwrap()));
+        gas_or_fail!(
+            interpreter,
+            gas::create2_cost(&interpreter.gas_overrides, len.try_into().unwrap())
+        );
         CreateScheme::Create2 { salt }
     } else {
-        gas!(interpreter, gas::CREATE);
+        gas!(
+            interpreter,
+            interpreter.gas_overrides.create.unwrap_or(gas::CREATE)
+        );
         CreateScheme::Create
     };
 
@@ -408,15 +466,15 @@ pub fn call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &
         return;
     };
 
-    let Some(LoadAccountResult { is_cold, is_empty }) = host.load_account(to) else {
+    let Some(load_result) = host.load_account(to) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
-    let Some(mut gas_limit) = calc_call_gas::<SPEC>(
+    let Some(gas_limit) = calc_call_gas::<SPEC>(
         interpreter,
-        is_cold,
+        load_result.is_cold,
         has_transfer,
-        is_empty,
+        load_result.is_new_account(SPEC::SPEC_ID),
         local_gas_limit,
     ) else {
         return;
@@ -425,9 +483,7 @@ pub fn call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &
     gas!(interpreter, gas_limit);
 
     // add call stipend if there is value to be transferred.
-    if has_transfer {
-        gas_limit = gas_limit.saturating_add(gas::CALL_STIPEND);
-    }
+    let gas_limit = gas_limit.saturating_add(gas::call_stipend(has_transfer));
 
     // Call host to interact with target contract
     interpreter.next_action = InterpreterAction::Call {
@@ -441,6 +497,7 @@ pub fn call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &
             scheme: CallScheme::Call,
             is_static: interpreter.is_static,
             is_eof: false,
+            dry_run: false,
             return_memory_offset,
         }),
     };
@@ -463,22 +520,17 @@ pub fn call_code<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, ho
         return;
     };
 
-    let Some(mut gas_limit) = calc_call_gas::<SPEC>(
-        interpreter,
-        is_cold,
-        value != U256::ZERO,
-        false,
-        local_gas_limit,
-    ) else {
+    let has_transfer = value != U256::ZERO;
+    let Some(gas_limit) =
+        calc_call_gas::<SPEC>(interpreter, is_cold, has_transfer, false, local_gas_limit)
+    else {
         return;
     };
 
     gas!(interpreter, gas_limit);
 
     // add call stipend if there is value to be transferred.
-    if value != U256::ZERO {
-        gas_limit = gas_limit.saturating_add(gas::CALL_STIPEND);
-    }
+    let gas_limit = gas_limit.saturating_add(gas::call_stipend(has_transfer));
 
     // Call host to interact with target contract
     interpreter.next_action = InterpreterAction::Call {
@@ -492,6 +544,7 @@ pub fn call_code<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, ho
             scheme: CallScheme::CallCode,
             is_static: interpreter.is_static,
             is_eof: false,
+            dry_run: false,
             return_memory_offset,
         }),
     };
@@ -533,6 +586,7 @@ pub fn delegate_call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter
             scheme: CallScheme::DelegateCall,
             is_static: interpreter.is_static,
             is_eof: false,
+            dry_run: false,
             return_memory_offset,
         }),
     };
@@ -574,6 +628,7 @@ pub fn static_call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
             scheme: CallScheme::StaticCall,
             is_static: true,
             is_eof: false,
+            dry_run: false,
             return_memory_offset,
         }),
     };
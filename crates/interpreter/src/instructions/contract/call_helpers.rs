@@ -1,22 +1,19 @@
 use crate::{
     gas,
     interpreter::Interpreter,
-    primitives::{Bytes, Spec, SpecId::*, U256},
+    primitives::{Spec, SpecId::*, U256},
+    CallInput,
 };
 use core::{cmp::min, ops::Range};
 
 #[inline]
 pub fn get_memory_input_and_out_ranges(
     interpreter: &mut Interpreter,
-) -> Option<(Bytes, Range<usize>)> {
+) -> Option<(CallInput, Range<usize>)> {
     pop_ret!(interpreter, in_offset, in_len, out_offset, out_len, None);
 
-    let in_range = resize_memory(interpreter, in_offset, in_len)?;
-
-    let mut input = Bytes::new();
-    if !in_range.is_empty() {
-        input = Bytes::copy_from_slice(interpreter.shared_memory.slice_range(in_range));
-    }
+    // input is read from the memory when the call frame is made.
+    let input = CallInput::SharedBuffer(resize_memory(interpreter, in_offset, in_len)?);
 
     let ret_range = resize_memory(interpreter, out_offset, out_len)?;
     Some((input, ret_range))
@@ -49,7 +46,13 @@ pub fn calc_call_gas<SPEC: Spec>(
     new_account_accounting: bool,
     local_gas_limit: u64,
 ) -> Option<u64> {
-    let call_cost = gas::call_cost(SPEC::SPEC_ID, has_transfer, is_cold, new_account_accounting);
+    let call_cost = gas::call_cost(
+        &interpreter.gas_overrides,
+        SPEC::SPEC_ID,
+        has_transfer,
+        is_cold,
+        new_account_accounting,
+    );
 
     gas!(interpreter, call_cost, None);
 
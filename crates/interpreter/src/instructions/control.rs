@@ -2,10 +2,10 @@ use super::utility::{read_i16, read_u16};
 use crate::{
     gas,
     primitives::{Bytes, Spec, U256},
-    Host, InstructionResult, Interpreter, InterpreterResult,
+    HostRead, InstructionResult, Interpreter, InterpreterResult,
 };
 
-pub fn rjump<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn rjump<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     require_eof!(interpreter);
     gas!(interpreter, gas::BASE);
     let offset = unsafe { read_i16(interpreter.instruction_pointer) } as isize;
@@ -14,7 +14,7 @@ pub fn rjump<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     interpreter.instruction_pointer = unsafe { interpreter.instruction_pointer.offset(offset + 2) };
 }
 
-pub fn rjumpi<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn rjumpi<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     require_eof!(interpreter);
     gas!(interpreter, gas::CONDITION_JUMP_GAS);
     pop!(interpreter, condition);
@@ -28,7 +28,7 @@ pub fn rjumpi<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     interpreter.instruction_pointer = unsafe { interpreter.instruction_pointer.offset(offset) };
 }
 
-pub fn rjumpv<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn rjumpv<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     require_eof!(interpreter);
     gas!(interpreter, gas::CONDITION_JUMP_GAS);
     pop!(interpreter, case);
@@ -53,13 +53,13 @@ pub fn rjumpv<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     interpreter.instruction_pointer = unsafe { interpreter.instruction_pointer.offset(offset) };
 }
 
-pub fn jump<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn jump<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::MID);
     pop!(interpreter, target);
     jump_inner(interpreter, target);
 }
 
-pub fn jumpi<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn jumpi<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::HIGH);
     pop!(interpreter, target, cond);
     if cond != U256::ZERO {
@@ -70,6 +70,8 @@ pub fn jumpi<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
 #[inline]
 fn jump_inner(interpreter: &mut Interpreter, target: U256) {
     let target = as_usize_or_fail!(interpreter, target, InstructionResult::InvalidJump);
+    // lazily analyzed bytecode finds its jump destinations on the first jump.
+    interpreter.contract.analyze_pending();
     if !interpreter.contract.is_valid_jump(target) {
         interpreter.instruction_result = InstructionResult::InvalidJump;
         return;
@@ -78,11 +80,11 @@ fn jump_inner(interpreter: &mut Interpreter, target: U256) {
     interpreter.instruction_pointer = unsafe { interpreter.bytecode.as_ptr().add(target) };
 }
 
-pub fn jumpdest_or_nop<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn jumpdest_or_nop<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::JUMPDEST);
 }
 
-pub fn callf<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn callf<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     require_eof!(interpreter);
     gas!(interpreter, gas::LOW);
 
@@ -114,7 +116,7 @@ pub fn callf<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     interpreter.load_eof_code(idx, 0)
 }
 
-pub fn retf<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn retf<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     require_eof!(interpreter);
     gas!(interpreter, gas::RETF_GAS);
 
@@ -125,7 +127,7 @@ pub fn retf<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     interpreter.load_eof_code(fframe.idx, fframe.pc);
 }
 
-pub fn jumpf<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn jumpf<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     require_eof!(interpreter);
     gas!(interpreter, gas::LOW);
 
@@ -147,7 +149,7 @@ pub fn jumpf<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     interpreter.load_eof_code(idx, 0)
 }
 
-pub fn pc<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn pc<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::BASE);
     // - 1 because we have already advanced the instruction pointer in `Interpreter::step`
     push!(interpreter, U256::from(interpreter.program_counter() - 1));
@@ -177,28 +179,28 @@ fn return_inner(interpreter: &mut Interpreter, instruction_result: InstructionRe
     };
 }
 
-pub fn ret<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn ret<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     return_inner(interpreter, InstructionResult::Return);
 }
 
 /// EIP-140: REVERT instruction
-pub fn revert<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn revert<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host: &mut H) {
     check!(interpreter, BYZANTIUM);
     return_inner(interpreter, InstructionResult::Revert);
 }
 
 /// Stop opcode. This opcode halts the execution.
-pub fn stop<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn stop<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     interpreter.instruction_result = InstructionResult::Stop;
 }
 
 /// Invalid opcode. This opcode halts the execution.
-pub fn invalid<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn invalid<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     interpreter.instruction_result = InstructionResult::InvalidEFOpcode;
 }
 
 /// Unknown opcode. This opcode halts the execution.
-pub fn unknown<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn unknown<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     interpreter.instruction_result = InstructionResult::OpcodeNotFound;
 }
 
@@ -210,17 +212,20 @@ mod test {
 
     use super::*;
     use crate::{
-        opcode::{make_instruction_table, CALLF, JUMPF, NOP, RETF, RJUMP, RJUMPI, RJUMPV, STOP},
-        DummyHost, FunctionReturnFrame, Gas, Interpreter,
+        analysis::to_analysed,
+        opcode::{
+            asm::assemble, make_instruction_table, CALLF, JUMPF, NOP, RETF, RJUMP, RJUMPV, STOP,
+        },
+        primitives::Address,
+        Contract, DummyHost, FunctionReturnFrame, Gas, Interpreter,
     };
 
     #[test]
     fn rjump() {
         let table = make_instruction_table::<_, PragueSpec>();
         let mut host = DummyHost::default();
-        let mut interp = Interpreter::new_bytecode(Bytecode::LegacyRaw(Bytes::from([
-            RJUMP, 0x00, 0x02, STOP, STOP,
-        ])));
+        let code = assemble("RJUMP 2 STOP STOP").unwrap();
+        let mut interp = Interpreter::new_bytecode(Bytecode::LegacyRaw(code));
         interp.is_eof = true;
         interp.gas = Gas::new(10000);
 
@@ -228,13 +233,58 @@ mod test {
         assert_eq!(interp.program_counter(), 5);
     }
 
+    #[test]
+    fn jump_analysis_is_lazy() {
+        let table = make_instruction_table::<_, PragueSpec>();
+        let mut host = DummyHost::default();
+        let lazy = |code: &str| {
+            let contract = Contract::new_lazy(
+                Bytes::new(),
+                Bytecode::new_raw(assemble(code).unwrap()),
+                None,
+                Address::ZERO,
+                None,
+                Address::ZERO,
+                U256::ZERO,
+            );
+            Interpreter::new(contract, 10_000, false)
+        };
+
+        let mut interp = lazy("PUSH0 PUSH0 STOP");
+        interp.step(&table, &mut host);
+        interp.step(&table, &mut host);
+        assert!(interp.contract.pending_analysis);
+
+        let mut interp = lazy("PUSH1 4 JUMP INVALID JUMPDEST STOP");
+        assert!(interp.contract.pending_analysis);
+        interp.step(&table, &mut host);
+        interp.step(&table, &mut host);
+        assert!(!interp.contract.pending_analysis);
+        assert_eq!(interp.program_counter(), 4);
+        assert_eq!(
+            interp.contract.bytecode,
+            to_analysed(Bytecode::new_raw(
+                assemble("PUSH1 4 JUMP INVALID JUMPDEST STOP").unwrap()
+            ))
+        );
+
+        // PUSH1 hides the JUMPDEST byte.
+        let mut interp = lazy("PUSH1 4 JUMP PUSH1 0x5b");
+        assert!(interp.contract.pending_analysis);
+        interp.contract.analyze_pending();
+        assert!(!interp.contract.pending_analysis);
+        assert!(!interp.contract.is_valid_jump(4));
+        interp.step(&table, &mut host);
+        interp.step(&table, &mut host);
+        assert_eq!(interp.instruction_result, InstructionResult::InvalidJump);
+    }
+
     #[test]
     fn rjumpi() {
         let table = make_instruction_table::<_, PragueSpec>();
         let mut host = DummyHost::default();
-        let mut interp = Interpreter::new_bytecode(Bytecode::LegacyRaw(Bytes::from([
-            RJUMPI, 0x00, 0x03, RJUMPI, 0x00, 0x01, STOP, STOP,
-        ])));
+        let code = assemble("RJUMPI 3 RJUMPI 1 STOP STOP").unwrap();
+        let mut interp = Interpreter::new_bytecode(Bytecode::LegacyRaw(code));
         interp.is_eof = true;
         interp.stack.push(U256::from(1)).unwrap();
         interp.stack.push(U256::from(0)).unwrap();
@@ -1,13 +1,12 @@
 use crate::{
     gas::{self, warm_cold_cost},
     interpreter::Interpreter,
-    primitives::{Bytes, Log, LogData, Spec, SpecId::*, B256, U256},
-    Host, InstructionResult, SStoreResult,
+    primitives::{Spec, SpecId::*, B256, U256},
+    Host, HostRead, InstructionResult, SStoreResult,
 };
 use core::cmp::min;
-use std::vec::Vec;
 
-pub fn balance<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn balance<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     pop_address!(interpreter, address);
     let Some((balance, is_cold)) = host.balance(address) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
@@ -16,7 +15,7 @@ pub fn balance<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host
     gas!(
         interpreter,
         if SPEC::enabled(BERLIN) {
-            warm_cold_cost(is_cold)
+            warm_cold_cost(&interpreter.gas_overrides, is_cold)
         } else if SPEC::enabled(ISTANBUL) {
             // EIP-1884: Repricing for trie-size-dependent opcodes
             700
@@ -30,7 +29,7 @@ pub fn balance<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host
 }
 
 /// EIP-1884: Repricing for trie-size-dependent opcodes
-pub fn selfbalance<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn selfbalance<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     check!(interpreter, ISTANBUL);
     gas!(interpreter, gas::LOW);
     let Some((balance, _)) = host.balance(interpreter.contract.target_address) else {
@@ -40,14 +39,17 @@ pub fn selfbalance<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
     push!(interpreter, balance);
 }
 
-pub fn extcodesize<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn extcodesize<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     pop_address!(interpreter, address);
     let Some((code, is_cold)) = host.code(address) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
     if SPEC::enabled(BERLIN) {
-        gas!(interpreter, warm_cold_cost(is_cold));
+        gas!(
+            interpreter,
+            warm_cold_cost(&interpreter.gas_overrides, is_cold)
+        );
     } else if SPEC::enabled(TANGERINE) {
         gas!(interpreter, 700);
     } else {
@@ -58,7 +60,7 @@ pub fn extcodesize<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
 }
 
 /// EIP-1052: EXTCODEHASH opcode
-pub fn extcodehash<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn extcodehash<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     check!(interpreter, CONSTANTINOPLE);
     pop_address!(interpreter, address);
     let Some((code_hash, is_cold)) = host.code_hash(address) else {
@@ -66,7 +68,10 @@ pub fn extcodehash<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
         return;
     };
     if SPEC::enabled(BERLIN) {
-        gas!(interpreter, warm_cold_cost(is_cold));
+        gas!(
+            interpreter,
+            warm_cold_cost(&interpreter.gas_overrides, is_cold)
+        );
     } else if SPEC::enabled(ISTANBUL) {
         gas!(interpreter, 700);
     } else {
@@ -75,7 +80,7 @@ pub fn extcodehash<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
     push_b256!(interpreter, code_hash);
 }
 
-pub fn extcodecopy<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn extcodecopy<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     pop_address!(interpreter, address);
     pop!(interpreter, memory_offset, code_offset, len_u256);
 
@@ -87,7 +92,12 @@ pub fn extcodecopy<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
     let len = as_usize_or_fail!(interpreter, len_u256);
     gas_or_fail!(
         interpreter,
-        gas::extcodecopy_cost(SPEC::SPEC_ID, len as u64, is_cold)
+        gas::extcodecopy_cost(
+            &interpreter.gas_overrides,
+            SPEC::SPEC_ID,
+            len as u64,
+            is_cold
+        )
     );
     if len == 0 {
         return;
@@ -102,7 +112,7 @@ pub fn extcodecopy<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
         .set_data(memory_offset, code_offset, len, &code);
 }
 
-pub fn blockhash<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn blockhash<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     gas!(interpreter, gas::BLOCKHASH);
     pop_top!(interpreter, number);
 
@@ -114,13 +124,16 @@ pub fn blockhash<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, ho
     *number = U256::from_be_bytes(hash.0);
 }
 
-pub fn sload<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn sload<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     pop_top!(interpreter, index);
     let Some((value, is_cold)) = host.sload(interpreter.contract.target_address, *index) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
-    gas!(interpreter, gas::sload_cost(SPEC::SPEC_ID, is_cold));
+    gas!(
+        interpreter,
+        gas::sload_cost(&interpreter.gas_overrides, SPEC::SPEC_ID, is_cold)
+    );
     *index = value;
 }
 
@@ -138,14 +151,21 @@ pub fn sstore<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host:
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
-    gas_or_fail!(interpreter, {
-        let remaining_gas = interpreter.gas.remaining();
-        gas::sstore_cost(SPEC::SPEC_ID, original, old, new, remaining_gas, is_cold)
-    });
-    refund!(
-        interpreter,
-        gas::sstore_refund(SPEC::SPEC_ID, original, old, new)
+    // EIP-1706 Disable SSTORE with gasleft lower than call stipend
+    if SPEC::enabled(ISTANBUL) && interpreter.gas.remaining() <= gas::CALL_STIPEND {
+        interpreter.instruction_result = InstructionResult::OutOfGas;
+        return;
+    }
+    let (gas_cost, refund) = gas::sstore_cost_and_refund(
+        &interpreter.gas_overrides,
+        SPEC::SPEC_ID,
+        original,
+        old,
+        new,
+        is_cold,
     );
+    gas!(interpreter, gas_cost);
+    refund!(interpreter, refund);
 }
 
 /// EIP-1153: Transient storage opcodes
@@ -162,7 +182,7 @@ pub fn tstore<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host:
 
 /// EIP-1153: Transient storage opcodes
 /// Load value from transient storage
-pub fn tload<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn tload<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     check!(interpreter, CANCUN);
     gas!(interpreter, gas::WARM_STORAGE_READ_COST);
 
@@ -176,13 +196,16 @@ pub fn log<const N: usize, H: Host + ?Sized>(interpreter: &mut Interpreter, host
 
     pop!(interpreter, offset, len);
     let len = as_usize_or_fail!(interpreter, len);
-    gas_or_fail!(interpreter, gas::log_cost(N as u8, len as u64));
-    let data = if len == 0 {
-        Bytes::new()
+    gas_or_fail!(
+        interpreter,
+        gas::log_cost(&interpreter.gas_overrides, N as u8, len as u64)
+    );
+    let offset = if len == 0 {
+        0
     } else {
         let offset = as_usize_or_fail!(interpreter, offset);
         resize_memory!(interpreter, offset, len);
-        Bytes::copy_from_slice(interpreter.shared_memory.slice(offset, len))
+        offset
     };
 
     if interpreter.stack.len() < N {
@@ -190,18 +213,19 @@ pub fn log<const N: usize, H: Host + ?Sized>(interpreter: &mut Interpreter, host
         return;
     }
 
-    let mut topics = Vec::with_capacity(N);
-    for _ in 0..N {
+    // Topics are collected on the stack, the host copies them with the data into its buffers.
+    let mut topics = [B256::ZERO; 4];
+    for topic in &mut topics[..N] {
         // SAFETY: stack bounds already checked few lines above
-        topics.push(B256::from(unsafe { interpreter.stack.pop_unsafe() }));
+        *topic = B256::from(unsafe { interpreter.stack.pop_unsafe() });
     }
-
-    let log = Log {
-        address: interpreter.contract.target_address,
-        data: LogData::new(topics, data).expect("LogData should have <=4 topics"),
+    let data = if len == 0 {
+        &[][..]
+    } else {
+        interpreter.shared_memory.slice(offset, len)
     };
 
-    host.log(log);
+    host.log_raw(interpreter.contract.target_address, &topics[..N], data);
 }
 
 pub fn selfdestruct<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
@@ -217,7 +241,10 @@ pub fn selfdestruct<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
     if !SPEC::enabled(LONDON) && !res.previously_destroyed {
         refund!(interpreter, gas::SELFDESTRUCT)
     }
-    gas!(interpreter, gas::selfdestruct_cost(SPEC::SPEC_ID, res));
+    gas!(
+        interpreter,
+        gas::selfdestruct_cost(&interpreter.gas_overrides, SPEC::SPEC_ID, res)
+    );
 
     interpreter.instruction_result = InstructionResult::SelfDestruct;
 }
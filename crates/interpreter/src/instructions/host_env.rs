@@ -1,32 +1,46 @@
 use crate::{
     gas,
     primitives::{Spec, SpecId::*, U256},
-    Host, Interpreter,
+    HostRead, Interpreter,
 };
 
 /// EIP-1344: ChainID opcode
-pub fn chainid<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn chainid<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     check!(interpreter, ISTANBUL);
     gas!(interpreter, gas::BASE);
-    push!(interpreter, U256::from(host.env().cfg.chain_id));
+    let env = host.env();
+    let chain_id = env
+        .cfg
+        .opcode_overrides
+        .as_ref()
+        .and_then(|o| o.chain_id)
+        .unwrap_or(env.cfg.chain_id);
+    push!(interpreter, U256::from(chain_id));
 }
 
-pub fn coinbase<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn coinbase<H: HostRead + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
     gas!(interpreter, gas::BASE);
-    push_b256!(interpreter, host.env().block.coinbase.into_word());
+    let env = host.env();
+    let coinbase = env
+        .cfg
+        .opcode_overrides
+        .as_ref()
+        .and_then(|o| o.coinbase)
+        .unwrap_or(env.block.coinbase);
+    push_b256!(interpreter, coinbase.into_word());
 }
 
-pub fn timestamp<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn timestamp<H: HostRead + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
     gas!(interpreter, gas::BASE);
     push!(interpreter, host.env().block.timestamp);
 }
 
-pub fn block_number<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn block_number<H: HostRead + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
     gas!(interpreter, gas::BASE);
     push!(interpreter, host.env().block.number);
 }
 
-pub fn difficulty<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn difficulty<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     gas!(interpreter, gas::BASE);
     if SPEC::enabled(MERGE) {
         push_b256!(interpreter, host.env().block.prevrandao.unwrap());
@@ -35,42 +49,56 @@ pub fn difficulty<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, h
     }
 }
 
-pub fn gaslimit<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn gaslimit<H: HostRead + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
     gas!(interpreter, gas::BASE);
     push!(interpreter, host.env().block.gas_limit);
 }
 
-pub fn gasprice<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn gasprice<H: HostRead + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
     gas!(interpreter, gas::BASE);
-    push!(interpreter, host.env().effective_gas_price());
+    let env = host.env();
+    let gas_price = env
+        .cfg
+        .opcode_overrides
+        .as_ref()
+        .and_then(|o| o.gas_price)
+        .unwrap_or_else(|| env.effective_gas_price());
+    push!(interpreter, gas_price);
 }
 
 /// EIP-3198: BASEFEE opcode
-pub fn basefee<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn basefee<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     check!(interpreter, LONDON);
     gas!(interpreter, gas::BASE);
     push!(interpreter, host.env().block.basefee);
 }
 
-pub fn origin<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn origin<H: HostRead + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
     gas!(interpreter, gas::BASE);
-    push_b256!(interpreter, host.env().tx.caller.into_word());
+    let env = host.env();
+    let origin = env
+        .cfg
+        .opcode_overrides
+        .as_ref()
+        .and_then(|o| o.origin)
+        .unwrap_or(env.tx().caller);
+    push_b256!(interpreter, origin.into_word());
 }
 
 // EIP-4844: Shard Blob Transactions
-pub fn blob_hash<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn blob_hash<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     check!(interpreter, CANCUN);
     gas!(interpreter, gas::VERYLOW);
     pop_top!(interpreter, index);
     let i = as_usize_saturated!(index);
-    *index = match host.env().tx.blob_hashes.get(i) {
+    *index = match host.env().tx().blob_hashes.get(i) {
         Some(hash) => U256::from_be_bytes(hash.0),
         None => U256::ZERO,
     };
 }
 
 /// EIP-7516: BLOBBASEFEE opcode
-pub fn blob_basefee<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn blob_basefee<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     check!(interpreter, CANCUN);
     gas!(interpreter, gas::BASE);
     push!(
@@ -78,3 +106,49 @@ pub fn blob_basefee<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
         U256::from(host.env().block.get_blob_gasprice().unwrap_or_default())
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Contract, DummyHost};
+    use revm_primitives::{Address, Env, LatestSpec, OpcodeEnvOverrides};
+
+    #[test]
+    fn opcode_overrides() {
+        let fake_coinbase = Address::with_last_byte(0x05);
+        let fake_origin = Address::with_last_byte(0x06);
+        let mut env = Env::default();
+        env.block.coinbase = Address::with_last_byte(0x04);
+        env.tx_mut().caller = Address::with_last_byte(0x01);
+        env.tx_mut().gas_price = U256::from(10);
+        let mut host = DummyHost::new(env);
+        let mut interpreter = Interpreter::new(Contract::default(), u64::MAX, false);
+
+        // the block and transaction are read without overrides.
+        coinbase(&mut interpreter, &mut host);
+        origin(&mut interpreter, &mut host);
+        gasprice(&mut interpreter, &mut host);
+        chainid::<DummyHost, LatestSpec>(&mut interpreter, &mut host);
+        pop!(interpreter, chain_id, gas_price, origin_word, coinbase_word);
+        assert_eq!(chain_id, U256::from(1));
+        assert_eq!(gas_price, U256::from(10));
+        assert_eq!(origin_word, U256::from(1));
+        assert_eq!(coinbase_word, U256::from(4));
+
+        host.env.cfg.opcode_overrides = Some(OpcodeEnvOverrides {
+            coinbase: Some(fake_coinbase),
+            origin: Some(fake_origin),
+            gas_price: Some(U256::from(7)),
+            chain_id: Some(1234),
+        });
+        coinbase(&mut interpreter, &mut host);
+        origin(&mut interpreter, &mut host);
+        gasprice(&mut interpreter, &mut host);
+        chainid::<DummyHost, LatestSpec>(&mut interpreter, &mut host);
+        pop!(interpreter, chain_id, gas_price, origin_word, coinbase_word);
+        assert_eq!(chain_id, U256::from(1234));
+        assert_eq!(gas_price, U256::from(7));
+        assert_eq!(origin_word, U256::from(6));
+        assert_eq!(coinbase_word, U256::from(5));
+    }
+}
@@ -41,7 +41,7 @@ macro_rules! check {
             !<SPEC as $crate::primitives::Spec>::SPEC_ID
                 .is_enabled_in($crate::primitives::SpecId::$min)
         } {
-            $interp.instruction_result = $crate::InstructionResult::NotActivated;
+            $interp.reject_not_activated();
             return;
         }
     };
@@ -103,6 +103,7 @@ macro_rules! resize_memory {
             if !$crate::interpreter::resize_memory(
                 &mut $interp.shared_memory,
                 &mut $interp.gas,
+                &$interp.gas_overrides,
                 new_size,
             ) {
                 $interp.instruction_result = $crate::InstructionResult::MemoryOOG;
@@ -1,11 +1,11 @@
 use crate::{
     gas,
     primitives::{Spec, U256},
-    Host, Interpreter,
+    HostRead, Interpreter,
 };
 use core::cmp::max;
 
-pub fn mload<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn mload<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::VERYLOW);
     pop_top!(interpreter, top);
     let offset = as_usize_or_fail!(interpreter, top);
@@ -13,7 +13,7 @@ pub fn mload<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     *top = interpreter.shared_memory.get_u256(offset);
 }
 
-pub fn mstore<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn mstore<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::VERYLOW);
     pop!(interpreter, offset, value);
     let offset = as_usize_or_fail!(interpreter, offset);
@@ -21,7 +21,7 @@ pub fn mstore<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     interpreter.shared_memory.set_u256(offset, value);
 }
 
-pub fn mstore8<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn mstore8<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::VERYLOW);
     pop!(interpreter, offset, value);
     let offset = as_usize_or_fail!(interpreter, offset);
@@ -29,13 +29,13 @@ pub fn mstore8<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     interpreter.shared_memory.set_byte(offset, value.byte(0))
 }
 
-pub fn msize<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn msize<H: HostRead + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::BASE);
     push!(interpreter, U256::from(interpreter.shared_memory.len()));
 }
 
 // EIP-5656: MCOPY - Memory copying instruction
-pub fn mcopy<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn mcopy<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host: &mut H) {
     check!(interpreter, CANCUN);
     pop!(interpreter, dst, src, len);
 
@@ -54,3 +54,116 @@ pub fn mcopy<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host:
     // copy memory in place
     interpreter.shared_memory.copy(dst, src, len);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::mcopy;
+    use crate::{
+        gas, num_words,
+        opcode::{make_instruction_table, MCOPY, PUSH1, STOP},
+        primitives::{
+            Address, Bytecode, Bytes, CancunSpec, GasScheduleOverrides, ShanghaiSpec, U256,
+        },
+        Contract, DummyHost, InstructionResult, Interpreter, RejectedOpcode, SharedMemory,
+    };
+    use proptest::prelude::*;
+
+    fn memory_gas(num_words: u64) -> u64 {
+        gas::memory_gas(&GasScheduleOverrides::NONE, num_words)
+    }
+
+    /// Runs `MCOPY(dst, src, len)` on memory initialized with `init`.
+    /// Returns the memory and the gas used by the instruction.
+    fn run_mcopy(init: &[u8], dst: usize, src: usize, len: usize) -> (Vec<u8>, u64) {
+        let mut interpreter = Interpreter::new(Contract::default(), u64::MAX, false);
+        interpreter.shared_memory = SharedMemory::new();
+        assert!(interpreter.resize_memory(init.len()));
+        interpreter.shared_memory.set(0, init);
+        for value in [len, src, dst] {
+            interpreter.stack.push(U256::from(value)).unwrap();
+        }
+
+        let gas_before = interpreter.gas.spent();
+        mcopy::<DummyHost, CancunSpec>(&mut interpreter, &mut DummyHost::default());
+        assert_eq!(interpreter.instruction_result, InstructionResult::Continue);
+        (
+            interpreter.shared_memory.context_memory().to_vec(),
+            interpreter.gas.spent() - gas_before,
+        )
+    }
+
+    /// Reference `MCOPY`, memory is expanded to words and copied as `memmove`.
+    fn reference_mcopy(init: &[u8], dst: usize, src: usize, len: usize) -> (Vec<u8>, u64) {
+        let words = |len: usize| num_words(len as u64);
+        let mut memory = init.to_vec();
+        memory.resize(words(init.len()) as usize * 32, 0);
+        let mut gas = gas::verylowcopy_cost(len as u64).unwrap();
+        if len != 0 {
+            let new_len = dst.max(src) + len;
+            if new_len > memory.len() {
+                gas += memory_gas(words(new_len)) - memory_gas(words(memory.len()));
+                memory.resize(words(new_len) as usize * 32, 0);
+            }
+            memory.copy_within(src..src + len, dst);
+        }
+        (memory, gas)
+    }
+
+    proptest! {
+        #[test]
+        fn mcopy_is_memmove(
+            init in proptest::collection::vec(any::<u8>(), 0..256),
+            dst in 0usize..320,
+            src in 0usize..320,
+            len in 0usize..160,
+        ) {
+            prop_assert_eq!(run_mcopy(&init, dst, src, len), reference_mcopy(&init, dst, src, len));
+        }
+    }
+
+    #[test]
+    fn mcopy_gas() {
+        let init = [0x11; 64];
+        // no copy, no expansion.
+        assert_eq!(run_mcopy(&init, 1000, 1000, 0).1, 3);
+        // forward and backward overlap inside memory.
+        let (memory, gas) = run_mcopy(&init, 1, 0, 33);
+        assert_eq!(gas, 3 + 3 * 2);
+        assert_eq!(memory, init);
+        // destination expands memory from 2 to 4 words.
+        let (memory, gas) = run_mcopy(&init, 96, 0, 32);
+        assert_eq!(gas, 3 + 3 + memory_gas(4) - memory_gas(2));
+        assert_eq!(memory.len(), 128);
+        assert_eq!(&memory[96..], &[0x11; 32]);
+        assert_eq!(&memory[64..96], &[0; 32]);
+    }
+
+    #[test]
+    fn mcopy_not_activated() {
+        // MCOPY(0, 0, 0), STOP
+        let code = Bytes::from(vec![PUSH1, 0, PUSH1, 0, PUSH1, 0, MCOPY, STOP]);
+        let contract = Contract::new(
+            Bytes::new(),
+            Bytecode::new_raw(code),
+            None,
+            Address::ZERO,
+            None,
+            Address::ZERO,
+            U256::ZERO,
+        );
+        let mut interpreter = Interpreter::new(contract, u64::MAX, false);
+        let table = make_instruction_table::<DummyHost, ShanghaiSpec>();
+        let _ = interpreter.run(SharedMemory::new(), &table, &mut DummyHost::default());
+        assert_eq!(
+            interpreter.instruction_result,
+            InstructionResult::NotActivated
+        );
+        assert_eq!(
+            interpreter.rejected_opcode,
+            Some(RejectedOpcode {
+                pc: 6,
+                opcode: MCOPY
+            })
+        );
+    }
+}
@@ -1,22 +1,23 @@
 pub mod analysis;
 mod contract;
+mod frame_info;
 #[cfg(feature = "serde")]
 pub mod serde;
 mod shared_memory;
 mod stack;
 
 pub use contract::Contract;
+pub use frame_info::FrameInfo;
 pub use shared_memory::{num_words, SharedMemory, EMPTY_SHARED_MEMORY};
 pub use stack::{Stack, STACK_LIMIT};
 
 use crate::{
     gas, primitives::Bytes, push, push_b256, return_ok, return_revert, CallOutcome, CreateOutcome,
-    FunctionStack, Gas, Host, InstructionResult, InterpreterAction,
+    FunctionStack, Gas, InstructionResult, InterpreterAction, OpCode,
 };
-use core::cmp::min;
-use revm_primitives::{Bytecode, Eof, U256};
-use std::borrow::ToOwned;
-use std::sync::Arc;
+use core::{cmp::min, ops::Range};
+use revm_primitives::{Bytecode, Eof, ExecutionProfile, GasScheduleOverrides, B256, U256};
+use std::{boxed::Box, sync::Arc};
 
 /// EVM bytecode interpreter.
 #[derive(Debug)]
@@ -27,6 +28,8 @@ pub struct Interpreter {
     pub gas: Gas,
     /// Contract information and invoking data
     pub contract: Contract,
+    /// Information about the executed frame, see [`FrameInfo`].
+    pub frame_info: FrameInfo,
     /// The execution control flag. If this is not set to `Continue`, the interpreter will stop
     /// execution.
     pub instruction_result: InstructionResult,
@@ -60,6 +63,24 @@ pub struct Interpreter {
     /// Set inside CALL or CREATE instructions and RETURN or REVERT instructions. Additionally those instructions will set
     /// InstructionResult to CallOrCreate/Return/Revert so we know the reason.
     pub next_action: InterpreterAction,
+    /// Opcode that halted the execution with [`InstructionResult::NotActivated`] because it is
+    /// not enabled in the current spec. Inspectors can read it in `step_end`.
+    pub rejected_opcode: Option<RejectedOpcode>,
+    /// Gas costs that replace the constants of the spec, read by the instructions.
+    ///
+    /// Set from [`CfgEnv::gas_overrides`](revm_primitives::CfgEnv::gas_overrides) when the
+    /// frame is created.
+    pub gas_overrides: GasScheduleOverrides,
+}
+
+/// Opcode rejected by the interpreter, see [`Interpreter::rejected_opcode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct RejectedOpcode {
+    /// Program counter of the opcode.
+    pub pc: usize,
+    /// The rejected opcode.
+    pub opcode: u8,
 }
 
 impl Default for Interpreter {
@@ -76,10 +97,12 @@ impl Interpreter {
         }
         let is_eof = contract.bytecode.is_eof();
         let bytecode = contract.bytecode.bytecode().clone();
+        let frame_info = FrameInfo::new(&contract);
         Self {
             instruction_pointer: bytecode.as_ptr(),
             bytecode,
             contract,
+            frame_info,
             gas: Gas::new(gas_limit),
             instruction_result: InstructionResult::Continue,
             function_stack: FunctionStack::default(),
@@ -90,6 +113,8 @@ impl Interpreter {
             shared_memory: EMPTY_SHARED_MEMORY,
             stack: Stack::new(),
             next_action: InterpreterAction::None,
+            rejected_opcode: None,
+            gas_overrides: GasScheduleOverrides::NONE,
         }
     }
 
@@ -99,6 +124,24 @@ impl Interpreter {
         self.is_eof_init = true;
     }
 
+    /// Returns information about the executed frame.
+    #[inline]
+    pub fn frame_info(&self) -> &FrameInfo {
+        &self.frame_info
+    }
+
+    /// Returns the hash of the executed bytecode, see [`FrameInfo::code_hash`].
+    ///
+    /// The bytecode is hashed on the first call if the hash is not known.
+    #[inline]
+    pub fn frame_code_hash(&mut self) -> B256 {
+        let bytecode = &self.contract.bytecode;
+        *self
+            .frame_info
+            .code_hash
+            .get_or_insert_with(|| bytecode.hash_slow())
+    }
+
     #[inline]
     pub fn eof(&self) -> Option<&Arc<Eof>> {
         self.contract.bytecode.eof()
@@ -133,6 +176,7 @@ impl Interpreter {
         };
         self.bytecode = code.clone();
         self.instruction_pointer = unsafe { self.bytecode.as_ptr().add(pc) };
+        self.frame_info.code_section = Some(idx);
     }
 
     /// Inserts the output of a `create` call into the interpreter.
@@ -151,6 +195,7 @@ impl Interpreter {
     ///
     /// - `Ok`: Pushes the address from `create_outcome` to the stack, updates gas costs, and records any gas refunds.
     /// - `Revert`: Pushes `U256::ZERO` to the stack and updates gas costs.
+    /// - `NonceOverflow`: Pushes `U256::ZERO` to the stack and returns the forwarded gas.
     /// - `FatalExternalError`: Sets the `instruction_result` to `InstructionResult::FatalExternalError`.
     /// - `Default`: Pushes `U256::ZERO` to the stack.
     ///
@@ -163,10 +208,11 @@ impl Interpreter {
     pub fn insert_create_outcome(&mut self, create_outcome: CreateOutcome) {
         self.instruction_result = InstructionResult::Continue;
 
-        let instruction_result = create_outcome.instruction_result();
+        let instruction_result = *create_outcome.instruction_result();
+        let gas = *create_outcome.gas();
         self.return_data_buffer = if instruction_result.is_revert() {
             // Save data to return data buffer if the create reverted
-            create_outcome.output().to_owned()
+            create_outcome.result.output
         } else {
             // Otherwise clear it
             Bytes::new()
@@ -176,12 +222,17 @@ impl Interpreter {
             return_ok!() => {
                 let address = create_outcome.address;
                 push_b256!(self, address.unwrap_or_default().into_word());
-                self.gas.erase_cost(create_outcome.gas().remaining());
-                self.gas.record_refund(create_outcome.gas().refunded());
+                self.gas.erase_cost(gas.remaining());
+                self.gas.record_refund(gas.refunded());
             }
             return_revert!() => {
                 push!(self, U256::ZERO);
-                self.gas.erase_cost(create_outcome.gas().remaining());
+                self.gas.erase_cost(gas.remaining());
+            }
+            InstructionResult::NonceOverflow => {
+                // Creation is aborted before the frame is entered, so the gas is returned.
+                push!(self, U256::ZERO);
+                self.gas.erase_cost(gas.remaining());
             }
             InstructionResult::FatalExternalError => {
                 panic!("Fatal external error in insert_create_outcome");
@@ -194,11 +245,12 @@ impl Interpreter {
 
     pub fn insert_eofcreate_outcome(&mut self, create_outcome: CreateOutcome) {
         self.instruction_result = InstructionResult::Continue;
-        let instruction_result = create_outcome.instruction_result();
+        let instruction_result = *create_outcome.instruction_result();
+        let gas = *create_outcome.gas();
 
-        self.return_data_buffer = if *instruction_result == InstructionResult::Revert {
+        self.return_data_buffer = if instruction_result == InstructionResult::Revert {
             // Save data to return data buffer if the create reverted
-            create_outcome.output().to_owned()
+            create_outcome.result.output
         } else {
             // Otherwise clear it. Note that RETURN opcode should abort.
             Bytes::new()
@@ -210,12 +262,12 @@ impl Interpreter {
                     self,
                     create_outcome.address.expect("EOF Address").into_word()
                 );
-                self.gas.erase_cost(create_outcome.gas().remaining());
-                self.gas.record_refund(create_outcome.gas().refunded());
+                self.gas.erase_cost(gas.remaining());
+                self.gas.record_refund(gas.refunded());
             }
             return_revert!() => {
                 push!(self, U256::ZERO);
-                self.gas.erase_cost(create_outcome.gas().remaining());
+                self.gas.erase_cost(gas.remaining());
             }
             InstructionResult::FatalExternalError => {
                 panic!("Fatal external error in insert_eofcreate_outcome");
@@ -255,19 +307,17 @@ impl Interpreter {
     ) {
         self.instruction_result = InstructionResult::Continue;
 
-        let out_offset = call_outcome.memory_start();
-        let out_len = call_outcome.memory_length();
+        let out_range = call_outcome.memory_offset.clone();
         let out_ins_result = *call_outcome.instruction_result();
         let out_gas = call_outcome.gas();
         self.return_data_buffer = call_outcome.result.output;
 
-        let target_len = min(out_len, self.return_data_buffer.len());
         match out_ins_result {
             return_ok!() => {
                 // return unspend gas.
                 self.gas.erase_cost(out_gas.remaining());
                 self.gas.record_refund(out_gas.refunded());
-                shared_memory.set(out_offset, &self.return_data_buffer[..target_len]);
+                copy_call_output(shared_memory, out_range, &self.return_data_buffer);
                 push!(
                     self,
                     if self.is_eof {
@@ -279,7 +329,7 @@ impl Interpreter {
             }
             return_revert!() => {
                 self.gas.erase_cost(out_gas.remaining());
-                shared_memory.set(out_offset, &self.return_data_buffer[..target_len]);
+                copy_call_output(shared_memory, out_range, &self.return_data_buffer);
                 push!(
                     self,
                     if self.is_eof {
@@ -305,6 +355,20 @@ impl Interpreter {
         }
     }
 
+    /// Halts the execution with [`InstructionResult::NotActivated`] and records the executed
+    /// opcode in [`Interpreter::rejected_opcode`].
+    ///
+    /// Called from the instruction, after the instruction pointer was advanced past the opcode.
+    #[cold]
+    pub fn reject_not_activated(&mut self) {
+        let pc = self.program_counter() - 1;
+        self.rejected_opcode = Some(RejectedOpcode {
+            pc,
+            opcode: self.bytecode[pc],
+        });
+        self.instruction_result = InstructionResult::NotActivated;
+    }
+
     /// Returns the opcode at the current instruction pointer.
     #[inline]
     pub fn current_opcode(&self) -> u8 {
@@ -329,6 +393,43 @@ impl Interpreter {
         &self.stack
     }
 
+    /// Returns the number of items on the stack.
+    #[inline]
+    pub fn stack_len(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns a reference to the `n`-th stack item counted from the top, where `0` is the top
+    /// of the stack, or `None` if the stack is not that deep.
+    ///
+    /// Does not allocate.
+    #[inline]
+    pub fn stack_peek(&self, n: usize) -> Option<&U256> {
+        self.stack.data().iter().rev().nth(n)
+    }
+
+    /// Returns the top `n` stack items, clamped to the stack length.
+    ///
+    /// Items are in stack order, so the top of the stack is the last element of the slice.
+    /// Does not allocate.
+    #[inline]
+    pub fn stack_slice_top(&self, n: usize) -> &[U256] {
+        let data = self.stack.data();
+        &data[data.len().saturating_sub(n)..]
+    }
+
+    /// Returns the memory of the current context in the given range, clamped to the current
+    /// memory size.
+    ///
+    /// Unlike [`SharedMemory::slice_range`] this does not panic and does not expand memory.
+    #[inline]
+    pub fn memory_slice(&self, range: Range<usize>) -> &[u8] {
+        let memory = self.shared_memory.context_memory();
+        let end = min(range.end, memory.len());
+        let start = min(range.start, end);
+        &memory[start..end]
+    }
+
     /// Returns the current program counter.
     #[inline]
     pub fn program_counter(&self) -> usize {
@@ -341,7 +442,7 @@ impl Interpreter {
     ///
     /// Internally it will increment instruction pointer by one.
     #[inline]
-    pub(crate) fn step<FN, H: Host + ?Sized>(&mut self, instruction_table: &[FN; 256], host: &mut H)
+    pub(crate) fn step<FN, H: ?Sized>(&mut self, instruction_table: &[FN; 256], host: &mut H)
     where
         FN: Fn(&mut Interpreter, &mut H),
     {
@@ -363,7 +464,10 @@ impl Interpreter {
     }
 
     /// Executes the interpreter until it returns or stops.
-    pub fn run<FN, H: Host + ?Sized>(
+    ///
+    /// Host is only passed to the instructions, so it needs only the capabilities that
+    /// the instructions of the table require, see [`HostRead`](crate::HostRead).
+    pub fn run<FN, H: ?Sized>(
         &mut self,
         shared_memory: SharedMemory,
         instruction_table: &[FN; 256],
@@ -378,7 +482,88 @@ impl Interpreter {
         while self.instruction_result == InstructionResult::Continue {
             self.step(instruction_table, host);
         }
+        self.take_next_action()
+    }
+
+    /// Same as [`Interpreter::run`], but every executed instruction is recorded in `profile`.
+    ///
+    /// Program counters are sampled under the code hash of the contract, the hash of EOF
+    /// bytecode is computed on every call of this function.
+    pub fn run_profiled<FN, H: ?Sized>(
+        &mut self,
+        shared_memory: SharedMemory,
+        instruction_table: &[FN; 256],
+        host: &mut H,
+        profile: &mut ExecutionProfile,
+    ) -> InterpreterAction
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        self.next_action = InterpreterAction::None;
+        self.shared_memory = shared_memory;
+        let code_hash = self.frame_code_hash();
+        while self.instruction_result == InstructionResult::Continue {
+            profile.record_step(self.current_opcode(), self.program_counter(), code_hash);
+            self.step(instruction_table, host);
+        }
+        self.take_next_action()
+    }
+
+    /// Same as [`Interpreter::run`], but instead of halting with
+    /// [`InstructionResult::OutOfGas`] when the gas left is less than the static gas of the
+    /// next instruction it returns [`Suspended`] without executing the instruction.
+    ///
+    /// Stack, memory, program counter and the state of the host are preserved, execution is
+    /// continued with [`Interpreter::resume`] after the gas is topped up with [`Gas::extend`].
+    ///
+    /// Only the static gas of the instruction, see [`OpCodeInfo::static_gas`], is checked
+    /// upfront. Dynamic costs such as memory expansion or cold access, and static costs of
+    /// older specs that are higher than in the latest spec, still halt with out of gas. Frames
+    /// of the calls made by this interpreter are not run by it and halt as usual.
+    ///
+    /// [`OpCodeInfo::static_gas`]: crate::opcode::OpCodeInfo::static_gas
+    pub fn run_resumable<FN, H: ?Sized>(
+        &mut self,
+        shared_memory: SharedMemory,
+        instruction_table: &[FN; 256],
+        host: &mut H,
+    ) -> Result<InterpreterAction, Suspended>
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        self.next_action = InterpreterAction::None;
+        self.shared_memory = shared_memory;
+        while self.instruction_result == InstructionResult::Continue {
+            let static_gas =
+                OpCode::info_by_op(self.current_opcode()).map_or(0, |info| info.static_gas());
+            if self.gas.remaining() < static_gas {
+                return Err(Suspended {
+                    shared_memory: Box::new(self.take_memory()),
+                    missing_gas: static_gas - self.gas.remaining(),
+                });
+            }
+            self.step(instruction_table, host);
+        }
+        Ok(self.take_next_action())
+    }
 
+    /// Continues the execution suspended by [`Interpreter::run_resumable`].
+    ///
+    /// Returns [`Suspended`] again if the gas was not topped up enough.
+    pub fn resume<FN, H: ?Sized>(
+        &mut self,
+        suspended: Suspended,
+        instruction_table: &[FN; 256],
+        host: &mut H,
+    ) -> Result<InterpreterAction, Suspended>
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        self.run_resumable(suspended.into_shared_memory(), instruction_table, host)
+    }
+
+    /// Returns the action set by the last instruction, or the halt if there is none.
+    fn take_next_action(&mut self) -> InterpreterAction {
         // Return next action if it is some.
         if self.next_action.is_some() {
             return core::mem::take(&mut self.next_action);
@@ -398,7 +583,38 @@ impl Interpreter {
     #[inline]
     #[must_use]
     pub fn resize_memory(&mut self, new_size: usize) -> bool {
-        resize_memory(&mut self.shared_memory, &mut self.gas, new_size)
+        resize_memory(
+            &mut self.shared_memory,
+            &mut self.gas,
+            &self.gas_overrides,
+            new_size,
+        )
+    }
+}
+
+/// Interpreter execution suspended by [`Interpreter::run_resumable`] because the gas left is
+/// less than the static gas of the next instruction.
+///
+/// Holds the memory of the interpreter until it is resumed with [`Interpreter::resume`], the
+/// memory is boxed to keep the error of [`Interpreter::run_resumable`] small.
+#[derive(Debug)]
+pub struct Suspended {
+    shared_memory: Box<SharedMemory>,
+    missing_gas: u64,
+}
+
+impl Suspended {
+    /// Returns the gas that needs to be added with [`Gas::extend`] to execute the next
+    /// instruction.
+    #[inline]
+    pub fn missing_gas(&self) -> u64 {
+        self.missing_gas
+    }
+
+    /// Returns the memory of the suspended interpreter, discarding the suspended execution.
+    #[inline]
+    pub fn into_shared_memory(self) -> SharedMemory {
+        *self.shared_memory
     }
 }
 
@@ -447,10 +663,15 @@ impl InterpreterResult {
 #[inline(never)]
 #[cold]
 #[must_use]
-pub fn resize_memory(memory: &mut SharedMemory, gas: &mut Gas, new_size: usize) -> bool {
+pub fn resize_memory(
+    memory: &mut SharedMemory,
+    gas: &mut Gas,
+    gas_overrides: &GasScheduleOverrides,
+    new_size: usize,
+) -> bool {
     let new_words = num_words(new_size as u64);
-    let new_cost = gas::memory_gas(new_words);
-    let current_cost = memory.current_expansion_cost();
+    let new_cost = gas::memory_gas(gas_overrides, new_words);
+    let current_cost = memory.current_expansion_cost(gas_overrides);
     let cost = new_cost - current_cost;
     let success = gas.record_cost(cost);
     if success {
@@ -459,11 +680,31 @@ pub fn resize_memory(memory: &mut SharedMemory, gas: &mut Gas, new_size: usize)
     success
 }
 
+/// Copies the output of a call to the `out_range` of the caller memory.
+///
+/// At most `min(out_range.len(), output.len())` bytes are copied. The write is additionally
+/// clamped to the end of the memory, so a range that is out of bounds (e.g. `usize::MAX..usize::MAX`
+/// used for empty ranges) never panics and memory outside of the `out_range` is never written.
+#[inline]
+fn copy_call_output(shared_memory: &mut SharedMemory, out_range: Range<usize>, output: &[u8]) {
+    let len = min(
+        min(out_range.len(), output.len()),
+        shared_memory.len().saturating_sub(out_range.start),
+    );
+    if len != 0 {
+        shared_memory.set(out_range.start, &output[..len]);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{opcode::InstructionTable, DummyHost};
-    use revm_primitives::CancunSpec;
+    use crate::{
+        instructions::{control, host, stack},
+        opcode::{self, InstructionTable},
+        DummyHost, Host, HostRead,
+    };
+    use revm_primitives::{Address, CancunSpec};
 
     #[test]
     fn object_safety() {
@@ -479,4 +720,211 @@ mod tests {
             &crate::opcode::make_instruction_table::<dyn Host, CancunSpec>();
         let _ = interp.run(EMPTY_SHARED_MEMORY, table, host);
     }
+
+    #[test]
+    fn read_only_host() {
+        let mut table: InstructionTable<dyn HostRead> = [control::unknown; 256];
+        table[opcode::PUSH1 as usize] = stack::push::<1, _>;
+        table[opcode::SLOAD as usize] = host::sload::<_, CancunSpec>;
+        table[opcode::STOP as usize] = control::stop;
+
+        // SLOAD(1), STOP
+        let bytecode = Bytecode::new_raw(Bytes::from_static(&[
+            opcode::PUSH1,
+            0x01,
+            opcode::SLOAD,
+            opcode::STOP,
+        ]));
+        let contract = Contract::new(
+            Bytes::new(),
+            bytecode,
+            None,
+            Address::ZERO,
+            None,
+            Address::ZERO,
+            U256::ZERO,
+        );
+        let mut interp = Interpreter::new(contract, u64::MAX, false);
+        let mut host = DummyHost::default();
+        host.storage.insert(U256::from(1), U256::from(42));
+        let host: &mut dyn HostRead = &mut host;
+
+        let _ = interp.run(EMPTY_SHARED_MEMORY, &table, host);
+        assert_eq!(interp.instruction_result, InstructionResult::Stop);
+        assert_eq!(interp.stack.data(), &[U256::from(42)]);
+    }
+
+    #[test]
+    fn resume_after_gas_top_ups() {
+        // Sum of 1..=10 in a loop, returned as a word.
+        let code = crate::opcode::asm::assemble(
+            "PUSH1 0 PUSH1 10
+            loop: JUMPDEST DUP1 ISZERO @end JUMPI
+            DUP1 SWAP2 ADD SWAP1 PUSH1 1 SWAP1 SUB @loop JUMP
+            end: JUMPDEST POP PUSH1 0 MSTORE PUSH1 0x20 PUSH1 0 RETURN",
+        )
+        .unwrap();
+        let contract = |gas_limit| {
+            let contract = Contract::new(
+                Bytes::new(),
+                Bytecode::new_raw(code.clone()),
+                None,
+                Address::ZERO,
+                None,
+                Address::ZERO,
+                U256::ZERO,
+            );
+            Interpreter::new(contract, gas_limit, false)
+        };
+        let table = &crate::opcode::make_instruction_table::<DummyHost, CancunSpec>();
+        let mut host = DummyHost::default();
+        let result = |action| match action {
+            InterpreterAction::Return { result } => result,
+            action => panic!("unexpected action {action:?}"),
+        };
+
+        let mut interp = contract(u64::MAX);
+        let expected = result(interp.run(SharedMemory::new(), table, &mut host));
+        assert_eq!(expected.output[31], 55);
+        let gas_used = expected.gas.spent();
+
+        // Quarter of the gas at a time, execution stops inside of the loop three times.
+        let slice = gas_used / 4;
+        let mut interp = contract(slice);
+        let mut top_ups = 0;
+        let mut outcome = interp.run_resumable(SharedMemory::new(), table, &mut host);
+        let action = loop {
+            match outcome {
+                Ok(action) => break action,
+                Err(suspended) => {
+                    assert!(suspended.missing_gas() > 0);
+                    top_ups += 1;
+                    interp.gas.extend(slice + 1);
+                    outcome = interp.resume(suspended, table, &mut host);
+                }
+            }
+        };
+        let result = result(action);
+        assert_eq!(top_ups, 3);
+        assert_eq!(result.result, expected.result);
+        assert_eq!(result.output, expected.output);
+        assert_eq!(result.gas.spent(), gas_used);
+        assert_eq!(result.gas.limit(), 4 * slice + 3);
+    }
+
+    #[test]
+    fn return_data_buffer_is_overwritten_by_outcomes() {
+        let mut memory = SharedMemory::new();
+        let mut interp = Interpreter::new(Contract::default(), u64::MAX, false);
+        let result = |result, output: &[u8]| {
+            InterpreterResult::new(result, Bytes::copy_from_slice(output), Gas::new(0))
+        };
+
+        interp.insert_call_outcome(
+            &mut memory,
+            CallOutcome::new(result(InstructionResult::Return, &[0xaa; 32]), 0..0),
+        );
+        interp.insert_call_outcome(
+            &mut memory,
+            CallOutcome::new(result(InstructionResult::Return, &[0xbb; 8]), 0..0),
+        );
+        // the bytes of the first call are gone.
+        assert_eq!(interp.return_data_buffer[..], [0xbb; 8]);
+
+        interp.insert_create_outcome(CreateOutcome::new(
+            result(InstructionResult::Revert, &[0xcc; 4]),
+            None,
+        ));
+        assert_eq!(interp.return_data_buffer[..], [0xcc; 4]);
+
+        interp.insert_create_outcome(CreateOutcome::new(
+            result(InstructionResult::Return, &[0xdd; 4]),
+            Some(Address::with_last_byte(1)),
+        ));
+        assert!(interp.return_data_buffer.is_empty());
+    }
+
+    /// Inserts a successful call outcome into 64 bytes of memory filled with `0xAA`
+    /// and returns the memory.
+    fn insert_output(out_range: Range<usize>, output: &[u8]) -> Vec<u8> {
+        let mut memory = SharedMemory::new();
+        memory.resize(64);
+        memory.set(0, &[0xAA; 64]);
+
+        let mut interp = Interpreter::new(Contract::default(), u64::MAX, false);
+        let result = InterpreterResult::new(
+            InstructionResult::Return,
+            Bytes::copy_from_slice(output),
+            Gas::new(0),
+        );
+        interp.insert_call_outcome(&mut memory, CallOutcome::new(result, out_range));
+        assert_eq!(interp.return_data_buffer[..], *output);
+        assert_eq!(interp.stack.peek(0), Ok(U256::from(1)));
+        memory.context_memory().to_vec()
+    }
+
+    #[test]
+    fn insert_call_outcome_zero_length_range() {
+        let memory = insert_output(usize::MAX..usize::MAX, &[1, 2, 3]);
+        assert_eq!(memory, [0xAA; 64]);
+        let memory = insert_output(10..10, &[1, 2, 3]);
+        assert_eq!(memory, [0xAA; 64]);
+    }
+
+    #[test]
+    fn insert_call_outcome_range_larger_than_output() {
+        let memory = insert_output(10..42, &[1, 2, 3]);
+        assert_eq!(memory[..10], [0xAA; 10]);
+        assert_eq!(memory[10..13], [1, 2, 3]);
+        // memory after the output is not cleared.
+        assert_eq!(memory[13..], [0xAA; 51]);
+    }
+
+    #[test]
+    fn insert_call_outcome_output_larger_than_range() {
+        let memory = insert_output(10..12, &[1, 2, 3]);
+        assert_eq!(memory[10..12], [1, 2]);
+        assert_eq!(memory[12..], [0xAA; 52]);
+    }
+
+    #[test]
+    fn insert_call_outcome_range_at_memory_end() {
+        let memory = insert_output(62..64, &[1, 2, 3]);
+        assert_eq!(memory[..62], [0xAA; 62]);
+        assert_eq!(memory[62..], [1, 2]);
+
+        // ranges past the end of memory are clamped.
+        let memory = insert_output(63..80, &[1, 2, 3]);
+        assert_eq!(memory[..63], [0xAA; 63]);
+        assert_eq!(memory[63..], [1]);
+        let memory = insert_output(64..96, &[1, 2, 3]);
+        assert_eq!(memory, [0xAA; 64]);
+        let memory = insert_output(usize::MAX - 1..usize::MAX, &[1, 2, 3]);
+        assert_eq!(memory, [0xAA; 64]);
+    }
+
+    #[test]
+    fn stack_and_memory_accessors() {
+        let mut interp = Interpreter::new(Contract::default(), u64::MAX, false);
+        assert_eq!(interp.stack_peek(0), None);
+        assert!(interp.stack_slice_top(2).is_empty());
+        for i in 1..=3 {
+            interp.stack.push(U256::from(i)).unwrap();
+        }
+        assert_eq!(interp.stack_len(), 3);
+        assert_eq!(interp.stack_peek(0), Some(&U256::from(3)));
+        assert_eq!(interp.stack_peek(2), Some(&U256::from(1)));
+        assert_eq!(interp.stack_peek(3), None);
+        assert_eq!(interp.stack_peek(usize::MAX), None);
+        assert_eq!(interp.stack_slice_top(2), [U256::from(2), U256::from(3)]);
+        assert_eq!(interp.stack_slice_top(10).len(), 3);
+
+        interp.shared_memory = SharedMemory::new();
+        interp.shared_memory.resize(32);
+        interp.shared_memory.set(0, &[0xAA; 32]);
+        assert_eq!(interp.memory_slice(0..4), [0xAA; 4]);
+        assert_eq!(interp.memory_slice(30..40), [0xAA; 2]);
+        assert!(interp.memory_slice(40..50).is_empty());
+        assert!(interp.memory_slice(usize::MAX - 1..usize::MAX).is_empty());
+    }
 }
@@ -4,14 +4,11 @@ use crate::{
     instructions::utility::{read_i16, read_u16},
     opcode,
     primitives::{
-        bitvec::prelude::{bitvec, BitVec, Lsb0},
-        eof::TypesSection,
-        legacy::JumpTable,
-        Bytecode, Bytes, Eof, LegacyAnalyzedBytecode,
+        eof::TypesSection, legacy::JumpTable, Bytecode, Bytes, Eof, LegacyAnalyzedBytecode,
     },
     OPCODE_INFO_JUMPTABLE, STACK_LIMIT,
 };
-use std::{sync::Arc, vec, vec::Vec};
+use std::{vec, vec::Vec};
 
 const EOF_NON_RETURNING_FUNCTION: u8 = 0x80;
 
@@ -22,48 +19,44 @@ const EOF_NON_RETURNING_FUNCTION: u8 = 0x80;
 /// If the bytecode is already analyzed, it is returned as-is.
 #[inline]
 pub fn to_analysed(bytecode: Bytecode) -> Bytecode {
-    let (bytes, len) = match bytecode {
-        Bytecode::LegacyRaw(bytecode) => {
-            let len = bytecode.len();
-            let mut padded_bytecode = Vec::with_capacity(len + 33);
-            padded_bytecode.extend_from_slice(&bytecode);
-            padded_bytecode.resize(len + 33, 0);
-            (Bytes::from(padded_bytecode), len)
-        }
-        n => return n,
+    let Bytecode::LegacyRaw(bytecode) = bytecode else {
+        return bytecode;
     };
-    let jump_table = analyze(bytes.as_ref());
-
-    Bytecode::LegacyAnalyzed(LegacyAnalyzedBytecode::new(bytes, len, jump_table))
+    let bytes = pad_legacy(&bytecode);
+    let jump_table = JumpTable::analyze(bytes.as_ref());
+
+    Bytecode::LegacyAnalyzed(LegacyAnalyzedBytecode::new(
+        bytes,
+        bytecode.len(),
+        jump_table,
+    ))
 }
 
-/// Analyze bytecode to build a jump map.
-fn analyze(code: &[u8]) -> JumpTable {
-    let mut jumps: BitVec<u8> = bitvec![u8, Lsb0; 0; code.len()];
-
-    let range = code.as_ptr_range();
-    let start = range.start;
-    let mut iterator = start;
-    let end = range.end;
-    while iterator < end {
-        let opcode = unsafe { *iterator };
-        if opcode::JUMPDEST == opcode {
-            // SAFETY: jumps are max length of the code
-            unsafe { jumps.set_unchecked(iterator.offset_from(start) as usize, true) }
-            iterator = unsafe { iterator.offset(1) };
-        } else {
-            let push_offset = opcode.wrapping_sub(opcode::PUSH1);
-            if push_offset < 32 {
-                // SAFETY: iterator access range is checked in the while loop
-                iterator = unsafe { iterator.offset((push_offset + 2) as isize) };
-            } else {
-                // SAFETY: iterator access range is checked in the while loop
-                iterator = unsafe { iterator.offset(1) };
-            }
-        }
-    }
+/// Pads raw legacy bytecode for execution without finding its jump destinations.
+///
+/// The returned bytecode has an empty jump table, see
+/// [`Contract::new_lazy`](crate::Contract::new_lazy). If the bytecode is already analyzed, it
+/// is returned as-is.
+#[inline]
+pub fn to_padded(bytecode: Bytecode) -> Bytecode {
+    let Bytecode::LegacyRaw(bytecode) = bytecode else {
+        return bytecode;
+    };
+    Bytecode::LegacyAnalyzed(LegacyAnalyzedBytecode::new(
+        pad_legacy(&bytecode),
+        bytecode.len(),
+        JumpTable::default(),
+    ))
+}
 
-    JumpTable(Arc::new(jumps))
+/// Copies the legacy bytecode and appends 33 zero bytes, so that a `PUSH32` at the end of the
+/// code is followed by a `STOP`.
+fn pad_legacy(bytecode: &[u8]) -> Bytes {
+    let len = bytecode.len();
+    let mut padded_bytecode = Vec::with_capacity(len + 33);
+    padded_bytecode.extend_from_slice(bytecode);
+    padded_bytecode.resize(len + 33, 0);
+    Bytes::from(padded_bytecode)
 }
 
 pub fn validate_raw_eof(bytecode: Bytes) -> Result<Eof, EofError> {
@@ -247,7 +240,7 @@ pub fn validate_eof_code(
     num_of_containers: usize,
     types: &[TypesSection],
 ) -> Result<HashSet<usize>, EofValidationError> {
-    let mut accessed_codes = HashSet::<usize>::new();
+    let mut accessed_codes = HashSet::<usize>::default();
     let this_types = &types[this_types_index];
 
     #[derive(Debug, Copy, Clone)]
@@ -561,6 +554,17 @@ mod test {
     use super::*;
     use revm_primitives::hex;
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn jump_table_serde_round_trip() {
+        // JUMPDEST, PUSH1 0x5b, JUMPDEST, STOP
+        let table = JumpTable::analyze(&hex!("5b605b5b00"));
+        let json = serde_json::to_string(&table).unwrap();
+        assert_eq!(json, r#"{"bits":"0x09","len":5}"#);
+        assert_eq!(serde_json::from_str::<JumpTable>(&json).unwrap(), table);
+        assert!(serde_json::from_str::<JumpTable>(r#"{"bits":"0x09","len":9}"#).is_err());
+    }
+
     #[test]
     fn test1() {
         // result:Result { result: false, exception: Some("EOF_ConflictingStackHeight") }
@@ -1,8 +1,8 @@
-use revm_primitives::TxKind;
-
-use super::analysis::to_analysed;
+use super::analysis::{to_analysed, to_padded};
 use crate::{
-    primitives::{Address, Bytecode, Bytes, Env, B256, U256},
+    primitives::{
+        legacy::JumpTable, Address, Bytecode, Bytes, Env, LegacyAnalyzedBytecode, B256, U256,
+    },
     CallInputs,
 };
 
@@ -26,6 +26,10 @@ pub struct Contract {
     pub caller: Address,
     /// Value send to contract from transaction or from CALL opcodes.
     pub call_value: U256,
+    /// `true` if the jump destinations of the legacy bytecode are not found yet, see
+    /// [`Contract::new_lazy`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pending_analysis: bool,
 }
 
 impl Contract {
@@ -50,28 +54,50 @@ impl Contract {
             bytecode_address,
             caller,
             call_value,
+            pending_analysis: false,
+        }
+    }
+
+    /// Instantiates a new contract without analyzing the given bytecode.
+    ///
+    /// Jump destinations of raw legacy bytecode are found on the first jump, see
+    /// [`Contract::analyze_pending`].
+    #[inline]
+    pub fn new_lazy(
+        input: Bytes,
+        bytecode: Bytecode,
+        hash: Option<B256>,
+        target_address: Address,
+        bytecode_address: Option<Address>,
+        caller: Address,
+        call_value: U256,
+    ) -> Self {
+        let pending_analysis = matches!(bytecode, Bytecode::LegacyRaw(_));
+
+        Self {
+            input,
+            bytecode: to_padded(bytecode),
+            hash,
+            target_address,
+            bytecode_address,
+            caller,
+            call_value,
+            pending_analysis,
         }
     }
 
     /// Creates a new contract from the given [`Env`].
     #[inline]
     pub fn new_env(env: &Env, bytecode: Bytecode, hash: Option<B256>) -> Self {
-        let contract_address = match env.tx.transact_to {
-            TxKind::Call(caller) => caller,
-            TxKind::Create => Address::ZERO,
-        };
-        let bytecode_address = match env.tx.transact_to {
-            TxKind::Call(caller) => Some(caller),
-            TxKind::Create => None,
-        };
+        let bytecode_address = env.tx().to();
         Self::new(
-            env.tx.data.clone(),
+            env.tx().data.clone(),
             bytecode,
             hash,
-            contract_address,
+            bytecode_address.unwrap_or_default(),
             bytecode_address,
-            env.tx.caller,
-            env.tx.value,
+            env.tx().caller,
+            env.tx().value,
         )
     }
 
@@ -94,7 +120,27 @@ impl Contract {
         )
     }
 
+    /// Finds the jump destinations of the bytecode if they were not found yet.
+    #[inline]
+    pub fn analyze_pending(&mut self) {
+        if !self.pending_analysis {
+            return;
+        }
+        self.pending_analysis = false;
+        if let Bytecode::LegacyAnalyzed(analyzed) = &self.bytecode {
+            let jump_table = JumpTable::analyze(analyzed.bytecode());
+            self.bytecode = Bytecode::LegacyAnalyzed(LegacyAnalyzedBytecode::new(
+                analyzed.bytecode().clone(),
+                analyzed.original_len(),
+                jump_table,
+            ));
+        }
+    }
+
     /// Returns whether the given position is a valid jump destination.
+    ///
+    /// There are no valid jump destinations while the analysis is pending, see
+    /// [`Contract::analyze_pending`].
     #[inline]
     pub fn is_valid_jump(&self, pos: usize) -> bool {
         self.bytecode
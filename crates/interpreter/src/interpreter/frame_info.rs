@@ -0,0 +1,41 @@
+use super::Contract;
+use crate::primitives::{Address, B256};
+
+/// Information about the frame that is currently executed by the interpreter.
+///
+/// It is created together with the interpreter and is available to step hooks through [`Interpreter::frame_info`](crate::Interpreter::frame_info), together with
+/// [`Interpreter::program_counter`](crate::Interpreter::program_counter) it allows
+/// mapping of the execution back to the source.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameInfo {
+    /// Hash of the executed bytecode. For creates this is the hash of the initcode.
+    ///
+    /// `None` if the hash is not known yet, it is computed on first use by
+    /// [`Interpreter::frame_code_hash`](crate::Interpreter::frame_code_hash).
+    pub code_hash: Option<B256>,
+    /// Target address of the frame.
+    pub address: Address,
+    /// Whether the frame executes initcode, set by the host that creates the create frame.
+    pub is_create: bool,
+    /// Whether the executed bytecode is EOF.
+    pub is_eof: bool,
+    /// Index of the currently executed EOF code section, `None` for legacy bytecode.
+    pub code_section: Option<usize>,
+}
+
+impl FrameInfo {
+    /// Creates the frame info of a call frame from the contract.
+    ///
+    /// The bytecode is not hashed, [`FrameInfo::code_hash`] is taken from [`Contract::hash`].
+    pub fn new(contract: &Contract) -> Self {
+        let is_eof = contract.bytecode.is_eof();
+        Self {
+            code_hash: contract.hash,
+            address: contract.target_address,
+            is_create: false,
+            is_eof,
+            code_section: is_eof.then_some(0),
+        }
+    }
+}
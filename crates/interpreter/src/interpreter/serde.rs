@@ -1,8 +1,9 @@
 use super::Interpreter;
 use crate::{
-    Contract, FunctionStack, Gas, InstructionResult, InterpreterAction, SharedMemory, Stack,
+    Contract, FrameInfo, FunctionStack, Gas, InstructionResult, InterpreterAction, RejectedOpcode,
+    SharedMemory, Stack,
 };
-use revm_primitives::Bytes;
+use revm_primitives::{Bytes, GasScheduleOverrides};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Serialize)]
@@ -11,6 +12,7 @@ struct InterpreterSerde<'a> {
 
     gas: &'a Gas,
     contract: &'a Contract,
+    frame_info: &'a FrameInfo,
     instruction_result: InstructionResult,
     bytecode: &'a Bytes,
     is_eof: bool,
@@ -21,6 +23,8 @@ struct InterpreterSerde<'a> {
     return_data_buffer: &'a Bytes,
     is_static: bool,
     next_action: &'a InterpreterAction,
+    rejected_opcode: Option<RejectedOpcode>,
+    gas_overrides: GasScheduleOverrides,
 }
 
 #[derive(Deserialize)]
@@ -29,6 +33,7 @@ struct InterpreterDe {
 
     gas: Gas,
     contract: Contract,
+    frame_info: FrameInfo,
     instruction_result: InstructionResult,
     bytecode: Bytes,
     is_eof: bool,
@@ -39,6 +44,8 @@ struct InterpreterDe {
     return_data_buffer: Bytes,
     is_static: bool,
     next_action: InterpreterAction,
+    rejected_opcode: Option<RejectedOpcode>,
+    gas_overrides: GasScheduleOverrides,
 }
 
 impl Serialize for Interpreter {
@@ -50,6 +57,7 @@ impl Serialize for Interpreter {
             program_counter: self.program_counter(),
             gas: &self.gas,
             contract: &self.contract,
+            frame_info: &self.frame_info,
             instruction_result: self.instruction_result,
             bytecode: &self.bytecode,
             is_eof: self.is_eof,
@@ -60,6 +68,8 @@ impl Serialize for Interpreter {
             return_data_buffer: &self.return_data_buffer,
             is_static: self.is_static,
             next_action: &self.next_action,
+            rejected_opcode: self.rejected_opcode,
+            gas_overrides: self.gas_overrides,
         }
         .serialize(serializer)
     }
@@ -74,6 +84,7 @@ impl<'de> Deserialize<'de> for Interpreter {
             program_counter,
             gas,
             contract,
+            frame_info,
             instruction_result,
             bytecode,
             is_eof,
@@ -84,6 +95,8 @@ impl<'de> Deserialize<'de> for Interpreter {
             return_data_buffer,
             is_static,
             next_action,
+            rejected_opcode,
+            gas_overrides,
         } = InterpreterDe::deserialize(deserializer)?;
 
         // Reconstruct the instruction pointer from usize
@@ -98,6 +111,7 @@ impl<'de> Deserialize<'de> for Interpreter {
             instruction_pointer,
             gas,
             contract,
+            frame_info,
             instruction_result,
             bytecode,
             is_eof,
@@ -108,6 +122,8 @@ impl<'de> Deserialize<'de> for Interpreter {
             return_data_buffer,
             is_static,
             next_action,
+            rejected_opcode,
+            gas_overrides,
         })
     }
 }
@@ -1,5 +1,5 @@
 use core::{cmp::min, fmt, ops::Range};
-use revm_primitives::{B256, U256};
+use revm_primitives::{GasScheduleOverrides, B256, U256};
 use std::vec::Vec;
 
 /// A sequential memory shared between calls, which uses
@@ -125,8 +125,8 @@ impl SharedMemory {
 
     /// Returns the gas cost for the current memory expansion.
     #[inline]
-    pub fn current_expansion_cost(&self) -> u64 {
-        crate::gas::memory_gas_for_len(self.len())
+    pub fn current_expansion_cost(&self, gas_overrides: &GasScheduleOverrides) -> u64 {
+        crate::gas::memory_gas_for_len(gas_overrides, self.len())
     }
 
     /// Resizes the memory in-place so that `len` is equal to `new_len`.
@@ -1,4 +1,7 @@
-use crate::primitives::{Address, Bytes, TxEnv, TxKind, U256};
+use crate::{
+    primitives::{Address, Bytes, TxEnv, TxKind, U256},
+    SharedMemory,
+};
 use core::ops::Range;
 use std::boxed::Box;
 
@@ -7,7 +10,7 @@ use std::boxed::Box;
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CallInputs {
     /// The call data of the call.
-    pub input: Bytes,
+    pub input: CallInput,
     /// The return memory offset where the output of the call is written.
     ///
     /// In EOF, this range is invalid as EOF calls do not write output to memory.
@@ -40,18 +43,28 @@ pub struct CallInputs {
     pub is_static: bool,
     /// Whether the call is initiated from EOF bytecode.
     pub is_eof: bool,
+    /// Whether the call frame is only checked.
+    ///
+    /// Frame creation does all the checks, value transfer and gas accounting and reverts
+    /// the journal checkpoint. Bytecode is not executed, the result is
+    /// [`InstructionResult::Stop`](crate::InstructionResult::Stop) if the checks passed.
+    /// Precompiles are still executed. Used for pre-flight checks of the calls.
+    pub dry_run: bool,
 }
 
 impl CallInputs {
-    /// Creates new call inputs.
+    /// Creates the call inputs of the first frame of a call transaction, as the mainnet
+    /// handler does.
+    ///
+    /// `gas_limit` is the transaction gas limit with the initial gas already subtracted.
     ///
     /// Returns `None` if the transaction is not a call.
     pub fn new(tx_env: &TxEnv, gas_limit: u64) -> Option<Self> {
-        let TxKind::Call(target_address) = tx_env.transact_to else {
+        let TxKind::Call(target_address) = tx_env.kind() else {
             return None;
         };
         Some(CallInputs {
-            input: tx_env.data.clone(),
+            input: CallInput::Bytes(tx_env.data.clone()),
             gas_limit,
             target_address,
             bytecode_address: target_address,
@@ -60,6 +73,7 @@ impl CallInputs {
             scheme: CallScheme::Call,
             is_static: false,
             is_eof: false,
+            dry_run: false,
             return_memory_offset: 0..0,
         })
     }
@@ -103,10 +117,15 @@ impl CallInputs {
 
     /// Returns the address of the transfer target account.
     ///
+    /// `CALLCODE` transfers the value to the caller itself, other calls to the target account.
+    ///
     /// This is only meaningful if `transfers_value` is `true`.
     #[inline]
     pub const fn transfer_to(&self) -> Address {
-        self.target_address
+        match self.scheme {
+            CallScheme::CallCode => self.caller,
+            _ => self.target_address,
+        }
     }
 
     /// Returns the call value, regardless of the transfer value type.
@@ -118,6 +137,75 @@ impl CallInputs {
     }
 }
 
+/// Input of a call.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CallInput {
+    /// Range of the caller memory that holds the input.
+    ///
+    /// Set by the call instructions so the input is not copied out of the memory, e.g. for
+    /// precompiles. The range is relative to the memory context of the caller.
+    SharedBuffer(Range<usize>),
+    /// Owned input.
+    Bytes(Bytes),
+}
+
+impl Default for CallInput {
+    #[inline]
+    fn default() -> Self {
+        CallInput::Bytes(Bytes::new())
+    }
+}
+
+impl From<Bytes> for CallInput {
+    #[inline]
+    fn from(bytes: Bytes) -> Self {
+        CallInput::Bytes(bytes)
+    }
+}
+
+impl CallInput {
+    /// Returns the length of the input.
+    #[inline]
+    pub fn len(&self) -> usize {
+        match self {
+            Self::SharedBuffer(range) => range.len(),
+            Self::Bytes(bytes) => bytes.len(),
+        }
+    }
+
+    /// Returns `true` if the input is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the input, `memory` is the memory of the caller.
+    ///
+    /// Returns `None` if the input is out of bounds of the memory context, `memory` is not
+    /// the memory of the caller.
+    #[inline]
+    pub fn as_slice<'a>(&'a self, memory: &'a SharedMemory) -> Option<&'a [u8]> {
+        match self {
+            Self::SharedBuffer(range) if range.is_empty() => Some(&[]),
+            Self::SharedBuffer(range) => memory.context_memory().get(range.clone()),
+            Self::Bytes(bytes) => Some(bytes),
+        }
+    }
+
+    /// Returns the owned input, input in the memory of the caller is copied.
+    ///
+    /// Returns `None` if the input is out of bounds of the memory context, see
+    /// [`CallInput::as_slice`].
+    #[inline]
+    pub fn bytes(&self, memory: &SharedMemory) -> Option<Bytes> {
+        match self {
+            Self::SharedBuffer(_) => self.as_slice(memory).map(Bytes::copy_from_slice),
+            Self::Bytes(bytes) => Some(bytes.clone()),
+        }
+    }
+}
+
 /// Call scheme.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -151,6 +239,11 @@ impl CallScheme {
     pub fn is_ext_delegate_call(&self) -> bool {
         matches!(self, Self::ExtDelegateCall)
     }
+
+    /// Returns true if the call can transfer value: `CALL`, `CALLCODE` and `EXTCALL`.
+    pub fn can_transfer_value(&self) -> bool {
+        matches!(self, Self::Call | Self::CallCode | Self::ExtCall)
+    }
 }
 
 /// Call value.
@@ -211,3 +304,71 @@ impl CallValue {
         matches!(self, Self::Apparent(_))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::address;
+
+    #[test]
+    fn new_from_tx_env() {
+        let tx = TxEnv {
+            caller: address!("1000000000000000000000000000000000000001"),
+            transact_to: Some(TxKind::Call(address!(
+                "2000000000000000000000000000000000000002"
+            ))),
+            value: U256::from(10),
+            data: Bytes::from_static(&[1, 2, 3]),
+            ..Default::default()
+        };
+        let inputs = CallInputs::new(&tx, 1000).unwrap();
+        assert_eq!(inputs.caller, tx.caller);
+        assert_eq!(inputs.target_address, tx.to().unwrap());
+        assert_eq!(inputs.bytecode_address, inputs.target_address);
+        assert_eq!(inputs.value, CallValue::Transfer(tx.value));
+        assert_eq!(inputs.scheme, CallScheme::Call);
+        assert_eq!(inputs.input, CallInput::Bytes(tx.data.clone()));
+        assert_eq!(inputs.gas_limit, 1000);
+
+        let create = TxEnv {
+            transact_to: Some(TxKind::Create),
+            ..tx
+        };
+        assert_eq!(CallInputs::new(&create, 1000), None);
+    }
+
+    #[test]
+    fn call_input_in_caller_memory() {
+        let mut memory = SharedMemory::new();
+        memory.new_context();
+        memory.resize(32);
+        memory.set(0, &[1; 32]);
+        memory.new_context();
+        memory.resize(32);
+        memory.set(4, &[1, 2, 3]);
+
+        let input = CallInput::SharedBuffer(4..7);
+        assert_eq!(input.len(), 3);
+        assert_eq!(input.as_slice(&memory), Some(&[1, 2, 3][..]));
+        assert_eq!(input.bytes(&memory), Some(Bytes::from_static(&[1, 2, 3])));
+        // empty input of the call instructions.
+        let input = CallInput::SharedBuffer(usize::MAX..usize::MAX);
+        assert!(input.is_empty());
+        assert_eq!(input.as_slice(&memory), Some(&[][..]));
+
+        let input = CallInput::Bytes(Bytes::from_static(&[4, 5]));
+        assert_eq!(input.as_slice(&memory), Some(&[4, 5][..]));
+    }
+
+    #[test]
+    fn call_input_out_of_caller_memory() {
+        let mut memory = SharedMemory::new();
+        memory.new_context();
+        memory.resize(32);
+
+        // range of a different memory.
+        let input = CallInput::SharedBuffer(30..40);
+        assert_eq!(input.as_slice(&memory), None);
+        assert_eq!(input.bytes(&memory), None);
+    }
+}
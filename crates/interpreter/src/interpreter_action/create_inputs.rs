@@ -1,5 +1,7 @@
 pub use crate::primitives::CreateScheme;
-use crate::primitives::{Address, Bytes, TxEnv, TxKind, U256};
+use crate::primitives::{
+    keccak256, Address, Bytes, InvalidTransaction, SpecId, TxEnv, TxKind, B256, U256,
+};
 use std::boxed::Box;
 
 /// Inputs for a create call.
@@ -21,7 +23,7 @@ pub struct CreateInputs {
 impl CreateInputs {
     /// Creates new create inputs.
     pub fn new(tx_env: &TxEnv, gas_limit: u64) -> Option<Self> {
-        let TxKind::Create = tx_env.transact_to else {
+        let TxKind::Create = tx_env.kind() else {
             return None;
         };
 
@@ -34,18 +36,112 @@ impl CreateInputs {
         })
     }
 
+    /// Creates the create inputs of the first frame of a create transaction, the mainnet
+    /// handler uses it for the legacy initcode.
+    ///
+    /// `gas_limit` is the transaction gas limit with the initial gas already subtracted.
+    /// `max_initcode_size` is the initcode size limit (EIP-3860), see
+    /// [`CfgEnv::max_initcode_size`](crate::primitives::CfgEnv::max_initcode_size), it is
+    /// enforced only from Shanghai.
+    ///
+    /// Returns `Ok(None)` if the transaction is not a create and
+    /// [`InvalidTransaction::CreateInitCodeSizeLimit`] if the initcode is too large.
+    pub fn from_tx_env(
+        tx_env: &TxEnv,
+        gas_limit: u64,
+        spec_id: SpecId,
+        max_initcode_size: usize,
+    ) -> Result<Option<Self>, InvalidTransaction> {
+        if !tx_env.is_create() {
+            return Ok(None);
+        }
+        if spec_id.is_enabled_in(SpecId::SHANGHAI) && tx_env.data.len() > max_initcode_size {
+            return Err(InvalidTransaction::CreateInitCodeSizeLimit);
+        }
+        Ok(Self::new(tx_env, gas_limit))
+    }
+
     /// Returns boxed create inputs.
     pub fn new_boxed(tx_env: &TxEnv, gas_limit: u64) -> Option<Box<Self>> {
         Self::new(tx_env, gas_limit).map(Box::new)
     }
 
     /// Returns the address that this create call will create.
+    ///
+    /// `nonce` is the nonce of the caller before the create, it is not used for CREATE2.
     pub fn created_address(&self, nonce: u64) -> Address {
         match self.scheme {
             CreateScheme::Create => self.caller.create(nonce),
-            CreateScheme::Create2 { salt } => self
-                .caller
-                .create2_from_code(salt.to_be_bytes(), &self.init_code),
+            CreateScheme::Create2 { .. } => {
+                self.created_address_with_hash(nonce, keccak256(&self.init_code))
+            }
+        }
+    }
+
+    /// Returns the address that this create call will create, with the precomputed
+    /// `init_code_hash`.
+    ///
+    /// Hash is used only for CREATE2, see [`CreateInputs::created_address`].
+    pub fn created_address_with_hash(&self, nonce: u64, init_code_hash: B256) -> Address {
+        match self.scheme {
+            CreateScheme::Create => self.caller.create(nonce),
+            CreateScheme::Create2 { salt } => {
+                self.caller.create2(salt.to_be_bytes(), init_code_hash)
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{address, bytes, MAX_INITCODE_SIZE};
+
+    fn create_tx(data: Bytes) -> TxEnv {
+        TxEnv {
+            caller: address!("1000000000000000000000000000000000000001"),
+            transact_to: Some(TxKind::Create),
+            value: U256::from(10),
+            data,
+            ..Default::default()
+        }
+    }
+
+    fn from_tx_env(
+        tx: &TxEnv,
+        spec_id: SpecId,
+    ) -> Result<Option<CreateInputs>, InvalidTransaction> {
+        CreateInputs::from_tx_env(tx, 1000, spec_id, MAX_INITCODE_SIZE)
+    }
+
+    #[test]
+    fn from_tx_env_fields() {
+        let tx = create_tx(bytes!("6000"));
+        let inputs = from_tx_env(&tx, SpecId::CANCUN).unwrap().unwrap();
+        assert_eq!(inputs.caller, tx.caller);
+        assert_eq!(inputs.value, tx.value);
+        assert_eq!(inputs.scheme, CreateScheme::Create);
+        assert_eq!(inputs.init_code, tx.data);
+        assert_eq!(inputs.gas_limit, 1000);
+
+        let call = TxEnv {
+            transact_to: Some(TxKind::Call(Address::ZERO)),
+            ..tx
+        };
+        assert_eq!(from_tx_env(&call, SpecId::CANCUN), Ok(None));
+    }
+
+    #[test]
+    fn from_tx_env_initcode_limit() {
+        let tx = create_tx(vec![0; MAX_INITCODE_SIZE + 1].into());
+        assert_eq!(
+            from_tx_env(&tx, SpecId::SHANGHAI),
+            Err(InvalidTransaction::CreateInitCodeSizeLimit)
+        );
+        // Initcode size is not limited before Shanghai.
+        assert!(matches!(from_tx_env(&tx, SpecId::MERGE), Ok(Some(_))));
+
+        let tx = create_tx(vec![0; MAX_INITCODE_SIZE].into());
+        assert!(matches!(from_tx_env(&tx, SpecId::SHANGHAI), Ok(Some(_))));
+    }
+}
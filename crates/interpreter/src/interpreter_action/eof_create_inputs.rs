@@ -1,7 +1,8 @@
-use crate::primitives::{Address, Bytes, Eof, TxEnv, U256};
+use crate::primitives::{Address, Bytes, Eof, TxEnv, B256, U256};
 
-/// EOF create can be called from two places:
+/// EOF create can be called from three places:
 /// * EOFCREATE opcode
+/// * TXCREATE opcode (experimental)
 /// * Creation transaction.
 ///
 /// Creation transaction uses initdata and packs EOF and initdata inside it.
@@ -9,6 +10,9 @@ use crate::primitives::{Address, Bytes, Eof, TxEnv, U256};
 ///
 /// Opcode creation uses already validated EOF bytecode, and input from Interpreter memory.
 /// Address is already known and is passed as an argument.
+///
+/// TXCREATE references the initcontainer of the initcode transaction by its hash, see
+/// [`TxEnv::initcodes`]. Initcontainers are validated with the transaction.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EOFCreateKind {
@@ -20,6 +24,11 @@ pub enum EOFCreateKind {
         input: Bytes,
         created_address: Address,
     },
+    TxInitcode {
+        hash: B256,
+        input: Bytes,
+        created_address: Address,
+    },
 }
 
 impl EOFCreateKind {
@@ -28,6 +37,9 @@ impl EOFCreateKind {
         match self {
             EOFCreateKind::Opcode {
                 created_address, ..
+            }
+            | EOFCreateKind::TxInitcode {
+                created_address, ..
             } => Some(created_address),
             EOFCreateKind::Tx { .. } => None,
         }
@@ -104,4 +116,25 @@ impl EOFCreateInputs {
             },
         )
     }
+
+    /// Returns a new instance of EOFCreateInput for the TXCREATE opcode.
+    pub fn new_tx_initcode(
+        caller: Address,
+        created_address: Address,
+        value: U256,
+        hash: B256,
+        gas_limit: u64,
+        input: Bytes,
+    ) -> EOFCreateInputs {
+        EOFCreateInputs::new(
+            caller,
+            value,
+            gas_limit,
+            EOFCreateKind::TxInitcode {
+                hash,
+                input,
+                created_address,
+            },
+        )
+    }
 }
@@ -25,22 +25,26 @@ pub mod instructions;
 pub mod interpreter;
 pub mod interpreter_action;
 pub mod opcode;
+mod run;
 
 // Reexport primary types.
 pub use function_stack::{FunctionReturnFrame, FunctionStack};
 pub use gas::Gas;
-pub use host::{DummyHost, Host, LoadAccountResult, SStoreResult, SelfDestructResult};
+pub use host::{
+    DummyHost, Host, HostRead, HostWrite, LoadAccountResult, SStoreResult, SelfDestructResult,
+};
 pub use instruction_result::*;
 pub use interpreter::{
-    analysis, num_words, Contract, Interpreter, InterpreterResult, SharedMemory, Stack,
-    EMPTY_SHARED_MEMORY, STACK_LIMIT,
+    analysis, num_words, Contract, FrameInfo, Interpreter, InterpreterResult, RejectedOpcode,
+    SharedMemory, Stack, Suspended, EMPTY_SHARED_MEMORY, STACK_LIMIT,
 };
 pub use interpreter_action::{
-    CallInputs, CallOutcome, CallScheme, CallValue, CreateInputs, CreateOutcome, CreateScheme,
-    EOFCreateInputs, EOFCreateKind, InterpreterAction,
+    CallInput, CallInputs, CallOutcome, CallScheme, CallValue, CreateInputs, CreateOutcome,
+    CreateScheme, EOFCreateInputs, EOFCreateKind, InterpreterAction,
 };
 pub use opcode::{Instruction, OpCode, OPCODE_INFO_JUMPTABLE};
 pub use primitives::{MAX_CODE_SIZE, MAX_INITCODE_SIZE};
+pub use run::{run_bytecode, run_bytecode_with_storage};
 
 #[doc(hidden)]
 pub use revm_primitives as primitives;
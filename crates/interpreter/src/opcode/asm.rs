@@ -0,0 +1,345 @@
+//! Assembler and disassembler of the EVM bytecode, for authoring tests.
+//!
+//! The source is a whitespace separated list of tokens:
+//! * Opcode names, case insensitive, e.g. `PUSH1`, `add`.
+//! * Immediate of the previous opcode, a decimal or `0x` prefixed hex number, e.g.
+//!   `PUSH2 0x0100` or `PUSH1 32`. Values are left padded to the immediate size.
+//! * Label definitions, e.g. `loop:`. Labels don't emit any bytes, they are usually followed
+//!   by `JUMPDEST`.
+//! * Label references, e.g. `@loop`, that push the offset of the label with the smallest
+//!   `PUSH` that fits it. `PUSH2 @loop` pushes it with the given width.
+//! * Raw bytes, a standalone `0x` prefixed hex number, e.g. `0xfe`.
+//!
+//! [`disassemble`] produces the canonical form of the source: opcode names in upper case,
+//! immediates as hex of their full width and unknown opcodes or truncated immediates as raw
+//! bytes. Assembling it produces the same bytecode.
+
+use super::{OpCode, PUSH1, PUSH32};
+use crate::primitives::{hex, Bytes, HashMap, U256};
+use core::fmt;
+use std::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Errors of [`assemble`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AsmError {
+    /// Token is not an opcode, a number, a label definition or a label reference.
+    UnknownToken(String),
+    /// Opcode with an immediate is at the end of the source.
+    MissingImmediate(String),
+    /// Immediate is not a number, or does not fit in the immediate of the opcode.
+    InvalidImmediate(String),
+    /// Label reference is used as an immediate of an opcode that is not a `PUSH`.
+    InvalidLabelReference(String),
+    /// Label is referenced but not defined.
+    UnknownLabel(String),
+    /// Label is defined twice.
+    DuplicateLabel(String),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AsmError {}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownToken(token) => write!(f, "unknown token `{token}`"),
+            Self::MissingImmediate(op) => write!(f, "missing immediate of `{op}`"),
+            Self::InvalidImmediate(token) => write!(f, "invalid immediate `{token}`"),
+            Self::InvalidLabelReference(label) => {
+                write!(f, "label reference `{label}` is not a PUSH immediate")
+            }
+            Self::UnknownLabel(label) => write!(f, "unknown label `{label}`"),
+            Self::DuplicateLabel(label) => write!(f, "duplicate label `{label}`"),
+        }
+    }
+}
+
+/// Parsed source item.
+enum Item<'a> {
+    /// Bytes that don't depend on labels.
+    Bytes(Vec<u8>),
+    /// Label definition.
+    Label(&'a str),
+    /// PUSH of the label offset, with a fixed width or the smallest one that fits.
+    LabelPush { label: &'a str, width: Option<u8> },
+}
+
+/// Assembles the source into bytecode, see the [module](self) documentation for the syntax.
+pub fn assemble(source: &str) -> Result<Bytes, AsmError> {
+    let items = parse(source)?;
+
+    // Labels are defined after their references, so the widths of the PUSHes are grown
+    // until all offsets fit. Widths only grow, so this terminates.
+    let mut widths: Vec<u8> = items
+        .iter()
+        .map(|item| match item {
+            Item::LabelPush { width, .. } => width.unwrap_or(1),
+            _ => 0,
+        })
+        .collect();
+    let labels = loop {
+        let labels = label_offsets(&items, &widths)?;
+        let mut changed = false;
+        for (item, width) in items.iter().zip(widths.iter_mut()) {
+            if let Item::LabelPush { label, width: None } = item {
+                let offset = *labels
+                    .get(label)
+                    .ok_or_else(|| AsmError::UnknownLabel(label.to_string()))?;
+                let needed = byte_width(offset);
+                if needed > *width {
+                    *width = needed;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break labels;
+        }
+    };
+
+    let mut code = Vec::new();
+    for (item, width) in items.iter().zip(widths) {
+        match item {
+            Item::Bytes(bytes) => code.extend_from_slice(bytes),
+            Item::Label(_) => {}
+            Item::LabelPush { label, .. } => {
+                let offset = *labels
+                    .get(label)
+                    .ok_or_else(|| AsmError::UnknownLabel(label.to_string()))?;
+                if byte_width(offset) > width {
+                    return Err(AsmError::InvalidImmediate(format!("@{label}")));
+                }
+                code.push(PUSH1 + width - 1);
+                code.extend_from_slice(&offset.to_be_bytes()[8 - width as usize..]);
+            }
+        }
+    }
+    Ok(code.into())
+}
+
+/// Disassembles the bytecode into the canonical source form, see the [module](self)
+/// documentation.
+pub fn disassemble(code: &[u8]) -> String {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < code.len() {
+        let op = code[i];
+        let Some(opcode) = OpCode::new(op) else {
+            tokens.push(format!("0x{op:02x}"));
+            i += 1;
+            continue;
+        };
+        let immediate_size = opcode.info().immediate_size() as usize;
+        let Some(immediate) = code.get(i + 1..i + 1 + immediate_size) else {
+            // truncated immediate.
+            tokens.extend(code[i..].iter().map(|byte| format!("0x{byte:02x}")));
+            break;
+        };
+        tokens.push(opcode.as_str().to_string());
+        if immediate_size != 0 {
+            tokens.push(format!("0x{}", hex::encode(immediate)));
+        }
+        i += 1 + immediate_size;
+    }
+    tokens.join(" ")
+}
+
+/// Parses the source into items.
+fn parse(source: &str) -> Result<Vec<Item<'_>>, AsmError> {
+    let mut items = Vec::new();
+    let mut tokens = source.split_whitespace();
+    while let Some(token) = tokens.next() {
+        if let Some(label) = token.strip_suffix(':') {
+            items.push(Item::Label(label));
+            continue;
+        }
+        if let Some(label) = token.strip_prefix('@') {
+            items.push(Item::LabelPush { label, width: None });
+            continue;
+        }
+        if let Some(digits) = token.strip_prefix("0x") {
+            let bytes = hex::decode(digits).map_err(|_| AsmError::UnknownToken(token.into()))?;
+            items.push(Item::Bytes(bytes));
+            continue;
+        }
+
+        let Some(opcode) = opcode_by_name(token) else {
+            return Err(AsmError::UnknownToken(token.into()));
+        };
+        let immediate_size = opcode.info().immediate_size();
+        if immediate_size == 0 {
+            items.push(Item::Bytes(vec![opcode.get()]));
+            continue;
+        }
+
+        let immediate = tokens
+            .next()
+            .ok_or_else(|| AsmError::MissingImmediate(opcode.as_str().into()))?;
+        if let Some(label) = immediate.strip_prefix('@') {
+            if !(PUSH1..=PUSH32).contains(&opcode.get()) {
+                return Err(AsmError::InvalidLabelReference(label.into()));
+            }
+            items.push(Item::LabelPush {
+                label,
+                width: Some(immediate_size),
+            });
+            continue;
+        }
+        let mut bytes = vec![opcode.get()];
+        bytes.extend_from_slice(&parse_immediate(immediate, immediate_size)?);
+        items.push(Item::Bytes(bytes));
+    }
+    Ok(items)
+}
+
+/// Returns the opcode with the given name, ignoring the case.
+///
+/// Does not use `OpCode::parse` so the assembler is available without the `parse` feature.
+fn opcode_by_name(name: &str) -> Option<OpCode> {
+    (0..=u8::MAX)
+        .filter_map(OpCode::new)
+        .find(|opcode| opcode.as_str().eq_ignore_ascii_case(name))
+}
+
+/// Parses a decimal or hex number into big endian bytes of the given size.
+fn parse_immediate(token: &str, size: u8) -> Result<Vec<u8>, AsmError> {
+    let invalid = || AsmError::InvalidImmediate(token.into());
+    let value = match token.strip_prefix("0x") {
+        Some(digits) => U256::from_str_radix(digits, 16),
+        None => U256::from_str_radix(token, 10),
+    }
+    .map_err(|_| invalid())?;
+    let bytes = value.to_be_bytes::<32>();
+    let (padding, immediate) = bytes.split_at(32 - size as usize);
+    if padding.iter().any(|byte| *byte != 0) {
+        return Err(invalid());
+    }
+    Ok(immediate.to_vec())
+}
+
+/// Returns the offsets of the labels for the given widths of the label PUSHes.
+fn label_offsets<'a>(items: &[Item<'a>], widths: &[u8]) -> Result<HashMap<&'a str, u64>, AsmError> {
+    let mut labels = HashMap::default();
+    let mut offset = 0u64;
+    for (item, width) in items.iter().zip(widths) {
+        match item {
+            Item::Bytes(bytes) => offset += bytes.len() as u64,
+            Item::Label(label) => {
+                if labels.insert(*label, offset).is_some() {
+                    return Err(AsmError::DuplicateLabel(label.to_string()));
+                }
+            }
+            Item::LabelPush { .. } => offset += 1 + *width as u64,
+        }
+    }
+    Ok(labels)
+}
+
+/// Returns the number of bytes needed to represent the offset, at least one.
+fn byte_width(offset: u64) -> u8 {
+    (8 - offset.leading_zeros() as u8 / 8).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::bytes;
+
+    #[test]
+    fn assemble_opcodes_and_immediates() {
+        assert_eq!(
+            assemble("PUSH1 0x02 push1 3 ADD PUSH2 0x01 RETURN").unwrap(),
+            bytes!("6002600301610001f3")
+        );
+        assert_eq!(
+            assemble("PUSH32 0xff").unwrap()[1..],
+            U256::from(0xff).to_be_bytes::<32>()
+        );
+        assert_eq!(assemble("0xfe 0x0102").unwrap(), bytes!("fe0102"));
+    }
+
+    #[test]
+    fn assemble_labels() {
+        // forward and backward references.
+        assert_eq!(
+            assemble("@end JUMP start: JUMPDEST @start JUMP end: JUMPDEST STOP").unwrap(),
+            bytes!("6007565b6003565b00")
+        );
+        assert_eq!(
+            assemble("PUSH2 @end JUMP end: JUMPDEST").unwrap(),
+            bytes!("610004565b")
+        );
+
+        // label offset above 255 needs two bytes.
+        let source = format!("@end JUMP {} end: JUMPDEST", "STOP ".repeat(300));
+        let code = assemble(&source).unwrap();
+        assert_eq!(code[..4], [0x61, 0x01, 0x30, 0x56]);
+        assert_eq!(code[0x130], 0x5b);
+    }
+
+    #[test]
+    fn assemble_errors() {
+        assert_eq!(
+            assemble("PUSH1 0x01 FOO"),
+            Err(AsmError::UnknownToken("FOO".into()))
+        );
+        assert_eq!(
+            assemble("PUSH1"),
+            Err(AsmError::MissingImmediate("PUSH1".into()))
+        );
+        assert_eq!(
+            assemble("PUSH1 256"),
+            Err(AsmError::InvalidImmediate("256".into()))
+        );
+        assert_eq!(
+            assemble("PUSH1 ADD"),
+            Err(AsmError::InvalidImmediate("ADD".into()))
+        );
+        assert_eq!(
+            assemble("@nowhere JUMP"),
+            Err(AsmError::UnknownLabel("nowhere".into()))
+        );
+        assert_eq!(
+            assemble("a: a: STOP"),
+            Err(AsmError::DuplicateLabel("a".into()))
+        );
+        assert_eq!(
+            assemble("RJUMP @a a:"),
+            Err(AsmError::InvalidLabelReference("a".into()))
+        );
+    }
+
+    #[test]
+    fn disassemble_canonical_form() {
+        assert_eq!(
+            disassemble(&bytes!("600261000101fe0c")),
+            "PUSH1 0x02 PUSH2 0x0001 ADD INVALID 0x0c"
+        );
+        // truncated immediate is printed as raw bytes.
+        assert_eq!(disassemble(&bytes!("016100")), "ADD 0x61 0x00");
+    }
+
+    #[test]
+    fn round_trip() {
+        let sources = [
+            "push1 1 PUSH1 0x02 add push0 mstore PUSH1 32 PUSH0 RETURN",
+            "@end JUMP start: JUMPDEST end: JUMPDEST STOP",
+            "PUSH4 7 0x0c0d SELFDESTRUCT",
+        ];
+        for source in sources {
+            let code = assemble(source).unwrap();
+            let canonical = disassemble(&code);
+            assert_eq!(assemble(&canonical).unwrap(), code, "{source}");
+            assert_eq!(disassemble(&assemble(&canonical).unwrap()), canonical);
+        }
+
+        // every byte sequence survives the round trip.
+        let code: Vec<u8> = (0..=u8::MAX).rev().collect();
+        assert_eq!(assemble(&disassemble(&code)).unwrap(), code);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{
+    opcode::make_instruction_table,
+    primitives::{spec_to_generic, Address, Bytecode, Bytes, Env, HashMap, SpecId, U256},
+    CallOutcome, Contract, CreateOutcome, DummyHost, Gas, InstructionResult, Interpreter,
+    InterpreterAction, InterpreterResult, SharedMemory,
+};
+
+/// Runs the given bytecode on the interpreter without any state.
+///
+/// Bytecode is executed with `input` as calldata and `gas_limit` as the available gas,
+/// using the default instruction table for the given `spec`.
+///
+/// The host is a [`DummyHost`] with empty state, this means that:
+/// * `SLOAD` of the executing contract returns zero (see [`run_bytecode_with_storage`]
+///   for a way to pre-seed the storage).
+/// * `BALANCE`, `EXTCODE*` and similar instructions see empty accounts.
+/// * `CALL`-like instructions succeed with empty output and all forwarded gas is returned,
+///   as if the callee had no code.
+/// * `CREATE`-like instructions fail and push zero on the stack.
+///
+/// If you need real state use `revm::Evm` instead.
+pub fn run_bytecode(
+    bytecode: Bytes,
+    input: Bytes,
+    gas_limit: u64,
+    spec: SpecId,
+) -> InterpreterResult {
+    run_bytecode_with_storage(bytecode, input, gas_limit, spec, HashMap::default())
+}
+
+/// Same as [`run_bytecode`] but storage of the executing contract is pre-seeded
+/// with the given `storage` map.
+pub fn run_bytecode_with_storage(
+    bytecode: Bytes,
+    input: Bytes,
+    gas_limit: u64,
+    spec: SpecId,
+    storage: HashMap<U256, U256>,
+) -> InterpreterResult {
+    let contract = Contract::new(
+        input,
+        Bytecode::new_raw(bytecode),
+        None,
+        Address::ZERO,
+        None,
+        Address::ZERO,
+        U256::ZERO,
+    );
+    let mut interpreter = Interpreter::new(contract, gas_limit, false);
+    let mut host = DummyHost::new(Env::default());
+    host.storage = storage;
+
+    let table = spec_to_generic!(spec, make_instruction_table::<DummyHost, SPEC>());
+
+    let mut shared_memory = SharedMemory::new();
+    loop {
+        let action = interpreter.run(shared_memory, &table, &mut host);
+        shared_memory = interpreter.take_memory();
+        match action {
+            InterpreterAction::Call { inputs } => {
+                // Calls are made to empty accounts. Return all of the gas.
+                let result = InterpreterResult::new(
+                    InstructionResult::Stop,
+                    Bytes::new(),
+                    Gas::new(inputs.gas_limit),
+                );
+                interpreter.insert_call_outcome(
+                    &mut shared_memory,
+                    CallOutcome::new(result, inputs.return_memory_offset),
+                );
+            }
+            InterpreterAction::Create { inputs } => {
+                let result = InterpreterResult::new(
+                    InstructionResult::Revert,
+                    Bytes::new(),
+                    Gas::new(inputs.gas_limit),
+                );
+                interpreter.insert_create_outcome(CreateOutcome::new(result, None));
+            }
+            InterpreterAction::EOFCreate { inputs } => {
+                let result = InterpreterResult::new(
+                    InstructionResult::Revert,
+                    Bytes::new(),
+                    Gas::new(inputs.gas_limit),
+                );
+                interpreter.insert_eofcreate_outcome(CreateOutcome::new(result, None));
+            }
+            InterpreterAction::Return { result } => return result,
+            InterpreterAction::None => unreachable!("interpreter returned without an action"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcode::asm::assemble;
+
+    const ADD_AND_RETURN: &str = "PUSH1 2 PUSH1 3 ADD PUSH1 0 MSTORE PUSH1 0x20 PUSH1 0 RETURN";
+
+    #[test]
+    fn add_and_return() {
+        let code = assemble(ADD_AND_RETURN).unwrap();
+        let result = run_bytecode(code, Bytes::new(), 100_000, SpecId::CANCUN);
+
+        assert_eq!(result.result, InstructionResult::Return);
+        assert_eq!(U256::from_be_slice(&result.output), U256::from(5));
+        assert!(result.gas.spent() > 0);
+    }
+
+    #[test]
+    fn seeded_storage() {
+        let code = assemble("PUSH1 1 SLOAD PUSH1 0 MSTORE PUSH1 0x20 PUSH1 0 RETURN").unwrap();
+        let storage = HashMap::from_iter([(U256::from(1), U256::from(42))]);
+        let result =
+            run_bytecode_with_storage(code, Bytes::new(), 100_000, SpecId::CANCUN, storage);
+
+        assert_eq!(result.result, InstructionResult::Return);
+        assert_eq!(U256::from_be_slice(&result.output), U256::from(42));
+    }
+
+    #[test]
+    fn out_of_gas() {
+        let code = assemble(ADD_AND_RETURN).unwrap();
+        let result = run_bytecode(code, Bytes::new(), 5, SpecId::CANCUN);
+        assert_eq!(result.result, InstructionResult::OutOfGas);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{Error, Precompile, PrecompileResult, PrecompileWithAddress};
-use revm_primitives::{Bytes, PrecompileOutput};
+use revm_primitives::PrecompileOutput;
 
 const F_ROUND: u64 = 1;
 const INPUT_LENGTH: usize = 213;
@@ -10,9 +10,7 @@ pub const FUN: PrecompileWithAddress =
 /// reference: <https://eips.ethereum.org/EIPS/eip-152>
 /// input format:
 /// [4 bytes for rounds][64 bytes for h][128 bytes for m][8 bytes for t_0][8 bytes for t_1][1 byte for f]
-pub fn run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
-    let input = &input[..];
-
+pub fn run(input: &[u8], gas_limit: u64) -> PrecompileResult {
     if input.len() != INPUT_LENGTH {
         return Err(Error::Blake2WrongLength.into());
     }
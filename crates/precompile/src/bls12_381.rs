@@ -87,7 +87,7 @@ mod test {
     #[case::map_fp_to_g1(map_fp_to_g1::map_fp_to_g1, "map_fp_to_G1_bls.json")]
     #[case::map_fp2_to_g2(map_fp2_to_g2::map_fp2_to_g2, "map_fp2_to_G2_bls.json")]
     fn test_bls(
-        #[case] precompile: fn(input: &Bytes, gas_limit: u64) -> PrecompileResult,
+        #[case] precompile: fn(input: &[u8], gas_limit: u64) -> PrecompileResult,
         #[case] file_name: &str,
     ) {
         let test_vectors = load_test_vectors(format!("test-vectors/{file_name}"))
@@ -148,6 +148,11 @@ mod test {
 
         let actual_output = msm_required_gas(k, multiplication_cost);
 
-        assert_eq!(expected_output, actual_output);
+        assert_eq!(Some(expected_output), actual_output);
+    }
+
+    #[test]
+    fn test_msm_required_gas_overflow() {
+        assert_eq!(msm_required_gas(usize::MAX, g1_mul::BASE_GAS_FEE), None);
     }
 }
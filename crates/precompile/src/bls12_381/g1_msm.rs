@@ -6,7 +6,7 @@ use super::{
 };
 use crate::{u64_to_address, PrecompileWithAddress};
 use blst::{blst_p1, blst_p1_affine, blst_p1_from_affine, blst_p1_to_affine, p1_affines};
-use revm_primitives::{Bytes, Precompile, PrecompileError, PrecompileOutput, PrecompileResult};
+use revm_primitives::{Precompile, PrecompileError, PrecompileOutput, PrecompileResult};
 
 /// [EIP-2537](https://eips.ethereum.org/EIPS/eip-2537#specification) BLS12_G1MSM precompile.
 pub const PRECOMPILE: PrecompileWithAddress =
@@ -23,7 +23,7 @@ pub const ADDRESS: u64 = 0x0d;
 /// Output is an encoding of multi-scalar-multiplication operation result - single G1
 /// point (`128` bytes).
 /// See also: <https://eips.ethereum.org/EIPS/eip-2537#abi-for-g1-multiexponentiation>
-pub(super) fn g1_msm(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+pub(super) fn g1_msm(input: &[u8], gas_limit: u64) -> PrecompileResult {
     let input_len = input.len();
     if input_len == 0 || input_len % g1_mul::INPUT_LENGTH != 0 {
         return Err(PrecompileError::Other(format!(
@@ -35,10 +35,10 @@ pub(super) fn g1_msm(input: &Bytes, gas_limit: u64) -> PrecompileResult {
     }
 
     let k = input_len / g1_mul::INPUT_LENGTH;
-    let required_gas = msm_required_gas(k, g1_mul::BASE_GAS_FEE);
-    if required_gas > gas_limit {
-        return Err(PrecompileError::OutOfGas.into());
-    }
+    let required_gas = match msm_required_gas(k, g1_mul::BASE_GAS_FEE) {
+        Some(required_gas) if required_gas <= gas_limit => required_gas,
+        _ => return Err(PrecompileError::OutOfGas.into()),
+    };
 
     let mut g1_points: Vec<blst_p1> = Vec::with_capacity(k);
     let mut scalars: Vec<u8> = Vec::with_capacity(k * SCALAR_LENGTH);
@@ -4,7 +4,7 @@ use super::{
 };
 use crate::{u64_to_address, PrecompileWithAddress};
 use blst::{blst_p2, blst_p2_affine, blst_p2_from_affine, blst_p2_mult, blst_p2_to_affine};
-use revm_primitives::{Bytes, Precompile, PrecompileError, PrecompileOutput, PrecompileResult};
+use revm_primitives::{Precompile, PrecompileError, PrecompileOutput, PrecompileResult};
 
 /// [EIP-2537](https://eips.ethereum.org/EIPS/eip-2537#specification) BLS12_G2MUL precompile.
 pub const PRECOMPILE: PrecompileWithAddress =
@@ -23,7 +23,7 @@ pub(super) const INPUT_LENGTH: usize = 288;
 /// Output is an encoding of multiplication operation result - single G2 point
 /// (`256` bytes).
 /// See also: <https://eips.ethereum.org/EIPS/eip-2537#abi-for-g2-multiplication>
-pub(super) fn g2_mul(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+pub(super) fn g2_mul(input: &[u8], gas_limit: u64) -> PrecompileResult {
     if BASE_GAS_FEE > gas_limit {
         return Err(PrecompileError::OutOfGas.into());
     }
@@ -14,13 +14,20 @@ static MSM_DISCOUNT_TABLE: [u16; 128] = [
 
 /// Implements the gas schedule for G1/G2 Multiscalar-multiplication assuming 30
 /// MGas/second, see also: <https://eips.ethereum.org/EIPS/eip-2537#g1g2-multiexponentiation>
-pub(super) fn msm_required_gas(k: usize, multiplication_cost: u64) -> u64 {
+///
+/// Returns `None` on overflow.
+pub(super) fn msm_required_gas(k: usize, multiplication_cost: u64) -> Option<u64> {
     if k == 0 {
-        return 0;
+        return Some(0);
     }
 
     let index = core::cmp::min(k - 1, MSM_DISCOUNT_TABLE.len() - 1);
     let discount = MSM_DISCOUNT_TABLE[index] as u64;
 
-    (k as u64 * discount * multiplication_cost) / MSM_MULTIPLIER
+    Some(
+        (k as u64)
+            .checked_mul(discount)?
+            .checked_mul(multiplication_cost)?
+            / MSM_MULTIPLIER,
+    )
 }
@@ -4,9 +4,7 @@ use super::{
 };
 use crate::{u64_to_address, PrecompileWithAddress};
 use blst::{blst_final_exp, blst_fp12, blst_fp12_is_one, blst_fp12_mul, blst_miller_loop};
-use revm_primitives::{
-    Bytes, Precompile, PrecompileError, PrecompileOutput, PrecompileResult, B256,
-};
+use revm_primitives::{Precompile, PrecompileError, PrecompileOutput, PrecompileResult, B256};
 
 /// [EIP-2537](https://eips.ethereum.org/EIPS/eip-2537#specification) BLS12_PAIRING precompile.
 pub const PRECOMPILE: PrecompileWithAddress =
@@ -31,7 +29,7 @@ const INPUT_LENGTH: usize = 384;
 /// is 0x01 if pairing result is equal to the multiplicative identity in a pairing
 /// target field and 0x00 otherwise.
 /// See also: <https://eips.ethereum.org/EIPS/eip-2537#abi-for-pairing>
-pub(super) fn pairing(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+pub(super) fn pairing(input: &[u8], gas_limit: u64) -> PrecompileResult {
     let input_len = input.len();
     if input_len == 0 || input_len % INPUT_LENGTH != 0 {
         return Err(PrecompileError::Other(format!(
@@ -41,10 +39,13 @@ pub(super) fn pairing(input: &Bytes, gas_limit: u64) -> PrecompileResult {
     }
 
     let k = input_len / INPUT_LENGTH;
-    let required_gas: u64 = PAIRING_MULTIPLIER_BASE * k as u64 + PAIRING_OFFSET_BASE;
-    if required_gas > gas_limit {
-        return Err(PrecompileError::OutOfGas.into());
-    }
+    let required_gas = PAIRING_MULTIPLIER_BASE
+        .checked_mul(k as u64)
+        .and_then(|gas| gas.checked_add(PAIRING_OFFSET_BASE));
+    let required_gas = match required_gas {
+        Some(required_gas) if required_gas <= gas_limit => required_gas,
+        _ => return Err(PrecompileError::OutOfGas.into()),
+    };
 
     // Accumulator for the fp12 multiplications of the miller loops.
     let mut acc = blst_fp12::default();
@@ -165,10 +165,13 @@ pub fn run_pair(
     pair_base_cost: u64,
     gas_limit: u64,
 ) -> PrecompileResult {
-    let gas_used = (input.len() / PAIR_ELEMENT_LEN) as u64 * pair_per_point_cost + pair_base_cost;
-    if gas_used > gas_limit {
-        return Err(Error::OutOfGas.into());
-    }
+    let gas_used = ((input.len() / PAIR_ELEMENT_LEN) as u64)
+        .checked_mul(pair_per_point_cost)
+        .and_then(|gas| gas.checked_add(pair_base_cost));
+    let gas_used = match gas_used {
+        Some(gas_used) if gas_used <= gas_limit => gas_used,
+        _ => return Err(Error::OutOfGas.into()),
+    };
 
     if input.len() % PAIR_ELEMENT_LEN != 0 {
         return Err(Error::Bn128PairLength.into());
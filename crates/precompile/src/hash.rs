@@ -1,6 +1,6 @@
-use super::calc_linear_cost_u32;
+use super::calc_linear_cost;
 use crate::{Error, Precompile, PrecompileResult, PrecompileWithAddress};
-use revm_primitives::{Bytes, PrecompileOutput};
+use revm_primitives::PrecompileOutput;
 use sha2::Digest;
 
 pub const SHA256: PrecompileWithAddress =
@@ -14,29 +14,27 @@ pub const RIPEMD160: PrecompileWithAddress = PrecompileWithAddress(
 /// See: <https://ethereum.github.io/yellowpaper/paper.pdf>
 /// See: <https://docs.soliditylang.org/en/develop/units-and-global-variables.html#mathematical-and-cryptographic-functions>
 /// See: <https://etherscan.io/address/0000000000000000000000000000000000000002>
-pub fn sha256_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
-    let cost = calc_linear_cost_u32(input.len(), 60, 12);
-    if cost > gas_limit {
-        Err(Error::OutOfGas.into())
-    } else {
-        let output = sha2::Sha256::digest(input);
-        Ok(PrecompileOutput::new(cost, output.to_vec().into()))
-    }
+pub fn sha256_run(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let cost = match calc_linear_cost(input.len(), 60, 12) {
+        Some(cost) if cost <= gas_limit => cost,
+        _ => return Err(Error::OutOfGas.into()),
+    };
+    let output = sha2::Sha256::digest(input);
+    Ok(PrecompileOutput::new(cost, output.to_vec().into()))
 }
 
 /// See: <https://ethereum.github.io/yellowpaper/paper.pdf>
 /// See: <https://docs.soliditylang.org/en/develop/units-and-global-variables.html#mathematical-and-cryptographic-functions>
 /// See: <https://etherscan.io/address/0000000000000000000000000000000000000003>
-pub fn ripemd160_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
-    let gas_used = calc_linear_cost_u32(input.len(), 600, 120);
-    if gas_used > gas_limit {
-        Err(Error::OutOfGas.into())
-    } else {
-        let mut hasher = ripemd::Ripemd160::new();
-        hasher.update(input);
+pub fn ripemd160_run(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let gas_used = match calc_linear_cost(input.len(), 600, 120) {
+        Some(gas_used) if gas_used <= gas_limit => gas_used,
+        _ => return Err(Error::OutOfGas.into()),
+    };
+    let mut hasher = ripemd::Ripemd160::new();
+    hasher.update(input);
 
-        let mut output = [0u8; 32];
-        hasher.finalize_into((&mut output[12..]).into());
-        Ok(PrecompileOutput::new(gas_used, output.to_vec().into()))
-    }
+    let mut output = [0u8; 32];
+    hasher.finalize_into((&mut output[12..]).into());
+    Ok(PrecompileOutput::new(gas_used, output.to_vec().into()))
 }
@@ -1,4 +1,4 @@
-use super::calc_linear_cost_u32;
+use super::calc_linear_cost;
 use crate::{Error, Precompile, PrecompileResult, PrecompileWithAddress};
 use revm_primitives::{Bytes, PrecompileOutput};
 
@@ -14,10 +14,14 @@ pub const IDENTITY_PER_WORD: u64 = 3;
 ///
 /// See: <https://ethereum.github.io/yellowpaper/paper.pdf>
 /// See: <https://etherscan.io/address/0000000000000000000000000000000000000004>
-pub fn identity_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
-    let gas_used = calc_linear_cost_u32(input.len(), IDENTITY_BASE, IDENTITY_PER_WORD);
-    if gas_used > gas_limit {
-        return Err(Error::OutOfGas.into());
-    }
-    Ok(PrecompileOutput::new(gas_used, input.clone()))
+/// Returns the input, the output is at most 32 bytes per [`IDENTITY_PER_WORD`] gas.
+pub fn identity_run(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let gas_used = match calc_linear_cost(input.len(), IDENTITY_BASE, IDENTITY_PER_WORD) {
+        Some(gas_used) if gas_used <= gas_limit => gas_used,
+        _ => return Err(Error::OutOfGas.into()),
+    };
+    Ok(PrecompileOutput::new(
+        gas_used,
+        Bytes::copy_from_slice(input),
+    ))
 }
@@ -37,8 +37,40 @@ pub use revm_primitives::{
 };
 use std::{boxed::Box, vec::Vec};
 
+/// Calculates the linear cost of the input of `len` bytes, `base + word * ceil(len / 32)`.
+///
+/// Returns `None` on overflow, the cost can't be paid and the precompile should run out of gas.
+#[inline]
+pub fn calc_linear_cost(len: usize, base: u64, word: u64) -> Option<u64> {
+    (len.div_ceil(32) as u64)
+        .checked_mul(word)?
+        .checked_add(base)
+}
+
+/// Calculates the linear cost of the input of `len` bytes, saturating at [u64::MAX].
+///
+/// See [calc_linear_cost].
+#[inline]
 pub fn calc_linear_cost_u32(len: usize, base: u64, word: u64) -> u64 {
-    (len as u64 + 32 - 1) / 32 * word + base
+    calc_linear_cost(len, base, word).unwrap_or(u64::MAX)
+}
+
+/// Largest output of a precompile of this crate that can be paid with `gas_limit`.
+///
+/// Every precompile charges the gas before it allocates its output. Outputs of fixed size are
+/// at most 256 bytes, the G2 point of the BLS12-381 precompiles. Outputs that depend on the
+/// input are bounded by the gas:
+/// * identity returns the input, 32 bytes per [`identity::IDENTITY_PER_WORD`] gas.
+/// * modexp returns `mod_len` bytes for at least `mod_len^2 / 320` gas, see
+///   [`modexp::byzantium_gas_calc`], which is less than the identity bound above 256 bytes.
+///
+/// Outputs of custom precompiles above this bound are rejected as out of gas by the EVM if
+/// `CfgEnv::bound_precompile_output` is set.
+#[inline]
+pub fn max_output_len(gas_limit: u64) -> usize {
+    const MAX_FIXED_OUTPUT_LEN: u64 = 256;
+    let len = (gas_limit / identity::IDENTITY_PER_WORD).saturating_mul(32);
+    usize::try_from(len.max(MAX_FIXED_OUTPUT_LEN)).unwrap_or(usize::MAX)
 }
 
 #[derive(Clone, Default, Debug)]
@@ -307,3 +339,36 @@ pub const fn u64_to_address(x: u64) -> Address {
         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, x[0], x[1], x[2], x[3], x[4], x[5], x[6], x[7],
     ])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Linear cost calculated without overflow.
+    fn wide_linear_cost(len: usize, base: u64, word: u64) -> u128 {
+        (len as u128).div_ceil(32) * word as u128 + base as u128
+    }
+
+    #[test]
+    fn linear_cost_overflow() {
+        // identity and sha256 can't overflow even for the largest input.
+        assert_eq!(
+            calc_linear_cost(usize::MAX, 15, 3),
+            u64::try_from(wide_linear_cost(usize::MAX, 15, 3)).ok()
+        );
+        assert_eq!(calc_linear_cost(usize::MAX, 600, 120), None);
+        assert_eq!(calc_linear_cost(usize::MAX - 31, 60, u64::MAX), None);
+        assert_eq!(calc_linear_cost(0, u64::MAX, u64::MAX), Some(u64::MAX));
+        assert_eq!(calc_linear_cost(1, u64::MAX, 1), None);
+        assert_eq!(calc_linear_cost_u32(usize::MAX, 600, 120), u64::MAX);
+    }
+
+    proptest! {
+        #[test]
+        fn linear_cost_never_wraps(len in any::<usize>(), base in any::<u64>(), word in any::<u64>()) {
+            let expected = u64::try_from(wide_linear_cost(len, base, word)).ok();
+            prop_assert_eq!(calc_linear_cost(len, base, word), expected);
+        }
+    }
+}
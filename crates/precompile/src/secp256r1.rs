@@ -31,7 +31,7 @@ pub const P256VERIFY: PrecompileWithAddress =
 /// | signed message hash |  r  |  s  | public key x | public key y |
 /// | :-----------------: | :-: | :-: | :----------: | :----------: |
 /// |          32         | 32  | 32  |     32       |      32      |
-pub fn p256_verify(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+pub fn p256_verify(input: &[u8], gas_limit: u64) -> PrecompileResult {
     if P256VERIFY_BASE > gas_limit {
         return Err(PrecompileError::OutOfGas.into());
     }
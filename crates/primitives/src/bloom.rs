@@ -0,0 +1,173 @@
+//! Logs bloom of the receipts and blocks.
+
+use crate::{Bloom, Log, ResultAndState};
+
+/// Returns the 2048-bit logs bloom of the given logs, as in the transaction receipt.
+///
+/// Address and topics of every log are added to the bloom.
+pub fn bloom_for_logs(logs: &[Log]) -> Bloom {
+    let mut bloom = Bloom::ZERO;
+    for log in logs {
+        bloom.accrue_log(log);
+    }
+    bloom
+}
+
+impl ResultAndState {
+    /// Returns the logs bloom of the transaction receipt.
+    ///
+    /// Bloom is empty if the transaction was not successful as it emits no logs.
+    #[inline]
+    pub fn logs_bloom(&self) -> Bloom {
+        bloom_for_logs(self.result.logs())
+    }
+}
+
+/// Accumulates the logs bloom of the block from its transactions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BloomBuilder {
+    bloom: Bloom,
+}
+
+impl BloomBuilder {
+    /// Creates an empty bloom builder.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the logs to the bloom.
+    #[inline]
+    pub fn accrue_logs(&mut self, logs: &[Log]) -> &mut Self {
+        for log in logs {
+            self.bloom.accrue_log(log);
+        }
+        self
+    }
+
+    /// Adds the receipt bloom of the transaction to the bloom.
+    #[inline]
+    pub fn accrue_bloom(&mut self, bloom: &Bloom) -> &mut Self {
+        self.bloom.accrue_bloom(bloom);
+        self
+    }
+
+    /// Adds the logs of the executed transaction to the bloom.
+    #[inline]
+    pub fn accrue_result(&mut self, result: &ResultAndState) -> &mut Self {
+        self.accrue_logs(result.result.logs())
+    }
+
+    /// Returns the accumulated bloom.
+    #[inline]
+    pub fn build(&self) -> Bloom {
+        self.bloom
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{address, b256, bytes, fixed_bytes, Address, Bytes, LogData, B256};
+    use crate::{ExecutionResult, Output, SuccessReason, TxType};
+    use std::vec::Vec;
+
+    const WETH: Address = address!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+    const USDC: Address = address!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+    const DEPOSIT: B256 = b256!("e1fffcc4923d04b559f4d29a8bfc6cda04eb5b0d3c460751c2402c5c5cc9109c");
+    const TRANSFER: B256 =
+        b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+    const ALICE: B256 = b256!("0000000000000000000000001000000000000000000000000000000000000001");
+    const BOB: B256 = b256!("0000000000000000000000002000000000000000000000000000000000000002");
+
+    fn log(address: Address, topics: &[B256]) -> Log {
+        Log {
+            address,
+            data: LogData::new_unchecked(topics.to_vec(), bytes!("01")),
+        }
+    }
+
+    /// WETH deposit followed by the transfer of the wrapped ether.
+    fn wrap_and_transfer() -> [Log; 2] {
+        [
+            log(WETH, &[DEPOSIT, ALICE]),
+            log(WETH, &[TRANSFER, ALICE, BOB]),
+        ]
+    }
+
+    fn usdc_transfer() -> [Log; 1] {
+        [log(USDC, &[TRANSFER, BOB, ALICE])]
+    }
+
+    const WRAP_AND_TRANSFER_BLOOM: Bloom = Bloom(fixed_bytes!("00000000000000000000080000000000000000000000000004000000000000000000000000000000000000000002000002000000080000000000000000000000000000000000000000000008000000000000000000000000000000008100000000000000000000000000000000000000000000000000080000000010000000000000000000000000000000000000000000000001000000000000000000000040000000000000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000400000000000000000"));
+    const USDC_TRANSFER_BLOOM: Bloom = Bloom(fixed_bytes!("00000000000000000000080000000000000000000000000004000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000008000008000000000000000000000000000000000100000000000000000000000000000000000000000000000000080000000010000000000000000000000000000000000000000000000000010000000000000000000040000000000000200000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"));
+    const BLOCK_BLOOM: Bloom = Bloom(fixed_bytes!("00000000000000000000080000000000000000000000000004000000000000000000000000000000000000000002000002000000080000000000000000000000000000000000000008000008000000000000000000000000000000008100000000000000000000000000000000000000000000000000080000000010000000000000000000000000000000000000000000000001010000000000000000000040000000000000200000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000400000000000000000"));
+
+    fn result(logs: &[Log]) -> ResultAndState {
+        ResultAndState {
+            result: ExecutionResult::Success {
+                reason: SuccessReason::Stop,
+                gas_used: 0,
+                gas_refunded: 0,
+                logs: logs.to_vec(),
+                output: Output::Call(Bytes::new()),
+            },
+            state: Default::default(),
+            tx_type: TxType::Eip1559,
+            selfdestructs: Vec::new(),
+            profile: None,
+            #[cfg(feature = "optimism")]
+            l1_fee: None,
+        }
+    }
+
+    #[test]
+    fn receipt_bloom() {
+        assert_eq!(bloom_for_logs(&[]), Bloom::ZERO);
+        assert_eq!(
+            bloom_for_logs(&wrap_and_transfer()),
+            WRAP_AND_TRANSFER_BLOOM
+        );
+        assert_eq!(bloom_for_logs(&usdc_transfer()), USDC_TRANSFER_BLOOM);
+        assert_eq!(
+            result(&wrap_and_transfer()).logs_bloom(),
+            WRAP_AND_TRANSFER_BLOOM
+        );
+    }
+
+    /// USDT approval, transaction
+    /// `0x21f6554c28453a01e7276c1db2fc1695bb512b170818bfa98fa8136433100616` of mainnet
+    /// block 19526841.
+    #[test]
+    fn mainnet_receipt_bloom() {
+        let log = Log {
+            address: address!("dac17f958d2ee523a2206206994597c13d831ec7"),
+            data: LogData::new_unchecked(
+                vec![
+                    b256!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925"),
+                    b256!("0000000000000000000000009a53bfba35269414f3b2d20b52ca01b15932c7b2"),
+                    b256!("00000000000000000000000039e5dbb9d2fead31234d7c647d6ce77d85826f76"),
+                ],
+                bytes!("00000000000000000000000000000000000000000052b7d2dcc80cd2e4000000"),
+            ),
+        };
+        let receipt_bloom = Bloom(fixed_bytes!("00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000200000000000000000040000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000400000800000000000000000000000000000000004000000000000000000800000000100000020000000000000000000080000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000010000000000000000000000000000"));
+        assert_eq!(bloom_for_logs(core::slice::from_ref(&log)), receipt_bloom);
+        assert_eq!(result(&[log]).logs_bloom(), receipt_bloom);
+    }
+
+    #[test]
+    fn block_bloom() {
+        let mut builder = BloomBuilder::new();
+        builder
+            .accrue_result(&result(&wrap_and_transfer()))
+            .accrue_logs(&usdc_transfer());
+        assert_eq!(builder.build(), BLOCK_BLOOM);
+
+        let mut builder = BloomBuilder::new();
+        builder
+            .accrue_bloom(&USDC_TRANSFER_BLOOM)
+            .accrue_bloom(&WRAP_AND_TRANSFER_BLOOM);
+        assert_eq!(builder.build(), BLOCK_BLOOM);
+    }
+}
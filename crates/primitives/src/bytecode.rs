@@ -35,6 +35,8 @@ impl Bytecode {
     }
 
     /// Return jump table if bytecode is analyzed
+    ///
+    /// Table can be stored and later supplied to [`Bytecode::new_analyzed`] to skip the analysis.
     #[inline]
     pub fn legacy_jump_table(&self) -> Option<&JumpTable> {
         match &self {
@@ -79,11 +81,18 @@ impl Bytecode {
     ///
     /// Bytecode needs to end with STOP (0x00) opcode as checked bytecode assumes
     /// that it is safe to iterate over bytecode without checking lengths.
+    ///
+    /// `jump_table` needs to contain the valid jump destinations of the bytecode, as built by
+    /// [`JumpTable::analyze`]. In debug builds this is checked by analyzing the bytecode again.
     pub unsafe fn new_analyzed(
         bytecode: Bytes,
         original_len: usize,
         jump_table: JumpTable,
     ) -> Self {
+        debug_assert!(
+            jump_table.same_destinations(&JumpTable::analyze(&bytecode)),
+            "jump table does not match the bytecode"
+        );
         Self::LegacyAnalyzed(LegacyAnalyzedBytecode::new(
             bytecode,
             original_len,
@@ -173,6 +182,31 @@ mod tests {
     use super::*;
     use std::sync::Arc;
 
+    #[test]
+    fn new_analyzed_with_precomputed_jump_table() {
+        // PUSH1 0x04, JUMP, INVALID, JUMPDEST, STOP
+        let bytes = Bytes::from_static(&[0x60, 0x04, 0x56, 0xfe, 0x5b, 0x00]);
+        let table = JumpTable::analyze(&bytes);
+        let (bits, len) = (table.as_slice().to_vec(), table.len());
+
+        let table = JumpTable::from_bits(&bits, len).unwrap();
+        let bytecode = unsafe { Bytecode::new_analyzed(bytes.clone(), bytes.len(), table) };
+        let table = bytecode.legacy_jump_table().unwrap();
+        assert_eq!(table.as_slice(), bits);
+        assert!(table.is_valid(4));
+        assert!(!table.is_valid(1));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "jump table does not match the bytecode")]
+    fn new_analyzed_with_wrong_jump_table() {
+        // PUSH1 0x5b, STOP, the pushed byte is not a jump destination.
+        let bytes = Bytes::from_static(&[0x60, 0x5b, 0x00]);
+        let table = JumpTable::from_bits(&[0b010], 3).unwrap();
+        let _ = unsafe { Bytecode::new_analyzed(bytes, 3, table) };
+    }
+
     #[test]
     fn eof_arc_clone() {
         let eof = Arc::new(Eof::default());
@@ -86,16 +86,24 @@ impl Eof {
         buffer.into()
     }
 
-    /// Decode EOF that have additional dangling bytes.
-    /// Assume that data section is fully filled.
+    /// Decode EOF that have additional dangling bytes, as the initcontainer of a creation
+    /// transaction followed by its calldata.
+    ///
+    /// The data section must be fully filled, bytes after the size declared in the header are
+    /// returned as dangling data. Subcontainers must contain all their sections, only their
+    /// data section can be truncated, and can't have dangling data.
     pub fn decode_dangling(mut eof: Bytes) -> Result<(Self, Bytes), EofDecodeError> {
         let (header, _) = EofHeader::decode(&eof)?;
-        let eof_size = header.body_size() + header.size();
-        if eof_size > eof.len() {
-            return Err(EofDecodeError::MissingInput);
-        }
-        let dangling_data = eof.split_off(eof_size);
+        header.check_sections_len(eof.len(), false)?;
+        let dangling_data = eof.split_off(header.eof_size());
         let body = EofBody::decode(&eof, &header)?;
+        for container in &body.container_section {
+            let (container_header, _) = EofHeader::decode(container)?;
+            container_header.check_sections_len(container.len(), true)?;
+            if container.len() > container_header.eof_size() {
+                return Err(EofDecodeError::DanglingData);
+            }
+        }
         Ok((
             Self {
                 header,
@@ -155,6 +163,28 @@ pub enum EofDecodeError {
     ZeroCodeSections,
     /// Invalid container number.
     TooManyContainerSections,
+    /// Section is shorter than its size declared in the header.
+    TruncatedSection {
+        /// Section whose size mismatched.
+        section: EofSection,
+        /// Size declared in the header.
+        declared: usize,
+        /// Bytes of the section that are present.
+        available: usize,
+    },
+}
+
+/// Section of the EOF body whose size is declared in the header.
+#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum EofSection {
+    /// Types section.
+    Types,
+    /// All code sections.
+    Code,
+    /// All container sections.
+    Container,
+    /// Data section.
+    Data,
 }
 
 #[cfg(test)]
@@ -172,35 +202,85 @@ mod test {
 
     #[test]
     fn decode_eof_dangling() {
+        let truncated = |section, declared, available| {
+            Err(EofDecodeError::TruncatedSection {
+                section,
+                declared,
+                available,
+            })
+        };
+        // (eof, dangling data, expected error)
         let test_cases = [
+            // zero trailing bytes.
             (
                 bytes!("ef000101000402000100010400000000800000fe"),
-                bytes!("010203"),
-                false,
+                bytes!(""),
+                Ok(()),
             ),
+            // calldata after the container.
             (
                 bytes!("ef000101000402000100010400000000800000fe"),
+                bytes!("010203"),
+                Ok(()),
+            ),
+            // full data section followed by extra bytes beyond the declared total size.
+            (
+                bytes!("ef000101000402000100010400020000800000fe0102"),
+                bytes!("03"),
+                Ok(()),
+            ),
+            // data section declared larger than available.
+            (
+                bytes!("ef000101000402000100010400020000800000fe01"),
                 bytes!(""),
-                false,
+                truncated(EofSection::Data, 2, 1),
             ),
+            // missing code section.
             (
                 bytes!("ef000101000402000100010400000000800000"),
                 bytes!(""),
-                true,
+                truncated(EofSection::Code, 1, 0),
+            ),
+            // missing types section.
+            (
+                bytes!("ef0001010004020001000104000000"),
+                bytes!(""),
+                truncated(EofSection::Types, 4, 0),
+            ),
+            // subcontainer with truncated data section.
+            (
+                bytes!("ef000101000402000100010300010015040000000080000000ef000101000402000100010400020000800000fe01"),
+                bytes!("01"),
+                Ok(()),
+            ),
+            // subcontainer with missing code section.
+            (
+                bytes!("ef000101000402000100010300010013040000000080000000ef000101000402000100010400000000800000"),
+                bytes!(""),
+                truncated(EofSection::Code, 1, 0),
+            ),
+            // subcontainer with dangling data.
+            (
+                bytes!("ef000101000402000100010300010015040000000080000000ef000101000402000100010400000000800000fe01"),
+                bytes!(""),
+                Err(EofDecodeError::DanglingData),
             ),
         ];
 
-        for (eof_bytes, dangling_data, is_err) in test_cases {
+        for (eof_bytes, dangling_data, expected) in test_cases {
             let mut raw = eof_bytes.to_vec();
             raw.extend(&dangling_data);
             let raw = Bytes::from(raw);
 
             let result = Eof::decode_dangling(raw.clone());
-            assert_eq!(result.is_err(), is_err);
-            if is_err {
+            assert_eq!(
+                result.as_ref().map(|_| ()).map_err(|e| *e),
+                expected,
+                "{raw}"
+            );
+            let Ok((decoded_eof, decoded_dangling)) = result else {
                 continue;
-            }
-            let (decoded_eof, decoded_dangling) = result.unwrap();
+            };
             assert_eq!(eof_bytes, decoded_eof.encode_slow());
             assert_eq!(decoded_dangling, dangling_data);
         }
@@ -1,6 +1,6 @@
 use super::{
     decode_helpers::{consume_u16, consume_u8},
-    EofDecodeError,
+    EofDecodeError, EofSection,
 };
 use std::vec::Vec;
 
@@ -99,6 +99,37 @@ impl EofHeader {
         self.size() + self.body_size()
     }
 
+    /// Checks that `len` raw bytes contain all sections declared in the header.
+    ///
+    /// Returns [`EofDecodeError::TruncatedSection`] for the first section that is not fully
+    /// present. The data section can be shorter than declared if `allow_truncated_data` is set,
+    /// as it is for subcontainers that get the data appended when deployed.
+    pub fn check_sections_len(
+        &self,
+        len: usize,
+        allow_truncated_data: bool,
+    ) -> Result<(), EofDecodeError> {
+        let sections = [
+            (EofSection::Types, self.types_size as usize),
+            (EofSection::Code, self.sum_code_sizes),
+            (EofSection::Container, self.sum_container_sizes),
+            (EofSection::Data, self.data_size as usize),
+        ];
+        let mut start = self.size();
+        for (section, declared) in sections {
+            let available = len.saturating_sub(start).min(declared);
+            if available < declared && !(allow_truncated_data && section == EofSection::Data) {
+                return Err(EofDecodeError::TruncatedSection {
+                    section,
+                    declared,
+                    available,
+                });
+            }
+            start += declared;
+        }
+        Ok(())
+    }
+
     /// Encodes EOF header into binary form.
     pub fn encode(&self, buffer: &mut Vec<u8>) {
         // magic	2 bytes	0xEF00	EOF prefix
@@ -1,10 +1,19 @@
 use crate::hex;
-use bitvec::vec::BitVec;
+use bitvec::{bitvec, order::Lsb0, vec::BitVec};
 use std::{fmt::Debug, sync::Arc};
 
 /// A map of valid `jump` destinations.
+///
+/// Bit at index `pc` is set if `pc` is a `JUMPDEST` opcode. Raw bits are stored in
+/// [`Lsb0`] order, see [`JumpTable::as_slice`] and [`JumpTable::from_bits`].
+///
+/// Serialized as raw bits with the number of bits.
 #[derive(Clone, Default, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "RawJumpTable", try_from = "RawJumpTable")
+)]
 pub struct JumpTable(pub Arc<BitVec<u8>>);
 
 impl Debug for JumpTable {
@@ -16,6 +25,49 @@ impl Debug for JumpTable {
 }
 
 impl JumpTable {
+    /// Analyzes the legacy bytecode to find valid jump destinations.
+    ///
+    /// Bytecode without `JUMPDEST` bytes or without `PUSH` opcodes is handled without walking
+    /// the opcodes.
+    pub fn analyze(code: &[u8]) -> Self {
+        const JUMPDEST: u8 = 0x5b;
+        const PUSH1: u8 = 0x60;
+
+        if !code.contains(&JUMPDEST) {
+            return Self(Arc::new(bitvec![u8, Lsb0; 0; code.len()]));
+        }
+        // no push data can hide a JUMPDEST byte, so every JUMPDEST byte is a destination.
+        if !code.iter().any(|opcode| opcode.wrapping_sub(PUSH1) < 32) {
+            return Self(Arc::new(jumpdest_bytes(code)));
+        }
+
+        let mut jumps: BitVec<u8> = bitvec![u8, Lsb0; 0; code.len()];
+
+        let range = code.as_ptr_range();
+        let start = range.start;
+        let mut iterator = start;
+        let end = range.end;
+        while iterator < end {
+            let opcode = unsafe { *iterator };
+            if JUMPDEST == opcode {
+                // SAFETY: jumps are max length of the code
+                unsafe { jumps.set_unchecked(iterator.offset_from(start) as usize, true) }
+                iterator = unsafe { iterator.offset(1) };
+            } else {
+                let push_offset = opcode.wrapping_sub(PUSH1);
+                if push_offset < 32 {
+                    // SAFETY: iterator access range is checked in the while loop
+                    iterator = unsafe { iterator.offset((push_offset + 2) as isize) };
+                } else {
+                    // SAFETY: iterator access range is checked in the while loop
+                    iterator = unsafe { iterator.offset(1) };
+                }
+            }
+        }
+
+        Self(Arc::new(jumps))
+    }
+
     /// Get the raw bytes of the jump map
     #[inline]
     pub fn as_slice(&self) -> &[u8] {
@@ -28,9 +80,148 @@ impl JumpTable {
         Self(Arc::new(BitVec::from_slice(slice)))
     }
 
+    /// Construct a jump map of `len` bits from raw bytes, as returned by [`JumpTable::as_slice`].
+    ///
+    /// Returns `None` if `bits` has less than `len` bits.
+    #[inline]
+    pub fn from_bits(bits: &[u8], len: usize) -> Option<Self> {
+        if len > bits.len().saturating_mul(8) {
+            return None;
+        }
+        let mut jumps = BitVec::from_slice(bits);
+        jumps.truncate(len);
+        Some(Self(Arc::new(jumps)))
+    }
+
+    /// Returns the number of bits in the jump map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the jump map has no bits.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     /// Check if `pc` is a valid jump destination.
     #[inline]
     pub fn is_valid(&self, pc: usize) -> bool {
         pc < self.0.len() && self.0[pc]
     }
+
+    /// Returns `true` if both jump maps have the same valid jump destinations.
+    ///
+    /// Unlike `==`, unset bits past the end of the shorter map are ignored.
+    #[inline]
+    pub fn same_destinations(&self, other: &Self) -> bool {
+        self.0.iter_ones().eq(other.0.iter_ones())
+    }
+}
+
+/// Serialized form of the [`JumpTable`].
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct RawJumpTable {
+    bits: crate::Bytes,
+    len: usize,
+}
+
+#[cfg(feature = "serde")]
+impl From<JumpTable> for RawJumpTable {
+    fn from(table: JumpTable) -> Self {
+        Self {
+            bits: crate::Bytes::copy_from_slice(table.as_slice()),
+            len: table.len(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawJumpTable> for JumpTable {
+    type Error = &'static str;
+
+    fn try_from(raw: RawJumpTable) -> Result<Self, Self::Error> {
+        Self::from_bits(&raw.bits, raw.len).ok_or("jump table has less bits than its length")
+    }
+}
+
+/// Returns the bits of the `JUMPDEST` bytes of the `code`.
+///
+/// Eight bytes are compared at a time, collecting the bits one by one is as slow as walking
+/// the opcodes.
+fn jumpdest_bytes(code: &[u8]) -> BitVec<u8> {
+    const JUMPDEST: u8 = 0x5b;
+    const JUMPDESTS: u64 = u64::from_le_bytes([JUMPDEST; 8]);
+    const LOW_BITS: u64 = 0x7f7f_7f7f_7f7f_7f7f;
+
+    let chunks = code.chunks_exact(8);
+    let remainder = chunks.remainder();
+    let mut bytes = std::vec::Vec::with_capacity(code.len() / 8 + 1);
+    for chunk in chunks {
+        let word = u64::from_le_bytes(chunk.try_into().unwrap()) ^ JUMPDESTS;
+        // high bit of every zero byte, JUMPDEST bytes are zero after the xor.
+        let zeros = !(((word & LOW_BITS) + LOW_BITS) | word | LOW_BITS);
+        // gathers the high bits into the top byte, first byte of the chunk in the lowest bit.
+        bytes.push(((zeros >> 7).wrapping_mul(0x0102_0408_1020_4080) >> 56) as u8);
+    }
+    if !remainder.is_empty() {
+        bytes.push(remainder.iter().rev().fold(0, |byte, opcode| {
+            (byte << 1) | u8::from(*opcode == JUMPDEST)
+        }));
+    }
+
+    let mut jumps = BitVec::from_vec(bytes);
+    jumps.truncate(code.len());
+    jumps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits_round_trip() {
+        // JUMPDEST, PUSH1 0x5b, JUMPDEST, STOP
+        let table = JumpTable::analyze(&[0x5b, 0x60, 0x5b, 0x5b, 0x00]);
+        assert_eq!(table.len(), 5);
+        assert!(table.is_valid(0));
+        assert!(!table.is_valid(2));
+        assert!(table.is_valid(3));
+
+        let restored = JumpTable::from_bits(table.as_slice(), table.len()).unwrap();
+        assert_eq!(restored, table);
+        assert_eq!(JumpTable::from_bits(table.as_slice(), 9), None);
+
+        // Longer map with the same destinations.
+        let padded = JumpTable::from_bits(table.as_slice(), 8).unwrap();
+        assert_ne!(padded, table);
+        assert!(padded.same_destinations(&table));
+    }
+
+    #[test]
+    fn analyze_fast_paths() {
+        // no JUMPDEST.
+        let table = JumpTable::analyze(&[0x60, 0x01, 0x56, 0x00]);
+        assert_eq!(table.len(), 4);
+        assert_eq!(table.0.count_ones(), 0);
+
+        // no PUSH, every JUMPDEST byte is a destination.
+        let table = JumpTable::analyze(&[0x5b, 0x00, 0x5b, 0x5b, 0x56]);
+        assert_eq!(table.len(), 5);
+        assert_eq!(table.0.iter_ones().collect::<std::vec::Vec<_>>(), [0, 2, 3]);
+        // bytes that differ from JUMPDEST in one bit, across a chunk of eight bytes.
+        let table =
+            JumpTable::analyze(&[0xdb, 0x5b, 0x1b, 0x5a, 0x5f, 0x00, 0x5b, 0xff, 0x5b, 0x5b]);
+        assert_eq!(table.len(), 10);
+        assert_eq!(
+            table.0.iter_ones().collect::<std::vec::Vec<_>>(),
+            [1, 6, 8, 9]
+        );
+
+        // PUSH2 hides the first two JUMPDEST bytes.
+        let table = JumpTable::analyze(&[0x61, 0x5b, 0x5b, 0x5b]);
+        assert_eq!(table.0.iter_ones().collect::<std::vec::Vec<_>>(), [3]);
+    }
 }
@@ -5,6 +5,9 @@ use alloy_primitives::{address, Address};
 /// By default the limit is `0x6000` (~25kb)
 pub const MAX_CODE_SIZE: usize = 0x6000;
 
+/// EVM call stack limit.
+pub const CALL_STACK_LIMIT: u64 = 1024;
+
 /// Number of block hashes that EVM can access in the past (pre-Prague).
 pub const BLOCK_HASH_HISTORY: usize = 256;
 
@@ -31,10 +34,21 @@ pub const BLOCKHASH_STORAGE_ADDRESS: Address = address!("25a219378dad9b3503c8268
 /// Limit of maximum initcode size is `2 * MAX_CODE_SIZE`.
 pub const MAX_INITCODE_SIZE: usize = 2 * MAX_CODE_SIZE;
 
+/// Maximum number of initcontainers in an initcode transaction, see [`crate::TxEnv::initcodes`].
+pub const MAX_INITCODE_COUNT: usize = 256;
+
 /// The address of precompile 3, which is handled specially in a few places.
 pub const PRECOMPILE3: Address =
     Address::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3]);
 
+// === EIP-1559 constants ===
+
+/// Bound divisor of the base fee, limits the change of the base fee between blocks.
+pub const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
+/// Elasticity multiplier, the gas target of the block is its gas limit divided by it.
+pub const ELASTICITY_MULTIPLIER: u64 = 2;
+
 // === EIP-4844 constants ===
 
 /// Gas consumption of a single data blob (== blob byte size).
@@ -2,9 +2,11 @@ use crate::{Account, AccountInfo, Address, Bytecode, HashMap, B256, U256};
 use auto_impl::auto_impl;
 
 pub mod components;
+mod prepared_commit;
 pub use components::{
     BlockHash, BlockHashRef, DatabaseComponentError, DatabaseComponents, State, StateRef,
 };
+pub use prepared_commit::{PrepareCommitError, PreparedCommit};
 
 /// EVM database interface.
 #[auto_impl(&mut, Box)]
@@ -32,6 +34,27 @@ pub trait DatabaseCommit {
     fn commit(&mut self, changes: HashMap<Address, Account>);
 }
 
+/// Two-phase commit interface.
+///
+/// Changes are first validated with [`DatabaseCommit2PC::prepare`] without modifying the
+/// database, and then committed with [`DatabaseCommit2PC::commit_prepared`] or aborted by
+/// dropping the [`PreparedCommit`].
+///
+/// Use [`WrapDatabaseCommit`] to provide the implementation for a [`DatabaseCommit`].
+#[auto_impl(&mut, Box)]
+pub trait DatabaseCommit2PC {
+    /// Validates the changes and prepares them for a commit.
+    fn prepare(
+        &self,
+        changes: HashMap<Address, Account>,
+    ) -> Result<PreparedCommit, PrepareCommitError> {
+        PreparedCommit::new(changes)
+    }
+
+    /// Commits the prepared changes to the database.
+    fn commit_prepared(&mut self, prepared: PreparedCommit);
+}
+
 /// EVM database interface.
 ///
 /// Contains the same methods as [`Database`], but with `&self` receivers instead of `&mut self`.
@@ -97,3 +120,45 @@ impl<T: DatabaseRef + DatabaseCommit> DatabaseCommit for WrapDatabaseRef<T> {
         self.0.commit(changes)
     }
 }
+
+/// Wraps a [`DatabaseCommit`] to provide a [`DatabaseCommit2PC`] implementation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WrapDatabaseCommit<T>(pub T);
+
+impl<T: DatabaseCommit> DatabaseCommit2PC for WrapDatabaseCommit<T> {
+    #[inline]
+    fn commit_prepared(&mut self, prepared: PreparedCommit) {
+        self.0.commit(prepared.into_changes())
+    }
+}
+
+impl<T: DatabaseCommit> DatabaseCommit for WrapDatabaseCommit<T> {
+    #[inline]
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.0.commit(changes)
+    }
+}
+
+impl<T: Database> Database for WrapDatabaseCommit<T> {
+    type Error = T::Error;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.0.basic(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.0.code_by_hash(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.0.storage(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.0.block_hash(number)
+    }
+}
@@ -0,0 +1,183 @@
+//! Changes prepared for a two-phase commit.
+use crate::{Address, EvmState, B256, KECCAK_EMPTY};
+use core::fmt;
+
+/// Validated changes of a transaction, ready to be committed with
+/// [`DatabaseCommit2PC::commit_prepared`](super::DatabaseCommit2PC::commit_prepared).
+///
+/// Only touched accounts are kept. Dropping the prepared commit aborts it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PreparedCommit {
+    changes: EvmState,
+    storage_slots: usize,
+    selfdestructs: usize,
+    code_size: usize,
+}
+
+impl PreparedCommit {
+    /// Validates the changes and computes their sizes.
+    ///
+    /// Code of created accounts is checked against its code hash.
+    pub fn new(mut changes: EvmState) -> Result<Self, PrepareCommitError> {
+        changes.retain(|_, account| account.is_touched());
+
+        let mut prepared = Self::default();
+        for (address, account) in &changes {
+            if account.is_selfdestructed() {
+                prepared.selfdestructs += 1;
+                continue;
+            }
+            prepared.storage_slots += account.changed_storage_slots().count();
+            if !account.is_created() {
+                continue;
+            }
+            let Some(code) = account.info.code.as_ref().filter(|code| !code.is_empty()) else {
+                continue;
+            };
+            let expected = code.hash_slow();
+            if account.info.code_hash != expected && account.info.code_hash != KECCAK_EMPTY {
+                return Err(PrepareCommitError::CodeHashMismatch {
+                    address: *address,
+                    code_hash: account.info.code_hash,
+                    expected,
+                });
+            }
+            prepared.code_size += code.len();
+        }
+        prepared.changes = changes;
+        Ok(prepared)
+    }
+
+    /// Returns the touched accounts.
+    #[inline]
+    pub fn changes(&self) -> &EvmState {
+        &self.changes
+    }
+
+    /// Consumes the prepared commit and returns the touched accounts.
+    #[inline]
+    pub fn into_changes(self) -> EvmState {
+        self.changes
+    }
+
+    /// Returns the number of touched accounts.
+    #[inline]
+    pub fn accounts(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Returns the number of changed storage slots.
+    #[inline]
+    pub fn storage_slots(&self) -> usize {
+        self.storage_slots
+    }
+
+    /// Returns the number of selfdestructed accounts.
+    #[inline]
+    pub fn selfdestructs(&self) -> usize {
+        self.selfdestructs
+    }
+
+    /// Returns the size of the code of the created accounts in bytes.
+    #[inline]
+    pub fn code_size(&self) -> usize {
+        self.code_size
+    }
+}
+
+/// Error returned when changes can't be prepared for a commit.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PrepareCommitError {
+    /// Code hash of a created account does not match its code.
+    CodeHashMismatch {
+        address: Address,
+        code_hash: B256,
+        expected: B256,
+    },
+    /// Account was changed but it is not known to the database.
+    AccountNotLoaded(Address),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PrepareCommitError {}
+
+impl fmt::Display for PrepareCommitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CodeHashMismatch {
+                address,
+                code_hash,
+                expected,
+            } => write!(
+                f,
+                "code hash {code_hash} of {address} does not match the hash of its code {expected}"
+            ),
+            Self::AccountNotLoaded(address) => write!(f, "account {address} is not loaded"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Account, AccountInfo, AccountStatus, Bytecode, Bytes, EvmStorageSlot, U256};
+
+    fn account(info: AccountInfo, status: AccountStatus) -> Account {
+        Account {
+            info,
+            storage: [(
+                U256::from(1),
+                EvmStorageSlot::new_changed(U256::ZERO, U256::from(2)),
+            )]
+            .into_iter()
+            .collect(),
+            status: status | AccountStatus::Touched,
+        }
+    }
+
+    #[test]
+    fn prepare_sizes_and_code_hash() {
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x5f, 0x00]));
+        let created = AccountInfo::from_bytecode(code.clone());
+        let mut changes: EvmState = [
+            (
+                Address::with_last_byte(1),
+                account(created.clone(), AccountStatus::Created),
+            ),
+            (
+                Address::with_last_byte(2),
+                account(AccountInfo::default(), AccountStatus::SelfDestructed),
+            ),
+            (
+                Address::with_last_byte(3),
+                account(AccountInfo::default(), AccountStatus::Loaded),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        changes.insert(Address::with_last_byte(4), Account::default());
+
+        let prepared = PreparedCommit::new(changes.clone()).unwrap();
+        assert_eq!(prepared.accounts(), 3);
+        assert_eq!(prepared.storage_slots(), 2);
+        assert_eq!(prepared.selfdestructs(), 1);
+        assert_eq!(prepared.code_size(), 2);
+        assert!(!prepared.changes().contains_key(&Address::with_last_byte(4)));
+
+        let mut invalid = created;
+        invalid.code_hash = B256::with_last_byte(1);
+        changes.insert(
+            Address::with_last_byte(1),
+            account(invalid, AccountStatus::Created),
+        );
+        assert_eq!(
+            PreparedCommit::new(changes),
+            Err(PrepareCommitError::CodeHashMismatch {
+                address: Address::with_last_byte(1),
+                code_hash: B256::with_last_byte(1),
+                expected: code.hash_slow(),
+            })
+        );
+    }
+}
@@ -1,30 +1,59 @@
 pub mod eip7702;
+#[cfg(feature = "alloy-consensus")]
+pub mod envelope;
 pub mod handler_cfg;
+pub mod patch;
+pub mod withdrawal;
 
 pub use eip7702::AuthorizationList;
+#[cfg(feature = "alloy-consensus")]
+pub use envelope::TxDecodeError;
 pub use handler_cfg::{CfgEnvWithHandlerCfg, EnvWithHandlerCfg, HandlerCfg};
+pub use patch::{BlockEnvPatch, CfgEnvPatch, EnvPatch, TxEnvPatch};
+pub use withdrawal::{Withdrawal, WEI_PER_GWEI};
 
 use crate::{
-    calc_blob_gasprice, AccessListItem, Account, Address, Bytes, InvalidHeader, InvalidTransaction,
-    Spec, SpecId, B256, GAS_PER_BLOB, KECCAK_EMPTY, MAX_BLOB_NUMBER_PER_BLOCK, MAX_INITCODE_SIZE,
-    U256, VERSIONED_HASH_VERSION_KZG,
+    calc_blob_gasprice, calc_next_block_base_fee_with_params, keccak256, AccessListItem, Account,
+    Address, Bytecode, Bytes, HashMap, InvalidHeader, InvalidTransaction, Spec, SpecId, B256,
+    BASE_FEE_MAX_CHANGE_DENOMINATOR, CALL_STACK_LIMIT, ELASTICITY_MULTIPLIER, GAS_PER_BLOB,
+    KECCAK_EMPTY, MAX_BLOB_NUMBER_PER_BLOCK, MAX_INITCODE_SIZE, U256, VERSIONED_HASH_VERSION_KZG,
 };
 use alloy_primitives::TxKind;
 use core::cmp::{min, Ordering};
 use core::hash::Hash;
 use std::boxed::Box;
+use std::sync::Arc;
 use std::vec::Vec;
 
 /// EVM environment configuration.
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Env {
     /// Configuration of the EVM itself.
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
     pub cfg: CfgEnv,
     /// Configuration of the block the transaction is in.
     pub block: BlockEnv,
     /// Configuration of the transaction that is being executed.
+    ///
+    /// Prefer [`Env::tx_mut`] for mutation, as changes made through this field are not
+    /// tracked by [`Env::tx_generation`].
+    #[cfg_attr(
+        feature = "deprecated-env-fields",
+        deprecated(note = "use `Env::tx` and `Env::tx_mut` accessors instead")
+    )]
     pub tx: TxEnv,
+    /// Incremented on every mutable access of the transaction through [`Env::tx_mut`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    tx_generation: u64,
+}
+
+/// The transaction generation is not compared.
+impl PartialEq for Env {
+    fn eq(&self, other: &Self) -> bool {
+        self.cfg == other.cfg && self.block == other.block && self.tx() == other.tx()
+    }
 }
 
 impl Env {
@@ -36,18 +65,55 @@ impl Env {
 
     /// Create boxed [Env].
     #[inline]
+    #[allow(deprecated)]
     pub fn boxed(cfg: CfgEnv, block: BlockEnv, tx: TxEnv) -> Box<Self> {
-        Box::new(Self { cfg, block, tx })
+        Box::new(Self {
+            cfg,
+            block,
+            tx,
+            tx_generation: 0,
+        })
+    }
+
+    /// Returns `true` if the amount minted with [`TxEnv::mint`] is kept when the transaction
+    /// halts, see [`CfgEnv::persist_mint_on_halt`]. Optimism deposits always keep it.
+    #[inline]
+    pub fn persist_mint_on_halt(&self) -> bool {
+        self.cfg.persist_mint_on_halt || self.tx().is_deposit()
+    }
+
+    /// Returns reference to the transaction environment.
+    #[inline]
+    #[allow(deprecated)]
+    pub fn tx(&self) -> &TxEnv {
+        &self.tx
+    }
+
+    /// Returns mutable reference to the transaction environment.
+    ///
+    /// Every call bumps [`Env::tx_generation`] so that previously
+    /// done validation of the transaction can be detected as stale.
+    #[inline]
+    #[allow(deprecated)]
+    pub fn tx_mut(&mut self) -> &mut TxEnv {
+        self.tx_generation = self.tx_generation.wrapping_add(1);
+        &mut self.tx
+    }
+
+    /// Returns the generation of the transaction environment.
+    ///
+    /// Generation changes on every [`Env::tx_mut`] call.
+    #[inline]
+    pub fn tx_generation(&self) -> u64 {
+        self.tx_generation
     }
 
     /// Calculates the effective gas price of the transaction.
+    ///
+    /// See [`TxEnv::effective_gas_price`].
     #[inline]
     pub fn effective_gas_price(&self) -> U256 {
-        if let Some(priority_fee) = self.tx.gas_priority_fee {
-            min(self.tx.gas_price, self.block.basefee + priority_fee)
-        } else {
-            self.tx.gas_price
-        }
+        self.tx().effective_gas_price(self.block.basefee)
     }
 
     /// Calculates the [EIP-4844] `data_fee` of the transaction.
@@ -58,7 +124,7 @@ impl Env {
     #[inline]
     pub fn calc_data_fee(&self) -> Option<U256> {
         self.block.get_blob_gasprice().map(|blob_gas_price| {
-            U256::from(blob_gas_price).saturating_mul(U256::from(self.tx.get_total_blob_gas()))
+            U256::from(blob_gas_price).saturating_mul(U256::from(self.tx().get_total_blob_gas()))
         })
     }
 
@@ -70,8 +136,8 @@ impl Env {
     /// See EIP-4844:
     /// <https://github.com/ethereum/EIPs/blob/master/EIPS/eip-4844.md#execution-layer-validation>
     pub fn calc_max_data_fee(&self) -> Option<U256> {
-        self.tx.max_fee_per_blob_gas.map(|max_fee_per_blob_gas| {
-            max_fee_per_blob_gas.saturating_mul(U256::from(self.tx.get_total_blob_gas()))
+        self.tx().max_fee_per_blob_gas.map(|max_fee_per_blob_gas| {
+            max_fee_per_blob_gas.saturating_mul(U256::from(self.tx().get_total_blob_gas()))
         })
     }
 
@@ -94,29 +160,47 @@ impl Env {
     /// Return initial spend gas (Gas needed to execute transaction).
     #[inline]
     pub fn validate_tx<SPEC: Spec>(&self) -> Result<(), InvalidTransaction> {
+        // Default destination of the transaction is the zero address.
+        if self.cfg.require_transact_to && self.tx().transact_to.is_none() {
+            return Err(InvalidTransaction::TransactToNotSet);
+        }
+
+        if self.tx().mint.is_some() && !self.cfg.allow_mint {
+            return Err(InvalidTransaction::MintNotAllowed);
+        }
+
         // Check if the transaction's chain id is correct
-        if let Some(tx_chain_id) = self.tx.chain_id {
+        if let Some(tx_chain_id) = self.tx().chain_id {
             if tx_chain_id != self.cfg.chain_id {
                 return Err(InvalidTransaction::InvalidChainId);
             }
         }
 
-        // Check if gas_limit is more than block_gas_limit
-        if !self.cfg.is_block_gas_limit_disabled()
-            && U256::from(self.tx.gas_limit) > self.block.gas_limit
-        {
-            return Err(InvalidTransaction::CallerGasLimitMoreThanBlock);
+        // Check if gas_limit is more than the cap, block_gas_limit by default
+        if !self.cfg.is_block_gas_limit_disabled() {
+            match self.cfg.tx_gas_limit_cap {
+                Some(cap) if self.tx().gas_limit > cap => {
+                    return Err(InvalidTransaction::CallerGasLimitMoreThanCap {
+                        gas_limit: self.tx().gas_limit,
+                        cap,
+                    });
+                }
+                None if U256::from(self.tx().gas_limit) > self.block.gas_limit => {
+                    return Err(InvalidTransaction::CallerGasLimitMoreThanBlock);
+                }
+                _ => {}
+            }
         }
 
         // Check that access list is empty for transactions before BERLIN
-        if !SPEC::enabled(SpecId::BERLIN) && !self.tx.access_list.is_empty() {
+        if !SPEC::enabled(SpecId::BERLIN) && !self.tx().access_list.is_empty() {
             return Err(InvalidTransaction::AccessListNotSupported);
         }
 
         // BASEFEE tx check
         if SPEC::enabled(SpecId::LONDON) {
-            if let Some(priority_fee) = self.tx.gas_priority_fee {
-                if priority_fee > self.tx.gas_price {
+            if let Some(priority_fee) = self.tx().gas_priority_fee {
+                if priority_fee > self.tx().gas_price {
                     // or gas_max_fee for eip1559
                     return Err(InvalidTransaction::PriorityFeeGreaterThanMaxFee);
                 }
@@ -131,80 +215,46 @@ impl Env {
         }
 
         // EIP-3860: Limit and meter initcode
-        if SPEC::enabled(SpecId::SHANGHAI) && self.tx.transact_to.is_create() {
-            let max_initcode_size = self
-                .cfg
-                .limit_contract_code_size
-                .map(|limit| limit.saturating_mul(2))
-                .unwrap_or(MAX_INITCODE_SIZE);
-            if self.tx.data.len() > max_initcode_size {
-                return Err(InvalidTransaction::CreateInitCodeSizeLimit);
-            }
-        }
-
-        // - For before CANCUN, check that `blob_hashes` and `max_fee_per_blob_gas` are empty / not set
-        if !SPEC::enabled(SpecId::CANCUN)
-            && (self.tx.max_fee_per_blob_gas.is_some() || !self.tx.blob_hashes.is_empty())
+        // This applies to EOF create transactions as well.
+        if SPEC::enabled(SpecId::SHANGHAI)
+            && self.tx().is_create()
+            && self.tx().data.len() > self.cfg.max_initcode_size()
         {
-            return Err(InvalidTransaction::BlobVersionedHashesNotSupported);
+            return Err(InvalidTransaction::CreateInitCodeSizeLimit);
         }
 
-        // Presence of max_fee_per_blob_gas means that this is blob transaction.
-        if let Some(max) = self.tx.max_fee_per_blob_gas {
-            // ensure that the user was willing to at least pay the current blob gasprice
-            let price = self.block.get_blob_gasprice().expect("already checked");
-            if U256::from(price) > max {
-                return Err(InvalidTransaction::BlobGasPriceGreaterThanMax);
-            }
-
-            // there must be at least one blob
-            if self.tx.blob_hashes.is_empty() {
-                return Err(InvalidTransaction::EmptyBlobs);
-            }
-
-            // The field `to` deviates slightly from the semantics with the exception
-            // that it MUST NOT be nil and therefore must always represent
-            // a 20-byte address. This means that blob transactions cannot
-            // have the form of a create transaction.
-            if self.tx.transact_to.is_create() {
-                return Err(InvalidTransaction::BlobCreateTransaction);
-            }
-
-            // all versioned blob hashes must start with VERSIONED_HASH_VERSION_KZG
-            for blob in self.tx.blob_hashes.iter() {
-                if blob[0] != VERSIONED_HASH_VERSION_KZG {
-                    return Err(InvalidTransaction::BlobVersionNotSupported);
-                }
-            }
-
-            // ensure the total blob gas spent is at most equal to the limit
-            // assert blob_gas_used <= MAX_BLOB_GAS_PER_BLOCK
-            let num_blobs = self.tx.blob_hashes.len();
-            if num_blobs > MAX_BLOB_NUMBER_PER_BLOCK as usize {
-                return Err(InvalidTransaction::TooManyBlobs {
-                    have: num_blobs,
-                    max: MAX_BLOB_NUMBER_PER_BLOCK as usize,
-                });
-            }
-        } else {
-            // if max_fee_per_blob_gas is not set, then blob_hashes must be empty
-            if !self.tx.blob_hashes.is_empty() {
-                return Err(InvalidTransaction::BlobVersionedHashesNotSupported);
-            }
+        match self.tx().tx_type() {
+            // EIP-4844: Shard Blob Transactions
+            TxType::Eip4844 => self.tx().validate_blob_tx(&self.block, SPEC::SPEC_ID),
+            // EIP-7702: Set EOA account code
+            TxType::Eip7702 => self.validate_eip7702_tx::<SPEC>(),
+            _ => Ok(()),
         }
+    }
 
+    /// Validates the fields of an EIP-7702 transaction.
+    fn validate_eip7702_tx<SPEC: Spec>(&self) -> Result<(), InvalidTransaction> {
         // check if EIP-7702 transaction is enabled.
-        if !SPEC::enabled(SpecId::PRAGUE) && self.tx.authorization_list.is_some() {
+        if !SPEC::enabled(SpecId::PRAGUE) {
             return Err(InvalidTransaction::AuthorizationListNotSupported);
         }
-
-        if self.tx.authorization_list.is_some() {
-            // Check if other fields are unset.
-            if self.tx.max_fee_per_blob_gas.is_some() || !self.tx.blob_hashes.is_empty() {
-                return Err(InvalidTransaction::AuthorizationListInvalidFields);
-            }
+        // Check if other fields are unset.
+        if self.tx().max_fee_per_blob_gas.is_some() || !self.tx().blob_hashes.is_empty() {
+            return Err(InvalidTransaction::AuthorizationListInvalidFields);
+        }
+        // There must be at least one authorization.
+        if self
+            .tx()
+            .authorization_list
+            .as_ref()
+            .is_some_and(|list| list.is_empty())
+        {
+            return Err(InvalidTransaction::EmptyAuthorizationList);
+        }
+        // Same as for blob transactions, `to` must be present.
+        if self.tx().is_create() {
+            return Err(InvalidTransaction::AuthorizationListCreateTransaction);
         }
-
         Ok(())
     }
 
@@ -222,7 +272,7 @@ impl Env {
         }
 
         // Check that the transaction's nonce is correct
-        if let Some(tx) = self.tx.nonce {
+        if let Some(tx) = self.tx().nonce {
             let state = account.info.nonce;
             match tx.cmp(&state) {
                 Ordering::Greater => {
@@ -235,25 +285,21 @@ impl Env {
             }
         }
 
-        let mut balance_check = U256::from(self.tx.gas_limit)
-            .checked_mul(self.tx.gas_price)
-            .and_then(|gas_cost| gas_cost.checked_add(self.tx.value))
+        // Blob fields are rejected before Cancun, so the blob cost is zero there.
+        let balance_check = self
+            .tx()
+            .max_cost()
             .ok_or(InvalidTransaction::OverflowPaymentInTransaction)?;
 
-        if SPEC::enabled(SpecId::CANCUN) {
-            // if the tx is not a blob tx, this will be None, so we add zero
-            let data_fee = self.calc_max_data_fee().unwrap_or_default();
-            balance_check = balance_check
-                .checked_add(U256::from(data_fee))
-                .ok_or(InvalidTransaction::OverflowPaymentInTransaction)?;
-        }
+        // Minted amount is credited before the gas is deducted.
+        let mint = U256::from(self.tx().mint.unwrap_or_default());
 
         // Check if account has enough balance for gas_limit*gas_price and value transfer.
         // Transfer will be done inside `*_inner` functions.
-        if balance_check > account.info.balance {
+        if balance_check > account.info.balance.saturating_add(mint) {
             if self.cfg.is_balance_check_disabled() {
                 // Add transaction cost to balance to ensure execution doesn't fail.
-                account.info.balance = balance_check;
+                account.info.balance = balance_check - mint;
             } else {
                 return Err(InvalidTransaction::LackOfFundForMaxFee {
                     fee: Box::new(balance_check),
@@ -262,6 +308,21 @@ impl Env {
             }
         }
 
+        // Mint is taken back if the transaction halts, unless `persist_mint_on_halt` is set,
+        // so the gas must be paid without it.
+        if mint > U256::ZERO
+            && !self.persist_mint_on_halt()
+            && !self.cfg.is_balance_check_disabled()
+        {
+            let max_fee = balance_check - self.tx().value;
+            if max_fee > account.info.balance {
+                return Err(InvalidTransaction::LackOfFundForMaxFee {
+                    fee: Box::new(max_fee),
+                    balance: Box::new(account.info.balance),
+                });
+            }
+        }
+
         Ok(())
     }
 }
@@ -286,6 +347,9 @@ pub struct CfgEnv {
     /// If some it will effects EIP-170: Contract code size limit. Useful to increase this because of tests.
     /// By default it is 0x6000 (~25kb).
     pub limit_contract_code_size: Option<usize>,
+    /// If some it will override the maximum depth of the call stack. Useful to lower it in tests.
+    /// By default it is [`CALL_STACK_LIMIT`] (1024).
+    pub call_stack_limit: Option<u64>,
     /// A hard memory limit in bytes beyond which [crate::result::OutOfGasError::Memory] cannot be resized.
     ///
     /// In cases where the gas limit may be extraordinarily high, it is recommended to set this to
@@ -301,6 +365,17 @@ pub struct CfgEnv {
     /// By default, it is set to `false`.
     #[cfg(feature = "optional_block_gas_limit")]
     pub disable_block_gas_limit: bool,
+    /// If some it replaces the block gas limit as the maximum gas limit of a transaction.
+    /// Useful for simulations that need more gas than the block allows, without changing
+    /// the block environment. Not checked if the block gas limit is disabled.
+    /// By default it is `None` and the block gas limit is used.
+    pub tx_gas_limit_cap: Option<u64>,
+    /// If some it caps the gas limit of every call and create frame except the first one,
+    /// whose gas limit is the transaction gas limit. Gas above the cap is not available to the
+    /// frame and is returned to the parent when the frame ends. Useful to simulate clients that
+    /// forward less gas, or to limit the damage of huge gas limits in sandboxed execution.
+    /// By default it is `None` and frames get the gas limit of their inputs.
+    pub max_frame_gas: Option<u64>,
     /// EIP-3607 rejects transactions from senders with deployed code. In development, it can be desirable to simulate
     /// calls from contracts, which this setting allows.
     /// By default, it is set to `false`.
@@ -320,14 +395,304 @@ pub struct CfgEnv {
     /// By default, it is set to `false`.
     #[cfg(feature = "optional_beneficiary_reward")]
     pub disable_beneficiary_reward: bool,
+    /// Skips the L1 data fee of Optimism non-deposit transactions, it is neither charged
+    /// nor paid to the L1 fee vault and L1 block info is not loaded. Useful for gas
+    /// estimation and simulation on state without the L1 block info predeploy.
+    /// Deposit transactions are not affected.
+    /// By default, it is set to `false`.
+    #[cfg(feature = "optimism")]
+    pub optimism_no_l1_fee: bool,
+    /// If the transaction is modified after it was pre-verified, `transact_preverified`
+    /// will validate it again instead of returning [`crate::EVMError::StaleValidation`].
+    /// By default, it is set to `false`.
+    pub revalidate_stale_tx: bool,
+    /// Loads code of the access list accounts together with the accounts, before the execution.
+    /// Gas is not affected, it only moves the database access out of the execution.
+    /// By default, it is set to `false`.
+    pub preload_access_list_code: bool,
+    /// Checks that the database returns the same value every time the same account or
+    /// storage slot is read inside one transaction, returning
+    /// [`crate::EVMError::DatabaseInconsistency`] otherwise. Intended for differential testing
+    /// of custom databases.
+    /// By default, it is set to `false`.
+    pub paranoid_db_checks: bool,
+    /// Rejects transactions without [`TxEnv::transact_to`] with
+    /// [`InvalidTransaction::TransactToNotSet`] instead of calling the zero address.
+    /// By default, it is set to `false`.
+    pub require_transact_to: bool,
+    /// Counts the executed opcodes and samples the program counters of every transaction,
+    /// the [`ExecutionProfile`](crate::ExecutionProfile) is returned in
+    /// [`ResultAndState::profile`](crate::ResultAndState::profile).
+    /// By default, it is set to `false`.
+    pub profile_execution: bool,
+    /// Steps between two program counter samples of the execution profile, 0 disables the
+    /// sampling.
+    /// By default, it is set to [`crate::DEFAULT_PROFILE_SAMPLE_INTERVAL`].
+    pub profile_sample_interval: u64,
+    /// Allows transactions to mint balance to the caller with [`TxEnv::mint`].
+    /// By default, it is set to `false`.
+    pub allow_mint: bool,
+    /// Keeps the amount minted with [`TxEnv::mint`] if the transaction halts, as Optimism
+    /// deposits do. Otherwise it is taken back from the caller after the gas is paid, and the
+    /// caller must be able to pay the gas without the minted amount.
+    /// By default, it is set to `true`.
+    pub persist_mint_on_halt: bool,
+    /// EIP-1559 parameters of the chain, used to derive the base fee of the next block
+    /// with [`BlockEnv::next_base_fee`]. If `None` chain default is used, for Ethereum
+    /// this is [`Eip1559Params::MAINNET`].
+    pub eip1559_params: Option<Eip1559Params>,
+    /// Values returned by the `COINBASE`, `ORIGIN`, `GASPRICE` and `CHAINID` opcodes instead
+    /// of the ones from the environment. Only the opcodes observe them, validation, fee
+    /// deduction and beneficiary reward still use the real environment.
+    /// By default, it is set to `None`.
+    pub opcode_overrides: Option<OpcodeEnvOverrides>,
+    /// Gas costs that replace the constants of the spec, for chains that reprice some of the
+    /// operations. Costs that are not overridden keep the value of the spec.
+    /// By default, it is set to `None`.
+    pub gas_overrides: Option<GasScheduleOverrides>,
+    /// Keccak-256 implementation used by the `KECCAK256` opcode, `CREATE2` address derivation
+    /// and hashing of the deployed code. Intended for provers that need to record or replace
+    /// the hashes computed by the EVM. Hash of empty input is always [`KECCAK_EMPTY`].
+    /// By default, it is set to `None` and [`keccak256`] is used.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub keccak_provider: Option<KeccakProvider>,
+    /// Addresses that start the transaction warm, in addition to the ones warmed by the spec.
+    /// Intended for chains with system contracts that are accessed by most of the transactions.
+    /// By default, it is empty.
+    pub extra_warm_addresses: Vec<Address>,
+    /// Contracts that exist at fixed addresses even if the database does not have them, like
+    /// the system contracts of a chain.
+    ///
+    /// The code is installed into the journaled state when the account is loaded and the
+    /// accounts start the transaction warm. Addresses that have code in the database are
+    /// skipped, the database is not written unless the transaction changes the account.
+    /// By default, it is set to `None`.
+    pub predeploys: Option<Predeploys>,
+    /// Rejects precompile outputs larger than the gas limit of the call can pay for as out of
+    /// gas, see `revm_precompile::max_output_len`. Precompiles of the spec never exceed the
+    /// bound, it only affects custom precompiles.
+    /// By default, it is set to `false`.
+    pub bound_precompile_output: bool,
+    /// Runs the first frame as a static call, so any state change halts with
+    /// [`HaltReason::StateChangeDuringStaticCall`](crate::HaltReason::StateChangeDuringStaticCall),
+    /// as do create transactions and transactions that transfer value. The caller is neither
+    /// charged nor has its nonce bumped, and the beneficiary is not rewarded.
+    /// By default, it is set to `false`.
+    pub force_static: bool,
+}
+
+/// Keccak-256 implementation, see [`CfgEnv::keccak_provider`].
+#[derive(Clone, Copy, Debug)]
+pub struct KeccakProvider(pub fn(&[u8]) -> B256);
+
+impl KeccakProvider {
+    /// Hashes the `bytes`.
+    #[inline]
+    pub fn hash(&self, bytes: &[u8]) -> B256 {
+        (self.0)(bytes)
+    }
+
+    /// Returns the `CREATE2` address of `deployer` with the `salt` and `init_code_hash`.
+    #[inline]
+    pub fn create2(&self, deployer: Address, salt: U256, init_code_hash: B256) -> Address {
+        // keccak256(0xff ++ deployer ++ salt ++ init_code_hash)[12..]
+        let mut preimage = [0u8; 85];
+        preimage[0] = 0xff;
+        preimage[1..21].copy_from_slice(deployer.as_slice());
+        preimage[21..53].copy_from_slice(&salt.to_be_bytes::<32>());
+        preimage[53..85].copy_from_slice(init_code_hash.as_slice());
+        Address::from_word(self.hash(&preimage))
+    }
+}
+
+impl PartialEq for KeccakProvider {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 as usize == other.0 as usize
+    }
+}
+
+impl Eq for KeccakProvider {}
+
+/// Contracts at fixed addresses, see [`CfgEnv::predeploys`].
+///
+/// Code hashes are computed once when the contracts are added, clones share the contracts.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        from = "HashMap<Address, Bytecode>",
+        into = "HashMap<Address, Bytecode>"
+    )
+)]
+pub struct Predeploys(Arc<HashMap<Address, (B256, Bytecode)>>);
+
+impl Predeploys {
+    /// Returns the code hash and the code of the contract at the `address`.
+    #[inline]
+    pub fn get(&self, address: &Address) -> Option<(B256, &Bytecode)> {
+        self.0.get(address).map(|(hash, code)| (*hash, code))
+    }
+
+    /// Returns `true` if there is a contract at the `address`.
+    #[inline]
+    pub fn contains(&self, address: &Address) -> bool {
+        self.0.contains_key(address)
+    }
+
+    /// Returns the addresses and the code of the contracts.
+    pub fn iter(&self) -> impl Iterator<Item = (&Address, &Bytecode)> {
+        self.0.iter().map(|(address, (_, code))| (address, code))
+    }
+}
+
+impl FromIterator<(Address, Bytecode)> for Predeploys {
+    fn from_iter<T: IntoIterator<Item = (Address, Bytecode)>>(iter: T) -> Self {
+        Self(Arc::new(
+            iter.into_iter()
+                .map(|(address, code)| (address, (code.hash_slow(), code)))
+                .collect(),
+        ))
+    }
+}
+
+impl From<HashMap<Address, Bytecode>> for Predeploys {
+    fn from(contracts: HashMap<Address, Bytecode>) -> Self {
+        contracts.into_iter().collect()
+    }
+}
+
+impl From<Predeploys> for HashMap<Address, Bytecode> {
+    fn from(predeploys: Predeploys) -> Self {
+        predeploys
+            .iter()
+            .map(|(address, code)| (*address, code.clone()))
+            .collect()
+    }
+}
+
+/// Overrides of the environment values observed by the env-reading opcodes.
+///
+/// See [`CfgEnv::opcode_overrides`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpcodeEnvOverrides {
+    /// Value returned by the `COINBASE` opcode.
+    pub coinbase: Option<Address>,
+    /// Value returned by the `ORIGIN` opcode.
+    pub origin: Option<Address>,
+    /// Value returned by the `GASPRICE` opcode.
+    pub gas_price: Option<U256>,
+    /// Value returned by the `CHAINID` opcode.
+    pub chain_id: Option<u64>,
+}
+
+/// Gas costs that replace the constants of the spec, `None` keeps the cost of the spec.
+///
+/// See [`CfgEnv::gas_overrides`]. `SSTORE` refunds that the spec derives from the storage
+/// costs follow the overridden costs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GasScheduleOverrides {
+    /// Cost of the first access to an account in the transaction (EIP-2929), 2600.
+    pub cold_account_access: Option<u64>,
+    /// Cost of the first access to a storage slot in the transaction (EIP-2929), 2100.
+    pub cold_sload: Option<u64>,
+    /// Cost of an access to a warm account or storage slot (EIP-2929), 100.
+    pub warm_storage_read: Option<u64>,
+    /// Cost of `SSTORE` that sets a zero slot to a non zero value, 20000.
+    pub sstore_set: Option<u64>,
+    /// Cost of `SSTORE` that changes a non zero slot, 5000. Since Berlin the cold storage
+    /// access cost is subtracted from it.
+    pub sstore_reset: Option<u64>,
+    /// Cost of `CREATE`, `CREATE2` and of the contract creation transaction, 32000.
+    pub create: Option<u64>,
+    /// Cost of every byte of the deployed code, 200.
+    pub code_deposit: Option<u64>,
+    /// Cost of every zero byte of the transaction data, 4.
+    pub tx_data_zero: Option<u64>,
+    /// Cost of every non zero byte of the transaction data, 16 (68 before Istanbul).
+    pub tx_data_non_zero: Option<u64>,
+    /// Cost of every address in the access list of the transaction, 2400.
+    pub access_list_address: Option<u64>,
+    /// Cost of every storage key in the access list of the transaction, 1900.
+    pub access_list_storage_key: Option<u64>,
+    /// Cost of `LOG0`-`LOG4`, 375.
+    pub log: Option<u64>,
+    /// Cost of every topic of the log, 375.
+    pub log_topic: Option<u64>,
+    /// Cost of every byte of the log data, 8.
+    pub log_data: Option<u64>,
+    /// Cost of every word of memory expansion, 3. The quadratic part is not affected.
+    pub memory_word: Option<u64>,
+    /// Cost of every byte of the `EXP` exponent, 50 (10 before Spurious Dragon).
+    pub exp_byte: Option<u64>,
+    /// Cost of transferring value with a call, 9000.
+    pub call_value: Option<u64>,
+    /// Cost of a call or `SELFDESTRUCT` that creates a new account, 25000.
+    pub new_account: Option<u64>,
+}
+
+impl GasScheduleOverrides {
+    /// Overrides that keep every cost of the spec.
+    pub const NONE: Self = Self {
+        cold_account_access: None,
+        cold_sload: None,
+        warm_storage_read: None,
+        sstore_set: None,
+        sstore_reset: None,
+        create: None,
+        code_deposit: None,
+        tx_data_zero: None,
+        tx_data_non_zero: None,
+        access_list_address: None,
+        access_list_storage_key: None,
+        log: None,
+        log_topic: None,
+        log_data: None,
+        memory_word: None,
+        exp_byte: None,
+        call_value: None,
+        new_account: None,
+    };
 }
 
 impl CfgEnv {
+    /// Hashes the `bytes` with the [`CfgEnv::keccak_provider`] if it is set.
+    #[inline]
+    pub fn keccak256(&self, bytes: &[u8]) -> B256 {
+        match self.keccak_provider {
+            Some(provider) => provider.hash(bytes),
+            None => crate::keccak256(bytes),
+        }
+    }
+
+    /// Returns the [`CfgEnv::gas_overrides`], [`GasScheduleOverrides::NONE`] if there are none.
+    #[inline]
+    pub fn gas_schedule_overrides(&self) -> &GasScheduleOverrides {
+        self.gas_overrides
+            .as_ref()
+            .unwrap_or(&GasScheduleOverrides::NONE)
+    }
+
     pub fn with_chain_id(mut self, chain_id: u64) -> Self {
         self.chain_id = chain_id;
         self
     }
 
+    /// Returns the max initcode size (EIP-3860), double of the max contract code size.
+    pub fn max_initcode_size(&self) -> usize {
+        self.limit_contract_code_size
+            .map(|limit| limit.saturating_mul(2))
+            .unwrap_or(MAX_INITCODE_SIZE)
+    }
+
+    /// Returns the maximum depth of the call stack.
+    pub fn max_call_depth(&self) -> u64 {
+        self.call_stack_limit.unwrap_or(CALL_STACK_LIMIT)
+    }
+
     #[cfg(feature = "optional_eip3607")]
     pub fn is_eip3607_disabled(&self) -> bool {
         self.disable_eip3607
@@ -395,6 +760,7 @@ impl Default for CfgEnv {
             chain_id: 1,
             perf_analyse_created_bytecodes: AnalysisKind::default(),
             limit_contract_code_size: None,
+            call_stack_limit: None,
             #[cfg(feature = "c-kzg")]
             kzg_settings: crate::kzg::EnvKzgSettings::Default,
             #[cfg(feature = "memory_limit")]
@@ -403,6 +769,8 @@ impl Default for CfgEnv {
             disable_balance_check: false,
             #[cfg(feature = "optional_block_gas_limit")]
             disable_block_gas_limit: false,
+            tx_gas_limit_cap: None,
+            max_frame_gas: None,
             #[cfg(feature = "optional_eip3607")]
             disable_eip3607: false,
             #[cfg(feature = "optional_gas_refund")]
@@ -411,6 +779,24 @@ impl Default for CfgEnv {
             disable_base_fee: false,
             #[cfg(feature = "optional_beneficiary_reward")]
             disable_beneficiary_reward: false,
+            #[cfg(feature = "optimism")]
+            optimism_no_l1_fee: false,
+            revalidate_stale_tx: false,
+            preload_access_list_code: false,
+            paranoid_db_checks: false,
+            require_transact_to: false,
+            profile_execution: false,
+            profile_sample_interval: crate::DEFAULT_PROFILE_SAMPLE_INTERVAL,
+            allow_mint: false,
+            persist_mint_on_halt: true,
+            eip1559_params: None,
+            opcode_overrides: None,
+            gas_overrides: None,
+            keccak_provider: None,
+            extra_warm_addresses: Vec::new(),
+            predeploys: None,
+            bound_precompile_output: false,
+            force_static: false,
         }
     }
 }
@@ -418,6 +804,7 @@ impl Default for CfgEnv {
 /// The block environment.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct BlockEnv {
     /// The number of ancestor blocks of this block (block height).
     pub number: U256,
@@ -456,7 +843,55 @@ pub struct BlockEnv {
     pub blob_excess_gas_and_price: Option<BlobExcessGasAndPrice>,
 }
 
+/// Parameters of the [EIP-1559] base fee calculation.
+///
+/// [EIP-1559]: https://eips.ethereum.org/EIPS/eip-1559
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Eip1559Params {
+    /// Bound divisor of the base fee, limits the change of the base fee between blocks.
+    pub max_change_denominator: u64,
+    /// Gas target of the block is its gas limit divided by the elasticity multiplier.
+    pub elasticity_multiplier: u64,
+}
+
+impl Eip1559Params {
+    /// Ethereum mainnet parameters.
+    pub const MAINNET: Self = Self {
+        max_change_denominator: BASE_FEE_MAX_CHANGE_DENOMINATOR,
+        elasticity_multiplier: ELASTICITY_MULTIPLIER,
+    };
+}
+
+impl Default for Eip1559Params {
+    fn default() -> Self {
+        Self::MAINNET
+    }
+}
+
 impl BlockEnv {
+    /// Returns the base fee of the next block, calculated from the `gas_used` in this block.
+    ///
+    /// See [`calc_next_block_base_fee_with_params`].
+    #[inline]
+    pub fn next_base_fee(&self, gas_used: u64, params: Eip1559Params) -> U256 {
+        U256::from(calc_next_block_base_fee_with_params(
+            gas_used,
+            self.gas_limit.saturating_to(),
+            self.basefee.saturating_to(),
+            params,
+        ))
+    }
+
+    /// Returns the blob gas price of a block with the given `excess_blob_gas`.
+    ///
+    /// Useful for estimating `max_fee_per_blob_gas` of a transaction for a future block,
+    /// see [`crate::calc_excess_blob_gas`] for the excess blob gas of the next block.
+    #[inline]
+    pub fn blob_gasprice_for_excess(excess_blob_gas: u64) -> u128 {
+        calc_blob_gasprice(excess_blob_gas)
+    }
+
     /// Takes `blob_excess_gas` saves it inside env
     /// and calculates `blob_fee` with [`BlobExcessGasAndPrice`].
     pub fn set_blob_excess_gas_and_price(&mut self, excess_blob_gas: u64) {
@@ -511,6 +946,7 @@ impl Default for BlockEnv {
 /// The transaction environment.
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct TxEnv {
     /// Caller aka Author aka transaction signer.
     pub caller: Address,
@@ -518,8 +954,11 @@ pub struct TxEnv {
     pub gas_limit: u64,
     /// The gas price of the transaction.
     pub gas_price: U256,
-    /// The destination of the transaction.
-    pub transact_to: TxKind,
+    /// The destination of the transaction, see [`TxEnv::kind`].
+    ///
+    /// If it is not set the transaction calls the zero address, unless
+    /// [`CfgEnv::require_transact_to`] rejects it.
+    pub transact_to: Option<TxKind>,
     /// The value sent to `transact_to`.
     pub value: U256,
     /// The data of the transaction.
@@ -572,22 +1011,245 @@ pub struct TxEnv {
     /// Set EOA account code for one transaction
     ///
     /// [EIP-Set EOA account code for one transaction](https://eips.ethereum.org/EIPS/eip-7702)
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
     pub authorization_list: Option<AuthorizationList>,
 
+    /// Custom transaction type identifier.
+    ///
+    /// Used by chains that have their own special transaction types. If set, handler
+    /// hooks registered for this type are used instead of the standard ones.
+    pub custom_tx_type: Option<u8>,
+
+    /// EOF initcontainers of the initcode transaction, referenced by their keccak256 hash
+    /// in the `TXCREATE` instruction.
+    ///
+    /// Experimental, the instruction is enabled with the `eof-txcreate` feature of
+    /// `revm-interpreter`.
+    pub initcodes: Vec<Bytes>,
+
+    /// Amount minted to the caller before the execution, allowed only if
+    /// [`CfgEnv::allow_mint`] is set.
+    ///
+    /// The transaction is validated and charged as usual with the minted amount added to the
+    /// balance of the caller. Optimism deposits mint with this field too, unconditionally and
+    /// even if the deposit fails since it is pre-paid on L1.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub mint: Option<u128>,
+
     #[cfg_attr(feature = "serde", serde(flatten))]
     #[cfg(feature = "optimism")]
     /// Optimism fields.
     pub optimism: OptimismFields,
 }
 
+/// Type of the transaction, as defined by [EIP-2718].
+///
+/// Discriminant is the type byte of the transaction envelope.
+///
+/// [EIP-2718]: https://eips.ethereum.org/EIPS/eip-2718
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
 pub enum TxType {
-    Legacy,
-    Eip1559,
-    BlobTx,
-    EofCreate,
+    /// Legacy transaction, without any of the typed transaction fields.
+    #[default]
+    Legacy = 0,
+    /// [EIP-2930](https://eips.ethereum.org/EIPS/eip-2930) access list transaction.
+    Eip2930 = 1,
+    /// [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559) dynamic fee transaction.
+    Eip1559 = 2,
+    /// [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844) blob transaction.
+    Eip4844 = 3,
+    /// [EIP-7702](https://eips.ethereum.org/EIPS/eip-7702) set code transaction.
+    Eip7702 = 4,
+    /// Optimism deposit transaction.
+    #[cfg(feature = "optimism")]
+    Deposit = 0x7E,
 }
 
 impl TxEnv {
+    /// Returns the initcontainer from [`Self::initcodes`] with the given keccak256 hash.
+    ///
+    /// Initcodes are hashed until one matches, nothing is cached between calls.
+    #[inline]
+    pub fn initcode_by_hash(&self, hash: B256) -> Option<&Bytes> {
+        self.initcodes
+            .iter()
+            .find(|initcode| keccak256(initcode) == hash)
+    }
+
+    /// Returns the type of the transaction, derived from the fields that are set.
+    ///
+    /// The most specific type wins: deposit (`source_hash` is set), EIP-7702
+    /// (`authorization_list` is set), EIP-4844 (any of the blob fields is set),
+    /// EIP-1559 (`gas_priority_fee` is set), EIP-2930 (`access_list` is not empty),
+    /// and legacy otherwise. Fields that are not allowed for the type are rejected
+    /// in [`Env::validate_tx`].
+    pub fn tx_type(&self) -> TxType {
+        #[cfg(feature = "optimism")]
+        if self.optimism.source_hash.is_some() {
+            return TxType::Deposit;
+        }
+        if self.authorization_list.is_some() {
+            TxType::Eip7702
+        } else if self.max_fee_per_blob_gas.is_some() || !self.blob_hashes.is_empty() {
+            TxType::Eip4844
+        } else if self.gas_priority_fee.is_some() {
+            TxType::Eip1559
+        } else if !self.access_list.is_empty() {
+            TxType::Eip2930
+        } else {
+            TxType::Legacy
+        }
+    }
+
+    /// Returns the destination of the transaction, the zero address if
+    /// [`TxEnv::transact_to`] is not set.
+    #[inline]
+    pub fn kind(&self) -> TxKind {
+        self.transact_to.unwrap_or(TxKind::Call(Address::ZERO))
+    }
+
+    /// Returns `true` if the transaction creates a contract.
+    #[inline]
+    pub fn is_create(&self) -> bool {
+        self.kind().is_create()
+    }
+
+    /// Returns `true` if the transaction calls an address.
+    #[inline]
+    pub fn is_call(&self) -> bool {
+        self.kind().is_call()
+    }
+
+    /// Returns the called address, `None` if the transaction creates a contract.
+    #[inline]
+    pub fn to(&self) -> Option<Address> {
+        self.kind().to().copied()
+    }
+
+    /// Returns `true` if this is an Optimism deposit transaction.
+    #[inline]
+    fn is_deposit(&self) -> bool {
+        #[cfg(feature = "optimism")]
+        if self.optimism.source_hash.is_some() {
+            return true;
+        }
+        false
+    }
+
+    /// Returns the gas price paid per unit of gas at the given `basefee`.
+    ///
+    /// For legacy and EIP-2930 transactions this is `gas_price`. For EIP-1559 style
+    /// transactions it is `min(max_fee, basefee + max_priority_fee)`.
+    /// Optimism deposit transactions do not pay for gas, so this is zero for them.
+    #[inline]
+    pub fn effective_gas_price(&self, basefee: U256) -> U256 {
+        if self.is_deposit() {
+            return U256::ZERO;
+        }
+        match self.gas_priority_fee {
+            Some(priority_fee) => min(self.gas_price, basefee.saturating_add(priority_fee)),
+            None => self.gas_price,
+        }
+    }
+
+    /// Returns the fee per unit of gas that goes to the block beneficiary at the given `basefee`.
+    ///
+    /// For legacy and EIP-2930 transactions this is `gas_price - basefee`. For EIP-1559 style
+    /// transactions the priority fee is clamped to `max_fee - basefee`. Both are floored at
+    /// zero, and Optimism deposit transactions always return zero.
+    #[inline]
+    pub fn priority_fee(&self, basefee: U256) -> U256 {
+        self.effective_gas_price(basefee).saturating_sub(basefee)
+    }
+
+    /// Returns the maximum amount of wei the transaction can cost the caller:
+    /// `gas_limit * max_fee + value + max_fee_per_blob_gas * blob_gas`.
+    ///
+    /// Returns `None` on overflow.
+    #[inline]
+    pub fn max_cost(&self) -> Option<U256> {
+        let blob_cost = self
+            .max_fee_per_blob_gas
+            .unwrap_or_default()
+            .checked_mul(U256::from(self.get_total_blob_gas()))?;
+        U256::from(self.gas_limit)
+            .checked_mul(self.gas_price)?
+            .checked_add(self.value)?
+            .checked_add(blob_cost)
+    }
+
+    /// Validates the EIP-4844 fields of the transaction against the block.
+    ///
+    /// Non-blob transactions must have empty `blob_hashes` and no `max_fee_per_blob_gas`.
+    /// Blob transactions must pay at least the blob gas price of the block, call an address,
+    /// and carry between one and [`MAX_BLOB_NUMBER_PER_BLOCK`] versioned hashes with the
+    /// [`VERSIONED_HASH_VERSION_KZG`] version.
+    ///
+    /// This is called by [`Env::validate_tx`].
+    pub fn validate_blob_tx(
+        &self,
+        block: &BlockEnv,
+        spec_id: SpecId,
+    ) -> Result<(), InvalidTransaction> {
+        // - For before CANCUN, check that `blob_hashes` and `max_fee_per_blob_gas` are empty / not set
+        if !spec_id.is_enabled_in(SpecId::CANCUN)
+            && (self.max_fee_per_blob_gas.is_some() || !self.blob_hashes.is_empty())
+        {
+            return Err(InvalidTransaction::BlobVersionedHashesNotSupported);
+        }
+
+        // Presence of max_fee_per_blob_gas means that this is blob transaction.
+        if let Some(max) = self.max_fee_per_blob_gas {
+            // blocks without blob gas price are pre-Cancun blocks.
+            let Some(price) = block.get_blob_gasprice() else {
+                return Err(InvalidTransaction::BlobVersionedHashesNotSupported);
+            };
+            // ensure that the user was willing to at least pay the current blob gasprice
+            if U256::from(price) > max {
+                return Err(InvalidTransaction::BlobGasPriceGreaterThanMax);
+            }
+
+            // there must be at least one blob
+            if self.blob_hashes.is_empty() {
+                return Err(InvalidTransaction::EmptyBlobs);
+            }
+
+            // The field `to` deviates slightly from the semantics with the exception
+            // that it MUST NOT be nil and therefore must always represent
+            // a 20-byte address. This means that blob transactions cannot
+            // have the form of a create transaction.
+            if self.is_create() {
+                return Err(InvalidTransaction::BlobCreateTransaction);
+            }
+
+            // all versioned blob hashes must start with VERSIONED_HASH_VERSION_KZG
+            for blob in self.blob_hashes.iter() {
+                if blob[0] != VERSIONED_HASH_VERSION_KZG {
+                    return Err(InvalidTransaction::BlobVersionNotSupported);
+                }
+            }
+
+            // ensure the total blob gas spent is at most equal to the limit
+            // assert blob_gas_used <= MAX_BLOB_GAS_PER_BLOCK
+            let num_blobs = self.blob_hashes.len();
+            if num_blobs > MAX_BLOB_NUMBER_PER_BLOCK as usize {
+                return Err(InvalidTransaction::TooManyBlobs {
+                    have: num_blobs,
+                    max: MAX_BLOB_NUMBER_PER_BLOCK as usize,
+                });
+            }
+        } else {
+            // if max_fee_per_blob_gas is not set, then blob_hashes must be empty
+            if !self.blob_hashes.is_empty() {
+                return Err(InvalidTransaction::BlobVersionedHashesNotSupported);
+            }
+        }
+
+        Ok(())
+    }
+
     /// See [EIP-4844], [`Env::calc_data_fee`], and [`Env::calc_max_data_fee`].
     ///
     /// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
@@ -610,7 +1272,8 @@ impl Default for TxEnv {
             gas_limit: u64::MAX,
             gas_price: U256::ZERO,
             gas_priority_fee: None,
-            transact_to: TxKind::Call(Address::ZERO), // will do nothing
+            // calls the zero address, see `CfgEnv::require_transact_to` to reject it.
+            transact_to: None,
             value: U256::ZERO,
             data: Bytes::new(),
             chain_id: None,
@@ -619,6 +1282,9 @@ impl Default for TxEnv {
             blob_hashes: Vec::new(),
             max_fee_per_blob_gas: None,
             authorization_list: None,
+            custom_tx_type: None,
+            initcodes: Vec::new(),
+            mint: None,
             #[cfg(feature = "optimism")]
             optimism: OptimismFields::default(),
         }
@@ -650,10 +1316,19 @@ impl BlobExcessGasAndPrice {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for BlobExcessGasAndPrice {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        // blob gas price is derived from the excess blob gas.
+        Ok(Self::new(u.arbitrary()?))
+    }
+}
+
 /// Additional [TxEnv] fields for optimism.
 #[cfg(feature = "optimism")]
 #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct OptimismFields {
     /// The source hash is used to make sure that deposit transactions do
     /// not have identical hashes.
@@ -667,11 +1342,6 @@ pub struct OptimismFields {
     /// These two deposit transaction sources specify a domain in the outer
     /// hash so there are no collisions.
     pub source_hash: Option<B256>,
-    /// The amount to increase the balance of the `from` account as part of
-    /// a deposit transaction. This is unconditional and is applied to the
-    /// `from` account even if the deposit transaction fails since
-    /// the deposit is pre-paid on L1.
-    pub mint: Option<u128>,
     /// Whether or not the transaction is a system transaction.
     pub is_system_transaction: Option<bool>,
     /// An enveloped EIP-2718 typed transaction. This is used
@@ -709,16 +1379,105 @@ pub enum AnalysisKind {
     /// Perform bytecode analysis.
     #[default]
     Analyse,
+    /// Do not perform bytecode analysis, jump destinations of raw bytecode are found on the
+    /// first jump of every frame that executes it.
+    ///
+    /// Created code that never jumps is never analyzed.
+    Lazy,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn blob_tx() -> TxEnv {
+        let mut blob_hash = B256::repeat_byte(0x11);
+        blob_hash[0] = VERSIONED_HASH_VERSION_KZG;
+        TxEnv {
+            transact_to: Some(TxKind::Call(Address::with_last_byte(1))),
+            blob_hashes: vec![blob_hash; 2],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_blob_tx_at_price_boundary() {
+        let mut block = BlockEnv::default();
+        block.set_blob_excess_gas_and_price(10_000_000);
+        let price = BlockEnv::blob_gasprice_for_excess(10_000_000);
+        assert!(price > 1);
+        assert_eq!(block.get_blob_gasprice(), Some(price));
+
+        let mut tx = blob_tx();
+        tx.max_fee_per_blob_gas = Some(U256::from(price));
+        assert_eq!(tx.validate_blob_tx(&block, SpecId::CANCUN), Ok(()));
+
+        tx.max_fee_per_blob_gas = Some(U256::from(price - 1));
+        assert_eq!(
+            tx.validate_blob_tx(&block, SpecId::CANCUN),
+            Err(InvalidTransaction::BlobGasPriceGreaterThanMax)
+        );
+
+        // blobs are not supported before Cancun.
+        tx.max_fee_per_blob_gas = Some(U256::from(price));
+        assert_eq!(
+            tx.validate_blob_tx(&block, SpecId::SHANGHAI),
+            Err(InvalidTransaction::BlobVersionedHashesNotSupported)
+        );
+    }
+
+    #[test]
+    fn test_blob_create_tx() {
+        let mut tx = blob_tx();
+        tx.transact_to = Some(TxKind::Create);
+        tx.max_fee_per_blob_gas = Some(U256::from(1));
+
+        let mut env = Env::default();
+        *env.tx_mut() = tx;
+        assert_eq!(
+            env.validate_tx::<crate::CancunSpec>(),
+            Err(InvalidTransaction::BlobCreateTransaction)
+        );
+        assert_eq!(
+            env.tx().validate_blob_tx(&env.block, SpecId::CANCUN),
+            Err(InvalidTransaction::BlobCreateTransaction)
+        );
+    }
+
+    #[test]
+    fn test_next_base_fee() {
+        let parent = BlockEnv {
+            gas_limit: U256::from(30_000_000),
+            basefee: U256::from(1_000_000_000),
+            ..Default::default()
+        };
+        let op = Eip1559Params {
+            max_change_denominator: 250,
+            elasticity_multiplier: 6,
+        };
+
+        // mainnet target is 15M gas, full block increases the base fee by 1/8.
+        assert_eq!(
+            parent.next_base_fee(30_000_000, Eip1559Params::MAINNET),
+            U256::from(1_125_000_000)
+        );
+        // OP target is 5M gas, full block increases the base fee by 5/250.
+        assert_eq!(
+            parent.next_base_fee(30_000_000, op),
+            U256::from(1_020_000_000)
+        );
+        // 5M gas is below the mainnet target and at the OP target.
+        assert_eq!(
+            parent.next_base_fee(5_000_000, Eip1559Params::MAINNET),
+            U256::from(1_000_000_000 - 1_000_000_000 / 8 * 10 / 15)
+        );
+        assert_eq!(parent.next_base_fee(5_000_000, op), parent.basefee);
+    }
+
     #[test]
     fn test_validate_tx_chain_id() {
         let mut env = Env::default();
-        env.tx.chain_id = Some(1);
+        env.tx_mut().chain_id = Some(1);
         env.cfg.chain_id = 2;
         assert_eq!(
             env.validate_tx::<crate::LatestSpec>(),
@@ -726,10 +1485,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_tx_transact_to_not_set() {
+        let mut env = Env::default();
+        assert_eq!(env.tx().to(), Some(Address::ZERO));
+        assert_eq!(env.validate_tx::<crate::LatestSpec>(), Ok(()));
+
+        env.cfg.require_transact_to = true;
+        assert_eq!(
+            env.validate_tx::<crate::LatestSpec>(),
+            Err(InvalidTransaction::TransactToNotSet)
+        );
+
+        env.tx_mut().transact_to = Some(TxKind::Call(Address::with_last_byte(1)));
+        assert!(env.tx().is_call());
+        assert_eq!(env.validate_tx::<crate::LatestSpec>(), Ok(()));
+
+        env.tx_mut().transact_to = Some(TxKind::Create);
+        assert!(env.tx().is_create());
+        assert_eq!(env.tx().to(), None);
+        assert_eq!(env.validate_tx::<crate::LatestSpec>(), Ok(()));
+
+        // explicit call to the zero address is valid.
+        env.tx_mut().transact_to = Some(TxKind::Call(Address::ZERO));
+        assert_eq!(env.validate_tx::<crate::LatestSpec>(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_tx_gas_limit_cap() {
+        let mut env = Env::default();
+        env.block.gas_limit = U256::from(30_000_000);
+        env.tx_mut().gas_limit = u64::MAX;
+        assert_eq!(
+            env.validate_tx::<crate::LatestSpec>(),
+            Err(InvalidTransaction::CallerGasLimitMoreThanBlock)
+        );
+
+        // cap replaces the block gas limit.
+        env.cfg.tx_gas_limit_cap = Some(u64::MAX);
+        assert_eq!(env.validate_tx::<crate::LatestSpec>(), Ok(()));
+
+        env.cfg.tx_gas_limit_cap = Some(20_000_000);
+        env.tx_mut().gas_limit = 25_000_000;
+        assert_eq!(
+            env.validate_tx::<crate::LatestSpec>(),
+            Err(InvalidTransaction::CallerGasLimitMoreThanCap {
+                gas_limit: 25_000_000,
+                cap: 20_000_000
+            })
+        );
+
+        #[cfg(feature = "optional_block_gas_limit")]
+        {
+            env.cfg.disable_block_gas_limit = true;
+            assert_eq!(env.validate_tx::<crate::LatestSpec>(), Ok(()));
+        }
+    }
+
     #[test]
     fn test_validate_tx_access_list() {
         let mut env = Env::default();
-        env.tx.access_list = vec![AccessListItem {
+        env.tx_mut().access_list = vec![AccessListItem {
             address: Address::ZERO,
             storage_keys: vec![],
         }];
@@ -738,4 +1554,165 @@ mod tests {
             Err(InvalidTransaction::AccessListNotSupported)
         );
     }
+
+    #[test]
+    fn test_tx_type() {
+        let access_list = vec![AccessListItem {
+            address: Address::ZERO,
+            storage_keys: vec![],
+        }];
+        assert_eq!(TxEnv::default().tx_type(), TxType::Legacy);
+        let tx = TxEnv {
+            access_list: access_list.clone(),
+            ..Default::default()
+        };
+        assert_eq!(tx.tx_type(), TxType::Eip2930);
+        let tx = TxEnv {
+            access_list,
+            gas_priority_fee: Some(U256::from(1)),
+            ..Default::default()
+        };
+        assert_eq!(tx.tx_type(), TxType::Eip1559);
+        assert_eq!(blob_tx().tx_type(), TxType::Eip4844);
+        let tx = TxEnv {
+            max_fee_per_blob_gas: Some(U256::from(1)),
+            ..Default::default()
+        };
+        assert_eq!(tx.tx_type(), TxType::Eip4844);
+        let tx = TxEnv {
+            authorization_list: Some(AuthorizationList::Recovered(vec![])),
+            ..Default::default()
+        };
+        assert_eq!(tx.tx_type(), TxType::Eip7702);
+        assert_eq!(tx.tx_type() as u8, 4);
+        #[cfg(feature = "optimism")]
+        {
+            let mut tx = tx;
+            tx.optimism.source_hash = Some(B256::ZERO);
+            assert_eq!(tx.tx_type(), TxType::Deposit);
+            assert_eq!(tx.tx_type() as u8, 0x7E);
+        }
+    }
+
+    #[test]
+    fn test_validate_tx_type_fields() {
+        // blob hashes without max fee per blob gas.
+        let mut env = Env::default();
+        env.tx_mut().blob_hashes = blob_tx().blob_hashes;
+        assert_eq!(env.tx().tx_type(), TxType::Eip4844);
+        assert_eq!(
+            env.validate_tx::<crate::CancunSpec>(),
+            Err(InvalidTransaction::BlobVersionedHashesNotSupported)
+        );
+
+        // blob transaction without `to`.
+        let mut env = Env::default();
+        *env.tx_mut() = blob_tx();
+        env.tx_mut().max_fee_per_blob_gas = Some(U256::from(1));
+        env.tx_mut().transact_to = Some(TxKind::Create);
+        assert_eq!(
+            env.validate_tx::<crate::CancunSpec>(),
+            Err(InvalidTransaction::BlobCreateTransaction)
+        );
+
+        // set code transaction with blob fields.
+        let mut env = Env::default();
+        *env.tx_mut() = blob_tx();
+        env.tx_mut().authorization_list = Some(AuthorizationList::Recovered(vec![]));
+        assert_eq!(env.tx().tx_type(), TxType::Eip7702);
+        assert_eq!(
+            env.validate_tx::<crate::PragueSpec>(),
+            Err(InvalidTransaction::AuthorizationListInvalidFields)
+        );
+
+        // set code transaction without authorizations.
+        let mut env = Env::default();
+        env.tx_mut().authorization_list = Some(AuthorizationList::Recovered(vec![]));
+        assert_eq!(
+            env.validate_tx::<crate::PragueSpec>(),
+            Err(InvalidTransaction::EmptyAuthorizationList)
+        );
+    }
+
+    #[test]
+    fn test_fee_helpers() {
+        let basefee = U256::from(10);
+
+        // legacy transaction pays its gas price, the tip is whatever is above the basefee.
+        let tx = TxEnv {
+            gas_price: U256::from(15),
+            ..Default::default()
+        };
+        assert_eq!(tx.effective_gas_price(basefee), U256::from(15));
+        assert_eq!(tx.priority_fee(basefee), U256::from(5));
+        // tip is floored at zero when the gas price is below the basefee.
+        assert_eq!(tx.priority_fee(U256::from(20)), U256::ZERO);
+
+        // EIP-1559 transaction with room for the full priority fee.
+        let tx = TxEnv {
+            gas_price: U256::from(20),
+            gas_priority_fee: Some(U256::from(3)),
+            ..Default::default()
+        };
+        assert_eq!(tx.effective_gas_price(basefee), U256::from(13));
+        assert_eq!(tx.priority_fee(basefee), U256::from(3));
+
+        // max priority fee exceeds `max_fee - basefee` and is clamped.
+        let tx = TxEnv {
+            gas_price: U256::from(12),
+            gas_priority_fee: Some(U256::from(5)),
+            ..Default::default()
+        };
+        assert_eq!(tx.effective_gas_price(basefee), U256::from(12));
+        assert_eq!(tx.priority_fee(basefee), U256::from(2));
+
+        // does not overflow on huge fees.
+        let tx = TxEnv {
+            gas_price: U256::MAX,
+            gas_priority_fee: Some(U256::MAX),
+            ..Default::default()
+        };
+        assert_eq!(tx.effective_gas_price(basefee), U256::MAX);
+        assert_eq!(tx.priority_fee(basefee), U256::MAX - basefee);
+
+        #[cfg(feature = "optimism")]
+        {
+            let mut tx = tx;
+            tx.optimism.source_hash = Some(B256::ZERO);
+            assert_eq!(tx.effective_gas_price(basefee), U256::ZERO);
+            assert_eq!(tx.priority_fee(basefee), U256::ZERO);
+        }
+
+        // `Env` delegates to the transaction with the block basefee.
+        let mut env = Env::default();
+        env.block.basefee = basefee;
+        env.tx_mut().gas_price = U256::from(12);
+        env.tx_mut().gas_priority_fee = Some(U256::from(5));
+        assert_eq!(env.effective_gas_price(), U256::from(12));
+    }
+
+    #[test]
+    fn test_max_cost() {
+        let mut tx = blob_tx();
+        tx.gas_limit = 100;
+        tx.gas_price = U256::from(7);
+        tx.gas_priority_fee = Some(U256::from(1));
+        tx.value = U256::from(1000);
+        tx.max_fee_per_blob_gas = Some(U256::from(2));
+        assert_eq!(
+            tx.max_cost(),
+            Some(U256::from(100 * 7 + 1000 + 2 * 2 * GAS_PER_BLOB))
+        );
+
+        tx.value = U256::MAX;
+        assert_eq!(tx.max_cost(), None);
+
+        let mut env = Env::default();
+        env.tx_mut().gas_limit = u64::MAX;
+        env.tx_mut().gas_price = U256::MAX;
+        assert_eq!(
+            env.validate_tx_against_state::<crate::LatestSpec>(&mut Account::default()),
+            Err(InvalidTransaction::OverflowPaymentInTransaction)
+        );
+    }
 }
@@ -0,0 +1,254 @@
+//! Decoding of the signed [EIP-2718] transaction envelopes into [`TxEnv`].
+//!
+//! [EIP-2718]: https://eips.ethereum.org/EIPS/eip-2718
+
+use crate::{Address, TxEnv, TxKind, U256};
+use alloy_consensus::{TxEip4844Variant, TxEnvelope, TxType};
+use alloy_eips::eip2718::{Decodable2718, Eip2718Error, Encodable2718};
+use core::fmt;
+use std::string::{String, ToString};
+
+/// Error of [`TxEnv::from_signed_envelope`].
+///
+/// Errors of the decoder and of the signature recovery are kept as messages so the error can be
+/// returned as [`EVMError::TxDecode`](crate::EVMError::TxDecode).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TxDecodeError {
+    /// Type of the envelope is not a supported transaction type.
+    UnsupportedType(u8),
+    /// Bytes are not a valid envelope, contains the message of the decoder.
+    Decode(String),
+    /// Bytes are left after the envelope.
+    TrailingBytes,
+    /// Gas limit of the transaction does not fit in `u64`.
+    GasLimitOverflow,
+    /// Signer can't be recovered from the signature, contains the message of the recovery.
+    Signature(String),
+}
+
+impl From<Eip2718Error> for TxDecodeError {
+    fn from(err: Eip2718Error) -> Self {
+        match err {
+            Eip2718Error::UnexpectedType(ty) => Self::UnsupportedType(ty),
+            err => Self::Decode(err.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for TxDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedType(ty) => write!(f, "unsupported transaction type {ty}"),
+            Self::Decode(err) => write!(f, "invalid transaction envelope: {err}"),
+            Self::TrailingBytes => write!(f, "trailing bytes after transaction envelope"),
+            Self::GasLimitOverflow => write!(f, "transaction gas limit overflows u64"),
+            Self::Signature(err) => write!(f, "invalid transaction signature: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TxDecodeError {}
+
+/// Converts the gas limit of the decoded transaction.
+fn gas_limit(limit: impl TryInto<u64>) -> Result<u64, TxDecodeError> {
+    limit
+        .try_into()
+        .map_err(|_| TxDecodeError::GasLimitOverflow)
+}
+
+impl TxEnv {
+    /// Decodes the signed transaction envelope, as found in the block body or sent to the
+    /// mempool, and recovers its signer.
+    ///
+    /// Returns the transaction environment with the signer as the caller, and the signer.
+    /// Legacy, EIP-2930, EIP-1559 and EIP-4844 transactions are supported, EIP-4844 with or
+    /// without the blob sidecar. With the `optimism` feature the envelope is stored in
+    /// [`OptimismFields::enveloped_tx`](crate::OptimismFields::enveloped_tx) for the L1 cost.
+    pub fn from_signed_envelope(mut bytes: &[u8]) -> Result<(TxEnv, Address), TxDecodeError> {
+        let envelope_bytes = bytes;
+        // the decoder doesn't report which type it doesn't support.
+        if let Some(&ty) = bytes.first().filter(|ty| **ty <= 0x7f) {
+            TxType::try_from(ty)?;
+        }
+        let envelope = TxEnvelope::decode_2718(&mut bytes)?;
+        // typed envelopes are decoded from a copy of `bytes`, their length is compared instead.
+        let trailing_bytes = if envelope.is_legacy() {
+            !bytes.is_empty()
+        } else {
+            envelope.encode_2718_len() != envelope_bytes.len()
+        };
+        if trailing_bytes {
+            return Err(TxDecodeError::TrailingBytes);
+        }
+
+        let mut env = TxEnv::default();
+        #[allow(unreachable_patterns)]
+        let caller = match &envelope {
+            TxEnvelope::Legacy(signed) => {
+                let tx = signed.tx();
+                env.gas_limit = gas_limit(tx.gas_limit)?;
+                env.gas_price = U256::from(tx.gas_price);
+                env.transact_to = Some(tx.to);
+                env.value = tx.value;
+                env.data = tx.input.clone();
+                env.nonce = Some(tx.nonce);
+                env.chain_id = tx.chain_id;
+                signed.recover_signer()
+            }
+            TxEnvelope::Eip2930(signed) => {
+                let tx = signed.tx();
+                env.gas_limit = gas_limit(tx.gas_limit)?;
+                env.gas_price = U256::from(tx.gas_price);
+                env.transact_to = Some(tx.to);
+                env.value = tx.value;
+                env.data = tx.input.clone();
+                env.nonce = Some(tx.nonce);
+                env.chain_id = Some(tx.chain_id);
+                env.access_list = tx.access_list.0.clone();
+                signed.recover_signer()
+            }
+            TxEnvelope::Eip1559(signed) => {
+                let tx = signed.tx();
+                env.gas_limit = gas_limit(tx.gas_limit)?;
+                env.gas_price = U256::from(tx.max_fee_per_gas);
+                env.gas_priority_fee = Some(U256::from(tx.max_priority_fee_per_gas));
+                env.transact_to = Some(tx.to);
+                env.value = tx.value;
+                env.data = tx.input.clone();
+                env.nonce = Some(tx.nonce);
+                env.chain_id = Some(tx.chain_id);
+                env.access_list = tx.access_list.0.clone();
+                signed.recover_signer()
+            }
+            TxEnvelope::Eip4844(signed) => {
+                let tx = match signed.tx() {
+                    TxEip4844Variant::TxEip4844(tx) => tx,
+                    TxEip4844Variant::TxEip4844WithSidecar(tx) => &tx.tx,
+                };
+                env.gas_limit = gas_limit(tx.gas_limit)?;
+                env.gas_price = U256::from(tx.max_fee_per_gas);
+                env.gas_priority_fee = Some(U256::from(tx.max_priority_fee_per_gas));
+                env.transact_to = Some(TxKind::Call(tx.to));
+                env.value = tx.value;
+                env.data = tx.input.clone();
+                env.nonce = Some(tx.nonce);
+                env.chain_id = Some(tx.chain_id);
+                env.access_list = tx.access_list.0.clone();
+                env.blob_hashes = tx.blob_versioned_hashes.clone();
+                env.max_fee_per_blob_gas = Some(U256::from(tx.max_fee_per_blob_gas));
+                signed.recover_signer()
+            }
+            _ => return Err(TxDecodeError::UnsupportedType(envelope_bytes[0])),
+        }
+        .map_err(|err| TxDecodeError::Signature(err.to_string()))?;
+
+        env.caller = caller;
+        #[cfg(feature = "optimism")]
+        {
+            env.optimism.enveloped_tx = Some(crate::Bytes::copy_from_slice(envelope_bytes));
+        }
+        Ok((env, caller))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{address, b256, bytes, hex, AccessListItem, TxType, B256};
+    use std::{vec, vec::Vec};
+
+    const SENDER: Address = address!("f39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+    const TO: Address = address!("70997970c51812dc3a010c7d01b50e0d17dc79c8");
+
+    fn decode(envelope: &str) -> TxEnv {
+        let (env, caller) = TxEnv::from_signed_envelope(&hex::decode(envelope).unwrap()).unwrap();
+        assert_eq!(caller, SENDER);
+        assert_eq!(env.caller, SENDER);
+        env
+    }
+
+    fn access_list() -> Vec<AccessListItem> {
+        vec![AccessListItem {
+            address: TO,
+            storage_keys: vec![B256::with_last_byte(1)],
+        }]
+    }
+
+    #[test]
+    fn legacy() {
+        let env = decode("f870078504a817c80082c3509470997970c51812dc3a010c7d01b50e0d17dc79c8880de0b6b3a764000084a9059cbb26a01ef5d86a1b94085c639897e2fb80c49a39c26a3bf3416df6b85894c6a8024a09a028e6712f0e0b3652365ac66963771ddd2ebef081ffbf869b96f5ee3e36a8dd1d");
+        assert_eq!(env.tx_type(), TxType::Legacy);
+        assert_eq!(env.nonce, Some(7));
+        assert_eq!(env.gas_price, U256::from(20_000_000_000u64));
+        assert_eq!(env.gas_limit, 50_000);
+        assert_eq!(env.transact_to, Some(TxKind::Call(TO)));
+        assert_eq!(env.value, U256::from(1_000_000_000_000_000_000u64));
+        assert_eq!(env.data, bytes!("a9059cbb"));
+        // EIP-155 chain id.
+        assert_eq!(env.chain_id, Some(1));
+    }
+
+    #[test]
+    fn eip2930() {
+        let env = decode("01f8a301088504a817c80082ea609470997970c51812dc3a010c7d01b50e0d17dc79c88084a9059cbbf838f79470997970c51812dc3a010c7d01b50e0d17dc79c8e1a0000000000000000000000000000000000000000000000000000000000000000101a09dfc0e10ecf3753eee8780a8fb69f8bc68128bef01f1ab647357d48ec47278e9a01fa71f195ff4d47ded8220c1cec70232c4967de421563a67346d545fa96cf705");
+        assert_eq!(env.tx_type(), TxType::Eip2930);
+        assert_eq!(env.nonce, Some(8));
+        assert_eq!(env.gas_price, U256::from(20_000_000_000u64));
+        assert_eq!(env.gas_limit, 60_000);
+        assert_eq!(env.transact_to, Some(TxKind::Call(TO)));
+        assert_eq!(env.access_list, access_list());
+        assert_eq!(env.chain_id, Some(1));
+    }
+
+    #[test]
+    fn eip1559() {
+        let env = decode("02f85c010984773594008506fc23ac0083011170808084a9059cbbc080a02b6c19dd6c4e0e48894426d797e9f12adbe197b3a2d85550f107f5df100f3683a04c24d65a43e8b10c6ee9ab57f947ef418d97e38a154ad27f22d6d76419cfebde");
+        assert_eq!(env.tx_type(), TxType::Eip1559);
+        assert_eq!(env.nonce, Some(9));
+        assert_eq!(env.gas_price, U256::from(30_000_000_000u64));
+        assert_eq!(env.gas_priority_fee, Some(U256::from(2_000_000_000u64)));
+        assert_eq!(env.gas_limit, 70_000);
+        assert_eq!(env.transact_to, Some(TxKind::Create));
+        assert!(env.access_list.is_empty());
+    }
+
+    #[test]
+    fn eip4844() {
+        let env = decode("03f8d0010a843b9aca008509502f9000830138809470997970c51812dc3a010c7d01b50e0d17dc79c80184a9059cbbf838f79470997970c51812dc3a010c7d01b50e0d17dc79c8e1a0000000000000000000000000000000000000000000000000000000000000000184b2d05e00e1a001ababababababababababababababababababababababababababababababab01a02e49aae8cd30e6a4694cf22613cb6c7eb9855f1d1dae2f22e5aae9087ce48a29a07df9df627a31958bd328e8563a64fb36951c96068c2647de60629afe6d5e9332");
+        assert_eq!(env.tx_type(), TxType::Eip4844);
+        assert_eq!(env.nonce, Some(10));
+        assert_eq!(env.gas_price, U256::from(40_000_000_000u64));
+        assert_eq!(env.gas_priority_fee, Some(U256::from(1_000_000_000u64)));
+        assert_eq!(env.gas_limit, 80_000);
+        assert_eq!(env.value, U256::from(1));
+        assert_eq!(env.access_list, access_list());
+        assert_eq!(
+            env.blob_hashes,
+            [b256!(
+                "01ababababababababababababababababababababababababababababababab"
+            )]
+        );
+        assert_eq!(env.max_fee_per_blob_gas, Some(U256::from(3_000_000_000u64)));
+    }
+
+    #[test]
+    fn invalid_envelope() {
+        let mut envelope = hex::decode("02f85c010984773594008506fc23ac0083011170808084a9059cbbc080a02b6c19dd6c4e0e48894426d797e9f12adbe197b3a2d85550f107f5df100f3683a04c24d65a43e8b10c6ee9ab57f947ef418d97e38a154ad27f22d6d76419cfebde").unwrap();
+        envelope.push(0);
+        assert!(matches!(
+            TxEnv::from_signed_envelope(&envelope),
+            Err(TxDecodeError::TrailingBytes)
+        ));
+        assert!(matches!(
+            TxEnv::from_signed_envelope(&envelope[..10]),
+            Err(TxDecodeError::Decode(_))
+        ));
+        // EIP-7702 is not supported yet.
+        assert_eq!(
+            TxEnv::from_signed_envelope(&[0x04, 0xc0]).unwrap_err(),
+            TxDecodeError::UnsupportedType(4)
+        );
+    }
+}
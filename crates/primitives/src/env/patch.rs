@@ -0,0 +1,334 @@
+use super::{
+    AnalysisKind, AuthorizationList, BlobExcessGasAndPrice, BlockEnv, CfgEnv, Eip1559Params, Env,
+    GasScheduleOverrides, KeccakProvider, OpcodeEnvOverrides, Predeploys, TxEnv,
+};
+use crate::{AccessListItem, Address, Bytes, B256, U256};
+use alloy_primitives::TxKind;
+use std::vec::Vec;
+
+/// Defines a patch struct with an optional override for every field of the environment
+/// struct, together with its `apply` and `diff` functions.
+///
+/// The environment struct is destructured with every listed field, so a field that is added to
+/// it but not to the list fails to compile.
+macro_rules! env_patch {
+    (
+        $(#[$meta:meta])*
+        $patch:ident for $env:ident {
+            $(
+                $(#[cfg($cfg:meta)])*
+                $(#[serde($serde:meta)])?
+                $field:ident: $ty:ty,
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, Default, PartialEq, Eq)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub struct $patch {
+            $(
+                $(#[cfg($cfg)])*
+                #[doc = concat!("Overrides [`", stringify!($env), "::", stringify!($field), "`].")]
+                #[cfg_attr(
+                    feature = "serde",
+                    serde(
+                        default,
+                        deserialize_with = "deserialize_some",
+                        skip_serializing_if = "Option::is_none"
+                    )
+                )]
+                $(#[cfg_attr(feature = "serde", serde($serde))])?
+                pub $field: Option<$ty>,
+            )*
+        }
+
+        impl $patch {
+            /// Returns `true` if the patch does not change any field.
+            pub fn is_empty(&self) -> bool {
+                $(
+                    $(#[cfg($cfg)])*
+                    if self.$field.is_some() {
+                        return false;
+                    }
+                )*
+                true
+            }
+
+            /// Overrides the fields that are set in the patch.
+            pub fn apply(&self, env: &mut $env) {
+                $(
+                    $(#[cfg($cfg)])*
+                    if let Some(value) = &self.$field {
+                        env.$field.clone_from(value);
+                    }
+                )*
+            }
+
+            /// Returns the patch that turns `from` into `to`, only the differing fields are set.
+            pub fn diff(from: &$env, to: &$env) -> Self {
+                let $env {
+                    $(
+                        $(#[cfg($cfg)])*
+                        $field: _,
+                    )*
+                } = from;
+                Self {
+                    $(
+                        $(#[cfg($cfg)])*
+                        $field: (from.$field != to.$field).then(|| to.$field.clone()),
+                    )*
+                }
+            }
+        }
+    };
+}
+
+env_patch! {
+    /// Overrides of the [`CfgEnv`] fields, `None` leaves the field unchanged.
+    CfgEnvPatch for CfgEnv {
+        chain_id: u64,
+        #[cfg(feature = "c-kzg")]
+        #[serde(skip)]
+        kzg_settings: crate::kzg::EnvKzgSettings,
+        perf_analyse_created_bytecodes: AnalysisKind,
+        limit_contract_code_size: Option<usize>,
+        call_stack_limit: Option<u64>,
+        #[cfg(feature = "memory_limit")]
+        memory_limit: u64,
+        #[cfg(feature = "optional_balance_check")]
+        disable_balance_check: bool,
+        #[cfg(feature = "optional_block_gas_limit")]
+        disable_block_gas_limit: bool,
+        tx_gas_limit_cap: Option<u64>,
+        max_frame_gas: Option<u64>,
+        #[cfg(feature = "optional_eip3607")]
+        disable_eip3607: bool,
+        #[cfg(feature = "optional_gas_refund")]
+        disable_gas_refund: bool,
+        #[cfg(feature = "optional_no_base_fee")]
+        disable_base_fee: bool,
+        #[cfg(feature = "optional_beneficiary_reward")]
+        disable_beneficiary_reward: bool,
+        #[cfg(feature = "optimism")]
+        optimism_no_l1_fee: bool,
+        revalidate_stale_tx: bool,
+        preload_access_list_code: bool,
+        paranoid_db_checks: bool,
+        require_transact_to: bool,
+        profile_execution: bool,
+        profile_sample_interval: u64,
+        allow_mint: bool,
+        persist_mint_on_halt: bool,
+        eip1559_params: Option<Eip1559Params>,
+        opcode_overrides: Option<OpcodeEnvOverrides>,
+        gas_overrides: Option<GasScheduleOverrides>,
+        #[serde(skip)]
+        keccak_provider: Option<KeccakProvider>,
+        extra_warm_addresses: Vec<Address>,
+        predeploys: Option<Predeploys>,
+        bound_precompile_output: bool,
+        force_static: bool,
+    }
+}
+
+env_patch! {
+    /// Overrides of the [`BlockEnv`] fields, `None` leaves the field unchanged.
+    BlockEnvPatch for BlockEnv {
+        number: U256,
+        coinbase: Address,
+        timestamp: U256,
+        gas_limit: U256,
+        basefee: U256,
+        difficulty: U256,
+        prevrandao: Option<B256>,
+        blob_excess_gas_and_price: Option<BlobExcessGasAndPrice>,
+    }
+}
+
+env_patch! {
+    /// Overrides of the [`TxEnv`] fields, `None` leaves the field unchanged.
+    TxEnvPatch for TxEnv {
+        caller: Address,
+        gas_limit: u64,
+        gas_price: U256,
+        transact_to: Option<TxKind>,
+        value: U256,
+        data: Bytes,
+        nonce: Option<u64>,
+        chain_id: Option<u64>,
+        access_list: Vec<AccessListItem>,
+        gas_priority_fee: Option<U256>,
+        blob_hashes: Vec<B256>,
+        max_fee_per_blob_gas: Option<U256>,
+        authorization_list: Option<AuthorizationList>,
+        custom_tx_type: Option<u8>,
+        initcodes: Vec<Bytes>,
+        mint: Option<u128>,
+        #[cfg(feature = "optimism")]
+        optimism: super::OptimismFields,
+    }
+}
+
+/// Overrides of the [`Env`] fields, used to express variations of a base environment.
+///
+/// Omitted fields are left unchanged, so a patch stays valid when fields are added to the
+/// environment. Fields that are set to `None`, like [`TxEnv::nonce`], are overridden with
+/// `Some(None)`, which is `null` in JSON.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct EnvPatch {
+    /// Overrides of the configuration.
+    pub cfg: CfgEnvPatch,
+    /// Overrides of the block.
+    pub block: BlockEnvPatch,
+    /// Overrides of the transaction.
+    pub tx: TxEnvPatch,
+}
+
+impl EnvPatch {
+    /// Returns `true` if the patch does not change any field.
+    pub fn is_empty(&self) -> bool {
+        self.cfg.is_empty() && self.block.is_empty() && self.tx.is_empty()
+    }
+}
+
+impl Env {
+    /// Overrides the fields that are set in the patch.
+    ///
+    /// [`Env::tx_generation`] is bumped only if the patch changes the transaction.
+    pub fn apply_patch(&mut self, patch: &EnvPatch) {
+        patch.cfg.apply(&mut self.cfg);
+        patch.block.apply(&mut self.block);
+        if !patch.tx.is_empty() {
+            patch.tx.apply(self.tx_mut());
+        }
+    }
+
+    /// Returns the patch that turns `self` into `other`.
+    pub fn diff(&self, other: &Env) -> EnvPatch {
+        // a field added to `Env` needs a patch as well.
+        #[allow(deprecated)]
+        let Env {
+            cfg: _,
+            block: _,
+            tx: _,
+            tx_generation: _,
+        } = self;
+        EnvPatch {
+            cfg: CfgEnvPatch::diff(&self.cfg, &other.cfg),
+            block: BlockEnvPatch::diff(&self.block, &other.block),
+            tx: TxEnvPatch::diff(self.tx(), other.tx()),
+        }
+    }
+}
+
+/// Deserializes a present field as `Some`, so that `null` overrides an optional field with
+/// `None` instead of leaving it unchanged.
+#[cfg(feature = "serde")]
+fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: serde::Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_env() -> Env {
+        let mut env = Env::default();
+        env.cfg.chain_id = 1;
+        env.block.number = U256::from(100);
+        env.block.basefee = U256::from(7);
+        let tx = env.tx_mut();
+        tx.caller = Address::with_last_byte(1);
+        tx.transact_to = Some(TxKind::Call(Address::with_last_byte(2)));
+        tx.gas_limit = 100_000;
+        tx.nonce = Some(3);
+        env
+    }
+
+    #[test]
+    fn diff_apply_round_trip() {
+        let base = base_env();
+        let mut other = base.clone();
+        other.cfg.limit_contract_code_size = Some(0x8000);
+        other.block.prevrandao = Some(B256::with_last_byte(1));
+        other.tx_mut().nonce = None;
+        other.tx_mut().data = Bytes::from_static(&[1, 2, 3]);
+
+        let patch = base.diff(&other);
+        assert_eq!(
+            patch.cfg,
+            CfgEnvPatch {
+                limit_contract_code_size: Some(Some(0x8000)),
+                ..Default::default()
+            }
+        );
+        assert_eq!(patch.tx.nonce, Some(None));
+        assert_eq!(patch.tx.gas_limit, None);
+
+        let mut patched = base.clone();
+        patched.apply_patch(&patch);
+        assert_eq!(patched.cfg, other.cfg);
+        assert_eq!(patched.block, other.block);
+        assert_eq!(patched.tx(), other.tx());
+        assert!(patched.diff(&other).is_empty());
+        assert!(base.diff(&base).is_empty());
+
+        // empty patch leaves the transaction generation unchanged.
+        let generation = patched.tx_generation();
+        patched.apply_patch(&EnvPatch::default());
+        assert_eq!(patched.tx_generation(), generation);
+        patched.apply_patch(&patch);
+        assert_ne!(patched.tx_generation(), generation);
+    }
+
+    #[test]
+    fn patches_of_base_env() {
+        let base = base_env();
+        let patches = [
+            EnvPatch::default(),
+            EnvPatch {
+                block: BlockEnvPatch {
+                    number: Some(U256::from(7)),
+                    basefee: Some(U256::from(10)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            EnvPatch {
+                tx: TxEnvPatch {
+                    gas_limit: Some(21_000),
+                    nonce: Some(None),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ];
+        let envs: Vec<Env> = patches
+            .iter()
+            .map(|patch| {
+                let mut env = base.clone();
+                env.apply_patch(patch);
+                env
+            })
+            .collect();
+
+        assert_eq!(envs[0].block, base.block);
+        assert_eq!(envs[0].tx(), base.tx());
+        assert_eq!(envs[1].block.basefee, U256::from(10));
+        assert_eq!(envs[1].block.number, U256::from(7));
+        assert_eq!(envs[1].block.timestamp, base.block.timestamp);
+        assert_eq!(envs[1].tx(), base.tx());
+        assert_eq!(envs[2].tx().gas_limit, 21_000);
+        assert_eq!(envs[2].tx().nonce, None);
+        assert_eq!(envs[2].tx().caller, base.tx().caller);
+        for (env, patch) in envs.iter().zip(&patches) {
+            assert_eq!(&base.diff(env), patch);
+        }
+    }
+}
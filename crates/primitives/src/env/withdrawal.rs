@@ -0,0 +1,30 @@
+use crate::{Address, U256};
+
+/// Number of wei in one gwei, the unit of [`Withdrawal::amount_gwei`].
+pub const WEI_PER_GWEI: u64 = 1_000_000_000;
+
+/// Beacon chain withdrawal of the block.
+///
+/// Incorporated as part of the Shanghai upgrade via [EIP-4895].
+///
+/// [EIP-4895]: https://eips.ethereum.org/EIPS/eip-4895
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Withdrawal {
+    /// Monotonically increasing identifier of the withdrawal.
+    pub index: u64,
+    /// Index of the validator the withdrawal is for.
+    pub validator: u64,
+    /// Recipient of the withdrawn amount.
+    pub address: Address,
+    /// Withdrawn amount in gwei.
+    pub amount_gwei: u64,
+}
+
+impl Withdrawal {
+    /// Returns the withdrawn amount in wei.
+    #[inline]
+    pub fn amount_wei(&self) -> U256 {
+        U256::from(self.amount_gwei) * U256::from(WEI_PER_GWEI)
+    }
+}
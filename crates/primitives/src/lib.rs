@@ -7,6 +7,7 @@
 #[cfg(not(feature = "std"))]
 extern crate alloc as std;
 
+mod bloom;
 mod bytecode;
 mod constants;
 pub mod db;
@@ -15,32 +16,74 @@ pub mod env;
 #[cfg(any(feature = "c-kzg", feature = "kzg-rs"))]
 pub mod kzg;
 pub mod precompile;
+pub mod profile;
 pub mod result;
 pub mod specification;
 pub mod state;
 pub mod utilities;
 pub use alloy_eips::eip2930::{AccessList, AccessListItem};
 pub use alloy_primitives::{
-    self, address, b256, bytes, fixed_bytes, hex, hex_literal, ruint, uint, Address, Bytes,
+    self, address, b256, bytes, fixed_bytes, hex, hex_literal, ruint, uint, Address, Bloom, Bytes,
     FixedBytes, Log, LogData, TxKind, B256, I256, U256,
 };
 pub use bitvec;
+pub use bloom::*;
 pub use bytecode::*;
 pub use constants::*;
 pub use env::*;
 
 cfg_if::cfg_if! {
     if #[cfg(all(not(feature = "hashbrown"), feature = "std"))] {
-        pub use std::collections::{hash_map, hash_set, HashMap, HashSet};
+        pub use std::collections::{hash_map, hash_set};
         use hashbrown as _;
     } else {
-        pub use hashbrown::{hash_map, hash_set, HashMap, HashSet};
+        pub use hashbrown::{hash_map, hash_set};
     }
 }
 
+// `rand-hash` takes precedence over `fx-hash`, a crate in the dependency graph that asks for
+// keyed hashing keeps it if another one enables `fx-hash`.
+cfg_if::cfg_if! {
+    if #[cfg(feature = "rand-hash")] {
+        /// Hasher builder of [`HashMap`] and [`HashSet`].
+        ///
+        /// SipHash with random keys selected by the `rand-hash` feature, it takes precedence
+        /// over `fx-hash`.
+        pub type DefaultHashBuilder = std::collections::hash_map::RandomState;
+        #[cfg(feature = "fx-hash")]
+        use fxhash as _;
+    } else if #[cfg(feature = "fx-hash")] {
+        /// Hasher builder of [`HashMap`] and [`HashSet`].
+        ///
+        /// FxHash selected by the `fx-hash` feature. It is fast but not keyed, do not use it
+        /// with attacker controlled keys.
+        pub type DefaultHashBuilder = core::hash::BuildHasherDefault<fxhash::FxHasher>;
+    } else if #[cfg(all(not(feature = "hashbrown"), feature = "std"))] {
+        /// Hasher builder of [`HashMap`] and [`HashSet`].
+        ///
+        /// SipHash with random keys, used with `std`.
+        pub type DefaultHashBuilder = std::collections::hash_map::RandomState;
+    } else {
+        /// Hasher builder of [`HashMap`] and [`HashSet`].
+        ///
+        /// Default hasher of `hashbrown`, it is not randomly keyed without `std`.
+        pub type DefaultHashBuilder = hashbrown::hash_map::DefaultHashBuilder;
+    }
+}
+
+/// Hash map with the [`DefaultHashBuilder`] hasher.
+///
+/// Use `HashMap::default()` to create it, `HashMap::new()` exists only for some hashers.
+pub type HashMap<K, V, S = DefaultHashBuilder> = hash_map::HashMap<K, V, S>;
+/// Hash set with the [`DefaultHashBuilder`] hasher.
+///
+/// Use `HashSet::default()` to create it, `HashSet::new()` exists only for some hashers.
+pub type HashSet<T, S = DefaultHashBuilder> = hash_set::HashSet<T, S>;
+
 #[cfg(any(feature = "c-kzg", feature = "kzg-rs"))]
 pub use kzg::{EnvKzgSettings, KzgSettings};
 pub use precompile::*;
+pub use profile::*;
 pub use result::*;
 pub use specification::*;
 pub use state::*;
@@ -1,5 +1,9 @@
 use crate::{Bytes, Env};
-use core::fmt::{self};
+use core::{
+    any::TypeId,
+    fmt::{self},
+    hash::{Hash, Hasher},
+};
 use dyn_clone::DynClone;
 use std::{boxed::Box, string::String, sync::Arc};
 
@@ -24,19 +28,19 @@ impl PrecompileOutput {
     }
 }
 
-pub type StandardPrecompileFn = fn(&Bytes, u64) -> PrecompileResult;
-pub type EnvPrecompileFn = fn(&Bytes, u64, env: &Env) -> PrecompileResult;
+pub type StandardPrecompileFn = fn(&[u8], u64) -> PrecompileResult;
+pub type EnvPrecompileFn = fn(&[u8], u64, env: &Env) -> PrecompileResult;
 
 /// Stateful precompile trait. It is used to create
 /// a arc precompile Precompile::Stateful.
 pub trait StatefulPrecompile: Sync + Send {
-    fn call(&self, bytes: &Bytes, gas_price: u64, env: &Env) -> PrecompileResult;
+    fn call(&self, bytes: &[u8], gas_price: u64, env: &Env) -> PrecompileResult;
 }
 
 /// Mutable stateful precompile trait. It is used to create
 /// a boxed precompile in Precompile::StatefulMut.
 pub trait StatefulPrecompileMut: DynClone + Send + Sync {
-    fn call_mut(&mut self, bytes: &Bytes, gas_price: u64, env: &Env) -> PrecompileResult;
+    fn call_mut(&mut self, bytes: &[u8], gas_price: u64, env: &Env) -> PrecompileResult;
 }
 
 dyn_clone::clone_trait_object!(StatefulPrecompileMut);
@@ -109,7 +113,7 @@ impl Precompile {
     }
 
     /// Call the precompile with the given input and gas limit and return the result.
-    pub fn call(&mut self, bytes: &Bytes, gas_price: u64, env: &Env) -> PrecompileResult {
+    pub fn call(&mut self, bytes: &[u8], gas_price: u64, env: &Env) -> PrecompileResult {
         match *self {
             Precompile::Standard(p) => p(bytes, gas_price),
             Precompile::Env(p) => p(bytes, gas_price, env),
@@ -121,7 +125,7 @@ impl Precompile {
     /// Call the precompile with the given input and gas limit and return the result.
     ///
     /// Returns an error if the precompile is mutable.
-    pub fn call_ref(&self, bytes: &Bytes, gas_price: u64, env: &Env) -> PrecompileResult {
+    pub fn call_ref(&self, bytes: &[u8], gas_price: u64, env: &Env) -> PrecompileResult {
         match *self {
             Precompile::Standard(p) => p(bytes, gas_price),
             Precompile::Env(p) => p(bytes, gas_price, env),
@@ -176,6 +180,8 @@ pub enum PrecompileError {
     BlobVerifyKzgProofFailed,
     /// Catch-all variant for other errors.
     Other(String),
+    /// Structured error of a custom precompile, see [`CustomPrecompileError`].
+    Custom(Arc<dyn CustomPrecompileError>),
 }
 
 impl PrecompileError {
@@ -184,10 +190,97 @@ impl PrecompileError {
         Self::Other(err.into())
     }
 
+    /// Returns a custom error.
+    pub fn custom(err: impl CustomPrecompileError) -> Self {
+        Self::Custom(Arc::new(err))
+    }
+
     /// Returns true if the error is out of gas.
     pub fn is_oog(&self) -> bool {
         matches!(self, Self::OutOfGas)
     }
+
+    /// Returns true if the error consumes all the gas of the call. Only custom errors can be
+    /// non-fatal, see [`CustomPrecompileError::is_fatal`].
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            Self::Custom(err) => err.is_fatal(),
+            _ => true,
+        }
+    }
+
+    /// Returns the custom error if it is of type `T`.
+    pub fn downcast_custom<T: CustomPrecompileError>(&self) -> Option<&T> {
+        match self {
+            Self::Custom(err) => err.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// Structured error of a custom precompile, returned as [`PrecompileError::Custom`].
+///
+/// Inspectors can read the error of the last precompile call with
+/// `EvmContext::precompile_error` and downcast it to the concrete type with
+/// [`PrecompileError::downcast_custom`].
+///
+/// Custom errors are compared and hashed by their type and [`code`](Self::code).
+pub trait CustomPrecompileError: fmt::Debug + fmt::Display + Send + Sync + 'static {
+    /// Stable code of the error.
+    fn code(&self) -> u32;
+
+    /// Returns true if the error consumes all the gas of the call, same as the other
+    /// precompile errors. Otherwise the call reverts with empty output and the gas is
+    /// returned to the caller.
+    ///
+    /// By default, it is `true`.
+    fn is_fatal(&self) -> bool {
+        true
+    }
+
+    /// Returns the type id of the error, used for downcasting. Can't be overridden.
+    #[doc(hidden)]
+    fn type_id(&self, _: private::Internal) -> TypeId {
+        TypeId::of::<Self>()
+    }
+}
+
+mod private {
+    #[derive(Debug)]
+    pub struct Internal;
+}
+
+impl dyn CustomPrecompileError {
+    /// Returns true if the error is of type `T`.
+    pub fn is<T: CustomPrecompileError>(&self) -> bool {
+        self.type_id(private::Internal) == TypeId::of::<T>()
+    }
+
+    /// Returns the error if it is of type `T`.
+    pub fn downcast_ref<T: CustomPrecompileError>(&self) -> Option<&T> {
+        if self.is::<T>() {
+            // SAFETY: type is checked above.
+            Some(unsafe { &*(self as *const Self as *const T) })
+        } else {
+            None
+        }
+    }
+}
+
+impl PartialEq for dyn CustomPrecompileError {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_id(private::Internal) == other.type_id(private::Internal)
+            && self.code() == other.code()
+    }
+}
+
+impl Eq for dyn CustomPrecompileError {}
+
+impl Hash for dyn CustomPrecompileError {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.type_id(private::Internal).hash(state);
+        self.code().hash(state);
+    }
 }
 
 impl From<PrecompileError> for PrecompileErrors {
@@ -215,6 +308,7 @@ impl fmt::Display for PrecompileError {
             Self::BlobMismatchedVersion => "mismatched blob version",
             Self::BlobVerifyKzgProofFailed => "verifying blob kzg proof failed",
             Self::Other(s) => s,
+            Self::Custom(err) => return err.fmt(f),
         };
         f.write_str(s)
     }
@@ -230,12 +324,7 @@ mod test {
         struct MyPrecompile {}
 
         impl StatefulPrecompileMut for MyPrecompile {
-            fn call_mut(
-                &mut self,
-                _bytes: &Bytes,
-                _gas_price: u64,
-                _env: &Env,
-            ) -> PrecompileResult {
+            fn call_mut(&mut self, _bytes: &[u8], _gas_price: u64, _env: &Env) -> PrecompileResult {
                 Err(PrecompileError::OutOfGas.into())
             }
         }
@@ -248,4 +337,37 @@ mod test {
             _ => panic!("not a state"),
         }
     }
+
+    #[test]
+    fn custom_error() {
+        #[derive(Debug)]
+        struct Denied(u32);
+
+        impl fmt::Display for Denied {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "denied by rule {}", self.0)
+            }
+        }
+
+        impl CustomPrecompileError for Denied {
+            fn code(&self) -> u32 {
+                self.0
+            }
+
+            fn is_fatal(&self) -> bool {
+                false
+            }
+        }
+
+        let err = PrecompileError::custom(Denied(7));
+        assert!(!err.is_fatal());
+        assert!(PrecompileError::OutOfGas.is_fatal());
+        assert_eq!(std::format!("{err}"), "denied by rule 7");
+        assert_eq!(err.downcast_custom::<Denied>().map(|e| e.0), Some(7));
+        assert!(PrecompileError::OutOfGas
+            .downcast_custom::<Denied>()
+            .is_none());
+        assert_eq!(err, PrecompileError::custom(Denied(7)));
+        assert_ne!(err, PrecompileError::custom(Denied(8)));
+    }
 }
@@ -0,0 +1,148 @@
+//! Execution profile collected by the interpreter loop.
+use crate::{HashMap, B256};
+use std::boxed::Box;
+
+/// Default number of steps between two program counter samples of the [`ExecutionProfile`].
+pub const DEFAULT_PROFILE_SAMPLE_INTERVAL: u64 = 64;
+
+/// Opcode histogram and sampled program counters of a transaction, collected when
+/// [`CfgEnv::profile_execution`](crate::CfgEnv::profile_execution) is set.
+///
+/// Every executed instruction is counted, program counters are sampled every
+/// [`ExecutionProfile::sample_interval`] steps to bound the cost of the hash map updates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionProfile {
+    /// Number of executed instructions per opcode, indexed by the opcode.
+    #[cfg_attr(feature = "serde", serde(with = "opcode_counts"))]
+    opcodes: Box<[u64; 256]>,
+    /// Sampled program counters and their hits per code hash.
+    pcs: HashMap<B256, HashMap<usize, u64>>,
+    /// Number of executed instructions.
+    steps: u64,
+    /// Steps between two samples, 0 disables the sampling.
+    sample_interval: u64,
+}
+
+impl Default for ExecutionProfile {
+    fn default() -> Self {
+        Self::new(DEFAULT_PROFILE_SAMPLE_INTERVAL)
+    }
+}
+
+impl ExecutionProfile {
+    /// Creates an empty profile that samples the program counter every `sample_interval`
+    /// steps, 0 disables the sampling.
+    pub fn new(sample_interval: u64) -> Self {
+        Self {
+            opcodes: Box::new([0; 256]),
+            pcs: HashMap::default(),
+            steps: 0,
+            sample_interval,
+        }
+    }
+
+    /// Records the execution of `opcode` at `pc` of the code with `code_hash`.
+    #[inline]
+    pub fn record_step(&mut self, opcode: u8, pc: usize, code_hash: B256) {
+        self.opcodes[opcode as usize] += 1;
+        self.steps += 1;
+        if self.sample_interval != 0 && self.steps.is_multiple_of(self.sample_interval) {
+            *self
+                .pcs
+                .entry(code_hash)
+                .or_default()
+                .entry(pc)
+                .or_default() += 1;
+        }
+    }
+
+    /// Returns the number of executed instructions per opcode, indexed by the opcode.
+    #[inline]
+    pub fn opcodes(&self) -> &[u64; 256] {
+        &self.opcodes
+    }
+
+    /// Returns the number of times `opcode` was executed.
+    #[inline]
+    pub fn opcode_count(&self, opcode: u8) -> u64 {
+        self.opcodes[opcode as usize]
+    }
+
+    /// Returns the number of executed instructions.
+    #[inline]
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Returns the number of steps between two program counter samples.
+    #[inline]
+    pub fn sample_interval(&self) -> u64 {
+        self.sample_interval
+    }
+
+    /// Returns the sampled program counters and their hits per code hash.
+    #[inline]
+    pub fn pc_samples(&self) -> &HashMap<B256, HashMap<usize, u64>> {
+        &self.pcs
+    }
+
+    /// Returns the sampled program counters of the code with `code_hash` and their hits.
+    #[inline]
+    pub fn pc_hits(&self, code_hash: &B256) -> Option<&HashMap<usize, u64>> {
+        self.pcs.get(code_hash)
+    }
+}
+
+/// Serializes the opcode counts as a sequence, serde does not support arrays of 256 elements.
+#[cfg(feature = "serde")]
+mod opcode_counts {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+    use std::{boxed::Box, vec::Vec};
+
+    pub(super) fn serialize<S: Serializer>(
+        counts: &[u64; 256],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        counts.as_slice().serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Box<[u64; 256]>, D::Error> {
+        let counts = Vec::<u64>::deserialize(deserializer)?;
+        let len = counts.len();
+        counts
+            .into_boxed_slice()
+            .try_into()
+            .map_err(|_| D::Error::invalid_length(len, &"256 opcode counts"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_and_sample() {
+        let code_hash = B256::with_last_byte(1);
+        let mut profile = ExecutionProfile::new(2);
+        for pc in 0..5 {
+            profile.record_step(0x5f, pc, code_hash);
+        }
+        profile.record_step(0x00, 5, code_hash);
+
+        assert_eq!(profile.steps(), 6);
+        assert_eq!(profile.opcode_count(0x5f), 5);
+        assert_eq!(profile.opcode_count(0x00), 1);
+        assert_eq!(profile.opcodes().iter().sum::<u64>(), 6);
+        // every second step is sampled.
+        let hits = profile.pc_hits(&code_hash).unwrap();
+        assert_eq!(hits.len(), 3);
+        assert!([1, 3, 5].iter().all(|pc| hits[pc] == 1));
+
+        let mut profile = ExecutionProfile::new(0);
+        profile.record_step(0x00, 0, code_hash);
+        assert!(profile.pc_samples().is_empty());
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{Address, Bytes, EvmState, Log, U256};
+use crate::{
+    db::PrepareCommitError, hex, AccountInfo, Address, Bytes, EvmState, ExecutionProfile, Log,
+    TxType, U256,
+};
 use core::fmt;
 use std::{boxed::Box, string::String, vec::Vec};
 
@@ -15,6 +18,44 @@ pub struct ResultAndState {
     pub result: ExecutionResult,
     /// State that got updated
     pub state: EvmState,
+    /// Type of the executed transaction, see [`TxEnv::tx_type`](crate::TxEnv::tx_type).
+    pub tx_type: TxType,
+    /// `SELFDESTRUCT`s executed in the frames that were not reverted, in execution order.
+    pub selfdestructs: Vec<SelfDestruct>,
+    /// Execution profile, set only if
+    /// [`CfgEnv::profile_execution`](crate::CfgEnv::profile_execution) is enabled.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub profile: Option<Box<ExecutionProfile>>,
+    /// L1 data fee charged for the transaction, `None` for deposit transactions and if
+    /// [`CfgEnv::optimism_no_l1_fee`](crate::CfgEnv::optimism_no_l1_fee) is set.
+    #[cfg(feature = "optimism")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub l1_fee: Option<U256>,
+}
+
+/// Executed `SELFDESTRUCT` and the balance it sent to the beneficiary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelfDestruct {
+    /// Account that executed `SELFDESTRUCT`.
+    pub address: Address,
+    /// Beneficiary of the balance.
+    pub target: Address,
+    /// Balance of the account when `SELFDESTRUCT` was executed.
+    ///
+    /// If the target is the account itself the balance is burned when the account is deleted,
+    /// otherwise it stays unchanged.
+    pub value: U256,
+    /// Whether the account is deleted at the end of the transaction.
+    ///
+    /// After Cancun (EIP-6780) only accounts created in the same transaction are deleted.
+    pub deleted: bool,
 }
 
 /// Result of a transaction execution.
@@ -98,6 +139,121 @@ impl ExecutionResult {
             | Self::Halt { gas_used, .. } => gas_used,
         }
     }
+
+    /// Returns the logs, output and gas used if execution is successful.
+    pub fn as_success(&self) -> Option<(&[Log], &Output, u64)> {
+        match self {
+            Self::Success {
+                logs,
+                output,
+                gas_used,
+                ..
+            } => Some((logs, output, *gas_used)),
+            _ => None,
+        }
+    }
+
+    /// Returns the output data if execution is reverted.
+    pub fn as_revert(&self) -> Option<&Bytes> {
+        match self {
+            Self::Revert { output, .. } => Some(output),
+            _ => None,
+        }
+    }
+
+    /// Consumes `self` and returns the logs, output and gas used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if execution is not successful.
+    #[track_caller]
+    pub fn expect_success(self) -> (Vec<Log>, Output, u64) {
+        match self {
+            Self::Success {
+                logs,
+                output,
+                gas_used,
+                ..
+            } => (logs, output, gas_used),
+            result => panic!("expected success, got {}", result.summary()),
+        }
+    }
+
+    /// Consumes `self` and returns the output data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if execution is not reverted.
+    #[track_caller]
+    pub fn expect_revert(self) -> Bytes {
+        match self {
+            Self::Revert { output, .. } => output,
+            result => panic!("expected revert, got {}", result.summary()),
+        }
+    }
+
+    /// Asserts that execution halted with the given `reason`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if execution is not halted or is halted with a different reason.
+    #[track_caller]
+    pub fn expect_halt(self, reason: HaltReason) {
+        match self {
+            Self::Halt { reason: halt, .. } if halt == reason => {}
+            result => panic!("expected halt with {reason:?}, got {}", result.summary()),
+        }
+    }
+
+    /// Returns a short description of the result, used in panic messages.
+    fn summary(&self) -> String {
+        let selector = |output: &Bytes| hex::encode_prefixed(&output[..output.len().min(4)]);
+        match self {
+            Self::Success {
+                reason,
+                gas_used,
+                output,
+                ..
+            } => std::format!(
+                "success ({reason:?}), gas used: {gas_used}, output: {}",
+                selector(output.data())
+            ),
+            Self::Revert { gas_used, output } => std::format!(
+                "revert ({}), gas used: {gas_used}, output: {}",
+                decode_revert_reason(output).unwrap_or_else(|| String::from("unknown reason")),
+                selector(output)
+            ),
+            Self::Halt { reason, gas_used } => {
+                std::format!("halt ({reason:?}), gas used: {gas_used}")
+            }
+        }
+    }
+}
+
+/// Decodes the revert reason of the `Error(string)` and `Panic(uint256)` Solidity errors.
+///
+/// Returns `None` if the output is not one of them.
+pub fn decode_revert_reason(output: &[u8]) -> Option<String> {
+    const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+    const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+    let (selector, data) = (output.get(..4)?, output.get(4..)?);
+    let word = |offset: usize| -> Option<usize> {
+        let word = U256::from_be_slice(data.get(offset..offset.checked_add(32)?)?);
+        word.try_into().ok()
+    };
+    if selector == PANIC_SELECTOR {
+        let code = U256::from_be_slice(data.get(..32)?);
+        return Some(std::format!("panic: {code:#x}"));
+    }
+    if selector != ERROR_SELECTOR {
+        return None;
+    }
+    let offset = word(0)?;
+    let len = word(offset)?;
+    let start = offset.checked_add(32)?;
+    let reason = data.get(start..start.checked_add(len)?)?;
+    core::str::from_utf8(reason).ok().map(String::from)
 }
 
 /// Output of a transaction execution.
@@ -150,6 +306,30 @@ pub enum EVMError<DBError> {
     Custom(String),
     /// Precompile error.
     Precompile(String),
+    /// Transaction was modified after it was pre-verified.
+    ///
+    /// Returned by `transact_preverified` when [`CfgEnv::revalidate_stale_tx`](crate::CfgEnv::revalidate_stale_tx)
+    /// is not set.
+    StaleValidation,
+    /// Database returned different values for the same key inside one transaction.
+    ///
+    /// Only checked when [`CfgEnv::paranoid_db_checks`](crate::CfgEnv::paranoid_db_checks) is set.
+    DatabaseInconsistency(Box<DatabaseInconsistency>),
+    /// Changes can't be prepared for a two-phase commit.
+    ///
+    /// Returned by `transact_prepare`.
+    PrepareCommit(PrepareCommitError),
+    /// Storage of the L1Block predeploy does not have the layout of the spec.
+    #[cfg(feature = "optimism")]
+    L1BlockLayoutMismatch {
+        expected: crate::L1BlockLayout,
+        found: crate::L1BlockLayout,
+    },
+    /// Transaction envelope can't be decoded.
+    ///
+    /// Returned by `transact_raw`.
+    #[cfg(feature = "alloy-consensus")]
+    TxDecode(crate::TxDecodeError),
 }
 
 impl<DBError> EVMError<DBError> {
@@ -164,6 +344,15 @@ impl<DBError> EVMError<DBError> {
             Self::Database(e) => EVMError::Database(op(e)),
             Self::Precompile(e) => EVMError::Precompile(e),
             Self::Custom(e) => EVMError::Custom(e),
+            Self::StaleValidation => EVMError::StaleValidation,
+            Self::DatabaseInconsistency(e) => EVMError::DatabaseInconsistency(e),
+            Self::PrepareCommit(e) => EVMError::PrepareCommit(e),
+            #[cfg(feature = "optimism")]
+            Self::L1BlockLayoutMismatch { expected, found } => {
+                EVMError::L1BlockLayoutMismatch { expected, found }
+            }
+            #[cfg(feature = "alloy-consensus")]
+            Self::TxDecode(e) => EVMError::TxDecode(e),
         }
     }
 }
@@ -175,7 +364,13 @@ impl<DBError: std::error::Error + 'static> std::error::Error for EVMError<DBErro
             Self::Transaction(e) => Some(e),
             Self::Header(e) => Some(e),
             Self::Database(e) => Some(e),
-            Self::Precompile(_) | Self::Custom(_) => None,
+            Self::DatabaseInconsistency(e) => Some(e.as_ref()),
+            Self::PrepareCommit(e) => Some(e),
+            #[cfg(feature = "alloy-consensus")]
+            Self::TxDecode(e) => Some(e),
+            Self::Precompile(_) | Self::Custom(_) | Self::StaleValidation => None,
+            #[cfg(feature = "optimism")]
+            Self::L1BlockLayoutMismatch { .. } => None,
         }
     }
 }
@@ -187,6 +382,102 @@ impl<DBError: fmt::Display> fmt::Display for EVMError<DBError> {
             Self::Header(e) => write!(f, "header validation error: {e}"),
             Self::Database(e) => write!(f, "database error: {e}"),
             Self::Precompile(e) | Self::Custom(e) => f.write_str(e),
+            Self::StaleValidation => f.write_str("transaction modified after pre-verification"),
+            Self::DatabaseInconsistency(e) => write!(f, "database inconsistency: {e}"),
+            Self::PrepareCommit(e) => write!(f, "prepare commit error: {e}"),
+            #[cfg(feature = "optimism")]
+            Self::L1BlockLayoutMismatch { expected, found } => write!(
+                f,
+                "L1Block storage layout mismatch: expected {expected}, found {found}"
+            ),
+            #[cfg(feature = "alloy-consensus")]
+            Self::TxDecode(e) => write!(f, "transaction decoding error: {e}"),
+        }
+    }
+}
+
+impl<DBError> From<PrepareCommitError> for EVMError<DBError> {
+    fn from(value: PrepareCommitError) -> Self {
+        Self::PrepareCommit(value)
+    }
+}
+
+#[cfg(feature = "alloy-consensus")]
+impl<DBError> From<crate::TxDecodeError> for EVMError<DBError> {
+    fn from(value: crate::TxDecodeError) -> Self {
+        Self::TxDecode(value)
+    }
+}
+
+impl<DBError> From<DatabaseInconsistency> for EVMError<DBError> {
+    fn from(value: DatabaseInconsistency) -> Self {
+        Self::DatabaseInconsistency(Box::new(value))
+    }
+}
+
+impl<DBError> From<Box<DatabaseInconsistency>> for EVMError<DBError> {
+    fn from(value: Box<DatabaseInconsistency>) -> Self {
+        Self::DatabaseInconsistency(value)
+    }
+}
+
+/// Two reads of the same database key returned different values inside one transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DatabaseInconsistency {
+    /// Account info returned by `Database::basic` changed.
+    ///
+    /// Accounts are compared by balance, nonce and code hash, code is not included.
+    Basic {
+        address: Address,
+        first: Option<AccountInfo>,
+        second: Option<AccountInfo>,
+    },
+    /// Value returned by `Database::storage` changed.
+    Storage {
+        address: Address,
+        slot: U256,
+        first: U256,
+        second: U256,
+    },
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DatabaseInconsistency {}
+
+impl fmt::Display for DatabaseInconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn account(f: &mut fmt::Formatter<'_>, info: &Option<AccountInfo>) -> fmt::Result {
+            match info {
+                Some(info) => write!(
+                    f,
+                    "{{ balance: {}, nonce: {}, code_hash: {} }}",
+                    info.balance, info.nonce, info.code_hash
+                ),
+                None => f.write_str("None"),
+            }
+        }
+
+        match self {
+            Self::Basic {
+                address,
+                first,
+                second,
+            } => {
+                write!(f, "account {address} was read as ")?;
+                account(f, first)?;
+                f.write_str(" and later as ")?;
+                account(f, second)
+            }
+            Self::Storage {
+                address,
+                slot,
+                first,
+                second,
+            } => write!(
+                f,
+                "storage slot {slot} of {address} was read as {first} and later as {second}"
+            ),
         }
     }
 }
@@ -217,12 +508,19 @@ pub enum InvalidTransaction {
     GasPriceLessThanBasefee,
     /// `gas_limit` in the tx is bigger than `block_gas_limit`.
     CallerGasLimitMoreThanBlock,
+    /// `gas_limit` in the tx is bigger than [`CfgEnv::tx_gas_limit_cap`](crate::CfgEnv::tx_gas_limit_cap).
+    CallerGasLimitMoreThanCap {
+        gas_limit: u64,
+        cap: u64,
+    },
     /// Initial gas for a Call is bigger than `gas_limit`.
     ///
     /// Initial gas for a Call contains:
     /// - initial stipend gas
     /// - gas for access list and input data
     CallGasCostMoreThanGasLimit,
+    /// EIP-7623: Gas floor of the transaction calldata is bigger than `gas_limit`.
+    GasFloorMoreThanGasLimit,
     /// EIP-3607 Reject transactions from senders with deployed code
     RejectCallerWithCode,
     /// Transaction account does not have enough amount of ether to cover transferred value and gas_limit*gas_price.
@@ -272,6 +570,29 @@ pub enum InvalidTransaction {
     AuthorizationListNotSupported,
     /// EIP-7702 transaction has invalid fields set.
     AuthorizationListInvalidFields,
+    /// EIP-7702 transaction has empty authorization list.
+    EmptyAuthorizationList,
+    /// EIP-7702 transaction can't be a create transaction.
+    /// `to` must be present
+    AuthorizationListCreateTransaction,
+    /// Initcode transactions are not enabled.
+    InitcodesNotSupported,
+    /// Initcode transaction can't be a create transaction.
+    /// `to` must be present
+    InitcodesCreateTransaction,
+    /// Transaction has more than [`crate::MAX_INITCODE_COUNT`] initcodes.
+    TooManyInitcodes {
+        max: usize,
+        have: usize,
+    },
+    /// Initcode of the initcode transaction is not a valid EOF initcontainer.
+    InvalidEofInitcode,
+    /// Transaction calls the zero address while
+    /// [`CfgEnv::require_transact_to`](crate::CfgEnv::require_transact_to) is set.
+    TransactToNotSet,
+    /// Transaction mints balance while [`CfgEnv::allow_mint`](crate::CfgEnv::allow_mint) is
+    /// not set, or an Optimism deposit sets [`TxEnv::mint`](crate::TxEnv::mint).
+    MintNotAllowed,
     /// System transactions are not supported post-regolith hardfork.
     ///
     /// Before the Regolith hardfork, there was a special field in the `Deposit` transaction
@@ -321,9 +642,18 @@ impl fmt::Display for InvalidTransaction {
             Self::CallerGasLimitMoreThanBlock => {
                 write!(f, "caller gas limit exceeds the block gas limit")
             }
+            Self::CallerGasLimitMoreThanCap { gas_limit, cap } => {
+                write!(
+                    f,
+                    "caller gas limit {gas_limit} exceeds the gas limit cap {cap}"
+                )
+            }
             Self::CallGasCostMoreThanGasLimit => {
                 write!(f, "call gas cost exceeds the gas limit")
             }
+            Self::GasFloorMoreThanGasLimit => {
+                write!(f, "gas floor exceeds the gas limit")
+            }
             Self::RejectCallerWithCode => {
                 write!(f, "reject transactions from senders with deployed code")
             }
@@ -367,6 +697,20 @@ impl fmt::Display for InvalidTransaction {
             Self::AuthorizationListInvalidFields => {
                 write!(f, "authorization list tx has invalid fields")
             }
+            Self::EmptyAuthorizationList => write!(f, "empty authorization list"),
+            Self::AuthorizationListCreateTransaction => {
+                write!(f, "authorization list tx cannot create contracts")
+            }
+            Self::InitcodesNotSupported => write!(f, "initcodes not supported"),
+            Self::InitcodesCreateTransaction => {
+                write!(f, "initcode tx cannot create contracts")
+            }
+            Self::TooManyInitcodes { max, have } => {
+                write!(f, "too many initcodes, have {have}, max {max}")
+            }
+            Self::InvalidEofInitcode => write!(f, "invalid EOF initcode"),
+            Self::TransactToNotSet => write!(f, "transact_to is not set"),
+            Self::MintNotAllowed => write!(f, "mint is not allowed"),
             #[cfg(feature = "optimism")]
             Self::DepositSystemTxPostRegolith => {
                 write!(
@@ -408,69 +752,546 @@ impl fmt::Display for InvalidHeader {
 }
 
 /// Reason a transaction successfully completed.
+///
+/// Every variant has a stable identifier, returned by [`SuccessReason::as_str`] and used by serde.
+/// Identifiers do not change when variants are reordered or renamed.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SuccessReason {
+    #[cfg_attr(feature = "serde", serde(rename = "Stop"))]
     Stop,
+    #[cfg_attr(feature = "serde", serde(rename = "Return"))]
     Return,
+    #[cfg_attr(feature = "serde", serde(rename = "SelfDestruct"))]
     SelfDestruct,
+    #[cfg_attr(feature = "serde", serde(rename = "EofReturnContract"))]
     EofReturnContract,
 }
 
+impl SuccessReason {
+    /// Returns the stable identifier of the reason.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Stop => "Stop",
+            Self::Return => "Return",
+            Self::SelfDestruct => "SelfDestruct",
+            Self::EofReturnContract => "EofReturnContract",
+        }
+    }
+}
+
+impl core::str::FromStr for SuccessReason {
+    type Err = UnknownIdentifier;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Stop" => Self::Stop,
+            "Return" => Self::Return,
+            "SelfDestruct" => Self::SelfDestruct,
+            "EofReturnContract" => Self::EofReturnContract,
+            _ => return Err(UnknownIdentifier),
+        })
+    }
+}
+
+impl fmt::Display for SuccessReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stop => write!(f, "stop"),
+            Self::Return => write!(f, "return"),
+            Self::SelfDestruct => write!(f, "selfdestruct"),
+            Self::EofReturnContract => write!(f, "EOF return contract"),
+        }
+    }
+}
+
 /// Indicates that the EVM has experienced an exceptional halt. This causes execution to
 /// immediately end with all gas being consumed.
+///
+/// Every variant has a stable identifier, returned by [`HaltReason::as_str`]. Identifiers do not
+/// change when variants are reordered or renamed.
+///
+/// Serde uses the same identifiers for the variant names, except that out of gas halts are
+/// externally tagged with the kind: `{"OutOfGas":"Basic"}` instead of `OutOfGas(Basic)`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HaltReason {
+    #[cfg_attr(feature = "serde", serde(rename = "OutOfGas"))]
     OutOfGas(OutOfGasError),
+    #[cfg_attr(feature = "serde", serde(rename = "OpcodeNotFound"))]
     OpcodeNotFound,
+    #[cfg_attr(feature = "serde", serde(rename = "InvalidEFOpcode"))]
     InvalidEFOpcode,
+    #[cfg_attr(feature = "serde", serde(rename = "InvalidJump"))]
     InvalidJump,
+    #[cfg_attr(feature = "serde", serde(rename = "NotActivated"))]
     NotActivated,
+    #[cfg_attr(feature = "serde", serde(rename = "StackUnderflow"))]
     StackUnderflow,
+    #[cfg_attr(feature = "serde", serde(rename = "StackOverflow"))]
     StackOverflow,
+    #[cfg_attr(feature = "serde", serde(rename = "OutOfOffset"))]
     OutOfOffset,
+    #[cfg_attr(feature = "serde", serde(rename = "CreateCollision"))]
     CreateCollision,
+    #[cfg_attr(feature = "serde", serde(rename = "PrecompileError"))]
     PrecompileError,
+    #[cfg_attr(feature = "serde", serde(rename = "NonceOverflow"))]
     NonceOverflow,
     /// Create init code size exceeds limit (runtime).
+    #[cfg_attr(feature = "serde", serde(rename = "CreateContractSizeLimit"))]
     CreateContractSizeLimit,
     /// Error on created contract that begins with EF
+    #[cfg_attr(feature = "serde", serde(rename = "CreateContractStartingWithEF"))]
     CreateContractStartingWithEF,
     /// EIP-3860: Limit and meter initcode. Initcode size limit exceeded.
+    #[cfg_attr(feature = "serde", serde(rename = "CreateInitCodeSizeLimit"))]
     CreateInitCodeSizeLimit,
 
     /* Internal Halts that can be only found inside Inspector */
+    #[cfg_attr(feature = "serde", serde(rename = "OverflowPayment"))]
     OverflowPayment,
+    #[cfg_attr(feature = "serde", serde(rename = "StateChangeDuringStaticCall"))]
     StateChangeDuringStaticCall,
+    #[cfg_attr(feature = "serde", serde(rename = "CallNotAllowedInsideStatic"))]
     CallNotAllowedInsideStatic,
+    #[cfg_attr(feature = "serde", serde(rename = "OutOfFunds"))]
     OutOfFunds,
+    #[cfg_attr(feature = "serde", serde(rename = "CallTooDeep"))]
     CallTooDeep,
 
     /// Aux data overflow, new aux data is larger tha u16 max size.
+    #[cfg_attr(feature = "serde", serde(rename = "EofAuxDataOverflow"))]
     EofAuxDataOverflow,
     /// Aud data is smaller then already present data size.
+    #[cfg_attr(feature = "serde", serde(rename = "EofAuxDataTooSmall"))]
     EofAuxDataTooSmall,
     /// EOF Subroutine stack overflow
+    #[cfg_attr(feature = "serde", serde(rename = "EOFFunctionStackOverflow"))]
     EOFFunctionStackOverflow,
 
     /* Optimism errors */
     #[cfg(feature = "optimism")]
+    #[cfg_attr(feature = "serde", serde(rename = "FailedDeposit"))]
     FailedDeposit,
 }
 
+impl HaltReason {
+    /// Returns true if the halt is specific to Optimism deposit transactions.
+    ///
+    /// Optimism halts are variants of [HaltReason] so chain generic code can handle
+    /// mainnet and optimism results with the same type.
+    pub const fn is_optimism(&self) -> bool {
+        #[cfg(feature = "optimism")]
+        if matches!(self, Self::FailedDeposit) {
+            return true;
+        }
+        false
+    }
+
+    /// Returns the stable identifier of the halt.
+    ///
+    /// Out of gas halts include the kind, e.g. `OutOfGas(Memory)`.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::OutOfGas(OutOfGasError::Basic) => "OutOfGas(Basic)",
+            Self::OutOfGas(OutOfGasError::MemoryLimit) => "OutOfGas(MemoryLimit)",
+            Self::OutOfGas(OutOfGasError::Memory) => "OutOfGas(Memory)",
+            Self::OutOfGas(OutOfGasError::Precompile) => "OutOfGas(Precompile)",
+            Self::OutOfGas(OutOfGasError::InvalidOperand) => "OutOfGas(InvalidOperand)",
+            Self::OpcodeNotFound => "OpcodeNotFound",
+            Self::InvalidEFOpcode => "InvalidEFOpcode",
+            Self::InvalidJump => "InvalidJump",
+            Self::NotActivated => "NotActivated",
+            Self::StackUnderflow => "StackUnderflow",
+            Self::StackOverflow => "StackOverflow",
+            Self::OutOfOffset => "OutOfOffset",
+            Self::CreateCollision => "CreateCollision",
+            Self::PrecompileError => "PrecompileError",
+            Self::NonceOverflow => "NonceOverflow",
+            Self::CreateContractSizeLimit => "CreateContractSizeLimit",
+            Self::CreateContractStartingWithEF => "CreateContractStartingWithEF",
+            Self::CreateInitCodeSizeLimit => "CreateInitCodeSizeLimit",
+            Self::OverflowPayment => "OverflowPayment",
+            Self::StateChangeDuringStaticCall => "StateChangeDuringStaticCall",
+            Self::CallNotAllowedInsideStatic => "CallNotAllowedInsideStatic",
+            Self::OutOfFunds => "OutOfFunds",
+            Self::CallTooDeep => "CallTooDeep",
+            Self::EofAuxDataOverflow => "EofAuxDataOverflow",
+            Self::EofAuxDataTooSmall => "EofAuxDataTooSmall",
+            Self::EOFFunctionStackOverflow => "EOFFunctionStackOverflow",
+            #[cfg(feature = "optimism")]
+            Self::FailedDeposit => "FailedDeposit",
+        }
+    }
+}
+
+impl core::str::FromStr for HaltReason {
+    type Err = UnknownIdentifier;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(kind) = s
+            .strip_prefix("OutOfGas(")
+            .and_then(|s| s.strip_suffix(')'))
+        {
+            return kind.parse().map(Self::OutOfGas);
+        }
+        Ok(match s {
+            "OpcodeNotFound" => Self::OpcodeNotFound,
+            "InvalidEFOpcode" => Self::InvalidEFOpcode,
+            "InvalidJump" => Self::InvalidJump,
+            "NotActivated" => Self::NotActivated,
+            "StackUnderflow" => Self::StackUnderflow,
+            "StackOverflow" => Self::StackOverflow,
+            "OutOfOffset" => Self::OutOfOffset,
+            "CreateCollision" => Self::CreateCollision,
+            "PrecompileError" => Self::PrecompileError,
+            "NonceOverflow" => Self::NonceOverflow,
+            "CreateContractSizeLimit" => Self::CreateContractSizeLimit,
+            "CreateContractStartingWithEF" => Self::CreateContractStartingWithEF,
+            "CreateInitCodeSizeLimit" => Self::CreateInitCodeSizeLimit,
+            "OverflowPayment" => Self::OverflowPayment,
+            "StateChangeDuringStaticCall" => Self::StateChangeDuringStaticCall,
+            "CallNotAllowedInsideStatic" => Self::CallNotAllowedInsideStatic,
+            "OutOfFunds" => Self::OutOfFunds,
+            "CallTooDeep" => Self::CallTooDeep,
+            "EofAuxDataOverflow" => Self::EofAuxDataOverflow,
+            "EofAuxDataTooSmall" => Self::EofAuxDataTooSmall,
+            "EOFFunctionStackOverflow" => Self::EOFFunctionStackOverflow,
+            #[cfg(feature = "optimism")]
+            "FailedDeposit" => Self::FailedDeposit,
+            _ => return Err(UnknownIdentifier),
+        })
+    }
+}
+
+impl fmt::Display for HaltReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfGas(err) => write!(f, "out of gas: {err}"),
+            Self::OpcodeNotFound => write!(f, "opcode not found"),
+            Self::InvalidEFOpcode => write!(f, "invalid 0xEF opcode"),
+            Self::InvalidJump => write!(f, "invalid jump destination"),
+            Self::NotActivated => write!(f, "feature or opcode not activated"),
+            Self::StackUnderflow => write!(f, "stack underflow"),
+            Self::StackOverflow => write!(f, "stack overflow"),
+            Self::OutOfOffset => write!(f, "out of offset"),
+            Self::CreateCollision => write!(f, "create collision"),
+            Self::PrecompileError => write!(f, "precompile error"),
+            Self::NonceOverflow => write!(f, "nonce overflow"),
+            Self::CreateContractSizeLimit => write!(f, "create contract size limit"),
+            Self::CreateContractStartingWithEF => {
+                write!(f, "create contract starting with 0xEF")
+            }
+            Self::CreateInitCodeSizeLimit => write!(f, "create initcode size limit"),
+            Self::OverflowPayment => write!(f, "overflow payment"),
+            Self::StateChangeDuringStaticCall => write!(f, "state change during static call"),
+            Self::CallNotAllowedInsideStatic => write!(f, "call not allowed inside static call"),
+            Self::OutOfFunds => write!(f, "out of funds"),
+            Self::CallTooDeep => write!(f, "call too deep"),
+            Self::EofAuxDataOverflow => write!(f, "EOF aux data overflow"),
+            Self::EofAuxDataTooSmall => write!(f, "EOF aux data too small"),
+            Self::EOFFunctionStackOverflow => write!(f, "EOF function stack overflow"),
+            #[cfg(feature = "optimism")]
+            Self::FailedDeposit => write!(f, "failed deposit"),
+        }
+    }
+}
+
+/// Kind of out of gas halt.
+///
+/// Every variant has a stable identifier, returned by [`OutOfGasError::as_str`] and used by serde.
+/// Identifiers do not change when variants are reordered or renamed.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OutOfGasError {
     // Basic OOG error
+    #[cfg_attr(feature = "serde", serde(rename = "Basic"))]
     Basic,
     // Tried to expand past REVM limit
+    #[cfg_attr(feature = "serde", serde(rename = "MemoryLimit"))]
     MemoryLimit,
     // Basic OOG error from memory expansion
+    #[cfg_attr(feature = "serde", serde(rename = "Memory"))]
     Memory,
     // Precompile threw OOG error
+    #[cfg_attr(feature = "serde", serde(rename = "Precompile"))]
     Precompile,
     // When performing something that takes a U256 and casts down to a u64, if its too large this would fire
     // i.e. in `as_usize_or_fail`
+    #[cfg_attr(feature = "serde", serde(rename = "InvalidOperand"))]
     InvalidOperand,
 }
+
+impl fmt::Display for OutOfGasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Basic => write!(f, "basic"),
+            Self::MemoryLimit => write!(f, "memory limit"),
+            Self::Memory => write!(f, "memory expansion"),
+            Self::Precompile => write!(f, "precompile"),
+            Self::InvalidOperand => write!(f, "invalid operand"),
+        }
+    }
+}
+
+impl OutOfGasError {
+    /// Returns the stable identifier of the kind.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Basic => "Basic",
+            Self::MemoryLimit => "MemoryLimit",
+            Self::Memory => "Memory",
+            Self::Precompile => "Precompile",
+            Self::InvalidOperand => "InvalidOperand",
+        }
+    }
+}
+
+impl core::str::FromStr for OutOfGasError {
+    type Err = UnknownIdentifier;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Basic" => Self::Basic,
+            "MemoryLimit" => Self::MemoryLimit,
+            "Memory" => Self::Memory,
+            "Precompile" => Self::Precompile,
+            "InvalidOperand" => Self::InvalidOperand,
+            _ => return Err(UnknownIdentifier),
+        })
+    }
+}
+
+/// Error returned when parsing an unknown stable identifier, see [`HaltReason::as_str`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnknownIdentifier;
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownIdentifier {}
+
+impl fmt::Display for UnknownIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unknown identifier")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bytes, hex};
+
+    fn revert(output: Bytes) -> ExecutionResult {
+        ExecutionResult::Revert {
+            gas_used: 100,
+            output,
+        }
+    }
+
+    #[test]
+    fn decode_error_and_panic() {
+        // Error("nope")
+        let error = hex!("08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000046e6f706500000000000000000000000000000000000000000000000000000000");
+        assert_eq!(decode_revert_reason(&error).as_deref(), Some("nope"));
+        // Panic(0x11), arithmetic overflow.
+        let panic =
+            hex!("4e487b710000000000000000000000000000000000000000000000000000000000000011");
+        assert_eq!(decode_revert_reason(&panic).as_deref(), Some("panic: 0x11"));
+
+        assert_eq!(decode_revert_reason(&error[..40]), None);
+        assert_eq!(decode_revert_reason(&[0xde, 0xad]), None);
+    }
+
+    /// Summarizes halts of any chain, mainnet and optimism halts share [HaltReason].
+    fn summarize_halts(results: &[ExecutionResult]) -> Vec<String> {
+        results
+            .iter()
+            .filter_map(|result| match result {
+                ExecutionResult::Halt { reason, gas_used } => {
+                    Some(std::format!("{reason} ({gas_used} gas)"))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn halt_reason_display() {
+        let results = [
+            ExecutionResult::Halt {
+                reason: HaltReason::OutOfGas(OutOfGasError::Memory),
+                gas_used: 100,
+            },
+            revert(Bytes::new()),
+            ExecutionResult::Halt {
+                reason: HaltReason::StackUnderflow,
+                gas_used: 21_000,
+            },
+        ];
+        assert_eq!(
+            summarize_halts(&results),
+            [
+                "out of gas: memory expansion (100 gas)",
+                "stack underflow (21000 gas)"
+            ]
+        );
+        assert!(!HaltReason::OpcodeNotFound.is_optimism());
+        #[cfg(feature = "optimism")]
+        {
+            assert!(HaltReason::FailedDeposit.is_optimism());
+            assert_eq!(HaltReason::FailedDeposit.to_string(), "failed deposit");
+        }
+    }
+
+    #[test]
+    fn accessors() {
+        let output = bytes!("deadbeef");
+        let result = revert(output.clone());
+        assert_eq!(result.as_revert(), Some(&output));
+        assert_eq!(result.as_success(), None);
+        assert_eq!(result.expect_revert(), output);
+
+        let result = ExecutionResult::Success {
+            reason: SuccessReason::Return,
+            gas_used: 21_000,
+            gas_refunded: 0,
+            logs: Vec::new(),
+            output: Output::Call(output.clone()),
+        };
+        assert_eq!(
+            result.as_success(),
+            Some((&[][..], &Output::Call(output.clone()), 21_000))
+        );
+        assert_eq!(
+            result.expect_success(),
+            (Vec::new(), Output::Call(output), 21_000)
+        );
+
+        ExecutionResult::Halt {
+            reason: HaltReason::OpcodeNotFound,
+            gas_used: 100,
+        }
+        .expect_halt(HaltReason::OpcodeNotFound);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "expected success, got revert (nope), gas used: 100, output: 0x08c379a0"
+    )]
+    fn expect_success_panics_with_reason() {
+        let error = hex!("08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000046e6f706500000000000000000000000000000000000000000000000000000000");
+        revert(Bytes::copy_from_slice(&error)).expect_success();
+    }
+
+    #[test]
+    #[should_panic(expected = "expected halt with OutOfFunds, got halt (OpcodeNotFound)")]
+    fn expect_halt_panics_on_other_reason() {
+        ExecutionResult::Halt {
+            reason: HaltReason::OpcodeNotFound,
+            gas_used: 100,
+        }
+        .expect_halt(HaltReason::OutOfFunds);
+    }
+
+    /// Identifiers are part of the RPC output, changing one is a breaking change.
+    #[test]
+    fn stable_identifiers() {
+        let success = [
+            (SuccessReason::Stop, "Stop"),
+            (SuccessReason::Return, "Return"),
+            (SuccessReason::SelfDestruct, "SelfDestruct"),
+            (SuccessReason::EofReturnContract, "EofReturnContract"),
+        ];
+        for (reason, id) in success {
+            assert_eq!(reason.as_str(), id);
+            assert_eq!(id.parse(), Ok(reason));
+        }
+
+        let out_of_gas = [
+            (OutOfGasError::Basic, "Basic"),
+            (OutOfGasError::MemoryLimit, "MemoryLimit"),
+            (OutOfGasError::Memory, "Memory"),
+            (OutOfGasError::Precompile, "Precompile"),
+            (OutOfGasError::InvalidOperand, "InvalidOperand"),
+        ];
+        for (error, id) in out_of_gas {
+            assert_eq!(error.as_str(), id);
+            assert_eq!(id.parse(), Ok(error));
+        }
+
+        let halts = [
+            (
+                HaltReason::OutOfGas(OutOfGasError::Basic),
+                "OutOfGas(Basic)",
+            ),
+            (
+                HaltReason::OutOfGas(OutOfGasError::MemoryLimit),
+                "OutOfGas(MemoryLimit)",
+            ),
+            (
+                HaltReason::OutOfGas(OutOfGasError::Memory),
+                "OutOfGas(Memory)",
+            ),
+            (
+                HaltReason::OutOfGas(OutOfGasError::Precompile),
+                "OutOfGas(Precompile)",
+            ),
+            (
+                HaltReason::OutOfGas(OutOfGasError::InvalidOperand),
+                "OutOfGas(InvalidOperand)",
+            ),
+            (HaltReason::OpcodeNotFound, "OpcodeNotFound"),
+            (HaltReason::InvalidEFOpcode, "InvalidEFOpcode"),
+            (HaltReason::InvalidJump, "InvalidJump"),
+            (HaltReason::NotActivated, "NotActivated"),
+            (HaltReason::StackUnderflow, "StackUnderflow"),
+            (HaltReason::StackOverflow, "StackOverflow"),
+            (HaltReason::OutOfOffset, "OutOfOffset"),
+            (HaltReason::CreateCollision, "CreateCollision"),
+            (HaltReason::PrecompileError, "PrecompileError"),
+            (HaltReason::NonceOverflow, "NonceOverflow"),
+            (
+                HaltReason::CreateContractSizeLimit,
+                "CreateContractSizeLimit",
+            ),
+            (
+                HaltReason::CreateContractStartingWithEF,
+                "CreateContractStartingWithEF",
+            ),
+            (
+                HaltReason::CreateInitCodeSizeLimit,
+                "CreateInitCodeSizeLimit",
+            ),
+            (HaltReason::OverflowPayment, "OverflowPayment"),
+            (
+                HaltReason::StateChangeDuringStaticCall,
+                "StateChangeDuringStaticCall",
+            ),
+            (
+                HaltReason::CallNotAllowedInsideStatic,
+                "CallNotAllowedInsideStatic",
+            ),
+            (HaltReason::OutOfFunds, "OutOfFunds"),
+            (HaltReason::CallTooDeep, "CallTooDeep"),
+            (HaltReason::EofAuxDataOverflow, "EofAuxDataOverflow"),
+            (HaltReason::EofAuxDataTooSmall, "EofAuxDataTooSmall"),
+            (
+                HaltReason::EOFFunctionStackOverflow,
+                "EOFFunctionStackOverflow",
+            ),
+            #[cfg(feature = "optimism")]
+            (HaltReason::FailedDeposit, "FailedDeposit"),
+        ];
+        for (halt, id) in halts {
+            assert_eq!(halt.as_str(), id);
+            assert_eq!(id.parse(), Ok(halt));
+        }
+
+        assert_eq!("OutOfGas".parse::<HaltReason>(), Err(UnknownIdentifier));
+        assert_eq!(
+            "OutOfGas(Stop)".parse::<HaltReason>(),
+            Err(UnknownIdentifier)
+        );
+        assert_eq!("stop".parse::<SuccessReason>(), Err(UnknownIdentifier));
+    }
+}
@@ -163,6 +163,39 @@ impl From<SpecId> for &'static str {
     }
 }
 
+/// Storage layout of the L1Block predeploy.
+#[cfg(feature = "optimism")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum L1BlockLayout {
+    /// L1 fee overhead and scalar in their own slots.
+    Bedrock,
+    /// Base fee and blob base fee scalars packed with the sequence number, overhead removed.
+    Ecotone,
+}
+
+#[cfg(feature = "optimism")]
+impl L1BlockLayout {
+    /// Returns the layout the predeploy has at the spec.
+    pub fn from_spec(spec_id: SpecId) -> Self {
+        if spec_id.is_enabled_in(SpecId::ECOTONE) {
+            Self::Ecotone
+        } else {
+            Self::Bedrock
+        }
+    }
+}
+
+#[cfg(feature = "optimism")]
+impl core::fmt::Display for L1BlockLayout {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Bedrock => f.write_str("Bedrock"),
+            Self::Ecotone => f.write_str("Ecotone"),
+        }
+    }
+}
+
 pub trait Spec: Sized + 'static {
     /// The specification ID.
     const SPEC_ID: SpecId;
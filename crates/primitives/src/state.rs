@@ -1,6 +1,7 @@
 use crate::{Address, Bytecode, HashMap, B256, KECCAK_EMPTY, U256};
 use bitflags::bitflags;
 use core::hash::{Hash, Hasher};
+use std::collections::BTreeMap;
 
 /// EVM State is a mapping from addresses to accounts.
 pub type EvmState = HashMap<Address, Account>;
@@ -57,7 +58,7 @@ impl Account {
     pub fn new_not_existing() -> Self {
         Self {
             info: AccountInfo::default(),
-            storage: HashMap::new(),
+            storage: HashMap::default(),
             status: AccountStatus::LoadedAsNotExisting,
         }
     }
@@ -140,13 +141,125 @@ impl Account {
     pub fn changed_storage_slots(&self) -> impl Iterator<Item = (&U256, &EvmStorageSlot)> {
         self.storage.iter().filter(|(_, slot)| slot.is_changed())
     }
+
+    /// Returns the storage diff between this account (pre state) and the `other`
+    /// account (post state).
+    ///
+    /// Value of the slot before is the present value in this account, or the original value
+    /// in the `other` account if the slot is not present here. Value of the slot after is the
+    /// present value in the `other` account, or the present value in this account if the slot
+    /// is not present there. Slots written back to their previous value are
+    /// [`SlotChange::Unchanged`].
+    pub fn storage_diff(&self, other: &Account) -> StorageDiff {
+        let mut slots = BTreeMap::new();
+        for (key, slot) in &self.storage {
+            let present = other
+                .storage
+                .get(key)
+                .map_or(slot.present_value, |other| other.present_value);
+            slots.insert(*key, SlotDiff::new(slot.present_value, present));
+        }
+        for (key, slot) in &other.storage {
+            if !self.storage.contains_key(key) {
+                slots.insert(*key, SlotDiff::new(slot.original_value, slot.present_value));
+            }
+        }
+        StorageDiff { slots }
+    }
+}
+
+/// Kind of the change of the storage slot, see [`Account::storage_diff`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SlotChange {
+    /// Slot was zero and is now set.
+    Added,
+    /// Slot was set and is now set to a different value.
+    Changed,
+    /// Slot was set and is now zero.
+    Cleared,
+    /// Slot value is the same.
+    Unchanged,
+}
+
+/// Change of a single storage slot, see [`Account::storage_diff`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SlotDiff {
+    /// Value before.
+    pub previous: U256,
+    /// Value after.
+    pub present: U256,
+}
+
+impl SlotDiff {
+    /// Creates a new slot diff.
+    pub fn new(previous: U256, present: U256) -> Self {
+        Self { previous, present }
+    }
+
+    /// Returns the kind of the change.
+    pub fn change(&self) -> SlotChange {
+        if self.previous == self.present {
+            SlotChange::Unchanged
+        } else if self.previous.is_zero() {
+            SlotChange::Added
+        } else if self.present.is_zero() {
+            SlotChange::Cleared
+        } else {
+            SlotChange::Changed
+        }
+    }
+
+    /// Returns `true` if the value of the slot changed.
+    pub fn is_changed(&self) -> bool {
+        self.previous != self.present
+    }
+}
+
+/// Storage diff of an account, see [`Account::storage_diff`].
+///
+/// Slots are ordered by key. As keys are compared as numbers this is the same order as
+/// the order of the big-endian [B256] keys.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StorageDiff {
+    /// Slots by key.
+    pub slots: BTreeMap<U256, SlotDiff>,
+}
+
+impl StorageDiff {
+    /// Returns the diff of the slot.
+    pub fn get(&self, key: &U256) -> Option<&SlotDiff> {
+        self.slots.get(key)
+    }
+
+    /// Returns `true` if no slot changed.
+    pub fn is_unchanged(&self) -> bool {
+        self.slots.values().all(|slot| !slot.is_changed())
+    }
+
+    /// Returns an iterator over all slots, ordered by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&U256, &SlotDiff)> {
+        self.slots.iter()
+    }
+
+    /// Returns an iterator over the changed slots, ordered by key.
+    pub fn changed(&self) -> impl Iterator<Item = (&U256, &SlotDiff)> {
+        self.iter().filter(|(_, slot)| slot.is_changed())
+    }
+
+    /// Returns an iterator over the changed slots with [B256] keys, ordered by key.
+    pub fn changed_b256(&self) -> impl Iterator<Item = (B256, &SlotDiff)> {
+        self.changed().map(|(key, slot)| (B256::from(*key), slot))
+    }
 }
 
 impl From<AccountInfo> for Account {
     fn from(info: AccountInfo) -> Self {
         Self {
             info,
-            storage: HashMap::new(),
+            storage: HashMap::default(),
             status: AccountStatus::Loaded,
         }
     }
@@ -234,6 +347,16 @@ impl Default for AccountInfo {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for AccountInfo {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        // code hash is derived from the legacy code.
+        let code = Bytecode::new_raw(u.arbitrary::<crate::Bytes>()?);
+        let code_hash = code.hash_slow();
+        Ok(Self::new(u.arbitrary()?, u.arbitrary()?, code_hash, code))
+    }
+}
+
 impl PartialEq for AccountInfo {
     fn eq(&self, other: &Self) -> bool {
         self.balance == other.balance
@@ -304,6 +427,7 @@ impl AccountInfo {
         self.code.take()
     }
 
+    /// Creates an account without code and nonce that holds `balance`.
     pub fn from_balance(balance: U256) -> Self {
         AccountInfo {
             balance,
@@ -311,6 +435,7 @@ impl AccountInfo {
         }
     }
 
+    /// Creates a contract account with the nonce 1, as set by `CREATE` since EIP-161.
     pub fn from_bytecode(bytecode: Bytecode) -> Self {
         let hash = bytecode.hash_slow();
 
@@ -321,11 +446,33 @@ impl AccountInfo {
             code_hash: hash,
         }
     }
+
+    /// Returns account info with the given balance.
+    pub fn with_balance(mut self, balance: U256) -> Self {
+        self.balance = balance;
+        self
+    }
+
+    /// Returns account info with the given nonce.
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    /// Returns account info with the given code and its hash.
+    pub fn with_code(mut self, code: Bytecode) -> Self {
+        self.code_hash = code.hash_slow();
+        self.code = Some(code);
+        self
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Account, KECCAK_EMPTY, U256};
+    use crate::{
+        Account, AccountInfo, Bytecode, Bytes, EvmStorageSlot, SlotChange, SlotDiff, B256,
+        KECCAK_EMPTY, U256,
+    };
 
     #[test]
     fn account_is_empty_balance() {
@@ -405,4 +552,79 @@ mod tests {
         // When marking cold account as warm, it should return true
         assert!(account.mark_warm());
     }
+
+    #[test]
+    fn account_storage_diff() {
+        let slot = |value: u64| EvmStorageSlot::new(U256::from(value));
+        let pre = Account {
+            storage: [
+                (U256::from(1), slot(10)),
+                (U256::from(2), slot(20)),
+                (U256::from(3), slot(30)),
+                (U256::from(4), slot(0)),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        let changed = |original: u64, present: u64| {
+            EvmStorageSlot::new_changed(U256::from(original), U256::from(present))
+        };
+        let post = Account {
+            storage: [
+                // written back to the original value.
+                (U256::from(1), changed(10, 10)),
+                // cleared.
+                (U256::from(2), changed(20, 0)),
+                (U256::from(3), changed(30, 31)),
+                (U256::from(4), changed(0, 40)),
+                // not present in the pre state.
+                (U256::from(5), changed(50, 0)),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        assert_eq!(post.changed_storage_slots().count(), 4);
+
+        let diff = pre.storage_diff(&post);
+        let change = |key: u64| diff.get(&U256::from(key)).unwrap().change();
+        assert_eq!(change(1), SlotChange::Unchanged);
+        assert_eq!(change(2), SlotChange::Cleared);
+        assert_eq!(change(3), SlotChange::Changed);
+        assert_eq!(change(4), SlotChange::Added);
+        assert_eq!(change(5), SlotChange::Cleared);
+        assert_eq!(
+            diff.get(&U256::from(5)),
+            Some(&SlotDiff::new(U256::from(50), U256::ZERO))
+        );
+        assert!(!diff.is_unchanged());
+
+        let keys: Vec<B256> = diff.changed_b256().map(|(key, _)| key).collect();
+        assert_eq!(keys, [2u64, 3, 4, 5].map(|key| B256::from(U256::from(key))));
+
+        // slot changed in the execution but written back to the pre state value.
+        let mut post = pre.clone();
+        post.storage.insert(U256::from(2), changed(21, 20));
+        let diff = pre.storage_diff(&post);
+        assert!(diff.is_unchanged());
+        assert_eq!(diff.changed().count(), 0);
+        assert_eq!(diff.iter().count(), 4);
+    }
+
+    #[test]
+    fn account_info_builders() {
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x00]));
+        let info = AccountInfo::from_balance(U256::from(10))
+            .with_nonce(2)
+            .with_code(code.clone());
+        assert_eq!(info.balance, U256::from(10));
+        assert_eq!(info.nonce, 2);
+        assert_eq!(info.code_hash, code.hash_slow());
+        assert_eq!(info.code, Some(code.clone()));
+        assert_eq!(
+            AccountInfo::from_bytecode(code).with_balance(U256::from(10)),
+            info.with_nonce(1)
+        );
+    }
 }
@@ -1,5 +1,6 @@
 use crate::{
-    b256, B256, BLOB_GASPRICE_UPDATE_FRACTION, MIN_BLOB_GASPRICE, TARGET_BLOB_GAS_PER_BLOCK,
+    b256, Eip1559Params, B256, BLOB_GASPRICE_UPDATE_FRACTION, MIN_BLOB_GASPRICE,
+    TARGET_BLOB_GAS_PER_BLOCK,
 };
 pub use alloy_primitives::keccak256;
 
@@ -7,6 +8,41 @@ pub use alloy_primitives::keccak256;
 pub const KECCAK_EMPTY: B256 =
     b256!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
 
+/// Calculates the base fee of the next block from the parent header's `gas_used`,
+/// `gas_limit` and `base_fee`, using Ethereum mainnet [`Eip1559Params`].
+///
+/// See also [the EIP-1559 specification](https://eips.ethereum.org/EIPS/eip-1559#specification).
+#[inline]
+pub fn calc_next_block_base_fee(gas_used: u64, gas_limit: u64, base_fee: u64) -> u64 {
+    calc_next_block_base_fee_with_params(gas_used, gas_limit, base_fee, Eip1559Params::MAINNET)
+}
+
+/// Calculates the base fee of the next block from the parent header's `gas_used`,
+/// `gas_limit` and `base_fee`, using the chain specific [`Eip1559Params`].
+#[inline]
+pub fn calc_next_block_base_fee_with_params(
+    gas_used: u64,
+    gas_limit: u64,
+    base_fee: u64,
+    params: Eip1559Params,
+) -> u64 {
+    let gas_target = gas_limit / params.elasticity_multiplier.max(1);
+    if gas_target == 0 || gas_used == gas_target {
+        return base_fee;
+    }
+    let base_fee = base_fee as u128;
+    let gas_target = gas_target as u128;
+    let gas_used = gas_used as u128;
+    let denominator = gas_target * params.max_change_denominator.max(1) as u128;
+    let next_base_fee = if gas_used > gas_target {
+        let delta = (base_fee * (gas_used - gas_target) / denominator).max(1);
+        base_fee + delta
+    } else {
+        base_fee - base_fee * (gas_target - gas_used) / denominator
+    };
+    next_base_fee.min(u64::MAX as u128) as u64
+}
+
 /// Calculates the `excess_blob_gas` from the parent header's `blob_gas_used` and `excess_blob_gas`.
 ///
 /// See also [the EIP-4844 helpers]<https://eips.ethereum.org/EIPS/eip-4844#helpers>
@@ -64,6 +100,25 @@ mod tests {
     use super::*;
     use crate::GAS_PER_BLOB;
 
+    #[test]
+    fn test_calc_next_block_base_fee() {
+        for t @ &(gas_used, gas_limit, base_fee, expected) in &[
+            // at target, base fee does not change.
+            (15_000_000, 30_000_000, 1_000_000_000, 1_000_000_000),
+            // full block increases the base fee by 12.5%.
+            (30_000_000, 30_000_000, 1_000_000_000, 1_125_000_000),
+            // empty block decreases the base fee by 12.5%.
+            (0, 30_000_000, 1_000_000_000, 875_000_000),
+            // increase is at least one.
+            (15_000_001, 30_000_000, 7, 8),
+            (20_000_000, 30_000_000, 0, 1),
+            (0, 30_000_000, 0, 0),
+        ] {
+            let actual = calc_next_block_base_fee(gas_used, gas_limit, base_fee);
+            assert_eq!(actual, expected, "test: {t:?}");
+        }
+    }
+
     // https://github.com/ethereum/go-ethereum/blob/28857080d732857030eda80c69b9ba2c8926f221/consensus/misc/eip4844/eip4844_test.go#L27
     #[test]
     fn test_calc_excess_blob_gas() {
@@ -4,7 +4,10 @@ use criterion::{
 use revm::{
     db::BenchmarkDB,
     interpreter::{analysis::to_analysed, Contract, DummyHost, Interpreter},
-    primitives::{address, bytes, hex, BerlinSpec, Bytecode, Bytes, TxKind, U256},
+    primitives::{
+        address, bytes, hex, legacy::JumpTable, AccessListItem, Address, BerlinSpec, Bytecode,
+        Bytes, TxKind, B256, MAX_CODE_SIZE, U256,
+    },
     Evm,
 };
 use revm_interpreter::{opcode::make_instruction_table, SharedMemory, EMPTY_SHARED_MEMORY};
@@ -14,7 +17,9 @@ fn analysis(c: &mut Criterion) {
     let evm = Evm::builder()
         .modify_tx_env(|tx| {
             tx.caller = address!("0000000000000000000000000000000000000002");
-            tx.transact_to = TxKind::Call(address!("0000000000000000000000000000000000000000"));
+            tx.transact_to = Some(TxKind::Call(address!(
+                "0000000000000000000000000000000000000000"
+            )));
             // evm.env.tx.data = bytes!("30627b7c");
             tx.data = bytes!("8035F0CE");
         })
@@ -42,6 +47,23 @@ fn analysis(c: &mut Criterion) {
         .build();
     bench_transact(&mut g, &mut evm);
 
+    // Worst case of the jump table analysis for the maximum code size, every other opcode is a
+    // PUSH1 with a JUMPDEST byte in its data.
+    let worst_case: Vec<u8> = [0x5b, 0x60].repeat(MAX_CODE_SIZE / 2);
+    g.bench_function("jump_table/worst_case", |b| {
+        b.iter(|| JumpTable::analyze(&worst_case))
+    });
+    // No JUMPDEST bytes, the opcodes are not walked.
+    let no_jumpdest = vec![0x60; MAX_CODE_SIZE];
+    g.bench_function("jump_table/no_jumpdest", |b| {
+        b.iter(|| JumpTable::analyze(&no_jumpdest))
+    });
+    // No PUSH opcodes, every JUMPDEST byte is a destination.
+    let no_push = vec![0x5b; MAX_CODE_SIZE];
+    g.bench_function("jump_table/no_push", |b| {
+        b.iter(|| JumpTable::analyze(&no_push))
+    });
+
     g.finish();
 }
 
@@ -50,7 +72,9 @@ fn snailtracer(c: &mut Criterion) {
         .with_db(BenchmarkDB::new_bytecode(bytecode(SNAILTRACER)))
         .modify_tx_env(|tx| {
             tx.caller = address!("1000000000000000000000000000000000000000");
-            tx.transact_to = TxKind::Call(address!("0000000000000000000000000000000000000000"));
+            tx.transact_to = Some(TxKind::Call(address!(
+                "0000000000000000000000000000000000000000"
+            )));
             tx.data = bytes!("30627b7c");
         })
         .build();
@@ -62,6 +86,11 @@ fn snailtracer(c: &mut Criterion) {
         .sample_size(10);
     bench_transact(&mut g, &mut evm);
     bench_eval(&mut g, &mut evm);
+
+    // compare with `transact/raw` for the overhead of the execution profile.
+    evm.cfg_mut().profile_execution = true;
+    g.bench_function("transact/profiled", |b| b.iter(|| evm.transact().unwrap()));
+    evm.cfg_mut().profile_execution = false;
     g.finish();
 }
 
@@ -70,7 +99,9 @@ fn transfer(c: &mut Criterion) {
         .with_db(BenchmarkDB::new_bytecode(Bytecode::new()))
         .modify_tx_env(|tx| {
             tx.caller = address!("0000000000000000000000000000000000000001");
-            tx.transact_to = TxKind::Call(address!("0000000000000000000000000000000000000000"));
+            tx.transact_to = Some(TxKind::Call(address!(
+                "0000000000000000000000000000000000000000"
+            )));
             tx.value = U256::from(10);
         })
         .build();
@@ -81,6 +112,104 @@ fn transfer(c: &mut Criterion) {
     g.finish();
 }
 
+/// Access list heavy transaction, compare between `rand-hash` and `fx-hash` features to
+/// measure the hasher of the journaled state.
+fn access_list(c: &mut Criterion) {
+    let access_list = (0..100u64)
+        .map(|i| AccessListItem {
+            address: Address::with_last_byte(2).create(i),
+            storage_keys: (0..100u64).map(|key| B256::from(U256::from(key))).collect(),
+        })
+        .collect();
+    let mut evm = Evm::builder()
+        .with_db(BenchmarkDB::new_bytecode(Bytecode::new()))
+        .modify_tx_env(|tx| {
+            tx.caller = address!("0000000000000000000000000000000000000001");
+            tx.transact_to = Some(TxKind::Call(address!(
+                "0000000000000000000000000000000000000000"
+            )));
+            tx.gas_limit = 30_000_000;
+            tx.access_list = access_list;
+        })
+        .build();
+
+    let mut g = c.benchmark_group("access_list");
+    g.noise_threshold(0.03).warm_up_time(Duration::from_secs(1));
+    bench_transact(&mut g, &mut evm);
+    g.finish();
+}
+
+/// Hashes 128 KB of calldata through the sha256 precompile. The input is borrowed from the
+/// caller memory, compare `transact` with the direct precompile call.
+fn precompile_sha256(c: &mut Criterion) {
+    // CALLDATACOPY(0, 0, CALLDATASIZE), STATICCALL(GAS, 0x02, 0, CALLDATASIZE, 0, 32), RETURN(0, 32)
+    let code = bytes!("365f5f3760205f365f60025afa60205ff3");
+    let input = Bytes::from(vec![0xab; 128 * 1024]);
+    let mut evm = Evm::builder()
+        .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+        .modify_tx_env(|tx| {
+            tx.caller = address!("0000000000000000000000000000000000000001");
+            tx.transact_to = Some(TxKind::Call(address!(
+                "0000000000000000000000000000000000000000"
+            )));
+            tx.gas_limit = 30_000_000;
+            tx.data = input.clone();
+        })
+        .build();
+
+    let mut g = c.benchmark_group("precompile_sha256");
+    g.noise_threshold(0.03).warm_up_time(Duration::from_secs(1));
+    bench_transact(&mut g, &mut evm);
+    g.bench_function("sha256_run", |b| {
+        b.iter(|| revm::precompile::hash::sha256_run(&input, u64::MAX).unwrap())
+    });
+    g.finish();
+}
+
+fn return_data(c: &mut Criterion) {
+    // Calls itself `CALLDATALOAD(0)` times, innermost call returns 256 KiB of zeros and
+    // every caller returns the return data of its sub call.
+    let code = bytes!("5f3580600c57620400005ff35b600190035f525f5f60205f5f305af1503d5f5f3e3d5ff3");
+    let mut evm = Evm::builder()
+        .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+        .modify_tx_env(|tx| {
+            tx.caller = address!("0000000000000000000000000000000000000001");
+            tx.transact_to = Some(TxKind::Call(address!(
+                "0000000000000000000000000000000000000000"
+            )));
+            tx.gas_limit = 30_000_000;
+            tx.data = Bytes::copy_from_slice(B256::with_last_byte(3).as_slice());
+        })
+        .build();
+
+    let mut g = c.benchmark_group("return_data");
+    g.noise_threshold(0.03).warm_up_time(Duration::from_secs(1));
+    bench_transact(&mut g, &mut evm);
+    g.finish();
+}
+
+/// Emits 1000 `LOG2` events with 32 bytes of data each.
+fn logs(c: &mut Criterion) {
+    // Emits `LOG2(0, 32, 1, 2)` `CALLDATALOAD(0)` times.
+    let code = bytes!("5f355b80156017576002600160205fa2600190036002565b00");
+    let mut evm = Evm::builder()
+        .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+        .modify_tx_env(|tx| {
+            tx.caller = address!("0000000000000000000000000000000000000001");
+            tx.transact_to = Some(TxKind::Call(address!(
+                "0000000000000000000000000000000000000000"
+            )));
+            tx.gas_limit = 30_000_000;
+            tx.data = Bytes::copy_from_slice(B256::from(U256::from(1000)).as_slice());
+        })
+        .build();
+
+    let mut g = c.benchmark_group("logs");
+    g.noise_threshold(0.03).warm_up_time(Duration::from_secs(1));
+    bench_transact(&mut g, &mut evm);
+    g.finish();
+}
+
 fn bench_transact<EXT>(g: &mut BenchmarkGroup<'_, WallTime>, evm: &mut Evm<'_, EXT, BenchmarkDB>) {
     let state = match evm.context.evm.db.0 {
         Bytecode::LegacyRaw(_) => "raw",
@@ -94,7 +223,7 @@ fn bench_transact<EXT>(g: &mut BenchmarkGroup<'_, WallTime>, evm: &mut Evm<'_, E
 fn bench_eval(g: &mut BenchmarkGroup<'_, WallTime>, evm: &mut Evm<'static, (), BenchmarkDB>) {
     g.bench_function("eval", |b| {
         let contract = Contract {
-            input: evm.context.evm.env.tx.data.clone(),
+            input: evm.context.evm.env.tx().data.clone(),
             bytecode: to_analysed(evm.context.evm.db.0.clone()),
             ..Default::default()
         };
@@ -124,6 +253,10 @@ criterion_group!(
     analysis,
     snailtracer,
     transfer,
+    access_list,
+    precompile_sha256,
+    return_data,
+    logs,
 );
 criterion_main!(benches);
 
@@ -0,0 +1,46 @@
+//! Fuzzes the invariants of the transaction execution on arbitrary environments, see
+//! `revm::test_utils::check_invariants`.
+//!
+//! Run with `cargo +nightly fuzz run env_invariants` from `crates/revm`. Minimized failures
+//! are added as regression fixtures next to `check_invariants`.
+#![no_main]
+
+use libfuzzer_sys::{
+    arbitrary::{self, Arbitrary},
+    fuzz_target,
+};
+use revm::{
+    primitives::{AccountInfo, Bytecode, Bytes, Env, SpecId, TxKind},
+    test_utils::check_invariants,
+};
+
+/// Gas limit cap, keeps the executions short.
+const MAX_GAS_LIMIT: u64 = 1_000_000;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    env: Env,
+    spec_id: u8,
+    caller: AccountInfo,
+    /// Code of the called contract.
+    code: Vec<u8>,
+}
+
+fuzz_target!(|input: Input| {
+    let Input {
+        mut env,
+        spec_id,
+        caller,
+        code,
+    } = input;
+    let spec_id = SpecId::try_from_u8(spec_id).unwrap_or(SpecId::LATEST);
+    let tx = env.tx_mut();
+    tx.gas_limit = tx.gas_limit.min(MAX_GAS_LIMIT);
+
+    let mut accounts = vec![(env.tx().caller, caller)];
+    if let TxKind::Call(address) = env.tx().kind() {
+        let code = Bytecode::new_raw(Bytes::from(code));
+        accounts.push((address, AccountInfo::from_bytecode(code)));
+    }
+    check_invariants(env, spec_id, accounts);
+});
@@ -1,13 +1,17 @@
 use crate::{
     db::{Database, DatabaseRef, EmptyDB, WrapDatabaseRef},
-    handler::register,
+    handler::{
+        mainnet, register, AccountOverride, ChainSpec, DeductCallerHandle, RewardBeneficiaryHandle,
+        ValidateEnvHandle,
+    },
     primitives::{
-        BlockEnv, CfgEnv, CfgEnvWithHandlerCfg, Env, EnvWithHandlerCfg, HandlerCfg, SpecId, TxEnv,
+        Address, BlockEnv, CfgEnv, CfgEnvWithHandlerCfg, Env, EnvWithHandlerCfg, HandlerCfg,
+        SpecId, TxEnv,
     },
     Context, ContextWithHandlerCfg, Evm, Handler,
 };
-use core::marker::PhantomData;
-use std::boxed::Box;
+use core::{fmt, marker::PhantomData};
+use std::{boxed::Box, sync::Arc};
 
 /// Evm Builder allows building or modifying EVM.
 /// Note that some of the methods that changes underlying structures
@@ -301,10 +305,22 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
     }
 
     /// Builds the [`Evm`].
+    ///
+    /// Configuration is not validated, use [`EvmBuilder::try_build`] to get a [`BuilderError`]
+    /// instead of an [`Evm`] that misbehaves during execution.
     pub fn build(self) -> Evm<'a, EXT, DB> {
         Evm::new(self.context, self.handler)
     }
 
+    /// Validates the handler configuration and builds the [`Evm`].
+    ///
+    /// Returns [`BuilderError`] if the spec id and the handler don't match or the spec id
+    /// requires support that is not compiled in.
+    pub fn try_build(self) -> Result<Evm<'a, EXT, DB>, BuilderError> {
+        validate_handler_cfg(self.handler.cfg())?;
+        Ok(self.build())
+    }
+
     /// Register Handler that modifies the behavior of EVM.
     /// Check [`Handler`] for more information.
     ///
@@ -341,6 +357,92 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
         }
     }
 
+    /// Register Handler that modifies the behavior of EVM, the register is shared and is
+    /// reapplied when the handler is rebuilt, see [`Handler::rebuild`].
+    ///
+    /// When called, EvmBuilder will transition from SetGenericStage to HandlerStage.
+    pub fn append_handler_register_arc(
+        mut self,
+        handle_register: register::HandleRegisterArc<EXT, DB>,
+    ) -> EvmBuilder<'a, HandlerStage, EXT, DB> {
+        self.handler
+            .append_handler_register(register::HandleRegisters::Arc(handle_register));
+        EvmBuilder {
+            context: self.context,
+            handler: self.handler,
+
+            phantom: PhantomData,
+        }
+    }
+
+    /// Registers the handles of a custom transaction type.
+    ///
+    /// The validate env, deduct caller and reward beneficiary handles are used for
+    /// transactions with [`TxEnv::custom_tx_type`] set to `tx_type`. All other transactions,
+    /// and the custom ones for handles that are `None`, use the previously set handles.
+    ///
+    /// When called, EvmBuilder will transition from SetGenericStage to HandlerStage.
+    pub fn with_custom_tx_handler(
+        self,
+        tx_type: u8,
+        validate_env: Option<ValidateEnvHandle<'static, DB>>,
+        deduct_caller: Option<DeductCallerHandle<'static, EXT, DB>>,
+        reward_beneficiary: Option<RewardBeneficiaryHandle<'static, EXT, DB>>,
+    ) -> EvmBuilder<'a, HandlerStage, EXT, DB>
+    where
+        EXT: 'static,
+        DB: 'static,
+    {
+        self.append_handler_register_arc(Arc::new(move |handler| {
+            register::register_custom_tx_handles(
+                handler,
+                move |env: &Env| env.tx().custom_tx_type == Some(tx_type),
+                validate_env.clone().map(|hook| hook as _),
+                deduct_caller.clone().map(|hook| hook as _),
+                reward_beneficiary.clone().map(|hook| hook as _),
+            )
+        }))
+    }
+
+    /// Sets a callback that overrides nonce and balance of the caller when the transaction
+    /// is validated against the state, for example with the pending nonce from a mempool.
+    ///
+    /// The override is applied around the previously set validation handle, so chain
+    /// specific validation still runs. It applies only to the validation step,
+    /// [`Evm::preverify_transaction`]. Transactions that are executed are validated against
+    /// the real state, also by [`Evm::transact_preverified`] if it validates the transaction
+    /// again.
+    ///
+    /// When called, EvmBuilder will transition from SetGenericStage to HandlerStage.
+    pub fn with_state_validation_override(
+        self,
+        account_override: impl Fn(Address) -> Option<AccountOverride> + 'static,
+    ) -> EvmBuilder<'a, HandlerStage, EXT, DB>
+    where
+        EXT: 'static,
+        DB: 'static,
+    {
+        let account_override = Arc::new(account_override);
+        self.append_handler_register_arc(Arc::new(move |handler| {
+            let account_override = account_override.clone();
+            let validate = handler.validation.tx_against_state.clone();
+            handler.validation.tx_against_state =
+                Arc::new(move |context: &mut Context<EXT, DB>| {
+                    if !context.evm.is_validation_only {
+                        return validate(context);
+                    }
+                    match account_override(context.evm.env.tx().caller) {
+                        Some(account_override) => mainnet::validate_tx_against_state_with_override(
+                            context,
+                            account_override,
+                            |context| validate(context),
+                        ),
+                        None => validate(context),
+                    }
+                });
+        }))
+    }
+
     /// Sets specification Id , that will mark the version of EVM.
     /// It represent the hard fork of ethereum.
     ///
@@ -358,6 +460,31 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
         }
     }
 
+    /// Sets the hardfork of a chain described by a [`ChainSpec`].
+    ///
+    /// The base EVM behavior follows the [`SpecId`] the hardfork maps to, the chain specific
+    /// handlers are registered on top of it with [`ChainSpec::register_handler`].
+    ///
+    /// # Note
+    ///
+    /// The registered handlers keep the given hardfork, changing the spec id afterwards with
+    /// [`Self::with_spec_id`] only changes the base EVM behavior.
+    ///
+    /// When called, EvmBuilder will transition from SetGenericStage to HandlerStage.
+    pub fn with_chain_spec<CS: ChainSpec>(
+        self,
+        hardfork: CS::Hardfork,
+    ) -> EvmBuilder<'a, HandlerStage, EXT, DB>
+    where
+        EXT: 'static,
+        DB: 'static,
+    {
+        self.with_spec_id(hardfork.into())
+            .append_handler_register_arc(Arc::new(move |handler| {
+                CS::register_handler(handler, hardfork)
+            }))
+    }
+
     /// Allows modification of Evm Database.
     pub fn modify_db(mut self, f: impl FnOnce(&mut DB)) -> Self {
         f(&mut self.context.evm.db);
@@ -384,13 +511,13 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
 
     /// Allows modification of Evm's Transaction Environment.
     pub fn modify_tx_env(mut self, f: impl FnOnce(&mut TxEnv)) -> Self {
-        f(&mut self.context.evm.env.tx);
+        f(self.context.evm.env.tx_mut());
         self
     }
 
     /// Sets Evm's Transaction Environment.
     pub fn with_tx_env(mut self, tx_env: TxEnv) -> Self {
-        self.context.evm.env.tx = tx_env;
+        *self.context.evm.env.tx_mut() = tx_env;
         self
     }
 
@@ -420,7 +547,7 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
 
     /// Clears Transaction environment of EVM.
     pub fn with_clear_tx_env(mut self) -> Self {
-        self.context.evm.env.tx.clear();
+        self.context.evm.env.tx_mut().clear();
         self
     }
     /// Clears Block environment of EVM.
@@ -436,6 +563,75 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
     }
 }
 
+/// Misconfiguration of the [`EvmBuilder`] returned by [`EvmBuilder::try_build`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BuilderError {
+    /// Spec id enables the KZG point evaluation precompile but the `c-kzg` feature is disabled.
+    KzgNotSupported {
+        /// Spec id of the handler.
+        spec_id: SpecId,
+    },
+    /// Optimism spec id is used with the mainnet handler.
+    #[cfg(feature = "optimism")]
+    OptimismSpecWithoutOptimismHandler {
+        /// Spec id of the handler.
+        spec_id: SpecId,
+    },
+    /// Optimism handler is used with a spec id before Bedrock.
+    #[cfg(feature = "optimism")]
+    OptimismHandlerBeforeBedrock {
+        /// Spec id of the handler.
+        spec_id: SpecId,
+    },
+}
+
+impl fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KzgNotSupported { spec_id } => write!(
+                f,
+                "spec {spec_id:?} requires the KZG point evaluation precompile, \
+                 enable the `c-kzg` feature"
+            ),
+            #[cfg(feature = "optimism")]
+            Self::OptimismSpecWithoutOptimismHandler { spec_id } => write!(
+                f,
+                "optimism spec {spec_id:?} is used without the optimism handler"
+            ),
+            #[cfg(feature = "optimism")]
+            Self::OptimismHandlerBeforeBedrock { spec_id } => write!(
+                f,
+                "optimism handler is used with spec {spec_id:?} before Bedrock"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuilderError {}
+
+/// Checks that the handler can execute transactions of its spec id.
+fn validate_handler_cfg(handler_cfg: HandlerCfg) -> Result<(), BuilderError> {
+    let spec_id = handler_cfg.spec_id;
+
+    #[cfg(feature = "optimism")]
+    {
+        use SpecId::*;
+        let is_optimism_spec = matches!(spec_id, BEDROCK | REGOLITH | CANYON | ECOTONE | FJORD);
+        if handler_cfg.is_optimism && !spec_id.is_enabled_in(BEDROCK) {
+            return Err(BuilderError::OptimismHandlerBeforeBedrock { spec_id });
+        }
+        if !handler_cfg.is_optimism && is_optimism_spec {
+            return Err(BuilderError::OptimismSpecWithoutOptimismHandler { spec_id });
+        }
+    }
+
+    if !cfg!(feature = "c-kzg") && spec_id.is_enabled_in(SpecId::CANCUN) {
+        return Err(BuilderError::KzgNotSupported { spec_id });
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::SpecId;
@@ -444,13 +640,19 @@ mod test {
         inspector::inspector_handle_register,
         inspectors::NoOpInspector,
         primitives::{
-            address, AccountInfo, Address, Bytecode, Bytes, PrecompileResult, TxKind, U256,
+            address, AccountInfo, Address, Bytecode, Bytes, EVMError, InvalidTransaction,
+            PrecompileResult, TxKind, U256,
         },
+        test_utils::contract_evm,
         Context, ContextPrecompile, ContextStatefulPrecompile, Evm, InMemoryDB, InnerEvmContext,
     };
     use revm_interpreter::{gas, Host, Interpreter};
     use revm_precompile::PrecompileOutput;
-    use std::{cell::RefCell, rc::Rc, sync::Arc};
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+        sync::Arc,
+    };
 
     /// Custom evm context
     #[derive(Default, Clone, Debug)]
@@ -474,7 +676,7 @@ mod test {
             .modify_db(|db| {
                 db.insert_account_info(to_addr, AccountInfo::new(U256::ZERO, 0, code_hash, code))
             })
-            .modify_tx_env(|tx| tx.transact_to = TxKind::Call(to_addr))
+            .modify_tx_env(|tx| tx.transact_to = Some(TxKind::Call(to_addr)))
             // we need to use handle register box to capture the custom context in the handle
             // register
             .append_handler_register_box(Box::new(move |handler| {
@@ -523,7 +725,7 @@ mod test {
             .modify_db(|db| {
                 db.insert_account_info(to_addr, AccountInfo::new(U256::ZERO, 0, code_hash, code))
             })
-            .modify_tx_env(|tx| tx.transact_to = TxKind::Call(to_addr))
+            .modify_tx_env(|tx| tx.transact_to = Some(TxKind::Call(to_addr)))
             .append_handler_register(|handler| {
                 handler.instruction_table.insert(0xEF, custom_instruction)
             })
@@ -611,7 +813,7 @@ mod test {
         impl ContextStatefulPrecompile<EmptyDB> for CustomPrecompile {
             fn call(
                 &self,
-                _input: &Bytes,
+                _input: &[u8],
                 _gas_price: u64,
                 _context: &mut InnerEvmContext<EmptyDB>,
             ) -> PrecompileResult {
@@ -637,4 +839,179 @@ mod test {
 
         evm.transact().unwrap();
     }
+
+    #[test]
+    fn state_validation_override() {
+        use crate::{
+            handler::AccountOverride,
+            primitives::{EVMError, InvalidTransaction},
+        };
+
+        let caller = address!("1000000000000000000000000000000000000001");
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                nonce: 5,
+                balance: U256::from(1_000_000),
+                ..Default::default()
+            },
+        );
+        let calls = Rc::new(Cell::new(0));
+        let build = |with_override: bool| {
+            let builder = Evm::builder().with_db(db.clone()).modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = Some(TxKind::Call(Address::ZERO));
+                tx.gas_limit = 21_000;
+                tx.nonce = Some(7);
+            });
+            if with_override {
+                let calls = calls.clone();
+                builder
+                    .with_state_validation_override(move |address| {
+                        calls.set(calls.get() + 1);
+                        (address == caller).then_some(AccountOverride {
+                            nonce: Some(7),
+                            balance: None,
+                        })
+                    })
+                    .build()
+            } else {
+                builder.build()
+            }
+        };
+
+        let nonce_too_high =
+            EVMError::Transaction(InvalidTransaction::NonceTooHigh { tx: 7, state: 5 });
+
+        // pending nonce is N+2, state nonce is N.
+        let mut evm = build(true);
+        assert_eq!(evm.preverify_transaction(), Ok(()));
+        assert_eq!(calls.get(), 1);
+        // execution validates against the real state, nothing is committed.
+        assert_eq!(evm.transact_commit(), Err(nonce_too_high.clone()));
+        assert_eq!(calls.get(), 1);
+        assert_eq!(evm.db().accounts[&caller].info.nonce, 5);
+        // validation still uses the override afterwards.
+        assert_eq!(evm.preverify_transaction(), Ok(()));
+        assert_eq!(calls.get(), 2);
+
+        let mut evm = build(false);
+        assert_eq!(evm.preverify_transaction(), Err(nonce_too_high));
+    }
+
+    #[test]
+    fn try_build_kzg() {
+        let result = Evm::builder().with_spec_id(SpecId::CANCUN).try_build();
+        if cfg!(feature = "c-kzg") {
+            assert!(result.is_ok());
+        } else {
+            assert_eq!(
+                result.err(),
+                Some(super::BuilderError::KzgNotSupported {
+                    spec_id: SpecId::CANCUN
+                })
+            );
+        }
+
+        assert!(Evm::builder()
+            .with_spec_id(SpecId::SHANGHAI)
+            .try_build()
+            .is_ok());
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn try_build_optimism() {
+        use super::BuilderError;
+
+        let result = Evm::builder()
+            .optimism()
+            .with_spec_id(SpecId::MERGE)
+            .try_build();
+        assert_eq!(
+            result.err(),
+            Some(BuilderError::OptimismHandlerBeforeBedrock {
+                spec_id: SpecId::MERGE
+            })
+        );
+
+        let result = Evm::builder()
+            .with_handler(crate::Handler::mainnet_with_spec(SpecId::REGOLITH))
+            .try_build();
+        assert_eq!(
+            result.err(),
+            Some(BuilderError::OptimismSpecWithoutOptimismHandler {
+                spec_id: SpecId::REGOLITH
+            })
+        );
+
+        assert!(Evm::builder()
+            .optimism()
+            .with_spec_id(SpecId::REGOLITH)
+            .try_build()
+            .is_ok());
+    }
+
+    const SYSTEM_TX_TYPE: u8 = 0x7F;
+
+    thread_local! {
+        static REWARDED: Cell<bool> = const { Cell::new(false) };
+    }
+
+    fn custom_tx_evm(custom_tx_type: Option<u8>) -> Evm<'static, (), InMemoryDB> {
+        contract_evm(Bytes::new())
+            .modify_block_env(|block| block.basefee = U256::from(10))
+            .modify_tx_env(|tx| {
+                tx.gas_price = U256::ZERO;
+                tx.custom_tx_type = custom_tx_type;
+            })
+            // Toy "fee-free system transaction": it skips env validation (no base fee is
+            // paid) and does not charge the caller.
+            .with_custom_tx_handler(
+                SYSTEM_TX_TYPE,
+                Some(Arc::new(|_| Ok(()))),
+                Some(Arc::new(|_| Ok(()))),
+                Some(Arc::new(|_, _| {
+                    REWARDED.with(|rewarded| rewarded.set(true));
+                    Ok(())
+                })),
+            )
+            .build()
+    }
+
+    #[test]
+    fn fee_free_system_tx() {
+        let mut evm = custom_tx_evm(Some(SYSTEM_TX_TYPE));
+
+        let result = evm.transact().unwrap();
+        assert!(result.result.is_success());
+        assert!(REWARDED.with(Cell::get));
+        // nonce is not bumped as standard deduct caller is skipped.
+        assert_eq!(result.state[&evm.tx().caller].info.nonce, 0);
+    }
+
+    #[test]
+    fn standard_tx_falls_back_to_mainnet() {
+        let mut evm = custom_tx_evm(None);
+
+        assert!(matches!(
+            evm.transact(),
+            Err(EVMError::Transaction(
+                InvalidTransaction::GasPriceLessThanBasefee
+            ))
+        ));
+        assert!(!REWARDED.with(Cell::get));
+    }
+
+    #[test]
+    fn custom_tx_handles_survive_spec_change() {
+        let mut evm = custom_tx_evm(Some(SYSTEM_TX_TYPE))
+            .modify()
+            .with_spec_id(SpecId::SHANGHAI)
+            .build();
+
+        evm.transact().unwrap().result.expect_success();
+        assert!(REWARDED.with(Cell::get));
+    }
 }
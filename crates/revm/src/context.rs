@@ -7,13 +7,12 @@ pub use context_precompiles::{
     ContextStatefulPrecompileBox, ContextStatefulPrecompileMut,
 };
 pub use evm_context::EvmContext;
-pub use inner_evm_context::InnerEvmContext;
-use revm_interpreter::as_usize_saturated;
+pub use inner_evm_context::{CallStackExhaustion, InnerEvmContext};
 
 use crate::{
     db::{Database, EmptyDB},
-    interpreter::{Host, LoadAccountResult, SStoreResult, SelfDestructResult},
-    primitives::{Address, Bytes, Env, HandlerCfg, Log, B256, BLOCK_HASH_HISTORY, U256},
+    interpreter::{HostRead, HostWrite, LoadAccountResult, SStoreResult, SelfDestructResult},
+    primitives::{Address, Bytes, Env, HandlerCfg, Log, B256, U256},
 };
 use std::boxed::Box;
 
@@ -97,39 +96,18 @@ where
     }
 }
 
-impl<EXT, DB: Database> Host for Context<EXT, DB> {
+impl<EXT, DB: Database> HostRead for Context<EXT, DB> {
     /// Returns reference to Environment.
     #[inline]
     fn env(&self) -> &Env {
         &self.evm.env
     }
 
-    fn env_mut(&mut self) -> &mut Env {
-        &mut self.evm.env
-    }
-
     fn block_hash(&mut self, number: u64) -> Option<B256> {
-        let block_number = as_usize_saturated!(self.env().block.number);
-        let requested_number = usize::try_from(number).unwrap_or(usize::MAX);
-
-        let Some(diff) = block_number.checked_sub(requested_number) else {
-            return Some(B256::ZERO);
-        };
-
-        // blockhash should push zero if number is same as current block number.
-        if diff == 0 {
-            return Some(B256::ZERO);
-        }
-
-        if diff <= BLOCK_HASH_HISTORY {
-            return self
-                .evm
-                .block_hash(number)
-                .map_err(|e| self.evm.error = Err(e))
-                .ok();
-        }
-
-        Some(B256::ZERO)
+        self.evm
+            .block_hash(number)
+            .map_err(|e| self.evm.error = Err(e))
+            .ok()
     }
 
     fn load_account(&mut self, address: Address) -> Option<LoadAccountResult> {
@@ -167,6 +145,20 @@ impl<EXT, DB: Database> Host for Context<EXT, DB> {
             .ok()
     }
 
+    fn tload(&mut self, address: Address, index: U256) -> U256 {
+        self.evm.tload(address, index)
+    }
+
+    fn initcode_by_hash(&mut self, hash: B256) -> Option<Bytes> {
+        self.evm.initcode_by_hash(hash)
+    }
+}
+
+impl<EXT, DB: Database> HostWrite for Context<EXT, DB> {
+    fn env_mut(&mut self) -> &mut Env {
+        &mut self.evm.env
+    }
+
     fn sstore(&mut self, address: Address, index: U256, value: U256) -> Option<SStoreResult> {
         self.evm
             .sstore(address, index, value)
@@ -174,10 +166,6 @@ impl<EXT, DB: Database> Host for Context<EXT, DB> {
             .ok()
     }
 
-    fn tload(&mut self, address: Address, index: U256) -> U256 {
-        self.evm.tload(address, index)
-    }
-
     fn tstore(&mut self, address: Address, index: U256, value: U256) {
         self.evm.tstore(address, index, value)
     }
@@ -186,6 +174,10 @@ impl<EXT, DB: Database> Host for Context<EXT, DB> {
         self.evm.journaled_state.log(log);
     }
 
+    fn log_raw(&mut self, address: Address, topics: &[B256], data: &[u8]) {
+        self.evm.journaled_state.log_raw(address, topics, data);
+    }
+
     fn selfdestruct(&mut self, address: Address, target: Address) -> Option<SelfDestructResult> {
         self.evm
             .inner
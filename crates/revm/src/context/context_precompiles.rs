@@ -1,7 +1,7 @@
 use super::InnerEvmContext;
 use crate::{
     precompile::{Precompile, PrecompileResult},
-    primitives::{db::Database, Address, Bytes, HashMap, HashSet},
+    primitives::{db::Database, Address, HashMap, HashSet},
 };
 use dyn_clone::DynClone;
 use revm_precompile::{PrecompileSpecId, PrecompileWithAddress, Precompiles};
@@ -95,7 +95,7 @@ impl<DB: Database> ContextPrecompiles<DB> {
 
     /// Returns precompiles addresses.
     #[inline]
-    pub fn addresses<'a>(&'a self) -> Box<dyn ExactSizeIterator<Item = &Address> + 'a> {
+    pub fn addresses<'a>(&'a self) -> Box<dyn ExactSizeIterator<Item = &'a Address> + 'a> {
         match self.inner {
             PrecompilesCow::StaticRef(inner) => Box::new(inner.addresses()),
             PrecompilesCow::Owned(ref inner) => Box::new(inner.keys()),
@@ -118,7 +118,7 @@ impl<DB: Database> ContextPrecompiles<DB> {
     pub fn call(
         &mut self,
         address: &Address,
-        bytes: &Bytes,
+        bytes: &[u8],
         gas_price: u64,
         evmctx: &mut InnerEvmContext<DB>,
     ) -> Option<PrecompileResult> {
@@ -198,7 +198,7 @@ impl<DB: Database> Default for PrecompilesCow<DB> {
 pub trait ContextStatefulPrecompile<DB: Database>: Sync + Send {
     fn call(
         &self,
-        bytes: &Bytes,
+        bytes: &[u8],
         gas_price: u64,
         evmctx: &mut InnerEvmContext<DB>,
     ) -> PrecompileResult;
@@ -209,7 +209,7 @@ pub trait ContextStatefulPrecompile<DB: Database>: Sync + Send {
 pub trait ContextStatefulPrecompileMut<DB: Database>: DynClone + Send + Sync {
     fn call_mut(
         &mut self,
-        bytes: &Bytes,
+        bytes: &[u8],
         gas_price: u64,
         evmctx: &mut InnerEvmContext<DB>,
     ) -> PrecompileResult;
@@ -1,14 +1,17 @@
 use revm_interpreter::CallValue;
-use revm_precompile::PrecompileErrors;
+use revm_precompile::{max_output_len, PrecompileError, PrecompileErrors};
 
 use super::inner_evm_context::InnerEvmContext;
 use crate::{
     db::Database,
     interpreter::{
-        return_ok, CallInputs, Contract, Gas, InstructionResult, Interpreter, InterpreterResult,
+        return_ok, CallInput, CallInputs, CallScheme, Contract, Gas, InstructionResult,
+        Interpreter, InterpreterResult, SharedMemory, EMPTY_SHARED_MEMORY,
     },
-    primitives::{Address, Bytes, EVMError, Env, EOF_MAGIC_BYTES, U256},
-    ContextPrecompiles, FrameOrResult, CALL_STACK_LIMIT,
+    primitives::{
+        Address, AnalysisKind, Bytecode, Bytes, EVMError, Env, EOF_MAGIC_BYTES, KECCAK_EMPTY, U256,
+    },
+    ContextPrecompiles, FrameOrResult,
 };
 use core::{
     fmt,
@@ -22,6 +25,10 @@ pub struct EvmContext<DB: Database> {
     pub inner: InnerEvmContext<DB>,
     /// Precompiles that are available for evm.
     pub precompiles: ContextPrecompiles<DB>,
+    /// Error of the last precompile call, see [`EvmContext::precompile_error`].
+    precompile_error: Option<PrecompileError>,
+    /// Memory of the frame that makes a call, see [`EvmContext::caller_memory`].
+    pub(crate) caller_memory: SharedMemory,
 }
 
 impl<DB: Database + Clone> Clone for EvmContext<DB>
@@ -32,6 +39,8 @@ where
         Self {
             inner: self.inner.clone(),
             precompiles: ContextPrecompiles::default(),
+            precompile_error: None,
+            caller_memory: EMPTY_SHARED_MEMORY,
         }
     }
 }
@@ -69,6 +78,8 @@ impl<DB: Database> EvmContext<DB> {
         Self {
             inner: InnerEvmContext::new(db),
             precompiles: ContextPrecompiles::default(),
+            precompile_error: None,
+            caller_memory: EMPTY_SHARED_MEMORY,
         }
     }
 
@@ -78,6 +89,8 @@ impl<DB: Database> EvmContext<DB> {
         Self {
             inner: InnerEvmContext::new_with_env(db, env),
             precompiles: ContextPrecompiles::default(),
+            precompile_error: None,
+            caller_memory: EMPTY_SHARED_MEMORY,
         }
     }
 
@@ -89,6 +102,8 @@ impl<DB: Database> EvmContext<DB> {
         EvmContext {
             inner: self.inner.with_db(db),
             precompiles: ContextPrecompiles::default(),
+            precompile_error: None,
+            caller_memory: EMPTY_SHARED_MEMORY,
         }
     }
 
@@ -100,17 +115,123 @@ impl<DB: Database> EvmContext<DB> {
         self.precompiles = precompiles;
     }
 
+    /// Sets the balance of the account and returns the previous one.
+    ///
+    /// This and the other state setters below are the supported way to change the state from
+    /// inside the [`Inspector`](crate::Inspector) hooks, e.g. to implement cheatcodes. The
+    /// account is loaded if needed and marked as touched, and the change is journaled, so it is
+    /// rolled back if the current frame reverts.
+    #[inline]
+    pub fn set_balance(
+        &mut self,
+        address: Address,
+        balance: U256,
+    ) -> Result<U256, EVMError<DB::Error>> {
+        let journaled_state = &mut self.inner.journaled_state;
+        journaled_state.load_account(address, &mut self.inner.db)?;
+        Ok(journaled_state.set_balance(address, balance))
+    }
+
+    /// Sets the storage slot of the account and returns the previous value.
+    ///
+    /// See [`EvmContext::set_balance`].
+    #[inline]
+    pub fn set_storage(
+        &mut self,
+        address: Address,
+        slot: U256,
+        value: U256,
+    ) -> Result<U256, EVMError<DB::Error>> {
+        let journaled_state = &mut self.inner.journaled_state;
+        journaled_state.load_account(address, &mut self.inner.db)?;
+        journaled_state.touch(&address);
+        let result = journaled_state.sstore(address, slot, value, &mut self.inner.db)?;
+        Ok(result.present_value)
+    }
+
+    /// Sets the code of the account and returns the previous one, empty if the account had no
+    /// code. Code is hashed with [`CfgEnv::keccak256`](crate::primitives::CfgEnv::keccak256).
+    ///
+    /// Unlike [`InnerEvmContext::set_code`] the previous code is restored on revert.
+    /// See [`EvmContext::set_balance`].
+    #[inline]
+    pub fn set_code(
+        &mut self,
+        address: Address,
+        code: Bytecode,
+    ) -> Result<Bytecode, EVMError<DB::Error>> {
+        let hash = if code.is_empty() {
+            KECCAK_EMPTY
+        } else {
+            self.inner.env.cfg.keccak256(code.original_byte_slice())
+        };
+        let journaled_state = &mut self.inner.journaled_state;
+        journaled_state.load_code(address, &mut self.inner.db)?;
+        Ok(journaled_state
+            .replace_code_with_hash(address, code, hash)
+            .unwrap_or_default())
+    }
+
+    /// Sets the nonce of the account and returns the previous one.
+    ///
+    /// See [`EvmContext::set_balance`].
+    #[inline]
+    pub fn set_nonce(&mut self, address: Address, nonce: u64) -> Result<u64, EVMError<DB::Error>> {
+        let journaled_state = &mut self.inner.journaled_state;
+        let (account, _) = journaled_state.load_account(address, &mut self.inner.db)?;
+        let had_nonce = account.info.nonce;
+        journaled_state.set_nonce(address, nonce);
+        Ok(had_nonce)
+    }
+
+    /// Returns the error of the last precompile call, if it failed.
+    ///
+    /// Intended for [`Inspector::call_end`](crate::Inspector::call_end) of the precompile call,
+    /// where custom errors can be downcast with [`PrecompileError::downcast_custom`].
+    #[inline]
+    pub fn precompile_error(&self) -> Option<&PrecompileError> {
+        self.precompile_error.as_ref()
+    }
+
+    /// Clears the error of the last precompile call.
+    #[inline]
+    pub fn clear_precompile_error(&mut self) {
+        self.precompile_error = None;
+    }
+
+    /// Returns the memory of the frame that makes a call.
+    ///
+    /// It is set while the call handle runs, the [`CallInput::SharedBuffer`] input of the call
+    /// is read from it.
+    #[inline]
+    pub fn caller_memory(&self) -> &SharedMemory {
+        &self.caller_memory
+    }
+
+    /// Returns the input of a call, see [`EvmContext::caller_memory`].
+    ///
+    /// Returns `None` if the input is out of bounds of the caller memory.
+    #[inline]
+    pub fn call_input<'a>(&'a self, input: &'a CallInput) -> Option<&'a [u8]> {
+        input.as_slice(&self.caller_memory)
+    }
+
     /// Call precompile contract
+    ///
+    /// Input in the caller memory is borrowed and is not copied.
     #[inline]
     fn call_precompile(
         &mut self,
         address: &Address,
-        input_data: &Bytes,
+        input: &CallInput,
         gas: Gas,
     ) -> Result<Option<InterpreterResult>, EVMError<DB::Error>> {
-        let Some(outcome) =
-            self.precompiles
-                .call(address, input_data, gas.limit(), &mut self.inner)
+        let input = input
+            .as_slice(&self.caller_memory)
+            .ok_or_else(call_input_out_of_bounds)?;
+        let Some(outcome) = self
+            .precompiles
+            .call(address, input, gas.limit(), &mut self.inner)
         else {
             return Ok(None);
         };
@@ -123,7 +244,13 @@ impl<DB: Database> EvmContext<DB> {
 
         match outcome {
             Ok(output) => {
-                if result.gas.record_cost(output.gas_used) {
+                // Precompiles of the spec never exceed the bound, larger output of a custom
+                // precompile is not paid for and is not exposed to the caller.
+                if self.env.cfg.bound_precompile_output
+                    && output.bytes.len() > max_output_len(result.gas.limit())
+                {
+                    result.result = InstructionResult::PrecompileOOG;
+                } else if result.gas.record_cost(output.gas_used) {
                     result.result = InstructionResult::Return;
                     result.output = output.bytes;
                 } else {
@@ -133,9 +260,12 @@ impl<DB: Database> EvmContext<DB> {
             Err(PrecompileErrors::Error(e)) => {
                 result.result = if e.is_oog() {
                     InstructionResult::PrecompileOOG
-                } else {
+                } else if e.is_fatal() {
                     InstructionResult::PrecompileError
+                } else {
+                    InstructionResult::Revert
                 };
+                self.precompile_error = Some(e);
             }
             Err(PrecompileErrors::Fatal { msg }) => return Err(EVMError::Precompile(msg)),
         }
@@ -148,7 +278,10 @@ impl<DB: Database> EvmContext<DB> {
         &mut self,
         inputs: &CallInputs,
     ) -> Result<FrameOrResult, EVMError<DB::Error>> {
+        // error of a previous precompile call doesn't belong to this call.
+        self.clear_precompile_error();
         let gas = Gas::new(inputs.gas_limit);
+        let (gas_limit, gas_excess) = self.frame_gas_limit(inputs.gas_limit);
 
         let return_result = |instruction_result: InstructionResult| {
             Ok(FrameOrResult::new_call_result(
@@ -162,7 +295,7 @@ impl<DB: Database> EvmContext<DB> {
         };
 
         // Check depth
-        if self.journaled_state.depth() > CALL_STACK_LIMIT {
+        if self.journaled_state.depth() > self.env.cfg.max_call_depth() {
             return return_result(InstructionResult::CallTooDeep);
         }
 
@@ -181,21 +314,32 @@ impl<DB: Database> EvmContext<DB> {
             return return_result(InstructionResult::InvalidExtDelegateCallTarget);
         }
 
+        debug_assert!(
+            inputs.scheme != CallScheme::CallCode || inputs.target_address == inputs.caller,
+            "CALLCODE target must be the caller"
+        );
+        debug_assert!(
+            inputs.scheme.can_transfer_value() || !inputs.transfers_value(),
+            "{:?} can't transfer value",
+            inputs.scheme
+        );
+
         // Create subroutine checkpoint
         let checkpoint = self.journaled_state.checkpoint();
 
         // Touch address. For "EIP-158 State Clear", this will erase empty accounts.
+        // CALLCODE transfers value to the caller itself, see `CallInputs::transfer_to`.
         match inputs.value {
             // if transfer value is zero, do the touch.
             CallValue::Transfer(value) if value == U256::ZERO => {
-                self.load_account(inputs.target_address)?;
-                self.journaled_state.touch(&inputs.target_address);
+                self.load_account(inputs.transfer_to())?;
+                self.journaled_state.touch(&inputs.transfer_to());
             }
             CallValue::Transfer(value) => {
                 // Transfer value from caller to called account
                 if let Some(result) = self.inner.journaled_state.transfer(
-                    &inputs.caller,
-                    &inputs.target_address,
+                    &inputs.transfer_from(),
+                    &inputs.transfer_to(),
                     value,
                     &mut self.inner.db,
                 )? {
@@ -206,8 +350,11 @@ impl<DB: Database> EvmContext<DB> {
             _ => {}
         };
 
-        if let Some(result) = self.call_precompile(&inputs.bytecode_address, &inputs.input, gas)? {
-            if matches!(result.result, return_ok!()) {
+        if let Some(mut result) =
+            self.call_precompile(&inputs.bytecode_address, &inputs.input, Gas::new(gas_limit))?
+        {
+            result.gas.extend(gas_excess);
+            if matches!(result.result, return_ok!()) && !inputs.dry_run {
                 self.journaled_state.checkpoint_commit();
             } else {
                 self.journaled_state.checkpoint_revert(checkpoint);
@@ -216,15 +363,37 @@ impl<DB: Database> EvmContext<DB> {
                 result,
                 inputs.return_memory_offset.clone(),
             ))
+        } else if inputs.dry_run {
+            // frame is not executed, the checks passed.
+            self.journaled_state.checkpoint_revert(checkpoint);
+            return_result(InstructionResult::Stop)
         } else if !bytecode.is_empty() {
-            let contract =
-                Contract::new_with_context(inputs.input.clone(), bytecode, Some(code_hash), inputs);
+            let input = inputs
+                .input
+                .bytes(&self.caller_memory)
+                .ok_or_else(call_input_out_of_bounds)?;
+            let contract = if self.env.cfg.perf_analyse_created_bytecodes == AnalysisKind::Lazy {
+                Contract::new_lazy(
+                    input,
+                    bytecode,
+                    Some(code_hash),
+                    inputs.target_address,
+                    Some(inputs.bytecode_address),
+                    inputs.caller,
+                    inputs.call_value(),
+                )
+            } else {
+                Contract::new_with_context(input, bytecode, Some(code_hash), inputs)
+            };
+            let mut interpreter = Interpreter::new(contract, gas_limit, inputs.is_static);
+            interpreter.gas_overrides = *self.env.cfg.gas_schedule_overrides();
             // Create interpreter and executes call and push new CallStackFrame.
             Ok(FrameOrResult::new_call_frame(
                 inputs.return_memory_offset.clone(),
                 checkpoint,
-                Interpreter::new(contract, gas.limit(), inputs.is_static),
-            ))
+                interpreter,
+            )
+            .with_gas_excess(gas_excess))
         } else {
             self.journaled_state.checkpoint_commit();
             return_result(InstructionResult::Stop)
@@ -232,6 +401,11 @@ impl<DB: Database> EvmContext<DB> {
     }
 }
 
+/// Error of a [`CallInput`] that is out of bounds of the caller memory.
+pub(crate) fn call_input_out_of_bounds<E>() -> EVMError<E> {
+    EVMError::Custom("call input is out of bounds of the caller memory".into())
+}
+
 /// Test utilities for the [`EvmContext`].
 #[cfg(any(test, feature = "test-utils"))]
 pub(crate) mod test_utils {
@@ -241,6 +415,7 @@ pub(crate) mod test_utils {
         journaled_state::JournaledState,
         primitives::{address, HashSet, SpecId, B256},
     };
+    use std::vec::Vec;
 
     /// Mock caller address.
     pub const MOCK_CALLER: Address = address!("0000000000000000000000000000000000000000");
@@ -248,7 +423,7 @@ pub(crate) mod test_utils {
     /// Creates `CallInputs` that calls a provided contract address from the mock caller.
     pub fn create_mock_call_inputs(to: Address) -> CallInputs {
         CallInputs {
-            input: Bytes::new(),
+            input: CallInput::default(),
             gas_limit: 0,
             bytecode_address: to,
             target_address: to,
@@ -256,6 +431,7 @@ pub(crate) mod test_utils {
             value: CallValue::Transfer(U256::ZERO),
             scheme: revm_interpreter::CallScheme::Call,
             is_eof: false,
+            dry_run: false,
             is_static: false,
             return_memory_offset: 0..0,
         }
@@ -289,14 +465,24 @@ pub(crate) mod test_utils {
         EvmContext {
             inner: InnerEvmContext {
                 env,
-                journaled_state: JournaledState::new(SpecId::CANCUN, HashSet::new()),
+                journaled_state: JournaledState::new(SpecId::CANCUN, HashSet::default()),
                 db,
                 error: Ok(()),
                 valid_authorizations: Vec::new(),
+                trace_call_stack: false,
+                call_stack_exhaustion: None,
+                execution_profile: None,
+                initcode_hashes: None,
+                eof_initcode_error: None,
+                is_validation_only: false,
                 #[cfg(feature = "optimism")]
                 l1_block_info: None,
+                #[cfg(feature = "optimism")]
+                tx_l1_cost: None,
             },
             precompiles: ContextPrecompiles::default(),
+            precompile_error: None,
+            caller_memory: EMPTY_SHARED_MEMORY,
         }
     }
 
@@ -305,14 +491,24 @@ pub(crate) mod test_utils {
         EvmContext {
             inner: InnerEvmContext {
                 env,
-                journaled_state: JournaledState::new(SpecId::CANCUN, HashSet::new()),
+                journaled_state: JournaledState::new(SpecId::CANCUN, HashSet::default()),
                 db,
                 error: Ok(()),
                 valid_authorizations: Default::default(),
+                trace_call_stack: false,
+                call_stack_exhaustion: None,
+                execution_profile: None,
+                initcode_hashes: None,
+                eof_initcode_error: None,
+                is_validation_only: false,
                 #[cfg(feature = "optimism")]
                 l1_block_info: None,
+                #[cfg(feature = "optimism")]
+                tx_l1_cost: None,
             },
             precompiles: ContextPrecompiles::default(),
+            precompile_error: None,
+            caller_memory: EMPTY_SHARED_MEMORY,
         }
     }
 }
@@ -321,13 +517,221 @@ pub(crate) mod test_utils {
 mod tests {
     use super::*;
     use crate::{
-        db::{CacheDB, EmptyDB},
-        primitives::{address, Bytecode},
-        Frame, JournalEntry,
+        db::{CacheDB, EmptyDB, InMemoryDB},
+        inspectors::NoOpInspector,
+        interpreter::{CallInputs, CallOutcome},
+        primitives::{
+            address, bytes, AccountInfo, Bytecode, ExecutionResult, HaltReason, ResultAndState,
+            TxKind, CALL_STACK_LIMIT,
+        },
+        test_utils::{contract_evm, CONTRACT},
+        Evm, Frame, Inspector, JournalEntry,
     };
-    use std::boxed::Box;
+    use proptest::prelude::*;
+    use std::{boxed::Box, vec::Vec};
     use test_utils::*;
 
+    /// Returns 1 MiB of zeros for 15 gas.
+    #[cfg(feature = "std")]
+    struct LargeOutputPrecompile;
+
+    #[cfg(feature = "std")]
+    impl<DB: Database> crate::ContextStatefulPrecompile<DB> for LargeOutputPrecompile {
+        fn call(
+            &self,
+            _input: &[u8],
+            _gas_limit: u64,
+            _context: &mut InnerEvmContext<DB>,
+        ) -> crate::primitives::PrecompileResult {
+            Ok(crate::primitives::PrecompileOutput::new(
+                15,
+                std::vec![0; 1 << 20].into(),
+            ))
+        }
+    }
+
+    /// Precompiles called with huge declared sizes and tiny gas run out of gas without
+    /// allocating memory proportional to the declared sizes.
+    #[cfg(feature = "std")]
+    #[test]
+    fn precompile_output_is_bounded_by_gas() {
+        use crate::{
+            precompile::PrecompileSpecId,
+            test_utils::alloc_counter::{peak_allocated, reset_peak_allocated},
+            ContextPrecompile,
+        };
+        use std::sync::Arc;
+
+        const LARGE_OUTPUT: Address = address!("0000000000000000000000000000000000000100");
+        let mut context = test_utils::create_empty_evm_context(Box::default(), EmptyDB::default());
+        context.precompiles = ContextPrecompiles::new(PrecompileSpecId::BERLIN);
+        context.precompiles.extend([(
+            LARGE_OUTPUT,
+            ContextPrecompile::ContextStateful(Arc::new(LargeOutputPrecompile)),
+        )]);
+
+        // modexp with 4 GiB base and modulus and a 64 KiB exponent.
+        let mut header = [0u8; 96];
+        header[28..32].copy_from_slice(&u32::MAX.to_be_bytes());
+        header[61..64].copy_from_slice(&[0x01, 0x00, 0x00]);
+        header[92..96].copy_from_slice(&u32::MAX.to_be_bytes());
+        let modexp = Bytes::copy_from_slice(&header);
+        // blake2 with `u32::MAX` rounds.
+        let mut blake2_rounds = [0xff; 213];
+        blake2_rounds[212] = 1;
+        let blake2_rounds = Bytes::copy_from_slice(&blake2_rounds);
+        for (address, input) in [
+            (address!("0000000000000000000000000000000000000005"), modexp),
+            (
+                address!("0000000000000000000000000000000000000009"),
+                blake2_rounds,
+            ),
+            (
+                address!("0000000000000000000000000000000000000008"),
+                Bytes::from(std::vec![0; 192 * 1000]),
+            ),
+        ] {
+            reset_peak_allocated();
+            let before = peak_allocated();
+            let result = context
+                .call_precompile(&address, &input.into(), Gas::new(1_000))
+                .unwrap()
+                .unwrap();
+            assert_eq!(result.result, InstructionResult::PrecompileOOG);
+            assert!(result.output.is_empty());
+            assert!(
+                peak_allocated() - before < 1024,
+                "{address} allocated {} bytes",
+                peak_allocated() - before
+            );
+        }
+
+        // output of custom precompiles is bounded only if it is enabled.
+        let result = context
+            .call_precompile(&LARGE_OUTPUT, &CallInput::default(), Gas::new(1_000))
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.result, InstructionResult::Return);
+        assert_eq!(result.output.len(), 1 << 20);
+        context.env.cfg.bound_precompile_output = true;
+        let result = context
+            .call_precompile(&LARGE_OUTPUT, &CallInput::default(), Gas::new(1_000))
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.result, InstructionResult::PrecompileOOG);
+        assert!(result.output.is_empty());
+        let result = context
+            .call_precompile(&LARGE_OUTPUT, &CallInput::default(), Gas::new(100_000))
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.result, InstructionResult::Return);
+        assert_eq!(result.output.len(), 1 << 20);
+    }
+
+    proptest! {
+        /// Linear cost precompiles charge `base + word * ceil(len / 32)` and run out of gas
+        /// instead of wrapping for any gas limit.
+        #[test]
+        fn linear_cost_precompiles(
+            input in prop::collection::vec(any::<u8>(), 0..512),
+            gas_limit in any::<u64>(),
+        ) {
+            let mut context =
+                test_utils::create_empty_evm_context(Box::default(), EmptyDB::default());
+            context.precompiles =
+                ContextPrecompiles::new(crate::precompile::PrecompileSpecId::LATEST);
+            let input = CallInput::Bytes(input.into());
+            for (address, base, word) in [
+                (address!("0000000000000000000000000000000000000002"), 60, 12),
+                (address!("0000000000000000000000000000000000000003"), 600, 120),
+                (address!("0000000000000000000000000000000000000004"), 15, 3),
+            ] {
+                let cost = input.len().div_ceil(32) as u128 * word + base;
+                let result = context
+                    .call_precompile(&address, &input, Gas::new(gas_limit))
+                    .unwrap()
+                    .unwrap();
+                if cost <= u128::from(gas_limit) {
+                    prop_assert_eq!(result.result, InstructionResult::Return);
+                    prop_assert_eq!(u128::from(result.gas.spent()), cost);
+                } else {
+                    prop_assert_eq!(result.result, InstructionResult::PrecompileOOG);
+                    prop_assert!(result.output.is_empty());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn precompile_input_from_caller_memory() {
+        const PROXY: Address = address!("2000000000000000000000000000000000000001");
+        // CALLDATACOPY(7, 0, CALLDATASIZE), CALL(GAS, CONTRACT, 0, 7, CALLDATASIZE, 0, 32),
+        // RETURN(0, 32)
+        let proxy = [
+            &[
+                0x36, 0x5f, 0x60, 0x07, 0x37, 0x60, 0x20, 0x5f, 0x36, 0x60, 0x07, 0x5f, 0x73,
+            ][..],
+            CONTRACT.as_slice(),
+            &[0x5a, 0xf1, 0x60, 0x20, 0x5f, 0xf3],
+        ]
+        .concat();
+        // CALLDATACOPY(0, 0, CALLDATASIZE), STATICCALL(GAS, 0x02, 0, CALLDATASIZE, 0, 32), RETURN(0, 32)
+        let code = bytes!("365f5f3760205f365f60025afa60205ff3");
+        for len in [0, 1, 32, 4096, 128 * 1024] {
+            let input = Bytes::from((0..len).map(|i| i as u8).collect::<Vec<_>>());
+            let expected = crate::precompile::hash::sha256_run(&input, u64::MAX).unwrap();
+            // precompile is called by the transaction frame and by a nested frame.
+            for target in [CONTRACT, PROXY] {
+                let mut evm = contract_evm(code.clone())
+                    .modify_db(|db| {
+                        db.insert_account_info(
+                            PROXY,
+                            AccountInfo::from_bytecode(Bytecode::new_raw(proxy.clone().into())),
+                        );
+                    })
+                    .modify_tx_env(|tx| {
+                        tx.transact_to = Some(TxKind::Call(target));
+                        tx.gas_limit = 30_000_000;
+                        tx.data = input.clone();
+                    })
+                    .with_external_context(NoOpInspector)
+                    .build();
+                let output = evm.transact().unwrap().result.expect_success().1;
+                assert_eq!(output.data(), &expected.bytes, "input length {len}");
+                // inspectors get a copy of the input.
+                let output = evm.inspect().unwrap().result.expect_success().1;
+                assert_eq!(output.data(), &expected.bytes, "input length {len}");
+            }
+        }
+    }
+
+    #[test]
+    fn precompile_error_is_cleared_by_next_call() {
+        let mut context = test_utils::create_empty_evm_context(Box::default(), EmptyDB::default());
+        context.precompiles = ContextPrecompiles::new(crate::precompile::PrecompileSpecId::LATEST);
+
+        // sha256 without gas.
+        let sha256 = create_mock_call_inputs(address!("0000000000000000000000000000000000000002"));
+        let Ok(FrameOrResult::Result(res)) = context.make_call_frame(&sha256) else {
+            panic!("Expected FrameOrResult::Result");
+        };
+        assert_eq!(
+            res.interpreter_result().result,
+            InstructionResult::PrecompileOOG
+        );
+        assert!(context.precompile_error().is_some());
+
+        let res = context.make_call_frame(&create_mock_call_inputs(Address::ZERO));
+        assert!(res.is_ok());
+        assert!(context.precompile_error().is_none());
+
+        // error is cleared between transactions as well.
+        context.make_call_frame(&sha256).unwrap();
+        let mut context = crate::Context::new(context, ());
+        crate::handler::mainnet::clear(&mut context);
+        assert!(context.evm.precompile_error().is_none());
+    }
+
     // Tests that the `EVMContext::make_call_frame` function returns an error if the
     // call stack is too deep.
     #[test]
@@ -411,4 +815,255 @@ mod tests {
         };
         assert_eq!(call_frame.return_memory_range, 0..0,);
     }
+
+    #[test]
+    fn test_make_call_frame_dry_run() {
+        let env = Env::default();
+        let mut cdb = CacheDB::new(EmptyDB::default());
+        let bal = U256::from(3_000_000_000_u128);
+        let by = Bytecode::new_raw(Bytes::from(vec![0x60, 0x00, 0x60, 0x00]));
+        let contract = address!("dead10000000000000000000000000000001dead");
+        cdb.insert_account_info(contract, crate::primitives::AccountInfo::from_bytecode(by));
+        let mut evm_context = create_cache_db_evm_context_with_balance(Box::new(env), cdb, bal);
+        let mut call_inputs = test_utils::create_mock_call_inputs(contract);
+        call_inputs.value = CallValue::Transfer(U256::from(1));
+        call_inputs.dry_run = true;
+        let res = evm_context.make_call_frame(&call_inputs);
+        let Ok(FrameOrResult::Result(result)) = res else {
+            panic!("Expected FrameOrResult::Result");
+        };
+        assert_eq!(result.interpreter_result().result, InstructionResult::Stop);
+
+        // value transfer is reverted.
+        let state = &evm_context.journaled_state.state;
+        assert_eq!(state[&MOCK_CALLER].info.balance, bal);
+        assert_eq!(state[&contract].info.balance, U256::ZERO);
+        assert_eq!(evm_context.journaled_state.depth, 0);
+
+        // dry run still checks that the caller can pay for the value.
+        call_inputs.value = CallValue::Transfer(bal + U256::from(1));
+        let res = evm_context.make_call_frame(&call_inputs);
+        let Ok(FrameOrResult::Result(result)) = res else {
+            panic!("Expected FrameOrResult::Result");
+        };
+        assert_eq!(
+            result.interpreter_result().result,
+            InstructionResult::OutOfFunds
+        );
+    }
+
+    #[test]
+    fn test_make_call_frame_callcode_transfers_to_caller() {
+        let env = Env::default();
+        let mut cdb = CacheDB::new(EmptyDB::default());
+        let bal = U256::from(3_000_000_000_u128);
+        let by = Bytecode::new_raw(Bytes::from(vec![0x60, 0x00, 0x60, 0x00]));
+        let library = address!("dead10000000000000000000000000000001dead");
+        cdb.insert_account_info(library, crate::primitives::AccountInfo::from_bytecode(by));
+        let mut evm_context = create_cache_db_evm_context_with_balance(Box::new(env), cdb, bal);
+        let mut call_inputs = test_utils::create_mock_call_inputs(library);
+        call_inputs.scheme = CallScheme::CallCode;
+        call_inputs.target_address = MOCK_CALLER;
+        call_inputs.value = CallValue::Transfer(U256::from(1));
+        assert_eq!(call_inputs.transfer_to(), MOCK_CALLER);
+        let res = evm_context.make_call_frame(&call_inputs);
+        let Ok(FrameOrResult::Frame(Frame::Call(_))) = res else {
+            panic!("Expected FrameOrResult::Frame(Frame::Call(..))");
+        };
+
+        // value is transferred to the caller itself, library balance is untouched.
+        evm_context.journaled_state.checkpoint_commit();
+        let state = &evm_context.journaled_state.state;
+        assert_eq!(state[&MOCK_CALLER].info.balance, bal);
+        assert_eq!(state[&library].info.balance, U256::ZERO);
+    }
+
+    #[test]
+    fn custom_precompile_error_in_inspector() {
+        use crate::{
+            interpreter::InstructionResult,
+            precompile::{CustomPrecompileError, PrecompileError, PrecompileResult},
+            ContextPrecompile, ContextStatefulPrecompile, InnerEvmContext,
+        };
+        use core::fmt;
+        use std::sync::Arc;
+
+        const PRECOMPILE: Address = address!("00000000000000000000000000000000000000ff");
+
+        #[derive(Debug)]
+        struct Denied {
+            rule: u32,
+            fatal: bool,
+        }
+
+        impl fmt::Display for Denied {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "denied by rule {}", self.rule)
+            }
+        }
+
+        impl CustomPrecompileError for Denied {
+            fn code(&self) -> u32 {
+                self.rule
+            }
+
+            fn is_fatal(&self) -> bool {
+                self.fatal
+            }
+        }
+
+        /// Denies every call, fatal if the input is not empty.
+        struct Denier;
+
+        impl ContextStatefulPrecompile<InMemoryDB> for Denier {
+            fn call(
+                &self,
+                input: &[u8],
+                _gas_limit: u64,
+                _context: &mut InnerEvmContext<InMemoryDB>,
+            ) -> PrecompileResult {
+                Err(PrecompileError::custom(Denied {
+                    rule: 7,
+                    fatal: !input.is_empty(),
+                })
+                .into())
+            }
+        }
+
+        /// Records the custom errors of the precompile calls.
+        #[derive(Default)]
+        struct Errors {
+            rules: Vec<(u32, InstructionResult)>,
+        }
+
+        impl<DB: Database> Inspector<DB> for Errors {
+            fn call_end(
+                &mut self,
+                context: &mut EvmContext<DB>,
+                _inputs: &CallInputs,
+                outcome: CallOutcome,
+            ) -> CallOutcome {
+                if let Some(denied) = context
+                    .precompile_error()
+                    .and_then(|err| err.downcast_custom::<Denied>())
+                {
+                    self.rules.push((denied.rule, outcome.result.result));
+                }
+                outcome
+            }
+        }
+
+        for (input, result) in [
+            (Bytes::new(), InstructionResult::Revert),
+            (bytes!("01"), InstructionResult::PrecompileError),
+        ] {
+            let mut evm = Evm::builder()
+                .with_db(InMemoryDB::default())
+                .with_external_context(Errors::default())
+                .modify_tx_env(|tx| {
+                    tx.caller = MOCK_CALLER;
+                    tx.gas_limit = 30_000;
+                    tx.transact_to = Some(TxKind::Call(PRECOMPILE));
+                    tx.data = input.clone();
+                })
+                .append_handler_register(|handler| {
+                    let precompiles = handler.pre_execution.load_precompiles();
+                    handler.pre_execution.load_precompiles = Arc::new(move || {
+                        let mut precompiles = precompiles.clone();
+                        precompiles.extend([(
+                            PRECOMPILE,
+                            ContextPrecompile::ContextStateful(Arc::new(Denier)),
+                        )]);
+                        precompiles
+                    });
+                })
+                .build();
+            let outcome = evm.inspect().unwrap().result;
+            assert_eq!(evm.context.external.rules, [(7, result)]);
+
+            // non-fatal error reverts without consuming the gas.
+            if result == InstructionResult::Revert {
+                assert!(matches!(outcome, ExecutionResult::Revert { .. }));
+                assert_eq!(outcome.gas_used(), 21_000);
+            } else {
+                assert!(matches!(
+                    outcome,
+                    ExecutionResult::Halt {
+                        reason: HaltReason::PrecompileError,
+                        gas_used: 30_000
+                    }
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn inspector_state_changes_are_reverted_with_frame() {
+        const REVERTER: Address = address!("3000000000000000000000000000000000000003");
+        const TARGET: Address = address!("4000000000000000000000000000000000000004");
+
+        /// Changes `TARGET` on the first step of `CONTRACT` and `REVERTER` frames.
+        #[derive(Default)]
+        struct Cheats {
+            entered: Vec<Address>,
+        }
+
+        impl<DB: Database> Inspector<DB> for Cheats {
+            fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+                let address = interp.contract.target_address;
+                if self.entered.contains(&address) {
+                    return;
+                }
+                self.entered.push(address);
+                if address == CONTRACT {
+                    assert_eq!(context.set_nonce(TARGET, 3).ok(), Some(0));
+                    assert_eq!(
+                        context.set_balance(TARGET, U256::from(1)).ok(),
+                        Some(U256::ZERO)
+                    );
+                } else {
+                    assert_eq!(
+                        context.set_balance(TARGET, U256::from(5)).ok(),
+                        Some(U256::from(1))
+                    );
+                    assert_eq!(
+                        context
+                            .set_storage(TARGET, U256::from(1), U256::from(2))
+                            .ok(),
+                        Some(U256::ZERO)
+                    );
+                    let code = Bytecode::new_raw(bytes!("00"));
+                    assert!(context
+                        .set_code(TARGET, code)
+                        .is_ok_and(|previous| previous.is_empty()));
+                }
+            }
+        }
+
+        // CALL(GAS, REVERTER, 0, 0, 0, 0, 0), STOP
+        let mut code = vec![0x5f, 0x5f, 0x5f, 0x5f, 0x5f, 0x73];
+        code.extend_from_slice(REVERTER.as_slice());
+        code.extend_from_slice(&[0x5a, 0xf1, 0x00]);
+        let mut evm = contract_evm(code)
+            .with_external_context(Cheats::default())
+            .modify_db(|db| {
+                // REVERT(0, 0)
+                db.insert_account_info(
+                    REVERTER,
+                    AccountInfo::from_bytecode(Bytecode::new_raw(bytes!("5f5ffd"))),
+                );
+            })
+            .build();
+        let ResultAndState { result, state, .. } = evm.inspect().unwrap();
+        assert!(result.is_success());
+        assert_eq!(evm.context.external.entered, [CONTRACT, REVERTER]);
+
+        // only the changes made in the top frame are kept.
+        let target = &state[&TARGET];
+        assert!(target.is_touched());
+        assert_eq!(target.info.nonce, 3);
+        assert_eq!(target.info.balance, U256::from(1));
+        assert_eq!(target.storage[&U256::from(1)].present_value, U256::ZERO);
+        assert!(target.info.code.as_ref().unwrap().is_empty());
+    }
 }
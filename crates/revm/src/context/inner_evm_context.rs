@@ -8,15 +8,27 @@ use crate::{
     },
     journaled_state::JournaledState,
     primitives::{
+        eof::EofDecodeError,
         keccak256, AccessListItem, Account, Address, AnalysisKind, Bytecode, Bytes, CreateScheme,
-        EVMError, Env, Eof, HashSet, Spec,
+        EVMError, Env, Eof, ExecutionProfile, HashMap, HashSet, Spec,
         SpecId::{self, *},
-        B256, EOF_MAGIC_BYTES, EOF_MAGIC_HASH, U256,
+        B256, BLOCKHASH_SERVE_WINDOW, BLOCKHASH_STORAGE_ADDRESS, BLOCK_HASH_HISTORY,
+        EOF_MAGIC_BYTES, EOF_MAGIC_HASH, KECCAK_EMPTY, U256,
     },
-    FrameOrResult, JournalCheckpoint, CALL_STACK_LIMIT,
+    FrameOrResult, JournalCheckpoint,
 };
 use std::{boxed::Box, sync::Arc, vec::Vec};
 
+/// Call stack that exceeded the call stack limit, recorded when
+/// [`InnerEvmContext::trace_call_stack`] is enabled.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CallStackExhaustion {
+    /// Depth of the call stack when the limit was exceeded.
+    pub depth: u64,
+    /// Target addresses of the frames on the call stack, starting with the outermost one.
+    pub addresses: Vec<Address>,
+}
+
 /// EVM contexts contains data that EVM needs for execution.
 #[derive(Debug)]
 pub struct InnerEvmContext<DB: Database> {
@@ -31,9 +43,31 @@ pub struct InnerEvmContext<DB: Database> {
     pub error: Result<(), EVMError<DB::Error>>,
     /// EIP-7702 Authorization list of accounts that needs to be cleared.
     pub valid_authorizations: Vec<Address>,
+    /// If true, the call stack that first exceeds the call stack limit in a transaction
+    /// is recorded in `call_stack_exhaustion`.
+    pub trace_call_stack: bool,
+    /// Call stack that exceeded the call stack limit in the last transaction.
+    pub call_stack_exhaustion: Option<CallStackExhaustion>,
+    /// Profile of the executed transaction, collected if
+    /// [`CfgEnv::profile_execution`](crate::primitives::CfgEnv::profile_execution) is set.
+    pub execution_profile: Option<Box<ExecutionProfile>>,
+    /// Indices of [`TxEnv::initcodes`](crate::primitives::TxEnv::initcodes) by their keccak256
+    /// hash, built on the first lookup in a transaction, see [`Self::initcode_by_hash`].
+    pub initcode_hashes: Option<HashMap<B256, usize>>,
+    /// Error of decoding the EOF initcode of the transaction, set if the EOF create
+    /// transaction failed with [`InstructionResult::InvalidEOFInitCode`] because the
+    /// initcontainer could not be split from the calldata.
+    pub eof_initcode_error: Option<EofDecodeError>,
+    /// Set while the transaction is only validated, by
+    /// [`Evm::preverify_transaction`](crate::Evm::preverify_transaction).
+    pub is_validation_only: bool,
     /// Used as temporary value holder to store L1 block info.
     #[cfg(feature = "optimism")]
     pub l1_block_info: Option<crate::optimism::L1BlockInfo>,
+    /// L1 data fee charged for the last transaction, `None` for deposit transactions
+    /// or if it is disabled with `optimism_no_l1_fee`.
+    #[cfg(feature = "optimism")]
+    pub tx_l1_cost: Option<U256>,
 }
 
 impl<DB: Database + Clone> Clone for InnerEvmContext<DB>
@@ -47,8 +81,16 @@ where
             db: self.db.clone(),
             error: self.error.clone(),
             valid_authorizations: self.valid_authorizations.clone(),
+            trace_call_stack: self.trace_call_stack,
+            call_stack_exhaustion: self.call_stack_exhaustion.clone(),
+            execution_profile: self.execution_profile.clone(),
+            initcode_hashes: self.initcode_hashes.clone(),
+            eof_initcode_error: self.eof_initcode_error,
+            is_validation_only: self.is_validation_only,
             #[cfg(feature = "optimism")]
             l1_block_info: self.l1_block_info.clone(),
+            #[cfg(feature = "optimism")]
+            tx_l1_cost: self.tx_l1_cost,
         }
     }
 }
@@ -57,12 +99,20 @@ impl<DB: Database> InnerEvmContext<DB> {
     pub fn new(db: DB) -> Self {
         Self {
             env: Box::default(),
-            journaled_state: JournaledState::new(SpecId::LATEST, HashSet::new()),
+            journaled_state: JournaledState::new(SpecId::LATEST, HashSet::default()),
             db,
             error: Ok(()),
             valid_authorizations: Default::default(),
+            trace_call_stack: false,
+            call_stack_exhaustion: None,
+            execution_profile: None,
+            initcode_hashes: None,
+            eof_initcode_error: None,
+            is_validation_only: false,
             #[cfg(feature = "optimism")]
             l1_block_info: None,
+            #[cfg(feature = "optimism")]
+            tx_l1_cost: None,
         }
     }
 
@@ -71,12 +121,20 @@ impl<DB: Database> InnerEvmContext<DB> {
     pub fn new_with_env(db: DB, env: Box<Env>) -> Self {
         Self {
             env,
-            journaled_state: JournaledState::new(SpecId::LATEST, HashSet::new()),
+            journaled_state: JournaledState::new(SpecId::LATEST, HashSet::default()),
             db,
             error: Ok(()),
             valid_authorizations: Default::default(),
+            trace_call_stack: false,
+            call_stack_exhaustion: None,
+            execution_profile: None,
+            initcode_hashes: None,
+            eof_initcode_error: None,
+            is_validation_only: false,
             #[cfg(feature = "optimism")]
             l1_block_info: None,
+            #[cfg(feature = "optimism")]
+            tx_l1_cost: None,
         }
     }
 
@@ -91,8 +149,16 @@ impl<DB: Database> InnerEvmContext<DB> {
             db,
             error: Ok(()),
             valid_authorizations: Default::default(),
+            trace_call_stack: self.trace_call_stack,
+            call_stack_exhaustion: self.call_stack_exhaustion,
+            execution_profile: self.execution_profile,
+            initcode_hashes: self.initcode_hashes,
+            eof_initcode_error: self.eof_initcode_error,
+            is_validation_only: self.is_validation_only,
             #[cfg(feature = "optimism")]
             l1_block_info: self.l1_block_info,
+            #[cfg(feature = "optimism")]
+            tx_l1_cost: self.tx_l1_cost,
         }
     }
 
@@ -104,19 +170,25 @@ impl<DB: Database> InnerEvmContext<DB> {
 
     /// Load access list for berlin hard fork.
     ///
-    /// Loading of accounts/storages is needed to make them warm.
+    /// Loading of accounts/storages is needed to make them warm. If
+    /// [`CfgEnv::preload_access_list_code`](crate::primitives::CfgEnv::preload_access_list_code)
+    /// is set, code of the accounts is loaded too.
     #[inline]
     pub fn load_access_list(&mut self) -> Result<(), EVMError<DB::Error>> {
+        let preload_code = self.env.cfg.preload_access_list_code;
         for AccessListItem {
             address,
             storage_keys,
-        } in self.env.tx.access_list.iter()
+        } in self.env.tx().access_list.iter()
         {
-            self.journaled_state.initial_account_load(
+            let account = self.journaled_state.initial_account_load(
                 *address,
                 storage_keys.iter().map(|i| U256::from_be_bytes(i.0)),
                 &mut self.db,
             )?;
+            if preload_code {
+                JournaledState::load_account_code(account, &mut self.db)?;
+            }
         }
         Ok(())
     }
@@ -127,16 +199,82 @@ impl<DB: Database> InnerEvmContext<DB> {
         &mut self.env
     }
 
+    /// Returns the initcontainer of [`TxEnv::initcodes`](crate::primitives::TxEnv::initcodes)
+    /// with the given keccak256 hash.
+    ///
+    /// Initcodes are hashed once, on the first lookup in the transaction.
+    #[inline]
+    pub fn initcode_by_hash(&mut self, hash: B256) -> Option<Bytes> {
+        let initcodes = &self.env.tx().initcodes;
+        let index = *self
+            .initcode_hashes
+            .get_or_insert_with(|| {
+                // first initcode with the hash wins.
+                (0..initcodes.len())
+                    .rev()
+                    .map(|index| (keccak256(&initcodes[index]), index))
+                    .collect()
+            })
+            .get(&hash)?;
+        initcodes.get(index).cloned()
+    }
+
     /// Returns the error by replacing it with `Ok(())`, if any.
     #[inline]
     pub fn take_error(&mut self) -> Result<(), EVMError<DB::Error>> {
         core::mem::replace(&mut self.error, Ok(()))
     }
 
-    /// Fetch block hash from database.
+    /// Fetch block hash of the given block `number`.
+    ///
+    /// Returns zero hash if `number` is the current block, a block in the future, or a block
+    /// older than [BLOCK_HASH_HISTORY]. Database is consulted only for blocks inside that window.
+    ///
+    /// If Prague is enabled, hashes of blocks older than [BLOCK_HASH_HISTORY] but inside
+    /// [BLOCKHASH_SERVE_WINDOW] are read from the EIP-2935 history storage contract.
     #[inline]
     pub fn block_hash(&mut self, number: u64) -> Result<B256, EVMError<DB::Error>> {
-        self.db.block_hash(number).map_err(EVMError::Database)
+        let block_number = self.env.block.number.saturating_to::<u64>();
+
+        let Some(diff) = block_number.checked_sub(number) else {
+            return Ok(B256::ZERO);
+        };
+
+        // blockhash should push zero if number is same as current block number.
+        if diff == 0 {
+            return Ok(B256::ZERO);
+        }
+
+        if diff <= BLOCK_HASH_HISTORY as u64 {
+            return self.db.block_hash(number).map_err(EVMError::Database);
+        }
+
+        // EIP-2935: Serve historical block hashes from state
+        if self.spec_id().is_enabled_in(PRAGUE) && diff <= BLOCKHASH_SERVE_WINDOW as u64 {
+            let index = U256::from(number % BLOCKHASH_SERVE_WINDOW as u64);
+            // Read is not journaled, it does not warm the slot.
+            let value = match self
+                .journaled_state
+                .state
+                .get(&BLOCKHASH_STORAGE_ADDRESS)
+                .and_then(|account| account.storage.get(&index))
+            {
+                Some(slot) => slot.present_value,
+                None => {
+                    let value = self
+                        .db
+                        .storage(BLOCKHASH_STORAGE_ADDRESS, index)
+                        .map_err(EVMError::Database)?;
+                    if let Some(db_reads) = &mut self.journaled_state.db_reads {
+                        db_reads.check_storage(BLOCKHASH_STORAGE_ADDRESS, index, value)?;
+                    }
+                    value
+                }
+            };
+            return Ok(B256::from(value.to_be_bytes()));
+        }
+
+        Ok(B256::ZERO)
     }
 
     /// Mark account as touched as only touched accounts will be added to state.
@@ -192,6 +330,31 @@ impl<DB: Database> InnerEvmContext<DB> {
             })
     }
 
+    /// Keeps the jump destinations found by a frame executed with [`AnalysisKind::Lazy`] in the
+    /// code of the loaded account, so that the next frames of the transaction don't analyze
+    /// the code again.
+    ///
+    /// Code is not tracked inside journal, the analyzed code is kept on revert as well.
+    pub fn keep_lazy_analysis(&mut self, contract: &Contract) {
+        if self.env.cfg.perf_analyse_created_bytecodes != AnalysisKind::Lazy
+            || contract.pending_analysis
+            || !matches!(contract.bytecode, Bytecode::LegacyAnalyzed(_))
+        {
+            return;
+        }
+        let (Some(address), Some(hash)) = (contract.bytecode_address, contract.hash) else {
+            return;
+        };
+        let Some(account) = self.journaled_state.state.get_mut(&address) else {
+            return;
+        };
+        if account.info.code_hash == hash
+            && matches!(account.info.code, Some(Bytecode::LegacyRaw(_)))
+        {
+            account.info.code = Some(contract.bytecode.clone());
+        }
+    }
+
     /// Get code hash of address.
     ///
     /// In case of EOF account it will return `EOF_MAGIC_HASH`
@@ -243,6 +406,19 @@ impl<DB: Database> InnerEvmContext<DB> {
         self.journaled_state.tstore(address, index, value)
     }
 
+    /// Sets the code of the warm account, hashing it with [`CfgEnv::keccak256`].
+    ///
+    /// [`CfgEnv::keccak256`]: crate::primitives::CfgEnv::keccak256
+    #[inline]
+    pub fn set_code(&mut self, address: Address, code: Bytecode) {
+        let hash = if code.is_empty() {
+            KECCAK_EMPTY
+        } else {
+            self.env.cfg.keccak256(code.original_byte_slice())
+        };
+        self.journaled_state.set_code_with_hash(address, code, hash)
+    }
+
     /// Selfdestructs the account.
     #[inline]
     pub fn selfdestruct(
@@ -254,6 +430,79 @@ impl<DB: Database> InnerEvmContext<DB> {
             .selfdestruct(address, target, &mut self.db)
     }
 
+    /// Returns the nonce of the account without loading it to the journal.
+    fn peek_nonce(&mut self, address: Address) -> Result<u64, EVMError<DB::Error>> {
+        if let Some(account) = self.journaled_state.state.get(&address) {
+            return Ok(account.info.nonce);
+        }
+        let info = self.db.basic(address).map_err(EVMError::Database)?;
+        if let Some(db_reads) = &mut self.journaled_state.db_reads {
+            db_reads.check_basic(address, &info)?;
+        }
+        Ok(info.map(|info| info.nonce).unwrap_or_default())
+    }
+
+    /// Returns the address that [`InnerEvmContext::make_create_frame`] would create for
+    /// the `inputs`.
+    ///
+    /// Nonce of the caller is read from the journal or from the database, nothing is journaled.
+    pub fn preview_create_address(
+        &mut self,
+        inputs: &CreateInputs,
+    ) -> Result<Address, EVMError<DB::Error>> {
+        let nonce = match inputs.scheme {
+            CreateScheme::Create => self.peek_nonce(inputs.caller)?,
+            CreateScheme::Create2 { .. } => 0,
+        };
+        Ok(self.create_address(inputs, nonce, self.init_code_hash(inputs)))
+    }
+
+    /// Returns the hash of the init code for CREATE2, zero for CREATE.
+    fn init_code_hash(&self, inputs: &CreateInputs) -> B256 {
+        match inputs.scheme {
+            CreateScheme::Create => B256::ZERO,
+            CreateScheme::Create2 { .. } => self.env.cfg.keccak256(&inputs.init_code),
+        }
+    }
+
+    /// Returns the address created by the `inputs`, CREATE2 hashes with the keccak
+    /// provider of the config if set.
+    fn create_address(&self, inputs: &CreateInputs, nonce: u64, init_code_hash: B256) -> Address {
+        match (inputs.scheme, self.env.cfg.keccak_provider) {
+            (CreateScheme::Create2 { salt }, Some(provider)) => {
+                provider.create2(inputs.caller, salt, init_code_hash)
+            }
+            _ => inputs.created_address_with_hash(nonce, init_code_hash),
+        }
+    }
+
+    /// Returns the address that [`InnerEvmContext::make_eofcreate_frame`] would create for
+    /// the `inputs`.
+    ///
+    /// For EOF create transactions the nonce of the transaction is used if set, otherwise
+    /// the nonce of the caller. Nothing is journaled.
+    pub fn preview_eofcreate_address(
+        &mut self,
+        inputs: &EOFCreateInputs,
+    ) -> Result<Address, EVMError<DB::Error>> {
+        match &inputs.kind {
+            EOFCreateKind::Opcode {
+                created_address, ..
+            }
+            | EOFCreateKind::TxInitcode {
+                created_address, ..
+            } => Ok(*created_address),
+            EOFCreateKind::Tx { .. } => {
+                let caller = self.env.tx().caller;
+                let nonce = match self.env.tx().nonce {
+                    Some(nonce) => nonce,
+                    None => self.peek_nonce(caller)?,
+                };
+                Ok(caller.create(nonce))
+            }
+        }
+    }
+
     /// Make create frame.
     #[inline]
     pub fn make_eofcreate_frame(
@@ -261,14 +510,16 @@ impl<DB: Database> InnerEvmContext<DB> {
         spec_id: SpecId,
         inputs: &EOFCreateInputs,
     ) -> Result<FrameOrResult, EVMError<DB::Error>> {
-        let return_error = |e| {
+        let (gas_limit, gas_excess) = self.frame_gas_limit(inputs.gas_limit);
+        // Created address is known for all errors, it is passed to the inspector.
+        let return_error = |e, created_address| {
             Ok(FrameOrResult::new_eofcreate_result(
                 InterpreterResult {
                     result: e,
                     gas: Gas::new(inputs.gas_limit),
                     output: Bytes::new(),
                 },
-                None,
+                Some(created_address),
             ))
         };
 
@@ -278,34 +529,63 @@ impl<DB: Database> InnerEvmContext<DB> {
                 input,
                 created_address,
             } => (input.clone(), initcode.clone(), *created_address),
+            EOFCreateKind::TxInitcode {
+                hash,
+                input,
+                created_address,
+            } => {
+                // Initcontainers are validated with the transaction, this guards against
+                // the execution of unvalidated transactions.
+                let Some(Ok(initcode)) = self.initcode_by_hash(*hash).map(Eof::decode) else {
+                    return return_error(InstructionResult::InvalidEOFInitCode, *created_address);
+                };
+                (input.clone(), initcode, *created_address)
+            }
             EOFCreateKind::Tx { initdata } => {
                 // Use nonce from tx (if set) or from account (if not).
                 // Nonce for call is bumped in deduct_caller
                 // TODO(make this part of nonce increment code)
-                let nonce = self.env.tx.nonce.unwrap_or_else(|| {
-                    let caller = self.env.tx.caller;
+                let nonce = self.env.tx().nonce.unwrap_or_else(|| {
+                    let caller = self.env.tx().caller;
                     self.load_account(caller)
                         .map(|(a, _)| a.info.nonce)
                         .unwrap_or_default()
                 });
+                let created_address = self.env.tx().caller.create(nonce);
+
+                // EIP-3860: Limit initcode. It is checked in tx validation,
+                // this guards against the execution of unvalidated transactions.
+                if initdata.len() > self.env.cfg.max_initcode_size() {
+                    return return_error(
+                        InstructionResult::CreateInitCodeSizeLimit,
+                        created_address,
+                    );
+                }
 
                 // decode eof and init code.
-                let Ok((eof, input)) = Eof::decode_dangling(initdata.clone()) else {
-                    return return_error(InstructionResult::InvalidEOFInitCode);
+                let (eof, input) = match Eof::decode_dangling(initdata.clone()) {
+                    Ok(decoded) => decoded,
+                    Err(e) => {
+                        self.eof_initcode_error = Some(e);
+                        return return_error(
+                            InstructionResult::InvalidEOFInitCode,
+                            created_address,
+                        );
+                    }
                 };
 
                 if validate_eof(&eof).is_err() {
                     // TODO (EOF) new error type.
-                    return return_error(InstructionResult::InvalidEOFInitCode);
+                    return return_error(InstructionResult::InvalidEOFInitCode, created_address);
                 }
 
-                (input, eof, self.env.tx.caller.create(nonce))
+                (input, eof, created_address)
             }
         };
 
         // Check depth
-        if self.journaled_state.depth() > CALL_STACK_LIMIT {
-            return return_error(InstructionResult::CallTooDeep);
+        if self.journaled_state.depth() > self.env.cfg.max_call_depth() {
+            return return_error(InstructionResult::CallTooDeep, created_address);
         }
 
         // Fetch balance of caller.
@@ -313,13 +593,12 @@ impl<DB: Database> InnerEvmContext<DB> {
 
         // Check if caller has enough balance to send to the created contract.
         if caller_balance < inputs.value {
-            return return_error(InstructionResult::OutOfFunds);
+            return return_error(InstructionResult::OutOfFunds, created_address);
         }
 
         // Increase nonce of caller and check if it overflows
         if self.journaled_state.inc_nonce(inputs.caller).is_none() {
-            // can't happen on mainnet.
-            return return_error(InstructionResult::Return);
+            return return_error(InstructionResult::NonceOverflow, created_address);
         }
 
         // Load account so it needs to be marked as warm for access list.
@@ -335,7 +614,7 @@ impl<DB: Database> InnerEvmContext<DB> {
         ) {
             Ok(checkpoint) => checkpoint,
             Err(e) => {
-                return return_error(e);
+                return return_error(e, created_address);
             }
         };
 
@@ -350,15 +629,16 @@ impl<DB: Database> InnerEvmContext<DB> {
             inputs.value,
         );
 
-        let mut interpreter = Interpreter::new(contract, inputs.gas_limit, false);
+        let mut interpreter = Interpreter::new(contract, gas_limit, false);
+        interpreter.gas_overrides = *self.env.cfg.gas_schedule_overrides();
+        interpreter.frame_info.is_create = true;
         // EOF init will enable RETURNCONTRACT opcode.
         interpreter.set_is_eof_init();
 
-        Ok(FrameOrResult::new_eofcreate_frame(
-            created_address,
-            checkpoint,
-            interpreter,
-        ))
+        Ok(
+            FrameOrResult::new_eofcreate_frame(created_address, checkpoint, interpreter)
+                .with_gas_excess(gas_excess),
+        )
     }
 
     /// If error is present revert changes, otherwise save EOF bytecode.
@@ -387,7 +667,10 @@ impl<DB: Database> InnerEvmContext<DB> {
         }
 
         // deduct gas for code deployment.
-        let gas_for_code = interpreter_result.output.len() as u64 * gas::CODEDEPOSIT;
+        let gas_for_code = gas::code_deposit_cost(
+            self.env.cfg.gas_schedule_overrides(),
+            interpreter_result.output.len(),
+        );
         if !interpreter_result.gas.record_cost(gas_for_code) {
             self.journaled_state.checkpoint_revert(journal_checkpoint);
             interpreter_result.result = InstructionResult::OutOfGas;
@@ -402,8 +685,7 @@ impl<DB: Database> InnerEvmContext<DB> {
             Eof::decode(interpreter_result.output.clone()).expect("Eof is already verified");
 
         // eof bytecode is going to be hashed.
-        self.journaled_state
-            .set_code(address, Bytecode::Eof(Arc::new(bytecode)));
+        self.set_code(address, Bytecode::Eof(Arc::new(bytecode)));
     }
 
     /// Make create frame.
@@ -413,6 +695,7 @@ impl<DB: Database> InnerEvmContext<DB> {
         spec_id: SpecId,
         inputs: &CreateInputs,
     ) -> Result<FrameOrResult, EVMError<DB::Error>> {
+        let (gas_limit, gas_excess) = self.frame_gas_limit(inputs.gas_limit);
         let return_error = |e| {
             Ok(FrameOrResult::new_create_result(
                 InterpreterResult {
@@ -425,7 +708,7 @@ impl<DB: Database> InnerEvmContext<DB> {
         };
 
         // Check depth
-        if self.journaled_state.depth() > CALL_STACK_LIMIT {
+        if self.journaled_state.depth() > self.env.cfg.max_call_depth() {
             return return_error(InstructionResult::CallTooDeep);
         }
 
@@ -448,18 +731,12 @@ impl<DB: Database> InnerEvmContext<DB> {
         if let Some(nonce) = self.journaled_state.inc_nonce(inputs.caller) {
             old_nonce = nonce - 1;
         } else {
-            return return_error(InstructionResult::Return);
+            return return_error(InstructionResult::NonceOverflow);
         }
 
         // Create address
-        let mut init_code_hash = B256::ZERO;
-        let created_address = match inputs.scheme {
-            CreateScheme::Create => inputs.caller.create(old_nonce),
-            CreateScheme::Create2 { salt } => {
-                init_code_hash = keccak256(&inputs.init_code);
-                inputs.caller.create2(salt.to_be_bytes(), init_code_hash)
-            }
-        };
+        let init_code_hash = self.init_code_hash(inputs);
+        let created_address = self.create_address(inputs, old_nonce, init_code_hash);
 
         // Load account so it needs to be marked as warm for access list.
         self.journaled_state
@@ -480,7 +757,12 @@ impl<DB: Database> InnerEvmContext<DB> {
 
         let bytecode = Bytecode::new_raw(inputs.init_code.clone());
 
-        let contract = Contract::new(
+        let new_contract = if self.env.cfg.perf_analyse_created_bytecodes == AnalysisKind::Lazy {
+            Contract::new_lazy
+        } else {
+            Contract::new
+        };
+        let contract = new_contract(
             Bytes::new(),
             bytecode,
             Some(init_code_hash),
@@ -490,11 +772,33 @@ impl<DB: Database> InnerEvmContext<DB> {
             inputs.value,
         );
 
-        Ok(FrameOrResult::new_create_frame(
-            created_address,
-            checkpoint,
-            Interpreter::new(contract, inputs.gas_limit, false),
-        ))
+        let mut interpreter = Interpreter::new(contract, gas_limit, false);
+        interpreter.gas_overrides = *self.env.cfg.gas_schedule_overrides();
+        interpreter.frame_info.is_create = true;
+        // initcode of CREATE is hashed only if the frame code hash is requested.
+        if let CreateScheme::Create = inputs.scheme {
+            interpreter.frame_info.code_hash = None;
+        }
+
+        Ok(
+            FrameOrResult::new_create_frame(created_address, checkpoint, interpreter)
+                .with_gas_excess(gas_excess),
+        )
+    }
+
+    /// Returns the gas limit of a new frame and the gas of the inputs above
+    /// [`CfgEnv::max_frame_gas`](crate::primitives::CfgEnv::max_frame_gas) that is returned
+    /// to the parent when the frame ends.
+    ///
+    /// Needs to be called before the journal checkpoint of the frame is created, the first
+    /// frame of the transaction is not capped.
+    #[inline]
+    pub fn frame_gas_limit(&self, gas_limit: u64) -> (u64, u64) {
+        let frame_gas_limit = match self.env.cfg.max_frame_gas {
+            Some(cap) if self.journaled_state.depth() > 0 => cap.min(gas_limit),
+            _ => gas_limit,
+        };
+        (frame_gas_limit, gas_limit - frame_gas_limit)
     }
 
     /// Handles call return.
@@ -552,7 +856,10 @@ impl<DB: Database> InnerEvmContext<DB> {
             interpreter_result.result = InstructionResult::CreateContractSizeLimit;
             return;
         }
-        let gas_for_code = interpreter_result.output.len() as u64 * gas::CODEDEPOSIT;
+        let gas_for_code = gas::code_deposit_cost(
+            self.env.cfg.gas_schedule_overrides(),
+            interpreter_result.output.len(),
+        );
         if !interpreter_result.gas.record_cost(gas_for_code) {
             // record code deposit gas cost and check if we are out of gas.
             // EIP-2 point 3: If contract creation does not have enough gas to pay for the
@@ -571,15 +878,731 @@ impl<DB: Database> InnerEvmContext<DB> {
 
         // Do analysis of bytecode straight away.
         let bytecode = match self.env.cfg.perf_analyse_created_bytecodes {
-            AnalysisKind::Raw => Bytecode::new_raw(interpreter_result.output.clone()),
+            AnalysisKind::Raw | AnalysisKind::Lazy => {
+                Bytecode::new_raw(interpreter_result.output.clone())
+            }
             AnalysisKind::Analyse => {
                 to_analysed(Bytecode::new_raw(interpreter_result.output.clone()))
             }
         };
 
         // set code
-        self.journaled_state.set_code(address, bytecode);
+        self.set_code(address, bytecode);
 
         interpreter_result.result = InstructionResult::Return;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{CacheDB, EmptyDB, InMemoryDB};
+    use crate::interpreter::{CallInputs, CallOutcome};
+    use crate::primitives::{
+        address, b256, bytes, eof::EofSection, AccountInfo, ExecutionResult, GasScheduleOverrides,
+        HaltReason, KeccakProvider, OutOfGasError, ResultAndState, TxKind,
+    };
+    use crate::test_utils::{contract_evm, CALLER, CONTRACT};
+    use crate::{Evm, EvmContext, Inspector};
+
+    const BLOCK_NUMBER: u64 = 10_000;
+
+    fn new_context(spec: SpecId) -> InnerEvmContext<CacheDB<EmptyDB>> {
+        let mut db = CacheDB::new(EmptyDB::default());
+        let index = U256::from((BLOCK_NUMBER - 257) % BLOCKHASH_SERVE_WINDOW as u64);
+        db.insert_account_storage(BLOCKHASH_STORAGE_ADDRESS, index, U256::from(0x2935))
+            .unwrap();
+
+        let mut env = Env::default();
+        env.block.number = U256::from(BLOCK_NUMBER);
+        let mut context = InnerEvmContext::new_with_env(db, Box::new(env));
+        context.journaled_state.set_spec_id(spec);
+        context
+    }
+
+    #[test]
+    fn lazy_analysis_is_kept() {
+        // PUSH1 4, JUMP, INVALID, JUMPDEST, STOP
+        let raw = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x04, 0x56, 0xfe, 0x5b, 0x00]));
+        let mut context = new_context(CANCUN);
+        context.env.cfg.perf_analyse_created_bytecodes = AnalysisKind::Lazy;
+        context
+            .db
+            .insert_account_info(CONTRACT, AccountInfo::from_bytecode(raw.clone()));
+
+        let hash = context.code_hash(CONTRACT).unwrap().0;
+        let lazy_contract = |bytecode| {
+            Contract::new_lazy(
+                Bytes::new(),
+                bytecode,
+                Some(hash),
+                CONTRACT,
+                Some(CONTRACT),
+                Address::ZERO,
+                U256::ZERO,
+            )
+        };
+        let loaded_code = |context: &InnerEvmContext<_>| {
+            context.journaled_state.state[&CONTRACT]
+                .info
+                .code
+                .clone()
+                .unwrap()
+        };
+
+        // nothing is kept if the frame did not jump.
+        context.keep_lazy_analysis(&lazy_contract(raw.clone()));
+        assert_eq!(loaded_code(&context), raw);
+
+        let mut contract = lazy_contract(raw.clone());
+        contract.analyze_pending();
+        context.keep_lazy_analysis(&contract);
+        assert_eq!(loaded_code(&context), to_analysed(raw));
+
+        // the next frame starts analyzed.
+        let next = lazy_contract(loaded_code(&context));
+        assert!(!next.pending_analysis);
+        assert!(next.bytecode.legacy_jump_table().unwrap().is_valid(4));
+    }
+
+    #[test]
+    fn block_hash_in_window() {
+        let mut context = new_context(CANCUN);
+        let expected = EmptyDB::default().block_hash(BLOCK_NUMBER - 1).unwrap();
+        assert_eq!(context.block_hash(BLOCK_NUMBER - 1).unwrap(), expected);
+    }
+
+    #[test]
+    fn block_hash_exactly_256_back() {
+        let mut context = new_context(CANCUN);
+        let number = BLOCK_NUMBER - 256;
+        let expected = EmptyDB::default().block_hash(number).unwrap();
+        assert_eq!(context.block_hash(number).unwrap(), expected);
+    }
+
+    #[test]
+    fn block_hash_257_back() {
+        let number = BLOCK_NUMBER - 257;
+
+        let mut context = new_context(CANCUN);
+        assert_eq!(context.block_hash(number).unwrap(), B256::ZERO);
+
+        let mut context = new_context(PRAGUE);
+        assert_eq!(
+            context.block_hash(number).unwrap(),
+            b256!("0000000000000000000000000000000000000000000000000000000000002935")
+        );
+        // history read does not load the account into the journal.
+        assert!(!context
+            .journaled_state
+            .state
+            .contains_key(&BLOCKHASH_STORAGE_ADDRESS));
+    }
+
+    #[test]
+    fn block_hash_current_and_future() {
+        let mut context = new_context(PRAGUE);
+        assert_eq!(context.block_hash(BLOCK_NUMBER).unwrap(), B256::ZERO);
+        assert_eq!(context.block_hash(BLOCK_NUMBER + 1).unwrap(), B256::ZERO);
+        assert_eq!(context.block_hash(u64::MAX).unwrap(), B256::ZERO);
+    }
+
+    #[test]
+    fn preview_create_address_matches_frame() {
+        let caller = Address::with_last_byte(0xca);
+        let mut context = new_context(CANCUN);
+        context.db.insert_account_info(
+            caller,
+            AccountInfo {
+                nonce: 5,
+                ..Default::default()
+            },
+        );
+
+        for scheme in [
+            CreateScheme::Create,
+            CreateScheme::Create2 {
+                salt: U256::from(7),
+            },
+        ] {
+            let inputs = CreateInputs {
+                caller,
+                scheme,
+                value: U256::ZERO,
+                init_code: Bytes::from_static(&[0x00]),
+                gas_limit: 100_000,
+            };
+            let preview = context.preview_create_address(&inputs).unwrap();
+            let frame = context
+                .make_create_frame(CANCUN, &inputs)
+                .unwrap()
+                .into_frame()
+                .unwrap();
+            assert_eq!(frame.created_address(), Some(preview));
+        }
+        assert_eq!(
+            context.preview_create_address(&CreateInputs {
+                caller,
+                scheme: CreateScheme::Create,
+                value: U256::ZERO,
+                init_code: Bytes::new(),
+                gas_limit: 0,
+            }),
+            Ok(caller.create(7))
+        );
+    }
+
+    #[test]
+    fn preview_create2_address_uses_keccak_provider() {
+        // Not keccak, the created address differs from the one of the global keccak.
+        fn reversed_keccak256(bytes: &[u8]) -> B256 {
+            let mut hash = keccak256(bytes);
+            hash.reverse();
+            hash
+        }
+
+        let mut context = new_context(CANCUN);
+        context.env.cfg.keccak_provider = Some(KeccakProvider(reversed_keccak256));
+        let inputs = CreateInputs {
+            caller: Address::with_last_byte(0xca),
+            scheme: CreateScheme::Create2 {
+                salt: U256::from(7),
+            },
+            value: U256::ZERO,
+            init_code: Bytes::from_static(&[0x00]),
+            gas_limit: 100_000,
+        };
+        let preview = context.preview_create_address(&inputs).unwrap();
+        assert_ne!(preview, inputs.created_address(0));
+        let frame = context
+            .make_create_frame(CANCUN, &inputs)
+            .unwrap()
+            .into_frame()
+            .unwrap();
+        assert_eq!(frame.created_address(), Some(preview));
+    }
+
+    #[test]
+    fn preview_create_address_journals_nothing() {
+        let caller = Address::with_last_byte(0xca);
+        let mut context = new_context(PRAGUE_EOF);
+        context.db.insert_account_info(
+            caller,
+            AccountInfo {
+                nonce: 3,
+                ..Default::default()
+            },
+        );
+        context.env.tx_mut().caller = caller;
+
+        let inputs = EOFCreateInputs::new_tx(context.env.tx(), 100_000);
+        assert_eq!(
+            context.preview_eofcreate_address(&inputs),
+            Ok(caller.create(3))
+        );
+        context.env.tx_mut().nonce = Some(9);
+        assert_eq!(
+            context.preview_eofcreate_address(&inputs),
+            Ok(caller.create(9))
+        );
+
+        let created_address = Address::with_last_byte(0xcc);
+        let inputs = EOFCreateInputs::new_opcode(
+            caller,
+            created_address,
+            U256::ZERO,
+            Eof::default(),
+            100_000,
+            Bytes::new(),
+        );
+        assert_eq!(
+            context.preview_eofcreate_address(&inputs),
+            Ok(created_address)
+        );
+
+        assert!(context.journaled_state.state.is_empty());
+        assert!(context
+            .journaled_state
+            .journal
+            .iter()
+            .all(|entries| entries.is_empty()));
+    }
+
+    #[test]
+    fn eofcreate_tx_initcode_size_limit() {
+        let mut context = new_context(PRAGUE_EOF);
+        let mut initdata = vec![0x00; context.env.cfg.max_initcode_size() + 1];
+        initdata[..2].copy_from_slice(&EOF_MAGIC_BYTES);
+        context.env.tx_mut().data = initdata.into();
+
+        let inputs = EOFCreateInputs::new_tx(context.env.tx(), 100_000);
+        let result = context
+            .make_eofcreate_frame(PRAGUE_EOF, &inputs)
+            .unwrap()
+            .into_result()
+            .unwrap();
+        assert_eq!(
+            result.interpreter_result().result,
+            InstructionResult::CreateInitCodeSizeLimit
+        );
+    }
+
+    #[test]
+    fn eofcreate_tx_records_truncated_initcode() {
+        let mut context = new_context(PRAGUE_EOF);
+        // data section declares 2 bytes, only 1 is present.
+        context.env.tx_mut().data = bytes!("ef000101000402000100010400020000800000fe01");
+
+        let inputs = EOFCreateInputs::new_tx(context.env.tx(), 100_000);
+        let result = context
+            .make_eofcreate_frame(PRAGUE_EOF, &inputs)
+            .unwrap()
+            .into_result()
+            .unwrap();
+        assert_eq!(
+            result.interpreter_result().result,
+            InstructionResult::InvalidEOFInitCode
+        );
+        assert_eq!(
+            context.eof_initcode_error,
+            Some(EofDecodeError::TruncatedSection {
+                section: EofSection::Data,
+                declared: 2,
+                available: 1,
+            })
+        );
+    }
+
+    /// Database that does not return code with the account and counts code fetches.
+    struct CodeCountingDB {
+        db: CacheDB<EmptyDB>,
+        code_fetches: usize,
+    }
+
+    impl Database for CodeCountingDB {
+        type Error = core::convert::Infallible;
+
+        fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            Ok(self.db.basic(address)?.map(AccountInfo::without_code))
+        }
+
+        fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+            self.code_fetches += 1;
+            self.db.code_by_hash(code_hash)
+        }
+
+        fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+            self.db.storage(address, index)
+        }
+
+        fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+            self.db.block_hash(number)
+        }
+    }
+
+    /// Records the number of code fetches when the first call starts.
+    #[derive(Default)]
+    struct FirstCallFetches(Option<usize>);
+
+    impl Inspector<CodeCountingDB> for FirstCallFetches {
+        fn call(
+            &mut self,
+            context: &mut EvmContext<CodeCountingDB>,
+            _inputs: &mut CallInputs,
+        ) -> Option<CallOutcome> {
+            self.0.get_or_insert(context.db.code_fetches);
+            None
+        }
+    }
+
+    /// Calls `CALLEE` from `CALLER_CONTRACT`, both are in the access list.
+    /// Returns gas used, code fetches before the first call and all code fetches.
+    fn run_access_listed_calls(preload_access_list_code: bool) -> (u64, usize, usize) {
+        const CALLER_CONTRACT: Address = address!("c000000000000000000000000000000000000001");
+        const CALLEE: Address = address!("c000000000000000000000000000000000000002");
+
+        // CALL(GAS, CALLEE, 0, 0, 0, 0, 0)
+        let mut code = vec![
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73,
+        ];
+        code.extend_from_slice(CALLEE.as_slice());
+        code.extend_from_slice(&[0x5a, 0xf1, 0x00]);
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        for (address, code) in [(CALLER_CONTRACT, code), (CALLEE, vec![0x00])] {
+            db.insert_account_info(
+                address,
+                AccountInfo::from_bytecode(Bytecode::new_raw(code.into())),
+            );
+        }
+
+        let mut evm = Evm::builder()
+            .with_db(CodeCountingDB {
+                db,
+                code_fetches: 0,
+            })
+            .with_external_context(crate::test_utils::AssertingInspector::new(
+                FirstCallFetches::default(),
+            ))
+            .modify_cfg_env(|cfg| cfg.preload_access_list_code = preload_access_list_code)
+            .modify_tx_env(|tx| {
+                tx.transact_to = Some(TxKind::Call(CALLER_CONTRACT));
+                tx.gas_limit = 100_000;
+                tx.access_list = [CALLER_CONTRACT, CALLEE]
+                    .into_iter()
+                    .map(|address| AccessListItem {
+                        address,
+                        storage_keys: Vec::new(),
+                    })
+                    .collect();
+            })
+            .append_handler_register(crate::inspector_handle_register)
+            .build();
+        let result = evm.transact().unwrap().result;
+        assert!(result.is_success());
+
+        let context = evm.into_context();
+        (
+            result.gas_used(),
+            context.external.into_inner().0.unwrap(),
+            context.evm.inner.db.code_fetches,
+        )
+    }
+
+    #[test]
+    fn preload_access_list_code() {
+        let (gas_used, before_call, total) = run_access_listed_calls(false);
+        assert_eq!(before_call, 0);
+        assert_eq!(total, 2);
+
+        let (preloaded_gas_used, before_call, total) = run_access_listed_calls(true);
+        assert_eq!(before_call, 2);
+        assert_eq!(total, 2);
+        assert_eq!(preloaded_gas_used, gas_used);
+    }
+
+    std::thread_local! {
+        static KECCAK_PREIMAGES: core::cell::RefCell<Vec<Bytes>> = const {
+            core::cell::RefCell::new(Vec::new())
+        };
+    }
+
+    fn recording_keccak256(bytes: &[u8]) -> B256 {
+        KECCAK_PREIMAGES.with_borrow_mut(|preimages| preimages.push(Bytes::copy_from_slice(bytes)));
+        keccak256(bytes)
+    }
+
+    #[test]
+    fn keccak_provider_create2() {
+        // PUSH1 0x01, PUSH1 0x00, RETURN: deploys a single STOP.
+        let init_code = [0x60, 0x01, 0x60, 0x00, 0xf3];
+        // MSTORE(0, init_code), CREATE2(0, 27, 5, 42), STOP
+        let mut code = vec![0x64];
+        code.extend_from_slice(&init_code);
+        code.extend_from_slice(&[
+            0x60, 0x00, 0x52, 0x60, 0x2a, 0x60, 0x05, 0x60, 0x1b, 0x60, 0x00, 0xf5, 0x00,
+        ]);
+
+        let mut evm = contract_evm(code)
+            .modify_cfg_env(|cfg| cfg.keccak_provider = Some(KeccakProvider(recording_keccak256)))
+            .build();
+        KECCAK_PREIMAGES.take();
+        let ResultAndState { result, state, .. } = evm.transact().unwrap();
+        result.expect_success();
+
+        let init_code_hash = keccak256(init_code);
+        let salt = U256::from(42).to_be_bytes::<32>();
+        let created = CONTRACT.create2(salt, init_code_hash);
+        let mut create2_preimage = vec![0xff];
+        create2_preimage.extend_from_slice(CONTRACT.as_slice());
+        create2_preimage.extend_from_slice(&salt);
+        create2_preimage.extend_from_slice(init_code_hash.as_slice());
+        assert_eq!(
+            KECCAK_PREIMAGES.take(),
+            [
+                Bytes::copy_from_slice(&init_code),
+                Bytes::from(create2_preimage),
+                Bytes::from_static(&[0x00]),
+            ]
+        );
+        assert_eq!(state[&created].info.code_hash, keccak256([0x00]));
+    }
+
+    #[test]
+    fn code_deposit_over_raised_code_size_limit() {
+        // RETURN(0, 0x7000), over the default code size limit.
+        let initcode = Bytes::from_static(&[0x61, 0x70, 0x00, 0x5f, 0xf3]);
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .modify_cfg_env(|cfg| {
+                cfg.limit_contract_code_size = Some(usize::MAX);
+                // 0x7000 * 2^63 wraps to zero, the deposit is free if the cost is not saturated.
+                cfg.gas_overrides = Some(GasScheduleOverrides {
+                    code_deposit: Some(1 << 63),
+                    ..Default::default()
+                });
+            })
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = Some(TxKind::Create);
+                tx.data = initcode;
+                tx.gas_limit = 1_000_000;
+            })
+            .build();
+
+        let result = evm.transact().unwrap().result;
+        assert_eq!(
+            result,
+            ExecutionResult::Halt {
+                reason: HaltReason::OutOfGas(OutOfGasError::Basic),
+                gas_used: 1_000_000,
+            }
+        );
+    }
+
+    #[test]
+    fn create_with_max_nonce_halts() {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            CALLER,
+            AccountInfo {
+                nonce: u64::MAX,
+                ..Default::default()
+            },
+        );
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = Some(TxKind::Create);
+                tx.gas_limit = 100_000;
+            })
+            .build();
+
+        let ResultAndState { result, state, .. } = evm.transact().unwrap();
+        assert_eq!(
+            result,
+            ExecutionResult::Halt {
+                reason: HaltReason::NonceOverflow,
+                gas_used: 100_000,
+            }
+        );
+        assert_eq!(state[&CALLER].info.nonce, u64::MAX);
+    }
+
+    #[test]
+    fn create_opcode_with_max_nonce_fails() {
+        // SSTORE(0, CREATE(0, 0, 0)), STOP
+        let code = Bytes::from_static(&[0x5f, 0x5f, 0x5f, 0xf0, 0x5f, 0x55, 0x00]);
+        let mut evm = contract_evm(code)
+            .modify_db(|db| {
+                db.accounts.get_mut(&CONTRACT).unwrap().info.nonce = u64::MAX;
+                db.insert_account_storage(CONTRACT, U256::ZERO, U256::from(1))
+                    .unwrap();
+            })
+            .modify_tx_env(|tx| tx.gas_limit = 1_000_000)
+            .build();
+
+        let ResultAndState { result, state, .. } = evm.transact().unwrap();
+        let (_, _, gas_used) = result.expect_success();
+        // Gas forwarded to the failed create is returned.
+        assert!(gas_used < 100_000, "gas used: {gas_used}");
+        let contract = &state[&CONTRACT];
+        assert_eq!(contract.info.nonce, u64::MAX);
+        assert_eq!(contract.storage[&U256::ZERO].present_value, U256::ZERO);
+    }
+
+    #[cfg(feature = "eof-txcreate")]
+    #[test]
+    fn txcreate_deploys_initcode_of_tx() {
+        use crate::primitives::{
+            eof::{EofBody, TypesSection},
+            InvalidTransaction,
+        };
+
+        const DEPLOYER: Address = address!("2000000000000000000000000000000000000002");
+
+        // STOP
+        let container = EofBody {
+            types_section: vec![TypesSection::new(0, 0x80, 0)],
+            code_section: vec![Bytes::from_static(&[0x00])],
+            container_section: vec![],
+            data_section: Bytes::new(),
+            is_data_filled: true,
+        }
+        .into_eof()
+        .raw;
+        // PUSH0, PUSH0, RETURNCONTRACT(0) of the container, with `data`.
+        let initcode = |data: &'static [u8]| {
+            EofBody {
+                types_section: vec![TypesSection::new(0, 0x80, 2)],
+                code_section: vec![Bytes::from_static(&[0x5f, 0x5f, 0xee, 0x00])],
+                container_section: vec![container.clone()],
+                data_section: Bytes::from_static(data),
+                is_data_filled: true,
+            }
+            .into_eof()
+            .raw
+        };
+        let hash = keccak256(initcode(&[]));
+
+        // TXCREATE(hash, 0, 0, 0, 0), SSTORE(0, address), STOP
+        let mut code = vec![0x5f, 0x5f, 0x5f, 0x5f, 0x7f];
+        code.extend_from_slice(hash.as_slice());
+        code.extend_from_slice(&[0xed, 0x5f, 0x55, 0x00]);
+        let deployer = EofBody {
+            types_section: vec![TypesSection::new(0, 0x80, 5)],
+            code_section: vec![code.into()],
+            container_section: vec![],
+            data_section: Bytes::new(),
+            is_data_filled: true,
+        }
+        .into_eof();
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            DEPLOYER,
+            AccountInfo::from_bytecode(Bytecode::Eof(Arc::new(deployer))),
+        );
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(SpecId::PRAGUE_EOF)
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = Some(TxKind::Call(DEPLOYER));
+                tx.gas_limit = 1_000_000;
+                tx.initcodes = vec![initcode(&[])];
+            })
+            .build();
+
+        let ResultAndState { result, state, .. } = evm.transact().unwrap();
+        result.expect_success();
+        let created = DEPLOYER.create2(B256::ZERO, hash);
+        assert_eq!(
+            state[&DEPLOYER].storage[&U256::ZERO].present_value,
+            U256::from_be_slice(created.as_slice())
+        );
+        assert_eq!(state[&created].info.code_hash, keccak256(&container));
+
+        // missing initcode pushes zero.
+        evm.tx_mut().initcodes = vec![initcode(&[0x01])];
+        let ResultAndState { result, state, .. } = evm.transact().unwrap();
+        result.expect_success();
+        assert_eq!(
+            state[&DEPLOYER].storage[&U256::ZERO].present_value,
+            U256::ZERO
+        );
+        assert!(!state.contains_key(&created));
+
+        // initcodes must be valid EOF containers.
+        evm.tx_mut().initcodes = vec![Bytes::from_static(&[0xef, 0x00, 0x01])];
+        assert!(matches!(
+            evm.transact(),
+            Err(EVMError::Transaction(
+                InvalidTransaction::InvalidEofInitcode
+            ))
+        ));
+    }
+
+    #[test]
+    fn max_frame_gas_returns_excess_to_parent() {
+        const CALLEE: Address = address!("3000000000000000000000000000000000000003");
+        // CALL(10_000_000, CALLEE, 0, 0, 0, 0, 32), POP, MSTORE(32, GAS), RETURN(0, 64)
+        let mut code = vec![0x60, 0x20, 0x5f, 0x5f, 0x5f, 0x5f, 0x73];
+        code.extend_from_slice(CALLEE.as_slice());
+        code.extend_from_slice(&[
+            0x62, 0x98, 0x96, 0x80, 0xf1, 0x50, 0x5a, 0x60, 0x20, 0x52, 0x60, 0x40, 0x5f, 0xf3,
+        ]);
+
+        let mut evm = contract_evm(code)
+            .modify_db(|db| {
+                // MSTORE(0, GAS), RETURN(0, 32)
+                db.insert_account_info(
+                    CALLEE,
+                    AccountInfo::from_bytecode(Bytecode::new_raw(bytes!("5a5f5260205ff3"))),
+                );
+            })
+            .modify_tx_env(|tx| {
+                tx.gas_limit = 20_000_000;
+                tx.gas_price = U256::ZERO;
+            })
+            .build();
+
+        // Returns gas left in the callee and in the caller after the call, and gas used.
+        fn run(evm: &mut Evm<'_, (), InMemoryDB>) -> (U256, U256, u64) {
+            let result = evm.transact().unwrap().result;
+            let output = result.output().unwrap();
+            (
+                U256::from_be_slice(&output[..32]),
+                U256::from_be_slice(&output[32..]),
+                result.gas_used(),
+            )
+        }
+
+        let (callee_gas, caller_gas, gas_used) = run(&mut evm);
+        // GAS costs 2.
+        assert_eq!(callee_gas, U256::from(10_000_000 - 2));
+
+        evm.cfg_mut().max_frame_gas = Some(1_000_000);
+        let (capped_callee_gas, capped_caller_gas, capped_gas_used) = run(&mut evm);
+        assert_eq!(capped_callee_gas, U256::from(1_000_000 - 2));
+        // Gas above the cap is returned to the caller.
+        assert_eq!(capped_caller_gas, caller_gas);
+        assert_eq!(capped_gas_used, gas_used);
+
+        // First frame is not capped.
+        evm.cfg_mut().max_frame_gas = Some(1_000);
+        let (_, capped_caller_gas, capped_gas_used) = run(&mut evm);
+        assert_eq!(capped_caller_gas, caller_gas);
+        assert_eq!(capped_gas_used, gas_used);
+    }
+
+    #[test]
+    fn lazy_analysis_matches_analysed_execution() {
+        // PUSH1 4, JUMP, INVALID, JUMPDEST, SSTORE(0, 1)
+        let runtime = bytes!("600456fe5b60015f5500");
+        // CODECOPY(0, 10, 10), RETURN(0, 10) followed by the runtime code.
+        let initcode: Bytes = [bytes!("600a600a5f39600a5ff3"), runtime.clone()]
+            .concat()
+            .into();
+
+        let run = |kind: AnalysisKind| {
+            let mut evm = Evm::builder()
+                .with_db(CacheDB::new(EmptyDB::default()))
+                .modify_cfg_env(|cfg| cfg.perf_analyse_created_bytecodes = kind)
+                .modify_tx_env(|tx| {
+                    tx.caller = CALLER;
+                    tx.transact_to = Some(TxKind::Create);
+                    tx.data = initcode.clone();
+                    tx.gas_limit = 100_000;
+                })
+                .build();
+            let created = evm.transact_commit().unwrap();
+            let (_, output, _) = created.as_success().expect("contract is created");
+            let address = *output.address().unwrap();
+            let code_hash = evm.db_mut().load_account(address).unwrap().info.code_hash;
+            let code = evm.db_mut().code_by_hash(code_hash).unwrap();
+
+            evm.tx_mut().transact_to = Some(TxKind::Call(address));
+            evm.tx_mut().data = Bytes::new();
+            let called = evm.transact().unwrap();
+            (
+                created,
+                code,
+                called.result,
+                called.state[&address].storage.clone(),
+            )
+        };
+
+        let (created, code, called, storage) = run(AnalysisKind::Analyse);
+        assert!(matches!(code, Bytecode::LegacyAnalyzed(_)));
+        assert!(called.is_success());
+        assert_eq!(storage[&U256::ZERO].present_value, U256::from(1));
+
+        let (lazy_created, lazy_code, lazy_called, lazy_storage) = run(AnalysisKind::Lazy);
+        assert_eq!(lazy_code, Bytecode::new_raw(runtime));
+        assert_eq!(lazy_created, created);
+        assert_eq!(lazy_called, called);
+        assert_eq!(lazy_storage, storage);
+    }
+}
@@ -8,18 +8,24 @@ mod alloydb;
 pub mod emptydb;
 #[cfg(feature = "ethersdb")]
 mod ethersdb;
+pub mod fork_db;
 pub mod in_memory_db;
+pub mod state_overlay;
 pub mod states;
 
 pub use crate::primitives::db::*;
 #[cfg(feature = "alloydb")]
 pub use alloydb::AlloyDB;
-pub use emptydb::{EmptyDB, EmptyDBTyped};
+pub use emptydb::{
+    BlockHashProvider, EmptyDB, EmptyDBTyped, ResolveBlockHash, StrictBlockHashes, UnknownBlockHash,
+};
 #[cfg(feature = "ethersdb")]
 pub use ethersdb::EthersDB;
+pub use fork_db::{ForkDB, ForkableDatabase};
 pub use in_memory_db::*;
+pub use state_overlay::{StateKey, StateOverlay};
 pub use states::{
-    AccountRevert, AccountStatus, BundleAccount, BundleState, CacheState, DBBox,
-    OriginalValuesKnown, PlainAccount, RevertToSlot, State, StateBuilder, StateDBBox,
-    StorageWithOriginalValues, TransitionAccount, TransitionState,
+    AccountRevert, AccountStatus, BundleAccount, BundleState, CacheState, DBBox, HashedBundleState,
+    HashedKeyCache, HashedStorage, OriginalValuesKnown, PlainAccount, RevertToSlot, State,
+    StateBuilder, StateDBBox, StorageWithOriginalValues, TransitionAccount, TransitionState,
 };
@@ -1,59 +1,173 @@
-use core::{convert::Infallible, fmt, marker::PhantomData};
+use core::{convert::Infallible, fmt, hash::BuildHasher, marker::PhantomData};
 use revm_interpreter::primitives::{
     db::{Database, DatabaseRef},
-    keccak256, AccountInfo, Address, Bytecode, B256, U256,
+    keccak256, AccountInfo, Address, Bytecode, HashMap, B256, U256,
 };
 use std::string::ToString;
 
+/// Source of the block hashes of [`EmptyDB`] and [`CacheDB`](crate::db::CacheDB).
+///
+/// Implemented for maps of block numbers to hashes and for closures, that can fetch the
+/// hashes lazily.
+pub trait BlockHashProvider {
+    /// Returns the hash of the block `number`, `None` if it is not known.
+    fn block_hash(&self, number: u64) -> Option<B256>;
+}
+
+/// Knows no block hashes.
+impl BlockHashProvider for () {
+    #[inline]
+    fn block_hash(&self, _number: u64) -> Option<B256> {
+        None
+    }
+}
+
+impl<S: BuildHasher> BlockHashProvider for HashMap<u64, B256, S> {
+    #[inline]
+    fn block_hash(&self, number: u64) -> Option<B256> {
+        self.get(&number).copied()
+    }
+}
+
+impl<F: Fn(u64) -> Option<B256>> BlockHashProvider for F {
+    #[inline]
+    fn block_hash(&self, number: u64) -> Option<B256> {
+        self(number)
+    }
+}
+
+/// Block hashes of a strict [`EmptyDBTyped`], see [`EmptyDBTyped::strict`].
+///
+/// Strictness is part of the type, so it is kept when the database is cloned or serialized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StrictBlockHashes<H>(pub H);
+
+/// Resolves the block hashes of [`EmptyDBTyped`].
+///
+/// Hashes unknown to a [`BlockHashProvider`] are `keccak256(number.to_string())`, hashes
+/// unknown to [`StrictBlockHashes`] are an [`UnknownBlockHash`] error.
+pub trait ResolveBlockHash<E> {
+    /// Returns the hash of the block `number`.
+    fn resolve_block_hash(&self, number: u64) -> Result<B256, E>;
+}
+
+impl<E, H: BlockHashProvider> ResolveBlockHash<E> for H {
+    #[inline]
+    fn resolve_block_hash(&self, number: u64) -> Result<B256, E> {
+        Ok(self
+            .block_hash(number)
+            .unwrap_or_else(|| keccak256(number.to_string().as_bytes())))
+    }
+}
+
+impl<E: From<UnknownBlockHash>, H: BlockHashProvider> ResolveBlockHash<E> for StrictBlockHashes<H> {
+    #[inline]
+    fn resolve_block_hash(&self, number: u64) -> Result<B256, E> {
+        self.0
+            .block_hash(number)
+            .ok_or_else(|| UnknownBlockHash(number).into())
+    }
+}
+
+/// Error of a strict [`EmptyDBTyped`] queried for a block hash its provider does not know.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct UnknownBlockHash(pub u64);
+
+impl fmt::Display for UnknownBlockHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown hash of block {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownBlockHash {}
+
 /// An empty database that always returns default values when queried.
 pub type EmptyDB = EmptyDBTyped<Infallible>;
 
 /// An empty database that always returns default values when queried.
 ///
-/// This is generic over a type which is used as the database error type.
+/// This is generic over a type which is used as the database error type and over the
+/// [`BlockHashProvider`] of the block hashes, see [`ResolveBlockHash`] for the hashes the
+/// provider does not know.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct EmptyDBTyped<E> {
+pub struct EmptyDBTyped<E, H = ()> {
+    block_hashes: H,
     _phantom: PhantomData<E>,
 }
 
-// Don't derive traits, because the type parameter is unused.
-impl<E> Clone for EmptyDBTyped<E> {
+// Don't derive traits, because the error type parameter is unused.
+impl<E, H: Clone> Clone for EmptyDBTyped<E, H> {
     fn clone(&self) -> Self {
-        *self
+        Self {
+            block_hashes: self.block_hashes.clone(),
+            _phantom: PhantomData,
+        }
     }
 }
 
-impl<E> Copy for EmptyDBTyped<E> {}
+impl<E, H: Copy> Copy for EmptyDBTyped<E, H> {}
 
-impl<E> Default for EmptyDBTyped<E> {
+impl<E, H: Default> Default for EmptyDBTyped<E, H> {
     fn default() -> Self {
-        Self::new()
+        Self {
+            block_hashes: H::default(),
+            _phantom: PhantomData,
+        }
     }
 }
 
-impl<E> fmt::Debug for EmptyDBTyped<E> {
+impl<E, H> fmt::Debug for EmptyDBTyped<E, H> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("EmptyDB").finish_non_exhaustive()
     }
 }
 
-impl<E> PartialEq for EmptyDBTyped<E> {
-    fn eq(&self, _: &Self) -> bool {
-        true
+impl<E, H: PartialEq> PartialEq for EmptyDBTyped<E, H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.block_hashes == other.block_hashes
     }
 }
 
-impl<E> Eq for EmptyDBTyped<E> {}
+impl<E, H: Eq> Eq for EmptyDBTyped<E, H> {}
 
 impl<E> EmptyDBTyped<E> {
     pub fn new() -> Self {
-        Self {
+        Self::default()
+    }
+}
+
+impl<E, H> EmptyDBTyped<E, H> {
+    /// Returns the database with the block hashes of the `provider`, for example a map of
+    /// block numbers to hashes.
+    pub fn with_block_hashes<P: BlockHashProvider>(self, provider: P) -> EmptyDBTyped<E, P> {
+        EmptyDBTyped {
+            block_hashes: provider,
             _phantom: PhantomData,
         }
     }
+
+    /// Returns the database that errors with [`UnknownBlockHash`] on block hashes unknown to
+    /// its provider instead of fabricating them.
+    pub fn strict(self) -> EmptyDBTyped<E, StrictBlockHashes<H>>
+    where
+        E: From<UnknownBlockHash>,
+        H: BlockHashProvider,
+    {
+        EmptyDBTyped {
+            block_hashes: StrictBlockHashes(self.block_hashes),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the block hash provider.
+    pub fn block_hashes(&self) -> &H {
+        &self.block_hashes
+    }
 }
 
-impl<E> Database for EmptyDBTyped<E> {
+impl<E, H: ResolveBlockHash<E>> Database for EmptyDBTyped<E, H> {
     type Error = E;
 
     #[inline]
@@ -77,7 +191,7 @@ impl<E> Database for EmptyDBTyped<E> {
     }
 }
 
-impl<E> DatabaseRef for EmptyDBTyped<E> {
+impl<E, H: ResolveBlockHash<E>> DatabaseRef for EmptyDBTyped<E, H> {
     type Error = E;
 
     #[inline]
@@ -97,14 +211,14 @@ impl<E> DatabaseRef for EmptyDBTyped<E> {
 
     #[inline]
     fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
-        Ok(keccak256(number.to_string().as_bytes()))
+        self.block_hashes.resolve_block_hash(number)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::primitives::b256;
+    use crate::primitives::{b256, HashMap};
 
     #[test]
     fn conform_block_hash_calculation() {
@@ -130,4 +244,44 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn block_hashes_map() {
+        let hash = b256!("88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6");
+        let mut block_hashes: HashMap<u64, B256> = HashMap::default();
+        block_hashes.insert(1u64, hash);
+
+        let db = EmptyDB::new().with_block_hashes(block_hashes);
+        assert_eq!(db.block_hash_ref(1), Ok(hash));
+        // Unknown hash is fabricated.
+        assert_eq!(db.block_hash_ref(0), EmptyDB::new().block_hash_ref(0));
+    }
+
+    #[test]
+    fn strict_block_hashes() {
+        let hash = b256!("88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6");
+        let db = EmptyDBTyped::<UnknownBlockHash>::new()
+            .with_block_hashes(move |number| (number == 1).then_some(hash))
+            .strict();
+        assert_eq!(db.block_hash_ref(1), Ok(hash));
+        assert_eq!(db.block_hash_ref(2), Err(UnknownBlockHash(2)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn strict_block_hashes_survive_serde() {
+        let hash = b256!("88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6");
+        let mut block_hashes: HashMap<u64, B256> = HashMap::default();
+        block_hashes.insert(1u64, hash);
+        let db = EmptyDBTyped::<UnknownBlockHash>::new()
+            .with_block_hashes(block_hashes)
+            .strict();
+
+        let serialized = serde_json::to_string(&db).unwrap();
+        let deserialized: EmptyDBTyped<UnknownBlockHash, StrictBlockHashes<HashMap<u64, B256>>> =
+            serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, db);
+        assert_eq!(deserialized.block_hash_ref(1), Ok(hash));
+        assert_eq!(deserialized.block_hash_ref(2), Err(UnknownBlockHash(2)));
+    }
 }
@@ -0,0 +1,581 @@
+use super::{AccountState, DatabaseCommit, DatabaseRef, DbAccount, StateOverlay};
+use crate::primitives::{
+    hash_map::Entry, Account, AccountInfo, Address, Bytecode, HashMap, HashSet, B256, KECCAK_EMPTY,
+    U256,
+};
+use crate::Database;
+use std::vec::Vec;
+
+/// A copy-on-write [Database] that layers local changes on top of a remote [DatabaseRef].
+///
+/// This is the "fork chain at block N and execute local transactions" pattern in one type:
+/// * Reads that are not served by local changes fall through to the remote database, which is
+///   expected to be pinned to [`ForkDB::block_number`]. Remote reads are cached.
+/// * All writes ([DatabaseCommit::commit], `insert_*` functions) are kept locally and are never
+///   written to the remote database.
+/// * Accounts that were deleted locally (e.g. selfdestructed) are not fetched from remote again,
+///   their info and storage stay masked until they are recreated.
+///
+/// [`ForkDB::roll_fork`] moves the fork to another block, it invalidates the data cached from
+/// the remote database and keeps all local changes.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForkDB<ExtDB> {
+    /// Accounts changed locally. Entries here always take precedence over the remote database.
+    pub accounts: HashMap<Address, DbAccount>,
+    /// Account info cached from the remote database at the pinned block.
+    pub remote_accounts: HashMap<Address, Option<AccountInfo>>,
+    /// Storage slots cached from the remote database at the pinned block.
+    pub remote_storage: HashMap<Address, HashMap<U256, U256>>,
+    /// Contracts by their code hash, both local and remote.
+    ///
+    /// Code is identified by its hash, so entries stay valid across [`ForkDB::roll_fork`].
+    pub contracts: HashMap<B256, Bytecode>,
+    /// Block hashes cached from the remote database.
+    pub block_hashes: HashMap<u64, B256>,
+    /// Accounts in [`ForkDB::accounts`] whose info is copied from the remote database, their info
+    /// is read again by [`ForkDB::roll_fork`].
+    remote_infos: HashSet<Address>,
+    /// Block number the fork is pinned to.
+    block_number: u64,
+    /// The remote database ([DatabaseRef]) that is used to load data.
+    ///
+    /// Note: this is read-only, data is never written to this database.
+    remote: ExtDB,
+}
+
+impl<ExtDB> ForkDB<ExtDB> {
+    /// Creates a new fork of the `remote` database pinned to the `block_number`.
+    pub fn new(remote: ExtDB, block_number: u64) -> Self {
+        let mut contracts = HashMap::default();
+        contracts.insert(KECCAK_EMPTY, Bytecode::default());
+        contracts.insert(B256::ZERO, Bytecode::default());
+        Self {
+            accounts: HashMap::default(),
+            remote_accounts: HashMap::default(),
+            remote_storage: HashMap::default(),
+            contracts,
+            block_hashes: HashMap::default(),
+            remote_infos: HashSet::default(),
+            block_number,
+            remote,
+        }
+    }
+
+    /// Returns the block number the fork is pinned to.
+    #[inline]
+    pub fn block_number(&self) -> u64 {
+        self.block_number
+    }
+
+    /// Returns a reference to the remote database.
+    #[inline]
+    pub fn remote(&self) -> &ExtDB {
+        &self.remote
+    }
+
+    /// Returns a mutable reference to the remote database.
+    ///
+    /// Can be used to re-pin the remote database (e.g. `AlloyDB::set_block_number`)
+    /// before calling [`ForkDB::roll_fork`].
+    #[inline]
+    pub fn remote_mut(&mut self) -> &mut ExtDB {
+        &mut self.remote
+    }
+
+    /// Consumes the fork and returns the remote database.
+    #[inline]
+    pub fn into_remote(self) -> ExtDB {
+        self.remote
+    }
+
+    /// Inserts the account's code into the contracts map.
+    ///
+    /// Note: This will not insert into the remote database.
+    pub fn insert_contract(&mut self, account: &mut AccountInfo) {
+        if let Some(code) = &account.code {
+            if !code.is_empty() {
+                if account.code_hash == KECCAK_EMPTY {
+                    account.code_hash = code.hash_slow();
+                }
+                self.contracts
+                    .entry(account.code_hash)
+                    .or_insert_with(|| code.clone());
+            }
+        }
+        if account.code_hash == B256::ZERO {
+            account.code_hash = KECCAK_EMPTY;
+        }
+    }
+
+    /// Insert account info locally but not override storage.
+    pub fn insert_account_info(&mut self, address: Address, mut info: AccountInfo) {
+        self.insert_contract(&mut info);
+        self.remote_infos.remove(&address);
+        let account = self.accounts.entry(address).or_default();
+        if account.account_state == AccountState::NotExisting {
+            // recreated accounts must not expose remote storage.
+            account.account_state = AccountState::StorageCleared;
+        }
+        account.info = info;
+    }
+}
+
+impl<ExtDB: DatabaseRef> ForkDB<ExtDB> {
+    /// Moves the fork to the `block_number`.
+    ///
+    /// All data cached from the remote database is dropped and the info of accounts that was
+    /// copied from the remote database by [`ForkDB::load_account`] is read again. Local changes
+    /// are kept.
+    pub fn roll_fork(&mut self, block_number: u64) -> Result<(), ExtDB::Error> {
+        self.block_number = block_number;
+        self.remote_accounts.clear();
+        self.remote_storage.clear();
+        self.block_hashes.clear();
+        for address in &self.remote_infos {
+            let info = self.remote.basic_ref(*address)?;
+            let account = self.accounts.get_mut(address).expect("account is loaded");
+            account.account_state = match info {
+                Some(_) => AccountState::None,
+                None => AccountState::NotExisting,
+            };
+            account.info = info.unwrap_or_default();
+        }
+        Ok(())
+    }
+
+    /// Returns the local account for the given address.
+    ///
+    /// If the account was not changed locally, its info will be copied from the remote database.
+    pub fn load_account(&mut self, address: Address) -> Result<&mut DbAccount, ExtDB::Error> {
+        if !self.accounts.contains_key(&address) {
+            let info = self.remote_basic(address)?;
+            self.accounts.insert(address, info.into());
+            self.remote_infos.insert(address);
+        }
+        Ok(self.accounts.get_mut(&address).expect("account is loaded"))
+    }
+
+    /// Insert account storage locally without overriding account info.
+    pub fn insert_account_storage(
+        &mut self,
+        address: Address,
+        slot: U256,
+        value: U256,
+    ) -> Result<(), ExtDB::Error> {
+        let account = self.load_account(address)?;
+        account.storage.insert(slot, value);
+        Ok(())
+    }
+
+    /// Returns remote account info, cached.
+    fn remote_basic(&mut self, address: Address) -> Result<Option<AccountInfo>, ExtDB::Error> {
+        match self.remote_accounts.entry(address) {
+            Entry::Occupied(entry) => Ok(entry.get().clone()),
+            Entry::Vacant(entry) => Ok(entry.insert(self.remote.basic_ref(address)?).clone()),
+        }
+    }
+
+    /// Returns remote storage slot, cached.
+    fn remote_storage(&mut self, address: Address, index: U256) -> Result<U256, ExtDB::Error> {
+        match self.remote_storage.entry(address).or_default().entry(index) {
+            Entry::Occupied(entry) => Ok(*entry.get()),
+            Entry::Vacant(entry) => Ok(*entry.insert(self.remote.storage_ref(address, index)?)),
+        }
+    }
+}
+
+impl<ExtDB> DatabaseCommit for ForkDB<ExtDB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        for (address, mut account) in changes {
+            if !account.is_touched() {
+                continue;
+            }
+            if account.is_selfdestructed() {
+                self.remote_infos.remove(&address);
+                let db_account = self.accounts.entry(address).or_default();
+                db_account.storage.clear();
+                db_account.account_state = AccountState::NotExisting;
+                db_account.info = AccountInfo::default();
+                continue;
+            }
+            let is_newly_created = account.is_created();
+            self.insert_contract(&mut account.info);
+
+            // Only changes are written, values that were only read stay served by the remote
+            // database and are read again by `roll_fork`.
+            let info_changed = is_newly_created
+                || match (
+                    self.accounts.get(&address),
+                    self.remote_accounts.get(&address),
+                ) {
+                    (Some(db_account), _) => db_account.info != account.info,
+                    (None, Some(Some(remote))) => *remote != account.info,
+                    (None, Some(None)) => !account.info.is_empty(),
+                    (None, None) => true,
+                };
+            let storage: Vec<_> = account
+                .storage
+                .into_iter()
+                .filter(|(_, slot)| slot.is_changed())
+                .map(|(key, slot)| (key, slot.present_value()))
+                .collect();
+            if !info_changed && storage.is_empty() {
+                continue;
+            }
+
+            let db_account = match self.accounts.entry(address) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let remote = self.remote_accounts.get(&address).cloned().flatten();
+                    if !info_changed {
+                        self.remote_infos.insert(address);
+                    }
+                    entry.insert(remote.into())
+                }
+            };
+            if info_changed {
+                self.remote_infos.remove(&address);
+                db_account.info = account.info;
+            }
+
+            db_account.account_state = if is_newly_created {
+                db_account.storage.clear();
+                AccountState::StorageCleared
+            } else if matches!(
+                db_account.account_state,
+                AccountState::StorageCleared | AccountState::NotExisting
+            ) {
+                // Remote storage stays masked for cleared and deleted accounts.
+                AccountState::StorageCleared
+            } else {
+                AccountState::Touched
+            };
+            db_account.storage.extend(storage);
+        }
+    }
+}
+
+impl<ExtDB: DatabaseRef> Database for ForkDB<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        match self.accounts.get(&address) {
+            Some(account) => Ok(account.info()),
+            None => self.remote_basic(address),
+        }
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.contracts.entry(code_hash) {
+            Entry::Occupied(entry) => Ok(entry.get().clone()),
+            Entry::Vacant(entry) => Ok(entry
+                .insert(self.remote.code_by_hash_ref(code_hash)?)
+                .clone()),
+        }
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(account) = self.accounts.get(&address) {
+            if let Some(value) = account.storage.get(&index) {
+                return Ok(*value);
+            }
+            if matches!(
+                account.account_state,
+                AccountState::StorageCleared | AccountState::NotExisting
+            ) {
+                return Ok(U256::ZERO);
+            }
+        }
+        self.remote_storage(address, index)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        match self.block_hashes.entry(number) {
+            Entry::Occupied(entry) => Ok(*entry.get()),
+            Entry::Vacant(entry) => Ok(*entry.insert(self.remote.block_hash_ref(number)?)),
+        }
+    }
+}
+
+impl<ExtDB: DatabaseRef> DatabaseRef for ForkDB<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(account) = self.accounts.get(&address) {
+            return Ok(account.info());
+        }
+        match self.remote_accounts.get(&address) {
+            Some(info) => Ok(info.clone()),
+            None => self.remote.basic_ref(address),
+        }
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.contracts.get(&code_hash) {
+            Some(code) => Ok(code.clone()),
+            None => self.remote.code_by_hash_ref(code_hash),
+        }
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(account) = self.accounts.get(&address) {
+            if let Some(value) = account.storage.get(&index) {
+                return Ok(*value);
+            }
+            if matches!(
+                account.account_state,
+                AccountState::StorageCleared | AccountState::NotExisting
+            ) {
+                return Ok(U256::ZERO);
+            }
+        }
+        match self
+            .remote_storage
+            .get(&address)
+            .and_then(|storage| storage.get(&index))
+        {
+            Some(value) => Ok(*value),
+            None => self.remote.storage_ref(address, index),
+        }
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        match self.block_hashes.get(&number) {
+            Some(hash) => Ok(*hash),
+            None => self.remote.block_hash_ref(number),
+        }
+    }
+}
+
+/// A [Database] with a copy-on-write layer over a base [DatabaseRef].
+///
+/// Clones duplicate only the local changes, the base is shared when it is cheap to clone,
+/// e.g. an `Arc` or a reference. Used to fork the EVM, see [`Evm::fork`](crate::Evm::fork).
+pub trait ForkableDatabase: Database + Clone {}
+
+impl<ExtDB: DatabaseRef + Clone> ForkableDatabase for ForkDB<ExtDB> {}
+
+impl<DB: DatabaseRef + Clone> ForkableDatabase for StateOverlay<DB> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        primitives::{AccountStatus, EvmStorageSlot},
+    };
+
+    const FORK_BLOCK: u64 = 100;
+
+    /// Remote database with canned data: account `0x01` with balance 10 and slot 1 set to 7.
+    fn remote() -> CacheDB<EmptyDB> {
+        let mut remote = CacheDB::new(EmptyDB::default());
+        remote.insert_account_info(
+            Address::with_last_byte(1),
+            AccountInfo {
+                balance: U256::from(10),
+                nonce: 1,
+                ..Default::default()
+            },
+        );
+        remote
+            .insert_account_storage(Address::with_last_byte(1), U256::from(1), U256::from(7))
+            .unwrap();
+        remote
+    }
+
+    fn commit_account(
+        db: &mut ForkDB<CacheDB<EmptyDB>>,
+        address: Address,
+        info: AccountInfo,
+        storage: &[(U256, U256)],
+        status: AccountStatus,
+    ) {
+        let account = Account {
+            info,
+            storage: storage
+                .iter()
+                .map(|(key, value)| (*key, EvmStorageSlot::new_changed(U256::ZERO, *value)))
+                .collect(),
+            status: status | AccountStatus::Touched,
+        };
+        db.commit(HashMap::from_iter([(address, account)]));
+    }
+
+    #[test]
+    fn reads_fall_through_and_writes_stay_local() {
+        let address = Address::with_last_byte(1);
+        let mut db = ForkDB::new(remote(), FORK_BLOCK);
+
+        assert_eq!(db.basic(address).unwrap().unwrap().balance, U256::from(10));
+        assert_eq!(db.storage(address, U256::from(1)), Ok(U256::from(7)));
+
+        commit_account(
+            &mut db,
+            address,
+            AccountInfo {
+                balance: U256::from(20),
+                nonce: 2,
+                ..Default::default()
+            },
+            &[(U256::from(2), U256::from(9))],
+            AccountStatus::default(),
+        );
+
+        assert_eq!(db.basic(address).unwrap().unwrap().balance, U256::from(20));
+        assert_eq!(db.storage_ref(address, U256::from(2)), Ok(U256::from(9)));
+        // untouched slot is still served by the remote.
+        assert_eq!(db.storage(address, U256::from(1)), Ok(U256::from(7)));
+        // remote is not modified.
+        assert_eq!(
+            db.remote().basic_ref(address).unwrap().unwrap().balance,
+            U256::from(10)
+        );
+        assert_eq!(
+            db.remote().storage_ref(address, U256::from(2)),
+            Ok(U256::ZERO)
+        );
+    }
+
+    #[test]
+    fn deleted_account_is_masked() {
+        let address = Address::with_last_byte(1);
+        let mut db = ForkDB::new(remote(), FORK_BLOCK);
+
+        commit_account(
+            &mut db,
+            address,
+            AccountInfo::default(),
+            &[],
+            AccountStatus::SelfDestructed,
+        );
+
+        assert_eq!(db.basic(address), Ok(None));
+        assert_eq!(db.storage(address, U256::from(1)), Ok(U256::ZERO));
+        assert_eq!(db.basic_ref(address), Ok(None));
+        assert_eq!(db.storage_ref(address, U256::from(1)), Ok(U256::ZERO));
+
+        // rolling the fork does not bring the remote account back.
+        db.roll_fork(FORK_BLOCK + 1).unwrap();
+        assert_eq!(db.basic(address), Ok(None));
+
+        // recreating the account does not expose the remote storage.
+        commit_account(
+            &mut db,
+            address,
+            AccountInfo {
+                nonce: 1,
+                ..Default::default()
+            },
+            &[],
+            AccountStatus::default(),
+        );
+        assert_eq!(db.basic(address).unwrap().unwrap().nonce, 1);
+        assert_eq!(db.storage(address, U256::from(1)), Ok(U256::ZERO));
+    }
+
+    #[test]
+    fn roll_fork_invalidates_remote_cache() {
+        let remote_account = Address::with_last_byte(1);
+        let local_account = Address::with_last_byte(2);
+        let mut db = ForkDB::new(remote(), FORK_BLOCK);
+
+        // warm the remote cache.
+        assert_eq!(db.storage(remote_account, U256::from(1)), Ok(U256::from(7)));
+        db.insert_account_info(
+            local_account,
+            AccountInfo {
+                balance: U256::from(5),
+                ..Default::default()
+            },
+        );
+        db.insert_account_storage(local_account, U256::from(1), U256::from(3))
+            .unwrap();
+        // info of the remote account is copied next to the local slot.
+        db.insert_account_storage(remote_account, U256::from(2), U256::from(4))
+            .unwrap();
+
+        // remote state changes at the next block.
+        db.remote_mut()
+            .insert_account_storage(remote_account, U256::from(1), U256::from(8))
+            .unwrap();
+        db.remote_mut().insert_account_info(
+            remote_account,
+            AccountInfo {
+                balance: U256::from(11),
+                nonce: 2,
+                ..Default::default()
+            },
+        );
+        // cached values are still served until the fork is rolled.
+        assert_eq!(db.storage(remote_account, U256::from(1)), Ok(U256::from(7)));
+        assert_eq!(
+            db.basic(remote_account).unwrap().unwrap().balance,
+            U256::from(10)
+        );
+
+        db.roll_fork(FORK_BLOCK + 1).unwrap();
+        assert_eq!(db.block_number(), FORK_BLOCK + 1);
+        assert_eq!(db.storage(remote_account, U256::from(1)), Ok(U256::from(8)));
+        let info = db.basic(remote_account).unwrap().unwrap();
+        assert_eq!((info.balance, info.nonce), (U256::from(11), 2));
+        assert_eq!(db.storage(remote_account, U256::from(2)), Ok(U256::from(4)));
+        // local writes are kept.
+        assert_eq!(
+            db.basic(local_account).unwrap().unwrap().balance,
+            U256::from(5)
+        );
+        assert_eq!(db.storage(local_account, U256::from(1)), Ok(U256::from(3)));
+    }
+
+    #[test]
+    fn commit_keeps_read_values_remote() {
+        let address = Address::with_last_byte(1);
+        let mut db = ForkDB::new(remote(), FORK_BLOCK);
+
+        // the account and slot 1 are only read, slot 2 is written.
+        let info = db.basic(address).unwrap().unwrap();
+        let value = db.storage(address, U256::from(1)).unwrap();
+        let account = Account {
+            info,
+            storage: HashMap::from_iter([
+                (U256::from(1), EvmStorageSlot::new(value)),
+                (
+                    U256::from(2),
+                    EvmStorageSlot::new_changed(U256::ZERO, U256::from(9)),
+                ),
+            ]),
+            status: AccountStatus::Touched,
+        };
+        db.commit(HashMap::from_iter([(address, account)]));
+        assert!(!db.accounts[&address].storage.contains_key(&U256::from(1)));
+
+        db.remote_mut()
+            .insert_account_storage(address, U256::from(1), U256::from(8))
+            .unwrap();
+        db.remote_mut().insert_account_info(
+            address,
+            AccountInfo {
+                balance: U256::from(11),
+                nonce: 2,
+                ..Default::default()
+            },
+        );
+        db.roll_fork(FORK_BLOCK + 1).unwrap();
+
+        assert_eq!(db.storage(address, U256::from(1)), Ok(U256::from(8)));
+        assert_eq!(db.basic(address).unwrap().unwrap().balance, U256::from(11));
+        assert_eq!(db.storage(address, U256::from(2)), Ok(U256::from(9)));
+
+        // a touched account without changes is not written at all.
+        let other = Address::with_last_byte(3);
+        let account = Account {
+            info: db.basic(other).unwrap().unwrap_or_default(),
+            storage: HashMap::default(),
+            status: AccountStatus::Touched,
+        };
+        db.commit(HashMap::from_iter([(other, account)]));
+        assert!(!db.accounts.contains_key(&other));
+    }
+}
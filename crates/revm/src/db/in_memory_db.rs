@@ -1,10 +1,14 @@
-use super::{DatabaseCommit, DatabaseRef, EmptyDB};
+use super::{
+    BlockHashProvider, DatabaseCommit, DatabaseCommit2PC, DatabaseRef, EmptyDB, PreparedCommit,
+};
 use crate::primitives::{
     hash_map::Entry, Account, AccountInfo, Address, Bytecode, HashMap, Log, B256, KECCAK_EMPTY,
     U256,
 };
 use crate::Database;
 use core::convert::Infallible;
+#[cfg(any(test, feature = "test-utils"))]
+use std::collections::BTreeMap;
 use std::vec::Vec;
 
 /// A [Database] implementation that stores all state changes in memory.
@@ -17,11 +21,15 @@ pub type InMemoryDB = CacheDB<EmptyDB>;
 /// Accounts and code are stored in two separate maps, the `accounts` map maps addresses to [DbAccount],
 /// whereas contracts are identified by their code hash, and are stored in the `contracts` map.
 /// The [DbAccount] holds the code hash of the contract, which is used to look up the contract in the `contracts` map.
-#[derive(Debug, Clone)]
+///
+/// Block hashes missing in the cache are asked from the [BlockHashProvider] `H` first, see
+/// [CacheDB::with_block_hash_provider], the same providers are used by [EmptyDB].
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct CacheDB<ExtDB> {
+pub struct CacheDB<ExtDB, H = ()> {
     /// Account info where None means it is not existing. Not existing state is needed for Pre TANGERINE forks.
-    /// `code` is always `None`, and bytecode can be found in `contracts`.
+    /// `code` is always `None`, and bytecode can be found in `contracts`. See [CacheDB::shrink_code]
+    /// for caches that still hold the code inside the accounts.
     pub accounts: HashMap<Address, DbAccount>,
     /// Tracks all contracts by their code hash.
     pub contracts: HashMap<B256, Bytecode>,
@@ -29,6 +37,9 @@ pub struct CacheDB<ExtDB> {
     pub logs: Vec<Log>,
     /// All cached block hashes from the [DatabaseRef].
     pub block_hashes: HashMap<U256, B256>,
+    /// Provider of the block hashes missing in `block_hashes`, asked before the underlying
+    /// database. See [CacheDB::with_block_hash_provider].
+    pub block_hash_provider: H,
     /// The underlying database ([DatabaseRef]) that is used to load data.
     ///
     /// Note: this is read-only, data is never written to this database.
@@ -43,36 +54,53 @@ impl<ExtDB: Default> Default for CacheDB<ExtDB> {
 
 impl<ExtDB> CacheDB<ExtDB> {
     pub fn new(db: ExtDB) -> Self {
-        let mut contracts = HashMap::new();
+        let mut contracts = HashMap::default();
         contracts.insert(KECCAK_EMPTY, Bytecode::default());
         contracts.insert(B256::ZERO, Bytecode::default());
         Self {
-            accounts: HashMap::new(),
+            accounts: HashMap::default(),
             contracts,
             logs: Vec::default(),
-            block_hashes: HashMap::new(),
+            block_hashes: HashMap::default(),
+            block_hash_provider: (),
             db,
         }
     }
+}
+
+impl<ExtDB, H> CacheDB<ExtDB, H> {
+    /// Returns the database that takes the block hashes missing in the cache from the
+    /// `provider`, and from the underlying database only if the provider does not know them.
+    ///
+    /// Useful to supply real historical hashes in fork tests.
+    pub fn with_block_hash_provider<P: BlockHashProvider>(self, provider: P) -> CacheDB<ExtDB, P> {
+        CacheDB {
+            accounts: self.accounts,
+            contracts: self.contracts,
+            logs: self.logs,
+            block_hashes: self.block_hashes,
+            block_hash_provider: provider,
+            db: self.db,
+        }
+    }
 
     /// Inserts the account's code into the cache.
     ///
     /// Accounts objects and code are stored separately in the cache, this will take the code from the account and instead map it to the code hash.
+    /// The code of the account is `None` afterwards, it is loaded by its hash with [Database::code_by_hash].
     ///
     /// Note: This will not insert into the underlying external database.
     pub fn insert_contract(&mut self, account: &mut AccountInfo) {
-        if let Some(code) = &account.code {
-            if !code.is_empty() {
-                if account.code_hash == KECCAK_EMPTY {
-                    account.code_hash = code.hash_slow();
-                }
-                self.contracts
-                    .entry(account.code_hash)
-                    .or_insert_with(|| code.clone());
-            }
-        }
-        if account.code_hash == B256::ZERO {
-            account.code_hash = KECCAK_EMPTY;
+        take_contract(&mut self.contracts, account);
+    }
+
+    /// Moves the code still stored inside the cached accounts into the `contracts` map.
+    ///
+    /// Accounts are cached without code since the code is shared by its hash, this is only
+    /// needed for caches that were built or deserialized with the code inside the accounts.
+    pub fn shrink_code(&mut self) {
+        for account in self.accounts.values_mut() {
+            take_contract(&mut self.contracts, &mut account.info);
         }
     }
 
@@ -81,9 +109,124 @@ impl<ExtDB> CacheDB<ExtDB> {
         self.insert_contract(&mut info);
         self.accounts.entry(address).or_default().info = info;
     }
+
+    /// Removes the account and its storage from the cache and returns it.
+    ///
+    /// The account is loaded again from the underlying database on the next access, for
+    /// [InMemoryDB] this is the same as if the account never existed.
+    pub fn remove_account(&mut self, address: Address) -> Option<DbAccount> {
+        self.accounts.remove(&address)
+    }
+
+    /// Returns an iterator over the cached accounts in no particular order.
+    ///
+    /// The [AccountState] of the account tells how the cached account relates to the
+    /// underlying database:
+    /// * [AccountState::None]: loaded from the underlying database or inserted, not changed by
+    ///   a committed transaction. Storage slots that are not cached are in the database.
+    /// * [AccountState::Touched]: changed by a committed transaction, storage slots that are not
+    ///   cached are in the database.
+    /// * [AccountState::StorageCleared]: created or recreated by a committed transaction, storage
+    ///   slots that are not cached are zero.
+    /// * [AccountState::NotExisting]: the account does not exist, either it was not found in the
+    ///   database or it was selfdestructed. Info and storage are empty.
+    pub fn accounts_iter(&self) -> impl Iterator<Item = (Address, &DbAccount)> {
+        self.accounts
+            .iter()
+            .map(|(address, account)| (*address, account))
+    }
+
+    /// Returns an iterator over the cached storage slots of the account in no particular order.
+    ///
+    /// The iterator is empty if the account is not cached. Slots set to zero are included.
+    pub fn storage_iter(&self, address: Address) -> impl Iterator<Item = (U256, U256)> + '_ {
+        self.accounts
+            .get(&address)
+            .into_iter()
+            .flat_map(|account| account.storage.iter().map(|(key, value)| (*key, *value)))
+    }
+
+    /// Returns the number of cached accounts, including the not existing ones.
+    pub fn accounts_len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Returns the number of cached storage slots of the account.
+    pub fn storage_len(&self, address: Address) -> usize {
+        self.accounts
+            .get(&address)
+            .map_or(0, |account| account.storage.len())
+    }
+
+    /// Asserts that the cached state is the `expected` state and panics with the list of
+    /// differences otherwise.
+    ///
+    /// The state consists of existing accounts with their info and non-zero storage slots.
+    /// Code is compared by its hash. Accounts that were not loaded from the underlying database
+    /// are not part of the cached state, for [InMemoryDB] that is the whole state.
+    #[cfg(any(test, feature = "test-utils"))]
+    #[track_caller]
+    pub fn assert_state_eq(
+        &self,
+        expected: &BTreeMap<Address, (AccountInfo, BTreeMap<U256, U256>)>,
+    ) {
+        use std::{collections::BTreeSet, format, string::String};
+
+        let actual: BTreeMap<_, _> = self
+            .accounts
+            .iter()
+            .filter_map(|(address, account)| {
+                let info = account.info()?;
+                let storage = account
+                    .storage
+                    .iter()
+                    .filter(|(_, value)| !value.is_zero())
+                    .map(|(key, value)| (*key, *value))
+                    .collect::<BTreeMap<_, _>>();
+                Some((*address, (info, storage)))
+            })
+            .collect();
+
+        let mut diff = String::new();
+        let addresses: BTreeSet<_> = actual.keys().chain(expected.keys()).collect();
+        for address in addresses {
+            match (expected.get(address), actual.get(address)) {
+                (Some(_), None) => diff += &format!("\n{address}: missing account"),
+                (None, Some(_)) => diff += &format!("\n{address}: unexpected account"),
+                (Some((expected_info, expected_storage)), Some((info, storage))) => {
+                    if expected_info != info {
+                        diff += &format!(
+                            "\n{address}: expected balance {}, nonce {}, code hash {}, \
+                             got balance {}, nonce {}, code hash {}",
+                            expected_info.balance,
+                            expected_info.nonce,
+                            expected_info.code_hash,
+                            info.balance,
+                            info.nonce,
+                            info.code_hash
+                        );
+                    }
+                    let slots: BTreeSet<_> =
+                        expected_storage.keys().chain(storage.keys()).collect();
+                    for slot in slots {
+                        let expected_value =
+                            expected_storage.get(slot).copied().unwrap_or_default();
+                        let value = storage.get(slot).copied().unwrap_or_default();
+                        if expected_value != value {
+                            diff += &format!(
+                                "\n{address}: slot {slot} expected {expected_value}, got {value}"
+                            );
+                        }
+                    }
+                }
+                (None, None) => unreachable!(),
+            }
+        }
+        assert!(diff.is_empty(), "state mismatch:{diff}");
+    }
 }
 
-impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
+impl<ExtDB: DatabaseRef, H> CacheDB<ExtDB, H> {
     /// Returns the account for the given address.
     ///
     /// If the account was not found in the cache, it will be loaded from the underlying database.
@@ -91,14 +234,9 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
         let db = &self.db;
         match self.accounts.entry(address) {
             Entry::Occupied(entry) => Ok(entry.into_mut()),
-            Entry::Vacant(entry) => Ok(entry.insert(
-                db.basic_ref(address)?
-                    .map(|info| DbAccount {
-                        info,
-                        ..Default::default()
-                    })
-                    .unwrap_or_else(DbAccount::new_not_existing),
-            )),
+            Entry::Vacant(entry) => {
+                Ok(entry.insert(load_db_account(&mut self.contracts, db.basic_ref(address)?)))
+            }
         }
     }
 
@@ -127,7 +265,7 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
     }
 }
 
-impl<ExtDB> DatabaseCommit for CacheDB<ExtDB> {
+impl<ExtDB, H> DatabaseCommit for CacheDB<ExtDB, H> {
     fn commit(&mut self, changes: HashMap<Address, Account>) {
         for (address, mut account) in changes {
             if !account.is_touched() {
@@ -165,21 +303,22 @@ impl<ExtDB> DatabaseCommit for CacheDB<ExtDB> {
     }
 }
 
-impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
+impl<ExtDB, H> DatabaseCommit2PC for CacheDB<ExtDB, H> {
+    fn commit_prepared(&mut self, prepared: PreparedCommit) {
+        self.commit(prepared.into_changes())
+    }
+}
+
+impl<ExtDB: DatabaseRef, H: BlockHashProvider> Database for CacheDB<ExtDB, H> {
     type Error = ExtDB::Error;
 
     fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
         let basic = match self.accounts.entry(address) {
             Entry::Occupied(entry) => entry.into_mut(),
-            Entry::Vacant(entry) => entry.insert(
-                self.db
-                    .basic_ref(address)?
-                    .map(|info| DbAccount {
-                        info,
-                        ..Default::default()
-                    })
-                    .unwrap_or_else(DbAccount::new_not_existing),
-            ),
+            Entry::Vacant(entry) => entry.insert(load_db_account(
+                &mut self.contracts,
+                self.db.basic_ref(address)?,
+            )),
         };
         Ok(basic.info())
     }
@@ -222,7 +361,7 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
                 let info = self.db.basic_ref(address)?;
                 let (account, value) = if info.is_some() {
                     let value = self.db.storage_ref(address, index)?;
-                    let mut account: DbAccount = info.into();
+                    let mut account = load_db_account(&mut self.contracts, info);
                     account.storage.insert(index, value);
                     (account, value)
                 } else {
@@ -235,18 +374,20 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
     }
 
     fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
-        match self.block_hashes.entry(U256::from(number)) {
-            Entry::Occupied(entry) => Ok(*entry.get()),
-            Entry::Vacant(entry) => {
-                let hash = self.db.block_hash_ref(number)?;
-                entry.insert(hash);
-                Ok(hash)
-            }
+        let key = U256::from(number);
+        if let Some(hash) = self.block_hashes.get(&key) {
+            return Ok(*hash);
         }
+        let hash = match self.block_hash_provider.block_hash(number) {
+            Some(hash) => hash,
+            None => self.db.block_hash_ref(number)?,
+        };
+        self.block_hashes.insert(key, hash);
+        Ok(hash)
     }
 }
 
-impl<ExtDB: DatabaseRef> DatabaseRef for CacheDB<ExtDB> {
+impl<ExtDB: DatabaseRef, H: BlockHashProvider> DatabaseRef for CacheDB<ExtDB, H> {
     type Error = ExtDB::Error;
 
     fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
@@ -285,11 +426,41 @@ impl<ExtDB: DatabaseRef> DatabaseRef for CacheDB<ExtDB> {
     fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
         match self.block_hashes.get(&U256::from(number)) {
             Some(entry) => Ok(*entry),
-            None => self.db.block_hash_ref(number),
+            None => match self.block_hash_provider.block_hash(number) {
+                Some(hash) => Ok(hash),
+                None => self.db.block_hash_ref(number),
+            },
         }
     }
 }
 
+/// Moves the code of the `account` into `contracts`, see [CacheDB::insert_contract].
+fn take_contract(contracts: &mut HashMap<B256, Bytecode>, account: &mut AccountInfo) {
+    if let Some(code) = account.code.take() {
+        if !code.is_empty() {
+            if account.code_hash == KECCAK_EMPTY {
+                account.code_hash = code.hash_slow();
+            }
+            contracts.entry(account.code_hash).or_insert(code);
+        }
+    }
+    if account.code_hash == B256::ZERO {
+        account.code_hash = KECCAK_EMPTY;
+    }
+}
+
+/// Creates the cached account from the account info of the underlying database.
+fn load_db_account(
+    contracts: &mut HashMap<B256, Bytecode>,
+    info: Option<AccountInfo>,
+) -> DbAccount {
+    let Some(mut info) = info else {
+        return DbAccount::new_not_existing();
+    };
+    take_contract(contracts, &mut info);
+    info.into()
+}
+
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DbAccount {
@@ -411,7 +582,96 @@ impl Database for BenchmarkDB {
 #[cfg(test)]
 mod tests {
     use super::{CacheDB, EmptyDB};
-    use crate::primitives::{db::Database, AccountInfo, Address, U256};
+    use crate::db::{EmptyDBTyped, UnknownBlockHash};
+    use crate::primitives::{
+        db::{Database, DatabaseRef},
+        AccountInfo, Address, Bytecode, Bytes, HashMap, HashSet, SpecId, B256, KECCAK_EMPTY, U256,
+    };
+    use crate::JournaledState;
+    use core::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn block_hash_provider_is_asked_lazily() {
+        let fetched = Arc::new(AtomicU64::new(0));
+        let provider = {
+            let fetched = fetched.clone();
+            move |number: u64| {
+                fetched.fetch_add(1, Ordering::Relaxed);
+                (number < 10).then(|| B256::with_last_byte(number as u8))
+            }
+        };
+        let mut db = CacheDB::new(EmptyDBTyped::<UnknownBlockHash>::new().strict())
+            .with_block_hash_provider(provider);
+        assert_eq!(fetched.load(Ordering::Relaxed), 0);
+
+        assert_eq!(db.block_hash_ref(1), Ok(B256::with_last_byte(1)));
+        assert_eq!(db.block_hash(1), Ok(B256::with_last_byte(1)));
+        assert_eq!(fetched.load(Ordering::Relaxed), 2);
+        // Hash is cached by the first mutable lookup.
+        assert_eq!(db.block_hash(1), Ok(B256::with_last_byte(1)));
+        assert_eq!(db.block_hash_ref(1), Ok(B256::with_last_byte(1)));
+        assert_eq!(fetched.load(Ordering::Relaxed), 2);
+
+        // Hashes unknown to the provider are taken from the underlying database.
+        assert_eq!(db.block_hash(10), Err(UnknownBlockHash(10)));
+        db.block_hashes
+            .insert(U256::from(10), B256::with_last_byte(10));
+        assert_eq!(db.block_hash(10), Ok(B256::with_last_byte(10)));
+    }
+
+    #[test]
+    fn accounts_are_cached_without_code() {
+        let codes: Vec<Bytecode> = (0..10u8)
+            .map(|i| Bytecode::new_raw(Bytes::from(vec![0x60, i, 0x00])))
+            .collect();
+        let mut db = CacheDB::new(EmptyDB::default());
+        for i in 0..10_000u64 {
+            let code = codes[i as usize % codes.len()].clone();
+            let mut address = Address::ZERO;
+            address[12..20].copy_from_slice(&i.to_be_bytes());
+            db.insert_account_info(address, AccountInfo::from_bytecode(code));
+        }
+
+        assert_eq!(db.accounts.len(), 10_000);
+        assert!(db.accounts.values().all(|acc| acc.info.code.is_none()));
+        // Ten bytecodes plus the two empty ones.
+        assert_eq!(db.contracts.len(), 12);
+        let code_len: usize = db.contracts.values().map(Bytecode::len).sum();
+        assert_eq!(code_len, codes.iter().map(Bytecode::len).sum::<usize>());
+
+        // Code is loaded by its hash when the account is loaded in the journal.
+        let address = Address::with_last_byte(3);
+        assert_eq!(db.basic(address).unwrap().unwrap().code, None);
+        let mut journal = JournaledState::new(SpecId::LATEST, HashSet::default());
+        let (account, _) = journal.load_code(address, &mut db).unwrap();
+        assert_eq!(account.info.code.as_ref(), Some(&codes[3]));
+        assert_eq!(account.info.code_hash, codes[3].hash_slow());
+    }
+
+    #[test]
+    fn shrink_code() {
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x01, 0x00]));
+        let hash = code.hash_slow();
+        let address = Address::with_last_byte(1);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.accounts.entry(address).or_default().info =
+            AccountInfo::new(U256::ZERO, 1, hash, code.clone());
+        let empty = Address::with_last_byte(2);
+        db.accounts.entry(empty).or_default().info = AccountInfo {
+            code: Some(Bytecode::default()),
+            ..Default::default()
+        };
+
+        db.shrink_code();
+
+        assert_eq!(db.accounts[&address].info.code, None);
+        assert_eq!(db.accounts[&address].info.code_hash, hash);
+        assert_eq!(db.accounts[&empty].info.code, None);
+        assert_eq!(db.accounts[&empty].info.code_hash, KECCAK_EMPTY);
+        assert_eq!(db.contracts.get(&hash), Some(&code));
+        assert_eq!(db.code_by_hash_ref(hash), Ok(code));
+    }
 
     #[test]
     fn test_insert_account_storage() {
@@ -457,7 +717,7 @@ mod tests {
 
         let mut new_state = CacheDB::new(init_state);
         new_state
-            .replace_account_storage(account, [(key1, value1)].into())
+            .replace_account_storage(account, HashMap::from_iter([(key1, value1)]))
             .unwrap();
 
         assert_eq!(new_state.basic(account).unwrap().unwrap().nonce, nonce);
@@ -488,4 +748,99 @@ mod tests {
             nonce
         );
     }
+
+    #[test]
+    fn assert_state_after_create_and_selfdestruct() {
+        use crate::{
+            primitives::{bytes, TxKind},
+            DatabaseCommit, Evm,
+        };
+        use std::collections::BTreeMap;
+
+        let caller = Address::with_last_byte(1);
+        let balance = U256::from(1_000_000);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(balance));
+
+        // SSTORE(0, 1), returns runtime code CALLER SELFDESTRUCT.
+        let init_code = bytes!("60016000556133ff6000526002601ef3");
+        let runtime = Bytecode::new_raw(bytes!("33ff"));
+        let contract = caller.create(0);
+        let transact = |db: &mut CacheDB<EmptyDB>, transact_to: TxKind, data| {
+            let mut evm = Evm::builder()
+                .with_db(&mut *db)
+                .with_spec_id(SpecId::SHANGHAI)
+                .modify_block_env(|block| block.coinbase = caller)
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = Some(transact_to);
+                    tx.data = data;
+                    tx.gas_limit = 100_000;
+                })
+                .build();
+            let result_and_state = evm.transact().unwrap();
+            assert!(result_and_state.result.is_success());
+            drop(evm);
+            db.commit(result_and_state.state);
+        };
+
+        transact(&mut db, TxKind::Create, init_code);
+        assert_eq!(
+            db.storage_iter(contract).collect::<Vec<_>>(),
+            [(U256::ZERO, U256::from(1))]
+        );
+        let mut expected = BTreeMap::from([
+            (
+                caller,
+                (
+                    AccountInfo {
+                        nonce: 1,
+                        ..AccountInfo::from_balance(balance)
+                    },
+                    BTreeMap::new(),
+                ),
+            ),
+            (
+                contract,
+                (
+                    AccountInfo::new(U256::ZERO, 1, runtime.hash_slow(), runtime),
+                    BTreeMap::from([(U256::ZERO, U256::from(1))]),
+                ),
+            ),
+        ]);
+        db.assert_state_eq(&expected);
+
+        transact(&mut db, TxKind::Call(contract), Bytes::new());
+        // Selfdestructed account stays cached as not existing.
+        assert_eq!(db.accounts_len(), 2);
+        assert_eq!(db.storage_len(contract), 0);
+        expected.remove(&contract);
+        expected.get_mut(&caller).unwrap().0.nonce = 2;
+        db.assert_state_eq(&expected);
+
+        assert!(db.remove_account(contract).is_some());
+        assert_eq!(
+            db.accounts_iter()
+                .map(|(address, _)| address)
+                .collect::<Vec<_>>(),
+            [caller]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "slot 1 expected 2, got 0")]
+    fn assert_state_eq_reports_slot() {
+        use std::collections::BTreeMap;
+
+        let address = Address::with_last_byte(1);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(address, AccountInfo::default());
+        db.assert_state_eq(&BTreeMap::from([(
+            address,
+            (
+                AccountInfo::default(),
+                BTreeMap::from([(U256::from(1), U256::from(2))]),
+            ),
+        )]));
+    }
 }
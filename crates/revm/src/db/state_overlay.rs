@@ -0,0 +1,337 @@
+use super::{AccountState, CacheDB, DatabaseCommit, DatabaseRef, DbAccount};
+use crate::primitives::{
+    hash_map::Entry, Account, AccountInfo, Address, Bytecode, HashMap, HashSet, B256, KECCAK_EMPTY,
+    U256,
+};
+use crate::Database;
+use std::vec::Vec;
+
+/// Key of a piece of state read or written by a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StateKey {
+    /// Account info (balance, nonce and code hash).
+    Account(Address),
+    /// Storage slot of the account.
+    Storage(Address, U256),
+    /// Bytecode by its hash.
+    Code(B256),
+    /// Block hash by block number.
+    BlockHash(u64),
+}
+
+/// A [Database] that executes on top of a shared immutable [DatabaseRef] and tracks
+/// the read and write sets of the execution.
+///
+/// It is meant for optimistic concurrent execution: every candidate transaction is executed
+/// over its own overlay of the same base state, possibly in parallel. Overlays that do not
+/// [conflict](StateOverlay::conflicts_with) can be merged in any order, otherwise the losing
+/// transaction needs to be re-executed on the updated state.
+///
+/// * Every key read from the base database is recorded in the [read set](StateOverlay::read_set).
+/// * Changes from [DatabaseCommit::commit] are buffered in the overlay, subsequent reads
+///   see them, and changed keys are recorded in the [write set](StateOverlay::write_set).
+///   Accounts that are touched but not changed are not part of the write set.
+/// * [`StateOverlay::apply_to`] writes buffered changes to a [CacheDB].
+#[derive(Debug, Clone)]
+pub struct StateOverlay<DB> {
+    /// Base database, it is never written to.
+    db: DB,
+    /// Accounts changed by committed transactions.
+    accounts: HashMap<Address, DbAccount>,
+    /// Account info read from the base database.
+    base_accounts: HashMap<Address, Option<AccountInfo>>,
+    /// Contracts by their code hash.
+    contracts: HashMap<B256, Bytecode>,
+    /// Keys read from the base database.
+    reads: HashSet<StateKey>,
+    /// Keys changed by committed transactions.
+    writes: HashSet<StateKey>,
+    /// Accounts whose storage was cleared by committed transactions.
+    cleared_storage: HashSet<Address>,
+    /// Buffered commits in the order they were made.
+    changes: Vec<HashMap<Address, Account>>,
+}
+
+impl<DB> StateOverlay<DB> {
+    /// Creates a new overlay over the `db`.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            accounts: HashMap::default(),
+            base_accounts: HashMap::default(),
+            contracts: HashMap::default(),
+            reads: HashSet::default(),
+            writes: HashSet::default(),
+            cleared_storage: HashSet::default(),
+            changes: Vec::new(),
+        }
+    }
+
+    /// Returns the base database.
+    #[inline]
+    pub fn db(&self) -> &DB {
+        &self.db
+    }
+
+    /// Returns all keys that were read from the base database.
+    #[inline]
+    pub fn read_set(&self) -> &HashSet<StateKey> {
+        &self.reads
+    }
+
+    /// Returns all keys that were changed by committed transactions.
+    ///
+    /// Storage of accounts that were selfdestructed or created is cleared, see
+    /// [`StateOverlay::conflicts_with`].
+    #[inline]
+    pub fn write_set(&self) -> &HashSet<StateKey> {
+        &self.writes
+    }
+
+    /// Returns `true` if execution on this overlay and on the `other` overlay can't be merged
+    /// without re-execution.
+    ///
+    /// This is the case if one overlay writes a key the other one reads or writes. Clearing the
+    /// storage of an account conflicts with the access to any of its storage slots.
+    pub fn conflicts_with<Other>(&self, other: &StateOverlay<Other>) -> bool {
+        self.writes_any(&other.reads)
+            || self.writes_any(&other.writes)
+            || other.writes_any(&self.reads)
+    }
+
+    /// Returns `true` if any of the `keys` is changed by this overlay.
+    fn writes_any(&self, keys: &HashSet<StateKey>) -> bool {
+        keys.iter().any(|key| {
+            self.writes.contains(key)
+                || matches!(key, StateKey::Storage(address, _) if self.cleared_storage.contains(address))
+        })
+    }
+
+    /// Applies all buffered changes to the `cache_db`, in the order they were committed.
+    pub fn apply_to<ExtDB>(&self, cache_db: &mut CacheDB<ExtDB>) {
+        for changes in &self.changes {
+            cache_db.commit(changes.clone());
+        }
+    }
+
+    /// Consumes the overlay and returns the buffered changes, in the order they were committed.
+    pub fn into_changes(self) -> Vec<HashMap<Address, Account>> {
+        self.changes
+    }
+}
+
+impl<DB: DatabaseRef> StateOverlay<DB> {
+    /// Returns account info from the base database and records the read.
+    fn base_basic(&mut self, address: Address) -> Result<Option<AccountInfo>, DB::Error> {
+        self.reads.insert(StateKey::Account(address));
+        match self.base_accounts.entry(address) {
+            Entry::Occupied(entry) => Ok(entry.get().clone()),
+            Entry::Vacant(entry) => Ok(entry.insert(self.db.basic_ref(address)?).clone()),
+        }
+    }
+}
+
+impl<DB> DatabaseCommit for StateOverlay<DB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        for (address, account) in &changes {
+            if !account.is_touched() {
+                continue;
+            }
+            if account.is_selfdestructed() {
+                let db_account = self.accounts.entry(*address).or_default();
+                db_account.storage.clear();
+                db_account.account_state = AccountState::NotExisting;
+                db_account.info = AccountInfo::default();
+                self.writes.insert(StateKey::Account(*address));
+                self.cleared_storage.insert(*address);
+                continue;
+            }
+
+            let previous = match self.accounts.get(address) {
+                Some(db_account) => db_account.info(),
+                None => self.base_accounts.get(address).cloned().flatten(),
+            }
+            .unwrap_or_default();
+
+            let mut info = account.info.clone();
+            if let Some(code) = &info.code {
+                if !code.is_empty() {
+                    if info.code_hash == KECCAK_EMPTY {
+                        info.code_hash = code.hash_slow();
+                    }
+                    self.contracts
+                        .entry(info.code_hash)
+                        .or_insert_with(|| code.clone());
+                }
+            }
+
+            let is_newly_created = account.is_created();
+            if is_newly_created || info != previous {
+                self.writes.insert(StateKey::Account(*address));
+            }
+
+            let db_account = self.accounts.entry(*address).or_default();
+            db_account.info = info;
+            db_account.account_state = if is_newly_created {
+                db_account.storage.clear();
+                self.cleared_storage.insert(*address);
+                AccountState::StorageCleared
+            } else if matches!(
+                db_account.account_state,
+                AccountState::StorageCleared | AccountState::NotExisting
+            ) {
+                AccountState::StorageCleared
+            } else {
+                AccountState::Touched
+            };
+            for (key, slot) in &account.storage {
+                if slot.is_changed() {
+                    self.writes.insert(StateKey::Storage(*address, *key));
+                }
+                db_account.storage.insert(*key, slot.present_value());
+            }
+        }
+        self.changes.push(changes);
+    }
+}
+
+impl<DB: DatabaseRef> Database for StateOverlay<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        match self.accounts.get(&address) {
+            Some(account) => Ok(account.info()),
+            None => self.base_basic(address),
+        }
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if let Some(code) = self.contracts.get(&code_hash) {
+            return Ok(code.clone());
+        }
+        self.reads.insert(StateKey::Code(code_hash));
+        let code = self.db.code_by_hash_ref(code_hash)?;
+        self.contracts.insert(code_hash, code.clone());
+        Ok(code)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(account) = self.accounts.get(&address) {
+            if let Some(value) = account.storage.get(&index) {
+                return Ok(*value);
+            }
+            if matches!(
+                account.account_state,
+                AccountState::StorageCleared | AccountState::NotExisting
+            ) {
+                return Ok(U256::ZERO);
+            }
+        }
+        self.reads.insert(StateKey::Storage(address, index));
+        self.db.storage_ref(address, index)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.reads.insert(StateKey::BlockHash(number));
+        self.db.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{EmptyDB, InMemoryDB},
+        primitives::{address, TxKind},
+        Evm,
+    };
+
+    const ALICE: Address = address!("a000000000000000000000000000000000000001");
+    const BOB: Address = address!("b000000000000000000000000000000000000002");
+    const CAROL: Address = address!("c000000000000000000000000000000000000003");
+    const DAVE: Address = address!("d000000000000000000000000000000000000004");
+
+    fn base() -> InMemoryDB {
+        let mut db = CacheDB::new(EmptyDB::default());
+        for address in [ALICE, BOB, CAROL, DAVE] {
+            db.insert_account_info(
+                address,
+                AccountInfo {
+                    balance: U256::from(1_000),
+                    ..Default::default()
+                },
+            );
+        }
+        db
+    }
+
+    /// Executes a value transfer over a fresh overlay of `base`.
+    fn transfer(
+        base: &InMemoryDB,
+        from: Address,
+        to: Address,
+        value: u64,
+    ) -> StateOverlay<&InMemoryDB> {
+        let mut evm = Evm::builder()
+            .with_db(StateOverlay::new(base))
+            .modify_tx_env(|tx| {
+                tx.caller = from;
+                tx.transact_to = Some(TxKind::Call(to));
+                tx.value = U256::from(value);
+                tx.gas_limit = 21_000;
+            })
+            .build();
+        evm.transact_commit().unwrap();
+        evm.into_context().evm.inner.db
+    }
+
+    fn balance(db: &InMemoryDB, address: Address) -> U256 {
+        db.basic_ref(address).unwrap().unwrap().balance
+    }
+
+    #[test]
+    fn independent_transactions() {
+        let mut base = base();
+        let tx1 = transfer(&base, ALICE, BOB, 10);
+        let tx2 = transfer(&base, CAROL, DAVE, 20);
+
+        assert!(tx1.read_set().contains(&StateKey::Account(ALICE)));
+        assert!(tx1.write_set().contains(&StateKey::Account(BOB)));
+        assert!(!tx1.conflicts_with(&tx2));
+        assert!(!tx2.conflicts_with(&tx1));
+
+        let (changes1, changes2) = (tx1.into_changes(), tx2.into_changes());
+        for changes in changes2.into_iter().chain(changes1) {
+            base.commit(changes);
+        }
+        assert_eq!(balance(&base, ALICE), U256::from(990));
+        assert_eq!(balance(&base, BOB), U256::from(1_010));
+        assert_eq!(balance(&base, CAROL), U256::from(980));
+        assert_eq!(balance(&base, DAVE), U256::from(1_020));
+    }
+
+    #[test]
+    fn conflicting_transactions() {
+        let base = base();
+        let tx1 = transfer(&base, ALICE, BOB, 10);
+        // spends from BOB whose balance is written by `tx1`.
+        let tx2 = transfer(&base, BOB, CAROL, 5);
+        assert!(tx1.conflicts_with(&tx2));
+        assert!(tx2.conflicts_with(&tx1));
+
+        // `tx1` wins, `tx2` is re-executed on the updated state.
+        let mut merged = base.clone();
+        tx1.apply_to(&mut merged);
+        let tx2 = transfer(&merged, BOB, CAROL, 5);
+        let changes = tx2.into_changes();
+        for changes in changes {
+            merged.commit(changes);
+        }
+
+        assert_eq!(balance(&merged, ALICE), U256::from(990));
+        assert_eq!(balance(&merged, BOB), U256::from(1_005));
+        assert_eq!(balance(&merged, CAROL), U256::from(1_005));
+        assert_eq!(merged.basic_ref(BOB).unwrap().unwrap().nonce, 1);
+    }
+}
@@ -4,6 +4,7 @@ pub mod bundle_state;
 pub mod cache;
 pub mod cache_account;
 pub mod changes;
+pub mod hashed_state;
 pub mod plain_account;
 pub mod reverts;
 pub mod state;
@@ -12,12 +13,15 @@ pub mod transition_account;
 pub mod transition_state;
 
 /// Account status for Block and Bundle states.
-pub use account_status::AccountStatus;
+pub use account_status::{
+    AccountStatus, AccountStatusEvent, AccountStatusTransition, InvalidTransition,
+};
 pub use bundle_account::BundleAccount;
 pub use bundle_state::{BundleBuilder, BundleState, OriginalValuesKnown};
 pub use cache::CacheState;
 pub use cache_account::CacheAccount;
 pub use changes::{PlainStateReverts, PlainStorageChangeset, PlainStorageRevert, StateChangeset};
+pub use hashed_state::{HashedBundleState, HashedKeyCache, HashedStorage};
 pub use plain_account::{PlainAccount, StorageSlot, StorageWithOriginalValues};
 pub use reverts::{AccountRevert, RevertToSlot};
 pub use state::{DBBox, State, StateDBBox};
@@ -1,3 +1,5 @@
+use core::fmt;
+
 /// After account get loaded from database it can be in a lot of different states
 /// while we execute multiple transaction and even blocks over account that is in memory.
 /// This structure models all possible states that account can be in.
@@ -26,6 +28,11 @@ impl AccountStatus {
         )
     }
 
+    /// Account is modified, it was changed, created or destroyed.
+    pub fn is_modified(&self) -> bool {
+        !self.is_not_modified()
+    }
+
     /// Account was destroyed by calling SELFDESTRUCT.
     /// This means that full account and storage are inside memory.
     pub fn was_destroyed(&self) -> bool {
@@ -37,6 +44,18 @@ impl AccountStatus {
         )
     }
 
+    /// Account was destroyed at any point, even if it was created or changed afterwards.
+    ///
+    /// Storage of such account needs to be wiped before the changed storage is written.
+    pub fn was_destroyed_at_any_point(&self) -> bool {
+        matches!(
+            self,
+            AccountStatus::Destroyed
+                | AccountStatus::DestroyedChanged
+                | AccountStatus::DestroyedAgain
+        )
+    }
+
     /// This means storage is known, it can be newly created or storage got destroyed.
     pub fn is_storage_known(&self) -> bool {
         matches!(
@@ -58,24 +77,7 @@ impl AccountStatus {
 
     /// Returns the next account status on creation.
     pub fn on_created(&self) -> AccountStatus {
-        match self {
-            // if account was destroyed previously just copy new info to it.
-            AccountStatus::DestroyedAgain
-            | AccountStatus::Destroyed
-            | AccountStatus::DestroyedChanged => AccountStatus::DestroyedChanged,
-            // if account is loaded from db.
-            AccountStatus::LoadedNotExisting
-            // Loaded empty eip161 to creates is not possible as CREATE2 was added after EIP-161
-            | AccountStatus::LoadedEmptyEIP161
-            | AccountStatus::Loaded
-            | AccountStatus::Changed
-            | AccountStatus::InMemoryChange => {
-                // If account is loaded and not empty this means that account has some balance.
-                // This means that account cannot be created.
-                // We are assuming that EVM did necessary checks before allowing account to be created.
-                AccountStatus::InMemoryChange
-            }
-        }
+        self.apply_infallible(AccountStatusEvent::Created)
     }
 
     /// Returns the next account status on touched empty account post state clear EIP (EIP-161).
@@ -84,22 +86,8 @@ impl AccountStatus {
     ///
     /// If current status is [AccountStatus::Loaded] or [AccountStatus::Changed].
     pub fn on_touched_empty_post_eip161(&self) -> AccountStatus {
-        match self {
-            // Account can be touched but not existing. The status should remain the same.
-            AccountStatus::LoadedNotExisting => AccountStatus::LoadedNotExisting,
-            // Account can be created empty and only then touched.
-            AccountStatus::InMemoryChange
-            | AccountStatus::Destroyed
-            | AccountStatus::LoadedEmptyEIP161 => AccountStatus::Destroyed,
-            // Transition to destroy the account.
-            AccountStatus::DestroyedAgain | AccountStatus::DestroyedChanged => {
-                AccountStatus::DestroyedAgain
-            }
-            // Account statuses considered unreachable.
-            AccountStatus::Loaded | AccountStatus::Changed => {
-                unreachable!("Wrong state transition, touch empty is not possible from {self:?}");
-            }
-        }
+        AccountStatusTransition::apply(*self, AccountStatusEvent::TouchedEmptyPostEip161)
+            .unwrap_or_else(|err| unreachable!("{err}"))
     }
 
     /// Returns the next account status on touched or created account pre state clear EIP (EIP-161).
@@ -111,75 +99,29 @@ impl AccountStatus {
     pub fn on_touched_created_pre_eip161(&self, had_no_info: bool) -> Option<AccountStatus> {
         match self {
             AccountStatus::LoadedEmptyEIP161 => None,
-            AccountStatus::DestroyedChanged => {
-                if had_no_info {
-                    None
-                } else {
-                    Some(AccountStatus::DestroyedChanged)
-                }
-            }
-            AccountStatus::Destroyed | AccountStatus::DestroyedAgain => {
-                Some(AccountStatus::DestroyedChanged)
-            }
-            AccountStatus::InMemoryChange | AccountStatus::LoadedNotExisting => {
-                Some(AccountStatus::InMemoryChange)
-            }
-            AccountStatus::Loaded | AccountStatus::Changed => {
-                unreachable!("Wrong state transition, touch crate is not possible from {self:?}")
-            }
+            AccountStatus::DestroyedChanged if had_no_info => None,
+            _ => Some(
+                AccountStatusTransition::apply(*self, AccountStatusEvent::TouchedCreatedPreEip161)
+                    .unwrap_or_else(|err| unreachable!("{err}")),
+            ),
         }
     }
 
     /// Returns the next account status on change.
     pub fn on_changed(&self, had_no_nonce_and_code: bool) -> AccountStatus {
-        match self {
-            // If the account was loaded as not existing, promote it to changed.
-            // This account was likely created by a balance transfer.
-            AccountStatus::LoadedNotExisting => AccountStatus::InMemoryChange,
-            // Change on empty account, should transfer storage if there is any.
-            // There is possibility that there are storage entries inside db.
-            // That storage is used in merkle tree calculation before state clear EIP.
-            AccountStatus::LoadedEmptyEIP161 => AccountStatus::InMemoryChange,
-            // The account was loaded as existing.
-            AccountStatus::Loaded => {
-                if had_no_nonce_and_code {
-                    // account is fully in memory
-                    AccountStatus::InMemoryChange
-                } else {
-                    // can be contract and some of storage slots can be present inside db.
-                    AccountStatus::Changed
-                }
-            }
-
-            // On change, the "changed" type account statuses are preserved.
-            // Any checks for empty accounts are done outside of this fn.
-            AccountStatus::Changed => AccountStatus::Changed,
-            AccountStatus::InMemoryChange => AccountStatus::InMemoryChange,
-            AccountStatus::DestroyedChanged => AccountStatus::DestroyedChanged,
-
-            // If account is destroyed and then changed this means this is
-            // balance transfer.
-            AccountStatus::Destroyed | AccountStatus::DestroyedAgain => {
-                AccountStatus::DestroyedChanged
-            }
-        }
+        self.apply_infallible(AccountStatusEvent::Changed {
+            had_no_nonce_and_code,
+        })
     }
 
     /// Returns the next account status on selfdestruct.
     pub fn on_selfdestructed(&self) -> AccountStatus {
-        match self {
-            // Non existing account can't be destroyed.
-            AccountStatus::LoadedNotExisting => AccountStatus::LoadedNotExisting,
-            // If account is created and selfdestructed in the same block, mark it as destroyed again.
-            // Note: there is no big difference between Destroyed and DestroyedAgain in this case,
-            // but was added for clarity.
-            AccountStatus::DestroyedChanged
-            | AccountStatus::DestroyedAgain
-            | AccountStatus::Destroyed => AccountStatus::DestroyedAgain,
-
-            // Transition to destroyed status.
-            _ => AccountStatus::Destroyed,
-        }
+        self.apply_infallible(AccountStatusEvent::SelfDestructed)
+    }
+
+    /// Applies the event that is valid in every status.
+    fn apply_infallible(&self, event: AccountStatusEvent) -> AccountStatus {
+        AccountStatusTransition::apply(*self, event).expect("event is valid in every status")
     }
 
     /// Transition to other state while preserving invariance of this state.
@@ -203,6 +145,166 @@ impl AccountStatus {
     }
 }
 
+/// Event that changes the [AccountStatus] of an account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AccountStatusEvent {
+    /// Account is created.
+    Created,
+    /// Account info or storage is changed.
+    Changed {
+        /// Account had no nonce and code before the change.
+        had_no_nonce_and_code: bool,
+    },
+    /// Account is selfdestructed.
+    SelfDestructed,
+    /// Empty account is touched after the state clear EIP (EIP-161).
+    TouchedEmptyPostEip161,
+    /// Account is touched before the state clear EIP (EIP-161), it is considered created.
+    TouchedCreatedPreEip161,
+}
+
+/// Error returned when the [AccountStatusEvent] can't happen in the [AccountStatus].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InvalidTransition {
+    /// Status of the account.
+    pub status: AccountStatus,
+    /// Event that is not possible in the status.
+    pub event: AccountStatusEvent,
+}
+
+impl fmt::Display for InvalidTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid account status transition: {:?} is not possible from {:?}",
+            self.event, self.status
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidTransition {}
+
+/// State machine of the [AccountStatus].
+///
+/// Every status change of [`CacheAccount`](super::CacheAccount), and with that every
+/// [`TransitionAccount`](super::TransitionAccount), goes through [`AccountStatusTransition::apply`].
+#[derive(Clone, Copy, Debug)]
+pub struct AccountStatusTransition;
+
+impl AccountStatusTransition {
+    /// Returns the status of the account after the `event`.
+    ///
+    /// Returns [InvalidTransition] if the `event` can't happen in the `status`, these are
+    /// touches of empty accounts in [AccountStatus::Loaded] and [AccountStatus::Changed],
+    /// as an account in those statuses is known to be non empty.
+    pub fn apply(
+        status: AccountStatus,
+        event: AccountStatusEvent,
+    ) -> Result<AccountStatus, InvalidTransition> {
+        let next = match event {
+            AccountStatusEvent::Created => match status {
+                // if account was destroyed previously just copy new info to it.
+                AccountStatus::DestroyedAgain
+                | AccountStatus::Destroyed
+                | AccountStatus::DestroyedChanged => AccountStatus::DestroyedChanged,
+                // if account is loaded from db.
+                AccountStatus::LoadedNotExisting
+                // Loaded empty eip161 to creates is not possible as CREATE2 was added after EIP-161
+                | AccountStatus::LoadedEmptyEIP161
+                | AccountStatus::Loaded
+                | AccountStatus::Changed
+                | AccountStatus::InMemoryChange => {
+                    // If account is loaded and not empty this means that account has some balance.
+                    // This means that account cannot be created.
+                    // We are assuming that EVM did necessary checks before allowing account to be created.
+                    AccountStatus::InMemoryChange
+                }
+            },
+            AccountStatusEvent::Changed {
+                had_no_nonce_and_code,
+            } => match status {
+                // If the account was loaded as not existing, promote it to changed.
+                // This account was likely created by a balance transfer.
+                AccountStatus::LoadedNotExisting => AccountStatus::InMemoryChange,
+                // Change on empty account, should transfer storage if there is any.
+                // There is possibility that there are storage entries inside db.
+                // That storage is used in merkle tree calculation before state clear EIP.
+                AccountStatus::LoadedEmptyEIP161 => AccountStatus::InMemoryChange,
+                // The account was loaded as existing.
+                AccountStatus::Loaded => {
+                    if had_no_nonce_and_code {
+                        // account is fully in memory
+                        AccountStatus::InMemoryChange
+                    } else {
+                        // can be contract and some of storage slots can be present inside db.
+                        AccountStatus::Changed
+                    }
+                }
+
+                // On change, the "changed" type account statuses are preserved.
+                // Any checks for empty accounts are done outside of this fn.
+                AccountStatus::Changed => AccountStatus::Changed,
+                AccountStatus::InMemoryChange => AccountStatus::InMemoryChange,
+                AccountStatus::DestroyedChanged => AccountStatus::DestroyedChanged,
+
+                // If account is destroyed and then changed this means this is
+                // balance transfer.
+                AccountStatus::Destroyed | AccountStatus::DestroyedAgain => {
+                    AccountStatus::DestroyedChanged
+                }
+            },
+            AccountStatusEvent::SelfDestructed => match status {
+                // Non existing account can't be destroyed.
+                AccountStatus::LoadedNotExisting => AccountStatus::LoadedNotExisting,
+                // If account is created and selfdestructed in the same block, mark it as destroyed again.
+                // Note: there is no big difference between Destroyed and DestroyedAgain in this case,
+                // but was added for clarity.
+                AccountStatus::DestroyedChanged
+                | AccountStatus::DestroyedAgain
+                | AccountStatus::Destroyed => AccountStatus::DestroyedAgain,
+
+                // Transition to destroyed status.
+                AccountStatus::Loaded
+                | AccountStatus::LoadedEmptyEIP161
+                | AccountStatus::InMemoryChange
+                | AccountStatus::Changed => AccountStatus::Destroyed,
+            },
+            AccountStatusEvent::TouchedEmptyPostEip161 => match status {
+                // Account can be touched but not existing. The status should remain the same.
+                AccountStatus::LoadedNotExisting => AccountStatus::LoadedNotExisting,
+                // Account can be created empty and only then touched.
+                AccountStatus::InMemoryChange
+                | AccountStatus::Destroyed
+                | AccountStatus::LoadedEmptyEIP161 => AccountStatus::Destroyed,
+                // Transition to destroy the account.
+                AccountStatus::DestroyedAgain | AccountStatus::DestroyedChanged => {
+                    AccountStatus::DestroyedAgain
+                }
+                // Account is known to be non empty.
+                AccountStatus::Loaded | AccountStatus::Changed => {
+                    return Err(InvalidTransition { status, event })
+                }
+            },
+            AccountStatusEvent::TouchedCreatedPreEip161 => match status {
+                AccountStatus::LoadedEmptyEIP161 => AccountStatus::LoadedEmptyEIP161,
+                AccountStatus::DestroyedChanged
+                | AccountStatus::Destroyed
+                | AccountStatus::DestroyedAgain => AccountStatus::DestroyedChanged,
+                AccountStatus::InMemoryChange | AccountStatus::LoadedNotExisting => {
+                    AccountStatus::InMemoryChange
+                }
+                // Account is known to be non empty.
+                AccountStatus::Loaded | AccountStatus::Changed => {
+                    return Err(InvalidTransition { status, event })
+                }
+            },
+        };
+        Ok(next)
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -249,5 +351,161 @@ mod test {
         assert!(!AccountStatus::Destroyed.is_modified_and_not_destroyed());
         assert!(!AccountStatus::DestroyedChanged.is_modified_and_not_destroyed());
         assert!(!AccountStatus::DestroyedAgain.is_modified_and_not_destroyed());
+
+        // account modified
+        assert!(!AccountStatus::Loaded.is_modified());
+        assert!(!AccountStatus::LoadedEmptyEIP161.is_modified());
+        assert!(!AccountStatus::LoadedNotExisting.is_modified());
+        assert!(AccountStatus::Changed.is_modified());
+        assert!(AccountStatus::InMemoryChange.is_modified());
+        assert!(AccountStatus::Destroyed.is_modified());
+        assert!(AccountStatus::DestroyedChanged.is_modified());
+        assert!(AccountStatus::DestroyedAgain.is_modified());
+
+        // account destroyed at any point
+        assert!(!AccountStatus::Loaded.was_destroyed_at_any_point());
+        assert!(!AccountStatus::LoadedEmptyEIP161.was_destroyed_at_any_point());
+        assert!(!AccountStatus::LoadedNotExisting.was_destroyed_at_any_point());
+        assert!(!AccountStatus::Changed.was_destroyed_at_any_point());
+        assert!(!AccountStatus::InMemoryChange.was_destroyed_at_any_point());
+        assert!(AccountStatus::Destroyed.was_destroyed_at_any_point());
+        assert!(AccountStatus::DestroyedChanged.was_destroyed_at_any_point());
+        assert!(AccountStatus::DestroyedAgain.was_destroyed_at_any_point());
+    }
+
+    #[test]
+    fn test_transition_matrix() {
+        use AccountStatus::*;
+        use AccountStatusEvent::*;
+
+        const STATUSES: [AccountStatus; 8] = [
+            LoadedNotExisting,
+            Loaded,
+            LoadedEmptyEIP161,
+            InMemoryChange,
+            AccountStatus::Changed,
+            Destroyed,
+            DestroyedChanged,
+            DestroyedAgain,
+        ];
+        let changed = AccountStatusEvent::Changed {
+            had_no_nonce_and_code: false,
+        };
+        let changed_no_code = AccountStatusEvent::Changed {
+            had_no_nonce_and_code: true,
+        };
+        let events = [
+            Created,
+            changed,
+            changed_no_code,
+            SelfDestructed,
+            TouchedEmptyPostEip161,
+            TouchedCreatedPreEip161,
+        ];
+        // Expected status for every status (row) and event (column), `None` is invalid.
+        let expected: [[Option<AccountStatus>; 6]; 8] = [
+            // LoadedNotExisting
+            [
+                Some(InMemoryChange),
+                Some(InMemoryChange),
+                Some(InMemoryChange),
+                Some(LoadedNotExisting),
+                Some(LoadedNotExisting),
+                Some(InMemoryChange),
+            ],
+            // Loaded
+            [
+                Some(InMemoryChange),
+                Some(AccountStatus::Changed),
+                Some(InMemoryChange),
+                Some(Destroyed),
+                None,
+                None,
+            ],
+            // LoadedEmptyEIP161
+            [
+                Some(InMemoryChange),
+                Some(InMemoryChange),
+                Some(InMemoryChange),
+                Some(Destroyed),
+                Some(Destroyed),
+                Some(LoadedEmptyEIP161),
+            ],
+            // InMemoryChange
+            [
+                Some(InMemoryChange),
+                Some(InMemoryChange),
+                Some(InMemoryChange),
+                Some(Destroyed),
+                Some(Destroyed),
+                Some(InMemoryChange),
+            ],
+            // Changed
+            [
+                Some(InMemoryChange),
+                Some(AccountStatus::Changed),
+                Some(AccountStatus::Changed),
+                Some(Destroyed),
+                None,
+                None,
+            ],
+            // Destroyed
+            [
+                Some(DestroyedChanged),
+                Some(DestroyedChanged),
+                Some(DestroyedChanged),
+                Some(DestroyedAgain),
+                Some(Destroyed),
+                Some(DestroyedChanged),
+            ],
+            // DestroyedChanged
+            [
+                Some(DestroyedChanged),
+                Some(DestroyedChanged),
+                Some(DestroyedChanged),
+                Some(DestroyedAgain),
+                Some(DestroyedAgain),
+                Some(DestroyedChanged),
+            ],
+            // DestroyedAgain
+            [
+                Some(DestroyedChanged),
+                Some(DestroyedChanged),
+                Some(DestroyedChanged),
+                Some(DestroyedAgain),
+                Some(DestroyedAgain),
+                Some(DestroyedChanged),
+            ],
+        ];
+
+        for (status, expected) in STATUSES.into_iter().zip(expected) {
+            for (event, expected) in events.into_iter().zip(expected) {
+                let result = AccountStatusTransition::apply(status, event);
+                match expected {
+                    Some(next) => assert_eq!(result, Ok(next), "{status:?} on {event:?}"),
+                    None => assert_eq!(
+                        result,
+                        Err(InvalidTransition { status, event }),
+                        "{status:?} on {event:?}"
+                    ),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_destroyed_created_changed_in_one_block() {
+        // Destroyed -> Created -> Changed stays DestroyedChanged, storage needs to be wiped.
+        let status = AccountStatus::Loaded.on_selfdestructed();
+        assert_eq!(status, AccountStatus::Destroyed);
+        let status = status.on_created();
+        assert_eq!(status, AccountStatus::DestroyedChanged);
+        let status = status.on_changed(false);
+        assert_eq!(status, AccountStatus::DestroyedChanged);
+        assert!(status.was_destroyed_at_any_point());
+        // destroyed again after the recreation.
+        let status = status.on_selfdestructed();
+        assert_eq!(status, AccountStatus::DestroyedAgain);
+        assert_eq!(status.on_created(), AccountStatus::DestroyedChanged);
     }
 }
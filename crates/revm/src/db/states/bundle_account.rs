@@ -94,7 +94,7 @@ impl BundleAccount {
             AccountInfoRevert::DeleteIt => {
                 self.info = None;
                 if self.original_info.is_none() {
-                    self.storage = HashMap::new();
+                    self.storage = HashMap::default();
                     return true;
                 } else {
                     // set all storage to zero but preserve original values.
@@ -106,6 +106,11 @@ impl BundleAccount {
             }
             AccountInfoRevert::RevertTo(info) => self.info = Some(info),
         };
+        // storage got wiped in this transition, present storage belongs to the
+        // new incarnation and only storage from revert existed before it.
+        if revert.wipe_storage {
+            self.storage.clear();
+        }
         // revert storage
         for (key, slot) in revert.storage {
             match slot {
@@ -297,7 +302,7 @@ impl BundleAccount {
                                 account: info_revert,
                                 storage: previous_storage,
                                 previous_status: AccountStatus::DestroyedChanged,
-                                wipe_storage: false,
+                                wipe_storage: transition.storage_was_destroyed,
                             })
                         }
                         AccountStatus::DestroyedAgain => {
@@ -346,13 +351,17 @@ impl BundleAccount {
                         }
                         AccountStatus::DestroyedChanged => {
                             // From destroyed changed to destroyed again.
-                            Some(AccountRevert::new_selfdestructed_again(
+                            let mut revert = AccountRevert::new_selfdestructed_again(
                                 // destroyed again will set empty account.
                                 AccountStatus::DestroyedChanged,
                                 AccountInfoRevert::RevertTo(self.info.clone().unwrap_or_default()),
                                 self.storage.drain().collect(),
                                 HashMap::default(),
-                            ))
+                            );
+                            // storage of previous incarnation is complete, mark the wipe
+                            // so revert does not leave slots of this incarnation behind.
+                            revert.wipe_storage = true;
+                            Some(revert)
                         }
                         _ => unreachable!("Invalid state to DestroyedAgain from {self:?}"),
                     }
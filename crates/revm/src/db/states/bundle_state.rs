@@ -1,8 +1,8 @@
 use super::{
     changes::{PlainStorageChangeset, StateChangeset},
     reverts::{AccountInfoRevert, Reverts},
-    AccountRevert, AccountStatus, BundleAccount, PlainStateReverts, RevertToSlot, StorageSlot,
-    TransitionState,
+    AccountRevert, AccountStatus, BundleAccount, HashedBundleState, HashedKeyCache,
+    PlainStateReverts, RevertToSlot, StorageSlot, TransitionState,
 };
 use core::{mem, ops::RangeInclusive};
 use revm_interpreter::primitives::{
@@ -55,15 +55,15 @@ impl OriginalValuesKnown {
 impl Default for BundleBuilder {
     fn default() -> Self {
         BundleBuilder {
-            states: HashSet::new(),
-            state_original: HashMap::new(),
-            state_present: HashMap::new(),
-            state_storage: HashMap::new(),
+            states: HashSet::default(),
+            state_original: HashMap::default(),
+            state_present: HashMap::default(),
+            state_storage: HashMap::default(),
             reverts: BTreeSet::new(),
             revert_range: 0..=0,
-            revert_account: HashMap::new(),
-            revert_storage: HashMap::new(),
-            contracts: HashMap::new(),
+            revert_account: HashMap::default(),
+            revert_storage: HashMap::default(),
+            contracts: HashMap::default(),
         }
     }
 }
@@ -494,6 +494,21 @@ impl BundleState {
         }
     }
 
+    /// Returns the state keyed by the `keccak256` hash of addresses and storage slots, sorted by
+    /// the hashed keys, ready to be fed to the secure state trie.
+    ///
+    /// Hashing is done in parallel if the `rayon` feature is enabled.
+    pub fn to_hashed_sorted(&self) -> HashedBundleState {
+        self.to_hashed_sorted_with_cache(&mut HashedKeyCache::default())
+    }
+
+    /// Same as [`Self::to_hashed_sorted`] but only hashes keys that are not in the cache yet.
+    ///
+    /// Reusing the cache after applying more transitions avoids rehashing the whole bundle.
+    pub fn to_hashed_sorted_with_cache(&self, cache: &mut HashedKeyCache) -> HashedBundleState {
+        HashedBundleState::from_bundle(self, cache)
+    }
+
     /// Returns the approximate size of changes in the bundle state.
     /// The estimation is not precise, because the information about the number of
     /// destroyed entries that need to be removed is not accessible to the bundle state.
@@ -780,7 +795,7 @@ impl BundleState {
                         let mut account = BundleAccount::new(
                             None,
                             None,
-                            HashMap::new(),
+                            HashMap::default(),
                             AccountStatus::LoadedNotExisting,
                         );
                         if !account.revert(revert_account) {
@@ -895,7 +910,7 @@ mod tests {
                         code_hash: KECCAK_EMPTY,
                         code: None,
                     }),
-                    HashMap::from([
+                    HashMap::from_iter([
                         (slot1(), (U256::from(0), U256::from(10))),
                         (slot2(), (U256::from(0), U256::from(15))),
                     ]),
@@ -909,7 +924,7 @@ mod tests {
                         code_hash: KECCAK_EMPTY,
                         code: None,
                     }),
-                    HashMap::from([]),
+                    HashMap::default(),
                 ),
             ],
             vec![vec![
@@ -937,7 +952,7 @@ mod tests {
                     code_hash: KECCAK_EMPTY,
                     code: None,
                 }),
-                HashMap::from([(slot1(), (U256::from(0), U256::from(15)))]),
+                HashMap::from_iter([(slot1(), (U256::from(0), U256::from(15)))]),
             )],
             vec![vec![(
                 account1(),
@@ -967,7 +982,7 @@ mod tests {
             )
             .state_storage(
                 account1(),
-                HashMap::from([(slot1(), (U256::from(0), U256::from(10)))]),
+                HashMap::from_iter([(slot1(), (U256::from(0), U256::from(10)))]),
             )
             .state_address(account2())
             .state_present_account_info(
@@ -1000,7 +1015,7 @@ mod tests {
             )
             .state_storage(
                 account1(),
-                HashMap::from([(slot1(), (U256::from(0), U256::from(15)))]),
+                HashMap::from_iter([(slot1(), (U256::from(0), U256::from(15)))]),
             )
             .revert_address(0, account1())
             .revert_account_info(
@@ -1130,7 +1145,7 @@ mod tests {
             Some(&BundleAccount::new(
                 None,
                 Some(AccountInfo::default()),
-                HashMap::new(),
+                HashMap::default(),
                 AccountStatus::Changed
             ))
         );
@@ -1265,7 +1280,7 @@ mod tests {
         assert!(builder.get_state_storage_mut().is_empty());
         builder
             .get_state_storage_mut()
-            .insert(account1(), HashMap::new());
+            .insert(account1(), HashMap::default());
         assert!(builder.get_state_storage_mut().contains_key(&account1()));
 
         // Test get_reverts_mut
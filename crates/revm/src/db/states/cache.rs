@@ -1,5 +1,6 @@
 use super::{
-    plain_account::PlainStorage, transition_account::TransitionAccount, CacheAccount, PlainAccount,
+    plain_account::PlainStorage, transition_account::TransitionAccount, CacheAccount,
+    InvalidTransition, PlainAccount,
 };
 use revm_interpreter::primitives::{
     Account, AccountInfo, Address, Bytecode, EvmState, HashMap, B256,
@@ -88,14 +89,31 @@ impl CacheState {
     }
 
     /// Apply output of revm execution and create account transitions that are used to build BundleState.
+    ///
+    /// # Panics
+    ///
+    /// If the account status transition is invalid, see [Self::try_apply_evm_state].
     pub fn apply_evm_state(&mut self, evm_state: EvmState) -> Vec<(Address, TransitionAccount)> {
+        self.try_apply_evm_state(evm_state)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Apply output of revm execution and create account transitions that are used to build BundleState.
+    ///
+    /// Returns [InvalidTransition] if an empty account is touched while its status says it is
+    /// non empty. Accounts applied before the invalid one stay applied, the cache should be
+    /// discarded.
+    pub fn try_apply_evm_state(
+        &mut self,
+        evm_state: EvmState,
+    ) -> Result<Vec<(Address, TransitionAccount)>, InvalidTransition> {
         let mut transitions = Vec::with_capacity(evm_state.len());
         for (address, account) in evm_state {
-            if let Some(transition) = self.apply_account_state(address, account) {
+            if let Some(transition) = self.apply_account_state(address, account)? {
                 transitions.push((address, transition));
             }
         }
-        transitions
+        Ok(transitions)
     }
 
     /// Apply updated account state to the cached account.
@@ -104,10 +122,10 @@ impl CacheState {
         &mut self,
         address: Address,
         account: Account,
-    ) -> Option<TransitionAccount> {
+    ) -> Result<Option<TransitionAccount>, InvalidTransition> {
         // not touched account are never changed.
         if !account.is_touched() {
-            return None;
+            return Ok(None);
         }
 
         let this_account = self
@@ -118,7 +136,7 @@ impl CacheState {
         // If it is marked as selfdestructed inside revm
         // we need to changed state to destroyed.
         if account.is_selfdestructed() {
-            return this_account.selfdestruct();
+            return Ok(this_account.selfdestruct());
         }
 
         let is_created = account.is_created();
@@ -141,7 +159,9 @@ impl CacheState {
         // by just setting storage inside CRATE constructor. Overlap of those contracts
         // is not possible because CREATE2 is introduced later.
         if is_created {
-            return Some(this_account.newly_created(account.info, changed_storage));
+            return Ok(Some(
+                this_account.newly_created(account.info, changed_storage),
+            ));
         }
 
         // Account is touched, but not selfdestructed or newly created.
@@ -158,7 +178,7 @@ impl CacheState {
                 this_account.touch_create_pre_eip161(changed_storage)
             }
         } else {
-            Some(this_account.change(account.info, changed_storage))
+            Ok(Some(this_account.change(account.info, changed_storage)))
         }
     }
 }
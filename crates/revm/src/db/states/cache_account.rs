@@ -1,6 +1,7 @@
 use super::{
-    plain_account::PlainStorage, AccountStatus, BundleAccount, PlainAccount,
-    StorageWithOriginalValues, TransitionAccount,
+    account_status::{AccountStatusEvent, AccountStatusTransition, InvalidTransition},
+    plain_account::PlainStorage,
+    AccountStatus, BundleAccount, PlainAccount, StorageWithOriginalValues, TransitionAccount,
 };
 use revm_interpreter::primitives::{AccountInfo, U256};
 use revm_precompile::HashMap;
@@ -109,10 +110,13 @@ impl CacheAccount {
     }
 
     /// Account got touched and before EIP161 state clear this account is considered created.
+    ///
+    /// Returns [InvalidTransition] if the account is known to be non empty, the account is
+    /// left unchanged.
     pub fn touch_create_pre_eip161(
         &mut self,
         storage: StorageWithOriginalValues,
-    ) -> Option<TransitionAccount> {
+    ) -> Result<Option<TransitionAccount>, InvalidTransition> {
         let previous_status = self.status;
 
         let had_no_info = self
@@ -120,51 +124,66 @@ impl CacheAccount {
             .as_ref()
             .map(|a| a.info.is_empty())
             .unwrap_or_default();
-        self.status = self.status.on_touched_created_pre_eip161(had_no_info)?;
+        // Statuses that are not changed by the touch don't make a transition.
+        if self.status == AccountStatus::LoadedEmptyEIP161
+            || (self.status == AccountStatus::DestroyedChanged && had_no_info)
+        {
+            return Ok(None);
+        }
+        self.status = AccountStatusTransition::apply(
+            self.status,
+            AccountStatusEvent::TouchedCreatedPreEip161,
+        )?;
 
         let plain_storage = storage.iter().map(|(k, v)| (*k, v.present_value)).collect();
         let previous_info = self.account.take().map(|a| a.info);
 
         self.account = Some(PlainAccount::new_empty_with_storage(plain_storage));
 
-        Some(TransitionAccount {
+        Ok(Some(TransitionAccount {
             info: Some(AccountInfo::default()),
             status: self.status,
             previous_info,
             previous_status,
             storage,
             storage_was_destroyed: false,
-        })
+        }))
     }
 
     /// Touch empty account, related to EIP-161 state clear.
     ///
     /// This account returns the Transition that is used to create the BundleState.
-    pub fn touch_empty_eip161(&mut self) -> Option<TransitionAccount> {
+    ///
+    /// Returns [InvalidTransition] if the account is known to be non empty, the account is
+    /// left unchanged.
+    pub fn touch_empty_eip161(&mut self) -> Result<Option<TransitionAccount>, InvalidTransition> {
         let previous_status = self.status;
 
+        // Set account state to Destroyed as we need to clear the storage if it exist.
+        self.status = AccountStatusTransition::apply(
+            self.status,
+            AccountStatusEvent::TouchedEmptyPostEip161,
+        )?;
+
         // Set account to None.
         let previous_info = self.account.take().map(|acc| acc.info);
 
-        // Set account state to Destroyed as we need to clear the storage if it exist.
-        self.status = self.status.on_touched_empty_post_eip161();
-
         if matches!(
             previous_status,
             AccountStatus::LoadedNotExisting
                 | AccountStatus::Destroyed
                 | AccountStatus::DestroyedAgain
         ) {
-            None
+            Ok(None)
         } else {
-            Some(TransitionAccount {
+            Ok(Some(TransitionAccount {
                 info: None,
                 status: self.status,
                 previous_info,
                 previous_status,
                 storage: HashMap::default(),
                 storage_was_destroyed: true,
-            })
+            }))
         }
     }
 
@@ -186,7 +205,7 @@ impl CacheAccount {
                 status: self.status,
                 previous_info,
                 previous_status,
-                storage: HashMap::new(),
+                storage: HashMap::default(),
                 storage_was_destroyed: true,
             })
         }
@@ -259,7 +278,7 @@ impl CacheAccount {
                 status: self.status,
                 previous_info,
                 previous_status,
-                storage: HashMap::new(),
+                storage: HashMap::default(),
                 storage_was_destroyed: false,
             },
         )
@@ -0,0 +1,240 @@
+use super::BundleState;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use revm_interpreter::primitives::{keccak256, AccountInfo, Address, HashMap, HashSet, B256, U256};
+use std::vec::Vec;
+
+/// Bundle state keyed by the `keccak256` hash of the address and of the storage slot, as used
+/// by the secure state trie.
+///
+/// Accounts and storages are sorted by the hashed address and slots by the hashed slot, so they
+/// can be fed to the trie directly.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HashedBundleState {
+    /// Hashed address and present account info, `None` if the account was destroyed.
+    pub accounts: Vec<(B256, Option<AccountInfo>)>,
+    /// Hashed address and changed storage of the account.
+    pub storages: Vec<(B256, HashedStorage)>,
+}
+
+/// Changed storage of an account keyed by the hashed slot.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HashedStorage {
+    /// Whether the storage needs to be wiped before the slots are applied.
+    pub wiped: bool,
+    /// Hashed slot and present value.
+    pub slots: Vec<(B256, U256)>,
+}
+
+/// Cache of hashed addresses and storage slots.
+///
+/// Reused between calls to [`BundleState::to_hashed_sorted_with_cache`] so that only keys that
+/// were not seen before are hashed.
+#[derive(Clone, Debug, Default)]
+pub struct HashedKeyCache {
+    addresses: HashMap<Address, B256>,
+    slots: HashMap<U256, B256>,
+}
+
+impl HashedKeyCache {
+    /// Returns the number of hashed addresses in the cache.
+    pub fn addresses_len(&self) -> usize {
+        self.addresses.len()
+    }
+
+    /// Returns the number of hashed storage slots in the cache.
+    pub fn slots_len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Clears the cache.
+    pub fn clear(&mut self) {
+        self.addresses.clear();
+        self.slots.clear();
+    }
+
+    /// Hashes the addresses and storage slots of the bundle that are not in the cache.
+    fn extend(&mut self, bundle: &BundleState) {
+        let addresses: Vec<Address> = bundle
+            .state
+            .keys()
+            .filter(|address| !self.addresses.contains_key(*address))
+            .copied()
+            .collect();
+        let slots: HashSet<U256> = bundle
+            .state
+            .values()
+            .flat_map(|account| account.storage.keys())
+            .filter(|slot| !self.slots.contains_key(*slot))
+            .copied()
+            .collect();
+
+        self.addresses
+            .extend(hash_keys(addresses, |address| keccak256(address)));
+        self.slots
+            .extend(hash_keys(slots.into_iter().collect(), |slot| {
+                keccak256(slot.to_be_bytes::<32>())
+            }));
+    }
+}
+
+impl HashedBundleState {
+    /// Creates the hashed view of the bundle, hashing only the keys missing from the cache.
+    pub(crate) fn from_bundle(bundle: &BundleState, cache: &mut HashedKeyCache) -> Self {
+        cache.extend(bundle);
+
+        let mut accounts = Vec::with_capacity(bundle.state.len());
+        let mut storages = Vec::new();
+        for (address, account) in &bundle.state {
+            let hashed_address = cache.addresses[address];
+            accounts.push((hashed_address, account.info.clone()));
+
+            let wiped = account.status.was_destroyed_at_any_point();
+            if !wiped && account.storage.is_empty() {
+                continue;
+            }
+            let mut slots: Vec<(B256, U256)> = account
+                .storage
+                .iter()
+                .map(|(slot, value)| (cache.slots[slot], value.present_value))
+                .collect();
+            sort_by_hashed_key(&mut slots);
+            storages.push((hashed_address, HashedStorage { wiped, slots }));
+        }
+        sort_by_hashed_key(&mut accounts);
+        sort_by_hashed_key(&mut storages);
+
+        Self { accounts, storages }
+    }
+}
+
+/// Hashes the keys, in parallel if the `rayon` feature is enabled.
+fn hash_keys<K: Send>(keys: Vec<K>, hash: impl Fn(&K) -> B256 + Send + Sync) -> Vec<(K, B256)> {
+    #[cfg(feature = "rayon")]
+    let keys = keys.into_par_iter();
+    #[cfg(not(feature = "rayon"))]
+    let keys = keys.into_iter();
+    keys.map(|key| {
+        let hashed = hash(&key);
+        (key, hashed)
+    })
+    .collect()
+}
+
+/// Sorts the entries by the hashed key, in parallel if the `rayon` feature is enabled.
+fn sort_by_hashed_key<T: Send>(entries: &mut [(B256, T)]) {
+    #[cfg(feature = "rayon")]
+    entries.par_sort_unstable_by_key(|(key, _)| *key);
+    #[cfg(not(feature = "rayon"))]
+    entries.sort_unstable_by_key(|(key, _)| *key);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::Bytecode;
+
+    fn bundle(accounts: &[(Address, &[(U256, U256)])]) -> BundleState {
+        BundleState::new(
+            accounts.iter().map(|(address, slots)| {
+                (
+                    *address,
+                    None,
+                    Some(AccountInfo::from_balance(U256::from(1))),
+                    slots
+                        .iter()
+                        .map(|(slot, value)| (*slot, (U256::ZERO, *value)))
+                        .collect(),
+                )
+            }),
+            Vec::<Vec<(Address, Option<Option<AccountInfo>>, Vec<(U256, U256)>)>>::new(),
+            Vec::<(B256, Bytecode)>::new(),
+        )
+    }
+
+    fn expected_storage(slots: &[(U256, U256)]) -> HashedStorage {
+        let mut slots: Vec<(B256, U256)> = slots
+            .iter()
+            .map(|(slot, value)| (keccak256(B256::from(*slot)), *value))
+            .collect();
+        slots.sort_by_key(|(hash, _)| *hash);
+        HashedStorage {
+            wiped: false,
+            slots,
+        }
+    }
+
+    #[test]
+    fn hashed_sorted_keys() {
+        let slots1 = [
+            (U256::from(1), U256::from(10)),
+            (U256::from(2), U256::from(20)),
+        ];
+        let slots3 = [
+            (U256::from(2), U256::from(30)),
+            (U256::from(3), U256::from(40)),
+            (U256::MAX, U256::from(50)),
+        ];
+        let accounts: [(Address, &[(U256, U256)]); 3] = [
+            (Address::with_last_byte(1), &slots1),
+            (Address::with_last_byte(2), &[]),
+            (Address::with_last_byte(3), &slots3),
+        ];
+        let hashed = bundle(&accounts).to_hashed_sorted();
+
+        let mut expected_accounts: Vec<_> = accounts
+            .iter()
+            .map(|(address, _)| {
+                (
+                    keccak256(address.as_slice()),
+                    Some(AccountInfo::from_balance(U256::from(1))),
+                )
+            })
+            .collect();
+        expected_accounts.sort_by_key(|(hash, _)| *hash);
+        assert_eq!(hashed.accounts, expected_accounts);
+
+        let mut expected_storages = vec![
+            (
+                keccak256(accounts[0].0.as_slice()),
+                expected_storage(&slots1),
+            ),
+            (
+                keccak256(accounts[2].0.as_slice()),
+                expected_storage(&slots3),
+            ),
+        ];
+        expected_storages.sort_by_key(|(hash, _)| *hash);
+        assert_eq!(hashed.storages, expected_storages);
+        assert!(hashed
+            .storages
+            .iter()
+            .all(|(_, storage)| storage.slots.windows(2).all(|w| w[0].0 < w[1].0)));
+    }
+
+    #[test]
+    fn hashed_sorted_cache_hashes_new_keys() {
+        let slots = [
+            (U256::from(1), U256::from(10)),
+            (U256::from(2), U256::from(20)),
+        ];
+        let mut state = bundle(&[(Address::with_last_byte(1), &slots)]);
+        let mut cache = HashedKeyCache::default();
+        let first = state.to_hashed_sorted_with_cache(&mut cache);
+        assert_eq!(first, state.to_hashed_sorted());
+        assert_eq!((cache.addresses_len(), cache.slots_len()), (1, 2));
+
+        // new account reuses slot 2 and adds slot 3.
+        let new_slots = [
+            (U256::from(2), U256::from(5)),
+            (U256::from(3), U256::from(6)),
+        ];
+        state
+            .state
+            .extend(bundle(&[(Address::with_last_byte(2), &new_slots)]).state);
+        let second = state.to_hashed_sorted_with_cache(&mut cache);
+        assert_eq!((cache.addresses_len(), cache.slots_len()), (2, 3));
+        assert_eq!(second, state.to_hashed_sorted());
+        assert_eq!(second.accounts.len(), 2);
+    }
+}
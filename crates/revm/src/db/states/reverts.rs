@@ -3,7 +3,7 @@ use super::{
     StorageWithOriginalValues,
 };
 use core::ops::{Deref, DerefMut};
-use revm_interpreter::primitives::{AccountInfo, Address, HashMap, U256};
+use revm_interpreter::primitives::{AccountInfo, Address, HashMap, HashSet, U256};
 use std::vec::Vec;
 
 /// Contains reverts of multiple account in multiple transitions (Transitions as a block).
@@ -45,9 +45,14 @@ impl Reverts {
 
     /// Consume reverts and create plain state reverts.
     ///
+    /// Storage of an account is marked as wiped only on its first wipe. Storage of every later
+    /// incarnation is created inside these reverts and is fully contained in their storage reverts,
+    /// so database wipe should be done only once.
+    ///
     /// Note that account are sorted by address.
     pub fn into_plain_state_reverts(mut self) -> PlainStateReverts {
         let mut state_reverts = PlainStateReverts::with_capacity(self.0.len());
+        let mut wiped_accounts: HashSet<Address> = HashSet::default();
         for reverts in self.0.drain(..) {
             // pessimistically pre-allocate assuming _all_ accounts changed.
             let mut accounts = Vec::with_capacity(reverts.len());
@@ -58,10 +63,11 @@ impl Reverts {
                     AccountInfoRevert::DeleteIt => accounts.push((address, None)),
                     AccountInfoRevert::DoNothing => (),
                 }
-                if revert_account.wipe_storage || !revert_account.storage.is_empty() {
+                let wiped = revert_account.wipe_storage && wiped_accounts.insert(address);
+                if wiped || !revert_account.storage.is_empty() {
                     storage.push(PlainStorageRevert {
                         address,
-                        wiped: revert_account.wipe_storage,
+                        wiped,
                         storage_revert: revert_account.storage.into_iter().collect::<Vec<_>>(),
                     });
                 }
@@ -1,11 +1,13 @@
 use super::{
     bundle_state::BundleRetention, cache::CacheState, plain_account::PlainStorage, BundleState,
-    CacheAccount, StateBuilder, TransitionAccount, TransitionState,
+    CacheAccount, HashedBundleState, HashedKeyCache, InvalidTransition, StateBuilder,
+    TransitionAccount, TransitionState,
 };
 use crate::db::EmptyDB;
 use revm_interpreter::primitives::{
-    db::{Database, DatabaseCommit},
-    hash_map, Account, AccountInfo, Address, Bytecode, HashMap, B256, BLOCK_HASH_HISTORY, U256,
+    db::{Database, DatabaseCommit, DatabaseCommit2PC, PrepareCommitError, PreparedCommit},
+    hash_map, Account, AccountInfo, Address, Bytecode, EvmState, HashMap, B256, BLOCK_HASH_HISTORY,
+    U256,
 };
 use std::{
     boxed::Box,
@@ -56,6 +58,8 @@ pub struct State<DB> {
     /// This map can be used to give different values for block hashes if in case
     /// The fork block is different or some blocks are not saved inside database.
     pub block_hashes: BTreeMap<u64, B256>,
+    /// Hashed addresses and storage slots of the bundle state, see [State::hashed_bundle_sorted].
+    pub hashed_key_cache: HashedKeyCache,
 }
 
 // Have ability to call State::builder without having to specify the type.
@@ -152,6 +156,16 @@ impl<DB: Database> State<DB> {
             .insert_account_with_storage(address, info, storage)
     }
 
+    /// Commits the changes like [DatabaseCommit::commit], but returns [InvalidTransition]
+    /// instead of panicking if an account status transition is invalid.
+    ///
+    /// The cache is partially applied on error, the state should be discarded.
+    pub fn try_commit(&mut self, evm_state: EvmState) -> Result<(), InvalidTransition> {
+        let transitions = self.cache.try_apply_evm_state(evm_state)?;
+        self.apply_transition(transitions);
+        Ok(())
+    }
+
     /// Apply evm transitions to transition state.
     pub fn apply_transition(&mut self, transitions: Vec<(Address, TransitionAccount)>) {
         // add transition to transition state.
@@ -187,9 +201,9 @@ impl<DB: Database> State<DB> {
                 let account = match info {
                     None => CacheAccount::new_loaded_not_existing(),
                     Some(acc) if acc.is_empty() => {
-                        CacheAccount::new_loaded_empty_eip161(HashMap::new())
+                        CacheAccount::new_loaded_empty_eip161(HashMap::default())
                     }
-                    Some(acc) => CacheAccount::new_loaded(acc, HashMap::new()),
+                    Some(acc) => CacheAccount::new_loaded(acc, HashMap::default()),
                 };
                 Ok(entry.insert(account))
             }
@@ -210,6 +224,16 @@ impl<DB: Database> State<DB> {
     pub fn take_bundle(&mut self) -> BundleState {
         core::mem::take(&mut self.bundle_state)
     }
+
+    /// Returns the bundle state keyed by hashed addresses and storage slots, sorted by the
+    /// hashed keys. See [BundleState::to_hashed_sorted].
+    ///
+    /// Hashes are cached inside the [State] so calling this again after more transitions are
+    /// merged only hashes the new keys.
+    pub fn hashed_bundle_sorted(&mut self) -> HashedBundleState {
+        self.bundle_state
+            .to_hashed_sorted_with_cache(&mut self.hashed_key_cache)
+    }
 }
 
 impl<DB: Database> Database for State<DB> {
@@ -291,9 +315,34 @@ impl<DB: Database> Database for State<DB> {
 }
 
 impl<DB: Database> DatabaseCommit for State<DB> {
+    /// # Panics
+    ///
+    /// If an account status transition is invalid. Use [State::try_commit] to recover from it.
     fn commit(&mut self, evm_state: HashMap<Address, Account>) {
-        let transitions = self.cache.apply_evm_state(evm_state);
-        self.apply_transition(transitions);
+        self.try_commit(evm_state)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<DB: Database> DatabaseCommit2PC for State<DB> {
+    /// Validates the changes, all touched accounts need to be loaded in the cache.
+    fn prepare(
+        &self,
+        changes: HashMap<Address, Account>,
+    ) -> Result<PreparedCommit, PrepareCommitError> {
+        let prepared = PreparedCommit::new(changes)?;
+        if let Some(address) = prepared
+            .changes()
+            .keys()
+            .find(|address| !self.cache.accounts.contains_key(*address))
+        {
+            return Err(PrepareCommitError::AccountNotLoaded(*address));
+        }
+        Ok(prepared)
+    }
+
+    fn commit_prepared(&mut self, prepared: PreparedCommit) {
+        self.commit(prepared.into_changes())
     }
 }
 
@@ -301,7 +350,7 @@ impl<DB: Database> DatabaseCommit for State<DB> {
 mod tests {
     use super::*;
     use crate::db::{
-        states::{reverts::AccountInfoRevert, StorageSlot},
+        states::{reverts::AccountInfoRevert, AccountStatusEvent, StorageSlot},
         AccountRevert, AccountStatus, BundleAccount, RevertToSlot,
     };
     use revm_interpreter::primitives::keccak256;
@@ -365,7 +414,7 @@ mod tests {
             nonce: 1,
             ..Default::default()
         };
-        let existing_account_initial_storage = HashMap::<U256, U256>::from([
+        let existing_account_initial_storage = HashMap::<U256, U256>::from_iter([
             (slot1, U256::from(100)), // 0x01 => 100
             (slot2, U256::from(200)), // 0x02 => 200
         ]);
@@ -393,7 +442,7 @@ mod tests {
                     info: Some(existing_account_changed_info.clone()),
                     previous_status: AccountStatus::Loaded,
                     previous_info: Some(existing_account_initial_info.clone()),
-                    storage: HashMap::from([(
+                    storage: HashMap::from_iter([(
                         slot1,
                         StorageSlot::new_changed(
                             *existing_account_initial_storage.get(&slot1).unwrap(),
@@ -426,7 +475,7 @@ mod tests {
                     info: Some(new_account_changed_info2.clone()),
                     previous_status: AccountStatus::InMemoryChange,
                     previous_info: Some(new_account_changed_info),
-                    storage: HashMap::from([(
+                    storage: HashMap::from_iter([(
                         slot1,
                         StorageSlot::new_changed(U256::ZERO, U256::from(1)),
                     )]),
@@ -440,7 +489,7 @@ mod tests {
                     info: Some(existing_account_changed_info.clone()),
                     previous_status: AccountStatus::InMemoryChange,
                     previous_info: Some(existing_account_changed_info.clone()),
-                    storage: HashMap::from([
+                    storage: HashMap::from_iter([
                         (
                             slot1,
                             StorageSlot::new_changed(U256::from(100), U256::from(1_000)),
@@ -477,7 +526,7 @@ mod tests {
                     AccountRevert {
                         account: AccountInfoRevert::DeleteIt,
                         previous_status: AccountStatus::LoadedNotExisting,
-                        storage: HashMap::from([(slot1, RevertToSlot::Some(U256::ZERO))]),
+                        storage: HashMap::from_iter([(slot1, RevertToSlot::Some(U256::ZERO))]),
                         wipe_storage: false,
                     }
                 ),
@@ -486,7 +535,7 @@ mod tests {
                     AccountRevert {
                         account: AccountInfoRevert::RevertTo(existing_account_initial_info.clone()),
                         previous_status: AccountStatus::Loaded,
-                        storage: HashMap::from([
+                        storage: HashMap::from_iter([
                             (
                                 slot1,
                                 RevertToSlot::Some(
@@ -516,7 +565,7 @@ mod tests {
                 info: Some(new_account_changed_info2),
                 original_info: None,
                 status: AccountStatus::InMemoryChange,
-                storage: HashMap::from([(
+                storage: HashMap::from_iter([(
                     slot1,
                     StorageSlot::new_changed(U256::ZERO, U256::from(1))
                 )]),
@@ -532,7 +581,7 @@ mod tests {
                 info: Some(existing_account_changed_info),
                 original_info: Some(existing_account_initial_info),
                 status: AccountStatus::InMemoryChange,
-                storage: HashMap::from([
+                storage: HashMap::from_iter([
                     (
                         slot1,
                         StorageSlot::new_changed(
@@ -620,7 +669,7 @@ mod tests {
                     info: Some(existing_account_with_storage_info.clone()),
                     previous_status: AccountStatus::Loaded,
                     previous_info: Some(existing_account_with_storage_info.clone()),
-                    storage: HashMap::from([
+                    storage: HashMap::from_iter([
                         (
                             slot1,
                             StorageSlot::new_changed(U256::from(1), U256::from(10)),
@@ -661,7 +710,7 @@ mod tests {
                     info: Some(existing_account_with_storage_info.clone()),
                     previous_status: AccountStatus::Changed,
                     previous_info: Some(existing_account_with_storage_info.clone()),
-                    storage: HashMap::from([
+                    storage: HashMap::from_iter([
                         (
                             slot1,
                             StorageSlot::new_changed(U256::from(10), U256::from(1)),
@@ -718,7 +767,7 @@ mod tests {
                 info: Some(existing_account_info.clone()),
                 previous_status: AccountStatus::Destroyed,
                 previous_info: None,
-                storage: HashMap::from([(
+                storage: HashMap::from_iter([(
                     slot1,
                     StorageSlot::new_changed(U256::ZERO, U256::from(1)),
                 )]),
@@ -748,7 +797,7 @@ mod tests {
                 info: Some(existing_account_info.clone()),
                 previous_status: AccountStatus::DestroyedAgain,
                 previous_info: None,
-                storage: HashMap::from([(
+                storage: HashMap::from_iter([(
                     slot2,
                     StorageSlot::new_changed(U256::ZERO, U256::from(2)),
                 )]),
@@ -762,12 +811,12 @@ mod tests {
 
         assert_eq!(
             bundle_state.state,
-            HashMap::from([(
+            HashMap::from_iter([(
                 existing_account_address,
                 BundleAccount {
                     info: Some(existing_account_info.clone()),
                     original_info: Some(existing_account_info.clone()),
-                    storage: HashMap::from([(
+                    storage: HashMap::from_iter([(
                         slot2,
                         StorageSlot::new_changed(U256::ZERO, U256::from(2))
                     )]),
@@ -783,10 +832,178 @@ mod tests {
                 AccountRevert {
                     account: AccountInfoRevert::DoNothing,
                     previous_status: AccountStatus::Loaded,
-                    storage: HashMap::from([(slot2, RevertToSlot::Destroyed)]),
+                    storage: HashMap::from_iter([(slot2, RevertToSlot::Destroyed)]),
                     wipe_storage: true,
                 }
             )])])
         )
     }
+
+    /// Checks that reverting an account destroyed, re-created and destroyed again
+    /// reconstructs every incarnation, ending with the state before the destroys.
+    #[test]
+    fn selfdestruct_twice_reverts_to_previous_state() {
+        let mut state = State::builder().with_bundle_update().build();
+
+        let address = Address::from_slice(&[0x1; 20]);
+        let info = |nonce| AccountInfo {
+            nonce,
+            ..Default::default()
+        };
+        let (slot1, slot2, slot3) = (U256::from(1), U256::from(2), U256::from(3));
+
+        // Existing account changes slot 0x01, this is the state that should be restored.
+        state.apply_transition(Vec::from([(
+            address,
+            TransitionAccount {
+                status: AccountStatus::Changed,
+                info: Some(info(2)),
+                previous_status: AccountStatus::Loaded,
+                previous_info: Some(info(1)),
+                storage: HashMap::from_iter([(
+                    slot1,
+                    StorageSlot::new_changed(U256::from(1), U256::from(10)),
+                )]),
+                storage_was_destroyed: false,
+            },
+        )]));
+        state.merge_transitions(BundleRetention::Reverts);
+        let previous_state = state.bundle_state.state.clone();
+
+        // destroy -> create2 and store slot 0x02 -> destroy -> create2 and store slot 0x03.
+        let transitions = [
+            TransitionAccount {
+                status: AccountStatus::Destroyed,
+                info: None,
+                previous_status: AccountStatus::Changed,
+                previous_info: Some(info(2)),
+                storage: HashMap::default(),
+                storage_was_destroyed: true,
+            },
+            TransitionAccount {
+                status: AccountStatus::DestroyedChanged,
+                info: Some(info(3)),
+                previous_status: AccountStatus::Destroyed,
+                previous_info: None,
+                storage: HashMap::from_iter([(
+                    slot2,
+                    StorageSlot::new_changed(U256::ZERO, U256::from(2)),
+                )]),
+                storage_was_destroyed: false,
+            },
+            TransitionAccount {
+                status: AccountStatus::DestroyedAgain,
+                info: None,
+                previous_status: AccountStatus::DestroyedChanged,
+                previous_info: Some(info(3)),
+                storage: HashMap::default(),
+                storage_was_destroyed: true,
+            },
+            TransitionAccount {
+                status: AccountStatus::DestroyedChanged,
+                info: Some(info(4)),
+                previous_status: AccountStatus::DestroyedAgain,
+                previous_info: None,
+                storage: HashMap::from_iter([(
+                    slot3,
+                    StorageSlot::new_changed(U256::ZERO, U256::from(3)),
+                )]),
+                storage_was_destroyed: false,
+            },
+        ];
+        for transition in transitions {
+            state.apply_transition(Vec::from([(address, transition)]));
+            state.merge_transitions(BundleRetention::Reverts);
+        }
+
+        let mut bundle_state = state.take_bundle();
+
+        // both destroys wipe the storage, but database wipe is done only on the first one.
+        let wipes = bundle_state
+            .reverts
+            .iter()
+            .map(|reverts| reverts.iter().any(|(_, revert)| revert.wipe_storage))
+            .collect::<Vec<_>>();
+        assert_eq!(wipes, [false, true, false, true, false]);
+        let plain_wipes = bundle_state
+            .reverts
+            .clone()
+            .into_plain_state_reverts()
+            .storage
+            .iter()
+            .map(|reverts| reverts.iter().any(|revert| revert.wiped))
+            .collect::<Vec<_>>();
+        assert_eq!(plain_wipes, [false, true, false, false, false]);
+
+        // revert the second incarnation.
+        bundle_state.revert(2);
+        let account = &bundle_state.state[&address];
+        assert_eq!(account.status, AccountStatus::DestroyedChanged);
+        assert_eq!(account.info, Some(info(3)));
+        assert_eq!(account.storage.len(), 1);
+        assert_eq!(account.storage[&slot2].present_value, U256::from(2));
+
+        // revert to the state before the destroys.
+        bundle_state.revert(2);
+        let account = &bundle_state.state[&address];
+        let previous_account = &previous_state[&address];
+        assert_eq!(account.status, previous_account.status);
+        assert_eq!(account.info, previous_account.info);
+        assert_eq!(account.storage.len(), previous_account.storage.len());
+        for (slot, value) in &previous_account.storage {
+            assert_eq!(account.storage[slot].present_value, value.present_value);
+        }
+    }
+
+    #[test]
+    fn hashed_bundle_sorted_after_more_transitions() {
+        let mut state = State::builder().with_bundle_update().build();
+        let created = |address: Address| {
+            (
+                address,
+                TransitionAccount {
+                    status: AccountStatus::InMemoryChange,
+                    info: Some(AccountInfo::from_balance(U256::from(1))),
+                    previous_status: AccountStatus::LoadedNotExisting,
+                    previous_info: None,
+                    ..Default::default()
+                },
+            )
+        };
+
+        state.apply_transition(Vec::from([created(Address::with_last_byte(1))]));
+        state.merge_transitions(BundleRetention::Reverts);
+        let hashed = state.hashed_bundle_sorted();
+        assert_eq!(hashed.accounts.len(), 1);
+        assert_eq!(state.hashed_key_cache.addresses_len(), 1);
+
+        state.apply_transition(Vec::from([created(Address::with_last_byte(2))]));
+        state.merge_transitions(BundleRetention::Reverts);
+        let hashed = state.hashed_bundle_sorted();
+        assert_eq!(hashed, state.bundle_state.to_hashed_sorted());
+        assert_eq!(hashed.accounts.len(), 2);
+        assert_eq!(state.hashed_key_cache.addresses_len(), 2);
+    }
+
+    #[test]
+    fn invalid_transition_is_returned() {
+        let address = Address::with_last_byte(1);
+        let mut state = State::builder().build();
+        state.insert_account(address, AccountInfo::from_balance(U256::from(1)));
+
+        // the account is loaded as non empty, it can't be touched as empty.
+        let mut account = Account::new_not_existing();
+        account.mark_touch();
+        let err = state
+            .try_commit(HashMap::from_iter([(address, account)]))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            InvalidTransition {
+                status: AccountStatus::Loaded,
+                event: AccountStatusEvent::TouchedEmptyPostEip161,
+            }
+        );
+        assert_eq!(state.cache.accounts[&address].status, AccountStatus::Loaded);
+    }
 }
@@ -1,4 +1,4 @@
-use super::{cache::CacheState, state::DBBox, BundleState, State, TransitionState};
+use super::{cache::CacheState, state::DBBox, BundleState, HashedKeyCache, State, TransitionState};
 use crate::db::EmptyDB;
 use revm_interpreter::primitives::{
     db::{Database, DatabaseRef, WrapDatabaseRef},
@@ -166,6 +166,7 @@ impl<DB: Database> StateBuilder<DB> {
             bundle_state: self.with_bundle_prestate.unwrap_or_default(),
             use_preloaded_bundle,
             block_hashes: self.with_block_hashes,
+            hashed_key_cache: HashedKeyCache::default(),
         }
     }
 }
@@ -0,0 +1,370 @@
+use crate::{
+    db::DatabaseRef,
+    primitives::{
+        Account, Address, Bytes, EVMError, Env, EnvWithHandlerCfg, EvmState, EvmStorageSlot,
+        ExecutionResult, HaltReason, HandlerCfg, Log, ResultAndState, SpecId, StorageDiff,
+        SuccessReason, TxEnv, B256, U256,
+    },
+    Evm,
+};
+use std::{boxed::Box, collections::BTreeMap, vec::Vec};
+
+/// Value of a field that differs between the left and the right execution.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FieldDiff<T> {
+    /// Value in the left execution.
+    pub left: T,
+    /// Value in the right execution.
+    pub right: T,
+}
+
+impl<T: PartialEq> FieldDiff<T> {
+    /// Returns the diff if the values are different.
+    pub fn new(left: T, right: T) -> Option<Self> {
+        (left != right).then_some(Self { left, right })
+    }
+}
+
+/// How the execution ended, without the gas, logs and output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ResultKind {
+    /// Execution was successful.
+    Success(SuccessReason),
+    /// Execution was reverted by `REVERT`.
+    Revert,
+    /// Execution halted.
+    Halt(HaltReason),
+}
+
+impl From<&ExecutionResult> for ResultKind {
+    fn from(result: &ExecutionResult) -> Self {
+        match result {
+            ExecutionResult::Success { reason, .. } => Self::Success(*reason),
+            ExecutionResult::Revert { .. } => Self::Revert,
+            ExecutionResult::Halt { reason, .. } => Self::Halt(*reason),
+        }
+    }
+}
+
+/// Difference of the [ExecutionResult]s.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ResultDiff {
+    /// How the execution ended.
+    pub kind: Option<FieldDiff<ResultKind>>,
+    /// Returned or reverted data.
+    pub output: Option<FieldDiff<Option<Bytes>>>,
+}
+
+impl ResultDiff {
+    /// Returns `true` if the results are the same.
+    pub fn is_empty(&self) -> bool {
+        self.kind.is_none() && self.output.is_none()
+    }
+}
+
+/// Difference of the gas used and refunded.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GasDiff {
+    /// Gas used by the transaction.
+    pub used: Option<FieldDiff<u64>>,
+    /// Gas refunded to the caller, zero if the execution did not succeed.
+    pub refunded: Option<FieldDiff<u64>>,
+}
+
+impl GasDiff {
+    /// Returns `true` if the gas is the same.
+    pub fn is_empty(&self) -> bool {
+        self.used.is_none() && self.refunded.is_none()
+    }
+}
+
+/// Difference of a single account after both executions.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AccountDiff {
+    /// Account balance.
+    pub balance: Option<FieldDiff<U256>>,
+    /// Account nonce.
+    pub nonce: Option<FieldDiff<u64>>,
+    /// Hash of the account code.
+    pub code_hash: Option<FieldDiff<B256>>,
+    /// Storage slots that differ, `previous` is the left value and `present` the right one.
+    pub storage: StorageDiff,
+}
+
+impl AccountDiff {
+    /// Returns `true` if the account is the same.
+    pub fn is_empty(&self) -> bool {
+        self.balance.is_none()
+            && self.nonce.is_none()
+            && self.code_hash.is_none()
+            && self.storage.slots.is_empty()
+    }
+}
+
+/// Differences between two executions of the same transaction, see [DiffExecutor].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExecutionDiff {
+    /// Difference of the execution results.
+    pub result_diff: ResultDiff,
+    /// Accounts that differ.
+    pub state_diff: BTreeMap<Address, AccountDiff>,
+    /// Difference of the gas used and refunded.
+    pub gas_diff: GasDiff,
+    /// Logs that differ by index. Missing logs are `None`.
+    pub log_diff: BTreeMap<usize, FieldDiff<Option<Log>>>,
+}
+
+impl ExecutionDiff {
+    /// Returns `true` if both executions have the same outcome.
+    pub fn is_empty(&self) -> bool {
+        self.result_diff.is_empty()
+            && self.state_diff.is_empty()
+            && self.gas_diff.is_empty()
+            && self.log_diff.is_empty()
+    }
+}
+
+/// Executes transactions under two handler configurations and reports the differences.
+///
+/// Each execution runs in its own [Evm] over a clone of the database, so the journaled
+/// states are isolated and nothing is committed. This is useful for checking how the
+/// behavior of a transaction changes between two specs.
+#[derive(Clone, Debug)]
+pub struct DiffExecutor<DB> {
+    db: DB,
+    env: Box<Env>,
+    left: HandlerCfg,
+    right: HandlerCfg,
+}
+
+impl<DB: DatabaseRef + Clone> DiffExecutor<DB> {
+    /// Creates a new executor comparing the `left` and `right` handler configurations.
+    pub fn new(db: DB, left: HandlerCfg, right: HandlerCfg) -> Self {
+        Self {
+            db,
+            env: Box::default(),
+            left,
+            right,
+        }
+    }
+
+    /// Creates a new executor comparing two mainnet specs.
+    pub fn new_with_spec_ids(db: DB, left: SpecId, right: SpecId) -> Self {
+        Self::new(db, HandlerCfg::new(left), HandlerCfg::new(right))
+    }
+
+    /// Sets the environment the transactions are executed in. Its transaction is ignored.
+    pub fn with_env(mut self, env: Box<Env>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Returns the database both executions start from.
+    pub fn db(&self) -> &DB {
+        &self.db
+    }
+
+    /// Executes `tx` with both configurations and returns the differences.
+    pub fn run(&self, tx: TxEnv) -> Result<ExecutionDiff, EVMError<DB::Error>> {
+        let left = self.execute(self.left, tx.clone())?;
+        let right = self.execute(self.right, tx)?;
+        self.diff(left, right)
+    }
+
+    fn execute(
+        &self,
+        handler_cfg: HandlerCfg,
+        tx: TxEnv,
+    ) -> Result<ResultAndState, EVMError<DB::Error>> {
+        let mut env = self.env.clone();
+        *env.tx_mut() = tx;
+        Evm::builder()
+            .with_ref_db(self.db.clone())
+            .with_env_with_handler_cfg(EnvWithHandlerCfg::new(env, handler_cfg))
+            .build()
+            .transact()
+    }
+
+    fn diff(
+        &self,
+        left: ResultAndState,
+        right: ResultAndState,
+    ) -> Result<ExecutionDiff, EVMError<DB::Error>> {
+        let (left_result, right_result) = (&left.result, &right.result);
+        let result_diff = ResultDiff {
+            kind: FieldDiff::new(left_result.into(), right_result.into()),
+            output: FieldDiff::new(
+                left_result.output().cloned(),
+                right_result.output().cloned(),
+            ),
+        };
+        let gas_diff = GasDiff {
+            used: FieldDiff::new(left_result.gas_used(), right_result.gas_used()),
+            refunded: FieldDiff::new(gas_refunded(left_result), gas_refunded(right_result)),
+        };
+
+        let (left_logs, right_logs) = (left_result.logs(), right_result.logs());
+        let log_diff = (0..left_logs.len().max(right_logs.len()))
+            .filter_map(|i| {
+                FieldDiff::new(left_logs.get(i).cloned(), right_logs.get(i).cloned())
+                    .map(|diff| (i, diff))
+            })
+            .collect();
+
+        Ok(ExecutionDiff {
+            result_diff,
+            state_diff: self.state_diff(left.state, right.state)?,
+            gas_diff,
+            log_diff,
+        })
+    }
+
+    /// Compares the accounts changed by either execution.
+    ///
+    /// Accounts and slots that are not present in one of the states are taken from the
+    /// database, as that is their value after the execution.
+    fn state_diff(
+        &self,
+        mut left: EvmState,
+        mut right: EvmState,
+    ) -> Result<BTreeMap<Address, AccountDiff>, EVMError<DB::Error>> {
+        let addresses: Vec<Address> = left
+            .iter()
+            .chain(right.iter())
+            .filter(|(_, account)| account.is_touched())
+            .map(|(address, _)| *address)
+            .collect();
+
+        let mut diffs = BTreeMap::new();
+        for address in addresses {
+            if diffs.contains_key(&address) {
+                continue;
+            }
+            let mut left_account = self.account_or_load(&mut left, address)?;
+            let mut right_account = self.account_or_load(&mut right, address)?;
+            fill_missing_slots(&mut left_account, &right_account);
+            fill_missing_slots(&mut right_account, &left_account);
+
+            let (left_info, right_info) = (&left_account.info, &right_account.info);
+            let mut storage = left_account.storage_diff(&right_account);
+            storage.slots.retain(|_, slot| slot.is_changed());
+            let diff = AccountDiff {
+                balance: FieldDiff::new(left_info.balance, right_info.balance),
+                nonce: FieldDiff::new(left_info.nonce, right_info.nonce),
+                code_hash: FieldDiff::new(left_info.code_hash, right_info.code_hash),
+                storage,
+            };
+            diffs.insert(address, diff);
+        }
+        diffs.retain(|_, diff| !diff.is_empty());
+        Ok(diffs)
+    }
+
+    fn account_or_load(
+        &self,
+        state: &mut EvmState,
+        address: Address,
+    ) -> Result<Account, EVMError<DB::Error>> {
+        if let Some(account) = state.remove(&address) {
+            return Ok(account);
+        }
+        let info = self
+            .db
+            .basic_ref(address)
+            .map_err(EVMError::Database)?
+            .unwrap_or_default();
+        Ok(info.into())
+    }
+}
+
+/// Gas refunded by a successful execution.
+fn gas_refunded(result: &ExecutionResult) -> u64 {
+    match result {
+        ExecutionResult::Success { gas_refunded, .. } => *gas_refunded,
+        _ => 0,
+    }
+}
+
+/// Adds the slots accessed only by `other` to `account` with their original value.
+fn fill_missing_slots(account: &mut Account, other: &Account) {
+    for (key, slot) in &other.storage {
+        account
+            .storage
+            .entry(*key)
+            .or_insert_with(|| EvmStorageSlot::new(slot.original_value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::InMemoryDB,
+        primitives::AccountInfo,
+        test_utils::{contract_db, contract_tx, CALLER, CONTRACT},
+    };
+
+    fn executor(code: Bytes) -> DiffExecutor<InMemoryDB> {
+        let mut db = contract_db(code);
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10_000_000)));
+        DiffExecutor::new_with_spec_ids(db, SpecId::SHANGHAI, SpecId::CANCUN)
+    }
+
+    fn tx(value: U256) -> TxEnv {
+        TxEnv {
+            value,
+            gas_price: U256::from(1),
+            ..contract_tx()
+        }
+    }
+
+    #[test]
+    fn tstore_halts_before_cancun() {
+        // TSTORE(0, 1), SSTORE(0, 1), STOP
+        let code = Bytes::from_static(&[0x60, 0x01, 0x5f, 0x5d, 0x60, 0x01, 0x5f, 0x55, 0x00]);
+        let diff = executor(code).run(tx(U256::ZERO)).unwrap();
+
+        assert_eq!(
+            diff.result_diff.kind,
+            FieldDiff::new(
+                ResultKind::Halt(HaltReason::NotActivated),
+                ResultKind::Success(SuccessReason::Stop)
+            )
+        );
+        assert_eq!(
+            diff.result_diff.output,
+            FieldDiff::new(None, Some(Bytes::new()))
+        );
+        let gas_used = diff.gas_diff.used.clone().unwrap();
+        assert_eq!(gas_used.left, 100_000);
+        assert!(gas_used.right < gas_used.left);
+        assert_eq!(diff.gas_diff.refunded, None);
+        assert!(diff.log_diff.is_empty());
+
+        let contract = &diff.state_diff[&CONTRACT];
+        assert_eq!(contract.balance, None);
+        assert_eq!(contract.storage.slots.len(), 1);
+        let slot = contract.storage.get(&U256::ZERO).unwrap();
+        assert_eq!((slot.previous, slot.present), (U256::ZERO, U256::from(1)));
+
+        // Caller pays for the gas used, the beneficiary receives it.
+        let caller = &diff.state_diff[&CALLER];
+        assert_eq!(
+            caller.balance,
+            FieldDiff::new(
+                U256::from(10_000_000 - gas_used.left),
+                U256::from(10_000_000 - gas_used.right)
+            )
+        );
+        assert_eq!(caller.nonce, None);
+        assert!(diff.state_diff[&Address::ZERO].balance.is_some());
+        assert_eq!(diff.state_diff.len(), 3);
+    }
+
+    #[test]
+    fn transfer_has_no_diff() {
+        let diff = executor(Bytes::new()).run(tx(U256::from(10))).unwrap();
+        assert!(diff.is_empty(), "{diff:?}");
+        assert_eq!(diff, ExecutionDiff::default());
+    }
+}
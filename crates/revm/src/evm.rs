@@ -1,21 +1,28 @@
 use crate::{
     builder::{EvmBuilder, HandlerStage, SetGenericStage},
-    db::{Database, DatabaseCommit, EmptyDB},
+    db::{Database, DatabaseCommit, DatabaseCommit2PC, EmptyDB, ForkableDatabase, PreparedCommit},
     handler::Handler,
+    inspector_handle_register,
     interpreter::{
-        CallInputs, CreateInputs, EOFCreateInputs, Host, InterpreterAction, SharedMemory,
+        gas::InitialAndFloorGas,
+        opcode::{BoxedInstruction, InstructionTables},
+        CallInputs, CallOutcome, CallValue, CreateInputs, EOFCreateInputs, Gas, HostRead,
+        InstructionResult, InterpreterAction, InterpreterResult, SharedMemory, Suspended,
+        EMPTY_SHARED_MEMORY,
     },
     primitives::{
-        specification::SpecId, BlockEnv, CfgEnv, EVMError, EVMResult, EnvWithHandlerCfg,
-        ExecutionResult, HandlerCfg, ResultAndState, TxEnv, TxKind, EOF_MAGIC_BYTES,
+        specification::SpecId, BlockEnv, Bytes, CfgEnv, EVMError, EVMResult, EnvWithHandlerCfg,
+        ExecutionResult, HandlerCfg, InvalidTransaction, ResultAndState, TxEnv, TxKind,
+        EOF_MAGIC_BYTES, U256,
     },
-    Context, ContextWithHandlerCfg, Frame, FrameOrResult, FrameResult,
+    spans::{phase_span, FrameSpans},
+    CallStackExhaustion, Context, ContextWithHandlerCfg, EvmContext, Frame, FrameOrResult,
+    FrameResult, GetInspector,
 };
-use core::fmt;
-use std::{boxed::Box, vec::Vec};
+use core::{fmt, mem};
+use std::{boxed::Box, rc::Rc, vec::Vec};
 
-/// EVM call stack limit.
-pub const CALL_STACK_LIMIT: u64 = 1024;
+pub use crate::primitives::CALL_STACK_LIMIT;
 
 /// EVM instance containing both internal EVM context and external context
 /// and the handler that dictates the logic of EVM (or hardfork specification).
@@ -25,6 +32,11 @@ pub struct Evm<'a, EXT, DB: Database> {
     /// Handler is a component of the of EVM that contains all the logic. Handler contains specification id
     /// and it different depending on the specified fork.
     pub handler: Handler<'a, Context<EXT, DB>, EXT, DB>,
+    /// Transaction generation recorded by [`Evm::preverify_transaction`].
+    preverified_tx_generation: Option<u64>,
+    /// Set while the journal of a transaction suspended by [`Evm::transact_resumable`] is
+    /// kept in the EVM.
+    has_suspended_tx: bool,
 }
 
 impl<EXT, DB> fmt::Debug for Evm<'_, EXT, DB>
@@ -43,12 +55,92 @@ where
 impl<EXT, DB: Database + DatabaseCommit> Evm<'_, EXT, DB> {
     /// Commit the changes to the database.
     pub fn transact_commit(&mut self) -> Result<ExecutionResult, EVMError<DB::Error>> {
-        let ResultAndState { result, state } = self.transact()?;
+        let ResultAndState { result, state, .. } = self.transact()?;
         self.context.evm.db.commit(state);
         Ok(result)
     }
 }
 
+impl<EXT, DB: Database + DatabaseCommit2PC> Evm<'_, EXT, DB> {
+    /// Transact transaction and prepare the changes for a two-phase commit.
+    ///
+    /// The database is not modified, commit the changes with [`Evm::commit_prepared`]
+    /// or drop them to abort. Changes that fail to prepare are returned as
+    /// [`EVMError::PrepareCommit`].
+    pub fn transact_prepare(
+        &mut self,
+    ) -> Result<(ExecutionResult, PreparedCommit), EVMError<DB::Error>> {
+        let ResultAndState { result, state, .. } = self.transact()?;
+        let prepared = self.context.evm.db.prepare(state)?;
+        Ok((result, prepared))
+    }
+
+    /// Commits changes prepared with [`Evm::transact_prepare`] to the database.
+    pub fn commit_prepared(&mut self, prepared: PreparedCommit) {
+        self.context.evm.db.commit_prepared(prepared)
+    }
+}
+
+impl<EXT: GetInspector<DB>, DB: Database + DatabaseCommit> Evm<'_, EXT, DB> {
+    /// Inspect transaction and commit the changes to the database.
+    ///
+    /// See [`Evm::inspect`].
+    pub fn inspect_commit(&mut self) -> Result<ExecutionResult, EVMError<DB::Error>> {
+        let ResultAndState { result, state, .. } = self.inspect()?;
+        self.context.evm.db.commit(state);
+        Ok(result)
+    }
+}
+
+impl<'a, EXT: GetInspector<DB> + 'a, DB: Database + 'a> Evm<'a, EXT, DB> {
+    /// Transact transaction with the inspector from the external context.
+    ///
+    /// [inspector_handle_register] is applied to the handler only for this call, it wraps the
+    /// current handles so changes made to the handler are kept. Transactions executed with
+    /// [`Evm::transact`] afterwards are not inspected.
+    /// The Evm should be built without the register, otherwise the inspector is called twice.
+    pub fn inspect(&mut self) -> EVMResult<DB::Error> {
+        self.with_inspector(Self::transact)
+    }
+
+    /// Transact pre-verified transaction with the inspector from the external context.
+    ///
+    /// See [`Evm::inspect`] and [`Evm::transact_preverified`].
+    pub fn inspect_preverified(&mut self) -> EVMResult<DB::Error> {
+        self.with_inspector(Self::transact_preverified)
+    }
+
+    /// Calls `f` with [inspector_handle_register] applied to the handler and restores the
+    /// handler afterwards.
+    ///
+    /// The register wraps only the instruction table and the execution handles. The
+    /// instructions of the inspected table call into the original table, so it is moved back
+    /// when the inspected one is dropped.
+    fn with_inspector<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let table = Rc::new(self.handler.take_instruction_table());
+        let execution = self.handler.execution.clone();
+        let inner = table.clone();
+        self.handler.instruction_table =
+            InstructionTables::Boxed(core::array::from_fn(|opcode| -> BoxedInstruction<'a, _> {
+                let table = inner.clone();
+                Box::new(move |interpreter, host| match &*table {
+                    InstructionTables::Plain(table) => table[opcode](interpreter, host),
+                    InstructionTables::Boxed(table) => table[opcode](interpreter, host),
+                })
+            }));
+        drop(inner);
+        inspector_handle_register(&mut self.handler);
+
+        let output = f(self);
+
+        drop(self.handler.take_instruction_table());
+        self.handler.instruction_table = Rc::into_inner(table)
+            .expect("instruction table is only shared with the inspected table");
+        self.handler.execution = execution;
+        output
+    }
+}
+
 impl<'a> Evm<'a, (), EmptyDB> {
     /// Returns evm builder with empty database and empty external context.
     pub fn builder() -> EvmBuilder<'a, SetGenericStage, (), EmptyDB> {
@@ -63,7 +155,12 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
         handler: Handler<'a, Context<EXT, DB>, EXT, DB>,
     ) -> Evm<'a, EXT, DB> {
         context.evm.journaled_state.set_spec_id(handler.cfg.spec_id);
-        Evm { context, handler }
+        Evm {
+            context,
+            handler,
+            preverified_tx_generation: None,
+            has_suspended_tx: false,
+        }
     }
 
     /// Allow for evm setting to be modified by feeding current evm
@@ -75,25 +172,82 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
     /// Runs main call loop.
     #[inline]
     pub fn run_the_loop(&mut self, first_frame: Frame) -> Result<FrameResult, EVMError<DB::Error>> {
+        match self.run_frames(first_frame, None, false)? {
+            Ok(result) => Ok(result),
+            Err(_) => unreachable!("only resumable runs are suspended"),
+        }
+    }
+
+    /// Runs the call loop starting with `first_frame`.
+    ///
+    /// If `resumable` is set the first frame is run with [`Interpreter::run_resumable`] and is
+    /// returned together with its [`Suspended`] execution when it runs out of gas. The memory
+    /// of the suspended execution is passed as `shared_memory` to continue it.
+    ///
+    /// [`Interpreter::run_resumable`]: crate::interpreter::Interpreter::run_resumable
+    fn run_frames(
+        &mut self,
+        first_frame: Frame,
+        shared_memory: Option<SharedMemory>,
+        resumable: bool,
+    ) -> FramesResult<DB::Error> {
         let mut call_stack: Vec<Frame> = Vec::with_capacity(1025);
+        let mut frame_spans = FrameSpans::default();
+        frame_spans.push(
+            &first_frame,
+            self.context.evm.journaled_state.depth() as usize,
+        );
         call_stack.push(first_frame);
 
-        #[cfg(feature = "memory_limit")]
-        let mut shared_memory =
-            SharedMemory::new_with_memory_limit(self.context.evm.env.cfg.memory_limit);
-        #[cfg(not(feature = "memory_limit"))]
-        let mut shared_memory = SharedMemory::new();
+        let mut shared_memory = match shared_memory {
+            Some(shared_memory) => shared_memory,
+            None => {
+                self.context.evm.call_stack_exhaustion = None;
+
+                #[cfg(feature = "memory_limit")]
+                let mut shared_memory =
+                    SharedMemory::new_with_memory_limit(self.context.evm.env.cfg.memory_limit);
+                #[cfg(not(feature = "memory_limit"))]
+                let mut shared_memory = SharedMemory::new();
 
-        shared_memory.new_context();
+                shared_memory.new_context();
+                shared_memory
+            }
+        };
 
         // Peek the last stack frame.
         let mut stack_frame = call_stack.last_mut().unwrap();
+        // Whether the first frame is on top of the call stack.
+        let mut is_first_frame = true;
 
         loop {
             // Execute the frame.
-            let next_action =
+            let next_action = if resumable && is_first_frame {
+                let interpreter = stack_frame.interpreter_mut();
+                let memory = mem::replace(&mut shared_memory, EMPTY_SHARED_MEMORY);
+                let next_action = match &self.handler.instruction_table {
+                    InstructionTables::Plain(table) => {
+                        interpreter.run_resumable(memory, table, &mut self.context)
+                    }
+                    InstructionTables::Boxed(table) => {
+                        interpreter.run_resumable(memory, table, &mut self.context)
+                    }
+                };
+                match next_action {
+                    Ok(next_action) => {
+                        shared_memory = interpreter.take_memory();
+                        next_action
+                    }
+                    Err(suspended) => {
+                        self.context.evm.take_error()?;
+                        let frame = call_stack.pop().expect("first frame is suspended");
+                        return Ok(Err((frame, suspended)));
+                    }
+                }
+            } else {
                 self.handler
-                    .execute_frame(stack_frame, &mut shared_memory, &mut self.context)?;
+                    .execute_frame(stack_frame, &mut shared_memory, &mut self.context)?
+            };
 
             // Take error and break the loop, if any.
             // This error can be set in the Interpreter when it interacts with the context.
@@ -101,7 +255,13 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
 
             let exec = &mut self.handler.execution;
             let frame_or_result = match next_action {
-                InterpreterAction::Call { inputs } => exec.call(&mut self.context, inputs)?,
+                InterpreterAction::Call { inputs } => {
+                    // the memory is lent to the context, the input of the call is read from it.
+                    mem::swap(&mut shared_memory, &mut self.context.evm.caller_memory);
+                    let frame_or_result = exec.call(&mut self.context, inputs);
+                    mem::swap(&mut shared_memory, &mut self.context.evm.caller_memory);
+                    frame_or_result?
+                }
                 InterpreterAction::Create { inputs } => exec.create(&mut self.context, inputs)?,
                 InterpreterAction::EOFCreate { inputs } => {
                     exec.eofcreate(&mut self.context, inputs)?
@@ -115,8 +275,10 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
                         .pop()
                         .expect("We just returned from Interpreter frame");
 
+                    // Gas above the frame gas cap is returned to the parent.
+                    let gas_excess = returned_frame.frame_data().gas_excess;
                     let ctx = &mut self.context;
-                    FrameOrResult::Result(match returned_frame {
+                    let mut result = match returned_frame {
                         Frame::Call(frame) => {
                             // return_call
                             FrameResult::Call(exec.call_return(ctx, frame, result)?)
@@ -129,21 +291,30 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
                             // return_eofcreate
                             FrameResult::EOFCreate(exec.eofcreate_return(ctx, frame, result)?)
                         }
-                    })
+                    };
+                    result.gas_mut().extend(gas_excess);
+                    frame_spans.pop(&result);
+                    FrameOrResult::Result(result)
                 }
                 InterpreterAction::None => unreachable!("InterpreterAction::None is not expected"),
             };
+            if self.context.evm.trace_call_stack {
+                record_call_stack_exhaustion(&mut self.context.evm, &call_stack, &frame_or_result);
+            }
             // handle result
             match frame_or_result {
                 FrameOrResult::Frame(frame) => {
                     shared_memory.new_context();
+                    frame_spans.push(&frame, self.context.evm.journaled_state.depth() as usize);
                     call_stack.push(frame);
                     stack_frame = call_stack.last_mut().unwrap();
+                    is_first_frame = false;
                 }
                 FrameOrResult::Result(result) => {
+                    is_first_frame = call_stack.len() == 1;
                     let Some(top_frame) = call_stack.last_mut() else {
                         // Break the loop if there are no more frames.
-                        return Ok(result);
+                        return Ok(Ok(result));
                     };
                     stack_frame = top_frame;
                     let ctx = &mut self.context;
@@ -168,6 +339,54 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
     }
 }
 
+/// Result of the first frame, or the first frame and its suspended execution.
+type FramesResult<E> = Result<Result<FrameResult, (Frame, Suspended)>, EVMError<E>>;
+
+/// Records the `call_stack` if the new frame could not be created because the call stack
+/// limit was exceeded. Only the first exhaustion in the transaction is recorded.
+fn record_call_stack_exhaustion<DB: Database>(
+    context: &mut EvmContext<DB>,
+    call_stack: &[Frame],
+    frame_or_result: &FrameOrResult,
+) {
+    let FrameOrResult::Result(result) = frame_or_result else {
+        return;
+    };
+    if result.instruction_result() != InstructionResult::CallTooDeep
+        || context.call_stack_exhaustion.is_some()
+    {
+        return;
+    }
+    context.call_stack_exhaustion = Some(CallStackExhaustion {
+        depth: context.journaled_state.depth(),
+        addresses: call_stack
+            .iter()
+            .map(|frame| frame.interpreter().contract.target_address)
+            .collect(),
+    });
+}
+
+impl<EXT: Clone, DB: ForkableDatabase> Evm<'_, EXT, DB>
+where
+    DB::Error: Clone,
+{
+    /// Forks the EVM, the fork starts from the current state and both continue independently.
+    ///
+    /// Only the local changes of the database, the journal, the environment and the external
+    /// context are cloned, the base database is shared, see [`ForkableDatabase`].
+    /// The handler of the fork is rebuilt from the registers with [`Handler::rebuild`].
+    ///
+    /// Returns `None` if the handler has a boxed register, it can't be applied to the fork.
+    pub fn fork(&self) -> Option<Self> {
+        Some(Evm {
+            context: self.context.clone(),
+            handler: self.handler.rebuild()?,
+            preverified_tx_generation: self.preverified_tx_generation,
+            has_suspended_tx: self.has_suspended_tx,
+        })
+    }
+}
+
 impl<EXT, DB: Database> Evm<'_, EXT, DB> {
     /// Returns specification (hardfork) that the EVM is instanced with.
     ///
@@ -178,9 +397,16 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
 
     /// Pre verify transaction by checking Environment, initial gas spend and if caller
     /// has enough balance to pay for the gas.
+    ///
+    /// On success, transaction generation is recorded, see [`Evm::transact_preverified`].
     #[inline]
     pub fn preverify_transaction(&mut self) -> Result<(), EVMError<DB::Error>> {
+        self.clear_suspended_tx();
+        self.context.evm.is_validation_only = true;
         let output = self.preverify_transaction_inner().map(|_| ());
+        self.context.evm.is_validation_only = false;
+        self.preverified_tx_generation =
+            output.is_ok().then(|| self.context.evm.env.tx_generation());
         self.clear();
         output
     }
@@ -190,20 +416,51 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         self.handler.post_execution().clear(&mut self.context);
     }
 
+    /// Clears the journal of the suspended transaction if it was dropped without being
+    /// resumed or discarded.
+    fn clear_suspended_tx(&mut self) {
+        if mem::take(&mut self.has_suspended_tx) {
+            self.clear();
+        }
+    }
+
+    /// Enables database read checks for the transaction if
+    /// [`CfgEnv::paranoid_db_checks`] is set.
+    #[inline]
+    fn set_db_read_checks(&mut self) {
+        let enabled = self.context.evm.env.cfg.paranoid_db_checks;
+        self.context.evm.journaled_state.set_db_read_checks(enabled);
+    }
+
     /// Transact pre-verified transaction
     ///
     /// This function will not validate the transaction.
+    ///
+    /// If transaction was modified with [`Env::tx_mut`](crate::primitives::Env::tx_mut) after
+    /// [`Evm::preverify_transaction`], it is validated again if
+    /// [`CfgEnv::revalidate_stale_tx`] is set, otherwise [`EVMError::StaleValidation`] is returned.
     #[inline]
     pub fn transact_preverified(&mut self) -> EVMResult<DB::Error> {
-        let initial_gas_spend = self
-            .handler
-            .validation()
-            .initial_tx_gas(&self.context.evm.env)
-            .map_err(|e| {
+        let _span = phase_span!("transact");
+        self.clear_suspended_tx();
+        let is_stale = self
+            .preverified_tx_generation
+            .take()
+            .is_some_and(|generation| generation != self.context.evm.env.tx_generation());
+
+        let init_and_floor_gas = if is_stale {
+            if !self.context.evm.env.cfg.revalidate_stale_tx {
                 self.clear();
-                e
-            })?;
-        let output = self.transact_preverified_inner(initial_gas_spend);
+                return Err(EVMError::StaleValidation);
+            }
+            self.preverify_transaction_inner()
+        } else {
+            self.handler
+                .validation()
+                .initial_tx_gas(&self.context.evm.env)
+        }
+        .inspect_err(|_| self.clear())?;
+        let output = self.transact_preverified_inner(init_and_floor_gas);
         let output = self.handler.post_execution().end(&mut self.context, output);
         self.clear();
         output
@@ -211,16 +468,18 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
 
     /// Pre verify transaction inner.
     #[inline]
-    fn preverify_transaction_inner(&mut self) -> Result<u64, EVMError<DB::Error>> {
+    fn preverify_transaction_inner(&mut self) -> Result<InitialAndFloorGas, EVMError<DB::Error>> {
+        let _span = phase_span!("validation");
+        self.set_db_read_checks();
         self.handler.validation().env(&self.context.evm.env)?;
-        let initial_gas_spend = self
+        let init_and_floor_gas = self
             .handler
             .validation()
             .initial_tx_gas(&self.context.evm.env)?;
         self.handler
             .validation()
             .tx_against_state(&mut self.context)?;
-        Ok(initial_gas_spend)
+        Ok(init_and_floor_gas)
     }
 
     /// Transact transaction
@@ -228,17 +487,41 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
     /// This function will validate the transaction.
     #[inline]
     pub fn transact(&mut self) -> EVMResult<DB::Error> {
-        let initial_gas_spend = self.preverify_transaction_inner().map_err(|e| {
-            self.clear();
-            e
-        })?;
+        let _span = phase_span!("transact");
+        self.clear_suspended_tx();
+        let init_and_floor_gas = self
+            .preverify_transaction_inner()
+            .inspect_err(|_| self.clear())?;
 
-        let output = self.transact_preverified_inner(initial_gas_spend);
+        let output = self.transact_preverified_inner(init_and_floor_gas);
         let output = self.handler.post_execution().end(&mut self.context, output);
         self.clear();
         output
     }
 
+    /// Transact transaction without changing the state.
+    ///
+    /// Runs [`Evm::transact`] with [`CfgEnv::force_static`] set, so no account of the returned
+    /// state is touched.
+    pub fn transact_readonly(&mut self) -> EVMResult<DB::Error> {
+        let force_static = mem::replace(&mut self.context.evm.env.cfg.force_static, true);
+        let output = self.transact();
+        self.context.evm.env.cfg.force_static = force_static;
+        output
+    }
+
+    /// Decodes the signed transaction envelope, sets it as the transaction and executes it.
+    ///
+    /// Caller is the recovered signer, see [`TxEnv::from_signed_envelope`]. Decoding errors
+    /// are returned as [`EVMError::TxDecode`].
+    #[cfg(feature = "alloy-consensus")]
+    #[inline]
+    pub fn transact_raw(&mut self, envelope: &[u8]) -> EVMResult<DB::Error> {
+        let (tx, _) = TxEnv::from_signed_envelope(envelope)?;
+        *self.tx_mut() = tx;
+        self.transact()
+    }
+
     /// Returns the reference of handler configuration
     #[inline]
     pub fn handler_cfg(&self) -> &HandlerCfg {
@@ -260,13 +543,13 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
     /// Returns the reference of transaction
     #[inline]
     pub fn tx(&self) -> &TxEnv {
-        &self.context.evm.env.tx
+        self.context.evm.env.tx()
     }
 
     /// Returns the mutable reference of transaction
     #[inline]
     pub fn tx_mut(&mut self) -> &mut TxEnv {
-        &mut self.context.evm.env.tx
+        self.context.evm.env.tx_mut()
     }
 
     /// Returns the reference of database
@@ -293,6 +576,15 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         &mut self.context.evm.env.block
     }
 
+    /// Consumes the EVM and returns the database.
+    ///
+    /// State changes are not in the database unless they were committed, for example
+    /// with [`Evm::transact_commit`].
+    #[inline]
+    pub fn into_db(self) -> DB {
+        self.context.evm.inner.db
+    }
+
     /// Modify spec id, this will create new EVM that matches this spec id.
     pub fn modify_spec_id(&mut self, spec_id: SpecId) {
         self.handler.modify_spec_id(spec_id);
@@ -323,68 +615,1101 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
     }
 
     /// Transact pre-verified transaction.
-    fn transact_preverified_inner(&mut self, initial_gas_spend: u64) -> EVMResult<DB::Error> {
-        let spec_id = self.spec_id();
+    fn transact_preverified_inner(
+        &mut self,
+        init_and_floor_gas: InitialAndFloorGas,
+    ) -> EVMResult<DB::Error> {
+        self.set_db_read_checks();
+        let gas_limit = self.pre_execution(init_and_floor_gas.initial_gas)?;
+        let result = self.execution(gas_limit)?;
+        self.post_execution(init_and_floor_gas.floor_gas, result)
+    }
+
+    /// Loads precompiles and accounts and deducts the caller. Returns the gas limit
+    /// of the first frame.
+    fn pre_execution(&mut self, initial_gas_spend: u64) -> Result<u64, EVMError<DB::Error>> {
+        let _span = phase_span!("pre_execution");
         let ctx = &mut self.context;
         let pre_exec = self.handler.pre_execution();
 
-        // load access list and beneficiary if needed.
-        pre_exec.load_accounts(ctx)?;
-
         // load precompiles
         let precompiles = pre_exec.load_precompiles();
         ctx.evm.set_precompiles(precompiles);
 
+        // load access list and warm addresses.
+        pre_exec.load_accounts(ctx)?;
+
         // deduce caller balance with its limit.
-        pre_exec.deduct_caller(ctx)?;
+        if !ctx.evm.env.cfg.force_static {
+            pre_exec.deduct_caller(ctx)?;
+        }
+
+        Ok(ctx.evm.env.tx().gas_limit - initial_gas_spend)
+    }
 
-        let gas_limit = ctx.evm.env.tx.gas_limit - initial_gas_spend;
+    /// Executes the first frame and all its sub calls.
+    fn execution(&mut self, gas_limit: u64) -> Result<FrameResult, EVMError<DB::Error>> {
+        let _span = phase_span!("execution");
+        // Starts the main running loop.
+        match self.first_frame(gas_limit)? {
+            FrameOrResult::Frame(first_frame) => self.run_the_loop(first_frame),
+            FrameOrResult::Result(result) => Ok(result),
+        }
+    }
 
+    /// Creates the first frame of the transaction.
+    fn first_frame(&mut self, gas_limit: u64) -> Result<FrameOrResult, EVMError<DB::Error>> {
+        let spec_id = self.spec_id();
+        let ctx = &mut self.context;
         let exec = self.handler.execution();
+        let force_static = ctx.evm.env.cfg.force_static;
+        if force_static && (ctx.evm.env.tx().is_create() || !ctx.evm.env.tx().value.is_zero()) {
+            return Ok(FrameOrResult::Result(FrameResult::Call(CallOutcome::new(
+                InterpreterResult {
+                    result: InstructionResult::StateChangeDuringStaticCall,
+                    gas: Gas::new(gas_limit),
+                    output: Bytes::new(),
+                },
+                0..0,
+            ))));
+        }
         // call inner handling of call/create
-        let first_frame_or_result = match ctx.evm.env.tx.transact_to {
-            TxKind::Call(_) => exec.call(
-                ctx,
-                CallInputs::new_boxed(&ctx.evm.env.tx, gas_limit).unwrap(),
-            )?,
+        let first_frame_or_result = match ctx.evm.env.tx().kind() {
+            TxKind::Call(_) => {
+                // Safe to unwrap because we are sure that it is call tx.
+                let mut inputs = CallInputs::new(ctx.evm.env.tx(), gas_limit).unwrap();
+                if force_static {
+                    inputs.is_static = true;
+                    // Zero value is not transferred, so the target account is not touched.
+                    inputs.value = CallValue::Apparent(U256::ZERO);
+                }
+                exec.call(ctx, Box::new(inputs))?
+            }
             TxKind::Create => {
                 // if first byte of data is magic 0xEF00, then it is EOFCreate.
                 if spec_id.is_enabled_in(SpecId::PRAGUE_EOF)
-                    && ctx.env().tx.data.get(0..2) == Some(&EOF_MAGIC_BYTES)
+                    && ctx.env().tx().data.get(0..2) == Some(&EOF_MAGIC_BYTES)
                 {
                     exec.eofcreate(
                         ctx,
-                        Box::new(EOFCreateInputs::new_tx(&ctx.evm.env.tx, gas_limit)),
+                        Box::new(EOFCreateInputs::new_tx(ctx.evm.env.tx(), gas_limit)),
                     )?
                 } else {
+                    let inputs = CreateInputs::from_tx_env(
+                        ctx.evm.env.tx(),
+                        gas_limit,
+                        spec_id,
+                        ctx.evm.env.cfg.max_initcode_size(),
+                    )?;
                     // Safe to unwrap because we are sure that it is create tx.
-                    exec.create(
-                        ctx,
-                        CreateInputs::new_boxed(&ctx.evm.env.tx, gas_limit).unwrap(),
-                    )?
+                    exec.create(ctx, Box::new(inputs.unwrap()))?
                 }
             }
         };
 
-        // Starts the main running loop.
-        let mut result = match first_frame_or_result {
-            FrameOrResult::Frame(first_frame) => self.run_the_loop(first_frame)?,
-            FrameOrResult::Result(result) => result,
-        };
-
-        let ctx = &mut self.context;
+        Ok(first_frame_or_result)
+    }
 
+    /// Handles the output of the first frame, refunds and rewards the gas and returns the
+    /// output of the transaction.
+    fn post_execution(&mut self, floor_gas: u64, mut result: FrameResult) -> EVMResult<DB::Error> {
         // handle output of call/create calls.
         self.handler
             .execution()
-            .last_frame_return(ctx, &mut result)?;
+            .last_frame_return(&mut self.context, &mut result)?;
 
+        let _span = phase_span!("post_execution");
+        let ctx = &mut self.context;
         let post_exec = self.handler.post_execution();
-        // Reimburse the caller
-        post_exec.reimburse_caller(ctx, result.gas())?;
-        // Reward beneficiary
-        post_exec.reward_beneficiary(ctx, result.gas())?;
+        // Calculate the final refund
+        post_exec.refund(ctx, result.gas_mut())?;
+        // EIP-7623: Increase calldata cost
+        result.gas_mut().apply_floor(floor_gas);
+        // the caller was not charged for a static transaction.
+        if !ctx.evm.env.cfg.force_static {
+            // Reimburse the caller
+            post_exec.reimburse_caller(ctx, result.gas())?;
+            // Reward beneficiary
+            post_exec.reward_beneficiary(ctx, result.gas())?;
+        }
         // Returns output of transaction.
         post_exec.output(ctx, result)
     }
+
+    /// Transact transaction like [`Evm::transact`], but the transaction is suspended instead of
+    /// halted when its first frame runs out of gas, see [`Interpreter::run_resumable`].
+    ///
+    /// The journal of the suspended transaction, with the checkpoint of the first frame, is kept
+    /// in the EVM until the transaction is continued with [`Evm::resume_transaction`] or
+    /// discarded with [`Evm::discard_transaction`]. If the suspended transaction is dropped
+    /// instead, its journal is cleared when the next transaction starts.
+    ///
+    /// Only the first frame is suspended, frames of its calls halt with out of gas as usual, as
+    /// do the dynamic costs checked by [`Interpreter::run_resumable`]. The first frame is run by
+    /// the EVM instead of the `execute_frame` handle, so it is not profiled, see
+    /// [`CfgEnv::profile_execution`].
+    ///
+    /// [`Interpreter::run_resumable`]: crate::interpreter::Interpreter::run_resumable
+    pub fn transact_resumable(&mut self) -> Result<ResumableOutcome, EVMError<DB::Error>> {
+        let _span = phase_span!("transact");
+        self.clear_suspended_tx();
+        let init_and_floor_gas = self
+            .preverify_transaction_inner()
+            .inspect_err(|_| self.clear())?;
+
+        self.set_db_read_checks();
+        let output = self
+            .pre_execution(init_and_floor_gas.initial_gas)
+            .and_then(|gas_limit| {
+                let _span = phase_span!("execution");
+                match self.first_frame(gas_limit)? {
+                    FrameOrResult::Frame(first_frame) => self.run_frames(first_frame, None, true),
+                    FrameOrResult::Result(result) => Ok(Ok(result)),
+                }
+            });
+        self.end_resumable(init_and_floor_gas, output)
+    }
+
+    /// Continues the transaction suspended by [`Evm::transact_resumable`] with `extra_gas`
+    /// added to the gas of its first frame. Returns the transaction suspended again if the gas
+    /// is not enough.
+    ///
+    /// Gas limit of the transaction environment is raised by `extra_gas` and the caller is
+    /// charged for it like for the gas limit, the charge is not reverted with the frame.
+    pub fn resume_transaction(
+        &mut self,
+        suspended: SuspendedTransaction,
+        extra_gas: u64,
+    ) -> Result<ResumableOutcome, EVMError<DB::Error>> {
+        let _span = phase_span!("transact");
+        self.has_suspended_tx = false;
+        let SuspendedTransaction {
+            mut frame,
+            suspended,
+            init_and_floor_gas,
+        } = suspended;
+
+        let output = self.charge_extra_gas(extra_gas).map(|()| {
+            frame.interpreter_mut().gas.extend(extra_gas);
+            Some(suspended.into_shared_memory())
+        });
+        let output = output.and_then(|memory| self.run_frames(frame, memory, true));
+        self.end_resumable(init_and_floor_gas, output)
+    }
+
+    /// Discards the transaction suspended by [`Evm::transact_resumable`], none of its changes
+    /// are kept.
+    pub fn discard_transaction(&mut self, suspended: SuspendedTransaction) {
+        drop(suspended);
+        self.has_suspended_tx = false;
+        self.clear();
+    }
+
+    /// Raises the gas limit of the transaction by `extra_gas` and charges the caller for it.
+    fn charge_extra_gas(&mut self, extra_gas: u64) -> Result<(), EVMError<DB::Error>> {
+        let env = &mut self.context.evm.inner.env;
+        let cost =
+            U256::from(extra_gas).saturating_mul(env.tx().effective_gas_price(env.block.basefee));
+        env.tx_mut().gas_limit = env.tx().gas_limit.saturating_add(extra_gas);
+        if env.cfg.force_static {
+            return Ok(());
+        }
+
+        // caller is loaded in pre execution. Balance is changed without a journal entry, so
+        // the charge is not reverted with the first frame.
+        let caller = self
+            .context
+            .evm
+            .inner
+            .journaled_state
+            .state
+            .get_mut(&env.tx().caller)
+            .expect("caller is loaded");
+        let Some(balance) = caller.info.balance.checked_sub(cost) else {
+            return Err(InvalidTransaction::LackOfFundForMaxFee {
+                fee: Box::new(cost),
+                balance: Box::new(caller.info.balance),
+            }
+            .into());
+        };
+        caller.info.balance = balance;
+        Ok(())
+    }
+
+    /// Finishes the transaction unless it is suspended.
+    fn end_resumable(
+        &mut self,
+        init_and_floor_gas: InitialAndFloorGas,
+        output: FramesResult<DB::Error>,
+    ) -> Result<ResumableOutcome, EVMError<DB::Error>> {
+        let output = match output {
+            Ok(Ok(result)) => self.post_execution(init_and_floor_gas.floor_gas, result),
+            Ok(Err((frame, suspended))) => {
+                self.has_suspended_tx = true;
+                return Ok(ResumableOutcome::Suspended(SuspendedTransaction {
+                    frame,
+                    suspended,
+                    init_and_floor_gas,
+                }));
+            }
+            Err(e) => Err(e),
+        };
+        let output = self.handler.post_execution().end(&mut self.context, output);
+        self.clear();
+        output.map(ResumableOutcome::Done)
+    }
+}
+
+/// Outcome of [`Evm::transact_resumable`] and [`Evm::resume_transaction`].
+#[derive(Debug)]
+pub enum ResumableOutcome {
+    /// Transaction is finished.
+    Done(ResultAndState),
+    /// First frame of the transaction ran out of gas.
+    Suspended(SuspendedTransaction),
+}
+
+/// Transaction suspended by [`Evm::transact_resumable`] because its first frame ran out of gas.
+#[derive(Debug)]
+pub struct SuspendedTransaction {
+    /// First frame of the transaction.
+    frame: Frame,
+    /// Suspended execution of the first frame.
+    suspended: Suspended,
+    init_and_floor_gas: InitialAndFloorGas,
+}
+
+impl SuspendedTransaction {
+    /// Returns the gas that needs to be added to execute the next instruction of the first
+    /// frame, see [`Suspended::missing_gas`].
+    pub fn missing_gas(&self) -> u64 {
+        self.suspended.missing_gas()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        db::{
+            states::bundle_state::BundleRetention, BenchmarkDB, CacheDB, Database,
+            DatabaseCommit2PC, DatabaseRef, EmptyDB, ForkDB, InMemoryDB, PrepareCommitError, State,
+            WrapDatabaseCommit,
+        },
+        handler::EthereumChainSpec,
+        interpreter::{
+            gas::validate_initial_tx_gas, CallInputs, CallOutcome, CreateInputs, CreateOutcome,
+            Interpreter,
+        },
+        primitives::{
+            address, bytes, Account, AccountInfo, Address, Bytecode, Bytes, DatabaseInconsistency,
+            EVMError, ExecutionResult, GasScheduleOverrides, HaltReason, InvalidTransaction,
+            ResultAndState, SpecId, TxKind, B256, BLOCKHASH_STORAGE_ADDRESS, MAX_INITCODE_SIZE,
+            U256,
+        },
+        test_utils::{contract_evm, AssertingInspector, CALLER, CONTRACT},
+        Evm, EvmContext, Inspector, ResumableOutcome,
+    };
+    use std::sync::Arc;
+
+    fn evm(revalidate_stale_tx: bool) -> Evm<'static, (), InMemoryDB> {
+        Evm::builder()
+            .with_db(InMemoryDB::default())
+            .modify_db(|db| {
+                db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(100_000)))
+            })
+            .modify_cfg_env(|cfg| cfg.revalidate_stale_tx = revalidate_stale_tx)
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.gas_limit = 21_000;
+                tx.gas_price = U256::from(1);
+                tx.transact_to = Some(TxKind::Call(Address::ZERO));
+            })
+            .build()
+    }
+
+    /// Counts executed instructions.
+    #[derive(Default)]
+    struct StepCounter {
+        steps: usize,
+    }
+
+    impl<DB: Database> Inspector<DB> for StepCounter {
+        fn step(&mut self, _interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+            self.steps += 1;
+        }
+    }
+
+    /// Records the inputs of the first frame.
+    #[derive(Default)]
+    struct FirstFrameInputs {
+        call: Option<CallInputs>,
+        create: Option<CreateInputs>,
+    }
+
+    impl<DB: Database> Inspector<DB> for FirstFrameInputs {
+        fn call(
+            &mut self,
+            _context: &mut EvmContext<DB>,
+            inputs: &mut CallInputs,
+        ) -> Option<CallOutcome> {
+            if self.call.is_none() && self.create.is_none() {
+                self.call = Some(inputs.clone());
+            }
+            None
+        }
+
+        fn create(
+            &mut self,
+            _context: &mut EvmContext<DB>,
+            inputs: &mut CreateInputs,
+        ) -> Option<CreateOutcome> {
+            if self.call.is_none() && self.create.is_none() {
+                self.create = Some(inputs.clone());
+            }
+            None
+        }
+    }
+
+    #[test]
+    fn first_frame_inputs_match_public_constructors() {
+        let shapes = [
+            (TxKind::Call(Address::ZERO), U256::ZERO, Bytes::new()),
+            (
+                TxKind::Call(Address::with_last_byte(5)),
+                U256::from(7),
+                Bytes::from_static(&[1, 0, 2]),
+            ),
+            (TxKind::Create, U256::ZERO, Bytes::from_static(&[0x00])),
+            (
+                TxKind::Create,
+                U256::from(7),
+                Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xf3]),
+            ),
+        ];
+        for (transact_to, value, data) in shapes {
+            let mut evm = evm(false)
+                .modify()
+                .modify_tx_env(|tx| {
+                    tx.gas_limit = 90_000;
+                    tx.transact_to = Some(transact_to);
+                    tx.value = value;
+                    tx.data = data.clone();
+                })
+                .reset_handler_with_external_context(AssertingInspector::new(
+                    FirstFrameInputs::default(),
+                ))
+                .build();
+            evm.inspect().unwrap();
+
+            let tx = evm.tx().clone();
+            let initial_gas = validate_initial_tx_gas(
+                &GasScheduleOverrides::NONE,
+                evm.spec_id(),
+                &tx.data,
+                tx.is_create(),
+                &tx.access_list,
+                0,
+            );
+            let gas_limit = tx.gas_limit - initial_gas;
+            let recorded = &evm.context.external;
+            assert_eq!(recorded.call, CallInputs::new(&tx, gas_limit));
+            assert_eq!(
+                recorded.create,
+                CreateInputs::from_tx_env(
+                    &tx,
+                    gas_limit,
+                    evm.spec_id(),
+                    evm.cfg().max_initcode_size()
+                )
+                .unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn create_first_frame_matches_create_inputs_from_tx_env() {
+        let oversized = Bytes::from(vec![0; MAX_INITCODE_SIZE + 1]);
+        let shapes = [
+            // oversized initcode is rejected from Shanghai.
+            (SpecId::CANCUN, U256::ZERO, oversized.clone()),
+            // and is not limited before it.
+            (SpecId::MERGE, U256::ZERO, oversized),
+            // value transfer.
+            (
+                SpecId::CANCUN,
+                U256::from(7),
+                Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xf3]),
+            ),
+        ];
+        for (spec_id, value, data) in shapes {
+            let mut evm = evm(false)
+                .modify()
+                .with_spec_id(spec_id)
+                .modify_db(|db| {
+                    db.insert_account_info(CALLER, AccountInfo::from_balance(U256::MAX))
+                })
+                .modify_tx_env(|tx| {
+                    tx.gas_limit = 1_000_000;
+                    tx.transact_to = Some(TxKind::Create);
+                    tx.value = value;
+                    tx.data = data.clone();
+                })
+                .reset_handler_with_external_context(AssertingInspector::new(
+                    FirstFrameInputs::default(),
+                ))
+                .build();
+            let result = evm.inspect();
+
+            let tx = evm.tx().clone();
+            let initial_gas = validate_initial_tx_gas(
+                &GasScheduleOverrides::NONE,
+                spec_id,
+                &tx.data,
+                true,
+                &tx.access_list,
+                0,
+            );
+            let expected = CreateInputs::from_tx_env(
+                &tx,
+                tx.gas_limit - initial_gas,
+                spec_id,
+                evm.cfg().max_initcode_size(),
+            );
+            match expected {
+                Ok(inputs) => {
+                    assert!(result.is_ok());
+                    assert_eq!(evm.context.external.create, inputs);
+                }
+                Err(err) => {
+                    assert_eq!(result.unwrap_err(), EVMError::Transaction(err));
+                    assert_eq!(evm.context.external.create, None);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn inspect_commit_matches_transact_commit() {
+        // PUSH1 0x2a, PUSH1 0x00, SSTORE, STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x2a, 0x60, 0x00, 0x55, 0x00]));
+        let build = || {
+            evm(false)
+                .modify()
+                .modify_db(|db| {
+                    db.insert_account_info(CONTRACT, AccountInfo::from_bytecode(code.clone()))
+                })
+                .modify_tx_env(|tx| {
+                    tx.gas_limit = 50_000;
+                    tx.transact_to = Some(TxKind::Call(CONTRACT));
+                })
+                .reset_handler_with_external_context(
+                    AssertingInspector::new(StepCounter::default()),
+                )
+                .build()
+        };
+
+        let mut plain = build();
+        let mut inspected = build();
+        let expected = plain.transact_commit().unwrap();
+        assert_eq!(inspected.inspect_commit().unwrap(), expected);
+        assert_eq!(inspected.context.external.steps, 4);
+        assert_eq!(
+            inspected
+                .db()
+                .accounts
+                .get(&CONTRACT)
+                .map(|a| (&a.info, &a.storage)),
+            plain
+                .db()
+                .accounts
+                .get(&CONTRACT)
+                .map(|a| (&a.info, &a.storage))
+        );
+        assert_eq!(
+            inspected.db().storage_ref(CONTRACT, U256::ZERO).unwrap(),
+            U256::from(0x2a)
+        );
+        assert_eq!(
+            inspected
+                .db()
+                .accounts
+                .get(&CALLER)
+                .map(|a| (&a.info, &a.storage)),
+            plain
+                .db()
+                .accounts
+                .get(&CALLER)
+                .map(|a| (&a.info, &a.storage))
+        );
+
+        inspected.preverify_transaction().unwrap();
+        assert!(inspected.inspect_preverified().unwrap().result.is_success());
+        assert_eq!(inspected.context.external.steps, 8);
+
+        // the register is applied once, also after the Evm is modified.
+        let mut inspected = inspected.modify().build();
+        assert!(inspected.inspect().unwrap().result.is_success());
+        assert_eq!(inspected.context.external.steps, 12);
+    }
+
+    #[test]
+    fn transact_after_inspect_is_not_inspected() {
+        // PUSH1 0x00, POP, STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00, 0x50, 0x00]));
+        let mut evm = evm(false)
+            .modify()
+            .modify_db(|db| db.insert_account_info(Address::ZERO, AccountInfo::from_bytecode(code)))
+            .modify_tx_env(|tx| tx.gas_limit = 50_000)
+            .reset_handler_with_external_context(AssertingInspector::new(StepCounter::default()))
+            .build();
+        assert!(evm.inspect().unwrap().result.is_success());
+        assert_eq!(evm.context.external.steps, 3);
+
+        assert!(evm.transact().unwrap().result.is_success());
+        assert_eq!(evm.context.external.steps, 3);
+        assert!(matches!(
+            evm.handler.instruction_table,
+            crate::interpreter::opcode::InstructionTables::Plain(_)
+        ));
+    }
+
+    #[test]
+    fn inspect_keeps_handler_changes() {
+        let mut evm = evm(false)
+            .modify()
+            .reset_handler_with_external_context(AssertingInspector::new(StepCounter::default()))
+            .build();
+        evm.handler.validation.env = Arc::new(|_| Err(EVMError::Custom("manual".into())));
+        assert_eq!(
+            evm.inspect().unwrap_err(),
+            EVMError::Custom("manual".into())
+        );
+        // the changed handle is restored together with the rest of the handler.
+        assert_eq!(
+            evm.transact().unwrap_err(),
+            EVMError::Custom("manual".into())
+        );
+    }
+
+    type ForkEvm = Evm<'static, (), ForkDB<Arc<CacheDB<EmptyDB>>>>;
+
+    const STORE_CONTRACT: Address = address!("2000000000000000000000000000000000000002");
+
+    /// Stores `value` in slot 0 of [`STORE_CONTRACT`].
+    fn store(evm: &mut ForkEvm, value: u64) {
+        evm.tx_mut().data = Bytes::copy_from_slice(B256::from(U256::from(value)).as_slice());
+        evm.transact_commit().unwrap().expect_success();
+    }
+
+    /// Returns slot 0 of [`STORE_CONTRACT`] and the nonce of the caller.
+    fn fork_state(evm: &ForkEvm) -> (U256, u64) {
+        let db = &evm.context.evm.db;
+        let slot = db.storage_ref(STORE_CONTRACT, U256::ZERO).unwrap();
+        (slot, db.basic_ref(CALLER).unwrap().unwrap().nonce)
+    }
+
+    #[test]
+    fn fork_isolates_writes() {
+        // SSTORE(0, CALLDATALOAD(0))
+        let code = Bytecode::new_raw(bytes!("5f355f5500"));
+        let mut base = CacheDB::new(EmptyDB::default());
+        base.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1_000_000)));
+        base.insert_account_info(STORE_CONTRACT, AccountInfo::from_bytecode(code));
+        let base = Arc::new(base);
+
+        let mut evm = Evm::builder()
+            .with_db(ForkDB::new(base.clone(), 0))
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = Some(TxKind::Call(STORE_CONTRACT));
+                tx.gas_limit = 100_000;
+            })
+            .with_chain_spec::<EthereumChainSpec>(SpecId::CANCUN)
+            .build();
+        store(&mut evm, 1);
+
+        let mut fork_a = evm.fork().unwrap();
+        let mut fork_b = evm.fork().unwrap();
+        assert_eq!(fork_a.spec_id(), SpecId::CANCUN);
+        assert_eq!(fork_a.handler.registers.len(), 1);
+        // both forks see the first transaction.
+        assert_eq!(fork_state(&fork_a), (U256::from(1), 1));
+        assert_eq!(fork_state(&fork_b), (U256::from(1), 1));
+
+        store(&mut fork_a, 2);
+        store(&mut fork_b, 3);
+        assert_eq!(fork_state(&fork_a), (U256::from(2), 2));
+        assert_eq!(fork_state(&fork_b), (U256::from(3), 2));
+        assert_eq!(fork_state(&evm), (U256::from(1), 1));
+        // base is shared and never written to.
+        assert_eq!(Arc::strong_count(&base), 4);
+        assert_eq!(base.storage_ref(STORE_CONTRACT, U256::ZERO), Ok(U256::ZERO));
+    }
+
+    /// Returns balance, nonce and storage of the account in the database.
+    fn db_account(db: &mut impl Database, address: Address) -> (U256, u64, U256) {
+        let info = db.basic(address).ok().flatten().unwrap_or_default();
+        let slot = db.storage(address, U256::ZERO).ok().unwrap_or_default();
+        (info.balance, info.nonce, slot)
+    }
+
+    #[test]
+    fn transact_prepare_abort_and_commit() {
+        // SSTORE(0, CALLDATALOAD(0))
+        let code = Bytecode::new_raw(bytes!("5f355f5500"));
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1_000_000)));
+        db.insert_account_info(STORE_CONTRACT, AccountInfo::from_bytecode(code));
+        fn store_evm<DB: Database>(db: DB) -> Evm<'static, (), DB> {
+            Evm::builder()
+                .with_db(db)
+                .modify_tx_env(|tx| {
+                    tx.caller = CALLER;
+                    tx.transact_to = Some(TxKind::Call(STORE_CONTRACT));
+                    tx.value = U256::from(10);
+                    tx.data = Bytes::copy_from_slice(B256::with_last_byte(7).as_slice());
+                    tx.gas_limit = 100_000;
+                })
+                .build()
+        }
+        let untouched = (U256::ZERO, 1, U256::ZERO);
+
+        let mut direct = store_evm(db.clone());
+        direct.transact_commit().unwrap().expect_success();
+        let expected = (U256::from(10), 1, U256::from(7));
+        assert_eq!(db_account(direct.db_mut(), STORE_CONTRACT), expected);
+
+        // cache database.
+        let mut cache = store_evm(db.clone());
+        let (result, prepared) = cache.transact_prepare().unwrap();
+        assert!(result.is_success());
+        assert!(prepared.changes().contains_key(&STORE_CONTRACT));
+        assert_eq!(prepared.storage_slots(), 1);
+        drop(prepared);
+        assert_eq!(db_account(cache.db_mut(), STORE_CONTRACT), untouched);
+        let (_, prepared) = cache.transact_prepare().unwrap();
+        cache.commit_prepared(prepared);
+        for address in [CALLER, STORE_CONTRACT, Address::ZERO] {
+            assert_eq!(
+                db_account(cache.db_mut(), address),
+                db_account(direct.db_mut(), address)
+            );
+        }
+
+        // state with bundle updates.
+        let mut state = store_evm(
+            State::builder()
+                .with_database(db.clone())
+                .with_bundle_update()
+                .build(),
+        );
+        let (_, prepared) = state.transact_prepare().unwrap();
+        drop(prepared);
+        assert_eq!(db_account(state.db_mut(), STORE_CONTRACT), untouched);
+        let (_, prepared) = state.transact_prepare().unwrap();
+        state.commit_prepared(prepared);
+        assert_eq!(db_account(state.db_mut(), STORE_CONTRACT), expected);
+
+        let mut direct_state = store_evm(
+            State::builder()
+                .with_database(db.clone())
+                .with_bundle_update()
+                .build(),
+        );
+        direct_state.transact_commit().unwrap();
+        let bundle = |evm: &mut Evm<'_, (), State<InMemoryDB>>| {
+            evm.db_mut().merge_transitions(BundleRetention::Reverts);
+            let mut bundle = evm.db_mut().take_bundle();
+            // reverts are collected from a hash map.
+            bundle.reverts.sort();
+            bundle
+        };
+        assert_eq!(bundle(&mut state), bundle(&mut direct_state));
+        // changed accounts need to be loaded in the state cache.
+        let mut unknown = Account::from(AccountInfo::default());
+        unknown.mark_touch();
+        let changes = [(Address::with_last_byte(0x42), unknown)]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            state.db().prepare(changes),
+            Err(PrepareCommitError::AccountNotLoaded(
+                Address::with_last_byte(0x42)
+            ))
+        );
+
+        // any database through the adapter.
+        let mut wrapped = store_evm(WrapDatabaseCommit(db));
+        let (_, prepared) = wrapped.transact_prepare().unwrap();
+        drop(prepared);
+        assert_eq!(db_account(wrapped.db_mut(), STORE_CONTRACT), untouched);
+        let (_, prepared) = wrapped.transact_prepare().unwrap();
+        wrapped.commit_prepared(prepared);
+        assert_eq!(db_account(wrapped.db_mut(), STORE_CONTRACT), expected);
+    }
+
+    #[test]
+    fn transact_preverified_unmodified() {
+        let mut evm = evm(false);
+        evm.preverify_transaction().unwrap();
+        assert!(evm.transact_preverified().unwrap().result.is_success());
+    }
+
+    #[test]
+    fn transact_preverified_stale_error() {
+        let mut evm = evm(false);
+        evm.preverify_transaction().unwrap();
+        evm.tx_mut().gas_limit = 10;
+        assert!(matches!(
+            evm.transact_preverified(),
+            Err(EVMError::StaleValidation)
+        ));
+    }
+
+    #[test]
+    fn transact_preverified_stale_revalidate() {
+        let mut evm = evm(true);
+        evm.preverify_transaction().unwrap();
+        // gas limit is now too low for the intrinsic cost.
+        evm.tx_mut().gas_limit = 10;
+        assert!(matches!(
+            evm.transact_preverified(),
+            Err(EVMError::Transaction(
+                InvalidTransaction::CallGasCostMoreThanGasLimit
+            ))
+        ));
+
+        // valid modification is executed after revalidation.
+        evm.tx_mut().gas_limit = 21_000;
+        evm.preverify_transaction().unwrap();
+        evm.tx_mut().value = U256::from(1);
+        assert!(evm.transact_preverified().unwrap().result.is_success());
+    }
+
+    /// Database that returns a different value on every storage read.
+    struct InconsistentStorageDB {
+        db: BenchmarkDB,
+        storage_reads: u64,
+    }
+
+    impl Database for InconsistentStorageDB {
+        type Error = core::convert::Infallible;
+
+        fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            self.db.basic(address)
+        }
+
+        fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+            self.db.code_by_hash(code_hash)
+        }
+
+        fn storage(&mut self, _address: Address, _index: U256) -> Result<U256, Self::Error> {
+            self.storage_reads += 1;
+            Ok(U256::from(self.storage_reads))
+        }
+
+        fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+            self.db.block_hash(number)
+        }
+    }
+
+    #[test]
+    fn paranoid_db_checks() {
+        // BLOCKHASH(0), POP, BLOCKHASH(0), POP, STOP
+        // Block 0 is served from the EIP-2935 history storage, read twice from the database.
+        let code = Bytes::from_static(&[0x60, 0x00, 0x40, 0x50, 0x60, 0x00, 0x40, 0x50, 0x00]);
+        let mut evm = Evm::builder()
+            .with_db(InconsistentStorageDB {
+                db: BenchmarkDB::new_bytecode(Bytecode::new_raw(code)),
+                storage_reads: 0,
+            })
+            .with_spec_id(SpecId::PRAGUE)
+            .modify_block_env(|block| block.number = U256::from(300))
+            .modify_tx_env(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.transact_to = Some(TxKind::Call(Address::ZERO));
+                tx.gas_limit = 100_000;
+            })
+            .build();
+
+        evm.transact().unwrap().result.expect_success();
+
+        evm.cfg_mut().paranoid_db_checks = true;
+        let Err(EVMError::DatabaseInconsistency(inconsistency)) = evm.transact() else {
+            panic!("expected database inconsistency");
+        };
+        assert_eq!(
+            *inconsistency,
+            DatabaseInconsistency::Storage {
+                address: BLOCKHASH_STORAGE_ADDRESS,
+                slot: U256::ZERO,
+                first: U256::from(3),
+                second: U256::from(4),
+            }
+        );
+    }
+
+    #[test]
+    fn call_stack_limit_is_configurable() {
+        // CALL(gas, ADDRESS, 0, 0, 0, 0, 0), revert if the call failed.
+        let code = Bytes::from_static(&[
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x30, 0x5a, 0xf1, 0x60,
+            0x15, 0x57, 0x60, 0x00, 0x60, 0x00, 0xfd, 0x5b, 0x00,
+        ]);
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+            .modify_cfg_env(|cfg| cfg.call_stack_limit = Some(10))
+            .modify_tx_env(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.transact_to = Some(TxKind::Call(Address::ZERO));
+                tx.gas_limit = 1_000_000;
+            })
+            .build();
+
+        evm.transact().unwrap().result.expect_revert();
+        assert_eq!(evm.context.evm.call_stack_exhaustion, None);
+
+        evm.context.evm.trace_call_stack = true;
+        evm.transact().unwrap().result.expect_revert();
+        let exhaustion = evm.context.evm.call_stack_exhaustion.clone().unwrap();
+        assert_eq!(exhaustion.depth, 11);
+        assert_eq!(exhaustion.addresses, vec![Address::ZERO; 11]);
+    }
+
+    #[test]
+    fn into_db_after_commit() {
+        // SSTORE(0, 1), STOP
+        let mut evm = contract_evm(Bytes::from_static(&[0x60, 0x01, 0x5f, 0x55, 0x00]))
+            .with_external_context(0u64)
+            .build();
+        evm.transact_commit().unwrap();
+        evm.db_mut()
+            .insert_account_storage(CONTRACT, U256::from(1), U256::from(2))
+            .unwrap();
+
+        let mut db = evm.into_db();
+        assert_eq!(db.basic(CALLER).unwrap().unwrap().nonce, 1);
+        assert_eq!(db.storage(CONTRACT, U256::ZERO).unwrap(), U256::from(1));
+        assert_eq!(db.storage(CONTRACT, U256::from(1)).unwrap(), U256::from(2));
+    }
+
+    #[rstest::rstest]
+    #[case::floor_over_execution(SpecId::PRAGUE, 1000, false, 61_000, 0)]
+    #[case::no_floor_before_prague(SpecId::CANCUN, 1000, false, 39_204, 0)]
+    #[case::execution_over_floor(SpecId::PRAGUE, 20, false, 23_524, 0)]
+    #[case::refund_under_floor(SpecId::PRAGUE, 20, true, 21_800, 0)]
+    #[case::refund_over_floor(SpecId::PRAGUE, 0, true, 21_204, 4_800)]
+    fn calldata_gas_floor(
+        #[case] spec: SpecId,
+        #[case] calldata_len: usize,
+        #[case] slot_set: bool,
+        #[case] gas_used: u64,
+        #[case] gas_refunded: u64,
+    ) {
+        // SSTORE(0, 0), STOP
+        let mut evm = contract_evm(Bytes::from_static(&[0x5f, 0x5f, 0x55, 0x00]))
+            .modify_db(|db| {
+                db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1_000_000)));
+                if slot_set {
+                    db.insert_account_storage(CONTRACT, U256::ZERO, U256::from(1))
+                        .unwrap();
+                }
+            })
+            .with_spec_id(spec)
+            .modify_tx_env(|tx| {
+                tx.data = vec![0x01; calldata_len].into();
+                tx.gas_price = U256::from(1);
+            })
+            .build();
+
+        let ResultAndState { result, state, .. } = evm.transact().unwrap();
+        let ExecutionResult::Success {
+            gas_used: used,
+            gas_refunded: refunded,
+            ..
+        } = result
+        else {
+            panic!("expected success, got {result:?}");
+        };
+        assert_eq!((used, refunded), (gas_used, gas_refunded));
+        // Caller pays for the floored gas.
+        assert_eq!(
+            state[&CALLER].info.balance,
+            U256::from(1_000_000 - gas_used)
+        );
+    }
+
+    #[cfg(feature = "alloy-consensus")]
+    #[test]
+    fn transact_raw_legacy_transfer() {
+        const SENDER: Address = address!("f39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+        const TO: Address = address!("70997970c51812dc3a010c7d01b50e0d17dc79c8");
+        // Nonce 7, gas price 20 gwei, gas limit 50_000, 1 ether to `TO` on chain 1.
+        let envelope = crate::primitives::hex::decode("f870078504a817c80082c3509470997970c51812dc3a010c7d01b50e0d17dc79c8880de0b6b3a764000084a9059cbb26a01ef5d86a1b94085c639897e2fb80c49a39c26a3bf3416df6b85894c6a8024a09a028e6712f0e0b3652365ac66963771ddd2ebef081ffbf869b96f5ee3e36a8dd1d").unwrap();
+        let ether = U256::from(1_000_000_000_000_000_000u64);
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .modify_db(|db| {
+                db.insert_account_info(
+                    SENDER,
+                    AccountInfo {
+                        nonce: 7,
+                        ..AccountInfo::from_balance(ether * U256::from(2))
+                    },
+                )
+            })
+            .build();
+
+        let ResultAndState { result, state, .. } = evm.transact_raw(&envelope).unwrap();
+        let (_, _, gas_used) = result.expect_success();
+        assert_eq!(evm.tx().caller, SENDER);
+        assert_eq!(state[&TO].info.balance, ether);
+        assert_eq!(state[&SENDER].info.nonce, 8);
+        assert_eq!(
+            state[&SENDER].info.balance,
+            ether - U256::from(gas_used * 20_000_000_000)
+        );
+
+        assert!(matches!(
+            evm.transact_raw(&envelope[1..]),
+            Err(EVMError::TxDecode(
+                crate::primitives::TxDecodeError::UnsupportedType(0x70)
+            ))
+        ));
+    }
+
+    #[test]
+    fn transact_readonly_forbids_state_changes() {
+        const VIEW: Address = address!("2000000000000000000000000000000000000002");
+        const WRITER: Address = address!("3000000000000000000000000000000000000003");
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10_000_000)));
+        // return SLOAD(0)
+        db.insert_account_info(
+            VIEW,
+            AccountInfo::from_bytecode(Bytecode::new_raw(bytes!("5f545f5260205ff3"))),
+        );
+        db.insert_account_storage(VIEW, U256::ZERO, U256::from(42))
+            .unwrap();
+        // SSTORE(0, 1)
+        db.insert_account_info(
+            WRITER,
+            AccountInfo::from_bytecode(Bytecode::new_raw(bytes!("60015f5500"))),
+        );
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.gas_limit = 100_000;
+                tx.gas_price = U256::from(1);
+            })
+            .build();
+        let mut transact = |transact_to, value| {
+            evm.tx_mut().transact_to = Some(transact_to);
+            evm.tx_mut().value = value;
+            let ResultAndState { result, state, .. } = evm.transact_readonly().unwrap();
+            assert!(state.values().all(|account| !account.is_touched()));
+            result
+        };
+
+        let result = transact(TxKind::Call(VIEW), U256::ZERO);
+        assert_eq!(
+            result.output().map(|output| U256::from_be_slice(output)),
+            Some(U256::from(42))
+        );
+
+        for (transact_to, value) in [
+            (TxKind::Call(WRITER), U256::ZERO),
+            (TxKind::Call(VIEW), U256::from(1)),
+            (TxKind::Create, U256::ZERO),
+        ] {
+            assert_eq!(
+                transact(transact_to, value),
+                ExecutionResult::Halt {
+                    reason: HaltReason::StateChangeDuringStaticCall,
+                    gas_used: 100_000,
+                }
+            );
+        }
+
+        let caller = evm.context.evm.db.accounts[&CALLER].info.clone();
+        assert_eq!(caller.balance, U256::from(10_000_000));
+        assert_eq!(caller.nonce, 0);
+    }
+
+    #[test]
+    fn resume_transaction_after_gas_top_ups() {
+        const TARGET: Address = address!("2000000000000000000000000000000000000002");
+        // Sum of 1..=10 in a loop, returned as a word:
+        // PUSH1 0, PUSH1 10, loop: JUMPDEST, DUP1, ISZERO, PUSH1 end, JUMPI,
+        // DUP1, SWAP2, ADD, SWAP1, PUSH1 1, SWAP1, SUB, PUSH1 loop, JUMP,
+        // end: JUMPDEST, POP, MSTORE(0), RETURN(0, 32)
+        let code = bytes!("6000600a5b801560155780910190600190036004565b5060005260206000f3");
+        let evm = |gas_limit| {
+            Evm::builder()
+                .with_db(CacheDB::new(EmptyDB::default()))
+                .modify_db(|db| {
+                    db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(100_000)));
+                    db.insert_account_info(
+                        TARGET,
+                        AccountInfo::from_bytecode(Bytecode::new_raw(code.clone())),
+                    );
+                })
+                .modify_tx_env(|tx| {
+                    tx.caller = CALLER;
+                    tx.transact_to = Some(TxKind::Call(TARGET));
+                    tx.gas_price = U256::from(1);
+                    tx.gas_limit = gas_limit;
+                })
+                .build()
+        };
+
+        let expected = evm(50_000).transact().unwrap();
+        let gas_used = expected.result.gas_used();
+        assert_eq!(expected.result.output().unwrap()[31], 55);
+
+        // Quarter of the execution gas at a time, the first frame is suspended three times.
+        let slice = (gas_used - 21_000) / 4;
+        let mut evm = evm(21_000 + slice);
+        let mut top_ups = 0;
+        let mut outcome = evm.transact_resumable().unwrap();
+        let result = loop {
+            match outcome {
+                ResumableOutcome::Done(result) => break result,
+                ResumableOutcome::Suspended(suspended) => {
+                    assert!(suspended.missing_gas() > 0);
+                    top_ups += 1;
+                    outcome = evm.resume_transaction(suspended, slice + 1).unwrap();
+                }
+            }
+        };
+        assert_eq!(top_ups, 3);
+        assert_eq!(result.result.output(), expected.result.output());
+        assert_eq!(result.result.gas_used(), gas_used);
+        // extra gas is charged and the unused gas is reimbursed.
+        assert_eq!(
+            result.state[&CALLER].info.balance,
+            expected.state[&CALLER].info.balance
+        );
+        assert_eq!(evm.tx().gas_limit, 21_000 + 4 * slice + 3);
+
+        // discarded transaction leaves no changes behind.
+        evm.tx_mut().gas_limit = 21_000 + slice;
+        let ResumableOutcome::Suspended(suspended) = evm.transact_resumable().unwrap() else {
+            panic!("transaction is suspended");
+        };
+        evm.tx_mut().gas_limit = 50_000;
+        evm.discard_transaction(suspended);
+        assert_eq!(evm.transact().unwrap(), expected);
+
+        // dropped transaction is cleared when the next one starts.
+        evm.tx_mut().gas_limit = 21_000 + slice;
+        let ResumableOutcome::Suspended(suspended) = evm.transact_resumable().unwrap() else {
+            panic!("transaction is suspended");
+        };
+        drop(suspended);
+        evm.tx_mut().gas_limit = 50_000;
+        assert_eq!(evm.transact().unwrap(), expected);
+    }
 }
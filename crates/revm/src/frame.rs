@@ -30,10 +30,13 @@ pub struct CreateFrame {
 #[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EOFCreateFrame {
+    /// EOF create frame has a created address.
     pub created_address: Address,
+    /// Frame data.
     pub frame_data: FrameData,
 }
 
+/// Data shared by all frame types.
 #[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrameData {
@@ -41,6 +44,12 @@ pub struct FrameData {
     pub checkpoint: JournalCheckpoint,
     /// Interpreter.
     pub interpreter: Interpreter,
+    /// Gas of the frame inputs above [`CfgEnv::max_frame_gas`] that is not available to the
+    /// interpreter and is added back to the result when the frame ends.
+    ///
+    /// [`CfgEnv::max_frame_gas`]: crate::primitives::CfgEnv::max_frame_gas
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub gas_excess: u64,
 }
 
 /// Call stack frame.
@@ -142,7 +151,19 @@ pub enum FrameOrResult {
     Result(FrameResult),
 }
 
+impl FrameData {
+    /// Creates new frame data.
+    pub fn new(checkpoint: JournalCheckpoint, interpreter: Interpreter) -> Self {
+        Self {
+            checkpoint,
+            interpreter,
+            gas_excess: 0,
+        }
+    }
+}
+
 impl Frame {
+    /// Creates new create frame.
     pub fn new_create(
         created_address: Address,
         checkpoint: JournalCheckpoint,
@@ -150,13 +171,23 @@ impl Frame {
     ) -> Self {
         Frame::Create(Box::new(CreateFrame {
             created_address,
-            frame_data: FrameData {
-                checkpoint,
-                interpreter,
-            },
+            frame_data: FrameData::new(checkpoint, interpreter),
+        }))
+    }
+
+    /// Creates new EOF create frame.
+    pub fn new_eofcreate(
+        created_address: Address,
+        checkpoint: JournalCheckpoint,
+        interpreter: Interpreter,
+    ) -> Self {
+        Frame::EOFCreate(Box::new(EOFCreateFrame {
+            created_address,
+            frame_data: FrameData::new(checkpoint, interpreter),
         }))
     }
 
+    /// Creates new call frame.
     pub fn new_call(
         return_memory_range: Range<usize>,
         checkpoint: JournalCheckpoint,
@@ -164,10 +195,7 @@ impl Frame {
     ) -> Self {
         Frame::Call(Box::new(CallFrame {
             return_memory_range,
-            frame_data: FrameData {
-                checkpoint,
-                interpreter,
-            },
+            frame_data: FrameData::new(checkpoint, interpreter),
         }))
     }
 
@@ -181,14 +209,25 @@ impl Frame {
         matches!(self, Frame::Create { .. })
     }
 
-    /// Returns created address if frame is create otherwise returns None.
+    /// Returns true if frame is EOF create frame.
+    pub fn is_eofcreate(&self) -> bool {
+        matches!(self, Frame::EOFCreate { .. })
+    }
+
+    /// Returns created address if frame is create or EOF create otherwise returns None.
     pub fn created_address(&self) -> Option<Address> {
         match self {
+            Frame::Call(_) => None,
             Frame::Create(create_frame) => Some(create_frame.created_address),
-            _ => None,
+            Frame::EOFCreate(eof_create_frame) => Some(eof_create_frame.created_address),
         }
     }
 
+    /// Returns the journal checkpoint of the frame.
+    pub fn checkpoint(&self) -> JournalCheckpoint {
+        self.frame_data().checkpoint
+    }
+
     /// Takes frame and returns frame data.
     pub fn into_frame_data(self) -> FrameData {
         match self {
@@ -237,18 +276,17 @@ impl FrameOrResult {
         Self::Frame(Frame::new_create(created_address, checkpoint, interpreter))
     }
 
+    /// Creates new EOF create frame.
     pub fn new_eofcreate_frame(
         created_address: Address,
         checkpoint: JournalCheckpoint,
         interpreter: Interpreter,
     ) -> Self {
-        Self::Frame(Frame::EOFCreate(Box::new(EOFCreateFrame {
+        Self::Frame(Frame::new_eofcreate(
             created_address,
-            frame_data: FrameData {
-                checkpoint,
-                interpreter,
-            },
-        })))
+            checkpoint,
+            interpreter,
+        ))
     }
 
     /// Creates new call frame.
@@ -275,6 +313,7 @@ impl FrameOrResult {
         }))
     }
 
+    /// Creates new EOF create result.
     pub fn new_eofcreate_result(
         interpreter_result: InterpreterResult,
         address: Option<Address>,
@@ -285,6 +324,7 @@ impl FrameOrResult {
         }))
     }
 
+    /// Creates new call result.
     pub fn new_call_result(
         interpreter_result: InterpreterResult,
         memory_offset: Range<usize>,
@@ -294,4 +334,128 @@ impl FrameOrResult {
             memory_offset,
         }))
     }
+
+    /// Sets [`FrameData::gas_excess`] of the frame, results are not changed.
+    #[inline]
+    pub fn with_gas_excess(mut self, gas_excess: u64) -> Self {
+        if let FrameOrResult::Frame(frame) = &mut self {
+            frame.frame_data_mut().gas_excess = gas_excess;
+        }
+        self
+    }
+
+    /// Returns true if this is a frame.
+    #[inline]
+    pub fn is_frame(&self) -> bool {
+        matches!(self, FrameOrResult::Frame(_))
+    }
+
+    /// Returns true if this is a result.
+    #[inline]
+    pub fn is_result(&self) -> bool {
+        matches!(self, FrameOrResult::Result(_))
+    }
+
+    /// Returns the frame if this is a frame otherwise returns None.
+    #[inline]
+    pub fn into_frame(self) -> Option<Frame> {
+        match self {
+            FrameOrResult::Frame(frame) => Some(frame),
+            FrameOrResult::Result(_) => None,
+        }
+    }
+
+    /// Returns the result if this is a result otherwise returns None.
+    #[inline]
+    pub fn into_result(self) -> Option<FrameResult> {
+        match self {
+            FrameOrResult::Frame(_) => None,
+            FrameOrResult::Result(result) => Some(result),
+        }
+    }
+
+    /// Maps the result with the given function, frames are returned unchanged.
+    #[inline]
+    pub fn map_result(self, f: impl FnOnce(FrameResult) -> FrameResult) -> Self {
+        match self {
+            FrameOrResult::Frame(frame) => FrameOrResult::Frame(frame),
+            FrameOrResult::Result(result) => FrameOrResult::Result(f(result)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        interpreter::Gas,
+        primitives::{address, Bytecode, Bytes, ExecutionResult, TxKind, B256},
+        Evm,
+    };
+    use std::{sync::Arc, vec::Vec};
+
+    const ORACLE: Address = address!("00000000000000000000000000000000000000aa");
+
+    #[test]
+    // the previous call handle captured by the new one is not `Send`.
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn short_circuit_call() {
+        // STATICCALL ORACLE with 32 bytes of return buffer and return the buffer.
+        let mut code = Vec::from([0x60, 0x20, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73]);
+        code.extend_from_slice(ORACLE.as_slice());
+        code.extend_from_slice(&[0x61, 0xff, 0xff, 0xfa, 0x50, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code.into())))
+            .modify_tx_env(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.transact_to = Some(TxKind::Call(Address::ZERO));
+            })
+            .append_handler_register(|handler| {
+                let prev_call = handler.execution.call.clone();
+                handler.execution.call = Arc::new(move |context, inputs| {
+                    if inputs.target_address != ORACLE {
+                        return prev_call(context, inputs);
+                    }
+                    let result = InterpreterResult::new(
+                        InstructionResult::Return,
+                        Bytes::copy_from_slice(B256::with_last_byte(42).as_slice()),
+                        Gas::new(inputs.gas_limit),
+                    );
+                    Ok(FrameOrResult::new_call_result(
+                        result,
+                        inputs.return_memory_offset.clone(),
+                    ))
+                });
+            })
+            .build();
+
+        match evm.transact().unwrap().result {
+            ExecutionResult::Success {
+                output: Output::Call(output),
+                ..
+            } => assert_eq!(output[..], B256::with_last_byte(42)[..]),
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn map_result() {
+        let result = FrameOrResult::new_call_result(
+            InterpreterResult::new(InstructionResult::Return, Bytes::new(), Gas::new(100)),
+            0..0,
+        );
+        assert!(result.is_result() && !result.is_frame());
+
+        let result = result
+            .map_result(|mut result| {
+                assert!(result.gas_mut().record_cost(40));
+                result
+            })
+            .into_result()
+            .unwrap();
+        assert_eq!(result.gas().remaining(), 60);
+        assert_eq!(result.output(), Output::Call(Bytes::new()));
+    }
 }
@@ -1,9 +1,11 @@
 // Modules.
+mod chain_spec;
 mod handle_types;
 pub mod mainnet;
 pub mod register;
 
 // Exports.
+pub use chain_spec::{ChainSpec, EthereumChainSpec};
 pub use handle_types::*;
 
 // Includes.
@@ -16,7 +18,7 @@ use core::mem;
 use register::{EvmHandler, HandleRegisters};
 use std::vec::Vec;
 
-use self::register::{HandleRegister, HandleRegisterBox};
+use self::register::{HandleRegister, HandleRegisterArc, HandleRegisterBox};
 
 /// Handler acts as a proxy and allow to define different behavior for different
 /// sections of the code. This allows nice integration of different chains or
@@ -171,6 +173,12 @@ impl<'a, EXT, DB: Database> EvmHandler<'a, EXT, DB> {
         self.registers.push(HandleRegisters::Box(register));
     }
 
+    /// Append shared handle register.
+    pub fn append_handler_register_arc(&mut self, register: HandleRegisterArc<EXT, DB>) {
+        register(self);
+        self.registers.push(HandleRegisters::Arc(register));
+    }
+
     /// Pop last handle register and reapply all registers that are left.
     pub fn pop_handle_register(&mut self) -> Option<HandleRegisters<EXT, DB>> {
         let out = self.registers.pop();
@@ -181,11 +189,26 @@ impl<'a, EXT, DB: Database> EvmHandler<'a, EXT, DB> {
             for register in registers {
                 base_handler.append_handler_register(register)
             }
+            base_handler.cfg = self.cfg();
             *self = base_handler;
         }
         out
     }
 
+    /// Creates a new handler with the same configuration by applying the registers to the
+    /// mainnet handler.
+    ///
+    /// Changes made to the handler outside of the registers are not kept. Returns `None` if
+    /// a boxed register was appended, see [`HandleRegisters::try_clone`].
+    pub fn rebuild(&self) -> Option<Self> {
+        let mut handler = Handler::mainnet_with_spec(self.cfg.spec_id);
+        for register in &self.registers {
+            handler.append_handler_register(register.try_clone()?)
+        }
+        handler.cfg = self.cfg();
+        Some(handler)
+    }
+
     /// Creates the Handler with Generic Spec.
     pub fn create_handle_generic<SPEC: Spec>(&mut self) -> EvmHandler<'a, EXT, DB> {
         let registers = core::mem::take(&mut self.registers);
@@ -248,5 +271,8 @@ mod test {
 
         // first handler is reapplied
         assert_eq!(*test.borrow(), 3);
+
+        // boxed registers can't be reapplied to a rebuilt handler.
+        assert!(handler.rebuild().is_none());
     }
 }
@@ -0,0 +1,162 @@
+use super::register::EvmHandler;
+use crate::primitives::{db::Database, SpecId};
+use core::fmt::Debug;
+
+/// Specification of a chain built on top of the Ethereum hardforks.
+///
+/// A chain can define its own hardfork enum with extra forks interleaved between the Ethereum
+/// ones. The base EVM behavior is selected by the [`SpecId`] the hardfork maps to, while the
+/// chain specific handlers registered in [`ChainSpec::register_handler`] can compare against
+/// the full hardfork.
+///
+/// Use it with [`EvmBuilder::with_chain_spec`](crate::EvmBuilder::with_chain_spec).
+pub trait ChainSpec: Sized + 'static {
+    /// Hardforks of the chain, ordered by activation.
+    type Hardfork: Copy + Debug + PartialEq + PartialOrd + Into<SpecId> + 'static;
+
+    /// Registers the chain specific handlers for the hardfork.
+    ///
+    /// Called on top of the mainnet handler of the [`SpecId`] the hardfork maps to.
+    fn register_handler<EXT, DB: Database>(
+        handler: &mut EvmHandler<'_, EXT, DB>,
+        hardfork: Self::Hardfork,
+    ) {
+        let _ = handler;
+        let _ = hardfork;
+    }
+}
+
+/// Ethereum mainnet, its hardforks are the [`SpecId`]s.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EthereumChainSpec;
+
+impl ChainSpec for EthereumChainSpec {
+    type Hardfork = SpecId;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        primitives::{
+            address, Address, Bytes, ExecutionResult, Output, PrecompileOutput, PrecompileResult,
+            TxKind,
+        },
+        ContextPrecompile, ContextStatefulPrecompile, Evm, InnerEvmContext,
+    };
+    use std::sync::Arc;
+
+    /// Example chain with one extra hardfork between Cancun and Prague.
+    struct CustomChainSpec;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    enum CustomHardfork {
+        Shanghai,
+        Cancun,
+        /// Enables the [`ECHO`] precompile, the base EVM stays at Cancun.
+        Echo,
+        Prague,
+    }
+
+    impl From<CustomHardfork> for SpecId {
+        fn from(hardfork: CustomHardfork) -> Self {
+            match hardfork {
+                CustomHardfork::Shanghai => SpecId::SHANGHAI,
+                CustomHardfork::Cancun | CustomHardfork::Echo => SpecId::CANCUN,
+                CustomHardfork::Prague => SpecId::PRAGUE,
+            }
+        }
+    }
+
+    const ECHO: Address = address!("0000000000000000000000000000000000000e00");
+
+    /// Returns the input.
+    struct EchoPrecompile;
+
+    impl<DB: Database> ContextStatefulPrecompile<DB> for EchoPrecompile {
+        fn call(
+            &self,
+            input: &[u8],
+            _gas_limit: u64,
+            _context: &mut InnerEvmContext<DB>,
+        ) -> PrecompileResult {
+            Ok(PrecompileOutput::new(15, Bytes::copy_from_slice(input)))
+        }
+    }
+
+    impl ChainSpec for CustomChainSpec {
+        type Hardfork = CustomHardfork;
+
+        fn register_handler<EXT, DB: Database>(
+            handler: &mut EvmHandler<'_, EXT, DB>,
+            hardfork: CustomHardfork,
+        ) {
+            if hardfork < CustomHardfork::Echo {
+                return;
+            }
+            let precompiles = handler.pre_execution.load_precompiles();
+            handler.pre_execution.load_precompiles = Arc::new(move || {
+                let mut precompiles = precompiles.clone();
+                precompiles.extend([(
+                    ECHO,
+                    ContextPrecompile::ContextStateful(Arc::new(EchoPrecompile)),
+                )]);
+                precompiles
+            });
+        }
+    }
+
+    fn call_echo(hardfork: CustomHardfork) -> (SpecId, ExecutionResult) {
+        let mut evm = Evm::builder()
+            .with_db(CacheDB::new(EmptyDB::default()))
+            .modify_tx_env(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.transact_to = Some(TxKind::Call(ECHO));
+                tx.data = Bytes::from_static(b"echo");
+                tx.gas_limit = 100_000;
+            })
+            .with_chain_spec::<CustomChainSpec>(hardfork)
+            .build();
+        let result = evm.transact().unwrap().result;
+        (evm.spec_id(), result)
+    }
+
+    #[test]
+    fn custom_hardfork_toggles_precompile() {
+        let (spec_id, result) = call_echo(CustomHardfork::Cancun);
+        assert_eq!(spec_id, SpecId::CANCUN);
+        assert_eq!(result.output(), Some(&Bytes::new()));
+        assert_eq!(result.gas_used(), 21_000 + 4 * 16);
+
+        for hardfork in [CustomHardfork::Echo, CustomHardfork::Prague] {
+            let (spec_id, result) = call_echo(hardfork);
+            assert_eq!(spec_id, hardfork.into());
+            assert!(matches!(
+                result,
+                ExecutionResult::Success {
+                    output: Output::Call(ref output),
+                    ..
+                } if output == &Bytes::from_static(b"echo")
+            ));
+            // EIP-7623 calldata floor of Prague, 10 gas per token of the non zero bytes.
+            let floor = if hardfork == CustomHardfork::Prague {
+                21_000 + 4 * 4 * 10
+            } else {
+                0
+            };
+            assert_eq!(result.gas_used(), (21_000 + 4 * 16 + 15).max(floor));
+        }
+
+        assert!(CustomHardfork::Shanghai < CustomHardfork::Echo);
+        assert_eq!(SpecId::from(CustomHardfork::Shanghai), SpecId::SHANGHAI);
+    }
+
+    #[test]
+    fn ethereum_chain_spec() {
+        let evm = Evm::builder()
+            .with_chain_spec::<EthereumChainSpec>(SpecId::BERLIN)
+            .build();
+        assert_eq!(evm.spec_id(), SpecId::BERLIN);
+    }
+}
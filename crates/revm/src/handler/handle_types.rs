@@ -8,7 +8,8 @@ pub mod validation;
 // Exports
 
 pub use validation::{
-    ValidateEnvHandle, ValidateInitialTxGasHandle, ValidateTxEnvAgainstState, ValidationHandler,
+    AccountOverride, ValidateEnvHandle, ValidateInitialTxGasHandle, ValidateTxEnvAgainstState,
+    ValidationHandler,
 };
 
 pub use execution::{
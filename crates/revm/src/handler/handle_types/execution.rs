@@ -143,6 +143,24 @@ pub struct ExecutionHandler<'a, EXT, DB: Database> {
     pub insert_eofcreate_outcome: InsertEOFCreateOutcomeHandle<'a, EXT, DB>,
 }
 
+impl<EXT, DB: Database> Clone for ExecutionHandler<'_, EXT, DB> {
+    fn clone(&self) -> Self {
+        Self {
+            last_frame_return: self.last_frame_return.clone(),
+            execute_frame: self.execute_frame.clone(),
+            call: self.call.clone(),
+            call_return: self.call_return.clone(),
+            insert_call_outcome: self.insert_call_outcome.clone(),
+            create: self.create.clone(),
+            create_return: self.create_return.clone(),
+            insert_create_outcome: self.insert_create_outcome.clone(),
+            eofcreate: self.eofcreate.clone(),
+            eofcreate_return: self.eofcreate_return.clone(),
+            insert_eofcreate_outcome: self.insert_eofcreate_outcome.clone(),
+        }
+    }
+}
+
 impl<'a, EXT: 'a, DB: Database + 'a> ExecutionHandler<'a, EXT, DB> {
     /// Creates mainnet ExecutionHandler.
     pub fn new<SPEC: Spec + 'a>() -> Self {
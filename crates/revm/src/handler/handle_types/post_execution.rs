@@ -7,6 +7,15 @@ use crate::{
 };
 use std::sync::Arc;
 
+/// Calculates the final gas refund of the transaction.
+///
+/// Called after [`last_frame_return`](crate::handler::ExecutionHandler::last_frame_return),
+/// `gas` is the gas of the transaction with the refund recorded by the execution.
+/// Refund can be modified here before the caller is reimbursed.
+pub type RefundHandle<'a, EXT, DB> = Arc<
+    dyn Fn(&mut Context<EXT, DB>, &mut Gas) -> EVMResultGeneric<(), <DB as Database>::Error> + 'a,
+>;
+
 /// Reimburse the caller with ethereum it didn't spent.
 pub type ReimburseCallerHandle<'a, EXT, DB> =
     Arc<dyn Fn(&mut Context<EXT, DB>, &Gas) -> EVMResultGeneric<(), <DB as Database>::Error> + 'a>;
@@ -41,6 +50,8 @@ pub type ClearHandle<'a, EXT, DB> = Arc<dyn Fn(&mut Context<EXT, DB>) + 'a>;
 
 /// Handles related to post execution after the stack loop is finished.
 pub struct PostExecutionHandler<'a, EXT, DB: Database> {
+    /// Calculate the final gas refund.
+    pub refund: RefundHandle<'a, EXT, DB>,
     /// Reimburse the caller with ethereum it didn't spent.
     pub reimburse_caller: ReimburseCallerHandle<'a, EXT, DB>,
     /// Reward the beneficiary with caller fee.
@@ -60,6 +71,7 @@ impl<'a, EXT: 'a, DB: Database + 'a> PostExecutionHandler<'a, EXT, DB> {
     /// Creates mainnet MainHandles.
     pub fn new<SPEC: Spec + 'a>() -> Self {
         Self {
+            refund: Arc::new(mainnet::refund::<SPEC, EXT, DB>),
             reimburse_caller: Arc::new(mainnet::reimburse_caller::<SPEC, EXT, DB>),
             reward_beneficiary: Arc::new(mainnet::reward_beneficiary::<SPEC, EXT, DB>),
             output: Arc::new(mainnet::output::<EXT, DB>),
@@ -70,6 +82,15 @@ impl<'a, EXT: 'a, DB: Database + 'a> PostExecutionHandler<'a, EXT, DB> {
 }
 
 impl<'a, EXT, DB: Database> PostExecutionHandler<'a, EXT, DB> {
+    /// Calculate the final gas refund.
+    pub fn refund(
+        &self,
+        context: &mut Context<EXT, DB>,
+        gas: &mut Gas,
+    ) -> Result<(), EVMError<DB::Error>> {
+        (self.refund)(context, gas)
+    }
+
     /// Reimburse the caller with gas that were not spend.
     pub fn reimburse_caller(
         &self,
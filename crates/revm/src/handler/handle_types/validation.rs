@@ -1,6 +1,7 @@
 use crate::{
     handler::mainnet,
-    primitives::{db::Database, EVMError, Env, Spec},
+    interpreter::gas::InitialAndFloorGas,
+    primitives::{db::Database, EVMError, Env, Spec, U256},
     Context,
 };
 use std::sync::Arc;
@@ -14,9 +15,21 @@ pub type ValidateEnvHandle<'a, DB> =
 pub type ValidateTxEnvAgainstState<'a, EXT, DB> =
     Arc<dyn Fn(&mut Context<EXT, DB>) -> Result<(), EVMError<<DB as Database>::Error>> + 'a>;
 
+/// Nonce and balance that are used instead of the state ones when the transaction
+/// is validated against the state.
+///
+/// Fields that are `None` are taken from the state.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AccountOverride {
+    /// Nonce of the account, for example the pending nonce of the sender.
+    pub nonce: Option<u64>,
+    /// Balance of the account.
+    pub balance: Option<U256>,
+}
+
 /// Initial gas calculation handle
 pub type ValidateInitialTxGasHandle<'a, DB> =
-    Arc<dyn Fn(&Env) -> Result<u64, EVMError<<DB as Database>::Error>> + 'a>;
+    Arc<dyn Fn(&Env) -> Result<InitialAndFloorGas, EVMError<<DB as Database>::Error>> + 'a>;
 
 /// Handles related to validation.
 pub struct ValidationHandler<'a, EXT, DB: Database> {
@@ -45,8 +58,8 @@ impl<'a, EXT, DB: Database> ValidationHandler<'a, EXT, DB> {
         (self.env)(env)
     }
 
-    /// Initial gas
-    pub fn initial_tx_gas(&self, env: &Env) -> Result<u64, EVMError<DB::Error>> {
+    /// Initial gas and the gas floor.
+    pub fn initial_tx_gas(&self, env: &Env) -> Result<InitialAndFloorGas, EVMError<DB::Error>> {
         (self.initial_tx_gas)(env)
     }
 
@@ -5,7 +5,7 @@ use crate::{
         return_ok, return_revert, CallInputs, CreateInputs, CreateOutcome, Gas, InstructionResult,
         SharedMemory,
     },
-    primitives::{EVMError, Env, Spec, SpecId},
+    primitives::{EVMError, Env, ExecutionProfile, Spec},
     CallFrame, Context, CreateFrame, Frame, FrameOrResult, FrameResult,
 };
 use core::mem;
@@ -25,9 +25,27 @@ pub fn execute_frame<SPEC: Spec, EXT, DB: Database>(
 ) -> Result<InterpreterAction, EVMError<DB::Error>> {
     let interpreter = frame.interpreter_mut();
     let memory = mem::replace(shared_memory, EMPTY_SHARED_MEMORY);
-    let next_action = match instruction_tables {
-        InstructionTables::Plain(table) => interpreter.run(memory, table, context),
-        InstructionTables::Boxed(table) => interpreter.run(memory, table, context),
+    let next_action = if context.evm.env.cfg.profile_execution {
+        let mut profile = context.evm.execution_profile.take().unwrap_or_else(|| {
+            Box::new(ExecutionProfile::new(
+                context.evm.env.cfg.profile_sample_interval,
+            ))
+        });
+        let next_action = match instruction_tables {
+            InstructionTables::Plain(table) => {
+                interpreter.run_profiled(memory, table, context, &mut profile)
+            }
+            InstructionTables::Boxed(table) => {
+                interpreter.run_profiled(memory, table, context, &mut profile)
+            }
+        };
+        context.evm.execution_profile = Some(profile);
+        next_action
+    } else {
+        match instruction_tables {
+            InstructionTables::Plain(table) => interpreter.run(memory, table, context),
+            InstructionTables::Boxed(table) => interpreter.run(memory, table, context),
+        }
     };
     // Take the shared memory back.
     *shared_memory = interpreter.take_memory();
@@ -48,7 +66,7 @@ pub fn frame_return_with_refund_flag<SPEC: Spec>(
     let refunded = gas.refunded();
 
     // Spend the gas limit. Gas is reimbursed when the tx returns successfully.
-    *gas = Gas::new_spent(env.tx.gas_limit);
+    *gas = Gas::new_spent(env.tx().gas_limit);
 
     match instruction_result {
         return_ok!() => {
@@ -67,17 +85,19 @@ pub fn frame_return_with_refund_flag<SPEC: Spec>(
     // gas spend. (Before london it was 2th part of gas spend)
     if refund_enabled {
         // EIP-3529: Reduction in refunds
-        gas.set_final_refund(SPEC::SPEC_ID.is_enabled_in(SpecId::LONDON));
+        gas.finalize_refund(SPEC::SPEC_ID);
     }
 }
 
 /// Handle output of the transaction
+///
+/// Refund is finalized in [`refund`](crate::handler::mainnet::refund) post execution handle.
 #[inline]
 pub fn last_frame_return<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
     frame_result: &mut FrameResult,
 ) -> Result<(), EVMError<DB::Error>> {
-    frame_return_with_refund_flag::<SPEC>(&context.evm.env, frame_result, true);
+    frame_return_with_refund_flag::<SPEC>(&context.evm.env, frame_result, false);
     Ok(())
 }
 
@@ -96,6 +116,9 @@ pub fn call_return<EXT, DB: Database>(
     frame: Box<CallFrame>,
     interpreter_result: InterpreterResult,
 ) -> Result<CallOutcome, EVMError<DB::Error>> {
+    context
+        .evm
+        .keep_lazy_analysis(&frame.frame_data.interpreter.contract);
     context
         .evm
         .call_return(&interpreter_result, frame.frame_data.checkpoint);
@@ -203,13 +226,23 @@ pub fn insert_eofcreate_outcome<EXT, DB: Database>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        primitives::{
+            address, bytes, AccessListItem, AccountInfo, Address, Bytecode, GasScheduleOverrides,
+            InvalidTransaction, SpecId, TxKind, B256, U256,
+        },
+        test_utils::{contract_evm, CONTRACT},
+        Evm,
+    };
     use revm_interpreter::primitives::CancunSpec;
     use revm_precompile::Bytes;
+    use std::{vec, vec::Vec};
 
     /// Creates frame result.
     fn call_last_frame_return(instruction_result: InstructionResult, gas: Gas) -> Gas {
         let mut env = Env::default();
-        env.tx.gas_limit = 100;
+        env.tx_mut().gas_limit = 100;
 
         let mut first_frame = FrameResult::Call(CallOutcome::new(
             InterpreterResult {
@@ -254,4 +287,217 @@ mod tests {
         assert_eq!(gas.spent(), 10);
         assert_eq!(gas.refunded(), 0);
     }
+
+    /// Gas used by a contract that calls `target` with `value`.
+    fn call_gas_used(spec: SpecId, target: Option<AccountInfo>, value: u8) -> u64 {
+        const TARGET: Address = address!("3000000000000000000000000000000000000003");
+        call_gas_used_with(spec, TARGET, target, value, false)
+    }
+
+    /// Gas used by a contract that calls `to` with `value` and zero gas, with `to`
+    /// added to the access list if `warm`.
+    fn call_gas_used_with(
+        spec: SpecId,
+        to: Address,
+        target: Option<AccountInfo>,
+        value: u8,
+        warm: bool,
+    ) -> u64 {
+        // CALL(0, to, value, 0, 0, 0, 0), STOP
+        let mut code = vec![
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, value, 0x73,
+        ];
+        code.extend_from_slice(to.as_slice());
+        code.extend_from_slice(&[0x60, 0x00, 0xf1, 0x00]);
+
+        let mut evm = contract_evm(code)
+            .modify_db(|db| {
+                db.accounts.get_mut(&CONTRACT).unwrap().info.balance = U256::from(100);
+                if let Some(target) = target {
+                    db.insert_account_info(to, target);
+                }
+            })
+            .with_spec_id(spec)
+            .modify_tx_env(|tx| {
+                if warm {
+                    tx.access_list = vec![AccessListItem {
+                        address: to,
+                        storage_keys: Vec::new(),
+                    }];
+                }
+            })
+            .build();
+        evm.transact().unwrap().result.expect_success().2
+    }
+
+    #[rstest::rstest]
+    #[case::tangerine_no_value(SpecId::TANGERINE, 0, 25_000, 0)]
+    #[case::tangerine_value(SpecId::TANGERINE, 1, 25_000, 0)]
+    #[case::spurious_dragon_no_value(SpecId::SPURIOUS_DRAGON, 0, 0, 0)]
+    #[case::spurious_dragon_value(SpecId::SPURIOUS_DRAGON, 1, 25_000, 25_000)]
+    fn call_new_account_gas(
+        #[case] spec: SpecId,
+        #[case] value: u8,
+        #[case] missing_charge: u64,
+        #[case] empty_charge: u64,
+    ) {
+        let funded = call_gas_used(spec, Some(AccountInfo::from_balance(U256::from(1))), value);
+        let missing = call_gas_used(spec, None, value);
+        let empty = call_gas_used(spec, Some(AccountInfo::default()), value);
+        assert_eq!(missing - funded, missing_charge);
+        assert_eq!(empty - funded, empty_charge);
+    }
+
+    #[rstest::rstest]
+    fn call_stipend_gas(
+        #[values(SpecId::BERLIN, SpecId::LONDON, SpecId::SHANGHAI)] spec: SpecId,
+        #[values(false, true)] warm: bool,
+        #[values(0, 1)] value: u8,
+        #[values("empty", "existing", "precompile")] target: &str,
+    ) {
+        // identity precompile, it exists as on mainnet and uses 15 gas of the stipend.
+        const IDENTITY: Address = address!("0000000000000000000000000000000000000004");
+        const TARGET: Address = address!("3000000000000000000000000000000000000003");
+        let (to, info, callee_gas) = match target {
+            "empty" => (TARGET, None, 0),
+            "existing" => (TARGET, Some(AccountInfo::from_balance(U256::from(1))), 0),
+            // without value precompile gets no gas and fails without using any.
+            "precompile" => (
+                IDENTITY,
+                Some(AccountInfo::from_balance(U256::from(1))),
+                if value == 0 { 0 } else { 15 },
+            ),
+            _ => unreachable!(),
+        };
+
+        // intrinsic gas, 7 pushes and the access list.
+        let mut expected = 21_000 + 7 * 3;
+        if warm {
+            expected += 2_400;
+        }
+        // precompiles are always warm.
+        expected += if warm || target == "precompile" {
+            100
+        } else {
+            2_600
+        };
+        if value != 0 {
+            // the stipend is free, what the callee does not use is returned to the caller.
+            expected += 9_000 - 2_300;
+            if target == "empty" {
+                expected += 25_000;
+            }
+        }
+        expected += callee_gas;
+
+        assert_eq!(call_gas_used_with(spec, to, info, value, warm), expected);
+    }
+
+    #[test]
+    fn nested_large_return_data() {
+        // Returns 256 KiB through `depth` nested calls to itself, see `return_data` bench.
+        let code =
+            bytes!("5f3580600c57620400005ff35b600190035f525f5f60205f5f305af1503d5f5f3e3d5ff3");
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+            .modify_tx_env(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.transact_to = Some(TxKind::Call(Address::ZERO));
+                tx.data = Bytes::copy_from_slice(B256::with_last_byte(3).as_slice());
+                tx.gas_limit = 30_000_000;
+            })
+            .build();
+
+        let (_, output, _) = evm.transact().unwrap().result.expect_success();
+        assert_eq!(output.data().len(), 256 * 1024);
+        assert!(output.data().iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn max_gas_limit_with_gas_limit_cap() {
+        const CALLEE: Address = address!("3000000000000000000000000000000000000003");
+        // CALL(GAS, CALLEE, 0, 0, 0, 0, 0), STOP
+        let mut code = vec![0x5f, 0x5f, 0x5f, 0x5f, 0x5f, 0x73];
+        code.extend_from_slice(CALLEE.as_slice());
+        code.extend_from_slice(&[0x5a, 0xf1, 0x00]);
+
+        let mut evm = contract_evm(code)
+            .modify_db(|db| {
+                // CALLEE forwards all the gas again, a frame deeper.
+                db.insert_account_info(
+                    CALLEE,
+                    AccountInfo::from_bytecode(Bytecode::new_raw(bytes!("5f5f5f5f5f305af100"))),
+                );
+            })
+            .modify_block_env(|block| block.gas_limit = U256::from(30_000_000))
+            .modify_tx_env(|tx| {
+                tx.gas_limit = u64::MAX;
+                tx.gas_price = U256::ZERO;
+            })
+            .build();
+        assert!(matches!(
+            evm.transact(),
+            Err(EVMError::Transaction(
+                InvalidTransaction::CallerGasLimitMoreThanBlock
+            ))
+        ));
+
+        evm.cfg_mut().tx_gas_limit_cap = Some(u64::MAX);
+        let result = evm.transact().unwrap().result;
+        // CALLEE calls itself until the call depth limit.
+        assert!(result.is_success());
+        assert!(result.gas_used() < 30_000_000);
+    }
+    #[test]
+    fn gas_overrides_reprice_opcodes() {
+        // SSTORE(0, 1), MSTORE(0, 0)
+        let mut evm = contract_evm(bytes!("60015f555f5f5200")).build();
+        let mut gas_used = |gas_overrides| {
+            evm.cfg_mut().gas_overrides = gas_overrides;
+            evm.transact().unwrap().result.gas_used()
+        };
+
+        let default = gas_used(None);
+        assert_eq!(default, 21_000 + 3 + 2 + 22_100 + 2 + 2 + 6);
+        assert_eq!(gas_used(Some(GasScheduleOverrides::NONE)), default);
+
+        let opcodes = gas_used(Some(GasScheduleOverrides {
+            sstore_set: Some(5_000),
+            cold_sload: Some(1_000),
+            memory_word: Some(10),
+            ..Default::default()
+        }));
+        assert_eq!(opcodes, default - 15_000 - 1_100 + 7);
+    }
+
+    #[test]
+    fn execution_profile_counts_opcodes() {
+        // PUSH1 1, PUSH1 2, ADD, POP, STOP
+        let code = bytes!("600160020150");
+        let code_hash = Bytecode::new_raw(code.clone()).hash_slow();
+        let mut evm = contract_evm(code).build();
+        assert_eq!(evm.transact().unwrap().profile, None);
+
+        evm.cfg_mut().profile_execution = true;
+        evm.cfg_mut().profile_sample_interval = 1;
+        let profile = evm.transact().unwrap().profile.unwrap();
+        assert_eq!(profile.steps(), 5);
+        assert_eq!(profile.opcode_count(0x60), 2);
+        assert_eq!(profile.opcode_count(0x01), 1);
+        assert_eq!(profile.opcode_count(0x50), 1);
+        // implicit STOP of the padding.
+        assert_eq!(profile.opcode_count(0x00), 1);
+        let mut pcs: Vec<_> = profile
+            .pc_hits(&code_hash)
+            .unwrap()
+            .keys()
+            .copied()
+            .collect();
+        pcs.sort_unstable();
+        assert_eq!(pcs, [0, 2, 4, 5, 6]);
+
+        // profile is collected per transaction.
+        let profile = evm.transact().unwrap().profile.unwrap();
+        assert_eq!(profile.steps(), 5);
+    }
 }
@@ -1,11 +1,12 @@
 use crate::{
     interpreter::{Gas, SuccessOrHalt},
     primitives::{
-        db::Database, EVMError, ExecutionResult, ResultAndState, Spec, SpecId::LONDON,
-        KECCAK_EMPTY, U256,
+        db::Database, EVMError, ExecutionResult, InvalidTransaction, ResultAndState, Spec,
+        SpecId::LONDON, KECCAK_EMPTY, U256,
     },
     Context, FrameResult,
 };
+use std::boxed::Box;
 
 /// Mainnet end handle does not change the output.
 #[inline]
@@ -21,10 +22,24 @@ pub fn end<EXT, DB: Database>(
 pub fn clear<EXT, DB: Database>(context: &mut Context<EXT, DB>) {
     // clear error and journaled state.
     let _ = context.evm.take_error();
+    context.evm.clear_precompile_error();
     context.evm.inner.journaled_state.clear();
     // Clear valid authorizations after each transaction.
     // If transaction is valid they are consumed in `output` handler.
     context.evm.inner.valid_authorizations.clear();
+    context.evm.inner.execution_profile = None;
+    context.evm.inner.initcode_hashes = None;
+    context.evm.inner.eof_initcode_error = None;
+}
+
+/// Finalizes the gas refund, refund is capped by EIP-3529 after London.
+#[inline]
+pub fn refund<SPEC: Spec, EXT, DB: Database>(
+    _context: &mut Context<EXT, DB>,
+    gas: &mut Gas,
+) -> Result<(), EVMError<DB::Error>> {
+    gas.finalize_refund(SPEC::SPEC_ID);
+    Ok(())
 }
 
 /// Reward beneficiary with gas fee.
@@ -34,27 +49,31 @@ pub fn reward_beneficiary<SPEC: Spec, EXT, DB: Database>(
     gas: &Gas,
 ) -> Result<(), EVMError<DB::Error>> {
     let beneficiary = context.evm.env.block.coinbase;
-    let effective_gas_price = context.evm.env.effective_gas_price();
+    let basefee = context.evm.env.block.basefee;
 
     // transfer fee to coinbase/beneficiary.
     // EIP-1559 discard basefee for coinbase transfer. Basefee amount of gas is discarded.
     let coinbase_gas_price = if SPEC::enabled(LONDON) {
-        effective_gas_price.saturating_sub(context.evm.env.block.basefee)
+        context.evm.env.tx().priority_fee(basefee)
     } else {
-        effective_gas_price
+        context.evm.env.tx().effective_gas_price(basefee)
     };
 
-    let (coinbase_account, _) = context
+    context
         .evm
         .inner
         .journaled_state
         .load_account(beneficiary, &mut context.evm.inner.db)?;
 
-    coinbase_account.mark_touch();
-    coinbase_account.info.balance = coinbase_account
-        .info
-        .balance
-        .saturating_add(coinbase_gas_price * U256::from(gas.spent() - gas.refunded() as u64));
+    context
+        .evm
+        .inner
+        .journaled_state
+        .balance_incr(
+            beneficiary,
+            coinbase_gas_price * U256::from(gas.spent() - gas.refunded() as u64),
+        )
+        .map_err(|_| InvalidTransaction::OverflowPaymentInTransaction)?;
 
     Ok(())
 }
@@ -64,7 +83,7 @@ pub fn reimburse_caller<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
     gas: &Gas,
 ) -> Result<(), EVMError<DB::Error>> {
-    let caller = context.evm.env.tx.caller;
+    let caller = context.evm.env.tx().caller;
     let effective_gas_price = context.evm.env.effective_gas_price();
 
     // return balance of not spend gas.
@@ -94,8 +113,32 @@ pub fn output<EXT, DB: Database>(
     let final_gas_used = result.gas().spent() - gas_refunded;
     let output = result.output();
     let instruction_result = result.into_interpreter_result();
+    let success_or_halt: SuccessOrHalt = instruction_result.result.into();
+
+    // Take back the minted amount from the caller of a halted transaction. Validation
+    // ensures that the gas is paid without it.
+    if let Some(mint) = context.evm.env.tx().mint {
+        if success_or_halt.is_halt() && !context.evm.env.persist_mint_on_halt() {
+            let caller = context.evm.inner.env.tx().caller;
+            let mint = U256::from(mint);
+            if context
+                .evm
+                .journaled_state
+                .balance_decr(caller, mint)
+                .is_err()
+            {
+                let balance = context.evm.journaled_state.account(caller).info.balance;
+                return Err(InvalidTransaction::LackOfFundForMaxFee {
+                    fee: Box::new(mint),
+                    balance: Box::new(balance),
+                }
+                .into());
+            }
+        }
+    }
 
     // reset journal and return present state.
+    let selfdestructs = context.evm.journaled_state.take_selfdestructs();
     let (mut state, logs) = context.evm.journaled_state.finalize();
 
     // clear code of authorized accounts.
@@ -108,7 +151,7 @@ pub fn output<EXT, DB: Database>(
         account.storage.clear();
     }
 
-    let result = match instruction_result.result.into() {
+    let result = match success_or_halt {
         SuccessOrHalt::Success(reason) => ExecutionResult::Success {
             reason,
             gas_used: final_gas_used,
@@ -133,5 +176,219 @@ pub fn output<EXT, DB: Database>(
         }
     };
 
-    Ok(ResultAndState { result, state })
+    Ok(ResultAndState {
+        result,
+        state,
+        tx_type: context.evm.env.tx().tx_type(),
+        selfdestructs,
+        profile: context.evm.execution_profile.take(),
+        #[cfg(feature = "optimism")]
+        l1_fee: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::InMemoryDB,
+        primitives::{
+            address, bytes, AccountInfo, Address, Bytecode, CancunSpec, OpcodeEnvOverrides,
+            SelfDestruct,
+        },
+        test_utils::{contract_evm, CALLER},
+    };
+
+    fn reward(coinbase_balance: U256) -> (Result<(), EVMError<core::convert::Infallible>>, U256) {
+        let coinbase = Address::with_last_byte(0xcb);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(coinbase, AccountInfo::from_balance(coinbase_balance));
+        let mut context: Context<(), InMemoryDB> = Context::new_with_db(db);
+        context.evm.inner.env.block.coinbase = coinbase;
+        context.evm.inner.env.tx_mut().gas_price = U256::from(2);
+
+        let mut gas = Gas::new(100);
+        assert!(gas.record_cost(10));
+        let result = reward_beneficiary::<CancunSpec, (), _>(&mut context, &gas);
+        let balance = context
+            .evm
+            .inner
+            .journaled_state
+            .account(coinbase)
+            .info
+            .balance;
+        (result, balance)
+    }
+
+    #[test]
+    fn test_reward_beneficiary() {
+        let (result, balance) = reward(U256::from(1));
+        assert!(result.is_ok());
+        assert_eq!(balance, U256::from(21));
+    }
+
+    #[test]
+    fn test_reward_beneficiary_overflow() {
+        let (result, balance) = reward(U256::MAX);
+        assert_eq!(
+            result,
+            Err(EVMError::Transaction(
+                InvalidTransaction::OverflowPaymentInTransaction
+            ))
+        );
+        assert_eq!(balance, U256::MAX);
+    }
+
+    #[test]
+    fn mint_on_halt() {
+        // INVALID
+        let mut evm = contract_evm(bytes!("fe"))
+            .modify_db(|db| {
+                db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1_000_000)))
+            })
+            .modify_cfg_env(|cfg| cfg.allow_mint = true)
+            .modify_tx_env(|tx| {
+                tx.gas_price = U256::from(1);
+                tx.mint = Some(500);
+            })
+            .build();
+
+        // all gas is consumed by the halt.
+        let ResultAndState { result, state, .. } = evm.transact().unwrap();
+        assert!(result.is_halt());
+        assert_eq!(
+            state[&CALLER].info.balance,
+            U256::from(1_000_000 + 500 - 100_000)
+        );
+
+        evm.cfg_mut().persist_mint_on_halt = false;
+        let ResultAndState { result, state, .. } = evm.transact().unwrap();
+        assert!(result.is_halt());
+        assert_eq!(state[&CALLER].info.balance, U256::from(1_000_000 - 100_000));
+    }
+
+    #[test]
+    fn mint_that_pays_gas_is_not_taken_back() {
+        // INVALID
+        let mut evm = contract_evm(bytes!("fe"))
+            .modify_cfg_env(|cfg| cfg.allow_mint = true)
+            .modify_tx_env(|tx| {
+                tx.gas_price = U256::from(1);
+                tx.mint = Some(100_000);
+            })
+            .build();
+
+        // the mint pays for the gas and is kept.
+        let ResultAndState { result, state, .. } = evm.transact().unwrap();
+        assert!(result.is_halt());
+        assert_eq!(state[&CALLER].info.balance, U256::ZERO);
+
+        // without the mint the caller can't pay for the gas.
+        evm.cfg_mut().persist_mint_on_halt = false;
+        assert_eq!(
+            evm.transact().unwrap_err(),
+            EVMError::Transaction(InvalidTransaction::LackOfFundForMaxFee {
+                fee: Box::new(U256::from(100_000)),
+                balance: Box::new(U256::ZERO),
+            })
+        );
+    }
+
+    #[test]
+    fn opcode_overrides_do_not_change_fees() {
+        const COINBASE: Address = address!("4000000000000000000000000000000000000004");
+        const FAKE_COINBASE: Address = address!("5000000000000000000000000000000000000005");
+        const FAKE_ORIGIN: Address = address!("6000000000000000000000000000000000000006");
+
+        // COINBASE, ORIGIN, GASPRICE, CHAINID, STOP
+        let mut evm = contract_evm(bytes!("41323a4600"))
+            .modify_db(|db| {
+                db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10_000_000)))
+            })
+            .modify_cfg_env(|cfg| {
+                cfg.opcode_overrides = Some(OpcodeEnvOverrides {
+                    coinbase: Some(FAKE_COINBASE),
+                    origin: Some(FAKE_ORIGIN),
+                    gas_price: Some(U256::from(7)),
+                    chain_id: Some(1234),
+                })
+            })
+            .modify_block_env(|block| block.coinbase = COINBASE)
+            .modify_tx_env(|tx| {
+                tx.gas_limit = 200_000;
+                tx.gas_price = U256::from(10);
+            })
+            .build();
+        let ResultAndState { result, state, .. } = evm.transact().unwrap();
+        let gas_used = result.expect_success().2;
+
+        // fees are charged to the real caller and paid to the real coinbase.
+        assert_eq!(
+            state[&COINBASE].info.balance,
+            U256::from(gas_used) * U256::from(10)
+        );
+        assert!(!state.contains_key(&FAKE_COINBASE));
+        assert!(!state.contains_key(&FAKE_ORIGIN));
+        assert_eq!(
+            state[&CALLER].info.balance,
+            U256::from(10_000_000 - gas_used * 10)
+        );
+    }
+
+    #[test]
+    fn selfdestructs_of_reverted_sub_calls_are_excluded() {
+        const REVERTER: Address = address!("3000000000000000000000000000000000000003");
+        const DESTRUCTED: Address = address!("4000000000000000000000000000000000000004");
+        const REVERTED: Address = address!("5000000000000000000000000000000000000005");
+        const BENEFICIARY: Address = address!("6000000000000000000000000000000000000006");
+        // CALL(GAS, address, 0, 0, 0, 0, 0), POP
+        let call = |code: &mut Vec<u8>, address: Address| {
+            code.extend_from_slice(&[0x5f, 0x5f, 0x5f, 0x5f, 0x5f, 0x73]);
+            code.extend_from_slice(address.as_slice());
+            code.extend_from_slice(&[0x5a, 0xf1, 0x50]);
+        };
+        let contract = |code: Vec<u8>, balance: u64| AccountInfo {
+            balance: U256::from(balance),
+            ..AccountInfo::from_bytecode(Bytecode::new_raw(code.into()))
+        };
+        // SELFDESTRUCT(BENEFICIARY)
+        let mut selfdestruct = vec![0x73];
+        selfdestruct.extend_from_slice(BENEFICIARY.as_slice());
+        selfdestruct.push(0xff);
+        // Calls DESTRUCTED and REVERTER, that calls REVERTED and reverts.
+        let mut code = Vec::new();
+        call(&mut code, DESTRUCTED);
+        call(&mut code, REVERTER);
+        let mut reverter = Vec::new();
+        call(&mut reverter, REVERTED);
+        reverter.extend_from_slice(&[0x5f, 0x5f, 0xfd]);
+
+        let mut evm = contract_evm(code)
+            .modify_db(|db| {
+                db.insert_account_info(REVERTER, contract(reverter, 0));
+                db.insert_account_info(DESTRUCTED, contract(selfdestruct.clone(), 7));
+                db.insert_account_info(REVERTED, contract(selfdestruct, 9));
+            })
+            .modify_tx_env(|tx| tx.gas_limit = 1_000_000)
+            .build();
+
+        let ResultAndState {
+            result,
+            state,
+            selfdestructs,
+            ..
+        } = evm.transact().unwrap();
+        result.expect_success();
+        assert_eq!(
+            selfdestructs,
+            [SelfDestruct {
+                address: DESTRUCTED,
+                target: BENEFICIARY,
+                value: U256::from(7),
+                deleted: false,
+            }]
+        );
+        assert_eq!(state[&BENEFICIARY].info.balance, U256::from(7));
+        assert_eq!(state[&REVERTED].info.balance, U256::from(9));
+    }
 }
@@ -6,13 +6,13 @@ use crate::{
     precompile::PrecompileSpecId,
     primitives::{
         db::Database,
-        Account, EVMError, Env, Spec,
+        Account, Address, EVMError, Env, InvalidTransaction, Spec, SpecId,
         SpecId::{CANCUN, PRAGUE, SHANGHAI},
-        TxKind, BLOCKHASH_STORAGE_ADDRESS, KECCAK_EMPTY, U256,
+        BLOCKHASH_STORAGE_ADDRESS, KECCAK_EMPTY, U256,
     },
-    Context, ContextPrecompiles,
+    Context, ContextPrecompiles, JournaledState,
 };
-use std::vec::Vec;
+use std::{boxed::Box, vec::Vec};
 
 /// Main precompile load
 #[inline]
@@ -20,6 +20,44 @@ pub fn load_precompiles<SPEC: Spec, DB: Database>() -> ContextPrecompiles<DB> {
     ContextPrecompiles::new(PrecompileSpecId::from_spec_id(SPEC::SPEC_ID))
 }
 
+/// Returns the addresses that are warm at the start of the transaction.
+///
+/// These are the precompiles, caller and callee (EIP-2929), `COINBASE` (EIP-3651) from
+/// Shanghai, the blockhash storage contract (EIP-2935) and authorities of the authorization
+/// list with a valid chain id (EIP-7702) from Prague, followed by
+/// [`CfgEnv::extra_warm_addresses`](crate::primitives::CfgEnv::extra_warm_addresses).
+pub fn warm_addresses<'a, DB: Database>(
+    spec_id: SpecId,
+    env: &'a Env,
+    precompiles: &'a ContextPrecompiles<DB>,
+) -> impl Iterator<Item = Address> + 'a {
+    let callee = env.tx().to();
+    let coinbase = spec_id
+        .is_enabled_in(SHANGHAI)
+        .then_some(env.block.coinbase);
+    let prague = spec_id.is_enabled_in(PRAGUE);
+    let blockhash_storage = prague.then_some(BLOCKHASH_STORAGE_ADDRESS);
+    let authorities = env
+        .tx()
+        .authorization_list
+        .as_ref()
+        .filter(|_| prague)
+        .into_iter()
+        .flat_map(|list| list.recovered_iter())
+        .filter(move |auth| auth.chain_id() == 0 || auth.chain_id() == env.cfg.chain_id)
+        .filter_map(|auth| auth.authority());
+
+    precompiles
+        .addresses()
+        .copied()
+        .chain(Some(env.tx().caller))
+        .chain(callee)
+        .chain(coinbase)
+        .chain(blockhash_storage)
+        .chain(authorities)
+        .chain(env.cfg.extra_warm_addresses.iter().copied())
+}
+
 /// Main load handle
 #[inline]
 pub fn load_accounts<SPEC: Spec, EXT, DB: Database>(
@@ -28,21 +66,25 @@ pub fn load_accounts<SPEC: Spec, EXT, DB: Database>(
     // set journaling state flag.
     context.evm.journaled_state.set_spec_id(SPEC::SPEC_ID);
 
-    // load coinbase
-    // EIP-3651: Warm COINBASE. Starts the `COINBASE` address warm
-    if SPEC::enabled(SHANGHAI) {
-        context.evm.inner.journaled_state.initial_account_load(
-            context.evm.inner.env.block.coinbase,
-            [],
-            &mut context.evm.inner.db,
-        )?;
-    }
+    // predeploys are installed when their accounts are loaded.
+    context
+        .evm
+        .inner
+        .journaled_state
+        .predeploys
+        .clone_from(&context.evm.inner.env.cfg.predeploys);
 
-    // Load blockhash storage address
-    // EIP-2935: Serve historical block hashes from state
-    if SPEC::enabled(PRAGUE) {
+    // load warm addresses. Precompiles are already warm and are not loaded.
+    let warm_addresses: Vec<_> = warm_addresses(
+        SPEC::SPEC_ID,
+        &context.evm.inner.env,
+        &context.evm.precompiles,
+    )
+    .filter(|address| !context.evm.precompiles.contains(address))
+    .collect();
+    for address in warm_addresses {
         context.evm.inner.journaled_state.initial_account_load(
-            BLOCKHASH_STORAGE_ADDRESS,
+            address,
             [],
             &mut context.evm.inner.db,
         )?;
@@ -50,7 +92,7 @@ pub fn load_accounts<SPEC: Spec, EXT, DB: Database>(
 
     // EIP-7702. Load bytecode to authorized accounts.
     if SPEC::enabled(PRAGUE) {
-        if let Some(authorization_list) = context.evm.inner.env.tx.authorization_list.as_ref() {
+        if let Some(authorization_list) = context.evm.inner.env.tx().authorization_list.as_ref() {
             let mut valid_auths = Vec::with_capacity(authorization_list.len());
             for authorization in authorization_list.recovered_iter() {
                 // 1. recover authority and authorized addresses.
@@ -125,7 +167,8 @@ pub fn load_accounts<SPEC: Spec, EXT, DB: Database>(
 pub fn deduct_caller_inner<SPEC: Spec>(caller_account: &mut Account, env: &Env) {
     // Subtract gas costs from the caller's account.
     // We need to saturate the gas cost to prevent underflow in case that `disable_balance_check` is enabled.
-    let mut gas_cost = U256::from(env.tx.gas_limit).saturating_mul(env.effective_gas_price());
+    let mut gas_cost = U256::from(env.tx().gas_limit)
+        .saturating_mul(env.tx().effective_gas_price(env.block.basefee));
 
     // EIP-4844
     if SPEC::enabled(CANCUN) {
@@ -137,7 +180,7 @@ pub fn deduct_caller_inner<SPEC: Spec>(caller_account: &mut Account, env: &Env)
     caller_account.info.balance = caller_account.info.balance.saturating_sub(gas_cost);
 
     // bump the nonce for calls. Nonce for CREATE will be bumped in `handle_create`.
-    if matches!(env.tx.transact_to, TxKind::Call(_)) {
+    if env.tx().is_call() {
         // Nonce is already checked
         caller_account.info.nonce = caller_account.info.nonce.saturating_add(1);
     }
@@ -146,20 +189,275 @@ pub fn deduct_caller_inner<SPEC: Spec>(caller_account: &mut Account, env: &Env)
     caller_account.mark_touch();
 }
 
+/// Deducts the gas cost from the caller balance and bumps the caller nonce for calls through
+/// the journal, unlike [`deduct_caller_inner`] the changes are reverted with the journal.
+///
+/// Note: Assume caller account is warm.
+#[inline]
+pub fn deduct_caller_journaled<SPEC: Spec>(
+    journaled_state: &mut JournaledState,
+    env: &Env,
+) -> Result<(), InvalidTransaction> {
+    let caller = env.tx().caller;
+    // Subtract gas costs from the caller's account.
+    // Maximum cost is checked for overflow in validation, saturating is only a safeguard.
+    let mut gas_cost = U256::from(env.tx().gas_limit)
+        .saturating_mul(env.tx().effective_gas_price(env.block.basefee));
+
+    // EIP-4844
+    if SPEC::enabled(CANCUN) {
+        let data_fee = env.calc_data_fee().expect("already checked");
+        gas_cost = gas_cost.saturating_add(data_fee);
+    }
+
+    // Balance is checked in validation, if `disable_balance_check` is enabled it is raised
+    // to the maximum cost there.
+    if journaled_state.balance_decr(caller, gas_cost).is_err() {
+        return Err(InvalidTransaction::LackOfFundForMaxFee {
+            fee: Box::new(gas_cost),
+            balance: Box::new(journaled_state.account(caller).info.balance),
+        });
+    }
+
+    // bump the nonce for calls. Nonce for CREATE will be bumped in `handle_create`.
+    if env.tx().is_call() {
+        // Nonce is already checked
+        journaled_state.inc_nonce(caller);
+    }
+    Ok(())
+}
+
+/// Credits the amount minted by the transaction to the caller, used by [`deduct_caller`] for
+/// [`TxEnv::mint`](crate::primitives::TxEnv::mint) and by chains with their own mint.
+///
+/// Note: Assume caller account is warm.
+#[inline]
+pub fn mint_to_caller(
+    journaled_state: &mut JournaledState,
+    caller: Address,
+    mint: u128,
+) -> Result<(), InvalidTransaction> {
+    journaled_state
+        .balance_incr(caller, U256::from(mint))
+        .map_err(|_| InvalidTransaction::OverflowPaymentInTransaction)
+}
+
 /// Deducts the caller balance to the transaction limit.
 #[inline]
 pub fn deduct_caller<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
 ) -> Result<(), EVMError<DB::Error>> {
     // load caller's account.
-    let (caller_account, _) = context
+    context
         .evm
         .inner
         .journaled_state
-        .load_account(context.evm.inner.env.tx.caller, &mut context.evm.inner.db)?;
+        .load_account(context.evm.inner.env.tx().caller, &mut context.evm.inner.db)?;
+
+    // mint to the caller before the gas is deducted.
+    if let Some(mint) = context.evm.inner.env.tx().mint {
+        mint_to_caller(
+            &mut context.evm.inner.journaled_state,
+            context.evm.inner.env.tx().caller,
+            mint,
+        )?;
+    }
 
     // deduct gas cost from caller's account.
-    deduct_caller_inner::<SPEC>(caller_account, &context.evm.inner.env);
+    deduct_caller_journaled::<SPEC>(
+        &mut context.evm.inner.journaled_state,
+        &context.evm.inner.env,
+    )?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{BenchmarkDB, CacheDB, EmptyDB},
+        primitives::{
+            address, bytes, AccessListItem, AccountInfo, Bytecode, LatestSpec, Predeploys,
+            ResultAndState, TxKind, B256,
+        },
+        test_utils::{contract_evm, CALLER},
+        Evm,
+    };
+    use core::hash::BuildHasher;
+
+    #[test]
+    fn mint_funds_call() {
+        const RECEIVER: Address = address!("2000000000000000000000000000000000000002");
+        let mut evm = Evm::builder()
+            .with_db(CacheDB::new(EmptyDB::default()))
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = Some(TxKind::Call(RECEIVER));
+                tx.value = U256::from(1_000);
+                tx.gas_limit = 21_000;
+                tx.gas_price = U256::from(1);
+                tx.mint = Some(30_000);
+            })
+            .build();
+        assert!(matches!(
+            evm.transact(),
+            Err(EVMError::Transaction(InvalidTransaction::MintNotAllowed))
+        ));
+
+        evm.cfg_mut().allow_mint = true;
+        let ResultAndState { result, state, .. } = evm.transact().unwrap();
+        assert!(result.is_success());
+        assert_eq!(
+            state[&CALLER].info.balance,
+            U256::from(30_000 - 21_000 - 1_000)
+        );
+        assert_eq!(state[&RECEIVER].info.balance, U256::from(1_000));
+    }
+
+    #[test]
+    fn deduct_caller_inner_and_journaled_agree() {
+        let mut env = Env::default();
+        env.tx_mut().caller = CALLER;
+        env.tx_mut().gas_limit = 21_000;
+        env.tx_mut().gas_price = U256::from(2);
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(100_000)));
+        let mut journaled_state = JournaledState::new(SpecId::LATEST, Default::default());
+        journaled_state.load_account(CALLER, &mut db).unwrap();
+        let mut account = journaled_state.account(CALLER).clone();
+
+        deduct_caller_inner::<LatestSpec>(&mut account, &env);
+        deduct_caller_journaled::<LatestSpec>(&mut journaled_state, &env).unwrap();
+        let journaled = journaled_state.account(CALLER);
+        assert_eq!(account.info, journaled.info);
+        assert_eq!(account.info.balance, U256::from(100_000 - 42_000));
+        assert_eq!(account.info.nonce, 1);
+        assert!(account.is_touched() && journaled.is_touched());
+    }
+
+    #[test]
+    fn predeploys_are_installed() {
+        const PREDEPLOY: Address = address!("4200000000000000000000000000000000000042");
+        // SSTORE(0, 1)
+        let code = Bytecode::new_raw(bytes!("60015f5500"));
+        let predeploys = Predeploys::from_iter([(PREDEPLOY, code)]);
+        let stored = |db: CacheDB<EmptyDB>| {
+            let mut evm = Evm::builder()
+                .with_db(db)
+                .modify_cfg_env(|cfg| cfg.predeploys = Some(predeploys.clone()))
+                .modify_tx_env(|tx| {
+                    tx.caller = CALLER;
+                    tx.transact_to = Some(TxKind::Call(PREDEPLOY));
+                    tx.gas_limit = 100_000;
+                })
+                .build();
+            let ResultAndState { result, state, .. } = evm.transact().unwrap();
+            assert!(result.is_success());
+            state[&PREDEPLOY].storage[&U256::ZERO].present_value
+        };
+
+        assert_eq!(stored(CacheDB::new(EmptyDB::default())), U256::from(1));
+
+        // SSTORE(0, 2)
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            PREDEPLOY,
+            AccountInfo::from_bytecode(Bytecode::new_raw(bytes!("60025f5500"))),
+        );
+        assert_eq!(stored(db), U256::from(2));
+    }
+
+    /// Executes an access list heavy transaction and returns the gas used and the loaded
+    /// storage keys of the accounts, collected with the hasher `S`.
+    fn access_list_execution<S: BuildHasher + Default>() -> (
+        u64,
+        std::collections::HashMap<Address, std::collections::HashSet<U256, S>, S>,
+    ) {
+        let addresses: Vec<Address> = (0..20u64)
+            .map(|i| Address::with_last_byte(2).create(i))
+            .collect();
+        let access_list = addresses
+            .iter()
+            .map(|&address| AccessListItem {
+                address,
+                storage_keys: (0..10u64).map(|key| B256::from(U256::from(key))).collect(),
+            })
+            .collect();
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new()))
+            .modify_tx_env(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.transact_to = Some(TxKind::Call(Address::ZERO));
+                tx.gas_limit = 1_000_000;
+                tx.access_list = access_list;
+            })
+            .build();
+
+        let ResultAndState { result, state, .. } = evm.transact().unwrap();
+        let loaded = state
+            .into_iter()
+            .map(|(address, account)| (address, account.storage.into_keys().collect()))
+            .collect();
+        (result.gas_used(), loaded)
+    }
+
+    /// Checks the execution of [`access_list_execution`] with the hasher `S`.
+    fn check_access_list_execution<S: BuildHasher + Default>() {
+        let (gas_used, loaded) = access_list_execution::<S>();
+        assert_eq!(gas_used, 21_000 + 20 * 2_400 + 200 * 1_900);
+
+        let expected: std::collections::HashMap<_, std::collections::HashSet<U256, S>, S> = (0
+            ..20u64)
+            .map(|i| {
+                let address = Address::with_last_byte(2).create(i);
+                (address, (0..10u64).map(U256::from).collect())
+            })
+            .chain([
+                (Address::ZERO, Default::default()),
+                (Address::with_last_byte(1), Default::default()),
+                // EIP-2935 blockhash storage is loaded from Prague.
+                (BLOCKHASH_STORAGE_ADDRESS, Default::default()),
+            ])
+            .collect();
+        assert_eq!(loaded, expected);
+    }
+
+    #[test]
+    fn access_list_execution_does_not_depend_on_hasher() {
+        // The journaled state uses the hasher selected by the `rand-hash` and `fx-hash`
+        // features, CI runs this test with each of them.
+        check_access_list_execution::<std::collections::hash_map::RandomState>();
+        check_access_list_execution::<core::hash::BuildHasherDefault<fxhash::FxHasher>>();
+    }
+
+    const BLOCK_COINBASE: Address = address!("c000000000000000000000000000000000000000");
+    const SYSTEM_CONTRACT: Address = address!("5000000000000000000000000000000000000005");
+    const COLD_ADDRESS: Address = address!("0000000000000000000000000000000000000abc");
+
+    #[rstest::rstest]
+    #[case::coinbase_cold_pre_shanghai(SpecId::LONDON, BLOCK_COINBASE, 2600)]
+    #[case::coinbase_warm_shanghai(SpecId::SHANGHAI, BLOCK_COINBASE, 100)]
+    #[case::extra_warm_address(SpecId::LONDON, SYSTEM_CONTRACT, 100)]
+    #[case::other_address_cold(SpecId::SHANGHAI, COLD_ADDRESS, 2600)]
+    fn warm_addresses_balance_gas(
+        #[case] spec: SpecId,
+        #[case] address: Address,
+        #[case] balance_gas: u64,
+    ) {
+        // BALANCE(address), POP, STOP
+        let mut code = vec![0x73];
+        code.extend_from_slice(address.as_slice());
+        code.extend_from_slice(&[0x31, 0x50, 0x00]);
+
+        let mut evm = contract_evm(code)
+            .with_spec_id(spec)
+            .modify_block_env(|block| block.coinbase = BLOCK_COINBASE)
+            .modify_cfg_env(|cfg| cfg.extra_warm_addresses = vec![SYSTEM_CONTRACT])
+            .build();
+        let gas_used = evm.transact().unwrap().result.expect_success().2;
+        // intrinsic gas, PUSH20 and POP.
+        assert_eq!(gas_used, 21_000 + 3 + balance_gas + 2);
+    }
+}
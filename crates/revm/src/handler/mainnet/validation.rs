@@ -1,7 +1,13 @@
-use revm_interpreter::gas;
+use revm_interpreter::{
+    analysis::validate_raw_eof,
+    gas::{self, InitialAndFloorGas},
+};
 
 use crate::{
-    primitives::{db::Database, EVMError, Env, InvalidTransaction, Spec},
+    handler::AccountOverride,
+    primitives::{
+        db::Database, EVMError, Env, InvalidTransaction, Spec, SpecId, MAX_INITCODE_COUNT,
+    },
     Context,
 };
 
@@ -10,6 +16,42 @@ pub fn validate_env<SPEC: Spec, DB: Database>(env: &Env) -> Result<(), EVMError<
     // Important: validate block before tx.
     env.validate_block_env::<SPEC>()?;
     env.validate_tx::<SPEC>()?;
+    validate_initcodes::<SPEC>(env)?;
+    Ok(())
+}
+
+/// Validates the initcontainers of the initcode transaction, see
+/// [`TxEnv::initcodes`](crate::primitives::TxEnv::initcodes).
+///
+/// Initcode transactions are experimental and only enabled with EOF.
+pub fn validate_initcodes<SPEC: Spec>(env: &Env) -> Result<(), InvalidTransaction> {
+    let initcodes = &env.tx().initcodes;
+    if initcodes.is_empty() {
+        return Ok(());
+    }
+    if !SPEC::enabled(SpecId::PRAGUE_EOF) {
+        return Err(InvalidTransaction::InitcodesNotSupported);
+    }
+    // Same as for blob transactions, `to` must be present.
+    if env.tx().is_create() {
+        return Err(InvalidTransaction::InitcodesCreateTransaction);
+    }
+    if initcodes.len() > MAX_INITCODE_COUNT {
+        return Err(InvalidTransaction::TooManyInitcodes {
+            max: MAX_INITCODE_COUNT,
+            have: initcodes.len(),
+        });
+    }
+    // Initcodes must be valid EOF initcontainers.
+    for initcode in initcodes {
+        if initcode.is_empty() || initcode.len() > env.cfg.max_initcode_size() {
+            return Err(InvalidTransaction::InvalidEofInitcode);
+        }
+        match validate_raw_eof(initcode.clone()) {
+            Ok(eof) if eof.body.is_data_filled => {}
+            _ => return Err(InvalidTransaction::InvalidEofInitcode),
+        }
+    }
     Ok(())
 }
 
@@ -18,7 +60,7 @@ pub fn validate_tx_against_state<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
 ) -> Result<(), EVMError<DB::Error>> {
     // load acc
-    let tx_caller = context.evm.env.tx.caller;
+    let tx_caller = context.evm.env.tx().caller;
     let (caller_account, _) = context
         .evm
         .inner
@@ -35,31 +77,285 @@ pub fn validate_tx_against_state<SPEC: Spec, EXT, DB: Database>(
     Ok(())
 }
 
-/// Validate initial transaction gas.
+/// Validates transaction against the state with `validate`, with nonce and balance of the
+/// caller taken from the `account_override`.
+///
+/// Overrides are applied to the caller account only while `validate` runs, state is restored
+/// afterwards.
+pub fn validate_tx_against_state_with_override<EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+    account_override: AccountOverride,
+    validate: impl FnOnce(&mut Context<EXT, DB>) -> Result<(), EVMError<DB::Error>>,
+) -> Result<(), EVMError<DB::Error>> {
+    let tx_caller = context.evm.env.tx().caller;
+    let (caller_account, _) = context
+        .evm
+        .inner
+        .journaled_state
+        .load_account(tx_caller, &mut context.evm.inner.db)?;
+    let (nonce, balance) = (caller_account.info.nonce, caller_account.info.balance);
+    caller_account.info.nonce = account_override.nonce.unwrap_or(nonce);
+    caller_account.info.balance = account_override.balance.unwrap_or(balance);
+
+    let output = validate(context);
+
+    let caller_account = context
+        .evm
+        .inner
+        .journaled_state
+        .state
+        .get_mut(&tx_caller)
+        .expect("caller is loaded");
+    caller_account.info.nonce = nonce;
+    caller_account.info.balance = balance;
+    output
+}
+
+/// Validate initial transaction gas and calculate the gas floor.
 pub fn validate_initial_tx_gas<SPEC: Spec, DB: Database>(
     env: &Env,
-) -> Result<u64, EVMError<DB::Error>> {
-    let input = &env.tx.data;
-    let is_create = env.tx.transact_to.is_create();
-    let access_list = &env.tx.access_list;
+) -> Result<InitialAndFloorGas, EVMError<DB::Error>> {
+    let input = &env.tx().data;
+    let is_create = env.tx().is_create();
+    let access_list = &env.tx().access_list;
     let authorization_list_num = env
-        .tx
+        .tx()
         .authorization_list
         .as_ref()
         .map(|l| l.len() as u64)
         .unwrap_or_default();
 
+    let overrides = env.cfg.gas_schedule_overrides();
     let initial_gas_spend = gas::validate_initial_tx_gas(
+        overrides,
         SPEC::SPEC_ID,
         input,
         is_create,
         access_list,
         authorization_list_num,
     );
+    // Initcontainers of the initcode transaction are charged as calldata.
+    let initial_gas_spend = env
+        .tx()
+        .initcodes
+        .iter()
+        .fold(initial_gas_spend, |gas, initcode| {
+            gas.saturating_add(gas::calldata_cost(overrides, SPEC::SPEC_ID, initcode))
+        });
 
     // Additional check to see if limit is big enough to cover initial gas.
-    if initial_gas_spend > env.tx.gas_limit {
+    if initial_gas_spend > env.tx().gas_limit {
         return Err(InvalidTransaction::CallGasCostMoreThanGasLimit.into());
     }
-    Ok(initial_gas_spend)
+
+    // EIP-7623: Increase calldata cost
+    // Limit must cover the gas floor as well.
+    let floor_gas = if SPEC::enabled(SpecId::PRAGUE) {
+        gas::calc_tx_floor_cost(input)
+    } else {
+        0
+    };
+    if floor_gas > env.tx().gas_limit {
+        return Err(InvalidTransaction::GasFloorMoreThanGasLimit.into());
+    }
+    Ok(InitialAndFloorGas {
+        initial_gas: initial_gas_spend,
+        floor_gas,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::EmptyDB,
+        primitives::{
+            eof::{EofBody, TypesSection},
+            Bytes, CancunSpec, Eof, GasScheduleOverrides, PragueEofSpec, PragueSpec, TxKind,
+            EOF_MAGIC_BYTES, MAX_INITCODE_SIZE,
+        },
+    };
+
+    /// Creates env with EOF create transaction with initdata of `len` bytes.
+    fn eof_create_env(len: usize) -> Env {
+        let mut initdata = vec![0x01; len];
+        initdata[..2].copy_from_slice(&EOF_MAGIC_BYTES);
+        let mut env = Env::default();
+        env.tx_mut().transact_to = Some(TxKind::Create);
+        env.tx_mut().gas_limit = 10_000_000;
+        env.tx_mut().data = Bytes::from(initdata);
+        env
+    }
+
+    #[test]
+    fn eof_create_tx_initcode_size_limit() {
+        let env = eof_create_env(MAX_INITCODE_SIZE);
+        assert!(validate_env::<PragueEofSpec, EmptyDB>(&env).is_ok());
+
+        let env = eof_create_env(MAX_INITCODE_SIZE + 1);
+        assert_eq!(
+            validate_env::<PragueEofSpec, EmptyDB>(&env),
+            Err(EVMError::Transaction(
+                InvalidTransaction::CreateInitCodeSizeLimit
+            ))
+        );
+    }
+
+    #[test]
+    fn create_tx_initcode_size_limit() {
+        let mut env = Env::default();
+        env.tx_mut().transact_to = Some(TxKind::Create);
+        env.tx_mut().gas_limit = 30_000_000;
+        env.tx_mut().data = vec![0; MAX_INITCODE_SIZE + 1].into();
+        assert_eq!(
+            validate_env::<CancunSpec, EmptyDB>(&env),
+            Err(EVMError::Transaction(
+                InvalidTransaction::CreateInitCodeSizeLimit
+            ))
+        );
+    }
+
+    #[test]
+    fn eof_create_tx_initial_gas() {
+        // EOF magic followed by 64 non zero bytes.
+        let env = eof_create_env(66);
+        // 0x00 byte of the magic is the only zero byte.
+        let data_cost = 65 * 16 + 4;
+        // 53000 for create and 2 gas per word of initcode.
+        let expected = 53_000 + data_cost + 3 * 2;
+        assert_eq!(
+            validate_initial_tx_gas::<PragueEofSpec, EmptyDB>(&env).map(|gas| gas.initial_gas),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn calldata_gas_floor_over_gas_limit() {
+        let mut env = Env::default();
+        env.tx_mut().data = vec![0x01; 1000].into();
+        // Covers the intrinsic gas of 37_000 but not the floor of 61_000.
+        env.tx_mut().gas_limit = 60_000;
+        assert_eq!(
+            validate_initial_tx_gas::<PragueSpec, EmptyDB>(&env),
+            Err(EVMError::Transaction(
+                InvalidTransaction::GasFloorMoreThanGasLimit
+            ))
+        );
+
+        env.tx_mut().gas_limit = 61_000;
+        assert_eq!(
+            validate_initial_tx_gas::<PragueSpec, EmptyDB>(&env),
+            Ok(InitialAndFloorGas {
+                initial_gas: 37_000,
+                floor_gas: 61_000,
+            })
+        );
+    }
+
+    #[test]
+    fn gas_overrides_reprice_calldata() {
+        let mut env = Env::default();
+        // two zero and three non zero bytes.
+        env.tx_mut().data = Bytes::from_static(&[0x00, 0x00, 0x01, 0x02, 0x03]);
+        env.tx_mut().gas_limit = 100_000;
+        let mut initial_gas = |gas_overrides| {
+            env.cfg.gas_overrides = gas_overrides;
+            validate_initial_tx_gas::<CancunSpec, EmptyDB>(&env).map(|gas| gas.initial_gas)
+        };
+
+        assert_eq!(initial_gas(None), Ok(21_000 + 2 * 4 + 3 * 16));
+        assert_eq!(
+            initial_gas(Some(GasScheduleOverrides::NONE)),
+            Ok(21_000 + 2 * 4 + 3 * 16)
+        );
+        // two zero bytes at 1 gas and three non-zero bytes at 40 gas.
+        assert_eq!(
+            initial_gas(Some(GasScheduleOverrides {
+                tx_data_zero: Some(1),
+                tx_data_non_zero: Some(40),
+                ..Default::default()
+            })),
+            Ok(21_000 + 2 + 3 * 40)
+        );
+    }
+
+    /// `RETURNCONTRACT` of a STOP container.
+    fn initcode() -> Bytes {
+        let container = EofBody {
+            types_section: vec![TypesSection::new(0, 0x80, 0)],
+            code_section: vec![Bytes::from_static(&[0x00])],
+            container_section: vec![],
+            data_section: Bytes::new(),
+            is_data_filled: true,
+        }
+        .into_eof()
+        .raw;
+        EofBody {
+            types_section: vec![TypesSection::new(0, 0x80, 2)],
+            code_section: vec![Bytes::from_static(&[0x5f, 0x5f, 0xee, 0x00])],
+            container_section: vec![container],
+            data_section: Bytes::new(),
+            is_data_filled: true,
+        }
+        .into_eof()
+        .raw
+    }
+
+    #[test]
+    fn initcodes() {
+        let initcode = initcode();
+        let mut env = Env::default();
+        env.tx_mut().initcodes = vec![initcode.clone()];
+        assert_eq!(validate_initcodes::<PragueEofSpec>(&env), Ok(()));
+        assert_eq!(
+            validate_initcodes::<CancunSpec>(&env),
+            Err(InvalidTransaction::InitcodesNotSupported)
+        );
+
+        env.tx_mut().initcodes.push(Bytes::new());
+        assert_eq!(
+            validate_initcodes::<PragueEofSpec>(&env),
+            Err(InvalidTransaction::InvalidEofInitcode)
+        );
+
+        // not an initcontainer.
+        env.tx_mut().initcodes = vec![Eof::default().raw];
+        assert_eq!(
+            validate_initcodes::<PragueEofSpec>(&env),
+            Err(InvalidTransaction::InvalidEofInitcode)
+        );
+
+        env.tx_mut().initcodes = vec![initcode; MAX_INITCODE_COUNT + 1];
+        assert_eq!(
+            validate_initcodes::<PragueEofSpec>(&env),
+            Err(InvalidTransaction::TooManyInitcodes {
+                max: MAX_INITCODE_COUNT,
+                have: MAX_INITCODE_COUNT + 1,
+            })
+        );
+
+        env.tx_mut().transact_to = Some(TxKind::Create);
+        assert_eq!(
+            validate_initcodes::<PragueEofSpec>(&env),
+            Err(InvalidTransaction::InitcodesCreateTransaction)
+        );
+    }
+
+    #[test]
+    fn initcodes_are_charged_as_calldata() {
+        let initcode = initcode();
+        let mut env = Env::default();
+        env.tx_mut().gas_limit = 1_000_000;
+        let base = validate_initial_tx_gas::<PragueEofSpec, EmptyDB>(&env)
+            .unwrap()
+            .initial_gas;
+
+        env.tx_mut().initcodes = vec![initcode.clone(), initcode.clone()];
+        let zero_bytes = initcode.iter().filter(|byte| **byte == 0).count() as u64;
+        let calldata_cost = zero_bytes * 4 + (initcode.len() as u64 - zero_bytes) * 16;
+        assert_eq!(
+            validate_initial_tx_gas::<PragueEofSpec, EmptyDB>(&env).map(|gas| gas.initial_gas),
+            Ok(base + 2 * calldata_cost)
+        );
+    }
 }
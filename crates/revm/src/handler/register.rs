@@ -1,5 +1,11 @@
-use crate::{db::Database, handler::Handler, Context};
-use std::boxed::Box;
+use crate::{
+    db::Database,
+    handler::{DeductCallerHandle, Handler, RewardBeneficiaryHandle, ValidateEnvHandle},
+    interpreter::Gas,
+    primitives::{EVMError, Env},
+    Context,
+};
+use std::{boxed::Box, sync::Arc};
 
 /// EVM Handler
 pub type EvmHandler<'a, EXT, DB> = Handler<'a, Context<EXT, DB>, EXT, DB>;
@@ -10,11 +16,16 @@ pub type HandleRegister<EXT, DB> = for<'a> fn(&mut EvmHandler<'a, EXT, DB>);
 // Boxed handle register
 pub type HandleRegisterBox<EXT, DB> = Box<dyn for<'a> Fn(&mut EvmHandler<'a, EXT, DB>)>;
 
+// Shared handle register, unlike the boxed one it can be reapplied by `Handler::rebuild`.
+pub type HandleRegisterArc<EXT, DB> = Arc<dyn for<'a> Fn(&mut EvmHandler<'a, EXT, DB>)>;
+
 pub enum HandleRegisters<EXT, DB: Database> {
     /// Plain function register
     Plain(HandleRegister<EXT, DB>),
     /// Boxed function register.
     Box(HandleRegisterBox<EXT, DB>),
+    /// Shared function register.
+    Arc(HandleRegisterArc<EXT, DB>),
 }
 
 impl<EXT, DB: Database> HandleRegisters<EXT, DB> {
@@ -23,6 +34,163 @@ impl<EXT, DB: Database> HandleRegisters<EXT, DB> {
         match self {
             HandleRegisters::Plain(f) => f(handler),
             HandleRegisters::Box(f) => f(handler),
+            HandleRegisters::Arc(f) => f(handler),
+        }
+    }
+
+    /// Clones the register, returns `None` for boxed registers as they can't be cloned.
+    pub fn try_clone(&self) -> Option<Self> {
+        match self {
+            HandleRegisters::Plain(f) => Some(HandleRegisters::Plain(*f)),
+            HandleRegisters::Box(_) => None,
+            HandleRegisters::Arc(f) => Some(HandleRegisters::Arc(f.clone())),
+        }
+    }
+}
+
+/// Sets the validate env, deduct caller and reward beneficiary handles of the transactions
+/// that match `is_custom`.
+///
+/// Previously set handles are called for all other transactions, and for the matching ones
+/// if the handle is `None`.
+pub(crate) fn register_custom_tx_handles<'a, EXT: 'a, DB: Database + 'a>(
+    handler: &mut EvmHandler<'a, EXT, DB>,
+    is_custom: impl Fn(&Env) -> bool + Clone + 'a,
+    validate_env: Option<ValidateEnvHandle<'a, DB>>,
+    deduct_caller: Option<DeductCallerHandle<'a, EXT, DB>>,
+    reward_beneficiary: Option<RewardBeneficiaryHandle<'a, EXT, DB>>,
+) {
+    if let Some(hook) = validate_env {
+        let is_custom = is_custom.clone();
+        let fallback = handler.validation.env.clone();
+        handler.validation.env = Arc::new(move |env| {
+            if is_custom(env) {
+                hook(env)
+            } else {
+                fallback(env)
+            }
+        });
+    }
+
+    if let Some(hook) = deduct_caller {
+        let is_custom = is_custom.clone();
+        let fallback = handler.pre_execution.deduct_caller.clone();
+        handler.pre_execution.deduct_caller = Arc::new(move |context| {
+            if is_custom(&context.evm.env) {
+                hook(context)
+            } else {
+                fallback(context)
+            }
+        });
+    }
+
+    if let Some(hook) = reward_beneficiary {
+        let fallback = handler.post_execution.reward_beneficiary.clone();
+        handler.post_execution.reward_beneficiary = Arc::new(move |context, gas| {
+            if is_custom(&context.evm.env) {
+                hook(context, gas)
+            } else {
+                fallback(context, gas)
+            }
+        });
+    }
+}
+
+/// Handle register for chains without gas fees.
+///
+/// Gas is still metered and limits are enforced, but refunds are zeroed, the caller is not
+/// reimbursed and the beneficiary is not rewarded. It is meant for chains with zero gas price,
+/// with non zero gas price the fee for the whole gas limit is deducted from the caller and burned.
+pub fn gasless<EXT, DB: Database>(handler: &mut EvmHandler<'_, EXT, DB>) {
+    handler.post_execution.refund = Arc::new(gasless_refund::<EXT, DB>);
+    handler.post_execution.reimburse_caller = Arc::new(gasless_skip::<EXT, DB>);
+    handler.post_execution.reward_beneficiary = Arc::new(gasless_skip::<EXT, DB>);
+}
+
+/// Zeroes the refund of the transaction.
+fn gasless_refund<EXT, DB: Database>(
+    _context: &mut Context<EXT, DB>,
+    gas: &mut Gas,
+) -> Result<(), EVMError<DB::Error>> {
+    gas.set_refund(0);
+    Ok(())
+}
+
+/// Skips reimbursement of the caller and reward of the beneficiary.
+fn gasless_skip<EXT, DB: Database>(
+    _context: &mut Context<EXT, DB>,
+    _gas: &Gas,
+) -> Result<(), EVMError<DB::Error>> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{DatabaseRef, InMemoryDB},
+        primitives::{address, AccountInfo, Address, Bytes, ExecutionResult, U256},
+        test_utils::{contract_evm, CALLER, CONTRACT},
+    };
+
+    const COINBASE: Address = address!("3000000000000000000000000000000000000003");
+
+    /// Clears the storage slot to get a refund and returns the result and balances of the
+    /// caller and coinbase.
+    fn run(register: Option<HandleRegister<(), InMemoryDB>>) -> (ExecutionResult, U256, U256) {
+        // PUSH1 0x00, PUSH1 0x00, SSTORE, STOP
+        let code = Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0x55, 0x00]);
+        let mut evm = contract_evm(code)
+            .modify_db(|db| {
+                db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1_000)));
+                db.insert_account_storage(CONTRACT, U256::ZERO, U256::from(1))
+                    .unwrap();
+            })
+            .modify_block_env(|block| {
+                block.coinbase = COINBASE;
+                block.basefee = U256::ZERO;
+            })
+            .modify_tx_env(|tx| tx.gas_price = U256::ZERO)
+            .build();
+        if let Some(register) = register {
+            evm.handler.append_handler_register_plain(register);
         }
+        let result = evm.transact_commit().unwrap();
+        let balance = |address| {
+            evm.db()
+                .basic_ref(address)
+                .unwrap()
+                .map(|info| info.balance)
+                .unwrap_or_default()
+        };
+        (result, balance(CALLER), balance(COINBASE))
+    }
+
+    #[test]
+    fn gasless_has_no_refund() {
+        let (mainnet, mainnet_caller, mainnet_coinbase) = run(None);
+        let (gasless, gasless_caller, gasless_coinbase) = run(Some(gasless));
+        assert!(mainnet.is_success() && gasless.is_success());
+
+        // same balances as nothing is paid for the gas.
+        assert_eq!(mainnet_caller, U256::from(1_000));
+        assert_eq!(gasless_caller, mainnet_caller);
+        assert_eq!(gasless_coinbase, U256::ZERO);
+        assert_eq!(gasless_coinbase, mainnet_coinbase);
+
+        // storage clear refund is not applied.
+        let refund = match mainnet {
+            ExecutionResult::Success { gas_refunded, .. } => gas_refunded,
+            _ => unreachable!(),
+        };
+        assert_eq!(refund, 4_800);
+        assert_eq!(gasless.gas_used(), mainnet.gas_used() + refund);
+        assert!(matches!(
+            gasless,
+            ExecutionResult::Success {
+                gas_refunded: 0,
+                ..
+            }
+        ));
     }
 }
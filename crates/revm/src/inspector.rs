@@ -1,8 +1,11 @@
+mod coverage;
 #[cfg(feature = "std")]
 mod customprinter;
 #[cfg(all(feature = "std", feature = "serde-json"))]
 mod eip3155;
 mod gas;
+mod gas_grief;
+mod gas_tree;
 mod handler_register;
 mod noop;
 
@@ -19,15 +22,44 @@ use auto_impl::auto_impl;
 
 /// [Inspector] implementations.
 pub mod inspectors {
+    pub use super::coverage::{CodeCoverage, CoverageInspector, CoverageMode};
     #[cfg(feature = "std")]
     pub use super::customprinter::CustomPrintTracer;
     #[cfg(all(feature = "std", feature = "serde-json"))]
     pub use super::eip3155::TracerEip3155;
     pub use super::gas::GasInspector;
+    pub use super::gas_grief::{GasGriefFinding, GasGriefInspector};
+    pub use super::gas_tree::{GasFrame, GasFrameKind, GasTreeInspector};
     pub use super::noop::NoOpInspector;
 }
 
 /// EVM [Interpreter] callbacks.
+///
+/// # Hook ordering
+///
+/// The hooks of a transaction are called in the following order:
+///
+/// * Every frame starts with [`call`](Inspector::call), [`create`](Inspector::create) or
+///   [`eofcreate`](Inspector::eofcreate) and ends with the matching `*_end` hook, with the same
+///   inputs. The end hook is called also when the begin hook returned an outcome and when the
+///   frame failed before its bytecode was interpreted (precompiles, call depth, insufficient
+///   balance, ...). Frames are properly nested.
+/// * [`initialize_interp`](Inspector::initialize_interp) is called once for every frame whose
+///   bytecode is interpreted, after the begin hook and before the first `step`.
+/// * [`step`](Inspector::step) and [`step_end`](Inspector::step_end) enclose every instruction
+///   of the innermost frame. `step_end` is not called if `step` stopped the execution.
+/// * [`log`](Inspector::log), [`selfdestruct`](Inspector::selfdestruct) and
+///   [`jump`](Inspector::jump) are called between `step` and `step_end` of the instruction that
+///   emitted them.
+/// * Instructions that start a frame (`CALL`, `CREATE`, ...) get their `step_end` before the
+///   begin hook of the new frame. The end hook of the new frame is called before the `step` of
+///   the next instruction of the parent.
+/// * In the begin and end hooks the journal depth is the number of enclosing frames, in all
+///   other hooks it is the depth of the innermost frame.
+///
+/// If the transaction fails with an error, end hooks of the open frames are not called.
+///
+/// The `AssertingInspector` from the `test_utils` module checks this order.
 #[auto_impl(&mut, Box)]
 pub trait Inspector<DB: Database> {
     /// Called before the interpreter is initialized.
@@ -136,7 +168,14 @@ pub trait Inspector<DB: Database> {
 
     /// Called when EOF creating is called.
     ///
-    /// This can happen from create TX or from EOFCREATE opcode.
+    /// This can happen from create TX or from EOFCREATE opcode. Address of the created
+    /// contract can be obtained with
+    /// [`InnerEvmContext::preview_eofcreate_address`](crate::InnerEvmContext::preview_eofcreate_address).
+    ///
+    /// If this returns `Some` then the [CreateOutcome] is used to override the result of the creation.
+    ///
+    /// If this returns `None` then the creation proceeds as normal.
+    #[inline]
     fn eofcreate(
         &mut self,
         context: &mut EvmContext<DB>,
@@ -148,6 +187,11 @@ pub trait Inspector<DB: Database> {
     }
 
     /// Called when eof creating has ended.
+    ///
+    /// It is called for every [`Inspector::eofcreate`], including creations that failed before
+    /// execution. Outcome contains the created address, returning a different outcome
+    /// will alter the result of the create.
+    #[inline]
     fn eofcreate_end(
         &mut self,
         context: &mut EvmContext<DB>,
@@ -166,4 +210,27 @@ pub trait Inspector<DB: Database> {
         let _ = target;
         let _ = value;
     }
+
+    /// Returns `true` if [`jump`](Inspector::jump) should be called.
+    ///
+    /// Jumps are not reported by default, so inspectors that do not need them do not pay for
+    /// decoding the jump operands.
+    #[inline]
+    fn inspect_jumps(&self) -> bool {
+        false
+    }
+
+    /// Called after `JUMP`, `JUMPI`, `RJUMP`, `RJUMPI` or `RJUMPV` is executed, if
+    /// [`inspect_jumps`](Inspector::inspect_jumps) returns `true`.
+    ///
+    /// `to_pc` is the next program counter, which is the following instruction if the condition
+    /// was not taken. A jump to an invalid destination halts the execution and is reported with
+    /// `is_valid` set to `false` and the attempted destination as `to_pc`.
+    #[inline]
+    fn jump(&mut self, from_pc: usize, to_pc: usize, condition_taken: bool, is_valid: bool) {
+        let _ = from_pc;
+        let _ = to_pc;
+        let _ = condition_taken;
+        let _ = is_valid;
+    }
 }
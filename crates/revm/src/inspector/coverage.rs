@@ -0,0 +1,272 @@
+//! CoverageInspector. Records instruction and branch coverage of the execution.
+
+use crate::{
+    interpreter::{opcode, Interpreter},
+    primitives::{bitvec::vec::BitVec, db::Database, HashMap, B256},
+    EvmContext, Inspector,
+};
+use std::{collections::BTreeSet, vec::Vec};
+
+/// What is recorded by the [CoverageInspector].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CoverageMode {
+    /// Record every executed program counter and every `JUMPI` edge.
+    #[default]
+    Full,
+    /// Record only `JUMPI` edges, other instructions are skipped.
+    EdgesOnly,
+}
+
+/// Coverage of a single bytecode.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CodeCoverage {
+    /// Bitset of executed program counters.
+    pub pcs: BitVec<u8>,
+    /// Executed `JUMPI` edges as `(pc, taken)` pairs.
+    pub edges: BTreeSet<(usize, bool)>,
+}
+
+impl CodeCoverage {
+    /// Returns `true` if the instruction at `pc` was executed.
+    #[inline]
+    pub fn is_visited(&self, pc: usize) -> bool {
+        self.pcs.get(pc).map(|bit| *bit).unwrap_or(false)
+    }
+
+    /// Marks the instruction at `pc` as executed.
+    #[inline]
+    pub fn visit(&mut self, pc: usize) {
+        if pc >= self.pcs.len() {
+            self.pcs.resize(pc + 1, false);
+        }
+        self.pcs.set(pc, true);
+    }
+
+    /// Adds the coverage of `other` to this one.
+    pub fn merge(&mut self, other: &CodeCoverage) {
+        if other.pcs.len() > self.pcs.len() {
+            self.pcs.resize(other.pcs.len(), false);
+        }
+        for pc in other.pcs.iter_ones() {
+            self.pcs.set(pc, true);
+        }
+        self.edges.extend(other.edges.iter().copied());
+    }
+}
+
+/// [Inspector] that records coverage of the executed bytecode, for coverage guided fuzzing.
+///
+/// Coverage is grouped by the hash of the executed bytecode, see
+/// [`FrameInfo::code_hash`](crate::interpreter::FrameInfo::code_hash). For EOF bytecode
+/// program counters are relative to the executed code section.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CoverageInspector {
+    mode: CoverageMode,
+    coverage: HashMap<B256, CodeCoverage>,
+}
+
+impl CoverageInspector {
+    /// Creates a new inspector that records all executed instructions and `JUMPI` edges.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new inspector that records only `JUMPI` edges.
+    pub fn edges_only() -> Self {
+        Self {
+            mode: CoverageMode::EdgesOnly,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the coverage mode.
+    #[inline]
+    pub fn mode(&self) -> CoverageMode {
+        self.mode
+    }
+
+    /// Returns the coverage of all executed bytecodes.
+    #[inline]
+    pub fn coverage(&self) -> &HashMap<B256, CodeCoverage> {
+        &self.coverage
+    }
+
+    /// Returns the coverage of the bytecode with the given hash.
+    #[inline]
+    pub fn code_coverage(&self, code_hash: &B256) -> Option<&CodeCoverage> {
+        self.coverage.get(code_hash)
+    }
+
+    /// Adds the coverage of `other` to this one.
+    pub fn merge(&mut self, other: &CoverageInspector) {
+        for (code_hash, coverage) in &other.coverage {
+            self.coverage.entry(*code_hash).or_default().merge(coverage);
+        }
+    }
+
+    /// Clears the recorded coverage.
+    pub fn clear(&mut self) {
+        self.coverage.clear();
+    }
+
+    /// Encodes the coverage in a compact binary format.
+    ///
+    /// Bytecodes are sorted by hash, each one is encoded as:
+    /// `code_hash (32 bytes) | bitset length in bytes (u32) | bitset | edge count (u32) | edges`,
+    /// where every edge is `pc (u32) | taken (u8)`. Integers are big-endian.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut code_hashes: Vec<_> = self.coverage.keys().collect();
+        code_hashes.sort();
+
+        let mut out = Vec::new();
+        for code_hash in code_hashes {
+            let coverage = &self.coverage[code_hash];
+            let bitset = coverage.pcs.as_raw_slice();
+            out.extend_from_slice(code_hash.as_slice());
+            out.extend_from_slice(&(bitset.len() as u32).to_be_bytes());
+            out.extend_from_slice(bitset);
+            out.extend_from_slice(&(coverage.edges.len() as u32).to_be_bytes());
+            for (pc, taken) in &coverage.edges {
+                out.extend_from_slice(&(*pc as u32).to_be_bytes());
+                out.push(*taken as u8);
+            }
+        }
+        out
+    }
+
+    /// Decodes the coverage encoded with [`CoverageInspector::encode`].
+    ///
+    /// Returns `None` if the input is malformed.
+    pub fn decode(mode: CoverageMode, mut input: &[u8]) -> Option<Self> {
+        fn take<'a>(input: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+            if input.len() < len {
+                return None;
+            }
+            let (head, tail) = input.split_at(len);
+            *input = tail;
+            Some(head)
+        }
+        fn take_u32(input: &mut &[u8]) -> Option<usize> {
+            let bytes = take(input, 4)?;
+            Some(u32::from_be_bytes(bytes.try_into().ok()?) as usize)
+        }
+
+        let mut coverage = HashMap::default();
+        while !input.is_empty() {
+            let code_hash = B256::from_slice(take(&mut input, 32)?);
+            let bitset_len = take_u32(&mut input)?;
+            let pcs = BitVec::from_slice(take(&mut input, bitset_len)?);
+            let edge_count = take_u32(&mut input)?;
+            let mut edges = BTreeSet::new();
+            for _ in 0..edge_count {
+                let pc = take_u32(&mut input)?;
+                let taken = match take(&mut input, 1)?[0] {
+                    0 => false,
+                    1 => true,
+                    _ => return None,
+                };
+                edges.insert((pc, taken));
+            }
+            coverage.insert(code_hash, CodeCoverage { pcs, edges });
+        }
+        Some(Self { mode, coverage })
+    }
+}
+
+impl<DB: Database> Inspector<DB> for CoverageInspector {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        let is_jumpi = interp.current_opcode() == opcode::JUMPI;
+        if self.mode == CoverageMode::EdgesOnly && !is_jumpi {
+            return;
+        }
+
+        let pc = interp.program_counter();
+        let coverage = self.coverage.entry(interp.frame_code_hash()).or_default();
+        if self.mode == CoverageMode::Full {
+            coverage.visit(pc);
+        }
+        if is_jumpi {
+            // JUMPI pops the destination first and the condition second.
+            if let Ok(condition) = interp.stack.peek(1) {
+                coverage.edges.insert((pc, !condition.is_zero()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        inspector_handle_register,
+        primitives::{address, Address, Bytecode, Bytes, TxKind, U256},
+        test_utils::AssertingInspector,
+        Evm,
+    };
+
+    // PUSH1 0x00, CALLDATALOAD, PUSH1 0x09, JUMPI, PUSH1 0x01, STOP, JUMPDEST, PUSH1 0x02, STOP
+    const CODE: &[u8] = &[
+        0x60, 0x00, 0x35, 0x60, 0x09, 0x57, 0x60, 0x01, 0x00, 0x5b, 0x60, 0x02, 0x00,
+    ];
+
+    fn run(inspector: CoverageInspector, condition: u64) -> CoverageInspector {
+        let bytecode = Bytecode::new_raw(Bytes::from_static(CODE));
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(bytecode))
+            .with_external_context(AssertingInspector::new(inspector))
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to = Some(TxKind::Call(Address::ZERO));
+                tx.gas_limit = 100_000;
+                tx.data = U256::from(condition).to_be_bytes_vec().into();
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        evm.transact().unwrap().result.expect_success();
+        let inspector = evm.into_context().external;
+        inspector.assert_finished();
+        inspector.into_inner()
+    }
+
+    fn code_hash() -> B256 {
+        Bytecode::new_raw(Bytes::from_static(CODE)).hash_slow()
+    }
+
+    #[test]
+    fn branch_coverage_union() {
+        let not_taken = run(CoverageInspector::new(), 0);
+        let taken = run(CoverageInspector::new(), 1);
+
+        let coverage = not_taken.code_coverage(&code_hash()).unwrap();
+        assert!(coverage.is_visited(6) && !coverage.is_visited(9));
+        assert_eq!(coverage.edges, BTreeSet::from([(5, false)]));
+
+        let coverage = taken.code_coverage(&code_hash()).unwrap();
+        assert!(!coverage.is_visited(6) && coverage.is_visited(9));
+        assert_eq!(coverage.edges, BTreeSet::from([(5, true)]));
+
+        let mut union = not_taken.clone();
+        union.merge(&taken);
+        assert_ne!(union, not_taken);
+        assert_ne!(union, taken);
+        let coverage = union.code_coverage(&code_hash()).unwrap();
+        assert!(coverage.is_visited(6) && coverage.is_visited(9));
+        assert_eq!(coverage.edges, BTreeSet::from([(5, false), (5, true)]));
+
+        let encoded = union.encode();
+        let decoded = CoverageInspector::decode(CoverageMode::Full, &encoded).unwrap();
+        assert_eq!(decoded.encode(), encoded);
+        let coverage = decoded.code_coverage(&code_hash()).unwrap();
+        assert!(coverage.is_visited(6) && coverage.is_visited(9) && !coverage.is_visited(7));
+        assert_eq!(coverage.edges.len(), 2);
+    }
+
+    #[test]
+    fn edges_only() {
+        let inspector = run(CoverageInspector::edges_only(), 1);
+        let coverage = inspector.code_coverage(&code_hash()).unwrap();
+        assert_eq!(coverage.pcs.count_ones(), 0);
+        assert_eq!(coverage.edges, BTreeSet::from([(5, true)]));
+    }
+}
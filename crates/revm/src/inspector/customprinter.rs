@@ -117,6 +117,7 @@ mod test {
         inspector_handle_register,
         inspectors::CustomPrintTracer,
         primitives::{address, bytes, SpecId},
+        test_utils::AssertingInspector,
         Evm, InMemoryDB,
     };
 
@@ -140,11 +141,11 @@ mod test {
             })
             .modify_tx_env(|tx| {
                 tx.caller = address!("5fdcca53617f4d2b9134b29090c87d01058e27e0");
-                tx.transact_to = crate::primitives::TxKind::Call(callee);
+                tx.transact_to = Some(crate::primitives::TxKind::Call(callee));
                 tx.data = crate::primitives::Bytes::new();
                 tx.value = crate::primitives::U256::ZERO;
             })
-            .with_external_context(CustomPrintTracer::default())
+            .with_external_context(AssertingInspector::new(CustomPrintTracer::default()))
             .with_spec_id(SpecId::BERLIN)
             .append_handler_register(inspector_handle_register)
             .build();
@@ -1,7 +1,8 @@
 use crate::{
     inspectors::GasInspector,
     interpreter::{
-        CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter, InterpreterResult,
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, FrameInfo, Interpreter,
+        InterpreterResult,
     },
     primitives::{db::Database, hex, HashMap, B256, U256},
     EvmContext, Inspector,
@@ -27,6 +28,10 @@ pub struct TracerEip3155 {
     skip: bool,
     include_memory: bool,
     memory: Option<String>,
+    include_frame_info: bool,
+    frame_info: Option<FrameInfo>,
+    section: Option<usize>,
+    function_depth: Option<usize>,
 }
 
 // # Output
@@ -47,6 +52,12 @@ struct Output {
     stack: Vec<String>,
     /// Depth of the call stack
     depth: u64,
+    /// Index of the executed EOF code section, `pc` is relative to it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    section: Option<usize>,
+    /// Depth of the EOF function stack, one for the first code section
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    function_depth: Option<usize>,
     /// Data returned by the function call
     return_data: String,
     /// Amount of **global** gas refunded
@@ -70,6 +81,21 @@ struct Output {
     /// Array of values, Stack of the called function
     #[serde(default, skip_serializing_if = "Option::is_none")]
     return_stack: Option<Vec<String>>,
+    /// Hash of the executed bytecode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    code_hash: Option<String>,
+    /// Address of the executed frame
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    /// Whether the frame executes initcode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    is_create: Option<bool>,
+    /// Whether the executed bytecode is EOF
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    is_eof: Option<bool>,
+    /// Index of the executed EOF code section
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    code_section: Option<usize>,
 }
 
 // # Summary and error handling
@@ -133,8 +159,12 @@ impl TracerEip3155 {
             gas_inspector: GasInspector::default(),
             print_summary: true,
             include_memory: false,
+            include_frame_info: false,
             stack: Default::default(),
             memory: Default::default(),
+            frame_info: None,
+            section: None,
+            function_depth: None,
             pc: 0,
             opcode: 0,
             gas: 0,
@@ -156,6 +186,13 @@ impl TracerEip3155 {
         self
     }
 
+    /// Include information about the executed frame (code hash, address, create and EOF flags)
+    /// for each step. These fields are not part of EIP-3155.
+    pub fn with_frame_info(mut self) -> Self {
+        self.include_frame_info = true;
+        self
+    }
+
     fn write_value(&mut self, value: &impl serde::Serialize) -> std::io::Result<()> {
         serde_json::to_writer(&mut *self.output, value)?;
         self.output.write_all(b"\n")?;
@@ -173,7 +210,7 @@ impl TracerEip3155 {
                 state_root: B256::ZERO.to_string(),
                 output: result.output.to_string(),
                 gas_used: hex_number(
-                    context.inner.env().tx.gas_limit - self.gas_inspector.gas_remaining(),
+                    context.inner.env().tx().gas_limit - self.gas_inspector.gas_remaining(),
                 ),
                 pass: result.is_ok(),
                 time: None,
@@ -191,12 +228,22 @@ impl<DB: Database> Inspector<DB> for TracerEip3155 {
 
     fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
         self.gas_inspector.step(interp, context);
-        self.stack.clone_from(interp.stack.data());
+        self.stack.clear();
+        self.stack
+            .extend_from_slice(interp.stack_slice_top(interp.stack_len()));
         self.memory = if self.include_memory {
             Some(hex::encode_prefixed(interp.shared_memory.context_memory()))
         } else {
             None
         };
+        self.frame_info = self.include_frame_info.then(|| {
+            interp.frame_code_hash();
+            interp.frame_info().clone()
+        });
+        self.section = interp.frame_info().code_section;
+        self.function_depth = self
+            .section
+            .map(|_| interp.function_stack.return_stack_len() + 1);
         self.pc = interp.program_counter();
         self.opcode = interp.current_opcode();
         self.mem_size = interp.shared_memory.len();
@@ -211,6 +258,7 @@ impl<DB: Database> Inspector<DB> for TracerEip3155 {
             return;
         }
 
+        let frame_info = self.frame_info.take();
         let value = Output {
             pc: self.pc as u64,
             op: self.opcode,
@@ -218,6 +266,8 @@ impl<DB: Database> Inspector<DB> for TracerEip3155 {
             gas_cost: hex_number(self.gas_inspector.last_gas_cost()),
             stack: self.stack.iter().map(hex_number_u256).collect(),
             depth: context.journaled_state.depth(),
+            section: self.section,
+            function_depth: self.function_depth,
             return_data: "0x".to_string(),
             refund: hex_number(self.refunded as u64),
             mem_size: self.mem_size.to_string(),
@@ -231,6 +281,14 @@ impl<DB: Database> Inspector<DB> for TracerEip3155 {
             memory: self.memory.take(),
             storage: None,
             return_stack: None,
+            code_hash: frame_info
+                .as_ref()
+                .and_then(|info| info.code_hash)
+                .map(|hash| hash.to_string()),
+            address: frame_info.as_ref().map(|info| info.address.to_string()),
+            is_create: frame_info.as_ref().map(|info| info.is_create),
+            is_eof: frame_info.as_ref().map(|info| info.is_eof),
+            code_section: frame_info.and_then(|info| info.code_section),
         };
         let _ = self.write_value(&value);
     }
@@ -284,3 +342,171 @@ fn hex_number_u256(b: &U256) -> String {
         format!("0x{s}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::InMemoryDB,
+        inspector_handle_register,
+        primitives::{
+            address,
+            eof::{EofBody, TypesSection},
+            keccak256, AccountInfo, Address, Bytecode, Bytes, SpecId, TxKind,
+        },
+        test_utils::AssertingInspector,
+        Evm,
+    };
+    use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+    const CALLEE: Address = address!("00000000000000000000000000000000000c0de0");
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Parses the traced steps.
+    fn steps(buffer: &SharedBuffer) -> Vec<serde_json::Value> {
+        let output = buffer.0.borrow();
+        std::str::from_utf8(&output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn eof_section_and_function_depth() {
+        let eof = EofBody {
+            types_section: vec![
+                TypesSection {
+                    inputs: 0,
+                    outputs: 0x80,
+                    max_stack_size: 0,
+                },
+                TypesSection {
+                    inputs: 0,
+                    outputs: 0,
+                    max_stack_size: 1,
+                },
+            ],
+            // CALLF(1), STOP
+            // PUSH1 0x01, POP, RETF
+            code_section: vec![
+                Bytes::from_static(&[0xe3, 0x00, 0x01, 0x00]),
+                Bytes::from_static(&[0x60, 0x01, 0x50, 0xe4]),
+            ],
+            container_section: vec![],
+            data_section: Bytes::new(),
+            is_data_filled: true,
+        }
+        .into_eof();
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            CALLEE,
+            AccountInfo::from_bytecode(Bytecode::Eof(Arc::new(eof))),
+        );
+
+        let buffer = SharedBuffer::default();
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(SpecId::PRAGUE_EOF)
+            .with_external_context(AssertingInspector::new(
+                TracerEip3155::new(Box::new(buffer.clone())).without_summary(),
+            ))
+            .modify_tx_env(|tx| {
+                tx.transact_to = Some(TxKind::Call(CALLEE));
+                tx.gas_limit = 1_000_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        evm.transact().unwrap().result.expect_success();
+
+        let steps: Vec<_> = steps(&buffer)
+            .iter()
+            .map(|step| {
+                (
+                    step["section"].as_u64().unwrap(),
+                    step["functionDepth"].as_u64().unwrap(),
+                    step["pc"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            steps,
+            [(0, 1, 0), (1, 2, 0), (1, 2, 2), (1, 2, 3), (0, 1, 3)]
+        );
+    }
+
+    #[test]
+    fn frame_info_of_create_then_call() {
+        // PUSH1 0x01, STOP
+        let callee_code = Bytes::from_static(&[0x60, 0x01, 0x00]);
+        // CALL(0xffff, CALLEE, 0, 0, 0, 0, 0), STOP
+        let mut init_code = vec![0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00];
+        init_code.push(0x73);
+        init_code.extend_from_slice(CALLEE.as_slice());
+        init_code.extend_from_slice(&[0x61, 0xff, 0xff, 0xf1, 0x00]);
+        let init_code = Bytes::from(init_code);
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            CALLEE,
+            AccountInfo {
+                code: Some(Bytecode::new_raw(callee_code.clone())),
+                ..Default::default()
+            },
+        );
+
+        let buffer = SharedBuffer::default();
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(AssertingInspector::new(
+                TracerEip3155::new(Box::new(buffer.clone()))
+                    .without_summary()
+                    .with_frame_info(),
+            ))
+            .modify_tx_env(|tx| {
+                tx.transact_to = Some(TxKind::Create);
+                tx.data = init_code.clone();
+                tx.gas_limit = 1_000_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        evm.transact().unwrap().result.expect_success();
+
+        let steps = steps(&buffer);
+
+        let callee = CALLEE.to_string();
+        let (call_steps, create_steps): (Vec<_>, Vec<_>) = steps
+            .iter()
+            .partition(|step| step["address"].as_str() == Some(callee.as_str()));
+
+        assert_eq!(call_steps.len(), 2);
+        assert_eq!(create_steps.len(), 9);
+        for step in create_steps {
+            assert_eq!(step["isCreate"], true);
+            assert_eq!(step["isEof"], false);
+            assert_eq!(step["codeHash"], keccak256(&init_code).to_string());
+            assert!(step.get("codeSection").is_none());
+            assert!(step.get("section").is_none());
+            assert!(step.get("functionDepth").is_none());
+        }
+        for step in call_steps {
+            assert_eq!(step["isCreate"], false);
+            assert_eq!(step["isEof"], false);
+            assert_eq!(step["codeHash"], keccak256(&callee_code).to_string());
+        }
+    }
+}
@@ -163,6 +163,7 @@ mod tests {
             inspector::inspector_handle_register,
             interpreter::opcode,
             primitives::{address, Bytecode, Bytes, TxKind},
+            test_utils::AssertingInspector,
             Evm,
         };
 
@@ -183,13 +184,15 @@ mod tests {
         ]);
         let bytecode = Bytecode::new_raw(contract_data);
 
-        let mut evm: Evm<'_, StackInspector, BenchmarkDB> = Evm::builder()
+        let mut evm: Evm<'_, AssertingInspector<StackInspector>, BenchmarkDB> = Evm::builder()
             .with_db(BenchmarkDB::new_bytecode(bytecode.clone()))
-            .with_external_context(StackInspector::default())
+            .with_external_context(AssertingInspector::new(StackInspector::default()))
             .modify_tx_env(|tx| {
                 tx.clear();
                 tx.caller = address!("1000000000000000000000000000000000000000");
-                tx.transact_to = TxKind::Call(address!("0000000000000000000000000000000000000000"));
+                tx.transact_to = Some(TxKind::Call(address!(
+                    "0000000000000000000000000000000000000000"
+                )));
                 tx.gas_limit = 21100;
             })
             .append_handler_register(inspector_handle_register)
@@ -199,6 +202,7 @@ mod tests {
         evm.transact().unwrap();
 
         let inspector = evm.into_context().external;
+        inspector.assert_finished();
 
         // starting from 100gas
         let steps = vec![
@@ -0,0 +1,316 @@
+//! GasGriefInspector. Reports subcalls that ran out of gas while their parent kept enough gas.
+
+use crate::{
+    interpreter::{
+        opcode, CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs,
+        InstructionResult, Interpreter,
+    },
+    primitives::{db::Database, Address},
+    Evm, EvmContext, FrameOrResult, Inspector,
+};
+use std::{boxed::Box, vec::Vec};
+
+/// Subcall that ran out of gas although its parent could have covered the shortfall.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GasGriefFinding {
+    /// Depth of the subcall, the top level call has depth 0.
+    pub depth: usize,
+    /// Target address of the subcall.
+    pub target: Address,
+    /// Gas forwarded to the subcall, including the call stipend.
+    pub forwarded: u64,
+    /// Gas the parent kept after forwarding, see the 63/64 rule of EIP-150.
+    pub retained: u64,
+    /// Additional gas the subcall needed, the gas it spent when executed again with the
+    /// retained gas minus the forwarded gas.
+    pub shortfall_estimate: u64,
+}
+
+/// Frame that has not ended yet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct GriefFrame {
+    /// Gas the parent kept after forwarding, `None` for the top level call and creates.
+    retained: Option<u64>,
+    /// Number of findings when the frame started.
+    findings_len: usize,
+}
+
+/// [Inspector] that detects gas griefing, subcalls that failed only because the parent
+/// forwarded too little gas.
+///
+/// When a subcall runs out of gas it is executed again on a copy of the journal, with the gas
+/// it was forwarded plus the gas its parent kept after the call, the 1/64 of EIP-150 or more if
+/// the gas was capped explicitly. The subcall is reported only if it succeeds with that gas, so
+/// a subcall that runs out of gas with any amount, like an endless loop, is not reported.
+///
+/// The subcall is executed again with the mainnet handler and precompiles of the spec, changes
+/// made to the handler and precompiles of the inspected EVM are not applied.
+///
+/// Findings of subcalls are discarded when an enclosing frame runs out of gas as well, the
+/// enclosing frame had no gas to spare and is reported instead if its parent did.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GasGriefInspector {
+    /// Frames that have not ended yet, the last one is the current frame.
+    stack: Vec<GriefFrame>,
+    /// Opcode of the last instruction.
+    last_opcode: u8,
+    /// Gas kept by the parent after the last call instruction, taken by the next frame.
+    pending_retained: Option<u64>,
+    findings: Vec<GasGriefFinding>,
+}
+
+impl GasGriefInspector {
+    /// Creates a new inspector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the findings of the inspected transactions.
+    pub fn findings(&self) -> &[GasGriefFinding] {
+        &self.findings
+    }
+
+    /// Consumes the inspector and returns the findings.
+    pub fn into_findings(self) -> Vec<GasGriefFinding> {
+        self.findings
+    }
+
+    fn start(&mut self, retained: Option<u64>) {
+        self.stack.push(GriefFrame {
+            retained,
+            findings_len: self.findings.len(),
+        });
+    }
+
+    /// Pops the frame and returns the gas the parent kept if the frame ran out of gas.
+    fn end(&mut self, result: InstructionResult) -> Option<u64> {
+        let frame = self.stack.pop()?;
+        if !is_out_of_gas(result) {
+            return None;
+        }
+        self.findings.truncate(frame.findings_len);
+        frame.retained.filter(|retained| *retained > 0)
+    }
+}
+
+/// Executes the call again with `gas_limit` and returns the gas it spent if it succeeded.
+///
+/// The journal is copied so the state of `context` is not changed.
+fn spent_with_gas<DB: Database>(
+    context: &mut EvmContext<DB>,
+    inputs: &CallInputs,
+    gas_limit: u64,
+) -> Option<u64> {
+    let env = context.env.clone();
+    let journaled_state = context.journaled_state.clone();
+    let spec_id = context.spec_id();
+    let mut evm = Evm::builder()
+        .with_db(&mut context.db)
+        .with_env(env)
+        .with_spec_id(spec_id)
+        .build();
+    evm.context.evm.journaled_state = journaled_state;
+    evm.context.evm.precompiles = evm.handler.pre_execution().load_precompiles();
+
+    let mut inputs = Box::new(inputs.clone());
+    inputs.gas_limit = gas_limit;
+    let result = match evm.context.evm.make_call_frame(&inputs).ok()? {
+        FrameOrResult::Frame(frame) => evm.run_the_loop(frame).ok()?,
+        FrameOrResult::Result(result) => result,
+    };
+    let result = result.interpreter_result();
+    result.result.is_ok().then(|| result.gas.spent())
+}
+
+impl<DB: Database> Inspector<DB> for GasGriefInspector {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        self.last_opcode = interp.current_opcode();
+        self.pending_retained = None;
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        if !interp.instruction_result.is_error() && is_call(self.last_opcode) {
+            // forwarded gas is already spent by the call instruction.
+            self.pending_retained = Some(interp.gas.remaining());
+        }
+    }
+
+    fn call(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        let retained = self.pending_retained.take();
+        self.start(retained);
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        let Some(retained) = self.end(outcome.result.result) else {
+            return outcome;
+        };
+        let forwarded = inputs.gas_limit;
+        if let Some(spent) = spent_with_gas(context, inputs, forwarded.saturating_add(retained)) {
+            self.findings.push(GasGriefFinding {
+                depth: context.journaled_state.depth() as usize,
+                target: inputs.target_address,
+                forwarded,
+                retained,
+                shortfall_estimate: spent.saturating_sub(forwarded),
+            });
+        }
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.start(None);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.end(outcome.result.result);
+        outcome
+    }
+
+    fn eofcreate(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.start(None);
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &EOFCreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.end(outcome.result.result);
+        outcome
+    }
+}
+
+/// Returns `true` if the frame ran out of gas.
+fn is_out_of_gas(result: InstructionResult) -> bool {
+    matches!(
+        result,
+        InstructionResult::OutOfGas
+            | InstructionResult::MemoryOOG
+            | InstructionResult::MemoryLimitOOG
+            | InstructionResult::PrecompileOOG
+            | InstructionResult::InvalidOperandOOG
+    )
+}
+
+/// Returns `true` if the opcode starts a call frame.
+fn is_call(op: u8) -> bool {
+    matches!(
+        op,
+        opcode::CALL
+            | opcode::CALLCODE
+            | opcode::DELEGATECALL
+            | opcode::STATICCALL
+            | opcode::EXTCALL
+            | opcode::EXTDELEGATECALL
+            | opcode::EXTSTATICCALL
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        inspector_handle_register,
+        primitives::{address, bytes, AccountInfo, Bytecode, Bytes, U256},
+        test_utils::{contract_evm, CALLER},
+    };
+    use std::vec;
+
+    const CHILD: Address = address!("3000000000000000000000000000000000000003");
+
+    /// `CALL(gas, CHILD, 0, 0, 0, 0, 0)`, `POP`, `STOP`, all gas is forwarded if `gas` is `None`.
+    fn parent(gas: Option<u8>) -> Bytes {
+        let mut code = vec![0x5f, 0x5f, 0x5f, 0x5f, 0x5f, 0x73];
+        code.extend_from_slice(CHILD.as_slice());
+        match gas {
+            Some(gas) => code.extend_from_slice(&[0x60, gas]),
+            None => code.push(0x5a),
+        }
+        code.extend_from_slice(&[0xf1, 0x50, 0x00]);
+        code.into()
+    }
+
+    fn findings(parent: Bytes, child: Bytes, gas_limit: u64) -> Vec<GasGriefFinding> {
+        let mut evm = contract_evm(parent)
+            .modify_db(|db| {
+                db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1_000_000)));
+                db.insert_account_info(CHILD, AccountInfo::from_bytecode(Bytecode::new_raw(child)));
+            })
+            .with_external_context(GasGriefInspector::new())
+            .modify_tx_env(|tx| tx.gas_limit = gas_limit)
+            .append_handler_register(inspector_handle_register)
+            .build();
+        // the parent succeeds, only the subcall fails.
+        evm.transact().unwrap().result.expect_success();
+        evm.into_context().external.into_findings()
+    }
+
+    #[test]
+    fn too_little_forwarded_gas_is_reported() {
+        // PUSH0, PUSH0, PUSH0, STOP: 6 gas, 5 are forwarded.
+        let findings = findings(parent(Some(5)), bytes!("5f5f5f00"), 100_000);
+        assert_eq!(findings.len(), 1);
+        let finding = findings[0];
+        assert_eq!(finding.depth, 1);
+        assert_eq!(finding.target, CHILD);
+        assert_eq!(finding.forwarded, 5);
+        assert_eq!(finding.shortfall_estimate, 1);
+        assert!(finding.retained > 70_000);
+    }
+
+    #[test]
+    fn genuine_out_of_gas_is_not_reported() {
+        // CREATE(0, 0, 0) costs 32000, more than the whole gas of the parent.
+        let findings = findings(parent(None), bytes!("5f5f5ff000"), 40_000);
+        assert_eq!(findings, []);
+    }
+
+    #[test]
+    fn endless_loop_is_not_reported() {
+        // JUMPDEST, PUSH0, JUMP runs out of gas with any amount.
+        let findings = findings(parent(Some(100)), bytes!("5b5f56"), 100_000);
+        assert_eq!(findings, []);
+    }
+
+    #[test]
+    fn sstore_out_of_gas() {
+        // SSTORE(0, 1) costs 22100, the static gas is only 100.
+        let child = bytes!("60015f5500");
+
+        // the parent kept about 13600, not enough for the SSTORE.
+        assert_eq!(findings(parent(Some(255)), child.clone(), 37_500), []);
+
+        let reported = findings(parent(Some(255)), child, 100_000);
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].forwarded, 255);
+        // PUSH1, PUSH0 and SSTORE.
+        assert_eq!(reported[0].shortfall_estimate, 3 + 2 + 22_100 - 255);
+    }
+}
@@ -0,0 +1,304 @@
+//! GasTreeInspector. Records the gas used by every frame as a call tree.
+
+use crate::{
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, Gas,
+        InstructionResult,
+    },
+    primitives::{db::Database, Address, FixedBytes},
+    EvmContext, Inspector,
+};
+use core::fmt::{self, Write};
+use std::{string::String, vec::Vec};
+
+/// Kind of a frame in the [GasTreeInspector].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GasFrameKind {
+    /// Call to a contract.
+    Call,
+    /// Call to a precompile, it never has children.
+    Precompile,
+    /// `CREATE`, `CREATE2` or `EOFCREATE`.
+    Create,
+}
+
+/// Gas used by a frame and its children.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GasFrame {
+    /// Kind of the frame.
+    pub kind: GasFrameKind,
+    /// Target address of the call or created address, `None` if the creation failed.
+    pub address: Option<Address>,
+    /// Function selector of the call, first four bytes of the input.
+    pub selector: Option<FixedBytes<4>>,
+    /// Gas used by the frame including its children.
+    pub gas_used: u64,
+    /// Gas used by the frame itself.
+    pub self_gas: u64,
+    /// Frames called by this frame, in call order.
+    pub children: Vec<GasFrame>,
+}
+
+impl GasFrame {
+    fn new(kind: GasFrameKind, address: Option<Address>, selector: Option<FixedBytes<4>>) -> Self {
+        Self {
+            kind,
+            address,
+            selector,
+            gas_used: 0,
+            self_gas: 0,
+            children: Vec::new(),
+        }
+    }
+
+    /// Sets the gas used by the frame, all gas is used if the frame failed.
+    fn finish(&mut self, result: InstructionResult, gas: &Gas) {
+        self.gas_used = if result.is_error() {
+            gas.limit()
+        } else {
+            gas.spent()
+        };
+        let children: u64 = self.children.iter().map(|child| child.gas_used).sum();
+        self.self_gas = self.gas_used.saturating_sub(children);
+    }
+
+    /// Writes the folded stack line of this frame and its children.
+    fn write_folded(&self, prefix: &str, out: &mut String) {
+        let mut stack = String::from(prefix);
+        if !stack.is_empty() {
+            stack.push(';');
+        }
+        write!(stack, "{self}").expect("write to string");
+        writeln!(out, "{stack} {}", self.self_gas).expect("write to string");
+        for child in &self.children {
+            child.write_folded(&stack, out);
+        }
+    }
+}
+
+/// Label of the frame, the address followed by the selector, e.g. `0x…:0xa9059cbb`.
+impl fmt::Display for GasFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.kind, self.address) {
+            (GasFrameKind::Create, Some(address)) => write!(f, "create:{address}")?,
+            (GasFrameKind::Create, None) => f.write_str("create")?,
+            (_, Some(address)) => write!(f, "{address}")?,
+            (_, None) => f.write_str("unknown")?,
+        }
+        if let Some(selector) = self.selector {
+            write!(f, ":{selector}")?;
+        }
+        Ok(())
+    }
+}
+
+/// [Inspector] that records the gas used by every frame as a call tree.
+///
+/// Every transaction adds one root frame, intrinsic gas of the transaction is not included.
+/// The tree can be exported to the folded stack format consumed by flamegraph tools with
+/// [`GasTreeInspector::to_folded_stacks`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GasTreeInspector {
+    /// Frames that have not ended yet, the last one is the current frame.
+    stack: Vec<GasFrame>,
+    /// Root frames of the executed transactions.
+    roots: Vec<GasFrame>,
+}
+
+impl GasTreeInspector {
+    /// Creates a new inspector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the root frames of the executed transactions.
+    pub fn roots(&self) -> &[GasFrame] {
+        &self.roots
+    }
+
+    /// Consumes the inspector and returns the root frames.
+    pub fn into_roots(self) -> Vec<GasFrame> {
+        self.roots
+    }
+
+    /// Returns the tree in the folded stack format, one line per frame with its self gas,
+    /// e.g. `0x…A;0x…B 1234`.
+    pub fn to_folded_stacks(&self) -> String {
+        let mut out = String::new();
+        for root in &self.roots {
+            root.write_folded("", &mut out);
+        }
+        out
+    }
+
+    /// Returns the root frames as a JSON array.
+    #[cfg(feature = "serde-json")]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(&self.roots).expect("frames are serializable")
+    }
+
+    fn start(&mut self, frame: GasFrame) {
+        self.stack.push(frame);
+    }
+
+    fn end(&mut self, address: Option<Address>, result: InstructionResult, gas: &Gas) {
+        let Some(mut frame) = self.stack.pop() else {
+            return;
+        };
+        if frame.kind == GasFrameKind::Create {
+            frame.address = address;
+        }
+        frame.finish(result, gas);
+        match self.stack.last_mut() {
+            Some(parent) => parent.children.push(frame),
+            None => self.roots.push(frame),
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for GasTreeInspector {
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        let kind = if context.precompiles.contains(&inputs.bytecode_address) {
+            GasFrameKind::Precompile
+        } else {
+            GasFrameKind::Call
+        };
+        let selector = match kind {
+            GasFrameKind::Call => context
+                .call_input(&inputs.input)
+                .and_then(|input| input.get(..4))
+                .map(FixedBytes::from_slice),
+            _ => None,
+        };
+        self.start(GasFrame::new(kind, Some(inputs.target_address), selector));
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.end(
+            Some(inputs.target_address),
+            outcome.result.result,
+            &outcome.result.gas,
+        );
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.start(GasFrame::new(GasFrameKind::Create, None, None));
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.end(outcome.address, outcome.result.result, &outcome.result.gas);
+        outcome
+    }
+
+    fn eofcreate(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.start(GasFrame::new(GasFrameKind::Create, None, None));
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &EOFCreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.end(outcome.address, outcome.result.result, &outcome.result.gas);
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        inspector_handle_register,
+        primitives::{address, bytes, AccountInfo, Bytecode, Bytes, U256},
+        test_utils::{contract_evm, CALLER, CONTRACT},
+    };
+    use std::{format, vec};
+
+    const CHILD_B: Address = address!("3000000000000000000000000000000000000003");
+    const CHILD_C: Address = address!("4000000000000000000000000000000000000004");
+    const IDENTITY: Address = address!("0000000000000000000000000000000000000004");
+
+    /// `CALL(GAS, address, 0, 0, 0, 0, 0)` followed by `POP`, 2617 gas for a cold address.
+    fn call(address: &[u8]) -> Vec<u8> {
+        let mut code = vec![0x5f, 0x5f, 0x5f, 0x5f, 0x5f, 0x60 + address.len() as u8 - 1];
+        code.extend_from_slice(address);
+        code.extend_from_slice(&[0x5a, 0xf1, 0x50]);
+        code
+    }
+
+    #[test]
+    fn gas_tree_of_two_children_and_precompile() {
+        let mut code = call(CHILD_B.as_slice());
+        code.extend(call(CHILD_C.as_slice()));
+        code.extend(call(&[0x04]));
+        code.push(0x00);
+
+        let mut evm = contract_evm(code)
+            .modify_db(|db| {
+                db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1_000_000)));
+                // PUSH1 1, POP, STOP: 5 gas.
+                db.insert_account_info(
+                    CHILD_B,
+                    AccountInfo::from_bytecode(Bytecode::new_raw(bytes!("60015000"))),
+                );
+                // PUSH0, STOP: 2 gas.
+                db.insert_account_info(
+                    CHILD_C,
+                    AccountInfo::from_bytecode(Bytecode::new_raw(bytes!("5f00"))),
+                );
+            })
+            .with_external_context(GasTreeInspector::new())
+            .modify_tx_env(|tx| tx.data = Bytes::from_static(&[0x12, 0x34, 0x56, 0x78]))
+            .append_handler_register(inspector_handle_register)
+            .build();
+        evm.transact().unwrap().result.expect_success();
+
+        let inspector = evm.into_context().external;
+        let root = &inspector.roots()[0];
+        assert_eq!(root.children.len(), 3);
+        assert_eq!(root.children[2].kind, GasFrameKind::Precompile);
+        assert!(root.children.iter().all(|child| child.children.is_empty()));
+        // two cold calls, a warm call to the precompile and the gas of the children.
+        assert_eq!(root.self_gas, 2 * 2617 + 117);
+        assert_eq!(root.gas_used, 2 * 2617 + 117 + 5 + 2 + 15);
+
+        let parent = format!("{CONTRACT}:0x12345678");
+        assert_eq!(
+            inspector.to_folded_stacks(),
+            format!(
+                "{parent} 5351\n{parent};{CHILD_B} 5\n{parent};{CHILD_C} 2\n{parent};{IDENTITY} 15\n"
+            )
+        );
+        #[cfg(feature = "serde-json")]
+        assert_eq!(inspector.to_json()[0]["children"][2]["kind"], "Precompile");
+    }
+}
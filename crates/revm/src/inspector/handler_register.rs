@@ -1,8 +1,9 @@
 use crate::{
+    context::evm_context::call_input_out_of_bounds,
     db::Database,
     handler::register::EvmHandler,
-    interpreter::{opcode, InstructionResult, Interpreter},
-    primitives::EVMError,
+    interpreter::{opcode, CallInput, InstructionResult, Interpreter},
+    primitives::{EVMError, U256},
     Context, FrameOrResult, FrameResult, Inspector, JournalEntry,
 };
 use core::cell::RefCell;
@@ -39,9 +40,6 @@ pub fn inspector_handle_register<DB: Database, EXT: GetInspector<DB>>(
 ) {
     let table = &mut handler.instruction_table;
 
-    // Update all instructions to call inspector step and step_end.
-    table.update_all(inspector_instruction);
-
     // Register inspector LOG* instructions.
     for opcode in opcode::LOG0..=opcode::LOG4 {
         table.update_boxed(opcode, move |prev, interpreter, host| {
@@ -50,10 +48,8 @@ pub fn inspector_handle_register<DB: Database, EXT: GetInspector<DB>>(
             // check if log was added. It is possible that revert happened
             // cause of gas or stack underflow.
             if host.evm.journaled_state.logs.len() == prev_log_len + 1 {
-                // clone log.
-                // TODO decide if we should remove this and leave the comment
-                // that log can be found as journaled_state.
-                let last_log = host.evm.journaled_state.logs.last().unwrap().clone();
+                // logs are buffered in the journal, copy the log for the inspector.
+                let last_log = host.evm.journaled_state.logs.last().unwrap().to_log();
                 // call Inspector
                 host.external.get_inspector().log(&mut host.evm, &last_log);
             }
@@ -78,6 +74,57 @@ pub fn inspector_handle_register<DB: Database, EXT: GetInspector<DB>>(
         }
     });
 
+    // Register jump instructions. The operands are decoded only if the inspector opted in.
+    for opcode in [
+        opcode::JUMP,
+        opcode::JUMPI,
+        opcode::RJUMP,
+        opcode::RJUMPI,
+        opcode::RJUMPV,
+    ] {
+        table.update_boxed(opcode, move |prev, interpreter, host| {
+            if !host.external.get_inspector().inspect_jumps() {
+                return prev(interpreter, host);
+            }
+            // PC was already incremented past the opcode.
+            let from_pc = interpreter.program_counter() - 1;
+            let target = interpreter
+                .stack_peek(0)
+                .map(|target| target.saturating_to::<usize>());
+            let condition_taken = match opcode {
+                opcode::JUMPI => interpreter.stack_peek(1).map(|cond| !cond.is_zero()),
+                opcode::RJUMPI => interpreter.stack_peek(0).map(|cond| !cond.is_zero()),
+                opcode::RJUMPV => {
+                    let max_index = interpreter.bytecode.get(from_pc + 1).copied();
+                    interpreter
+                        .stack_peek(0)
+                        .zip(max_index)
+                        .map(|(case, max_index)| *case <= U256::from(max_index))
+                }
+                _ => Some(true),
+            };
+
+            prev(interpreter, host);
+
+            let (to_pc, is_valid) = match interpreter.instruction_result {
+                InstructionResult::Continue => (interpreter.program_counter(), true),
+                InstructionResult::InvalidJump => (target.unwrap_or_default(), false),
+                // stack underflow, out of gas or not activated.
+                _ => return,
+            };
+            host.external.get_inspector().jump(
+                from_pc,
+                to_pc,
+                condition_taken.unwrap_or_default(),
+                is_valid,
+            );
+        });
+    }
+
+    // Update all instructions to call inspector step and step_end. This wraps the LOG* and
+    // SELFDESTRUCT handlers above so their hooks are called before step_end.
+    table.update_all(inspector_instruction);
+
     // call and create input stack shared between handlers. They are used to share
     // inputs in *_end Inspector calls.
     let call_input_stack = Rc::<RefCell<Vec<_>>>::default();
@@ -111,6 +158,14 @@ pub fn inspector_handle_register<DB: Database, EXT: GetInspector<DB>>(
     let call_input_stack_inner = call_input_stack.clone();
     let prev_handle = handler.execution.call.clone();
     handler.execution.call = Arc::new(move |ctx, mut inputs| {
+        // Inspectors see the input as bytes, it is copied out of the caller memory.
+        if let CallInput::SharedBuffer(_) = inputs.input {
+            let input = inputs
+                .input
+                .bytes(ctx.evm.caller_memory())
+                .ok_or_else(call_input_out_of_bounds)?;
+            inputs.input = CallInput::Bytes(input);
+        }
         // Call inspector to change input or return outcome.
         let outcome = ctx.external.get_inspector().call(&mut ctx.evm, &mut inputs);
         call_input_stack_inner.borrow_mut().push(inputs.clone());
@@ -251,8 +306,14 @@ fn inspector_instruction<INSP, DB>(
 mod tests {
     use super::*;
     use crate::{
+        db::InMemoryDB,
         inspectors::NoOpInspector,
-        interpreter::{CallInputs, CallOutcome, CreateInputs, CreateOutcome},
+        interpreter::{
+            CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, Gas,
+            InterpreterResult,
+        },
+        primitives::{address, Address, Bytes, ExecutionResult, HaltReason, SpecId, TxKind},
+        test_utils::AssertingInspector,
         Evm, EvmContext,
     };
 
@@ -354,13 +415,15 @@ mod tests {
         ]);
         let bytecode = Bytecode::new_raw(contract_data);
 
-        let mut evm: Evm<'_, StackInspector, BenchmarkDB> = Evm::builder()
+        let mut evm: Evm<'_, AssertingInspector<StackInspector>, BenchmarkDB> = Evm::builder()
             .with_db(BenchmarkDB::new_bytecode(bytecode.clone()))
-            .with_external_context(StackInspector::default())
+            .with_external_context(AssertingInspector::new(StackInspector::default()))
             .modify_tx_env(|tx| {
                 tx.clear();
                 tx.caller = address!("1000000000000000000000000000000000000000");
-                tx.transact_to = TxKind::Call(address!("0000000000000000000000000000000000000000"));
+                tx.transact_to = Some(TxKind::Call(address!(
+                    "0000000000000000000000000000000000000000"
+                )));
                 tx.gas_limit = 21100;
             })
             .append_handler_register(inspector_handle_register)
@@ -370,6 +433,7 @@ mod tests {
         evm.transact().unwrap();
 
         let inspector = evm.into_context().external;
+        inspector.assert_finished();
 
         assert_eq!(inspector.step, 6);
         assert_eq!(inspector.step_end, 6);
@@ -378,6 +442,163 @@ mod tests {
         assert!(inspector.call_end);
     }
 
+    /// Blocks EOF creation to the address and rewrites gas of the outcome.
+    #[derive(Default)]
+    struct EofCreateInspector {
+        blocked: Option<Address>,
+        spend_all_gas: bool,
+        created: Vec<Option<Address>>,
+    }
+
+    impl<DB: Database> Inspector<DB> for EofCreateInspector {
+        fn eofcreate(
+            &mut self,
+            context: &mut EvmContext<DB>,
+            inputs: &mut EOFCreateInputs,
+        ) -> Option<CreateOutcome> {
+            let address = context.preview_eofcreate_address(inputs).ok()?;
+            (Some(address) == self.blocked).then(|| {
+                CreateOutcome::new(
+                    InterpreterResult::new(
+                        InstructionResult::Revert,
+                        Bytes::new(),
+                        Gas::new(inputs.gas_limit),
+                    ),
+                    None,
+                )
+            })
+        }
+
+        fn eofcreate_end(
+            &mut self,
+            _context: &mut EvmContext<DB>,
+            _inputs: &EOFCreateInputs,
+            mut outcome: CreateOutcome,
+        ) -> CreateOutcome {
+            self.created.push(outcome.address);
+            if self.spend_all_gas {
+                outcome.result.gas = Gas::new_spent(outcome.gas().limit());
+            }
+            outcome
+        }
+    }
+
+    /// Runs EOF create transaction with invalid initcode.
+    fn eofcreate_tx(inspector: EofCreateInspector) -> (ExecutionResult, EofCreateInspector) {
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .with_external_context(AssertingInspector::new(inspector))
+            .with_spec_id(SpecId::PRAGUE_EOF)
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = Some(TxKind::Create);
+                tx.data = Bytes::from_static(&[0xEF, 0x00, 0x01, 0x02]);
+                tx.gas_limit = 100_000;
+            })
+            .build();
+        let result = evm.inspect().unwrap().result;
+        let inspector = evm.into_context().external;
+        inspector.assert_finished();
+        (result, inspector.into_inner())
+    }
+
+    const CALLER: Address = address!("1000000000000000000000000000000000000000");
+
+    /// Intrinsic gas of the EOF create transaction.
+    const INTRINSIC_GAS: u64 = 53_000 + 3 * 16 + 4 + 2;
+
+    #[test]
+    fn test_eofcreate_blocked() {
+        let (result, inspector) = eofcreate_tx(EofCreateInspector {
+            blocked: Some(CALLER.create(0)),
+            ..Default::default()
+        });
+        assert_eq!(result.gas_used(), INTRINSIC_GAS);
+        result.expect_revert();
+        // end hook is called for the outcome of the begin hook.
+        assert_eq!(inspector.created, vec![None]);
+    }
+
+    #[test]
+    fn test_eofcreate_early_failure_outcome() {
+        // invalid initcode fails before execution, end hook sees the created address.
+        let (result, inspector) = eofcreate_tx(EofCreateInspector::default());
+        assert_eq!(result.gas_used(), INTRINSIC_GAS);
+        result.expect_revert();
+        assert_eq!(inspector.created, vec![Some(CALLER.create(0))]);
+
+        let (result, inspector) = eofcreate_tx(EofCreateInspector {
+            spend_all_gas: true,
+            ..Default::default()
+        });
+        assert_eq!(result.gas_used(), 100_000);
+        result.expect_revert();
+        assert_eq!(inspector.created.len(), 1);
+    }
+
+    #[test]
+    fn test_hook_order_of_frames_without_interpretation() {
+        use crate::{db::BenchmarkDB, interpreter::opcode, primitives::Bytecode};
+
+        let contract_data: Bytes = Bytes::from(vec![
+            // CALL(gas, 0x04, 0, 0, 0, 0, 0), identity precompile.
+            opcode::PUSH1,
+            0x0,
+            opcode::PUSH1,
+            0x0,
+            opcode::PUSH1,
+            0x0,
+            opcode::PUSH1,
+            0x0,
+            opcode::PUSH1,
+            0x0,
+            opcode::PUSH1,
+            0x4,
+            opcode::GAS,
+            opcode::CALL,
+            opcode::POP,
+            // CALL(gas, 0x05, 0xffffffff, 0, 0, 0, 0), insufficient balance.
+            opcode::PUSH1,
+            0x0,
+            opcode::PUSH1,
+            0x0,
+            opcode::PUSH1,
+            0x0,
+            opcode::PUSH1,
+            0x0,
+            opcode::PUSH4,
+            0xff,
+            0xff,
+            0xff,
+            0xff,
+            opcode::PUSH1,
+            0x5,
+            opcode::GAS,
+            opcode::CALL,
+            opcode::POP,
+            // LOG0(0, 0)
+            opcode::PUSH1,
+            0x0,
+            opcode::PUSH1,
+            0x0,
+            opcode::LOG0,
+            opcode::STOP,
+        ]);
+
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(contract_data)))
+            .with_external_context(AssertingInspector::new(NoOpInspector))
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = Some(TxKind::Call(Address::ZERO));
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        evm.transact().unwrap().result.expect_success();
+        evm.context.external.assert_finished();
+    }
+
     #[test]
     fn test_inspector_reg() {
         let mut noop = NoOpInspector;
@@ -386,4 +607,148 @@ mod tests {
             .append_handler_register(inspector_handle_register)
             .build();
     }
+
+    /// Records the jumps if enabled.
+    #[derive(Default)]
+    struct JumpInspector {
+        enabled: bool,
+        jumps: Vec<(usize, usize, bool, bool)>,
+    }
+
+    impl<DB: Database> Inspector<DB> for JumpInspector {
+        fn inspect_jumps(&self) -> bool {
+            self.enabled
+        }
+
+        fn jump(&mut self, from_pc: usize, to_pc: usize, condition_taken: bool, is_valid: bool) {
+            self.jumps.push((from_pc, to_pc, condition_taken, is_valid));
+        }
+    }
+
+    fn run_jumps(enabled: bool) -> Vec<(usize, usize, bool, bool)> {
+        use crate::{db::BenchmarkDB, primitives::Bytecode};
+
+        // counts down from 3 and jumps to an invalid destination at the end.
+        let code = Bytes::from(vec![
+            opcode::PUSH1,
+            0x3,
+            opcode::JUMPDEST,
+            opcode::PUSH1,
+            0x1,
+            opcode::SWAP1,
+            opcode::SUB,
+            opcode::DUP1,
+            opcode::PUSH1,
+            0x2,
+            opcode::JUMPI,
+            opcode::PUSH1,
+            0xff,
+            opcode::JUMP,
+        ]);
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+            .with_external_context(AssertingInspector::new(JumpInspector {
+                enabled,
+                ..Default::default()
+            }))
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = Some(TxKind::Call(Address::ZERO));
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        let result = evm.transact().unwrap().result;
+        assert!(matches!(
+            result,
+            ExecutionResult::Halt {
+                reason: HaltReason::InvalidJump,
+                ..
+            }
+        ));
+        let inspector = evm.into_context().external;
+        inspector.assert_finished();
+        inspector.into_inner().jumps
+    }
+
+    #[test]
+    fn test_jump_hook() {
+        assert_eq!(
+            run_jumps(true),
+            [
+                (10, 2, true, true),
+                (10, 2, true, true),
+                (10, 11, false, true),
+                (13, 0xff, true, false),
+            ]
+        );
+        assert!(run_jumps(false).is_empty());
+    }
+
+    /// Step hooks reading the top of the stack do not allocate.
+    #[cfg(feature = "std")]
+    mod stack_peek {
+        use super::*;
+        use crate::{
+            db::BenchmarkDB,
+            primitives::{Bytecode, U256},
+            test_utils::alloc_counter::allocations,
+        };
+
+        /// Reads the slot and value of every SSTORE from the top of the stack.
+        #[derive(Default)]
+        struct SstorePeekInspector {
+            sstores: usize,
+            allocations: usize,
+            last: Option<(U256, U256)>,
+        }
+
+        impl<DB: Database> Inspector<DB> for SstorePeekInspector {
+            fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+                if interp.current_opcode() != opcode::SSTORE {
+                    return;
+                }
+                let before = allocations();
+                let slot = *interp.stack_peek(0).unwrap();
+                let value = *interp.stack_peek(1).unwrap();
+                assert_eq!(interp.stack_slice_top(2), [value, slot]);
+                self.allocations += allocations() - before;
+                self.sstores += 1;
+                self.last = Some((slot, value));
+            }
+        }
+
+        #[test]
+        fn test_step_stack_peek_does_not_allocate() {
+            let code = Bytes::from(vec![
+                opcode::PUSH1,
+                0x1,
+                opcode::PUSH1,
+                0x0,
+                opcode::SSTORE,
+                opcode::PUSH1,
+                0x2,
+                opcode::PUSH1,
+                0x1,
+                opcode::SSTORE,
+                opcode::STOP,
+            ]);
+            let mut evm = Evm::builder()
+                .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+                .with_external_context(SstorePeekInspector::default())
+                .modify_tx_env(|tx| {
+                    tx.caller = CALLER;
+                    tx.transact_to = Some(TxKind::Call(Address::ZERO));
+                    tx.gas_limit = 100_000;
+                })
+                .append_handler_register(inspector_handle_register)
+                .build();
+            assert!(evm.transact().unwrap().result.is_success());
+
+            let inspector = evm.into_context().external;
+            assert_eq!(inspector.sstores, 2);
+            assert_eq!(inspector.allocations, 0);
+            assert_eq!(inspector.last, Some((U256::from(1), U256::from(2))));
+        }
+    }
 }
@@ -1,13 +1,18 @@
 use crate::{
     interpreter::{InstructionResult, LoadAccountResult, SStoreResult, SelfDestructResult},
     primitives::{
-        db::Database, hash_map::Entry, Account, Address, Bytecode, EVMError, EvmState,
-        EvmStorageSlot, HashMap, HashSet, Log, SpecId, SpecId::*, TransientStorage, B256,
-        KECCAK_EMPTY, PRECOMPILE3, U256,
+        db::Database, hash_map::Entry, Account, AccountInfo, Address, Bytecode,
+        DatabaseInconsistency, EVMError, EvmState, EvmStorageSlot, HashMap, HashSet, Log,
+        Predeploys, SelfDestruct, SpecId, SpecId::*, TransientStorage, B256, KECCAK_EMPTY,
+        PRECOMPILE3, U256,
     },
 };
 use core::mem;
-use std::vec::Vec;
+use std::{boxed::Box, vec::Vec};
+
+mod log_buffer;
+
+pub use log_buffer::{LogBuffer, LogRef};
 
 /// JournalState is internal EVM state that is used to contain state and track changes to that state.
 /// It contains journal of changes that happened to state so that they can be reverted.
@@ -19,7 +24,12 @@ pub struct JournaledState {
     /// [EIP-1153](https://eips.ethereum.org/EIPS/eip-1153) transient storage that is discarded after every transactions
     pub transient_storage: TransientStorage,
     /// logs
-    pub logs: Vec<Log>,
+    ///
+    /// Logs were a `Vec<Log>` before they were buffered, use [`JournaledState::logs_to_vec`]
+    /// where owned [`Log`]s are needed.
+    pub logs: LogBuffer,
+    /// Executed selfdestructs, reverted together with the logs.
+    pub selfdestructs: Vec<SelfDestruct>,
     /// how deep are we in call stack.
     pub depth: usize,
     /// journal with changes that happened between calls.
@@ -35,6 +45,16 @@ pub struct JournaledState {
     /// Note that this not include newly loaded accounts, account and storage
     /// is considered warm if it is found in the `State`.
     pub warm_preloaded_addresses: HashSet<Address>,
+    /// Values first read from the database in the current transaction.
+    ///
+    /// `None` if the database reads are not checked, see [`JournaledState::set_db_read_checks`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub db_reads: Option<DbReads>,
+    /// Contracts installed into the accounts loaded from the database that have no code,
+    /// see [`CfgEnv::predeploys`](crate::primitives::CfgEnv::predeploys).
+    ///
+    /// The accounts are warm when they are first accessed.
+    pub predeploys: Option<Predeploys>,
 }
 
 impl JournaledState {
@@ -51,13 +71,16 @@ impl JournaledState {
     ///
     pub fn new(spec: SpecId, warm_preloaded_addresses: HashSet<Address>) -> JournaledState {
         Self {
-            state: HashMap::new(),
+            state: HashMap::default(),
             transient_storage: TransientStorage::default(),
-            logs: Vec::new(),
+            logs: LogBuffer::default(),
+            selfdestructs: Vec::new(),
             journal: vec![vec![]],
             depth: 0,
             spec,
             warm_preloaded_addresses,
+            db_reads: None,
+            predeploys: None,
         }
     }
 
@@ -73,6 +96,19 @@ impl JournaledState {
         self.spec = spec;
     }
 
+    /// Enables or disables checking that the database returns the same values for the same
+    /// keys inside one transaction.
+    ///
+    /// See [`CfgEnv::paranoid_db_checks`](crate::primitives::CfgEnv::paranoid_db_checks).
+    #[inline]
+    pub fn set_db_read_checks(&mut self, enabled: bool) {
+        if !enabled {
+            self.db_reads = None;
+        } else if self.db_reads.is_none() {
+            self.db_reads = Some(DbReads::default());
+        }
+    }
+
     /// Mark account as touched as only touched accounts will be added to state.
     /// This is especially important for state clear where touched empty accounts needs to
     /// be removed from state.
@@ -92,37 +128,55 @@ impl JournaledState {
         }
     }
 
-    /// Clears the JournaledState. Preserving only the spec.
+    /// Clears the JournaledState. Preserving only the spec and the allocated log buffers.
     pub fn clear(&mut self) {
         let spec = self.spec;
-        *self = Self::new(spec, HashSet::new());
+        // keep the log buffers allocated for the next transaction.
+        let mut logs = core::mem::take(&mut self.logs);
+        logs.clear();
+        *self = Self::new(spec, HashSet::default());
+        self.logs = logs;
     }
 
     /// Does cleanup and returns modified state.
     ///
-    /// This resets the [JournaledState] to its initial state in [Self::new]
+    /// This resets the [JournaledState] to its initial state in [Self::new]. Selfdestructs are
+    /// cleared, take them with [Self::take_selfdestructs] before.
     #[inline]
     pub fn finalize(&mut self) -> (EvmState, Vec<Log>) {
         let Self {
             state,
             transient_storage,
             logs,
+            selfdestructs,
             depth,
             journal,
             // kept, see [Self::new]
             spec: _,
             warm_preloaded_addresses: _,
+            db_reads,
+            predeploys: _,
         } = self;
 
+        if let Some(db_reads) = db_reads {
+            *db_reads = DbReads::default();
+        }
         *transient_storage = TransientStorage::default();
+        selfdestructs.clear();
         *journal = vec![vec![]];
         *depth = 0;
         let state = mem::take(state);
-        let logs = mem::take(logs);
+        let logs = logs.take_logs();
 
         (state, logs)
     }
 
+    /// Takes the selfdestructs executed in the frames that were not reverted.
+    #[inline]
+    pub fn take_selfdestructs(&mut self) -> Vec<SelfDestruct> {
+        mem::take(&mut self.selfdestructs)
+    }
+
     /// Returns the _loaded_ [Account] for the given address.
     ///
     /// This assumes that the account has already been loaded.
@@ -168,6 +222,11 @@ impl JournaledState {
         self.set_code_with_hash(address, code, hash)
     }
 
+    /// Increments the nonce of the account and returns the new nonce.
+    ///
+    /// Returns `None` if the nonce would overflow, in which case the account is not changed.
+    ///
+    /// Note: Assume account is warm.
     #[inline]
     pub fn inc_nonce(&mut self, address: Address) -> Option<u64> {
         let account = self.state.get_mut(&address).unwrap();
@@ -186,6 +245,112 @@ impl JournaledState {
         Some(account.info.nonce)
     }
 
+    /// Sets the nonce of the account. The previous nonce is restored on revert.
+    ///
+    /// Note: Assume account is warm.
+    #[inline]
+    pub fn set_nonce(&mut self, address: Address, nonce: u64) {
+        let account = self.state.get_mut(&address).unwrap();
+        Self::touch_account(self.journal.last_mut().unwrap(), &address, account);
+        self.journal
+            .last_mut()
+            .unwrap()
+            .push(JournalEntry::NonceSet {
+                address,
+                had_nonce: account.info.nonce,
+            });
+
+        account.info.nonce = nonce;
+    }
+
+    /// Sets the balance of the account and returns the previous one. The previous balance is
+    /// restored on revert.
+    ///
+    /// Note: Assume account is warm.
+    #[inline]
+    pub fn set_balance(&mut self, address: Address, balance: U256) -> U256 {
+        let account = self.state.get_mut(&address).unwrap();
+        Self::touch_account(self.journal.last_mut().unwrap(), &address, account);
+        let had_balance = mem::replace(&mut account.info.balance, balance);
+        self.journal
+            .last_mut()
+            .unwrap()
+            .push(JournalEntry::BalanceSet {
+                address,
+                had_balance,
+            });
+        had_balance
+    }
+
+    /// Adds `amount` to the balance of the account and marks it touched. The change is
+    /// restored on revert.
+    ///
+    /// Returns [`InstructionResult::OverflowPayment`] if the balance would overflow, in which
+    /// case the account is not changed.
+    ///
+    /// Note: Assume account is warm.
+    #[inline]
+    pub fn balance_incr(
+        &mut self,
+        address: Address,
+        amount: U256,
+    ) -> Result<(), InstructionResult> {
+        let balance = self.state.get(&address).unwrap().info.balance;
+        let new_balance = balance
+            .checked_add(amount)
+            .ok_or(InstructionResult::OverflowPayment)?;
+        self.set_balance(address, new_balance);
+        Ok(())
+    }
+
+    /// Subtracts `amount` from the balance of the account and marks it touched. The change is
+    /// restored on revert.
+    ///
+    /// Returns [`InstructionResult::OutOfFunds`] if the balance is less than `amount`, in
+    /// which case the account is not changed.
+    ///
+    /// Note: Assume account is warm.
+    #[inline]
+    pub fn balance_decr(
+        &mut self,
+        address: Address,
+        amount: U256,
+    ) -> Result<(), InstructionResult> {
+        let balance = self.state.get(&address).unwrap().info.balance;
+        let new_balance = balance
+            .checked_sub(amount)
+            .ok_or(InstructionResult::OutOfFunds)?;
+        self.set_balance(address, new_balance);
+        Ok(())
+    }
+
+    /// Replaces the code and its hash of the account and returns the previous code.
+    /// The previous code is restored on revert, unlike [Self::set_code_with_hash] that assumes
+    /// the account had no code.
+    ///
+    /// Note: Assume account is warm and that hash is calculated from code.
+    #[inline]
+    pub fn replace_code_with_hash(
+        &mut self,
+        address: Address,
+        code: Bytecode,
+        hash: B256,
+    ) -> Option<Bytecode> {
+        let account = self.state.get_mut(&address).unwrap();
+        Self::touch_account(self.journal.last_mut().unwrap(), &address, account);
+        let had_code_hash = mem::replace(&mut account.info.code_hash, hash);
+        let had_code = account.info.code.replace(code);
+        self.journal
+            .last_mut()
+            .unwrap()
+            .push(JournalEntry::CodeSet {
+                address,
+                had_code: had_code.clone(),
+                had_code_hash,
+            });
+        had_code
+    }
+
     /// Transfers balance from two accounts. Returns error if sender balance is not enough.
     #[inline]
     pub fn transfer<DB: Database>(
@@ -365,6 +530,15 @@ impl JournaledState {
                 JournalEntry::NonceChange { address } => {
                     state.get_mut(&address).unwrap().info.nonce -= 1;
                 }
+                JournalEntry::NonceSet { address, had_nonce } => {
+                    state.get_mut(&address).unwrap().info.nonce = had_nonce;
+                }
+                JournalEntry::BalanceSet {
+                    address,
+                    had_balance,
+                } => {
+                    state.get_mut(&address).unwrap().info.balance = had_balance;
+                }
                 JournalEntry::AccountCreated { address } => {
                     let account = &mut state.get_mut(&address).unwrap();
                     account.unmark_created();
@@ -415,6 +589,15 @@ impl JournaledState {
                     acc.info.code_hash = KECCAK_EMPTY;
                     acc.info.code = None;
                 }
+                JournalEntry::CodeSet {
+                    address,
+                    had_code,
+                    had_code_hash,
+                } => {
+                    let acc = state.get_mut(&address).unwrap();
+                    acc.info.code_hash = had_code_hash;
+                    acc.info.code = had_code;
+                }
             }
         }
     }
@@ -424,6 +607,7 @@ impl JournaledState {
     pub fn checkpoint(&mut self) -> JournalCheckpoint {
         let checkpoint = JournalCheckpoint {
             log_i: self.logs.len(),
+            selfdestruct_i: self.selfdestructs.len(),
             journal_i: self.journal.len(),
         };
         self.depth += 1;
@@ -460,6 +644,7 @@ impl JournaledState {
             });
 
         self.logs.truncate(checkpoint.log_i);
+        self.selfdestructs.truncate(checkpoint.selfdestruct_i);
         self.journal.truncate(checkpoint.journal_i);
     }
 
@@ -470,6 +655,14 @@ impl JournaledState {
     /// current spec enables Cancun, this happens only when the account associated to address
     /// is created in the same tx
     ///
+    /// Selfdestruct to self per spec:
+    ///  * Pre Cancun: account is marked as destroyed and its balance is burned.
+    ///  * Post Cancun, account created in the same tx: same as pre Cancun.
+    ///  * Post Cancun, account not created in the same tx: nothing changes, balance is retained.
+    ///
+    /// [SelfDestructResult::previously_destroyed] is set if the account was already
+    /// selfdestructed in this transaction, so gas refund (pre London) is given only once.
+    ///
     /// references:
     ///  * <https://github.com/ethereum/go-ethereum/blob/141cd425310b503c5678e674a8c3872cf46b7086/core/vm/instructions.go#L832-L833>
     ///  * <https://github.com/ethereum/go-ethereum/blob/141cd425310b503c5678e674a8c3872cf46b7086/core/state/statedb.go#L449>
@@ -499,7 +692,14 @@ impl JournaledState {
         let is_cancun_enabled = SpecId::enabled(self.spec, CANCUN);
 
         // EIP-6780 (Cancun hard-fork): selfdestruct only if contract is created in the same tx
-        let journal_entry = if acc.is_created() || !is_cancun_enabled {
+        let deleted = acc.is_created() || !is_cancun_enabled;
+        self.selfdestructs.push(SelfDestruct {
+            address,
+            target,
+            value: balance,
+            deleted,
+        });
+        let journal_entry = if deleted {
             acc.mark_selfdestruct();
             acc.info.balance = U256::ZERO;
             Some(JournalEntry::AccountDestroyed {
@@ -530,11 +730,47 @@ impl JournaledState {
         Ok(SelfDestructResult {
             had_value: balance != U256::ZERO,
             is_cold: load_result.is_cold,
-            target_exists: !load_result.is_empty,
+            target_exists: !load_result.is_new_account(self.spec),
             previously_destroyed,
         })
     }
 
+    /// Loads the account from the database and installs the predeploy at the `address` if the
+    /// database has no code for it.
+    ///
+    /// Predeploy exists even if the database does not have the account.
+    #[inline]
+    fn db_account<DB: Database>(
+        db_reads: &mut Option<DbReads>,
+        predeploys: &Option<Predeploys>,
+        address: Address,
+        db: &mut DB,
+    ) -> Result<Account, EVMError<DB::Error>> {
+        let info = db.basic(address).map_err(EVMError::Database)?;
+        if let Some(db_reads) = db_reads {
+            db_reads.check_basic(address, &info)?;
+        }
+        let predeploy = predeploys
+            .as_ref()
+            .and_then(|predeploys| predeploys.get(&address));
+        let account = match (info, predeploy) {
+            (Some(mut info), Some((code_hash, code))) if info.code_hash == KECCAK_EMPTY => {
+                info.code_hash = code_hash;
+                info.code = Some(code.clone());
+                info.into()
+            }
+            (Some(info), _) => info.into(),
+            (None, Some((code_hash, code))) => AccountInfo {
+                code_hash,
+                code: Some(code.clone()),
+                ..Default::default()
+            }
+            .into(),
+            (None, None) => Account::new_not_existing(),
+        };
+        Ok(account)
+    }
+
     /// Initial load of account. This load will not be tracked inside journal
     #[inline]
     pub fn initial_account_load<DB: Database>(
@@ -546,12 +782,12 @@ impl JournaledState {
         // load or get account.
         let account = match self.state.entry(address) {
             Entry::Occupied(entry) => entry.into_mut(),
-            Entry::Vacant(vac) => vac.insert(
-                db.basic(address)
-                    .map_err(EVMError::Database)?
-                    .map(|i| i.into())
-                    .unwrap_or(Account::new_not_existing()),
-            ),
+            Entry::Vacant(vac) => vac.insert(Self::db_account(
+                &mut self.db_reads,
+                &self.predeploys,
+                address,
+                db,
+            )?),
         };
         // preload storages.
         for storage_key in storage_keys.into_iter() {
@@ -559,6 +795,9 @@ impl JournaledState {
                 let storage = db
                     .storage(address, storage_key)
                     .map_err(EVMError::Database)?;
+                if let Some(db_reads) = &mut self.db_reads {
+                    db_reads.check_storage(address, storage_key, storage)?;
+                }
                 entry.insert(EvmStorageSlot::new(storage));
             }
         }
@@ -579,15 +818,14 @@ impl JournaledState {
                 (account, is_cold)
             }
             Entry::Vacant(vac) => {
-                let account =
-                    if let Some(account) = db.basic(address).map_err(EVMError::Database)? {
-                        account.into()
-                    } else {
-                        Account::new_not_existing()
-                    };
+                let account = Self::db_account(&mut self.db_reads, &self.predeploys, address, db)?;
 
-                // precompiles are warm loaded so we need to take that into account
-                let is_cold = !self.warm_preloaded_addresses.contains(&address);
+                // precompiles and predeploys are warm loaded so we need to take that into account
+                let is_cold = !self.warm_preloaded_addresses.contains(&address)
+                    && !self
+                        .predeploys
+                        .as_ref()
+                        .is_some_and(|predeploys| predeploys.contains(&address));
 
                 (vac.insert(account), is_cold)
             }
@@ -606,26 +844,24 @@ impl JournaledState {
 
     /// Load account from database to JournaledState.
     ///
-    /// Return boolean pair where first is `is_cold` second bool `is_exists`.
+    /// Returns whether the account is cold, empty and whether it exists, see [LoadAccountResult].
     #[inline]
     pub fn load_account_exist<DB: Database>(
         &mut self,
         address: Address,
         db: &mut DB,
     ) -> Result<LoadAccountResult, EVMError<DB::Error>> {
-        let spec = self.spec;
         let (acc, is_cold) = self.load_account(address, db)?;
 
-        let is_spurious_dragon_enabled = SpecId::enabled(spec, SPURIOUS_DRAGON);
-        let is_empty = if is_spurious_dragon_enabled {
-            acc.is_empty()
-        } else {
-            let loaded_not_existing = acc.is_loaded_as_not_existing();
-            let is_not_touched = !acc.is_touched();
-            loaded_not_existing && is_not_touched
-        };
+        // Account that was not found in the database exists after it is touched,
+        // as pre EIP-161 touch creates the account.
+        let exists_in_db = !acc.is_loaded_as_not_existing() || acc.is_touched();
 
-        Ok(LoadAccountResult { is_empty, is_cold })
+        Ok(LoadAccountResult {
+            is_cold,
+            is_empty: acc.is_empty(),
+            exists_in_db,
+        })
     }
 
     /// Loads code.
@@ -636,18 +872,30 @@ impl JournaledState {
         db: &mut DB,
     ) -> Result<(&mut Account, bool), EVMError<DB::Error>> {
         let (acc, is_cold) = self.load_account(address, db)?;
-        if acc.info.code.is_none() {
-            if acc.info.code_hash == KECCAK_EMPTY {
+        Self::load_account_code(acc, db)?;
+        Ok((acc, is_cold))
+    }
+
+    /// Loads the code of the account from the database if it is not already loaded.
+    ///
+    /// Code is not tracked inside journal.
+    #[inline]
+    pub fn load_account_code<DB: Database>(
+        account: &mut Account,
+        db: &mut DB,
+    ) -> Result<(), EVMError<DB::Error>> {
+        if account.info.code.is_none() {
+            if account.info.code_hash == KECCAK_EMPTY {
                 let empty = Bytecode::default();
-                acc.info.code = Some(empty);
+                account.info.code = Some(empty);
             } else {
                 let code = db
-                    .code_by_hash(acc.info.code_hash)
+                    .code_by_hash(account.info.code_hash)
                     .map_err(EVMError::Database)?;
-                acc.info.code = Some(code);
+                account.info.code = Some(code);
             }
         }
-        Ok((acc, is_cold))
+        Ok(())
     }
 
     /// Load storage slot
@@ -677,7 +925,11 @@ impl JournaledState {
                 let value = if is_newly_created {
                     U256::ZERO
                 } else {
-                    db.storage(address, key).map_err(EVMError::Database)?
+                    let value = db.storage(address, key).map_err(EVMError::Database)?;
+                    if let Some(db_reads) = &mut self.db_reads {
+                        db_reads.check_storage(address, key, value)?;
+                    }
+                    value
                 };
 
                 vac.insert(EvmStorageSlot::new(value));
@@ -802,7 +1054,85 @@ impl JournaledState {
     /// push log into subroutine
     #[inline]
     pub fn log(&mut self, log: Log) {
-        self.logs.push(log);
+        self.log_raw(log.address, log.topics(), &log.data.data);
+    }
+
+    /// Pushes a log given by its parts into subroutine, without allocating a [`Log`].
+    ///
+    /// The log is attributed to the current depth.
+    #[inline]
+    pub fn log_raw(&mut self, address: Address, topics: &[B256], data: &[u8]) {
+        self.logs.push_with_depth(self.depth, address, topics, data);
+    }
+
+    /// Returns the logs of the transaction together with the depth of the frame that emitted
+    /// them, the first frame is at depth 1.
+    ///
+    /// Depth is recorded when the log is emitted, so it is kept when the frame is committed.
+    /// Logs of the reverted frames are removed.
+    pub fn logs_with_depth(&self) -> impl ExactSizeIterator<Item = (usize, LogRef<'_>)> {
+        self.logs.iter_with_depth()
+    }
+
+    /// Returns copies of the logs of the transaction, logs are not removed.
+    pub fn logs_to_vec(&self) -> Vec<Log> {
+        self.logs.iter().map(|log| log.to_log()).collect()
+    }
+}
+
+/// Values read from the database, used to check that the database returns the same value
+/// every time the same key is read.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DbReads {
+    accounts: HashMap<Address, Option<AccountInfo>>,
+    storage: HashMap<(Address, U256), U256>,
+}
+
+impl DbReads {
+    /// Records the account info read from the database.
+    ///
+    /// Returns an error if it differs from the first read of the same account.
+    pub fn check_basic(
+        &mut self,
+        address: Address,
+        info: &Option<AccountInfo>,
+    ) -> Result<(), Box<DatabaseInconsistency>> {
+        let info = info.clone().map(AccountInfo::without_code);
+        match self.accounts.entry(address) {
+            Entry::Occupied(first) if *first.get() != info => {
+                Err(Box::new(DatabaseInconsistency::Basic {
+                    address,
+                    first: first.get().clone(),
+                    second: info,
+                }))
+            }
+            Entry::Occupied(_) => Ok(()),
+            Entry::Vacant(entry) => {
+                entry.insert(info);
+                Ok(())
+            }
+        }
+    }
+
+    /// Records the storage value read from the database.
+    ///
+    /// Returns an error if it differs from the first read of the same slot.
+    pub fn check_storage(
+        &mut self,
+        address: Address,
+        slot: U256,
+        value: U256,
+    ) -> Result<(), Box<DatabaseInconsistency>> {
+        let first = *self.storage.entry((address, slot)).or_insert(value);
+        if first != value {
+            return Err(Box::new(DatabaseInconsistency::Storage {
+                address,
+                slot,
+                first,
+                second: value,
+            }));
+        }
+        Ok(())
     }
 }
 
@@ -842,6 +1172,14 @@ pub enum JournalEntry {
     NonceChange {
         address: Address, //geth has nonce value,
     },
+    /// Set nonce
+    /// Action: Set nonce to a new value
+    /// Revert: Restore the previous nonce
+    NonceSet { address: Address, had_nonce: u64 },
+    /// Set balance
+    /// Action: Set balance to a new value
+    /// Revert: Restore the previous balance
+    BalanceSet { address: Address, had_balance: U256 },
     /// Create account:
     /// Actions: Mark account as created
     /// Revert: Unmart account as created and reset nonce to zero.
@@ -870,6 +1208,14 @@ pub enum JournalEntry {
     /// Action: Account code changed
     /// Revert: Revert to previous bytecode.
     CodeChange { address: Address },
+    /// Code replaced
+    /// Action: Account code and code hash replaced
+    /// Revert: Restore the previous code and code hash.
+    CodeSet {
+        address: Address,
+        had_code: Option<Bytecode>,
+        had_code_hash: B256,
+    },
 }
 
 /// SubRoutine checkpoint that will help us to go back from this
@@ -877,5 +1223,410 @@ pub enum JournalEntry {
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct JournalCheckpoint {
     log_i: usize,
+    selfdestruct_i: usize,
     journal_i: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        primitives::{address, AccountInfo, Bytes},
+        test_utils::{CALLER, CONTRACT},
+    };
+    use rstest::rstest;
+
+    const TARGET: Address = address!("3000000000000000000000000000000000000003");
+
+    #[rstest]
+    #[case::frontier(FRONTIER)]
+    #[case::cancun(CANCUN)]
+    fn predeploy_is_warm_and_exists(#[case] spec: SpecId) {
+        // STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x00]));
+        let mut journal = JournaledState::new(spec, HashSet::default());
+        journal.predeploys = Some(Predeploys::from_iter([(TARGET, code.clone())]));
+        let mut db = CacheDB::new(EmptyDB::default());
+
+        let load = journal.load_account_exist(TARGET, &mut db).unwrap();
+        assert!(!load.is_cold);
+        // no NEWACCOUNT cost before Spurious Dragon either.
+        assert!(!load.is_new_account(spec));
+        let account = journal.account(TARGET);
+        assert_eq!(account.info.code_hash, code.hash_slow());
+        assert_eq!(account.info.code, Some(code));
+
+        // accounts without predeploys are not changed.
+        let load = journal.load_account_exist(CONTRACT, &mut db).unwrap();
+        assert!(load.is_cold);
+        assert!(load.is_new_account(spec));
+    }
+
+    /// Creates a journal with `CONTRACT` holding `balance` and `CALLER` with some funds.
+    fn setup(spec: SpecId, balance: U256) -> (JournaledState, CacheDB<EmptyDB>) {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1000)));
+        let mut contract = AccountInfo::from_balance(balance);
+        contract.nonce = 1;
+        db.insert_account_info(CONTRACT, contract);
+
+        let mut journal = JournaledState::new(spec, HashSet::default());
+        journal.load_account(CALLER, &mut db).unwrap();
+        journal.load_account(CONTRACT, &mut db).unwrap();
+        (journal, db)
+    }
+
+    /// Creates `TARGET` inside the journal as a new account funded with `value`.
+    fn create_target(journal: &mut JournaledState, db: &mut CacheDB<EmptyDB>, value: U256) {
+        journal.load_account(TARGET, db).unwrap();
+        let spec = journal.spec;
+        journal
+            .create_account_checkpoint(CALLER, TARGET, value, spec)
+            .unwrap();
+        journal.checkpoint_commit();
+    }
+
+    #[rstest]
+    #[case::frontier(FRONTIER)]
+    #[case::london(LONDON)]
+    #[case::shanghai(SHANGHAI)]
+    fn selfdestruct_to_self_pre_cancun_burns_balance(#[case] spec: SpecId) {
+        let (mut journal, mut db) = setup(spec, U256::from(100));
+
+        let res = journal.selfdestruct(CONTRACT, CONTRACT, &mut db).unwrap();
+        assert!(res.had_value);
+        assert!(!res.previously_destroyed);
+
+        let acc = journal.account(CONTRACT);
+        assert!(acc.is_selfdestructed());
+        assert_eq!(acc.info.balance, U256::ZERO);
+    }
+
+    #[rstest]
+    #[case::cancun(CANCUN)]
+    #[case::prague(PRAGUE)]
+    fn selfdestruct_to_self_post_cancun_retains_balance(#[case] spec: SpecId) {
+        let (mut journal, mut db) = setup(spec, U256::from(100));
+
+        let res = journal.selfdestruct(CONTRACT, CONTRACT, &mut db).unwrap();
+        assert!(res.had_value);
+        assert!(!res.previously_destroyed);
+
+        let acc = journal.account(CONTRACT);
+        assert!(!acc.is_selfdestructed());
+        assert_eq!(acc.info.balance, U256::from(100));
+    }
+
+    #[rstest]
+    #[case::shanghai(SHANGHAI)]
+    #[case::cancun(CANCUN)]
+    fn selfdestruct_created_account_to_self_burns_balance(#[case] spec: SpecId) {
+        let (mut journal, mut db) = setup(spec, U256::ZERO);
+        create_target(&mut journal, &mut db, U256::from(10));
+
+        let res = journal.selfdestruct(TARGET, TARGET, &mut db).unwrap();
+        assert!(res.had_value);
+
+        let acc = journal.account(TARGET);
+        assert!(acc.is_selfdestructed());
+        assert_eq!(acc.info.balance, U256::ZERO);
+        assert_eq!(journal.account(CALLER).info.balance, U256::from(990));
+    }
+
+    #[rstest]
+    #[case::frontier(FRONTIER)]
+    #[case::berlin(BERLIN)]
+    #[case::cancun(CANCUN)]
+    fn selfdestruct_twice_sets_previously_destroyed(#[case] spec: SpecId) {
+        let (mut journal, mut db) = setup(spec, U256::ZERO);
+        create_target(&mut journal, &mut db, U256::from(10));
+
+        let first = journal.selfdestruct(TARGET, CONTRACT, &mut db).unwrap();
+        assert!(first.had_value);
+        assert!(!first.previously_destroyed);
+
+        let second = journal.selfdestruct(TARGET, CONTRACT, &mut db).unwrap();
+        assert!(!second.had_value);
+        assert!(second.previously_destroyed);
+
+        assert_eq!(journal.account(CONTRACT).info.balance, U256::from(10));
+        assert_eq!(journal.account(TARGET).info.balance, U256::ZERO);
+    }
+
+    #[rstest]
+    #[case::frontier(FRONTIER)]
+    #[case::cancun(CANCUN)]
+    fn selfdestruct_zero_balance(#[case] spec: SpecId) {
+        let (mut journal, mut db) = setup(spec, U256::ZERO);
+
+        let res = journal.selfdestruct(CONTRACT, TARGET, &mut db).unwrap();
+        assert!(!res.had_value);
+        assert!(!res.target_exists);
+        assert!(res.is_cold);
+        assert_eq!(journal.account(TARGET).info.balance, U256::ZERO);
+    }
+
+    #[rstest]
+    #[case::frontier(FRONTIER)]
+    #[case::cancun(CANCUN)]
+    fn selfdestruct_to_self_revert(#[case] spec: SpecId) {
+        let (mut journal, mut db) = setup(spec, U256::from(100));
+
+        let checkpoint = journal.checkpoint();
+        journal.selfdestruct(CONTRACT, CONTRACT, &mut db).unwrap();
+        journal.checkpoint_revert(checkpoint);
+
+        let acc = journal.account(CONTRACT);
+        assert!(!acc.is_selfdestructed());
+        assert_eq!(acc.info.balance, U256::from(100));
+    }
+
+    #[rstest]
+    #[case::frontier(FRONTIER, true)]
+    #[case::cancun(CANCUN, false)]
+    fn selfdestructs_of_reverted_checkpoint_are_dropped(
+        #[case] spec: SpecId,
+        #[case] deleted: bool,
+    ) {
+        let (mut journal, mut db) = setup(spec, U256::from(100));
+
+        let checkpoint = journal.checkpoint();
+        journal.selfdestruct(CONTRACT, TARGET, &mut db).unwrap();
+        journal.checkpoint_revert(checkpoint);
+        assert!(journal.selfdestructs.is_empty());
+
+        journal.checkpoint();
+        journal.selfdestruct(CONTRACT, TARGET, &mut db).unwrap();
+        journal.checkpoint_commit();
+
+        assert_eq!(
+            journal.take_selfdestructs(),
+            [SelfDestruct {
+                address: CONTRACT,
+                target: TARGET,
+                value: U256::from(100),
+                deleted,
+            }]
+        );
+    }
+
+    #[test]
+    fn set_nonce_is_reverted() {
+        let (mut journal, _) = setup(LATEST, U256::ZERO);
+
+        let checkpoint = journal.checkpoint();
+        journal.set_nonce(CALLER, u64::MAX);
+        assert_eq!(journal.account(CALLER).info.nonce, u64::MAX);
+        assert!(journal.account(CALLER).is_touched());
+        assert_eq!(journal.inc_nonce(CALLER), None);
+        assert_eq!(journal.account(CALLER).info.nonce, u64::MAX);
+
+        journal.set_nonce(CALLER, 5);
+        assert_eq!(journal.inc_nonce(CALLER), Some(6));
+        journal.checkpoint_revert(checkpoint);
+
+        assert_eq!(journal.account(CALLER).info.nonce, 0);
+    }
+
+    #[test]
+    fn set_balance_and_replace_code_are_reverted() {
+        let (mut journal, mut db) = setup(LATEST, U256::from(7));
+        journal.load_code(CONTRACT, &mut db).unwrap();
+        let code = journal.account(CONTRACT).info.code.clone();
+        let code_hash = journal.account(CONTRACT).info.code_hash;
+
+        let checkpoint = journal.checkpoint();
+        assert_eq!(journal.set_balance(CONTRACT, U256::from(9)), U256::from(7));
+        let new_code = Bytecode::new_raw(Bytes::from_static(&[0x00]));
+        assert_eq!(
+            journal.replace_code_with_hash(CONTRACT, new_code.clone(), new_code.hash_slow()),
+            code
+        );
+        assert_eq!(journal.account(CONTRACT).info.balance, U256::from(9));
+        assert_eq!(journal.account(CONTRACT).info.code, Some(new_code));
+        journal.checkpoint_revert(checkpoint);
+
+        assert_eq!(journal.account(CONTRACT).info.balance, U256::from(7));
+        assert_eq!(journal.account(CONTRACT).info.code, code);
+        assert_eq!(journal.account(CONTRACT).info.code_hash, code_hash);
+    }
+
+    #[test]
+    fn balance_incr_and_decr() {
+        let (mut journal, mut db) = setup(LATEST, U256::from(7));
+        journal.load_account(CONTRACT, &mut db).unwrap();
+
+        let checkpoint = journal.checkpoint();
+        assert_eq!(journal.balance_incr(CONTRACT, U256::from(3)), Ok(()));
+        assert_eq!(journal.balance_decr(CONTRACT, U256::from(4)), Ok(()));
+        assert_eq!(journal.account(CONTRACT).info.balance, U256::from(6));
+        assert!(journal.account(CONTRACT).is_touched());
+
+        assert_eq!(
+            journal.balance_decr(CONTRACT, U256::from(7)),
+            Err(InstructionResult::OutOfFunds)
+        );
+        journal.set_balance(CONTRACT, U256::MAX);
+        assert_eq!(
+            journal.balance_incr(CONTRACT, U256::from(1)),
+            Err(InstructionResult::OverflowPayment)
+        );
+        assert_eq!(journal.account(CONTRACT).info.balance, U256::MAX);
+
+        journal.checkpoint_revert(checkpoint);
+        assert_eq!(journal.account(CONTRACT).info.balance, U256::from(7));
+    }
+
+    #[test]
+    fn db_read_checks() {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1000)));
+        let mut journal = JournaledState::new(SpecId::LATEST, HashSet::default());
+        journal.set_db_read_checks(true);
+        journal.load_account(CALLER, &mut db).unwrap();
+
+        // Drop the loaded account so it is read from the database again.
+        journal.state.clear();
+        journal.load_account(CALLER, &mut db).unwrap();
+
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(2000)));
+        journal.state.clear();
+        let Err(EVMError::DatabaseInconsistency(inconsistency)) =
+            journal.load_account(CALLER, &mut db)
+        else {
+            panic!("expected database inconsistency");
+        };
+        assert_eq!(
+            *inconsistency,
+            DatabaseInconsistency::Basic {
+                address: CALLER,
+                first: Some(AccountInfo::from_balance(U256::from(1000))),
+                second: Some(AccountInfo::from_balance(U256::from(2000))),
+            }
+        );
+        assert_eq!(
+            inconsistency.to_string(),
+            format!(
+                "account {CALLER} was read as {{ balance: 1000, nonce: 0, code_hash: {KECCAK_EMPTY} }} \
+                 and later as {{ balance: 2000, nonce: 0, code_hash: {KECCAK_EMPTY} }}"
+            )
+        );
+
+        // Reads are forgotten at the end of the transaction.
+        journal.finalize();
+        journal.load_account(CALLER, &mut db).unwrap();
+        journal.state.clear();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1000)));
+        journal.set_db_read_checks(false);
+        journal.load_account(CALLER, &mut db).unwrap();
+    }
+
+    #[rstest]
+    #[case::tangerine(SpecId::TANGERINE)]
+    #[case::spurious_dragon(SpecId::SPURIOUS_DRAGON)]
+    fn load_account_exist_flags(#[case] spec: SpecId) {
+        let is_eip161 = spec.is_enabled_in(SPURIOUS_DRAGON);
+        let (mut journal, mut db) = setup(spec, U256::ZERO);
+        db.insert_account_info(TARGET, AccountInfo::default());
+
+        // Empty account that exists in the database.
+        let result = journal.load_account_exist(TARGET, &mut db).unwrap();
+        assert!(result.is_empty);
+        assert!(result.exists_in_db);
+        assert_eq!(result.is_new_account(spec), is_eip161);
+
+        // Account that does not exist.
+        let missing = address!("4000000000000000000000000000000000000004");
+        let result = journal.load_account_exist(missing, &mut db).unwrap();
+        assert!(result.is_empty);
+        assert!(!result.exists_in_db);
+        assert!(result.is_new_account(spec));
+
+        // Touch creates the account before EIP-161.
+        journal.touch(&missing);
+        let result = journal.load_account_exist(missing, &mut db).unwrap();
+        assert!(result.exists_in_db);
+        assert_eq!(result.is_new_account(spec), is_eip161);
+
+        // Non empty account is never new.
+        let result = journal.load_account_exist(CALLER, &mut db).unwrap();
+        assert!(!result.is_empty);
+        assert!(!result.is_new_account(spec));
+    }
+
+    #[test]
+    fn logs_keep_depth_of_emission() {
+        let (mut journal, _) = setup(LATEST, U256::ZERO);
+        let log = |journal: &mut JournaledState, topic: u8| {
+            journal.log_raw(CONTRACT, &[B256::with_last_byte(topic)], &[topic]);
+        };
+
+        journal.checkpoint();
+        log(&mut journal, 1);
+        journal.checkpoint();
+        log(&mut journal, 2);
+        let checkpoint = journal.checkpoint();
+        log(&mut journal, 3);
+        journal.checkpoint_revert(checkpoint);
+        log(&mut journal, 2);
+        journal.checkpoint_commit();
+        journal.checkpoint_commit();
+
+        assert_eq!(journal.depth, 0);
+        assert_eq!(
+            journal
+                .logs_with_depth()
+                .map(|(depth, log)| (depth, log.data[0]))
+                .collect::<Vec<_>>(),
+            [(1, 1), (2, 2), (2, 2)]
+        );
+        assert_eq!(
+            journal.logs_to_vec(),
+            [1, 2, 2].map(|topic| Log::new_unchecked(
+                CONTRACT,
+                vec![B256::with_last_byte(topic)],
+                Bytes::from(vec![topic])
+            ))
+        );
+        assert_eq!(journal.logs.len(), 3);
+    }
+
+    /// Emitting a log allocates at most once, for the topics of the finalized [`Log`].
+    #[cfg(feature = "std")]
+    #[test]
+    fn log_allocations() {
+        use crate::{
+            db::BenchmarkDB,
+            primitives::{bytes, Bytecode, TxKind},
+            test_utils::alloc_counter::allocations,
+            Evm,
+        };
+
+        // Emits `LOG2(0, 32, 1, 2)` `CALLDATALOAD(0)` times.
+        let code = bytes!("5f355b80156017576002600160205fa2600190036002565b00");
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = Some(TxKind::Call(Address::ZERO));
+                tx.gas_limit = 30_000_000;
+            })
+            .build();
+        let mut emit = |count: u64| {
+            evm.tx_mut().data = Bytes::copy_from_slice(B256::from(U256::from(count)).as_slice());
+            let before = allocations();
+            let logs = evm.transact().unwrap().result.into_logs();
+            let allocated = allocations() - before;
+            assert_eq!(logs.len(), count as usize);
+            allocated
+        };
+
+        let few = emit(10);
+        let many = emit(1010);
+        // buffers grow a few times, every log allocates its topics.
+        assert!(many - few <= 1000 + 64, "{} allocations", many - few);
+        // buffers are kept between transactions.
+        assert!(emit(1010) - few <= 1000, "buffers reallocated");
+    }
+}
@@ -0,0 +1,228 @@
+use crate::primitives::{Address, Bytes, Log, B256};
+use core::ops::Range;
+use std::vec::Vec;
+
+/// Logs of a transaction.
+///
+/// Topics and data of all logs are appended to shared buffers, so emitting a log does not
+/// allocate once the buffers have grown. The buffers are kept between transactions.
+/// Logs are converted to [`Log`] when the transaction is finalized, see [`LogBuffer::take_logs`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogBuffer {
+    entries: Vec<LogEntry>,
+    topics: Vec<B256>,
+    data: Vec<u8>,
+}
+
+/// Log stored in the [`LogBuffer`], topics and data end where the next log starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct LogEntry {
+    address: Address,
+    topics_start: usize,
+    data_start: usize,
+    /// Depth of the frame that emitted the log, 0 if it is not known.
+    #[cfg_attr(feature = "serde", serde(default))]
+    depth: usize,
+}
+
+/// Borrowed log from the [`LogBuffer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LogRef<'a> {
+    /// The address which emitted this log.
+    pub address: Address,
+    /// The indexed topic list.
+    pub topics: &'a [B256],
+    /// The plain data.
+    pub data: &'a [u8],
+}
+
+impl LogRef<'_> {
+    /// Copies the log into a [`Log`].
+    pub fn to_log(&self) -> Log {
+        Log::new_unchecked(
+            self.address,
+            self.topics.to_vec(),
+            Bytes::copy_from_slice(self.data),
+        )
+    }
+}
+
+impl LogBuffer {
+    /// Returns the number of logs.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no logs.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Appends a log, its depth is not known.
+    #[inline]
+    pub fn push(&mut self, address: Address, topics: &[B256], data: &[u8]) {
+        self.push_with_depth(0, address, topics, data);
+    }
+
+    /// Appends a log emitted by the frame at `depth`.
+    #[inline]
+    pub fn push_with_depth(
+        &mut self,
+        depth: usize,
+        address: Address,
+        topics: &[B256],
+        data: &[u8],
+    ) {
+        self.entries.push(LogEntry {
+            address,
+            topics_start: self.topics.len(),
+            data_start: self.data.len(),
+            depth,
+        });
+        self.topics.extend_from_slice(topics);
+        self.data.extend_from_slice(data);
+    }
+
+    /// Appends a [`Log`].
+    #[inline]
+    pub fn push_log(&mut self, log: &Log) {
+        self.push(log.address, log.topics(), &log.data.data);
+    }
+
+    /// Returns the log at `index`.
+    pub fn get(&self, index: usize) -> Option<LogRef<'_>> {
+        let entry = self.entries.get(index)?;
+        let (topics, data) = self.ranges(index);
+        Some(LogRef {
+            address: entry.address,
+            topics: &self.topics[topics],
+            data: &self.data[data],
+        })
+    }
+
+    /// Returns the depth of the frame that emitted the log at `index`, 0 if it is not known.
+    pub fn depth(&self, index: usize) -> Option<usize> {
+        self.entries.get(index).map(|entry| entry.depth)
+    }
+
+    /// Returns the last log.
+    pub fn last(&self) -> Option<LogRef<'_>> {
+        self.get(self.len().checked_sub(1)?)
+    }
+
+    /// Returns an iterator over the logs.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = LogRef<'_>> {
+        (0..self.len()).map(|index| self.get(index).expect("index in bounds"))
+    }
+
+    /// Returns an iterator over the logs together with the depth of the frame that emitted them.
+    pub fn iter_with_depth(&self) -> impl ExactSizeIterator<Item = (usize, LogRef<'_>)> {
+        self.entries
+            .iter()
+            .zip(self.iter())
+            .map(|(entry, log)| (entry.depth, log))
+    }
+
+    /// Removes the logs after the first `len`, used when a frame is reverted.
+    pub fn truncate(&mut self, len: usize) {
+        if let Some(entry) = self.entries.get(len) {
+            self.topics.truncate(entry.topics_start);
+            self.data.truncate(entry.data_start);
+            self.entries.truncate(len);
+        }
+    }
+
+    /// Removes all logs, keeping the allocated buffers.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Converts the logs to [`Log`]s and clears the buffer, keeping the allocated buffers.
+    ///
+    /// Data of all logs shares one allocation, so every log allocates only its topics.
+    pub fn take_logs(&mut self) -> Vec<Log> {
+        let data = Bytes::copy_from_slice(&self.data);
+        let logs = (0..self.len())
+            .map(|index| {
+                let (topics, data_range) = self.ranges(index);
+                Log::new_unchecked(
+                    self.entries[index].address,
+                    self.topics[topics].to_vec(),
+                    data.slice(data_range),
+                )
+            })
+            .collect();
+        self.clear();
+        logs
+    }
+
+    /// Returns the ranges of the topics and data of the log at `index`.
+    fn ranges(&self, index: usize) -> (Range<usize>, Range<usize>) {
+        let entry = &self.entries[index];
+        let (topics_end, data_end) = match self.entries.get(index + 1) {
+            Some(next) => (next.topics_start, next.data_start),
+            None => (self.topics.len(), self.data.len()),
+        };
+        (entry.topics_start..topics_end, entry.data_start..data_end)
+    }
+}
+
+impl From<Vec<Log>> for LogBuffer {
+    fn from(logs: Vec<Log>) -> Self {
+        let mut buffer = Self::default();
+        for log in &logs {
+            buffer.push_log(log);
+        }
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::bytes;
+
+    fn log(address: u8, topics: &[u8], data: Bytes) -> Log {
+        Log::new_unchecked(
+            Address::with_last_byte(address),
+            topics
+                .iter()
+                .map(|topic| B256::with_last_byte(*topic))
+                .collect(),
+            data,
+        )
+    }
+
+    #[test]
+    fn push_truncate_and_take() {
+        let logs = [
+            log(1, &[1, 2], bytes!("0102")),
+            log(2, &[], Bytes::new()),
+            log(3, &[3, 4, 5, 6], bytes!("030405")),
+        ];
+        let mut buffer = LogBuffer::default();
+        for log in &logs {
+            buffer.push_log(log);
+        }
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.get(1).unwrap().to_log(), logs[1]);
+        assert_eq!(buffer.last().unwrap().to_log(), logs[2]);
+        assert!(buffer.get(3).is_none());
+        assert!(buffer
+            .iter()
+            .map(|log| log.to_log())
+            .eq(logs.iter().cloned()));
+
+        buffer.truncate(1);
+        assert_eq!(buffer.len(), 1);
+        buffer.push_log(&logs[2]);
+        assert_eq!(buffer.take_logs(), [logs[0].clone(), logs[2].clone()]);
+        assert!(buffer.is_empty());
+        assert!(buffer.last().is_none());
+        assert_eq!(buffer.take_logs(), []);
+    }
+}
@@ -10,6 +10,7 @@ extern crate alloc as std;
 
 mod builder;
 mod context;
+mod diff;
 
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
@@ -22,27 +23,48 @@ mod inspector;
 mod journaled_state;
 #[cfg(feature = "optimism")]
 pub mod optimism;
+pub mod prelude;
+#[cfg(feature = "rpc-compat")]
+pub mod rpc_compat;
+mod simulation;
+mod spans;
+mod withdrawal;
 
 // Export items.
 
-pub use builder::EvmBuilder;
+pub use builder::{BuilderError, EvmBuilder};
 pub use context::{
-    Context, ContextPrecompile, ContextPrecompiles, ContextStatefulPrecompile,
+    CallStackExhaustion, Context, ContextPrecompile, ContextPrecompiles, ContextStatefulPrecompile,
     ContextStatefulPrecompileArc, ContextStatefulPrecompileBox, ContextStatefulPrecompileMut,
     ContextWithHandlerCfg, EvmContext, InnerEvmContext,
 };
+// Kept for compatibility, the canonical path of these is `revm::db`.
+#[doc(hidden)]
 pub use db::{
-    CacheState, DBBox, State, StateBuilder, StateDBBox, TransitionAccount, TransitionState,
+    CacheState, DBBox, InMemoryDB, State, StateBuilder, StateDBBox, TransitionAccount,
+    TransitionState,
+};
+pub use db::{Database, DatabaseCommit, DatabaseRef};
+pub use diff::{
+    AccountDiff, DiffExecutor, ExecutionDiff, FieldDiff, GasDiff, ResultDiff, ResultKind,
+};
+pub use evm::{Evm, ResumableOutcome, SuspendedTransaction, CALL_STACK_LIMIT};
+pub use frame::{
+    CallFrame, CreateFrame, EOFCreateFrame, Frame, FrameData, FrameOrResult, FrameResult,
 };
-pub use db::{Database, DatabaseCommit, DatabaseRef, InMemoryDB};
-pub use evm::{Evm, CALL_STACK_LIMIT};
-pub use frame::{CallFrame, CreateFrame, Frame, FrameData, FrameOrResult, FrameResult};
 pub use handler::Handler;
 pub use inspector::{inspector_handle_register, inspectors, GetInspector, Inspector};
-pub use journaled_state::{JournalCheckpoint, JournalEntry, JournaledState};
+pub use journaled_state::{
+    DbReads, JournalCheckpoint, JournalEntry, JournaledState, LogBuffer, LogRef,
+};
+pub use simulation::{BlockOverrides, SimulatedBlock, SimulationSession, DEFAULT_BLOCK_TIME};
+pub use withdrawal::apply_withdrawals;
 // export Optimism types, helpers, and constants
 #[cfg(feature = "optimism")]
-pub use optimism::{L1BlockInfo, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT};
+pub use optimism::{
+    L1BlockInfo, L1BlockInfoError, L1BlockLayout, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT,
+    L1_FEE_RECIPIENT,
+};
 
 // Reexport libraries
 
@@ -1,11 +1,22 @@
 //! Optimism-specific constants, types, and helpers.
 
+mod eip1559;
 mod fast_lz;
 mod handler_register;
+mod hardfork;
 mod l1block;
 
+pub use eip1559::{
+    decode_holocene_extra_data, eip1559_params, EIP1559_DENOMINATOR_BEDROCK,
+    EIP1559_DENOMINATOR_CANYON, EIP1559_ELASTICITY,
+};
 pub use handler_register::{
     deduct_caller, end, last_frame_return, load_accounts, load_precompiles,
-    optimism_handle_register, output, reward_beneficiary, validate_env, validate_tx_against_state,
+    optimism_handle_register, optimism_handle_register_with_spec, output, refund,
+    reward_beneficiary, validate_deposit_env, validate_env, validate_tx_against_state,
+};
+pub use hardfork::{OptimismChainSpec, OptimismHardfork};
+pub use l1block::{
+    L1BlockInfo, L1BlockInfoError, L1BlockLayout, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT,
+    L1_FEE_RECIPIENT,
 };
-pub use l1block::{L1BlockInfo, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT};
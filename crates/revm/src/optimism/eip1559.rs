@@ -0,0 +1,102 @@
+use crate::primitives::{Eip1559Params, SpecId};
+
+/// EIP-1559 base fee max change denominator of OP Stack chains before Canyon.
+pub const EIP1559_DENOMINATOR_BEDROCK: u64 = 50;
+
+/// EIP-1559 base fee max change denominator of OP Stack chains since Canyon.
+pub const EIP1559_DENOMINATOR_CANYON: u64 = 250;
+
+/// EIP-1559 elasticity multiplier of OP Stack chains.
+pub const EIP1559_ELASTICITY: u64 = 6;
+
+/// Version of the Holocene `extra_data` header field.
+const HOLOCENE_EXTRA_DATA_VERSION: u8 = 0;
+
+/// Returns the default OP Stack [`Eip1559Params`] for the given spec.
+pub fn eip1559_params(spec_id: SpecId) -> Eip1559Params {
+    Eip1559Params {
+        max_change_denominator: if spec_id.is_enabled_in(SpecId::CANYON) {
+            EIP1559_DENOMINATOR_CANYON
+        } else {
+            EIP1559_DENOMINATOR_BEDROCK
+        },
+        elasticity_multiplier: EIP1559_ELASTICITY,
+    }
+}
+
+/// Decodes [`Eip1559Params`] from the Holocene `extra_data` header field.
+///
+/// `extra_data` is a version byte followed by big-endian `u32` denominator and `u32`
+/// elasticity. If both values are zero the `default` parameters are returned.
+///
+/// Returns `None` if `extra_data` is malformed.
+pub fn decode_holocene_extra_data(
+    extra_data: &[u8],
+    default: Eip1559Params,
+) -> Option<Eip1559Params> {
+    let [HOLOCENE_EXTRA_DATA_VERSION, rest @ ..] = extra_data else {
+        return None;
+    };
+    let rest: &[u8; 8] = rest.try_into().ok()?;
+    let denominator = u32::from_be_bytes(rest[..4].try_into().unwrap()) as u64;
+    let elasticity = u32::from_be_bytes(rest[4..].try_into().unwrap()) as u64;
+    match (denominator, elasticity) {
+        (0, 0) => Some(default),
+        (0, _) | (_, 0) => None,
+        (max_change_denominator, elasticity_multiplier) => Some(Eip1559Params {
+            max_change_denominator,
+            elasticity_multiplier,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{BlockEnv, U256};
+
+    #[test]
+    fn mainnet_and_op_params_on_same_parent() {
+        let parent = BlockEnv {
+            gas_limit: U256::from(30_000_000),
+            basefee: U256::from(1_000_000_000),
+            ..Default::default()
+        };
+        let op = eip1559_params(SpecId::CANYON);
+        assert_eq!(
+            op,
+            Eip1559Params {
+                max_change_denominator: 250,
+                elasticity_multiplier: 6,
+            }
+        );
+        assert_eq!(
+            eip1559_params(SpecId::BEDROCK).max_change_denominator,
+            EIP1559_DENOMINATOR_BEDROCK
+        );
+
+        // 10M gas is below the mainnet target of 15M and above the OP target of 5M.
+        let mainnet_next = parent.next_base_fee(10_000_000, Eip1559Params::MAINNET);
+        let op_next = parent.next_base_fee(10_000_000, op);
+        assert_eq!(mainnet_next, U256::from(958_333_334));
+        assert_eq!(op_next, U256::from(1_004_000_000));
+    }
+
+    #[test]
+    fn holocene_extra_data() {
+        let default = eip1559_params(SpecId::CANYON);
+        let extra_data = [0, 0, 0, 0, 8, 0, 0, 0, 2];
+        assert_eq!(
+            decode_holocene_extra_data(&extra_data, default),
+            Some(Eip1559Params::MAINNET)
+        );
+        assert_eq!(decode_holocene_extra_data(&[0; 9], default), Some(default));
+        // wrong version, wrong length and only one zero value.
+        assert_eq!(decode_holocene_extra_data(&[1; 9], default), None);
+        assert_eq!(decode_holocene_extra_data(&[0; 8], default), None);
+        assert_eq!(
+            decode_holocene_extra_data(&[0, 0, 0, 0, 8, 0, 0, 0, 0], default),
+            None
+        );
+    }
+}
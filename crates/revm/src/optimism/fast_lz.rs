@@ -166,7 +166,9 @@ mod tests {
             .with_db(BenchmarkDB::new_bytecode(contract_bytecode.clone()))
             .modify_tx_env(|tx| {
                 tx.caller = address!("1000000000000000000000000000000000000000");
-                tx.transact_to = TxKind::Call(address!("0000000000000000000000000000000000000000"));
+                tx.transact_to = Some(TxKind::Call(address!(
+                    "0000000000000000000000000000000000000000"
+                )));
                 tx.data = FastLz::fastLzCall::new((input,)).abi_encode().into();
             })
             .build();
@@ -2,14 +2,14 @@
 
 use crate::{
     handler::{
-        mainnet::{self, deduct_caller_inner},
-        register::EvmHandler,
+        mainnet::{self, deduct_caller_journaled},
+        register::{register_custom_tx_handles, EvmHandler},
     },
     interpreter::{return_ok, return_revert, Gas, InstructionResult},
     optimism,
     primitives::{
-        db::Database, spec_to_generic, Account, EVMError, Env, ExecutionResult, HaltReason,
-        HashMap, InvalidTransaction, ResultAndState, Spec, SpecId, SpecId::REGOLITH, U256,
+        db::Database, spec_to_generic, EVMError, Env, ExecutionResult, HaltReason,
+        InvalidTransaction, ResultAndState, Spec, SpecId, SpecId::REGOLITH, TxType, U256,
     },
     Context, ContextPrecompiles, FrameResult,
 };
@@ -17,9 +17,18 @@ use core::ops::Mul;
 use revm_precompile::{secp256r1, PrecompileSpecId};
 use std::string::ToString;
 use std::sync::Arc;
+use std::vec::Vec;
 
 pub fn optimism_handle_register<DB: Database, EXT>(handler: &mut EvmHandler<'_, EXT, DB>) {
-    spec_to_generic!(handler.cfg.spec_id, {
+    optimism_handle_register_with_spec(handler, handler.cfg.spec_id)
+}
+
+/// Registers the Optimism handlers of the given spec, independently of the spec of the handler.
+pub fn optimism_handle_register_with_spec<DB: Database, EXT>(
+    handler: &mut EvmHandler<'_, EXT, DB>,
+    spec_id: SpecId,
+) {
+    spec_to_generic!(spec_id, {
         // validate environment
         handler.validation.env = Arc::new(validate_env::<SPEC, DB>);
         // Validate transaction against state.
@@ -30,31 +39,45 @@ pub fn optimism_handle_register<DB: Database, EXT>(handler: &mut EvmHandler<'_,
         handler.pre_execution.load_accounts = Arc::new(load_accounts::<SPEC, EXT, DB>);
         // An estimated batch cost is charged from the caller and added to L1 Fee Vault.
         handler.pre_execution.deduct_caller = Arc::new(deduct_caller::<SPEC, EXT, DB>);
-        // Refund is calculated differently then mainnet.
+        // Gas is reported and refunded differently then mainnet.
         handler.execution.last_frame_return = Arc::new(last_frame_return::<SPEC, EXT, DB>);
+        handler.post_execution.refund = Arc::new(refund::<SPEC, EXT, DB>);
         handler.post_execution.reward_beneficiary = Arc::new(reward_beneficiary::<SPEC, EXT, DB>);
         // In case of halt of deposit transaction return Error.
         handler.post_execution.output = Arc::new(output::<SPEC, EXT, DB>);
         handler.post_execution.end = Arc::new(end::<SPEC, EXT, DB>);
     });
+
+    // Deposit transactions are pre-verified on L1, they have their own env validation.
+    register_custom_tx_handles(
+        handler,
+        |env: &Env| env.tx().optimism.source_hash.is_some(),
+        Some(Arc::new(validate_deposit_env::<DB>)),
+        None,
+        None,
+    );
 }
 
-/// Validate environment for the Optimism chain.
+/// Validate environment of the Optimism transactions that are not deposits.
 pub fn validate_env<SPEC: Spec, DB: Database>(env: &Env) -> Result<(), EVMError<DB::Error>> {
-    // Do not perform any extra validation for deposit transactions, they are pre-verified on L1.
-    if env.tx.optimism.source_hash.is_some() {
-        return Ok(());
-    }
     // Important: validate block before tx.
     env.validate_block_env::<SPEC>()?;
 
     // Do not allow for a system transaction to be processed if Regolith is enabled.
-    let tx = &env.tx.optimism;
+    let tx = &env.tx().optimism;
     if tx.is_system_transaction.unwrap_or(false) && SPEC::enabled(SpecId::REGOLITH) {
         return Err(InvalidTransaction::DepositSystemTxPostRegolith.into());
     }
 
     env.validate_tx::<SPEC>()?;
+    mainnet::validate_initcodes::<SPEC>(env)?;
+    Ok(())
+}
+
+/// Validate environment of the deposit transactions.
+///
+/// Deposits are pre-verified on L1 and mint unconditionally, nothing is checked.
+pub fn validate_deposit_env<DB: Database>(_env: &Env) -> Result<(), EVMError<DB::Error>> {
     Ok(())
 }
 
@@ -62,7 +85,7 @@ pub fn validate_env<SPEC: Spec, DB: Database>(env: &Env) -> Result<(), EVMError<
 pub fn validate_tx_against_state<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
 ) -> Result<(), EVMError<DB::Error>> {
-    if context.evm.inner.env.tx.optimism.source_hash.is_some() {
+    if context.evm.inner.env.tx().optimism.source_hash.is_some() {
         return Ok(());
     }
     mainnet::validate_tx_against_state::<SPEC, EXT, DB>(context)
@@ -75,9 +98,9 @@ pub fn last_frame_return<SPEC: Spec, EXT, DB: Database>(
     frame_result: &mut FrameResult,
 ) -> Result<(), EVMError<DB::Error>> {
     let env = context.evm.inner.env();
-    let is_deposit = env.tx.optimism.source_hash.is_some();
-    let tx_system = env.tx.optimism.is_system_transaction;
-    let tx_gas_limit = env.tx.gas_limit;
+    let is_deposit = env.tx().optimism.source_hash.is_some();
+    let tx_system = env.tx().optimism.is_system_transaction;
+    let tx_gas_limit = env.tx().gas_limit;
     let is_regolith = SPEC::enabled(REGOLITH);
 
     let instruction_result = frame_result.interpreter_result().result;
@@ -132,10 +155,22 @@ pub fn last_frame_return<SPEC: Spec, EXT, DB: Database>(
         }
         _ => {}
     }
+    Ok(())
+}
+
+/// Finalizes the gas refund.
+#[inline]
+pub fn refund<SPEC: Spec, EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+    gas: &mut Gas,
+) -> Result<(), EVMError<DB::Error>> {
+    let env = context.evm.inner.env();
+    let is_deposit = env.tx().optimism.source_hash.is_some();
     // Prior to Regolith, deposit transactions did not receive gas refunds.
-    let is_gas_refund_disabled = env.cfg.is_gas_refund_disabled() || (is_deposit && !is_regolith);
+    let is_gas_refund_disabled =
+        env.cfg.is_gas_refund_disabled() || (is_deposit && !SPEC::enabled(REGOLITH));
     if !is_gas_refund_disabled {
-        gas.set_final_refund(SPEC::SPEC_ID.is_enabled_in(SpecId::LONDON));
+        gas.finalize_refund(SPEC::SPEC_ID);
     }
     Ok(())
 }
@@ -161,11 +196,12 @@ pub fn load_accounts<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
 ) -> Result<(), EVMError<DB::Error>> {
     // the L1-cost fee is only computed for Optimism non-deposit transactions.
-
-    if context.evm.inner.env.tx.optimism.source_hash.is_none() {
+    context.evm.inner.tx_l1_cost = None;
+    if context.evm.inner.env.tx().optimism.source_hash.is_none()
+        && !context.evm.inner.env.cfg.optimism_no_l1_fee
+    {
         let l1_block_info =
-            crate::optimism::L1BlockInfo::try_fetch(&mut context.evm.inner.db, SPEC::SPEC_ID)
-                .map_err(EVMError::Database)?;
+            crate::optimism::L1BlockInfo::try_fetch(&mut context.evm.inner.db, SPEC::SPEC_ID)?;
 
         // storage l1 block info for later use.
         context.evm.inner.l1_block_info = Some(l1_block_info);
@@ -179,50 +215,67 @@ pub fn load_accounts<SPEC: Spec, EXT, DB: Database>(
 pub fn deduct_caller<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
 ) -> Result<(), EVMError<DB::Error>> {
+    let caller = context.evm.inner.env.tx().caller;
     // load caller's account.
-    let (caller_account, _) = context
+    context
         .evm
         .inner
         .journaled_state
-        .load_account(context.evm.inner.env.tx.caller, &mut context.evm.inner.db)?;
+        .load_account(caller, &mut context.evm.inner.db)?;
 
-    // If the transaction is a deposit with a `mint` value, add the mint value
-    // in wei to the caller's balance. This should be persisted to the database
+    // If the transaction has a `mint` value, add the mint value in wei to the
+    // caller's balance. For deposits this should be persisted to the database
     // prior to the rest of execution.
-    if let Some(mint) = context.evm.inner.env.tx.optimism.mint {
-        caller_account.info.balance += U256::from(mint);
+    if let Some(mint) = context.evm.inner.env.tx().mint {
+        mainnet::mint_to_caller(&mut context.evm.inner.journaled_state, caller, mint)?;
+    }
+
+    // Deposits skip validation, so a nonce bump that would overflow is only caught here
+    // and reported as a failed deposit.
+    if context.evm.inner.env.tx().optimism.source_hash.is_some()
+        && context.evm.inner.env.tx().is_call()
+        && context.evm.inner.journaled_state.account(caller).info.nonce == u64::MAX
+    {
+        return Err(InvalidTransaction::NonceOverflowInTransaction.into());
     }
 
     // We deduct caller max balance after minting and before deducing the
     // l1 cost, max values is already checked in pre_validate but l1 cost wasn't.
-    deduct_caller_inner::<SPEC>(caller_account, &context.evm.inner.env);
+    deduct_caller_journaled::<SPEC>(
+        &mut context.evm.inner.journaled_state,
+        &context.evm.inner.env,
+    )?;
 
     // If the transaction is not a deposit transaction, subtract the L1 data fee from the
     // caller's balance directly after minting the requested amount of ETH.
-    if context.evm.inner.env.tx.optimism.source_hash.is_none() {
+    if context.evm.inner.env.tx().optimism.source_hash.is_none()
+        && !context.evm.inner.env.cfg.optimism_no_l1_fee
+    {
         // get envelope
-        let Some(enveloped_tx) = &context.evm.inner.env.tx.optimism.enveloped_tx else {
+        let Some(enveloped_tx) = &context.evm.inner.env.tx().optimism.enveloped_tx else {
             return Err(EVMError::Custom(
                 "[OPTIMISM] Failed to load enveloped transaction.".to_string(),
             ));
         };
 
-        let tx_l1_cost = context
-            .evm
-            .inner
-            .l1_block_info
-            .as_ref()
-            .expect("L1BlockInfo should be loaded")
-            .calculate_tx_l1_cost(enveloped_tx, SPEC::SPEC_ID);
-        if tx_l1_cost.gt(&caller_account.info.balance) {
+        // L1 block info is loaded in `load_accounts`, a custom handler may skip it.
+        let Some(l1_block_info) = &context.evm.inner.l1_block_info else {
+            return Err(EVMError::Custom(
+                "[OPTIMISM] Failed to load L1 block information.".to_string(),
+            ));
+        };
+
+        let tx_l1_cost = l1_block_info.calculate_tx_l1_cost(enveloped_tx, SPEC::SPEC_ID);
+        let journaled_state = &mut context.evm.inner.journaled_state;
+        if journaled_state.balance_decr(caller, tx_l1_cost).is_err() {
             return Err(EVMError::Transaction(
                 InvalidTransaction::LackOfFundForMaxFee {
                     fee: tx_l1_cost.into(),
-                    balance: caller_account.info.balance.into(),
+                    balance: journaled_state.account(caller).info.balance.into(),
                 },
             ));
         }
-        caller_account.info.balance = caller_account.info.balance.saturating_sub(tx_l1_cost);
+        context.evm.inner.tx_l1_cost = Some(tx_l1_cost);
     }
     Ok(())
 }
@@ -233,7 +286,7 @@ pub fn reward_beneficiary<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
     gas: &Gas,
 ) -> Result<(), EVMError<DB::Error>> {
-    let is_deposit = context.evm.inner.env.tx.optimism.source_hash.is_some();
+    let is_deposit = context.evm.inner.env.tx().optimism.source_hash.is_some();
 
     // transfer fee to coinbase/beneficiary.
     if !is_deposit {
@@ -243,43 +296,54 @@ pub fn reward_beneficiary<SPEC: Spec, EXT, DB: Database>(
     if !is_deposit {
         // If the transaction is not a deposit transaction, fees are paid out
         // to both the Base Fee Vault as well as the L1 Fee Vault.
-        let Some(l1_block_info) = &context.evm.inner.l1_block_info else {
-            return Err(EVMError::Custom(
-                "[OPTIMISM] Failed to load L1 block information.".to_string(),
-            ));
-        };
+        if !context.evm.inner.env.cfg.optimism_no_l1_fee {
+            let Some(l1_block_info) = &context.evm.inner.l1_block_info else {
+                return Err(EVMError::Custom(
+                    "[OPTIMISM] Failed to load L1 block information.".to_string(),
+                ));
+            };
 
-        let Some(enveloped_tx) = &context.evm.inner.env.tx.optimism.enveloped_tx else {
-            return Err(EVMError::Custom(
-                "[OPTIMISM] Failed to load enveloped transaction.".to_string(),
-            ));
-        };
+            let Some(enveloped_tx) = &context.evm.inner.env.tx().optimism.enveloped_tx else {
+                return Err(EVMError::Custom(
+                    "[OPTIMISM] Failed to load enveloped transaction.".to_string(),
+                ));
+            };
 
-        let l1_cost = l1_block_info.calculate_tx_l1_cost(enveloped_tx, SPEC::SPEC_ID);
+            let l1_cost = l1_block_info.calculate_tx_l1_cost(enveloped_tx, SPEC::SPEC_ID);
 
-        // Send the L1 cost of the transaction to the L1 Fee Vault.
-        let (l1_fee_vault_account, _) = context
-            .evm
-            .inner
-            .journaled_state
-            .load_account(optimism::L1_FEE_RECIPIENT, &mut context.evm.inner.db)?;
-        l1_fee_vault_account.mark_touch();
-        l1_fee_vault_account.info.balance += l1_cost;
+            // Send the L1 cost of the transaction to the L1 Fee Vault.
+            context
+                .evm
+                .inner
+                .journaled_state
+                .load_account(optimism::L1_FEE_RECIPIENT, &mut context.evm.inner.db)?;
+            context
+                .evm
+                .inner
+                .journaled_state
+                .balance_incr(optimism::L1_FEE_RECIPIENT, l1_cost)
+                .map_err(|_| InvalidTransaction::OverflowPaymentInTransaction)?;
+        }
 
         // Send the base fee of the transaction to the Base Fee Vault.
-        let (base_fee_vault_account, _) = context
-            .evm
-            .inner
-            .journaled_state
-            .load_account(optimism::BASE_FEE_RECIPIENT, &mut context.evm.inner.db)?;
-        base_fee_vault_account.mark_touch();
-        base_fee_vault_account.info.balance += context
+        let base_fee = context
             .evm
             .inner
             .env
             .block
             .basefee
             .mul(U256::from(gas.spent() - gas.refunded() as u64));
+        context
+            .evm
+            .inner
+            .journaled_state
+            .load_account(optimism::BASE_FEE_RECIPIENT, &mut context.evm.inner.db)?;
+        context
+            .evm
+            .inner
+            .journaled_state
+            .balance_incr(optimism::BASE_FEE_RECIPIENT, base_fee)
+            .map_err(|_| InvalidTransaction::OverflowPaymentInTransaction)?;
     }
     Ok(())
 }
@@ -290,13 +354,14 @@ pub fn output<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
     frame_result: FrameResult,
 ) -> Result<ResultAndState, EVMError<DB::Error>> {
-    let result = mainnet::output::<EXT, DB>(context, frame_result)?;
+    let mut result = mainnet::output::<EXT, DB>(context, frame_result)?;
+    result.l1_fee = context.evm.inner.tx_l1_cost;
 
     if result.result.is_halt() {
         // Post-regolith, if the transaction is a deposit transaction and it halts,
         // we bubble up to the global return handler. The mint value will be persisted
         // and the caller nonce will be incremented there.
-        let is_deposit = context.evm.inner.env.tx.optimism.source_hash.is_some();
+        let is_deposit = context.evm.inner.env.tx().optimism.source_hash.is_some();
         if is_deposit && SPEC::enabled(REGOLITH) {
             return Err(EVMError::Transaction(
                 InvalidTransaction::HaltedDepositPostRegolith,
@@ -314,7 +379,7 @@ pub fn end<SPEC: Spec, EXT, DB: Database>(
 ) -> Result<ResultAndState, EVMError<DB::Error>> {
     evm_output.or_else(|err| {
         if matches!(err, EVMError::Transaction(_))
-            && context.evm.inner.env().tx.optimism.source_hash.is_some()
+            && context.evm.inner.env().tx().optimism.source_hash.is_some()
         {
             // If the transaction is a deposit transaction and it failed
             // for any reason, the caller nonce must be bumped, and the
@@ -322,27 +387,21 @@ pub fn end<SPEC: Spec, EXT, DB: Database>(
             // also returned as a special Halt variant so that consumers can more
             // easily distinguish between a failed deposit and a failed
             // normal transaction.
-            let caller = context.evm.inner.env().tx.caller;
-
-            // Increment sender nonce and account balance for the mint amount. Deposits
-            // always persist the mint amount, even if the transaction fails.
-            let account = {
-                let mut acc = Account::from(
-                    context
-                        .evm
-                        .db
-                        .basic(caller)
-                        .unwrap_or_default()
-                        .unwrap_or_default(),
-                );
-                acc.info.nonce = acc.info.nonce.saturating_add(1);
-                acc.info.balance = acc.info.balance.saturating_add(U256::from(
-                    context.evm.inner.env().tx.optimism.mint.unwrap_or(0),
-                ));
-                acc.mark_touch();
-                acc
-            };
-            let state = HashMap::from([(caller, account)]);
+            let caller = context.evm.inner.env().tx().caller;
+
+            // Discard the changes of the failed deposit, then increment sender nonce and
+            // account balance for the mint amount. Deposits always persist the mint amount,
+            // even if the transaction fails.
+            let journaled_state = &mut context.evm.inner.journaled_state;
+            journaled_state.finalize();
+            journaled_state.load_account(caller, &mut context.evm.inner.db)?;
+            // The nonce saturates, a failed deposit can't fail again.
+            let _ = journaled_state.inc_nonce(caller);
+            journaled_state.touch(&caller);
+            if let Some(mint) = context.evm.inner.env.tx().mint {
+                mainnet::mint_to_caller(journaled_state, caller, mint)?;
+            }
+            let (state, _) = journaled_state.finalize();
 
             // The gas used of a failed deposit post-regolith is the gas
             // limit of the transaction. pre-regolith, it is the gas limit
@@ -351,12 +410,12 @@ pub fn end<SPEC: Spec, EXT, DB: Database>(
             let is_system_tx = context
                 .evm
                 .env()
-                .tx
+                .tx()
                 .optimism
                 .is_system_transaction
                 .unwrap_or(false);
             let gas_used = if SPEC::enabled(REGOLITH) || !is_system_tx {
-                context.evm.inner.env().tx.gas_limit
+                context.evm.inner.env().tx().gas_limit
             } else {
                 0
             };
@@ -367,6 +426,10 @@ pub fn end<SPEC: Spec, EXT, DB: Database>(
                     gas_used,
                 },
                 state,
+                tx_type: TxType::Deposit,
+                selfdestructs: Vec::new(),
+                profile: None,
+                l1_fee: None,
             })
         } else {
             Err(err)
@@ -382,8 +445,7 @@ mod tests {
     use crate::{
         db::{EmptyDB, InMemoryDB},
         primitives::{
-            bytes, state::AccountInfo, Address, BedrockSpec, Bytes, Env, LatestSpec, RegolithSpec,
-            B256,
+            bytes, state::AccountInfo, Address, BedrockSpec, Bytes, Env, RegolithSpec, TxKind, B256,
         },
         L1BlockInfo,
     };
@@ -405,14 +467,15 @@ mod tests {
             0..0,
         ));
         last_frame_return::<SPEC, _, _>(&mut ctx, &mut first_frame).unwrap();
+        refund::<SPEC, _, _>(&mut ctx, first_frame.gas_mut()).unwrap();
         *first_frame.gas()
     }
 
     #[test]
     fn test_revert_gas() {
         let mut env = Env::default();
-        env.tx.gas_limit = 100;
-        env.tx.optimism.source_hash = None;
+        env.tx_mut().gas_limit = 100;
+        env.tx_mut().optimism.source_hash = None;
 
         let gas =
             call_last_frame_return::<BedrockSpec>(env, InstructionResult::Revert, Gas::new(90));
@@ -424,8 +487,8 @@ mod tests {
     #[test]
     fn test_consume_gas() {
         let mut env = Env::default();
-        env.tx.gas_limit = 100;
-        env.tx.optimism.source_hash = Some(B256::ZERO);
+        env.tx_mut().gas_limit = 100;
+        env.tx_mut().optimism.source_hash = Some(B256::ZERO);
 
         let gas =
             call_last_frame_return::<RegolithSpec>(env, InstructionResult::Stop, Gas::new(90));
@@ -437,8 +500,8 @@ mod tests {
     #[test]
     fn test_consume_gas_with_refund() {
         let mut env = Env::default();
-        env.tx.gas_limit = 100;
-        env.tx.optimism.source_hash = Some(B256::ZERO);
+        env.tx_mut().gas_limit = 100;
+        env.tx_mut().optimism.source_hash = Some(B256::ZERO);
 
         let mut ret_gas = Gas::new(90);
         ret_gas.record_refund(20);
@@ -458,8 +521,8 @@ mod tests {
     #[test]
     fn test_consume_gas_sys_deposit_tx() {
         let mut env = Env::default();
-        env.tx.gas_limit = 100;
-        env.tx.optimism.source_hash = Some(B256::ZERO);
+        env.tx_mut().gas_limit = 100;
+        env.tx_mut().optimism.source_hash = Some(B256::ZERO);
 
         let gas = call_last_frame_return::<BedrockSpec>(env, InstructionResult::Stop, Gas::new(90));
         assert_eq!(gas.remaining(), 0);
@@ -486,9 +549,9 @@ mod tests {
             ..Default::default()
         });
         // Enveloped needs to be some but it will deduce zero fee.
-        context.evm.inner.env.tx.optimism.enveloped_tx = Some(bytes!(""));
+        context.evm.inner.env.tx_mut().optimism.enveloped_tx = Some(bytes!(""));
         // added mint value is 10.
-        context.evm.inner.env.tx.optimism.mint = Some(10);
+        context.evm.inner.env.tx_mut().mint = Some(10);
 
         deduct_caller::<RegolithSpec, (), _>(&mut context).unwrap();
 
@@ -502,6 +565,43 @@ mod tests {
         assert_eq!(account.info.balance, U256::from(1010));
     }
 
+    #[test]
+    fn test_failed_deposit_persists_mint() {
+        let caller = Address::ZERO;
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                nonce: 1,
+                balance: U256::from(1000),
+                ..Default::default()
+            },
+        );
+        let mut context: Context<(), InMemoryDB> = Context::new_with_db(db);
+        context.evm.inner.env.tx_mut().optimism.source_hash = Some(B256::ZERO);
+        context.evm.inner.env.tx_mut().mint = Some(10);
+
+        let output = end::<RegolithSpec, (), InMemoryDB>(
+            &mut context,
+            Err(EVMError::Transaction(
+                InvalidTransaction::NonceOverflowInTransaction,
+            )),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            output.result,
+            ExecutionResult::Halt {
+                reason: HaltReason::FailedDeposit,
+                ..
+            }
+        ));
+        let account = &output.state[&caller];
+        assert!(account.is_touched());
+        assert_eq!(account.info.nonce, 2);
+        assert_eq!(account.info.balance, U256::from(1010));
+    }
+
     #[test]
     fn test_remove_l1_cost_non_deposit() {
         let caller = Address::ZERO;
@@ -521,12 +621,12 @@ mod tests {
             ..Default::default()
         });
         // l1block cost is 1048 fee.
-        context.evm.inner.env.tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
+        context.evm.inner.env.tx_mut().optimism.enveloped_tx = Some(bytes!("FACADE"));
         // added mint value is 10.
-        context.evm.inner.env.tx.optimism.mint = Some(10);
+        context.evm.inner.env.tx_mut().mint = Some(10);
         // Putting source_hash to some makes it a deposit transaction.
         // so enveloped_tx gas cost is ignored.
-        context.evm.inner.env.tx.optimism.source_hash = Some(B256::ZERO);
+        context.evm.inner.env.tx_mut().optimism.source_hash = Some(B256::ZERO);
 
         deduct_caller::<RegolithSpec, (), _>(&mut context).unwrap();
 
@@ -540,6 +640,28 @@ mod tests {
         assert_eq!(account.info.balance, U256::from(1010));
     }
 
+    #[test]
+    fn test_deposit_nonce_overflow() {
+        let caller = Address::ZERO;
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                nonce: u64::MAX,
+                ..Default::default()
+            },
+        );
+        let mut context: Context<(), InMemoryDB> = Context::new_with_db(db);
+        context.evm.inner.env.tx_mut().optimism.source_hash = Some(B256::ZERO);
+
+        assert_eq!(
+            deduct_caller::<RegolithSpec, (), _>(&mut context),
+            Err(EVMError::Transaction(
+                InvalidTransaction::NonceOverflowInTransaction
+            ))
+        );
+    }
+
     #[test]
     fn test_remove_l1_cost() {
         let caller = Address::ZERO;
@@ -559,7 +681,7 @@ mod tests {
             ..Default::default()
         });
         // l1block cost is 1048 fee.
-        context.evm.inner.env.tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
+        context.evm.inner.env.tx_mut().optimism.enveloped_tx = Some(bytes!("FACADE"));
         deduct_caller::<RegolithSpec, (), _>(&mut context).unwrap();
 
         // Check the account balance is updated.
@@ -591,7 +713,7 @@ mod tests {
             ..Default::default()
         });
         // l1block cost is 1048 fee.
-        context.evm.inner.env.tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
+        context.evm.inner.env.tx_mut().optimism.enveloped_tx = Some(bytes!("FACADE"));
 
         assert_eq!(
             deduct_caller::<RegolithSpec, (), _>(&mut context),
@@ -608,7 +730,7 @@ mod tests {
     fn test_validate_sys_tx() {
         // mark the tx as a system transaction.
         let mut env = Env::default();
-        env.tx.optimism.is_system_transaction = Some(true);
+        env.tx_mut().optimism.is_system_transaction = Some(true);
         assert_eq!(
             validate_env::<RegolithSpec, EmptyDB>(&env),
             Err(EVMError::Transaction(
@@ -622,19 +744,147 @@ mod tests {
 
     #[test]
     fn test_validate_deposit_tx() {
+        let handler = EvmHandler::<(), EmptyDB>::optimism_with_spec(SpecId::REGOLITH);
         // Set source hash.
         let mut env = Env::default();
-        env.tx.optimism.source_hash = Some(B256::ZERO);
-        assert!(validate_env::<RegolithSpec, EmptyDB>(&env).is_ok());
+        env.tx_mut().optimism.source_hash = Some(B256::ZERO);
+        assert!(handler.validation().env(&env).is_ok());
+
+        // deposits mint without `allow_mint`.
+        env.tx_mut().mint = Some(1);
+        assert!(handler.validation().env(&env).is_ok());
     }
 
     #[test]
     fn test_validate_tx_against_state_deposit_tx() {
+        let handler = EvmHandler::<(), EmptyDB>::optimism_with_spec(SpecId::LATEST);
         // Set source hash.
         let mut env = Env::default();
-        env.tx.optimism.source_hash = Some(B256::ZERO);
+        env.tx_mut().optimism.source_hash = Some(B256::ZERO);
 
         // Nonce and balance checks should be skipped for deposit transactions.
-        assert!(validate_env::<LatestSpec, EmptyDB>(&env).is_ok());
+        assert!(handler.validation().env(&env).is_ok());
+    }
+
+    #[test]
+    fn test_deposit_skips_env_validation() {
+        let handler = EvmHandler::<(), EmptyDB>::optimism_with_spec(SpecId::REGOLITH);
+        // system transactions are not allowed after Regolith, but deposits are not checked.
+        let mut env = Env::default();
+        env.tx_mut().optimism.is_system_transaction = Some(true);
+        assert!(handler.validation().env(&env).is_err());
+
+        env.tx_mut().optimism.source_hash = Some(B256::ZERO);
+        assert!(handler.validation().env(&env).is_ok());
+    }
+
+    #[test]
+    fn test_no_l1_fee_without_l1_block_info() {
+        let caller = Address::with_last_byte(1);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+        let mut evm = crate::Evm::builder()
+            .with_db(db)
+            .optimism()
+            .with_spec_id(SpecId::ECOTONE)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = Some(TxKind::Call(Address::with_last_byte(0xff)));
+                tx.gas_limit = 21_000;
+                // not a deposit and without enveloped transaction.
+                tx.optimism.source_hash = None;
+                tx.optimism.enveloped_tx = None;
+            })
+            .build();
+
+        assert!(matches!(evm.transact(), Err(EVMError::Custom(_))));
+
+        evm.cfg_mut().optimism_no_l1_fee = true;
+        let output = evm.transact().unwrap();
+        assert!(output.result.is_success());
+        assert_eq!(evm.context.evm.inner.tx_l1_cost, None);
+        assert_eq!(output.l1_fee, None);
+    }
+
+    #[test]
+    fn test_l1_fee_in_result() {
+        let caller = Address::with_last_byte(1);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(10_000_000)));
+        // Bedrock L1 base fee, fee overhead and fee scalar slots.
+        for (slot, value) in [(1, 1_000), (5, 1_000), (6, 1_000_000)] {
+            db.insert_account_storage(
+                optimism::L1_BLOCK_CONTRACT,
+                U256::from(slot),
+                U256::from(value),
+            )
+            .unwrap();
+        }
+        let mut evm = crate::Evm::builder()
+            .with_db(db)
+            .optimism()
+            .with_spec_id(SpecId::REGOLITH)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = Some(TxKind::Call(Address::with_last_byte(0xff)));
+                tx.gas_limit = 21_000;
+                tx.optimism.source_hash = None;
+                tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
+            })
+            .build();
+        let expected = L1BlockInfo::try_fetch(evm.db_mut(), SpecId::REGOLITH)
+            .unwrap()
+            .calculate_tx_l1_cost(&bytes!("FACADE"), SpecId::REGOLITH);
+        assert!(expected > U256::ZERO);
+
+        let output = evm.transact().unwrap();
+        assert!(output.result.is_success());
+        assert_eq!(output.l1_fee, Some(expected));
+        assert_eq!(
+            output.state[&caller].info.balance,
+            U256::from(10_000_000) - expected
+        );
+    }
+
+    #[test]
+    fn test_calldata_gas_floor() {
+        let caller = Address::with_last_byte(1);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+        let mut evm = crate::Evm::builder()
+            .with_db(db)
+            .optimism()
+            .with_spec_id(SpecId::PRAGUE)
+            .modify_cfg_env(|cfg| cfg.optimism_no_l1_fee = true)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = Some(TxKind::Call(Address::with_last_byte(0xff)));
+                // intrinsic gas is 37_000, the floor is 61_000.
+                tx.data = vec![0x01; 1000].into();
+                tx.gas_limit = 100_000;
+                tx.gas_price = U256::from(1);
+                tx.optimism.source_hash = None;
+                tx.optimism.enveloped_tx = None;
+            })
+            .build();
+
+        let ResultAndState { result, state, .. } = evm.transact().unwrap();
+        assert_eq!(result.gas_used(), 61_000);
+        assert_eq!(state[&caller].info.balance, U256::from(1_000_000 - 61_000));
+    }
+
+    #[test]
+    fn test_deduct_caller_without_l1_block_info() {
+        let caller = Address::ZERO;
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1000)));
+        let mut context: Context<(), InMemoryDB> = Context::new_with_db(db);
+        context.evm.inner.env.tx_mut().optimism.enveloped_tx = Some(bytes!("FACADE"));
+
+        // `load_accounts` was not called so L1 block info is missing.
+        assert!(matches!(
+            deduct_caller::<RegolithSpec, (), _>(&mut context),
+            Err(EVMError::Custom(_))
+        ));
     }
 }
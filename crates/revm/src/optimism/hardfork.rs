@@ -0,0 +1,161 @@
+use crate::{
+    handler::{register::EvmHandler, ChainSpec},
+    optimism::optimism_handle_register_with_spec,
+    primitives::{db::Database, SpecId},
+};
+
+/// Optimism hardforks interleaved with the Ethereum hardforks, ordered by activation.
+///
+/// Every hardfork maps to the [`SpecId`] of the same name, which selects the base EVM behavior.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OptimismHardfork {
+    Frontier,
+    FrontierThawing,
+    Homestead,
+    DaoFork,
+    Tangerine,
+    SpuriousDragon,
+    Byzantium,
+    Constantinople,
+    Petersburg,
+    Istanbul,
+    MuirGlacier,
+    Berlin,
+    London,
+    ArrowGlacier,
+    GrayGlacier,
+    Merge,
+    Bedrock,
+    Regolith,
+    Shanghai,
+    Canyon,
+    Cancun,
+    Ecotone,
+    Fjord,
+    Prague,
+    PragueEof,
+    #[default]
+    Latest,
+}
+
+impl From<OptimismHardfork> for SpecId {
+    fn from(hardfork: OptimismHardfork) -> Self {
+        match hardfork {
+            OptimismHardfork::Frontier => SpecId::FRONTIER,
+            OptimismHardfork::FrontierThawing => SpecId::FRONTIER_THAWING,
+            OptimismHardfork::Homestead => SpecId::HOMESTEAD,
+            OptimismHardfork::DaoFork => SpecId::DAO_FORK,
+            OptimismHardfork::Tangerine => SpecId::TANGERINE,
+            OptimismHardfork::SpuriousDragon => SpecId::SPURIOUS_DRAGON,
+            OptimismHardfork::Byzantium => SpecId::BYZANTIUM,
+            OptimismHardfork::Constantinople => SpecId::CONSTANTINOPLE,
+            OptimismHardfork::Petersburg => SpecId::PETERSBURG,
+            OptimismHardfork::Istanbul => SpecId::ISTANBUL,
+            OptimismHardfork::MuirGlacier => SpecId::MUIR_GLACIER,
+            OptimismHardfork::Berlin => SpecId::BERLIN,
+            OptimismHardfork::London => SpecId::LONDON,
+            OptimismHardfork::ArrowGlacier => SpecId::ARROW_GLACIER,
+            OptimismHardfork::GrayGlacier => SpecId::GRAY_GLACIER,
+            OptimismHardfork::Merge => SpecId::MERGE,
+            OptimismHardfork::Bedrock => SpecId::BEDROCK,
+            OptimismHardfork::Regolith => SpecId::REGOLITH,
+            OptimismHardfork::Shanghai => SpecId::SHANGHAI,
+            OptimismHardfork::Canyon => SpecId::CANYON,
+            OptimismHardfork::Cancun => SpecId::CANCUN,
+            OptimismHardfork::Ecotone => SpecId::ECOTONE,
+            OptimismHardfork::Fjord => SpecId::FJORD,
+            OptimismHardfork::Prague => SpecId::PRAGUE,
+            OptimismHardfork::PragueEof => SpecId::PRAGUE_EOF,
+            OptimismHardfork::Latest => SpecId::LATEST,
+        }
+    }
+}
+
+/// Optimism chain, registers the Optimism handlers of the hardfork on top of the mainnet ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OptimismChainSpec;
+
+impl ChainSpec for OptimismChainSpec {
+    type Hardfork = OptimismHardfork;
+
+    fn register_handler<EXT, DB: Database>(
+        handler: &mut EvmHandler<'_, EXT, DB>,
+        hardfork: OptimismHardfork,
+    ) {
+        // Overrides the handlers registered for the spec id of the handler if the builder
+        // started from the optimism handler.
+        handler.cfg.is_optimism = true;
+        optimism_handle_register_with_spec(handler, hardfork.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        primitives::{EVMError, Env, InvalidTransaction},
+        Evm,
+    };
+
+    #[test]
+    fn hardfork_order_matches_spec_id() {
+        let hardforks = [
+            OptimismHardfork::Merge,
+            OptimismHardfork::Bedrock,
+            OptimismHardfork::Regolith,
+            OptimismHardfork::Shanghai,
+            OptimismHardfork::Canyon,
+            OptimismHardfork::Cancun,
+            OptimismHardfork::Ecotone,
+            OptimismHardfork::Fjord,
+            OptimismHardfork::Prague,
+            OptimismHardfork::Latest,
+        ];
+        for pair in hardforks.windows(2) {
+            assert!(pair[0] < pair[1]);
+            assert!(SpecId::from(pair[0]) < SpecId::from(pair[1]));
+        }
+    }
+
+    #[test]
+    fn builds_optimism_handler() {
+        let evm = Evm::builder()
+            .with_chain_spec::<OptimismChainSpec>(OptimismHardfork::Ecotone)
+            .build();
+        assert_eq!(evm.spec_id(), SpecId::ECOTONE);
+        assert!(evm.handler.cfg.is_optimism);
+    }
+
+    #[test]
+    fn handlers_follow_hardfork() {
+        let mut env = Env::default();
+        env.tx_mut().optimism.is_system_transaction = Some(true);
+        let validate = |hardfork, spec_id| {
+            Evm::builder()
+                .with_chain_spec::<OptimismChainSpec>(hardfork)
+                .with_spec_id(spec_id)
+                .build()
+                .handler
+                .validation()
+                .env(&env)
+        };
+        let post_regolith = Err(EVMError::Transaction(
+            InvalidTransaction::DepositSystemTxPostRegolith,
+        ));
+
+        assert_eq!(
+            validate(OptimismHardfork::Regolith, SpecId::REGOLITH),
+            post_regolith
+        );
+        // the spec id changes only the base EVM, the Optimism handlers keep the hardfork.
+        assert_ne!(
+            validate(OptimismHardfork::Bedrock, SpecId::REGOLITH),
+            post_regolith
+        );
+        assert_eq!(
+            validate(OptimismHardfork::Regolith, SpecId::BEDROCK),
+            post_regolith
+        );
+    }
+}
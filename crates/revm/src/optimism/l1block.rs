@@ -1,6 +1,8 @@
 use crate::optimism::fast_lz::flz_compress_len;
-use crate::primitives::{address, db::Database, Address, SpecId, U256};
-use core::ops::Mul;
+use crate::primitives::{address, db::Database, Address, EVMError, SpecId, U256};
+use core::{fmt, ops::Mul};
+
+pub use crate::primitives::L1BlockLayout;
 
 const ZERO_BYTE_COST: u64 = 4;
 const NON_ZERO_BYTE_COST: u64 = 16;
@@ -35,6 +37,44 @@ pub const BASE_FEE_RECIPIENT: Address = address!("420000000000000000000000000000
 /// The address of the L1Block contract.
 pub const L1_BLOCK_CONTRACT: Address = address!("4200000000000000000000000000000000000015");
 
+/// Error returned by [L1BlockInfo::try_fetch].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum L1BlockInfoError<DBError> {
+    /// Database error.
+    Database(DBError),
+    /// Storage of the L1Block predeploy does not have the layout of the spec.
+    LayoutMismatch {
+        expected: L1BlockLayout,
+        found: L1BlockLayout,
+    },
+}
+
+impl<DBError: fmt::Display> fmt::Display for L1BlockInfoError<DBError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Database(e) => write!(f, "database error: {e}"),
+            Self::LayoutMismatch { expected, found } => write!(
+                f,
+                "L1Block storage layout mismatch: expected {expected}, found {found}"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<DBError: fmt::Debug + fmt::Display> std::error::Error for L1BlockInfoError<DBError> {}
+
+impl<DBError> From<L1BlockInfoError<DBError>> for EVMError<DBError> {
+    fn from(value: L1BlockInfoError<DBError>) -> Self {
+        match value {
+            L1BlockInfoError::Database(e) => EVMError::Database(e),
+            L1BlockInfoError::LayoutMismatch { expected, found } => {
+                EVMError::L1BlockLayoutMismatch { expected, found }
+            }
+        }
+    }
+}
+
 /// L1 block info
 ///
 /// We can extract L1 epoch data from each L2 block, by looking at the `setL1BlockValues`
@@ -64,56 +104,82 @@ pub struct L1BlockInfo {
 
 impl L1BlockInfo {
     /// Try to fetch the L1 block info from the database.
-    pub fn try_fetch<DB: Database>(db: &mut DB, spec_id: SpecId) -> Result<L1BlockInfo, DB::Error> {
+    ///
+    /// Slots are read with the [L1BlockLayout] of the spec. Before Ecotone the values only the
+    /// Ecotone predeploy writes must be empty, otherwise the storage is rejected. After Ecotone
+    /// empty values mark the first Ecotone block, where the fee scalars are not yet set.
+    pub fn try_fetch<DB: Database>(
+        db: &mut DB,
+        spec_id: SpecId,
+    ) -> Result<L1BlockInfo, L1BlockInfoError<DB::Error>> {
         // Ensure the L1 Block account is loaded into the cache after Ecotone. With EIP-4788, it is no longer the case
         // that the L1 block account is loaded into the cache prior to the first inquiry for the L1 block info.
         if spec_id.is_enabled_in(SpecId::CANCUN) {
-            let _ = db.basic(L1_BLOCK_CONTRACT)?;
+            let _ = db
+                .basic(L1_BLOCK_CONTRACT)
+                .map_err(L1BlockInfoError::Database)?;
         }
 
-        let l1_base_fee = db.storage(L1_BLOCK_CONTRACT, L1_BASE_FEE_SLOT)?;
-
-        if !spec_id.is_enabled_in(SpecId::ECOTONE) {
-            let l1_fee_overhead = db.storage(L1_BLOCK_CONTRACT, L1_OVERHEAD_SLOT)?;
-            let l1_fee_scalar = db.storage(L1_BLOCK_CONTRACT, L1_SCALAR_SLOT)?;
-
-            Ok(L1BlockInfo {
-                l1_base_fee,
-                l1_fee_overhead: Some(l1_fee_overhead),
-                l1_base_fee_scalar: l1_fee_scalar,
-                ..Default::default()
-            })
-        } else {
-            let l1_blob_base_fee = db.storage(L1_BLOCK_CONTRACT, ECOTONE_L1_BLOB_BASE_FEE_SLOT)?;
-            let l1_fee_scalars = db
-                .storage(L1_BLOCK_CONTRACT, ECOTONE_L1_FEE_SCALARS_SLOT)?
-                .to_be_bytes::<32>();
-
-            let l1_base_fee_scalar = U256::from_be_slice(
-                l1_fee_scalars[BASE_FEE_SCALAR_OFFSET..BASE_FEE_SCALAR_OFFSET + 4].as_ref(),
-            );
-            let l1_blob_base_fee_scalar = U256::from_be_slice(
-                l1_fee_scalars[BLOB_BASE_FEE_SCALAR_OFFSET..BLOB_BASE_FEE_SCALAR_OFFSET + 4]
-                    .as_ref(),
-            );
-
-            // Check if the L1 fee scalars are empty. If so, we use the Bedrock cost function. The L1 fee overhead is
-            // only necessary if `empty_scalars` is true, as it was deprecated in Ecotone.
-            let empty_scalars = l1_blob_base_fee == U256::ZERO
-                && l1_fee_scalars[BASE_FEE_SCALAR_OFFSET..BLOB_BASE_FEE_SCALAR_OFFSET + 4]
-                    == EMPTY_SCALARS;
-            let l1_fee_overhead = empty_scalars
-                .then(|| db.storage(L1_BLOCK_CONTRACT, L1_OVERHEAD_SLOT))
-                .transpose()?;
-
-            Ok(L1BlockInfo {
-                l1_base_fee,
-                l1_base_fee_scalar,
-                l1_blob_base_fee: Some(l1_blob_base_fee),
-                l1_blob_base_fee_scalar: Some(l1_blob_base_fee_scalar),
-                empty_scalars,
-                l1_fee_overhead,
-            })
+        let l1_base_fee = db
+            .storage(L1_BLOCK_CONTRACT, L1_BASE_FEE_SLOT)
+            .map_err(L1BlockInfoError::Database)?;
+        let l1_blob_base_fee = db
+            .storage(L1_BLOCK_CONTRACT, ECOTONE_L1_BLOB_BASE_FEE_SLOT)
+            .map_err(L1BlockInfoError::Database)?;
+        let l1_fee_scalars = db
+            .storage(L1_BLOCK_CONTRACT, ECOTONE_L1_FEE_SCALARS_SLOT)
+            .map_err(L1BlockInfoError::Database)?
+            .to_be_bytes::<32>();
+
+        // before Ecotone the fee scalars slot only holds the sequence number.
+        let empty_scalars = l1_blob_base_fee == U256::ZERO
+            && l1_fee_scalars[BASE_FEE_SCALAR_OFFSET..BLOB_BASE_FEE_SCALAR_OFFSET + 4]
+                == EMPTY_SCALARS;
+        match L1BlockLayout::from_spec(spec_id) {
+            L1BlockLayout::Bedrock if !empty_scalars => Err(L1BlockInfoError::LayoutMismatch {
+                expected: L1BlockLayout::Bedrock,
+                found: L1BlockLayout::Ecotone,
+            }),
+            L1BlockLayout::Bedrock => {
+                let l1_fee_overhead = db
+                    .storage(L1_BLOCK_CONTRACT, L1_OVERHEAD_SLOT)
+                    .map_err(L1BlockInfoError::Database)?;
+                let l1_fee_scalar = db
+                    .storage(L1_BLOCK_CONTRACT, L1_SCALAR_SLOT)
+                    .map_err(L1BlockInfoError::Database)?;
+
+                Ok(L1BlockInfo {
+                    l1_base_fee,
+                    l1_fee_overhead: Some(l1_fee_overhead),
+                    l1_base_fee_scalar: l1_fee_scalar,
+                    ..Default::default()
+                })
+            }
+            L1BlockLayout::Ecotone => {
+                let l1_base_fee_scalar = U256::from_be_slice(
+                    l1_fee_scalars[BASE_FEE_SCALAR_OFFSET..BASE_FEE_SCALAR_OFFSET + 4].as_ref(),
+                );
+                let l1_blob_base_fee_scalar = U256::from_be_slice(
+                    l1_fee_scalars[BLOB_BASE_FEE_SCALAR_OFFSET..BLOB_BASE_FEE_SCALAR_OFFSET + 4]
+                        .as_ref(),
+                );
+
+                // If the L1 fee scalars are empty, we use the Bedrock cost function. The L1 fee overhead is
+                // only necessary if `empty_scalars` is true, as it was deprecated in Ecotone.
+                let l1_fee_overhead = empty_scalars
+                    .then(|| db.storage(L1_BLOCK_CONTRACT, L1_OVERHEAD_SLOT))
+                    .transpose()
+                    .map_err(L1BlockInfoError::Database)?;
+
+                Ok(L1BlockInfo {
+                    l1_base_fee,
+                    l1_base_fee_scalar,
+                    l1_blob_base_fee: Some(l1_blob_base_fee),
+                    l1_blob_base_fee_scalar: Some(l1_blob_base_fee_scalar),
+                    empty_scalars,
+                    l1_fee_overhead,
+                })
+            }
         }
     }
 
@@ -244,7 +310,98 @@ impl L1BlockInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::primitives::bytes;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        primitives::bytes,
+    };
+
+    fn l1_block_db(slots: &[(U256, U256)]) -> CacheDB<EmptyDB> {
+        let mut db = CacheDB::new(EmptyDB::default());
+        for (slot, value) in slots {
+            db.insert_account_storage(L1_BLOCK_CONTRACT, *slot, *value)
+                .unwrap();
+        }
+        db
+    }
+
+    /// Ecotone fee scalars slot with the sequence number in the lowest bytes.
+    fn fee_scalars(base_fee_scalar: u32, blob_base_fee_scalar: u32) -> U256 {
+        let mut slot = [0u8; 32];
+        slot[BASE_FEE_SCALAR_OFFSET..BASE_FEE_SCALAR_OFFSET + 4]
+            .copy_from_slice(&base_fee_scalar.to_be_bytes());
+        slot[BLOB_BASE_FEE_SCALAR_OFFSET..BLOB_BASE_FEE_SCALAR_OFFSET + 4]
+            .copy_from_slice(&blob_base_fee_scalar.to_be_bytes());
+        slot[24..].copy_from_slice(&7u64.to_be_bytes());
+        U256::from_be_bytes(slot)
+    }
+
+    #[test]
+    fn test_try_fetch_bedrock_layout() {
+        let mut db = l1_block_db(&[
+            (L1_BASE_FEE_SLOT, U256::from(10)),
+            // sequence number only.
+            (ECOTONE_L1_FEE_SCALARS_SLOT, U256::from(7)),
+            (L1_OVERHEAD_SLOT, U256::from(188)),
+            (L1_SCALAR_SLOT, U256::from(684_000)),
+        ]);
+        for spec_id in [SpecId::BEDROCK, SpecId::CANYON] {
+            let info = L1BlockInfo::try_fetch(&mut db, spec_id).unwrap();
+            assert_eq!(info.l1_base_fee, U256::from(10));
+            assert_eq!(info.l1_fee_overhead, Some(U256::from(188)));
+            assert_eq!(info.l1_base_fee_scalar, U256::from(684_000));
+            assert_eq!(info.l1_blob_base_fee, None);
+            assert!(!info.empty_scalars);
+        }
+
+        // first Ecotone block, scalars are not set yet.
+        let info = L1BlockInfo::try_fetch(&mut db, SpecId::ECOTONE).unwrap();
+        assert!(info.empty_scalars);
+        assert_eq!(info.l1_fee_overhead, Some(U256::from(188)));
+        assert_eq!(info.l1_blob_base_fee, Some(U256::ZERO));
+    }
+
+    #[test]
+    fn test_try_fetch_ecotone_layout() {
+        let mut db = l1_block_db(&[
+            (L1_BASE_FEE_SLOT, U256::from(10)),
+            (ECOTONE_L1_FEE_SCALARS_SLOT, fee_scalars(1_368, 810_949)),
+            (ECOTONE_L1_BLOB_BASE_FEE_SLOT, U256::from(3)),
+            // stale value left by Bedrock.
+            (L1_OVERHEAD_SLOT, U256::from(188)),
+        ]);
+        for spec_id in [SpecId::ECOTONE, SpecId::FJORD] {
+            let info = L1BlockInfo::try_fetch(&mut db, spec_id).unwrap();
+            assert_eq!(info.l1_base_fee, U256::from(10));
+            assert_eq!(info.l1_fee_overhead, None);
+            assert_eq!(info.l1_base_fee_scalar, U256::from(1_368));
+            assert_eq!(info.l1_blob_base_fee, Some(U256::from(3)));
+            assert_eq!(info.l1_blob_base_fee_scalar, Some(U256::from(810_949)));
+            assert!(!info.empty_scalars);
+        }
+
+        let mismatch = L1BlockInfoError::LayoutMismatch {
+            expected: L1BlockLayout::Bedrock,
+            found: L1BlockLayout::Ecotone,
+        };
+        assert_eq!(
+            L1BlockInfo::try_fetch(&mut db, SpecId::CANYON).unwrap_err(),
+            mismatch
+        );
+
+        // blob base fee alone marks the Ecotone layout.
+        let mut db = l1_block_db(&[(ECOTONE_L1_BLOB_BASE_FEE_SLOT, U256::from(3))]);
+        assert_eq!(
+            L1BlockInfo::try_fetch(&mut db, SpecId::REGOLITH).unwrap_err(),
+            mismatch
+        );
+        assert!(matches!(
+            EVMError::from(mismatch),
+            EVMError::L1BlockLayoutMismatch {
+                expected: L1BlockLayout::Bedrock,
+                found: L1BlockLayout::Ecotone,
+            }
+        ));
+    }
 
     #[test]
     fn test_data_gas_non_zero_bytes() {
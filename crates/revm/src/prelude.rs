@@ -0,0 +1,56 @@
+//! Commonly used items, imported with `use revm::prelude::*`.
+//!
+//! Every item is re-exported from its canonical path: primitive types from
+//! [`revm::primitives`](crate::primitives), databases from [`revm::db`](crate::db), inspectors
+//! from [`revm::inspectors`](crate::inspectors) and the rest from the crate root. Types that are
+//! also reachable through other paths, such as [`Address`] through the interpreter crate, are
+//! the same types of the same `alloy-primitives` version.
+//!
+//! # Example
+//!
+//! ```
+//! use revm::prelude::*;
+//!
+//! let caller = Address::with_last_byte(1);
+//! let receiver = Address::repeat_byte(0x42);
+//! let mut db = CacheDB::new(EmptyDB::default());
+//! db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+//!
+//! let mut evm = Evm::builder()
+//!     .with_db(db)
+//!     .with_spec_id(SpecId::CANCUN)
+//!     .modify_tx_env(|tx| {
+//!         tx.caller = caller;
+//!         tx.transact_to = Some(TransactTo::Call(receiver));
+//!         tx.value = U256::from(10);
+//!         tx.gas_limit = 21_000;
+//!     })
+//!     .build();
+//!
+//! let result = evm.transact_commit().unwrap();
+//! assert!(matches!(
+//!     result,
+//!     ExecutionResult::Success {
+//!         output: Output::Call(_),
+//!         ..
+//!     }
+//! ));
+//! let receiver = evm.db().basic_ref(receiver).unwrap().unwrap();
+//! assert_eq!(receiver.balance, U256::from(10));
+//! ```
+
+pub use crate::{
+    db::{CacheDB, EmptyDB, InMemoryDB},
+    inspector_handle_register,
+    inspectors::{GasInspector, NoOpInspector},
+    primitives::{
+        AccountInfo, Address, BlockEnv, Bytecode, Bytes, CfgEnv, EVMError, Env, ExecutionResult,
+        HaltReason, Log, Output, ResultAndState, SpecId, TransactTo, TxEnv, TxKind, B256, U256,
+    },
+    Database, DatabaseCommit, DatabaseRef, Evm, EvmBuilder, GetInspector, Inspector,
+};
+
+#[cfg(feature = "std")]
+pub use crate::inspectors::CustomPrintTracer;
+#[cfg(all(feature = "std", feature = "serde-json"))]
+pub use crate::inspectors::TracerEip3155;
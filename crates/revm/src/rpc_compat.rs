@@ -0,0 +1,523 @@
+//! Conversions between the revm types and the `alloy-rpc-types-eth` call requests and results.
+
+use crate::{
+    db::{CacheDB, DatabaseRef},
+    primitives::{
+        decode_revert_reason, Address, Bytecode, Bytes, Env, EvmState, ExecutionResult, HaltReason,
+        ResultAndState, TxEnv, TxKind, U256,
+    },
+};
+use alloy_rpc_types_eth::{state::StateOverride, Log as RpcLog, TransactionRequest};
+use core::fmt;
+use std::{string::String, vec::Vec};
+
+/// Values of the fields that are missing in a [`TransactionRequest`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TxRequestDefaults {
+    /// Caller if `from` is missing.
+    pub caller: Address,
+    /// Gas limit if `gas` is missing, usually the gas limit of the block.
+    pub gas_limit: u64,
+    /// Base fee of the block, used as the gas price if no fee is set.
+    pub basefee: U256,
+    /// Chain id if `chainId` is missing, `None` skips the chain id check.
+    pub chain_id: Option<u64>,
+}
+
+impl TxRequestDefaults {
+    /// Takes the defaults from the block and the configuration of the environment.
+    pub fn from_env(env: &Env) -> Self {
+        Self {
+            caller: Address::ZERO,
+            gas_limit: env.block.gas_limit.saturating_to(),
+            basefee: env.block.basefee,
+            chain_id: Some(env.cfg.chain_id),
+        }
+    }
+}
+
+/// Error returned when a [`TransactionRequest`] can't be converted to a [`TxEnv`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TxRequestError {
+    /// Both `gasPrice` and `maxFeePerGas` are set.
+    ConflictingFeeFields,
+    /// `gas` does not fit into `u64`.
+    GasLimitOverflow(u128),
+    /// Blob transaction without a `to` address, blob transactions can't create contracts.
+    BlobCreate,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TxRequestError {}
+
+impl fmt::Display for TxRequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConflictingFeeFields => {
+                f.write_str("both gasPrice and maxFeePerGas are set in the request")
+            }
+            Self::GasLimitOverflow(gas) => write!(f, "gas limit {gas} does not fit into u64"),
+            Self::BlobCreate => f.write_str("blob transaction can't create a contract"),
+        }
+    }
+}
+
+/// Conversion of a [`TransactionRequest`] into a [`TxEnv`].
+pub trait TxEnvFromRequest: Sized {
+    /// Converts the request, missing fields are taken from `defaults`.
+    ///
+    /// * Missing `to` creates a contract.
+    /// * Missing fees use the base fee as the gas price. With only `maxPriorityFeePerGas` set
+    ///   the max fee is the base fee plus the priority fee.
+    /// * Missing `nonce` skips the nonce check.
+    fn try_from_request(
+        request: TransactionRequest,
+        defaults: &TxRequestDefaults,
+    ) -> Result<Self, TxRequestError>;
+}
+
+impl TxEnvFromRequest for TxEnv {
+    fn try_from_request(
+        request: TransactionRequest,
+        defaults: &TxRequestDefaults,
+    ) -> Result<Self, TxRequestError> {
+        let transact_to = request.to.unwrap_or(TxKind::Create);
+        let blob_hashes = request.blob_versioned_hashes.unwrap_or_default();
+        if !blob_hashes.is_empty() && transact_to.is_create() {
+            return Err(TxRequestError::BlobCreate);
+        }
+        let gas_limit = match request.gas {
+            Some(gas) => u64::try_from(gas).map_err(|_| TxRequestError::GasLimitOverflow(gas))?,
+            None => defaults.gas_limit,
+        };
+        let (gas_price, gas_priority_fee) = match (
+            request.gas_price,
+            request.max_fee_per_gas,
+            request.max_priority_fee_per_gas,
+        ) {
+            (Some(_), Some(_), _) => return Err(TxRequestError::ConflictingFeeFields),
+            (Some(gas_price), None, _) => (U256::from(gas_price), None),
+            (None, Some(max_fee), priority_fee) => (
+                U256::from(max_fee),
+                Some(U256::from(priority_fee.unwrap_or_default())),
+            ),
+            (None, None, Some(priority_fee)) => {
+                let priority_fee = U256::from(priority_fee);
+                (
+                    defaults.basefee.saturating_add(priority_fee),
+                    Some(priority_fee),
+                )
+            }
+            (None, None, None) => (defaults.basefee, None),
+        };
+
+        Ok(TxEnv {
+            caller: request.from.unwrap_or(defaults.caller),
+            gas_limit,
+            gas_price,
+            gas_priority_fee,
+            transact_to: Some(transact_to),
+            value: request.value.unwrap_or_default(),
+            data: request.input.into_input().unwrap_or_default(),
+            nonce: request.nonce,
+            chain_id: request.chain_id.or(defaults.chain_id),
+            access_list: request.access_list.map(|list| list.0).unwrap_or_default(),
+            blob_hashes,
+            max_fee_per_blob_gas: request.max_fee_per_blob_gas.map(U256::from),
+            ..Default::default()
+        })
+    }
+}
+
+/// Result of a simulated call in the shape of the RPC responses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimulatedCallResult {
+    /// `true` if the call succeeded.
+    pub status: bool,
+    /// Gas used by the transaction.
+    pub gas_used: u64,
+    /// Output of the call, the revert data if it reverted.
+    pub return_data: Bytes,
+    /// Logs of the call, without the block and transaction fields.
+    pub logs: Vec<RpcLog>,
+    /// Decoded `Error(string)` or `Panic(uint256)` revert reason.
+    pub revert_reason: Option<String>,
+    /// Reason of the halt, if the call halted.
+    pub halt_reason: Option<HaltReason>,
+    /// State changed by the call.
+    pub state: EvmState,
+}
+
+impl From<ResultAndState> for SimulatedCallResult {
+    fn from(ResultAndState { result, state, .. }: ResultAndState) -> Self {
+        let status = result.is_success();
+        let gas_used = result.gas_used();
+        let (return_data, logs, revert_reason, halt_reason) = match result {
+            ExecutionResult::Success { output, logs, .. } => (output.into_data(), logs, None, None),
+            ExecutionResult::Revert { output, .. } => {
+                let reason = decode_revert_reason(&output);
+                (output, Vec::new(), reason, None)
+            }
+            ExecutionResult::Halt { reason, .. } => (Bytes::new(), Vec::new(), None, Some(reason)),
+        };
+        Self {
+            status,
+            gas_used,
+            return_data,
+            logs: logs
+                .into_iter()
+                .map(|inner| RpcLog {
+                    inner,
+                    ..Default::default()
+                })
+                .collect(),
+            revert_reason,
+            halt_reason,
+            state,
+        }
+    }
+}
+
+/// Error returned when a [`StateOverride`] can't be applied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StateOverrideError<DBError> {
+    /// Both `state` and `stateDiff` are set for the account.
+    BothStateAndStateDiff(Address),
+    /// Error of the database.
+    Database(DBError),
+}
+
+#[cfg(feature = "std")]
+impl<DBError: fmt::Debug + fmt::Display> std::error::Error for StateOverrideError<DBError> {}
+
+impl<DBError: fmt::Display> fmt::Display for StateOverrideError<DBError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BothStateAndStateDiff(address) => {
+                write!(f, "both state and stateDiff are set for {address}")
+            }
+            Self::Database(err) => write!(f, "database error: {err}"),
+        }
+    }
+}
+
+/// Applies the `eth_call` state overrides to the database.
+///
+/// `state` replaces the whole storage of the account, `stateDiff` only the given slots.
+pub fn apply_state_overrides<ExtDB: DatabaseRef>(
+    db: &mut CacheDB<ExtDB>,
+    overrides: &StateOverride,
+) -> Result<(), StateOverrideError<ExtDB::Error>> {
+    for (address, account_override) in overrides {
+        if account_override.state.is_some() && account_override.state_diff.is_some() {
+            return Err(StateOverrideError::BothStateAndStateDiff(*address));
+        }
+        let mut info = db
+            .load_account(*address)
+            .map_err(StateOverrideError::Database)?
+            .info()
+            .unwrap_or_default();
+        if let Some(balance) = account_override.balance {
+            info.balance = balance;
+        }
+        if let Some(nonce) = account_override.nonce {
+            info.nonce = nonce.to();
+        }
+        if let Some(code) = &account_override.code {
+            info = info.with_code(Bytecode::new_raw(code.clone()));
+        }
+        db.insert_account_info(*address, info);
+
+        if let Some(state) = &account_override.state {
+            let storage = state
+                .iter()
+                .map(|(slot, value)| ((*slot).into(), (*value).into()))
+                .collect();
+            db.replace_account_storage(*address, storage)
+                .map_err(StateOverrideError::Database)?;
+        }
+        if let Some(state_diff) = &account_override.state_diff {
+            for (slot, value) in state_diff {
+                db.insert_account_storage(*address, (*slot).into(), (*value).into())
+                    .map_err(StateOverrideError::Database)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{DatabaseRef, EmptyDB},
+        primitives::{
+            address, b256, bytes, AccessList, AccessListItem, Log, LogData, Output, SuccessReason,
+            B256,
+        },
+    };
+    use alloy_rpc_types_eth::{state::AccountOverride, TransactionInput};
+
+    const FROM: Address = address!("1000000000000000000000000000000000000001");
+    const TO: Address = address!("2000000000000000000000000000000000000002");
+
+    fn defaults() -> TxRequestDefaults {
+        TxRequestDefaults {
+            caller: Address::ZERO,
+            gas_limit: 30_000_000,
+            basefee: U256::from(7),
+            chain_id: Some(1),
+        }
+    }
+
+    fn request() -> TransactionRequest {
+        TransactionRequest {
+            from: Some(FROM),
+            to: Some(TxKind::Call(TO)),
+            input: TransactionInput::new(bytes!("12345678")),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn legacy_request() {
+        let tx = TxEnv::try_from_request(
+            TransactionRequest {
+                gas_price: Some(20),
+                gas: Some(50_000),
+                value: Some(U256::from(3)),
+                nonce: Some(4),
+                ..request()
+            },
+            &defaults(),
+        )
+        .unwrap();
+        assert_eq!(tx.caller, FROM);
+        assert_eq!(tx.transact_to, Some(TxKind::Call(TO)));
+        assert_eq!(tx.gas_limit, 50_000);
+        assert_eq!(tx.gas_price, U256::from(20));
+        assert_eq!(tx.gas_priority_fee, None);
+        assert_eq!(tx.value, U256::from(3));
+        assert_eq!(tx.data, bytes!("12345678"));
+        assert_eq!(tx.nonce, Some(4));
+        assert_eq!(tx.chain_id, Some(1));
+    }
+
+    #[test]
+    fn eip1559_request() {
+        let access_list = AccessList(vec![AccessListItem {
+            address: TO,
+            storage_keys: vec![B256::ZERO],
+        }]);
+        let tx = TxEnv::try_from_request(
+            TransactionRequest {
+                max_fee_per_gas: Some(100),
+                max_priority_fee_per_gas: Some(2),
+                access_list: Some(access_list.clone()),
+                chain_id: Some(10),
+                ..request()
+            },
+            &defaults(),
+        )
+        .unwrap();
+        assert_eq!(tx.gas_limit, 30_000_000);
+        assert_eq!(tx.gas_price, U256::from(100));
+        assert_eq!(tx.gas_priority_fee, Some(U256::from(2)));
+        assert_eq!(tx.access_list, access_list.0);
+        assert_eq!(tx.chain_id, Some(10));
+        assert_eq!(tx.nonce, None);
+
+        // only the priority fee is set.
+        let tx = TxEnv::try_from_request(
+            TransactionRequest {
+                max_priority_fee_per_gas: Some(2),
+                ..request()
+            },
+            &defaults(),
+        )
+        .unwrap();
+        assert_eq!(tx.gas_price, U256::from(9));
+        assert_eq!(tx.gas_priority_fee, Some(U256::from(2)));
+
+        // no fees, gas price is the base fee.
+        let tx = TxEnv::try_from_request(request(), &defaults()).unwrap();
+        assert_eq!(tx.gas_price, U256::from(7));
+        assert_eq!(tx.gas_priority_fee, None);
+
+        assert_eq!(
+            TxEnv::try_from_request(
+                TransactionRequest {
+                    gas_price: Some(1),
+                    max_fee_per_gas: Some(1),
+                    ..request()
+                },
+                &defaults(),
+            ),
+            Err(TxRequestError::ConflictingFeeFields)
+        );
+    }
+
+    #[test]
+    fn eip4844_request() {
+        let blob_hash = b256!("01a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8");
+        let tx = TxEnv::try_from_request(
+            TransactionRequest {
+                max_fee_per_gas: Some(100),
+                max_fee_per_blob_gas: Some(5),
+                blob_versioned_hashes: Some(vec![blob_hash]),
+                ..request()
+            },
+            &defaults(),
+        )
+        .unwrap();
+        assert_eq!(tx.blob_hashes, [blob_hash]);
+        assert_eq!(tx.max_fee_per_blob_gas, Some(U256::from(5)));
+        assert_eq!(tx.gas_priority_fee, Some(U256::ZERO));
+
+        assert_eq!(
+            TxEnv::try_from_request(
+                TransactionRequest {
+                    to: None,
+                    blob_versioned_hashes: Some(vec![blob_hash]),
+                    ..request()
+                },
+                &defaults(),
+            ),
+            Err(TxRequestError::BlobCreate)
+        );
+    }
+
+    #[test]
+    fn create_request() {
+        let tx = TxEnv::try_from_request(
+            TransactionRequest {
+                to: None,
+                input: TransactionInput::new(bytes!("5f5ff3")),
+                ..Default::default()
+            },
+            &defaults(),
+        )
+        .unwrap();
+        assert_eq!(tx.transact_to, Some(TxKind::Create));
+        assert_eq!(tx.caller, Address::ZERO);
+        assert_eq!(tx.data, bytes!("5f5ff3"));
+
+        assert_eq!(
+            TxEnv::try_from_request(
+                TransactionRequest {
+                    gas: Some(u128::from(u64::MAX) + 1),
+                    ..request()
+                },
+                &defaults(),
+            ),
+            Err(TxRequestError::GasLimitOverflow(u128::from(u64::MAX) + 1))
+        );
+    }
+
+    #[test]
+    fn simulated_call_result() {
+        let log = Log {
+            address: TO,
+            data: LogData::new_unchecked(vec![B256::ZERO], bytes!("01")),
+        };
+        let result = SimulatedCallResult::from(ResultAndState {
+            result: ExecutionResult::Success {
+                reason: SuccessReason::Return,
+                gas_used: 21_000,
+                gas_refunded: 0,
+                logs: vec![log.clone()],
+                output: Output::Call(bytes!("2a")),
+            },
+            state: EvmState::default(),
+            tx_type: Default::default(),
+            selfdestructs: Vec::new(),
+            profile: None,
+            #[cfg(feature = "optimism")]
+            l1_fee: None,
+        });
+        assert!(result.status);
+        assert_eq!(result.gas_used, 21_000);
+        assert_eq!(result.return_data, bytes!("2a"));
+        assert_eq!(result.logs.len(), 1);
+        assert_eq!(result.logs[0].inner, log);
+
+        let revert = bytes!("08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000046e6f706500000000000000000000000000000000000000000000000000000000");
+        let result = SimulatedCallResult::from(ResultAndState {
+            result: ExecutionResult::Revert {
+                gas_used: 22_000,
+                output: revert.clone(),
+            },
+            state: EvmState::default(),
+            tx_type: Default::default(),
+            selfdestructs: Vec::new(),
+            profile: None,
+            #[cfg(feature = "optimism")]
+            l1_fee: None,
+        });
+        assert!(!result.status);
+        assert_eq!(result.return_data, revert);
+        assert_eq!(result.revert_reason.as_deref(), Some("nope"));
+        assert!(result.logs.is_empty());
+    }
+
+    #[test]
+    fn state_overrides() {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_storage(TO, U256::from(1), U256::from(1))
+            .unwrap();
+        db.insert_account_storage(FROM, U256::from(1), U256::from(1))
+            .unwrap();
+
+        let slot = B256::with_last_byte(2);
+        let value = B256::with_last_byte(3);
+        let overrides: StateOverride = [
+            (
+                TO,
+                AccountOverride {
+                    balance: Some(U256::from(100)),
+                    code: Some(bytes!("5f5ff3")),
+                    state: Some([(slot, value)].into_iter().collect()),
+                    ..Default::default()
+                },
+            ),
+            (
+                FROM,
+                AccountOverride {
+                    state_diff: Some([(slot, value)].into_iter().collect()),
+                    ..Default::default()
+                },
+            ),
+        ]
+        .into_iter()
+        .collect();
+        apply_state_overrides(&mut db, &overrides).unwrap();
+
+        let to = db.load_account(TO).unwrap().info().unwrap();
+        assert_eq!(to.balance, U256::from(100));
+        // code is kept by its hash, not in the cached account.
+        assert_eq!(
+            db.code_by_hash_ref(to.code_hash).unwrap().original_bytes(),
+            bytes!("5f5ff3")
+        );
+        assert_eq!(
+            db.storage_iter(TO).collect::<Vec<_>>(),
+            [(U256::from(2), U256::from(3))]
+        );
+        assert_eq!(db.storage_len(FROM), 2);
+
+        let both: StateOverride = [(
+            TO,
+            AccountOverride {
+                state: Some(Default::default()),
+                state_diff: Some(Default::default()),
+                ..Default::default()
+            },
+        )]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            apply_state_overrides(&mut db, &both),
+            Err(StateOverrideError::BothStateAndStateDiff(TO))
+        );
+    }
+}
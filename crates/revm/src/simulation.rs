@@ -0,0 +1,281 @@
+use crate::{
+    db::{Database, DatabaseCommit},
+    primitives::{
+        calc_excess_blob_gas, Address, BlockEnv, EVMError, Eip1559Params, ExecutionResult,
+        HandlerCfg, SpecId, TxEnv, B256, U256,
+    },
+    Evm,
+};
+use std::vec::Vec;
+
+/// Default number of seconds between two simulated blocks.
+pub const DEFAULT_BLOCK_TIME: u64 = 12;
+
+/// Fields of the [BlockEnv] that are set explicitly instead of being derived from the
+/// previous block, see [`SimulationSession::next_block`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockOverrides {
+    /// Block number.
+    pub number: Option<U256>,
+    /// Block timestamp.
+    pub timestamp: Option<U256>,
+    /// Block gas limit.
+    pub gas_limit: Option<U256>,
+    /// Coinbase of the block.
+    pub coinbase: Option<Address>,
+    /// Base fee per gas.
+    pub basefee: Option<U256>,
+    /// Prevrandao of the block.
+    pub prevrandao: Option<B256>,
+    /// Excess blob gas, blob gas price is calculated from it.
+    pub excess_blob_gas: Option<u64>,
+}
+
+/// Block executed by the [SimulationSession].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimulatedBlock {
+    /// Block environment the transactions were executed in.
+    pub block: BlockEnv,
+    /// Results of the executed transactions.
+    pub results: Vec<ExecutionResult>,
+    /// Gas used by all transactions of the block.
+    pub gas_used: u64,
+    /// Blob gas used by all transactions of the block.
+    pub blob_gas_used: u64,
+}
+
+impl SimulatedBlock {
+    fn new(block: BlockEnv) -> Self {
+        Self {
+            block,
+            results: Vec::new(),
+            gas_used: 0,
+            blob_gas_used: 0,
+        }
+    }
+}
+
+/// Executes transactions in a sequence of blocks, `eth_simulateV1` style.
+///
+/// All transactions are committed to the database of the [Evm], usually an in-memory
+/// overlay such as [`CacheDB`](crate::db::CacheDB). Between blocks the [BlockEnv] is advanced
+/// as the chain would do it: number is increased by one, timestamp by the block time, and
+/// base fee (EIP-1559) and excess blob gas (EIP-4844) are derived from the gas used by the
+/// previous block.
+pub struct SimulationSession<'a, EXT, DB: Database> {
+    /// Always `Some`, it is taken only while the [Evm] is modified.
+    evm: Option<Evm<'a, EXT, DB>>,
+    /// Executed blocks, last one is the current block.
+    blocks: Vec<SimulatedBlock>,
+    /// Number of seconds between two blocks.
+    block_time: u64,
+}
+
+impl<'a, EXT, DB: Database + DatabaseCommit> SimulationSession<'a, EXT, DB> {
+    /// Creates a new session, the first block is the block environment of the `evm`.
+    pub fn new(evm: Evm<'a, EXT, DB>) -> Self {
+        let block = evm.block().clone();
+        Self {
+            evm: Some(evm),
+            blocks: vec![SimulatedBlock::new(block)],
+            block_time: DEFAULT_BLOCK_TIME,
+        }
+    }
+
+    /// Sets the number of seconds between two blocks.
+    pub fn with_block_time(mut self, block_time: u64) -> Self {
+        self.block_time = block_time;
+        self
+    }
+
+    /// Returns the [Evm].
+    #[inline]
+    pub fn evm(&self) -> &Evm<'a, EXT, DB> {
+        self.evm.as_ref().expect("evm is set")
+    }
+
+    /// Returns the mutable [Evm].
+    #[inline]
+    pub fn evm_mut(&mut self) -> &mut Evm<'a, EXT, DB> {
+        self.evm.as_mut().expect("evm is set")
+    }
+
+    /// Returns all blocks, the last one is the current block.
+    #[inline]
+    pub fn blocks(&self) -> &[SimulatedBlock] {
+        &self.blocks
+    }
+
+    /// Returns the current block.
+    #[inline]
+    pub fn current_block(&self) -> &SimulatedBlock {
+        self.blocks.last().expect("there is at least one block")
+    }
+
+    /// Consumes the session and returns the [Evm] and all blocks.
+    pub fn into_parts(self) -> (Evm<'a, EXT, DB>, Vec<SimulatedBlock>) {
+        (self.evm.expect("evm is set"), self.blocks)
+    }
+
+    /// Executes the transactions in the current block and commits them to the database.
+    ///
+    /// Returns results of the executed transactions. On error, results of the transactions
+    /// executed before the failing one are kept in the block.
+    pub fn run(
+        &mut self,
+        txs: impl IntoIterator<Item = TxEnv>,
+    ) -> Result<&[ExecutionResult], EVMError<DB::Error>> {
+        let evm = self.evm.as_mut().expect("evm is set");
+        let block = self.blocks.last_mut().expect("there is at least one block");
+        let first = block.results.len();
+        for tx in txs {
+            let blob_gas = tx.get_total_blob_gas();
+            *evm.tx_mut() = tx;
+            let result = evm.transact_commit()?;
+            block.gas_used += result.gas_used();
+            block.blob_gas_used += blob_gas;
+            block.results.push(result);
+        }
+        Ok(&block.results[first..])
+    }
+
+    /// Starts the next block.
+    ///
+    /// The new [BlockEnv] is derived from the current one and `overrides` are applied on top.
+    /// Base fee is calculated with [`CfgEnv::eip1559_params`](crate::primitives::CfgEnv::eip1559_params)
+    /// or with the chain defaults if they are not set.
+    pub fn next_block(&mut self, overrides: BlockOverrides) -> &BlockEnv {
+        let evm = self.evm.take().expect("evm is set");
+        let spec_id = evm.spec_id();
+        let parent = self.current_block();
+
+        let mut block = parent.block.clone();
+        block.number = parent.block.number.saturating_add(U256::from(1));
+        block.timestamp = parent
+            .block
+            .timestamp
+            .saturating_add(U256::from(self.block_time));
+        if spec_id.is_enabled_in(SpecId::LONDON) {
+            let params = evm
+                .cfg()
+                .eip1559_params
+                .unwrap_or_else(|| default_eip1559_params(evm.handler_cfg()));
+            block.basefee = parent.block.next_base_fee(parent.gas_used, params);
+        }
+        if let Some(excess_blob_gas) = parent.block.get_blob_excess_gas() {
+            block.set_blob_excess_gas_and_price(calc_excess_blob_gas(
+                excess_blob_gas,
+                parent.blob_gas_used,
+            ));
+        }
+
+        let BlockOverrides {
+            number,
+            timestamp,
+            gas_limit,
+            coinbase,
+            basefee,
+            prevrandao,
+            excess_blob_gas,
+        } = overrides;
+        block.number = number.unwrap_or(block.number);
+        block.timestamp = timestamp.unwrap_or(block.timestamp);
+        block.gas_limit = gas_limit.unwrap_or(block.gas_limit);
+        block.coinbase = coinbase.unwrap_or(block.coinbase);
+        block.basefee = basefee.unwrap_or(block.basefee);
+        block.prevrandao = prevrandao.or(block.prevrandao);
+        if let Some(excess_blob_gas) = excess_blob_gas {
+            block.set_blob_excess_gas_and_price(excess_blob_gas);
+        }
+
+        self.evm = Some(evm.modify().with_block_env(block.clone()).build());
+        self.blocks.push(SimulatedBlock::new(block));
+        &self.current_block().block
+    }
+}
+
+/// Returns the default [`Eip1559Params`] of the chain.
+fn default_eip1559_params(handler_cfg: &HandlerCfg) -> Eip1559Params {
+    #[cfg(feature = "optimism")]
+    if handler_cfg.is_optimism {
+        return crate::optimism::eip1559_params(handler_cfg.spec_id);
+    }
+    let _ = handler_cfg;
+    Eip1559Params::MAINNET
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::InMemoryDB,
+        primitives::{address, calc_next_block_base_fee, AccountInfo, TxKind},
+        test_utils::CALLER,
+    };
+
+    const RECEIVER: Address = address!("2000000000000000000000000000000000000002");
+
+    fn transfer(nonce: u64, gas_price: U256) -> TxEnv {
+        TxEnv {
+            caller: CALLER,
+            transact_to: Some(TxKind::Call(RECEIVER)),
+            value: U256::from(1),
+            gas_limit: 21_000,
+            gas_price,
+            nonce: Some(nonce),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn basefee_follows_gas_usage() {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            CALLER,
+            AccountInfo {
+                balance: U256::from(1_000_000_000_000u64),
+                ..Default::default()
+            },
+        );
+        let evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(SpecId::CANCUN)
+            .modify_block_env(|block| {
+                block.number = U256::from(1);
+                block.timestamp = U256::from(1_000);
+                // gas target of 15_000 is exceeded by a single transfer.
+                block.gas_limit = U256::from(30_000);
+                block.basefee = U256::from(1_000);
+                block.set_blob_excess_gas_and_price(0);
+            })
+            .build();
+        let mut session = SimulationSession::new(evm);
+
+        let results = session.run([transfer(0, U256::from(1_000))]).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(session.current_block().gas_used, 21_000);
+
+        let block = session.next_block(BlockOverrides::default()).clone();
+        assert_eq!(block.number, U256::from(2));
+        assert_eq!(block.timestamp, U256::from(1_012));
+        let expected_basefee = calc_next_block_base_fee(21_000, 30_000, 1_000);
+        assert!(expected_basefee > 1_000);
+        assert_eq!(block.basefee, U256::from(expected_basefee));
+        assert_eq!(block.get_blob_excess_gas(), Some(0));
+        assert_eq!(session.evm().block(), &block);
+
+        // gas price below the new base fee is rejected.
+        assert!(session.run([transfer(1, U256::from(1_000))]).is_err());
+        session
+            .run([transfer(1, U256::from(expected_basefee))])
+            .unwrap();
+
+        let (evm, blocks) = session.into_parts();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].results.len(), 1);
+        assert_eq!(blocks[1].results.len(), 1);
+        assert_eq!(blocks[1].block.basefee, U256::from(expected_basefee));
+        let receiver = evm.db().accounts.get(&RECEIVER).unwrap();
+        assert_eq!(receiver.info.balance, U256::from(2));
+    }
+}
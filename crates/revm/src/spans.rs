@@ -0,0 +1,160 @@
+//! [`tracing`](https://docs.rs/tracing) spans of the transaction phases and call frames.
+//!
+//! Spans are emitted only with the `tracing` feature, otherwise they are zero sized and
+//! compiled away. Interpreter loop is never instrumented.
+
+use crate::{Frame, FrameResult};
+#[cfg(feature = "tracing")]
+use std::vec::Vec;
+
+/// Enters a span of the transaction phase that is exited when the returned guard is dropped.
+#[cfg(feature = "tracing")]
+macro_rules! phase_span {
+    ($name:literal) => {
+        ::tracing::debug_span!($name).entered()
+    };
+}
+
+/// Enters a span of the transaction phase that is exited when the returned guard is dropped.
+#[cfg(not(feature = "tracing"))]
+macro_rules! phase_span {
+    ($name:literal) => {
+        $crate::spans::DisabledSpan
+    };
+}
+
+/// Guard of the phase span when the `tracing` feature is disabled.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct DisabledSpan;
+
+pub(crate) use phase_span;
+
+/// Spans of the frames in the call stack.
+///
+/// Frames outlive the scope they are created in, so their spans are not entered but created
+/// with the span of the parent frame, or the current span for the first frame, as a parent.
+#[derive(Debug, Default)]
+pub(crate) struct FrameSpans {
+    #[cfg(feature = "tracing")]
+    spans: Vec<tracing::Span>,
+}
+
+impl FrameSpans {
+    /// Opens the span of the new `frame` at `depth`.
+    #[inline]
+    pub(crate) fn push(&mut self, frame: &Frame, depth: usize) {
+        #[cfg(feature = "tracing")]
+        {
+            let kind = match frame {
+                Frame::Call(_) => "call",
+                Frame::Create(_) => "create",
+                Frame::EOFCreate(_) => "eofcreate",
+            };
+            let interpreter = frame.interpreter();
+            let parent = self
+                .spans
+                .last()
+                .cloned()
+                .unwrap_or_else(tracing::Span::current);
+            self.spans.push(tracing::debug_span!(
+                parent: &parent,
+                "frame",
+                kind,
+                address = %interpreter.contract.target_address,
+                depth,
+                gas_limit = interpreter.gas.limit(),
+                gas_used = tracing::field::Empty,
+                result = tracing::field::Empty,
+            ));
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = (frame, depth);
+    }
+
+    /// Records the `result` of the last frame and closes its span.
+    #[inline]
+    pub(crate) fn pop(&mut self, result: &FrameResult) {
+        #[cfg(feature = "tracing")]
+        if let Some(span) = self.spans.pop() {
+            span.record("gas_used", result.gas().spent());
+            span.record(
+                "result",
+                tracing::field::debug(result.interpreter_result().result),
+            );
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = result;
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use crate::{
+        primitives::{address, AccountInfo, Address, Bytecode},
+        test_utils::contract_evm,
+    };
+    use std::vec;
+
+    #[test]
+    fn tracing_spans() {
+        use std::sync::{Arc, Mutex};
+        use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, Layer};
+
+        /// Name of a created span and of its parent.
+        type SpanEdge = (&'static str, Option<&'static str>);
+
+        /// Records names of the created spans and their parents.
+        #[derive(Clone, Default)]
+        struct SpanTree(Arc<Mutex<Vec<SpanEdge>>>);
+
+        impl<S: tracing::Subscriber + for<'a> LookupSpan<'a>> Layer<S> for SpanTree {
+            fn on_new_span(
+                &self,
+                _attrs: &tracing::span::Attributes<'_>,
+                id: &tracing::span::Id,
+                ctx: Context<'_, S>,
+            ) {
+                let span = ctx.span(id).unwrap();
+                let parent = span.parent().map(|parent| parent.name());
+                self.0.lock().unwrap().push((span.name(), parent));
+            }
+        }
+
+        const CALLEE: Address = address!("3000000000000000000000000000000000000003");
+        // CALL(gas, CALLEE, 0, 0, 0, 0, 0), STOP
+        let mut code = vec![
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73,
+        ];
+        code.extend_from_slice(CALLEE.as_slice());
+        code.extend_from_slice(&[0x5a, 0xf1, 0x00]);
+
+        let mut evm = contract_evm(code)
+            .modify_db(|db| {
+                // PUSH0 STOP, so the callee has a frame.
+                db.insert_account_info(
+                    CALLEE,
+                    AccountInfo::from_bytecode(Bytecode::new_raw(vec![0x5f, 0x00].into())),
+                );
+            })
+            .build();
+
+        let tree = SpanTree::default();
+        let subscriber = tracing_subscriber::registry().with(tree.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            evm.transact().unwrap().result.expect_success();
+        });
+
+        assert_eq!(
+            *tree.0.lock().unwrap(),
+            [
+                ("transact", None),
+                ("validation", Some("transact")),
+                ("pre_execution", Some("transact")),
+                ("execution", Some("transact")),
+                ("frame", Some("execution")),
+                ("frame", Some("frame")),
+                ("post_execution", Some("transact")),
+            ]
+        );
+    }
+}
@@ -1,2 +1,12 @@
+#[cfg(all(test, feature = "std"))]
+pub(crate) mod alloc_counter;
+mod fixture;
+mod inspector;
+mod invariants;
+
+pub use fixture::{contract_db, contract_evm, contract_tx, CALLER, CONTRACT};
+pub use inspector::AssertingInspector;
+pub use invariants::check_invariants;
+
 #[doc(hidden)]
 pub use crate::context::evm_context::test_utils::*;
@@ -0,0 +1,65 @@
+//! Global allocator counting the heap allocations and the allocated bytes of the current thread.
+
+use core::cell::Cell;
+use std::alloc::{GlobalAlloc, Layout, System};
+
+std::thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+    static PEAK_ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Records `size` allocated bytes, updating the peak.
+fn record_alloc(size: usize) {
+    let _ = ALLOCATED.try_with(|allocated| {
+        allocated.set(allocated.get() + size);
+        let _ = PEAK_ALLOCATED.try_with(|peak| peak.set(peak.get().max(allocated.get())));
+    });
+}
+
+/// Records `size` freed bytes, memory allocated by other threads is ignored.
+fn record_dealloc(size: usize) {
+    let _ = ALLOCATED.try_with(|allocated| allocated.set(allocated.get().saturating_sub(size)));
+}
+
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        record_alloc(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        record_dealloc(layout.size());
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        record_alloc(new_size);
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        record_dealloc(layout.size());
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Returns the number of allocations made by the current thread.
+pub(crate) fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// Returns the most bytes allocated at once by the current thread since the last
+/// [`reset_peak_allocated`].
+pub(crate) fn peak_allocated() -> usize {
+    PEAK_ALLOCATED.with(Cell::get)
+}
+
+/// Resets the peak to the bytes currently allocated by the current thread.
+pub(crate) fn reset_peak_allocated() {
+    PEAK_ALLOCATED.with(|peak| peak.set(ALLOCATED.with(Cell::get)));
+}
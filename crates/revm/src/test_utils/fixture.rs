@@ -0,0 +1,43 @@
+//! EVM of the tests that call a single contract.
+
+use crate::{
+    builder::SetGenericStage,
+    db::InMemoryDB,
+    primitives::{address, AccountInfo, Address, Bytecode, Bytes, TxEnv, TxKind},
+    Evm, EvmBuilder,
+};
+
+/// Caller of the [`contract_tx`].
+pub const CALLER: Address = address!("1000000000000000000000000000000000000001");
+
+/// Address of the contract of the [`contract_db`].
+pub const CONTRACT: Address = address!("2000000000000000000000000000000000000002");
+
+/// Returns an [`InMemoryDB`] with the `code` deployed at [`CONTRACT`].
+pub fn contract_db(code: impl Into<Bytes>) -> InMemoryDB {
+    let mut db = InMemoryDB::default();
+    db.insert_account_info(
+        CONTRACT,
+        AccountInfo::from_bytecode(Bytecode::new_raw(code.into())),
+    );
+    db
+}
+
+/// Returns a transaction from [`CALLER`] that calls [`CONTRACT`] with a gas limit of 100_000.
+pub fn contract_tx() -> TxEnv {
+    TxEnv {
+        caller: CALLER,
+        transact_to: Some(TxKind::Call(CONTRACT)),
+        gas_limit: 100_000,
+        ..Default::default()
+    }
+}
+
+/// Returns a builder of an EVM with the [`contract_db`] of the `code` and the [`contract_tx`].
+pub fn contract_evm(
+    code: impl Into<Bytes>,
+) -> EvmBuilder<'static, SetGenericStage, (), InMemoryDB> {
+    Evm::builder()
+        .with_db(contract_db(code))
+        .with_tx_env(contract_tx())
+}
@@ -0,0 +1,242 @@
+use crate::{
+    db::Database,
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, InstructionResult,
+        Interpreter,
+    },
+    primitives::{Address, Log, U256},
+    EvmContext, Inspector,
+};
+use core::ops::{Deref, DerefMut};
+use std::vec::Vec;
+
+/// Inputs of an open frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum FrameInputs {
+    Call(CallInputs),
+    Create(CreateInputs),
+    EOFCreate(EOFCreateInputs),
+}
+
+/// Frame that was started with a begin hook and not yet ended.
+#[derive(Clone, Debug)]
+struct OpenFrame {
+    inputs: FrameInputs,
+    /// Whether [`Inspector::initialize_interp`] was called for the frame.
+    interpreted: bool,
+}
+
+/// Inspector wrapper that panics if the hooks are not called in the order described
+/// in [`Inspector`].
+///
+/// All hooks are forwarded to the wrapped inspector, which is accessible through [Deref].
+#[derive(Clone, Debug, Default)]
+pub struct AssertingInspector<I> {
+    inner: I,
+    frames: Vec<OpenFrame>,
+    in_step: bool,
+}
+
+impl<I> AssertingInspector<I> {
+    /// Wraps the inspector.
+    pub fn new(inner: I) -> Self {
+        Self {
+            inner,
+            frames: Vec::new(),
+            in_step: false,
+        }
+    }
+
+    /// Returns the wrapped inspector.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+
+    /// Panics if a frame or an instruction was not ended.
+    pub fn assert_finished(&self) {
+        assert!(!self.in_step, "step without step_end");
+        assert!(
+            self.frames.is_empty(),
+            "frames without end hook: {:?}",
+            self.frames
+        );
+    }
+
+    /// Forgets the open frames, needed after a transaction failed with an error.
+    pub fn reset(&mut self) {
+        self.frames.clear();
+        self.in_step = false;
+    }
+
+    fn assert_depth<DB: Database>(&self, hook: &str, context: &EvmContext<DB>) {
+        assert_eq!(
+            context.journaled_state.depth(),
+            self.frames.len() as u64,
+            "{hook}: journal depth does not match open frames"
+        );
+    }
+
+    fn begin_frame<DB: Database>(&mut self, hook: &str, context: &EvmContext<DB>) {
+        assert!(!self.in_step, "{hook} before step_end");
+        self.assert_depth(hook, context);
+    }
+
+    fn push_frame(&mut self, inputs: FrameInputs) {
+        self.frames.push(OpenFrame {
+            inputs,
+            interpreted: false,
+        });
+    }
+
+    fn end_frame<DB: Database>(
+        &mut self,
+        hook: &str,
+        context: &EvmContext<DB>,
+        inputs: FrameInputs,
+    ) {
+        assert!(!self.in_step, "{hook} before step_end");
+        let frame = self
+            .frames
+            .pop()
+            .unwrap_or_else(|| panic!("{hook} without begin hook"));
+        assert_eq!(frame.inputs, inputs, "{hook} does not match the begin hook");
+        self.assert_depth(hook, context);
+    }
+
+    fn assert_in_step(&self, hook: &str) {
+        assert!(self.in_step, "{hook} outside of an instruction");
+    }
+}
+
+impl<I> Deref for AssertingInspector<I> {
+    type Target = I;
+
+    fn deref(&self) -> &I {
+        &self.inner
+    }
+}
+
+impl<I> DerefMut for AssertingInspector<I> {
+    fn deref_mut(&mut self) -> &mut I {
+        &mut self.inner
+    }
+}
+
+impl<DB: Database, I: Inspector<DB>> Inspector<DB> for AssertingInspector<I> {
+    fn initialize_interp(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        assert!(!self.in_step, "initialize_interp before step_end");
+        let frame = self
+            .frames
+            .last_mut()
+            .expect("initialize_interp outside of a frame");
+        assert!(!frame.interpreted, "initialize_interp called twice");
+        frame.interpreted = true;
+        self.assert_depth("initialize_interp", context);
+        self.inner.initialize_interp(interp, context);
+    }
+
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        assert!(!self.in_step, "step before step_end");
+        assert!(
+            self.frames.last().is_some_and(|frame| frame.interpreted),
+            "step before initialize_interp"
+        );
+        self.assert_depth("step", context);
+        self.inner.step(interp, context);
+        // execution stops without step_end if the step changed the result.
+        self.in_step = interp.instruction_result == InstructionResult::Continue;
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        self.assert_in_step("step_end");
+        self.assert_depth("step_end", context);
+        self.in_step = false;
+        self.inner.step_end(interp, context);
+    }
+
+    fn log(&mut self, context: &mut EvmContext<DB>, log: &Log) {
+        self.assert_in_step("log");
+        self.inner.log(context, log);
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        self.begin_frame("call", context);
+        let outcome = self.inner.call(context, inputs);
+        self.push_frame(FrameInputs::Call(inputs.clone()));
+        outcome
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.end_frame("call_end", context, FrameInputs::Call(inputs.clone()));
+        self.inner.call_end(context, inputs, outcome)
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.begin_frame("create", context);
+        let outcome = self.inner.create(context, inputs);
+        self.push_frame(FrameInputs::Create(inputs.clone()));
+        outcome
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.end_frame("create_end", context, FrameInputs::Create(inputs.clone()));
+        self.inner.create_end(context, inputs, outcome)
+    }
+
+    fn eofcreate(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.begin_frame("eofcreate", context);
+        let outcome = self.inner.eofcreate(context, inputs);
+        self.push_frame(FrameInputs::EOFCreate(inputs.clone()));
+        outcome
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &EOFCreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.end_frame(
+            "eofcreate_end",
+            context,
+            FrameInputs::EOFCreate(inputs.clone()),
+        );
+        self.inner.eofcreate_end(context, inputs, outcome)
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        self.assert_in_step("selfdestruct");
+        self.inner.selfdestruct(contract, target, value);
+    }
+
+    fn inspect_jumps(&self) -> bool {
+        self.inner.inspect_jumps()
+    }
+
+    fn jump(&mut self, from_pc: usize, to_pc: usize, condition_taken: bool, is_valid: bool) {
+        self.assert_in_step("jump");
+        self.inner.jump(from_pc, to_pc, condition_taken, is_valid);
+    }
+}
@@ -0,0 +1,253 @@
+//! Invariants of the transaction execution.
+//!
+//! Checked by the `env_invariants` fuzz target in `crates/revm/fuzz` on arbitrary environments
+//! and by the regression fixtures below.
+
+use super::AssertingInspector;
+use crate::{
+    db::{CacheDB, DatabaseRef, EmptyDB},
+    inspector_handle_register,
+    interpreter::{CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs},
+    primitives::{
+        AccountInfo, Address, EVMResult, Env, ExecutionResult, Log, ResultAndState, SpecId,
+    },
+    Database, Evm, EvmContext, Inspector,
+};
+use core::convert::Infallible;
+use std::{boxed::Box, vec::Vec};
+
+/// Executes the transaction of the environment against the accounts and panics if any of the
+/// invariants is violated:
+/// * Execution does not panic.
+/// * Executing the transaction twice from the same state gives the same result.
+/// * Gas used is not more than the gas limit of the transaction.
+/// * Refund is not more than the gas used, which holds for every refund cap.
+/// * Logs of the result are the logs of the frames that were not reverted.
+/// * Accounts that are not touched are not changed.
+/// * Inspector hooks are called in the documented order, see [`AssertingInspector`].
+pub fn check_invariants(env: Env, spec_id: SpecId, accounts: Vec<(Address, AccountInfo)>) {
+    let mut db = CacheDB::new(EmptyDB::default());
+    for (address, info) in accounts {
+        db.insert_account_info(address, info);
+    }
+
+    let (result, committed_logs) = transact(&db, &env, spec_id);
+    let (again, _) = transact(&db, &env, spec_id);
+    assert_eq!(result, again, "execution is not deterministic");
+
+    let Ok(ResultAndState { result, state, .. }) = result else {
+        return;
+    };
+
+    assert!(
+        result.gas_used() <= env.tx().gas_limit,
+        "gas used {} is more than the gas limit {}",
+        result.gas_used(),
+        env.tx().gas_limit
+    );
+    if let ExecutionResult::Success {
+        gas_used,
+        gas_refunded,
+        ..
+    } = &result
+    {
+        assert!(
+            gas_refunded <= gas_used,
+            "refund {gas_refunded} is more than the gas used {gas_used}"
+        );
+    }
+
+    if result.is_success() {
+        assert_eq!(result.logs(), committed_logs, "logs of the reverted frames");
+    } else {
+        assert!(result.logs().is_empty(), "logs of the failed transaction");
+    }
+
+    for (address, account) in &state {
+        if account.is_touched() {
+            continue;
+        }
+        let original = db.basic_ref(*address).unwrap().unwrap_or_default();
+        assert_eq!(
+            (
+                account.info.balance,
+                account.info.nonce,
+                account.info.code_hash
+            ),
+            (original.balance, original.nonce, original.code_hash),
+            "untouched account {address} is changed"
+        );
+        assert_eq!(
+            account.changed_storage_slots().count(),
+            0,
+            "storage of the untouched account {address} is changed"
+        );
+    }
+}
+
+/// Executes the transaction on a copy of the database and returns the result and the logs
+/// of the frames that were not reverted.
+fn transact(
+    db: &CacheDB<EmptyDB>,
+    env: &Env,
+    spec_id: SpecId,
+) -> (EVMResult<Infallible>, Vec<Log>) {
+    let mut evm = Evm::builder()
+        .with_db(db.clone())
+        .with_env(Box::new(env.clone()))
+        .with_spec_id(spec_id)
+        .with_external_context(AssertingInspector::new(CommittedLogs::default()))
+        .append_handler_register(inspector_handle_register)
+        .build();
+    let result = evm.transact();
+    let inspector = evm.into_context().external;
+    inspector.assert_finished();
+    (result, inspector.into_inner().logs)
+}
+
+/// Collects the logs, dropping the logs of the reverted frames.
+#[derive(Debug, Default)]
+struct CommittedLogs {
+    /// Number of the logs at the start of the open frames.
+    frames: Vec<usize>,
+    logs: Vec<Log>,
+}
+
+impl CommittedLogs {
+    fn frame_end(&mut self, is_ok: bool) {
+        let start = self.frames.pop().expect("frame end without start");
+        if !is_ok {
+            self.logs.truncate(start);
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for CommittedLogs {
+    fn log(&mut self, _context: &mut EvmContext<DB>, log: &Log) {
+        self.logs.push(log.clone());
+    }
+
+    fn call(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        self.frames.push(self.logs.len());
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.frame_end(outcome.result.result.is_ok());
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.frames.push(self.logs.len());
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.frame_end(outcome.result.result.is_ok());
+        outcome
+    }
+
+    fn eofcreate(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.frames.push(self.logs.len());
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &EOFCreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.frame_end(outcome.result.result.is_ok());
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        primitives::{bytes, Bytecode, Bytes, TxKind, U256},
+        test_utils::{CALLER, CONTRACT},
+    };
+
+    /// Calls `CONTRACT` with the code, funded `CALLER` and the given gas limit.
+    fn check(code: Bytes, gas_limit: u64, spec_id: SpecId) {
+        let mut env = Env::default();
+        env.tx_mut().caller = CALLER;
+        env.tx_mut().transact_to = Some(TxKind::Call(CONTRACT));
+        env.tx_mut().gas_limit = gas_limit;
+        env.tx_mut().gas_price = U256::from(1);
+        env.block.basefee = U256::from(1);
+        check_invariants(
+            env,
+            spec_id,
+            vec![
+                (CALLER, AccountInfo::from_balance(U256::from(10_000_000))),
+                (
+                    CONTRACT,
+                    AccountInfo::from_bytecode(Bytecode::new_raw(code)),
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn log_in_reverted_subcall() {
+        // Without calldata the contract calls itself with one byte of calldata, logs and
+        // stops. With calldata it logs and reverts.
+        let code = bytes!(
+            // JUMPI(0x11, CALLDATASIZE)
+            "36601157"
+            // CALL(GAS, ADDRESS, 0, 0, 1, 0, 0), LOG0(0, 0), STOP
+            "5f5f60015f5f305af1"
+            "5f5fa000"
+            // JUMPDEST, LOG0(0, 0), REVERT(0, 0)
+            "5b5f5fa05f5ffd"
+        );
+        check(code, 100_000, SpecId::CANCUN);
+    }
+
+    #[test]
+    fn refund_of_cleared_slot() {
+        // SSTORE(0, 1), SSTORE(0, 0), STOP
+        let code = bytes!("6001600055600060005500");
+        check(code.clone(), 100_000, SpecId::BERLIN);
+        check(code, 100_000, SpecId::CANCUN);
+    }
+
+    #[test]
+    fn out_of_gas_in_loop() {
+        // JUMPDEST, PUSH0, JUMP
+        check(bytes!("5b5f56"), 50_000, SpecId::CANCUN);
+    }
+
+    #[test]
+    fn invalid_env_is_rejected() {
+        let mut env = Env::default();
+        env.tx_mut().gas_limit = 0;
+        check_invariants(env, SpecId::LATEST, Vec::new());
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{
+    db::Database,
+    primitives::{hash_map::Entry, Account, EvmState, Withdrawal},
+};
+
+/// Credits the withdrawals of the block to their recipients.
+///
+/// Withdrawals are processed after all transactions of a post-Shanghai block, see [EIP-4895].
+/// Amounts are converted from gwei to wei and recipients that don't exist are created.
+/// Every recipient is marked as touched, even for zero amounts, so the empty account
+/// rules of [EIP-161] apply when the returned state is committed with
+/// [`DatabaseCommit::commit`](crate::DatabaseCommit::commit).
+///
+/// [EIP-4895]: https://eips.ethereum.org/EIPS/eip-4895
+/// [EIP-161]: https://eips.ethereum.org/EIPS/eip-161
+pub fn apply_withdrawals<DB: Database>(
+    db: &mut DB,
+    withdrawals: &[Withdrawal],
+) -> Result<EvmState, DB::Error> {
+    let mut state = EvmState::default();
+    for withdrawal in withdrawals {
+        let account = match state.entry(withdrawal.address) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let account = match db.basic(withdrawal.address)? {
+                    Some(info) => Account::from(info),
+                    None => Account::new_not_existing(),
+                };
+                entry.insert(account)
+            }
+        };
+        account.info.balance = account.info.balance.saturating_add(withdrawal.amount_wei());
+        account.mark_touch();
+    }
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{EmptyDB, InMemoryDB, State},
+        primitives::{address, AccountInfo, Address, U256, WEI_PER_GWEI},
+        DatabaseCommit,
+    };
+
+    const RECIPIENT: Address = address!("4000000000000000000000000000000000000004");
+
+    fn withdrawal(index: u64, amount_gwei: u64) -> Withdrawal {
+        Withdrawal {
+            index,
+            validator: 7,
+            address: RECIPIENT,
+            amount_gwei,
+        }
+    }
+
+    #[test]
+    fn credits_amounts_in_wei() {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(RECIPIENT, AccountInfo::from_balance(U256::from(1)));
+
+        let state = apply_withdrawals(&mut db, &[withdrawal(0, 2), withdrawal(1, 3)]).unwrap();
+        assert_eq!(
+            state[&RECIPIENT].info.balance,
+            U256::from(1 + 5 * WEI_PER_GWEI)
+        );
+        assert!(state[&RECIPIENT].is_touched());
+
+        db.commit(state);
+        assert_eq!(
+            db.basic(RECIPIENT).unwrap().unwrap().balance,
+            U256::from(1 + 5 * WEI_PER_GWEI)
+        );
+    }
+
+    #[test]
+    fn creates_recipient() {
+        let mut db = State::builder().with_database(EmptyDB::default()).build();
+        let state = apply_withdrawals(&mut db, &[withdrawal(0, 1)]).unwrap();
+        db.commit(state);
+        assert_eq!(
+            db.basic(RECIPIENT).unwrap().unwrap().balance,
+            U256::from(WEI_PER_GWEI)
+        );
+    }
+
+    #[test]
+    fn zero_amount_to_nonexistent_account() {
+        // EIP-161: touched empty account is removed.
+        let mut db = State::builder().with_database(EmptyDB::default()).build();
+        let state = apply_withdrawals(&mut db, &[withdrawal(0, 0)]).unwrap();
+        assert!(state[&RECIPIENT].is_touched());
+        assert!(state[&RECIPIENT].is_empty());
+        db.commit(state);
+        assert_eq!(db.basic(RECIPIENT).unwrap(), None);
+
+        // Before Spurious Dragon touched empty account is created.
+        let mut db = State::builder()
+            .with_database(EmptyDB::default())
+            .without_state_clear()
+            .build();
+        let state = apply_withdrawals(&mut db, &[withdrawal(0, 0)]).unwrap();
+        db.commit(state);
+        assert!(db.basic(RECIPIENT).unwrap().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,77 @@
+//! Registers a custom `execution.call` handler that short-circuits calls to a
+//! specific address without executing any bytecode.
+use revm::{
+    db::BenchmarkDB,
+    interpreter::{Gas, InstructionResult, InterpreterResult},
+    primitives::{address, Address, Bytecode, Bytes, ExecutionResult, Output, TxKind, B256},
+    Evm, FrameOrResult,
+};
+use std::sync::Arc;
+
+/// Calls to this address are answered by the handler.
+const ORACLE: Address = address!("00000000000000000000000000000000000000aa");
+
+/// Output returned for calls to the [ORACLE].
+fn answer() -> B256 {
+    B256::with_last_byte(42)
+}
+
+// the previous call handle captured by the new one is not `Send`.
+#[allow(clippy::arc_with_non_send_sync)]
+fn main() -> anyhow::Result<()> {
+    // STATICCALL the oracle, copy 32 bytes of its output to memory and return them.
+    let mut code = vec![
+        0x60, 0x20, // PUSH1 0x20 (retSize)
+        0x60, 0x00, // PUSH1 0x00 (retOffset)
+        0x60, 0x00, // PUSH1 0x00 (argsSize)
+        0x60, 0x00, // PUSH1 0x00 (argsOffset)
+        0x73, // PUSH20 ORACLE
+    ];
+    code.extend_from_slice(ORACLE.as_slice());
+    code.extend_from_slice(&[
+        0x61, 0xff, 0xff, // PUSH2 0xffff (gas)
+        0xfa, // STATICCALL
+        0x50, // POP
+        0x60, 0x20, // PUSH1 0x20
+        0x60, 0x00, // PUSH1 0x00
+        0xf3, // RETURN
+    ]);
+
+    let mut evm = Evm::builder()
+        .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code.into())))
+        .modify_tx_env(|tx| {
+            tx.caller = Address::with_last_byte(1);
+            tx.transact_to = Some(TxKind::Call(Address::ZERO));
+        })
+        .append_handler_register(|handler| {
+            let prev_call = handler.execution.call.clone();
+            handler.execution.call = Arc::new(move |context, inputs| {
+                if inputs.target_address != ORACLE {
+                    return prev_call(context, inputs);
+                }
+                // Nothing is executed, so all gas is returned to the caller.
+                let result = InterpreterResult::new(
+                    InstructionResult::Return,
+                    Bytes::copy_from_slice(answer().as_slice()),
+                    Gas::new(inputs.gas_limit),
+                );
+                Ok(FrameOrResult::new_call_result(
+                    result,
+                    inputs.return_memory_offset.clone(),
+                ))
+            });
+        })
+        .build();
+
+    let output = match evm.transact()?.result {
+        ExecutionResult::Success {
+            output: Output::Call(output),
+            ..
+        } => output,
+        result => anyhow::bail!("unexpected result: {result:?}"),
+    };
+    assert_eq!(output[..], answer()[..]);
+    println!("oracle answered: {output}");
+
+    Ok(())
+}
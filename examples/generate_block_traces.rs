@@ -146,10 +146,10 @@ async fn main() -> anyhow::Result<()> {
                     etx.access_list = Default::default();
                 }
 
-                etx.transact_to = match tx.to {
+                etx.transact_to = Some(match tx.to {
                     Some(to_address) => TxKind::Call(Address::from(to_address.as_fixed_bytes())),
                     None => TxKind::Create,
-                };
+                });
             })
             .build();
 
@@ -0,0 +1,108 @@
+//! Evaluates view-only bytecode against a host that implements only `HostRead`.
+//!
+//! The instruction table contains only the instructions that read the state, state modifying
+//! opcodes are unknown to it and halt the execution.
+use revm::{
+    interpreter::{
+        instructions::{arithmetic, control, host, memory, stack},
+        opcode::{self, InstructionTable},
+        Contract, HostRead, InstructionResult, Interpreter, InterpreterAction, LoadAccountResult,
+        EMPTY_SHARED_MEMORY,
+    },
+    primitives::{Address, Bytecode, Bytes, CancunSpec, Env, HashMap, B256, KECCAK_EMPTY, U256},
+};
+
+/// Host that exposes storage of a single contract for reading.
+#[derive(Default)]
+struct ViewHost {
+    env: Env,
+    storage: HashMap<U256, U256>,
+}
+
+impl HostRead for ViewHost {
+    fn env(&self) -> &Env {
+        &self.env
+    }
+
+    fn load_account(&mut self, _address: Address) -> Option<LoadAccountResult> {
+        Some(LoadAccountResult::default())
+    }
+
+    fn block_hash(&mut self, _number: u64) -> Option<B256> {
+        Some(B256::ZERO)
+    }
+
+    fn balance(&mut self, _address: Address) -> Option<(U256, bool)> {
+        Some((U256::ZERO, false))
+    }
+
+    fn code(&mut self, _address: Address) -> Option<(Bytes, bool)> {
+        Some((Bytes::new(), false))
+    }
+
+    fn code_hash(&mut self, _address: Address) -> Option<(B256, bool)> {
+        Some((KECCAK_EMPTY, false))
+    }
+
+    fn sload(&mut self, _address: Address, index: U256) -> Option<(U256, bool)> {
+        Some((self.storage.get(&index).copied().unwrap_or_default(), false))
+    }
+
+    fn tload(&mut self, _address: Address, _index: U256) -> U256 {
+        U256::ZERO
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let mut table: InstructionTable<ViewHost> = [control::unknown; 256];
+    table[opcode::STOP as usize] = control::stop;
+    table[opcode::ADD as usize] = arithmetic::add;
+    table[opcode::SLOAD as usize] = host::sload::<ViewHost, CancunSpec>;
+    table[opcode::MSTORE as usize] = memory::mstore;
+    table[opcode::PUSH1 as usize] = stack::push::<1, ViewHost>;
+    table[opcode::RETURN as usize] = control::ret;
+
+    // Return the sum of storage slots 0 and 1.
+    let code = Bytes::from_static(&[
+        0x60, 0x00, // PUSH1 0x00
+        0x54, // SLOAD
+        0x60, 0x01, // PUSH1 0x01
+        0x54, // SLOAD
+        0x01, // ADD
+        0x60, 0x00, // PUSH1 0x00
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 0x20
+        0x60, 0x00, // PUSH1 0x00
+        0xf3, // RETURN
+    ]);
+
+    let mut host = ViewHost::default();
+    host.storage.insert(U256::from(0), U256::from(40));
+    host.storage.insert(U256::from(1), U256::from(2));
+
+    let contract = Contract::new(
+        Bytes::new(),
+        Bytecode::new_raw(code),
+        None,
+        Address::ZERO,
+        None,
+        Address::ZERO,
+        U256::ZERO,
+    );
+    let mut interpreter = Interpreter::new(contract, 1_000_000, true);
+    let action = interpreter.run(EMPTY_SHARED_MEMORY, &table, &mut host);
+
+    let InterpreterAction::Return { result } = action else {
+        anyhow::bail!("unexpected action: {action:?}");
+    };
+    anyhow::ensure!(
+        result.result == InstructionResult::Return,
+        "execution failed: {:?}",
+        result.result
+    );
+    let sum = U256::from_be_slice(&result.output);
+    println!("slot0 + slot1 = {sum}");
+    anyhow::ensure!(sum == U256::from(42));
+
+    Ok(())
+}
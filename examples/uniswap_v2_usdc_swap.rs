@@ -95,7 +95,7 @@ fn balance_of(token: Address, address: Address, cache_db: &mut AlloyCacheDB) ->
         .modify_tx_env(|tx| {
             // 0x1 because calling USDC proxy from zero address fails
             tx.caller = address!("0000000000000000000000000000000000000001");
-            tx.transact_to = TxKind::Call(token);
+            tx.transact_to = Some(TxKind::Call(token));
             tx.data = encoded.into();
             tx.value = U256::from(0);
         })
@@ -139,7 +139,7 @@ async fn get_amount_out(
         .with_db(cache_db)
         .modify_tx_env(|tx| {
             tx.caller = address!("0000000000000000000000000000000000000000");
-            tx.transact_to = TxKind::Call(uniswap_v2_router);
+            tx.transact_to = Some(TxKind::Call(uniswap_v2_router));
             tx.data = encoded.into();
             tx.value = U256::from(0);
         })
@@ -172,7 +172,7 @@ fn get_reserves(pair_address: Address, cache_db: &mut AlloyCacheDB) -> Result<(U
         .with_db(cache_db)
         .modify_tx_env(|tx| {
             tx.caller = address!("0000000000000000000000000000000000000000");
-            tx.transact_to = TxKind::Call(pair_address);
+            tx.transact_to = Some(TxKind::Call(pair_address));
             tx.data = encoded.into();
             tx.value = U256::from(0);
         })
@@ -221,7 +221,7 @@ fn swap(
         .with_db(cache_db)
         .modify_tx_env(|tx| {
             tx.caller = from;
-            tx.transact_to = TxKind::Call(pool_address);
+            tx.transact_to = Some(TxKind::Call(pool_address));
             tx.data = encoded.into();
             tx.value = U256::from(0);
         })
@@ -254,7 +254,7 @@ fn transfer(
         .with_db(cache_db)
         .modify_tx_env(|tx| {
             tx.caller = from;
-            tx.transact_to = TxKind::Call(token);
+            tx.transact_to = Some(TxKind::Call(token));
             tx.data = encoded.into();
             tx.value = U256::from(0);
         })